@@ -0,0 +1 @@
+buildtime::proto_build_main!("movementlabs/networks/suzuka/load_soak/v1beta1.proto");
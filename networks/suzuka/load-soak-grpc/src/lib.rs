@@ -0,0 +1,3 @@
+tonic::include_proto!("movementlabs.networks.suzuka.load_soak.v1beta1");
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+	tonic::include_file_descriptor_set!("suzuka-load-soak-grpc-descriptor");
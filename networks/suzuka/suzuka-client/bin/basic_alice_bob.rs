@@ -6,7 +6,9 @@ use aptos_sdk::{
 };
 use std::str::FromStr;
 use std::sync::Arc;
-use suzuka_client::load_soak_testing::{execute_test, init_test, ExecutionConfig, Scenario};
+use suzuka_client::load_soak_testing::{
+	execute_test, init_test, measure_latency, ExecutionConfig, Operation, Scenario,
+};
 use url::Url;
 
 fn main() {
@@ -21,6 +23,8 @@ fn main() {
 	// Execute the test.
 	let result = execute_test(config, Arc::new(create_scenario));
 	tracing::info!("End Test with result {result:?}",);
+	// Fail the process if the success criteria are not met, to gate the soak runs.
+	std::process::exit(result.exit_code());
 }
 
 // Scenario constructor function use by the Test runtime to create new scenarios.
@@ -73,7 +77,7 @@ impl Scenario for BasicScenario {
 		tracing::info!("{} Before alice fund", self.id);
 		self.log_exec_info(&format!("{} Before alice fund", self.id));
 		// Create the accounts on chain, but only fund Alice.
-		faucet_client.fund(alice.address(), 100_000_000).await?;
+		measure_latency(Operation::Fund, faucet_client.fund(alice.address(), 100_000_000)).await?;
 		tracing::info!("{} Before Bod create_account", self.id);
 		self.log_exec_info(&format!("{} Before Bod create_account", self.id));
 		faucet_client.create_account(bob.address()).await?;
@@ -95,12 +99,13 @@ impl Scenario for BasicScenario {
 		);
 
 		// Have Alice send Bob some coins.
-		let txn_hash = coin_client
-			.transfer(&mut alice, bob.address(), 1_000, None)
-			.await
-			.context("Failed to submit transaction to transfer coins")?;
-		rest_client
-			.wait_for_transaction(&txn_hash)
+		let txn_hash = measure_latency(
+			Operation::Submit,
+			coin_client.transfer(&mut alice, bob.address(), 1_000, None),
+		)
+		.await
+		.context("Failed to submit transaction to transfer coins")?;
+		measure_latency(Operation::WaitForTransaction, rest_client.wait_for_transaction(&txn_hash))
 			.await
 			.context("Failed when waiting for the transfer transaction")?;
 
@@ -121,12 +126,13 @@ impl Scenario for BasicScenario {
 		self.log_exec_info(&format!("Scenario:{} ended", self.id));
 
 		// Have Alice send Bob some coins.
-		let txn_hash = coin_client
-			.transfer(&mut alice, bob.address(), 1_000, None)
-			.await
-			.context("Failed to submit transaction to transfer coins")?;
-		rest_client
-			.wait_for_transaction(&txn_hash)
+		let txn_hash = measure_latency(
+			Operation::Submit,
+			coin_client.transfer(&mut alice, bob.address(), 1_000, None),
+		)
+		.await
+		.context("Failed to submit transaction to transfer coins")?;
+		measure_latency(Operation::WaitForTransaction, rest_client.wait_for_transaction(&txn_hash))
 			.await
 			.context("Failed when waiting for the transfer transaction")?;
 
@@ -145,13 +151,14 @@ impl Scenario for BasicScenario {
 		);
 
 		// Have Alice send Bob some more coins.
-		let txn_hash = coin_client
-			.transfer(&mut alice, bob.address(), 1_000, None)
-			.await
-			.context("Failed to submit transaction to transfer coins")?; // <:!:section_5
+		let txn_hash = measure_latency(
+			Operation::Submit,
+			coin_client.transfer(&mut alice, bob.address(), 1_000, None),
+		)
+		.await
+		.context("Failed to submit transaction to transfer coins")?; // <:!:section_5
 															 // :!:>section_6
-		rest_client
-			.wait_for_transaction(&txn_hash)
+		measure_latency(Operation::WaitForTransaction, rest_client.wait_for_transaction(&txn_hash))
 			.await
 			.context("Failed when waiting for the transfer transaction")?; // <:!:section_6
 
@@ -0,0 +1,27 @@
+module counter::counter {
+    use std::signer;
+
+    /// A counter stored under the account which increments it.
+    struct Counter has key {
+        value: u64,
+    }
+
+    /// Increments the counter of the account, creating it on the first call.
+    public entry fun increment(account: &signer) acquires Counter {
+        let addr = signer::address_of(account);
+        if (!exists<Counter>(addr)) {
+            move_to(account, Counter { value: 0 });
+        };
+        let counter = borrow_global_mut<Counter>(addr);
+        counter.value = counter.value + 1;
+    }
+
+    #[view]
+    public fun get(addr: address): u64 acquires Counter {
+        if (exists<Counter>(addr)) {
+            borrow_global<Counter>(addr).value
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use anyhow::{Context, Result};
+use aptos_sdk::{
+	move_types::{identifier::Identifier, language_storage::ModuleId},
+	rest_client::aptos_api_types::ViewFunction,
+	types::{account_address::AccountAddress, transaction::EntryFunction, LocalAccount},
+};
+use buildtime_helpers::cargo::cargo_workspace;
+use commander::run_command;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::sync::Arc;
+/// A scenario that publishes the counter Move module from a generated account then calls its
+/// increment entry function in a burst, to load the code publishing and the storage writes.
+/// The Move package is compiled with the aptos CLI, which must be in the PATH.
+/// To run it use: cargo run --release --bin counter_storm
+use suzuka_client::{
+	load_soak_testing::{
+		execute_test, init_test, measure_latency, ExecutionConfig, Operation, Scenario,
+	},
+	FaucetPool, FaucetPoolConfig, SuzukaClient,
+};
+
+/// The Move package of the counter module, from the root of the cargo workspace.
+const COUNTER_PACKAGE_PATH: &str = "networks/suzuka/suzuka-client/bin/counter";
+
+/// The number of increment calls of a scenario.
+static NUMBER_CALLS: Lazy<u64> = Lazy::new(|| {
+	std::env::var("COUNTER_STORM_NUMBER_CALLS")
+		.unwrap_or("20".to_string())
+		.parse()
+		.unwrap_or(20)
+});
+
+static SUZUKA_CLIENT: Lazy<Arc<SuzukaClient>> =
+	Lazy::new(|| Arc::new(SuzukaClient::try_from_env().unwrap()));
+
+/// The faucet shared by the scenarios, so that they queue and retry when it rate limits them.
+static FAUCET_POOL: Lazy<FaucetPool> =
+	Lazy::new(|| FaucetPool::new(SUZUKA_CLIENT.clone(), FaucetPoolConfig::default()));
+
+fn main() {
+	// Define the Test config. Use the default parameters.
+	let config = ExecutionConfig::default();
+
+	// Init the Test before execution
+	if let Err(err) = init_test(&config) {
+		println!("Test init fail ; {err}",);
+	}
+
+	// Execute the test.
+	let result = execute_test(config, Arc::new(create_scenario));
+	tracing::info!("End Test with result {result:?}",);
+	// Fail the process if the success criteria are not met, to gate the soak runs.
+	std::process::exit(result.exit_code());
+}
+
+// Scenario constructor function use by the Test runtime to create new scenarios.
+fn create_scenario(id: usize) -> Box<dyn Scenario> {
+	Box::new(CounterStormScenario { id })
+}
+
+pub struct CounterStormScenario {
+	id: usize,
+}
+
+/// An argument of the payload written by aptos move build-publish-payload.
+#[derive(Deserialize)]
+struct PayloadArg {
+	value: serde_json::Value,
+}
+
+/// The payload written by aptos move build-publish-payload.
+#[derive(Deserialize)]
+struct PublishPayload {
+	args: Vec<PayloadArg>,
+}
+
+fn decode_hex(value: &serde_json::Value) -> Result<Vec<u8>> {
+	let value = value.as_str().context("Publish payload argument is not a hex string")?;
+	Ok(hex::decode(value.trim_start_matches("0x"))?)
+}
+
+/// Compiles the counter package with the module published at the address and builds the
+/// payload of the publication.
+async fn build_publish_payload(address: AccountAddress) -> Result<EntryFunction> {
+	let package_dir = cargo_workspace()?.join(COUNTER_PACKAGE_PATH);
+	// The scenarios run concurrently, each one compiles in its own directory.
+	let output_dir = tempfile::tempdir()?;
+	let payload_file = output_dir.path().join("publish.json");
+	run_command(
+		"aptos",
+		&[
+			"move",
+			"build-publish-payload",
+			"--package-dir",
+			&package_dir.to_string_lossy(),
+			"--output-dir",
+			&output_dir.path().to_string_lossy(),
+			"--named-addresses",
+			&format!("counter={}", address.to_hex_literal()),
+			"--json-output-file",
+			&payload_file.to_string_lossy(),
+			"--assume-yes",
+		],
+	)
+	.await
+	.context("Failed to compile the counter package")?;
+
+	publish_entry_function(&std::fs::read_to_string(&payload_file)?)
+}
+
+/// Builds the call of the publication from the payload written by the aptos CLI.
+fn publish_entry_function(payload: &str) -> Result<EntryFunction> {
+	let payload: PublishPayload = serde_json::from_str(payload)?;
+	let [metadata, code] = payload.args.as_slice() else {
+		anyhow::bail!("Unexpected publish payload arguments");
+	};
+	let metadata = decode_hex(&metadata.value)?;
+	let code = code
+		.value
+		.as_array()
+		.context("Publish payload code is not an array")?
+		.iter()
+		.map(decode_hex)
+		.collect::<Result<Vec<_>>>()?;
+	Ok(EntryFunction::new(
+		ModuleId::new(AccountAddress::ONE, Identifier::new("code")?),
+		Identifier::new("publish_package_txn")?,
+		vec![],
+		vec![bcs::to_bytes(&metadata)?, bcs::to_bytes(&code)?],
+	))
+}
+
+/// Builds the call of the increment entry function of the counter module published at the address.
+fn increment_entry_function(address: AccountAddress) -> Result<EntryFunction> {
+	Ok(EntryFunction::new(
+		ModuleId::new(address, Identifier::new("counter")?),
+		Identifier::new("increment")?,
+		vec![],
+		vec![],
+	))
+}
+
+#[async_trait::async_trait]
+impl Scenario for CounterStormScenario {
+	async fn run(self: Box<Self>) -> Result<()> {
+		let client = &**SUZUKA_CLIENT;
+
+		let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+		tracing::info!("Scenario:{} account: {}", self.id, account.address().to_hex_literal());
+		measure_latency(Operation::Fund, FAUCET_POOL.fund(account.address(), 100_000_000))
+			.await
+			.context("Failed to fund the account")?;
+
+		// Publish the counter module.
+		let publish = build_publish_payload(account.address()).await?;
+		let pending =
+			measure_latency(Operation::Submit, client.submit_entry_function(&account, publish))
+				.await
+				.context("Failed to submit the publication of the counter module")?;
+		measure_latency(Operation::WaitForTransaction, client.wait(&pending))
+			.await
+			.context("Failed when waiting for the publication of the counter module")?;
+		self.log_exec_info(&format!("Scenario:{} counter module published", self.id));
+
+		// Submit the increment calls in a burst, then wait for all of them.
+		let mut pendings = vec![];
+		for _ in 0..*NUMBER_CALLS {
+			let increment = increment_entry_function(account.address())?;
+			let pending = measure_latency(
+				Operation::Submit,
+				client.submit_entry_function(&account, increment),
+			)
+			.await
+			.context("Failed to submit the increment call")?;
+			pendings.push(pending);
+		}
+		for pending in &pendings {
+			measure_latency(Operation::WaitForTransaction, client.wait(pending))
+				.await
+				.context("Failed when waiting for the increment call")?;
+		}
+
+		let view_request = ViewFunction {
+			module: ModuleId::new(account.address(), Identifier::new("counter")?),
+			function: Identifier::new("get")?,
+			ty_args: vec![],
+			args: vec![bcs::to_bytes(&account.address())?],
+		};
+		let values: Vec<u64> =
+			client.rest_client().view_bcs(&view_request, None).await?.into_inner();
+		anyhow::ensure!(
+			values == [*NUMBER_CALLS],
+			"Counter is {values:?} after {} increment calls",
+			*NUMBER_CALLS
+		);
+		self.log_exec_info(&format!("Scenario:{} ended", self.id));
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_publish_entry_function_encodes_the_package() -> Result<()> {
+		let payload = r#"{
+			"function_id": "0x1::code::publish_package_txn",
+			"type_args": [],
+			"args": [
+				{ "type": "hex", "value": "0x0102" },
+				{ "type": "hex", "value": ["0xaa", "0xbbcc"] }
+			]
+		}"#;
+
+		let publish = publish_entry_function(payload)?;
+
+		assert_eq!(publish.module(), &ModuleId::new(AccountAddress::ONE, Identifier::new("code")?));
+		assert_eq!(publish.function().as_str(), "publish_package_txn");
+		assert!(publish.ty_args().is_empty());
+		assert_eq!(
+			publish.args(),
+			&[bcs::to_bytes(&vec![1u8, 2])?, bcs::to_bytes(&vec![vec![0xaau8], vec![0xbb, 0xcc]])?]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_publish_entry_function_rejects_malformed_payloads() {
+		let missing_code = r#"{ "args": [{ "type": "hex", "value": "0x01" }] }"#;
+		assert!(publish_entry_function(missing_code).is_err());
+		let not_hex = r#"{ "args": [{ "value": "0x01" }, { "value": ["0xzz"] }] }"#;
+		assert!(publish_entry_function(not_hex).is_err());
+		let code_not_array = r#"{ "args": [{ "value": "0x01" }, { "value": "0x02" }] }"#;
+		assert!(publish_entry_function(code_not_array).is_err());
+	}
+
+	#[test]
+	fn test_increment_entry_function_targets_the_published_module() -> Result<()> {
+		let address = AccountAddress::from_hex_literal("0xcafe")?;
+
+		let increment = increment_entry_function(address)?;
+
+		assert_eq!(increment.module(), &ModuleId::new(address, Identifier::new("counter")?));
+		assert_eq!(increment.function().as_str(), "increment");
+		assert!(increment.ty_args().is_empty());
+		assert!(increment.args().is_empty());
+		Ok(())
+	}
+}
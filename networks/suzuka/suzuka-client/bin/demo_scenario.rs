@@ -26,6 +26,8 @@ fn main() {
 	// Execute the test.
 	let result = execute_test(config, Arc::new(create_demo_scenario));
 	tracing::info!("End Test with result {result:?}",);
+	// Fail the process if the success criteria are not met, to gate the soak runs.
+	std::process::exit(result.exit_code());
 }
 
 // Scenario constructor function use by the Test runtime to create new scenarios.
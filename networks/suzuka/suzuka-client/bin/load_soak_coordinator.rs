@@ -0,0 +1,28 @@
+/// The coordinator of a load test distributed over several machines. It waits for the workers,
+/// started with LOADTEST_COORDINATOR_URL set to its address, assigns them their scenarios then
+/// aggregates their results in the result file.
+/// To run it use: cargo run --release --bin load_soak_coordinator
+use suzuka_client::load_soak_testing::{
+	init_test, run_coordinator, CoordinatorConfig, ExecutionConfig,
+};
+
+fn main() {
+	// Define the Test config. The workers run the part of the test assigned to them.
+	let config = ExecutionConfig::default();
+
+	// Init the Test before execution
+	if let Err(err) = init_test(&config) {
+		println!("Test init fail ; {err}",);
+	}
+
+	let result = match run_coordinator(config, CoordinatorConfig::default()) {
+		Ok(result) => result,
+		Err(err) => {
+			tracing::error!("Coordinator failed: {err}");
+			std::process::exit(1);
+		}
+	};
+	tracing::info!("End Test with result {result:?}",);
+	// Fail the process if the success criteria are not met, to gate the soak runs.
+	std::process::exit(result.exit_code());
+}
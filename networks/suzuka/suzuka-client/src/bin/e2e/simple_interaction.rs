@@ -57,6 +57,11 @@ static FAUCET_URL: Lazy<Url> = Lazy::new(|| {
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let _guard = movement_tracing::init_tracing_subscriber(
+		movement_tracing::Config::with_telemetry_from_config(&dot_movement, "suzuka-client")?,
+	);
+
 	// :!:>section_1a
 	let rest_client = Client::new(NODE_URL.clone());
 	let faucet_client = FaucetClient::new(FAUCET_URL.clone(), NODE_URL.clone()); // <:!:section_1a
@@ -0,0 +1,323 @@
+use crate::{
+	crypto::HashValue,
+	offline::TransactionParams,
+	rest_client::{
+		aptos_api_types::{PendingTransaction, Transaction},
+		error::RestError,
+		Client, FaucetClient,
+	},
+	transaction_builder::{aptos_stdlib, TransactionFactory},
+	types::{
+		account_address::AccountAddress,
+		chain_id::ChainId,
+		transaction::{EntryFunction, SignedTransaction, TransactionPayload},
+		LocalAccount,
+	},
+};
+use serde::de::DeserializeOwned;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+use url::Url;
+
+/// The default maximum gas amount of the transactions submitted by the client.
+pub(crate) const DEFAULT_MAX_GAS_AMOUNT: u64 = 100_000;
+/// The default time to wait for a transaction to be committed.
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The default interval between two polls of a transaction being waited for.
+const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, thiserror::Error)]
+pub enum SuzukaClientError {
+	#[error("Failed to load the suzuka config: {0}")]
+	Config(String),
+	#[error("Rest request failed: {0}")]
+	Rest(#[from] RestError),
+	#[error("Faucet request failed: {0}")]
+	Faucet(String),
+	#[error("Faucet rate limited the request, retry after {retry_after:?}")]
+	RateLimited { retry_after: Option<Duration> },
+	#[error("Transaction {hash} failed with status {vm_status}")]
+	TransactionFailed { hash: String, vm_status: String },
+	#[error("Transaction {hash} expired before being committed")]
+	TransactionExpired { hash: String },
+	#[error("Transaction {hash} not committed after {timeout:?}")]
+	Timeout { hash: String, timeout: Duration },
+	#[error("Failed to decode the event data: {0}")]
+	Decode(#[from] serde_json::Error),
+}
+
+/// A client of a Suzuka node, wrapping the REST and faucet clients of the Aptos SDK.
+/// The chain id is fetched from the node on the first transaction and then cached.
+pub struct SuzukaClient {
+	rest_client: Client,
+	faucet_client: FaucetClient,
+	faucet_url: Url,
+	http_client: reqwest::Client,
+	chain_id: OnceCell<ChainId>,
+	max_gas_amount: u64,
+	wait_timeout: Duration,
+	polling_interval: Duration,
+}
+
+impl SuzukaClient {
+	pub fn new(node_url: Url, faucet_url: Url) -> Self {
+		SuzukaClient {
+			rest_client: Client::new(node_url.clone()),
+			faucet_client: FaucetClient::new(faucet_url.clone(), node_url),
+			faucet_url,
+			http_client: reqwest::Client::new(),
+			chain_id: OnceCell::new(),
+			max_gas_amount: DEFAULT_MAX_GAS_AMOUNT,
+			wait_timeout: DEFAULT_WAIT_TIMEOUT,
+			polling_interval: DEFAULT_POLLING_INTERVAL,
+		}
+	}
+
+	/// Creates the client connected to the node and faucet of the suzuka config.
+	pub fn from_config(config: &suzuka_config::Config) -> Result<Self, SuzukaClientError> {
+		let client = &config.execution_config.maptos_config.client;
+		let parse_url = |hostname: &str, port: u16| {
+			Url::from_str(&format!("http://{hostname}:{port}"))
+				.map_err(|err| SuzukaClientError::Config(err.to_string()))
+		};
+		let node_url =
+			parse_url(&client.maptos_rest_connection_hostname, client.maptos_rest_connection_port)?;
+		let faucet_url = parse_url(
+			&client.maptos_faucet_rest_connection_hostname,
+			client.maptos_faucet_rest_connection_port,
+		)?;
+		Ok(SuzukaClient::new(node_url, faucet_url))
+	}
+
+	/// Creates the client from the suzuka config of the .movement directory set in the env.
+	pub fn try_from_env() -> Result<Self, SuzukaClientError> {
+		let dot_movement = dot_movement::DotMovement::try_from_env()
+			.map_err(|err| SuzukaClientError::Config(err.to_string()))?;
+		let config = dot_movement
+			.try_get_config_from_json::<suzuka_config::Config>()
+			.map_err(|err| SuzukaClientError::Config(err.to_string()))?;
+		SuzukaClient::from_config(&config)
+	}
+
+	pub fn with_max_gas_amount(mut self, max_gas_amount: u64) -> Self {
+		self.max_gas_amount = max_gas_amount;
+		self
+	}
+
+	pub fn with_wait_timeout(mut self, wait_timeout: Duration) -> Self {
+		self.wait_timeout = wait_timeout;
+		self
+	}
+
+	pub fn with_polling_interval(mut self, polling_interval: Duration) -> Self {
+		self.polling_interval = polling_interval;
+		self
+	}
+
+	/// The underlying REST client, for the requests not wrapped by this client.
+	pub fn rest_client(&self) -> &Client {
+		&self.rest_client
+	}
+
+	pub fn faucet_client(&self) -> &FaucetClient {
+		&self.faucet_client
+	}
+
+	pub async fn chain_id(&self) -> Result<ChainId, SuzukaClientError> {
+		let chain_id = self
+			.chain_id
+			.get_or_try_init(|| async {
+				let index = self.rest_client.get_index().await?;
+				Ok::<_, SuzukaClientError>(ChainId::new(index.inner().chain_id))
+			})
+			.await?;
+		Ok(*chain_id)
+	}
+
+	pub async fn transaction_factory(&self) -> Result<TransactionFactory, SuzukaClientError> {
+		Ok(TransactionFactory::new(self.chain_id().await?).with_max_gas_amount(self.max_gas_amount))
+	}
+
+	/// Creates the account on chain if needed and funds it from the faucet, then waits for the
+	/// funding transactions. The faucet rate limiting the request is reported apart so that the
+	/// caller can retry it later.
+	pub async fn fund(
+		&self,
+		address: AccountAddress,
+		amount: u64,
+	) -> Result<(), SuzukaClientError> {
+		let mut url = self.faucet_url.clone();
+		url.set_path("mint");
+		url.set_query(Some(&format!(
+			"auth_key={}&amount={amount}&return_txns=true",
+			address.to_hex()
+		)));
+		let response = self
+			.http_client
+			.post(url)
+			.header("content-length", 0)
+			.send()
+			.await
+			.map_err(|err| SuzukaClientError::Faucet(err.to_string()))?;
+
+		let status = response.status();
+		if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+			let retry_after = response
+				.headers()
+				.get(reqwest::header::RETRY_AFTER)
+				.and_then(|value| value.to_str().ok())
+				.and_then(|value| value.parse().ok())
+				.map(Duration::from_secs);
+			return Err(SuzukaClientError::RateLimited { retry_after });
+		}
+		if !status.is_success() {
+			let body = response.text().await.unwrap_or_default();
+			return Err(SuzukaClientError::Faucet(format!("status {status}: {body}")));
+		}
+
+		let bytes = response
+			.bytes()
+			.await
+			.map_err(|err| SuzukaClientError::Faucet(err.to_string()))?;
+		let transactions: Vec<SignedTransaction> =
+			bcs::from_bytes(&bytes).map_err(|err| SuzukaClientError::Faucet(err.to_string()))?;
+		for transaction in transactions {
+			self.wait_by_hash(
+				transaction.committed_hash(),
+				transaction.expiration_timestamp_secs(),
+			)
+			.await?;
+		}
+		Ok(())
+	}
+
+	/// Submits the transfer of an amount of coins, without waiting for it.
+	pub async fn transfer(
+		&self,
+		from: &LocalAccount,
+		to: AccountAddress,
+		amount: u64,
+	) -> Result<PendingTransaction, SuzukaClientError> {
+		self.submit_payload(from, aptos_stdlib::aptos_coin_transfer(to, amount)).await
+	}
+
+	/// Submits the call of an entry function signed by the account, without waiting for it.
+	pub async fn submit_entry_function(
+		&self,
+		account: &LocalAccount,
+		entry_function: EntryFunction,
+	) -> Result<PendingTransaction, SuzukaClientError> {
+		self.submit_payload(account, TransactionPayload::EntryFunction(entry_function))
+			.await
+	}
+
+	async fn submit_payload(
+		&self,
+		account: &LocalAccount,
+		payload: TransactionPayload,
+	) -> Result<PendingTransaction, SuzukaClientError> {
+		let transaction_builder = self.transaction_factory().await?.payload(payload);
+		let transaction = account.sign_with_transaction_builder(transaction_builder);
+		Ok(self.rest_client.submit(&transaction).await?.into_inner())
+	}
+
+	/// Polls the transaction until it is committed, and fails if it is not successful, has
+	/// expired or is still not committed after the wait timeout.
+	pub async fn wait(
+		&self,
+		pending: &PendingTransaction,
+	) -> Result<Transaction, SuzukaClientError> {
+		self.wait_by_hash(pending.hash.into(), pending.request.expiration_timestamp_secs.into())
+			.await
+	}
+
+	/// Polls the transaction with the hash like [SuzukaClient::wait], for the transactions
+	/// submitted without getting a pending transaction back.
+	pub async fn wait_by_hash(
+		&self,
+		hash: HashValue,
+		expiration_timestamp_secs: u64,
+	) -> Result<Transaction, SuzukaClientError> {
+		let start = Instant::now();
+		loop {
+			match self.rest_client.get_transaction_by_hash(hash).await {
+				Ok(response) => match response.into_inner() {
+					Transaction::PendingTransaction(_) => {}
+					transaction if transaction.success() => return Ok(transaction),
+					transaction => {
+						return Err(SuzukaClientError::TransactionFailed {
+							hash: hash.to_hex_literal(),
+							vm_status: transaction.vm_status(),
+						})
+					}
+				},
+				// The transaction is not found until the node has received it.
+				Err(err) => tracing::debug!("Transaction {hash} not found yet: {err}"),
+			}
+
+			let now_secs = SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map(|now| now.as_secs())
+				.unwrap_or_default();
+			if now_secs > expiration_timestamp_secs {
+				return Err(SuzukaClientError::TransactionExpired { hash: hash.to_hex_literal() });
+			}
+			if start.elapsed() >= self.wait_timeout {
+				return Err(SuzukaClientError::Timeout {
+					hash: hash.to_hex_literal(),
+					timeout: self.wait_timeout,
+				});
+			}
+			tokio::time::sleep(self.polling_interval).await;
+		}
+	}
+
+	/// Submits a transaction signed offline in BCS, and returns its hash to wait for it with
+	/// [SuzukaClient::wait_by_hash].
+	pub async fn submit_bcs(
+		&self,
+		transaction: &SignedTransaction,
+	) -> Result<HashValue, SuzukaClientError> {
+		self.rest_client.submit_bcs(transaction).await?;
+		Ok(transaction.committed_hash())
+	}
+
+	/// Fetches the chain id and the sequence number of the sender, to carry them to the machine
+	/// building the transaction offline.
+	pub async fn transaction_params(
+		&self,
+		sender: AccountAddress,
+		expiration_timestamp_secs: u64,
+	) -> Result<TransactionParams, SuzukaClientError> {
+		let account = self.rest_client.get_account(sender).await?.into_inner();
+		Ok(TransactionParams::new(
+			sender,
+			account.sequence_number,
+			self.chain_id().await?,
+			expiration_timestamp_secs,
+		)
+		.with_max_gas_amount(self.max_gas_amount))
+	}
+
+	/// Gets the events of the event handle field of a resource of the account, with their data
+	/// decoded.
+	pub async fn get_events<T: DeserializeOwned>(
+		&self,
+		address: AccountAddress,
+		event_handle_struct: &str,
+		field_name: &str,
+		start: Option<u64>,
+		limit: Option<u16>,
+	) -> Result<Vec<T>, SuzukaClientError> {
+		let events = self
+			.rest_client
+			.get_account_events(address, event_handle_struct, field_name, start, limit)
+			.await?
+			.into_inner();
+		events
+			.into_iter()
+			.map(|event| Ok(serde_json::from_value(event.data)?))
+			.collect()
+	}
+}
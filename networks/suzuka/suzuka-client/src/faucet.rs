@@ -0,0 +1,215 @@
+use crate::{
+	client::{SuzukaClient, SuzukaClientError},
+	types::{account_address::AccountAddress, LocalAccount},
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// A faucet funding accounts, implemented by [SuzukaClient] and replaced in the tests.
+#[async_trait::async_trait]
+pub trait Faucet: Send + Sync {
+	async fn fund(&self, address: AccountAddress, amount: u64) -> Result<(), SuzukaClientError>;
+}
+
+#[async_trait::async_trait]
+impl Faucet for SuzukaClient {
+	async fn fund(&self, address: AccountAddress, amount: u64) -> Result<(), SuzukaClientError> {
+		SuzukaClient::fund(self, address, amount).await
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct FaucetPoolConfig {
+	/// The maximum number of requests sent to the faucet at the same time, the others are queued.
+	pub max_concurrent_requests: usize,
+	/// The number of times a rate limited or failed request is retried.
+	pub max_retries: u32,
+	/// The delay before the first retry, doubled on each retry.
+	pub initial_backoff: Duration,
+	pub max_backoff: Duration,
+}
+
+impl Default for FaucetPoolConfig {
+	fn default() -> Self {
+		FaucetPoolConfig {
+			max_concurrent_requests: 4,
+			max_retries: 10,
+			initial_backoff: Duration::from_millis(500),
+			max_backoff: Duration::from_secs(30),
+		}
+	}
+}
+
+/// Funds the accounts through a queue of faucet requests, so that concurrent scenarios don't
+/// fail when the faucet rate limits them.
+/// When the faucet rate limits a request, all the requests are paused for the delay it asks
+/// for, or for the backoff when it doesn't tell.
+pub struct FaucetPool<F = SuzukaClient> {
+	faucet: Arc<F>,
+	config: FaucetPoolConfig,
+	permits: Semaphore,
+	paused_until: Mutex<Option<Instant>>,
+}
+
+impl<F: Faucet> FaucetPool<F> {
+	pub fn new(faucet: Arc<F>, config: FaucetPoolConfig) -> Self {
+		FaucetPool {
+			permits: Semaphore::new(config.max_concurrent_requests),
+			faucet,
+			config,
+			paused_until: Mutex::new(None),
+		}
+	}
+
+	/// Funds the account, retrying the request with backoff while the faucet rate limits it or
+	/// fails to reach the node.
+	pub async fn fund(
+		&self,
+		address: AccountAddress,
+		amount: u64,
+	) -> Result<(), SuzukaClientError> {
+		let mut backoff = self.config.initial_backoff;
+		let mut retries = 0;
+		loop {
+			let result = {
+				let _permit = self.permits.acquire().await.expect("Faucet pool semaphore closed");
+				self.wait_rate_limit().await;
+				self.faucet.fund(address, amount).await
+			};
+			let err = match result {
+				Ok(()) => return Ok(()),
+				Err(err) if retries < self.config.max_retries => err,
+				Err(err) => return Err(err),
+			};
+			retries += 1;
+			match err {
+				SuzukaClientError::RateLimited { retry_after } => {
+					let delay = retry_after.unwrap_or(backoff);
+					tracing::debug!("Faucet rate limited funding {address}, pausing for {delay:?}");
+					self.pause(delay);
+				}
+				SuzukaClientError::Faucet(_) | SuzukaClientError::Rest(_) => {
+					tracing::warn!(
+						"Faucet failed to fund {address}, retrying in {backoff:?}: {err}"
+					);
+					tokio::time::sleep(backoff).await;
+				}
+				err => return Err(err),
+			}
+			backoff = (backoff * 2).min(self.config.max_backoff);
+		}
+	}
+
+	/// Generates new accounts and funds them, to reuse them across the scenario iterations.
+	pub async fn prefund(
+		&self,
+		count: usize,
+		amount: u64,
+	) -> Result<Vec<LocalAccount>, SuzukaClientError> {
+		let accounts: Vec<LocalAccount> =
+			(0..count).map(|_| LocalAccount::generate(&mut rand::rngs::OsRng)).collect();
+		futures::future::try_join_all(
+			accounts.iter().map(|account| self.fund(account.address(), amount)),
+		)
+		.await?;
+		Ok(accounts)
+	}
+
+	fn pause(&self, delay: Duration) {
+		let until = Instant::now() + delay;
+		let mut paused_until = self.paused_until.lock().expect("Faucet pool lock poisoned");
+		if *paused_until < Some(until) {
+			*paused_until = Some(until);
+		}
+	}
+
+	async fn wait_rate_limit(&self) {
+		loop {
+			let paused_until = *self.paused_until.lock().expect("Faucet pool lock poisoned");
+			match paused_until {
+				Some(until) if until > Instant::now() => {
+					tokio::time::sleep_until(until.into()).await;
+				}
+				_ => return,
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicU32, Ordering};
+
+	/// A faucet rate limiting a number of requests before funding the accounts.
+	struct RateLimitedFaucet {
+		rate_limited: AtomicU32,
+		funded: Mutex<Vec<AccountAddress>>,
+	}
+
+	impl RateLimitedFaucet {
+		fn new(rate_limited: u32) -> Self {
+			RateLimitedFaucet {
+				rate_limited: AtomicU32::new(rate_limited),
+				funded: Mutex::default(),
+			}
+		}
+	}
+
+	#[async_trait::async_trait]
+	impl Faucet for RateLimitedFaucet {
+		async fn fund(
+			&self,
+			address: AccountAddress,
+			_amount: u64,
+		) -> Result<(), SuzukaClientError> {
+			let limited = self
+				.rate_limited
+				.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| count.checked_sub(1))
+				.is_ok();
+			if limited {
+				return Err(SuzukaClientError::RateLimited {
+					retry_after: Some(Duration::from_millis(20)),
+				});
+			}
+			self.funded.lock().unwrap().push(address);
+			Ok(())
+		}
+	}
+
+	fn config(max_retries: u32) -> FaucetPoolConfig {
+		FaucetPoolConfig {
+			max_concurrent_requests: 2,
+			max_retries,
+			initial_backoff: Duration::from_millis(10),
+			max_backoff: Duration::from_millis(50),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_prefund_retries_rate_limited_requests() -> Result<(), SuzukaClientError> {
+		let faucet = Arc::new(RateLimitedFaucet::new(3));
+		let pool = FaucetPool::new(faucet.clone(), config(5));
+
+		let start = Instant::now();
+		let accounts = pool.prefund(5, 1_000).await?;
+		assert!(start.elapsed() >= Duration::from_millis(20));
+		let mut funded = faucet.funded.lock().unwrap().clone();
+		let mut addresses: Vec<_> = accounts.iter().map(LocalAccount::address).collect();
+		funded.sort();
+		addresses.sort();
+		assert_eq!(funded, addresses);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_fund_fails_after_max_retries() {
+		let faucet = Arc::new(RateLimitedFaucet::new(10));
+		let pool = FaucetPool::new(faucet.clone(), config(2));
+
+		let result = pool.fund(AccountAddress::ONE, 1_000).await;
+		assert!(matches!(result, Err(SuzukaClientError::RateLimited { .. })), "{result:?}");
+		assert_eq!(faucet.rate_limited.load(Ordering::SeqCst), 7);
+	}
+}
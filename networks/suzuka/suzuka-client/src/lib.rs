@@ -1,5 +1,10 @@
+pub mod client;
+pub mod faucet;
 pub mod load_soak_testing;
+pub mod offline;
 #[cfg(test)]
 pub mod tests;
 
 pub use aptos_sdk::*;
+pub use client::{SuzukaClient, SuzukaClientError};
+pub use faucet::{Faucet, FaucetPool, FaucetPoolConfig};
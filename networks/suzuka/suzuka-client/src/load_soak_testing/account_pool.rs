@@ -0,0 +1,140 @@
+use crate::{
+	faucet::{Faucet, FaucetPool},
+	types::LocalAccount,
+	SuzukaClient, SuzukaClientError,
+};
+use std::collections::VecDeque;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+
+/// A pool of funded accounts created before the test, handed out to the scenarios and recycled
+/// when they end, so that the scenarios don't call the faucet on each iteration.
+/// The accounts keep their sequence number between the scenarios, the transactions are signed
+/// with the local sequence number without fetching it from the node.
+pub struct AccountPool {
+	accounts: Mutex<VecDeque<LocalAccount>>,
+	available: Semaphore,
+	size: usize,
+}
+
+impl AccountPool {
+	/// Creates and funds the accounts of the pool.
+	pub async fn new<F: Faucet>(
+		faucet: &FaucetPool<F>,
+		size: usize,
+		amount: u64,
+	) -> Result<Arc<Self>, SuzukaClientError> {
+		let accounts = faucet.prefund(size, amount).await?;
+		tracing::info!("Account pool funded {size} accounts");
+		Ok(AccountPool::from_accounts(accounts))
+	}
+
+	/// Creates the pool of already funded accounts.
+	pub fn from_accounts(accounts: Vec<LocalAccount>) -> Arc<Self> {
+		Arc::new(AccountPool {
+			available: Semaphore::new(accounts.len()),
+			size: accounts.len(),
+			accounts: Mutex::new(accounts.into()),
+		})
+	}
+
+	/// The number of accounts of the pool, handed out or not.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// The number of accounts not handed out.
+	pub fn available(&self) -> usize {
+		self.available.available_permits()
+	}
+
+	/// Hands out an account, waiting for one to be recycled when they are all in use.
+	pub async fn acquire(self: &Arc<Self>) -> PooledAccount {
+		self.available.acquire().await.expect("Account pool semaphore closed").forget();
+		let account = self
+			.accounts
+			.lock()
+			.expect("Account pool lock poisoned")
+			.pop_front()
+			.expect("Account pool has a permit without account");
+		PooledAccount { account: Some(account), pool: self.clone() }
+	}
+
+	fn recycle(&self, account: LocalAccount) {
+		self.accounts.lock().expect("Account pool lock poisoned").push_back(account);
+		self.available.add_permits(1);
+	}
+}
+
+/// An account handed out by the [AccountPool], given back to the pool when dropped.
+pub struct PooledAccount {
+	account: Option<LocalAccount>,
+	pool: Arc<AccountPool>,
+}
+
+impl PooledAccount {
+	/// Sets the local sequence number to the one of the node. To call when a transaction signed
+	/// with the account was not submitted, otherwise the next transactions would have a gap.
+	pub async fn resync_sequence_number(
+		&self,
+		client: &SuzukaClient,
+	) -> Result<(), SuzukaClientError> {
+		let account = client.rest_client().get_account(self.address()).await?.into_inner();
+		self.set_sequence_number(account.sequence_number);
+		Ok(())
+	}
+}
+
+impl Deref for PooledAccount {
+	type Target = LocalAccount;
+
+	fn deref(&self) -> &LocalAccount {
+		self.account.as_ref().expect("Pooled account already recycled")
+	}
+}
+
+impl Drop for PooledAccount {
+	fn drop(&mut self) {
+		if let Some(account) = self.account.take() {
+			self.pool.recycle(account);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	#[tokio::test]
+	async fn test_accounts_are_recycled() {
+		let accounts: Vec<_> =
+			(0..2).map(|_| LocalAccount::generate(&mut rand::rngs::OsRng)).collect();
+		let first_address = accounts[0].address();
+		let pool = AccountPool::from_accounts(accounts);
+
+		let first = pool.acquire().await;
+		let second = pool.acquire().await;
+		assert_eq!(first.address(), first_address);
+		assert_eq!(pool.available(), 0);
+		first.increment_sequence_number();
+		first.increment_sequence_number();
+
+		// All the accounts are in use, the next scenario waits for one to be recycled.
+		let waiting = tokio::spawn({
+			let pool = pool.clone();
+			async move { pool.acquire().await.address() }
+		});
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		assert!(!waiting.is_finished());
+		drop(first);
+		assert_eq!(waiting.await.unwrap(), first_address);
+
+		drop(second);
+		let recycled = pool.acquire().await;
+		assert_eq!(recycled.address(), first_address);
+		assert_eq!(recycled.sequence_number(), 2);
+		assert_eq!(pool.size(), 2);
+	}
+}
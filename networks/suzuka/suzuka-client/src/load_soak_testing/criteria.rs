@@ -0,0 +1,111 @@
+use super::results::TestResults;
+use std::time::Duration;
+
+/// The success criteria of a test, checked on its results at the end of the test so that a soak
+/// run can gate a release. The criteria which are not set are not checked.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SuccessCriteria {
+	/// The maximum ratio of failed scenarios, between 0 and 1.
+	pub max_error_rate: Option<f64>,
+	/// The maximum p99 latency of each operation.
+	pub max_p99_latency: Option<Duration>,
+	/// The maximum time without any operation or scenario completing.
+	pub max_stall: Option<Duration>,
+}
+
+impl SuccessCriteria {
+	/// The criteria set by the env vars LOADTEST_MAX_ERROR_RATE, LOADTEST_MAX_P99_MILLI and
+	/// LOADTEST_MAX_STALL_SECS.
+	pub fn from_env() -> Self {
+		fn env_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+			std::env::var(name).ok().and_then(|val| val.parse().ok())
+		}
+		SuccessCriteria {
+			max_error_rate: env_var("LOADTEST_MAX_ERROR_RATE"),
+			max_p99_latency: env_var("LOADTEST_MAX_P99_MILLI").map(Duration::from_millis),
+			max_stall: env_var("LOADTEST_MAX_STALL_SECS").map(Duration::from_secs),
+		}
+	}
+
+	/// The criteria the results don't meet, described for the test report.
+	pub fn violations(&self, results: &TestResults) -> Vec<String> {
+		let mut violations = vec![];
+		if let Some(max_error_rate) = self.max_error_rate {
+			let error_rate = results.error_rate();
+			if error_rate > max_error_rate {
+				violations.push(format!(
+					"error rate {error_rate:.4} ({} of {} scenarios failed) above {max_error_rate}",
+					results.failed_scenarios, results.scenarios
+				));
+			}
+		}
+		if let Some(max_p99_latency) = self.max_p99_latency {
+			for stats in &results.operations {
+				if stats.p99_milli > max_p99_latency.as_millis() {
+					violations.push(format!(
+						"operation {} p99 latency {}ms above {}ms",
+						stats.operation,
+						stats.p99_milli,
+						max_p99_latency.as_millis()
+					));
+				}
+			}
+		}
+		if let Some(max_stall) = self.max_stall {
+			if results.longest_stall_milli > max_stall.as_millis() {
+				violations.push(format!(
+					"no operation completed for {}ms, above {}ms",
+					results.longest_stall_milli,
+					max_stall.as_millis()
+				));
+			}
+		}
+		violations
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::load_soak_testing::latency::{LatencyStats, Operation};
+
+	#[test]
+	fn test_criteria_violations() {
+		let results = TestResults {
+			duration_milli: 60_000,
+			average_execution_time_milli: 1_500,
+			scenarios: 200,
+			failed_scenarios: 3,
+			longest_stall_milli: 12_000,
+			scenario_types: vec![],
+			operations: vec![
+				LatencyStats::new(Operation::Submit, vec![Duration::from_millis(100); 10]),
+				LatencyStats::new(Operation::WaitForTransaction, vec![Duration::from_secs(3); 10]),
+			],
+			violations: vec![],
+		};
+
+		assert!(SuccessCriteria::default().violations(&results).is_empty());
+		let criteria = SuccessCriteria {
+			max_error_rate: Some(0.02),
+			max_p99_latency: Some(Duration::from_secs(2)),
+			max_stall: Some(Duration::from_secs(15)),
+		};
+		assert_eq!(
+			criteria.violations(&results),
+			["operation wait_for_transaction p99 latency 3000ms above 2000ms"]
+		);
+		let criteria = SuccessCriteria {
+			max_error_rate: Some(0.01),
+			max_p99_latency: None,
+			max_stall: Some(Duration::from_secs(10)),
+		};
+		assert_eq!(
+			criteria.violations(&results),
+			[
+				"error rate 0.0150 (3 of 200 scenarios failed) above 0.01",
+				"no operation completed for 12000ms, above 10000ms"
+			]
+		);
+	}
+}
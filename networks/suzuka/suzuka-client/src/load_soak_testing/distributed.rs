@@ -0,0 +1,454 @@
+use super::latency::LatencyStats;
+use super::results::{ScenarioTypeStats, TestResults};
+use super::{execute_mix_test, finish_test, ExecutionConfig, ScenarioMix, TestKind};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use suzuka_load_soak_grpc as grpc;
+use suzuka_load_soak_grpc::load_soak_coordinator_service_client::LoadSoakCoordinatorServiceClient;
+use suzuka_load_soak_grpc::load_soak_coordinator_service_server::{
+	LoadSoakCoordinatorService, LoadSoakCoordinatorServiceServer,
+};
+use suzuka_load_soak_grpc::register_response::Kind;
+use tokio::sync::watch;
+use tonic::{transport::Server, Request, Response, Status};
+
+/// Defines how the coordinator of a distributed test is run.
+#[derive(Clone, Debug)]
+pub struct CoordinatorConfig {
+	/// The address the coordinator listens on for the workers.
+	pub listen_address: SocketAddr,
+	/// The number of workers the scenarios are divided between. The test starts once they all registered.
+	pub number_workers: usize,
+	/// The delay between the registration of the last worker and the start of the test, so that all the workers know the start time before it.
+	pub start_delay: Duration,
+}
+
+impl Default for CoordinatorConfig {
+	fn default() -> Self {
+		let listen_address = std::env::var("LOADTEST_COORDINATOR_LISTEN_ADDRESS")
+			.unwrap_or("0.0.0.0:30770".to_string())
+			.parse()
+			.unwrap_or(SocketAddr::from(([0, 0, 0, 0], 30770)));
+		let number_workers: usize = std::env::var("LOADTEST_NUMBER_WORKERS")
+			.unwrap_or("2".to_string())
+			.parse()
+			.unwrap_or(2);
+		let start_delay: u64 = std::env::var("LOADTEST_START_DELAY_SECS")
+			.unwrap_or("5".to_string())
+			.parse()
+			.unwrap_or(5);
+		CoordinatorConfig {
+			listen_address,
+			number_workers,
+			start_delay: Duration::from_secs(start_delay),
+		}
+	}
+}
+
+/// The share of a worker of a distributed test: the scenarios it runs, from its first scenario id.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkerAssignment {
+	pub first_scenario_id: usize,
+	pub kind: TestKind,
+}
+
+/// The share of the worker of index among number_workers of the total, the first workers take the remainder.
+fn share(total: usize, number_workers: usize, index: usize) -> usize {
+	total / number_workers + usize::from(index < total % number_workers)
+}
+
+/// Divides the scenarios of the test between the workers. The scenario ids of the workers follow each other from 1,
+/// so that the scenario mix is the same as when the test is run by a single process.
+/// The profile test can't be divided, its scenario count is adjusted by the running process.
+pub fn assign_scenarios(
+	kind: &TestKind,
+	number_workers: usize,
+) -> Result<Vec<WorkerAssignment>, String> {
+	if number_workers == 0 {
+		return Err("No worker to run the test".to_string());
+	}
+	let mut first_scenario_id = 1;
+	let mut assignments = vec![];
+	for index in 0..number_workers {
+		let (kind, number_scenarios) = match kind {
+			TestKind::Load { number_scenarios } => {
+				let number_scenarios = share(*number_scenarios, number_workers, index);
+				(TestKind::Load { number_scenarios }, number_scenarios)
+			}
+			TestKind::Soak { min_scenarios, max_scenarios, duration, number_cycle } => {
+				let max_scenarios = share(*max_scenarios, number_workers, index);
+				let kind = TestKind::Soak {
+					min_scenarios: share(*min_scenarios, number_workers, index),
+					max_scenarios,
+					duration: *duration,
+					number_cycle: *number_cycle,
+				};
+				(kind, max_scenarios)
+			}
+			TestKind::Profile { .. } => {
+				return Err("The profile test can't be distributed between workers".to_string())
+			}
+		};
+		if number_scenarios == 0 {
+			return Err(format!("Less scenarios than the {number_workers} workers"));
+		}
+		assignments.push(WorkerAssignment { first_scenario_id, kind });
+		first_scenario_id += number_scenarios;
+	}
+	Ok(assignments)
+}
+
+fn unix_milli(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+struct CoordinatorState {
+	assignments: Vec<WorkerAssignment>,
+	registered: usize,
+	results: Vec<Option<TestResults>>,
+}
+
+/// Serves the assignments to the workers and collects their results.
+struct Coordinator {
+	state: Arc<Mutex<CoordinatorState>>,
+	start_delay: Duration,
+	/// The start time of the test, set when the last worker registers.
+	start_time: watch::Sender<Option<u64>>,
+	/// Set when all the workers reported their results.
+	all_reported: watch::Sender<bool>,
+}
+
+impl Coordinator {
+	fn lock_state(&self) -> std::sync::MutexGuard<'_, CoordinatorState> {
+		self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+#[tonic::async_trait]
+impl LoadSoakCoordinatorService for Coordinator {
+	async fn register(
+		&self,
+		request: Request<grpc::RegisterRequest>,
+	) -> Result<Response<grpc::RegisterResponse>, Status> {
+		let worker_name = request.into_inner().worker_name;
+		let mut start_time = self.start_time.subscribe();
+		let (worker_id, assignment) = {
+			let mut state = self.lock_state();
+			let worker_id = state.registered;
+			let Some(assignment) = state.assignments.get(worker_id).cloned() else {
+				return Err(Status::resource_exhausted("All the workers already registered"));
+			};
+			state.registered += 1;
+			if state.registered == state.assignments.len() {
+				self.start_time
+					.send_replace(Some(unix_milli(SystemTime::now() + self.start_delay)));
+			}
+			(worker_id, assignment)
+		};
+		tracing::info!(
+			"Worker {worker_name} registered as worker {worker_id} with the scenarios from {}: {:?}",
+			assignment.first_scenario_id,
+			assignment.kind
+		);
+
+		// Wait for all the workers to register.
+		let start_time = (*start_time
+			.wait_for(Option::is_some)
+			.await
+			.map_err(|_| Status::unavailable("Coordinator stopped"))?)
+		.unwrap_or_default();
+		let kind = match assignment.kind {
+			TestKind::Load { number_scenarios } => {
+				Kind::Load(grpc::LoadTest { number_scenarios: number_scenarios as u64 })
+			}
+			TestKind::Soak { min_scenarios, max_scenarios, duration, number_cycle } => {
+				Kind::Soak(grpc::SoakTest {
+					min_scenarios: min_scenarios as u64,
+					max_scenarios: max_scenarios as u64,
+					duration_milli: duration.as_millis() as u64,
+					number_cycle,
+				})
+			}
+			TestKind::Profile { .. } => {
+				return Err(Status::internal("The profile test can't be distributed"))
+			}
+		};
+		Ok(Response::new(grpc::RegisterResponse {
+			worker_id: worker_id as u32,
+			first_scenario_id: assignment.first_scenario_id as u64,
+			kind: Some(kind),
+			start_time_unix_milli: start_time,
+		}))
+	}
+
+	async fn report_results(
+		&self,
+		request: Request<grpc::ReportResultsRequest>,
+	) -> Result<Response<grpc::ReportResultsResponse>, Status> {
+		let request = request.into_inner();
+		let Some(results) = request.results else {
+			return Err(Status::invalid_argument("Missing results"));
+		};
+		let results = TestResults::try_from(results).map_err(Status::invalid_argument)?;
+		tracing::info!(
+			"Worker {} reported {} scenarios, {} failed",
+			request.worker_id,
+			results.scenarios,
+			results.failed_scenarios
+		);
+
+		let mut state = self.lock_state();
+		let Some(worker_results) = state.results.get_mut(request.worker_id as usize) else {
+			return Err(Status::not_found(format!("Unknown worker {}", request.worker_id)));
+		};
+		*worker_results = Some(results);
+		if state.results.iter().all(Option::is_some) {
+			self.all_reported.send_replace(true);
+		}
+		Ok(Response::new(grpc::ReportResultsResponse {}))
+	}
+}
+
+/// Coordinates a test run by remote workers: the scenarios of the test defined in the config are divided between the
+/// workers, which all start the test at the same time once they registered. The coordinator returns once all the
+/// workers reported their results. The aggregated results are checked against the success criteria of the config
+/// then written to its result file.
+pub fn run_coordinator(
+	config: ExecutionConfig,
+	coordinator: CoordinatorConfig,
+) -> Result<TestResults, anyhow::Error> {
+	let assignments =
+		assign_scenarios(&config.kind, coordinator.number_workers).map_err(anyhow::Error::msg)?;
+	let rt = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+	let worker_results = rt.block_on(async {
+		let state = Arc::new(Mutex::new(CoordinatorState {
+			results: vec![None; assignments.len()],
+			assignments,
+			registered: 0,
+		}));
+		let (all_reported, mut reported) = watch::channel(false);
+		let service = Coordinator {
+			state: state.clone(),
+			start_delay: coordinator.start_delay,
+			start_time: watch::channel(None).0,
+			all_reported,
+		};
+
+		tracing::info!(
+			"Coordinator: serving on {} for {} workers",
+			coordinator.listen_address,
+			coordinator.number_workers
+		);
+		Server::builder()
+			.add_service(LoadSoakCoordinatorServiceServer::new(service))
+			.serve_with_shutdown(coordinator.listen_address, async move {
+				let _ = reported.wait_for(|reported| *reported).await;
+			})
+			.await?;
+
+		let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		Ok::<_, anyhow::Error>(state.results.drain(..).flatten().collect::<Vec<_>>())
+	})?;
+
+	let results = TestResults::aggregate(&worker_results);
+	let results = finish_test(&config.resultfile, &config.criteria, results);
+	tracing::info!(
+		"End distributed test with {} workers. Passed:{}",
+		worker_results.len(),
+		results.passed()
+	);
+	Ok(results)
+}
+
+/// Runs the test as a worker of the coordinator: registers, waits for the start time, runs the assigned scenarios
+/// then reports the results to the coordinator. The results are also written to the result file of the worker.
+pub(super) fn run_worker(
+	coordinator_url: String,
+	mut config: ExecutionConfig,
+	mix: ScenarioMix,
+) -> TestResults {
+	let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+		Ok(rt) => rt,
+		Err(err) => panic!("Tokio RT runtime fail to start because of this error:{err}"),
+	};
+	let worker_name = std::env::var("HOSTNAME").unwrap_or(format!("worker-{}", std::process::id()));
+	let registration = rt
+		.block_on(async {
+			let mut client =
+				LoadSoakCoordinatorServiceClient::connect(coordinator_url.clone()).await?;
+			let response = client.register(grpc::RegisterRequest { worker_name }).await?;
+			Ok::<_, anyhow::Error>(response.into_inner())
+		})
+		.and_then(|response| Ok((WorkerAssignment::try_from(&response)?, response)));
+	let (assignment, response) = match registration {
+		Ok(registration) => registration,
+		Err(err) => panic!("Worker fail to register to the coordinator {coordinator_url} because of this error:{err}"),
+	};
+	tracing::info!(
+		"Registered as worker {} with the scenarios from {}: {:?}",
+		response.worker_id,
+		assignment.first_scenario_id,
+		assignment.kind
+	);
+
+	config.kind = assignment.kind;
+	config.first_scenario_id = assignment.first_scenario_id;
+	config.coordinator_url = None;
+	// All the workers start the test at the same time.
+	let start_time = UNIX_EPOCH + Duration::from_millis(response.start_time_unix_milli);
+	if let Ok(wait) = start_time.duration_since(SystemTime::now()) {
+		std::thread::sleep(wait);
+	}
+	let results = execute_mix_test(config, mix);
+
+	let report = rt.block_on(async {
+		let mut client = LoadSoakCoordinatorServiceClient::connect(coordinator_url.clone()).await?;
+		client
+			.report_results(grpc::ReportResultsRequest {
+				worker_id: response.worker_id,
+				results: Some((&results).into()),
+			})
+			.await?;
+		Ok::<_, anyhow::Error>(())
+	});
+	if let Err(err) = report {
+		tracing::warn!("Test results fail to be reported to {coordinator_url} because: {err}");
+	}
+	results
+}
+
+impl TryFrom<&grpc::RegisterResponse> for WorkerAssignment {
+	type Error = anyhow::Error;
+
+	fn try_from(response: &grpc::RegisterResponse) -> Result<Self, Self::Error> {
+		let kind = match &response.kind {
+			Some(Kind::Load(load)) => {
+				TestKind::Load { number_scenarios: load.number_scenarios as usize }
+			}
+			Some(Kind::Soak(soak)) => TestKind::Soak {
+				min_scenarios: soak.min_scenarios as usize,
+				max_scenarios: soak.max_scenarios as usize,
+				duration: Duration::from_millis(soak.duration_milli),
+				number_cycle: soak.number_cycle,
+			},
+			None => anyhow::bail!("No test kind assigned by the coordinator"),
+		};
+		Ok(WorkerAssignment { first_scenario_id: response.first_scenario_id as usize, kind })
+	}
+}
+
+impl From<&TestResults> for grpc::TestResults {
+	fn from(results: &TestResults) -> Self {
+		grpc::TestResults {
+			duration_milli: results.duration_milli as u64,
+			average_execution_time_milli: results.average_execution_time_milli as u64,
+			scenarios: results.scenarios as u64,
+			failed_scenarios: results.failed_scenarios as u64,
+			longest_stall_milli: results.longest_stall_milli as u64,
+			scenario_types: results
+				.scenario_types
+				.iter()
+				.map(|stats| grpc::ScenarioTypeStats {
+					name: stats.name.clone(),
+					weight: stats.weight,
+					scenarios: stats.scenarios as u64,
+					failed: stats.failed as u64,
+					average_execution_time_milli: stats.average_execution_time_milli as u64,
+				})
+				.collect(),
+			operations: results
+				.operations
+				.iter()
+				.map(|stats| grpc::LatencyStats {
+					operation: stats.operation.to_string(),
+					count: stats.count as u64,
+					p50_milli: stats.p50_milli as u64,
+					p95_milli: stats.p95_milli as u64,
+					p99_milli: stats.p99_milli as u64,
+					max_milli: stats.max_milli as u64,
+				})
+				.collect(),
+		}
+	}
+}
+
+impl TryFrom<grpc::TestResults> for TestResults {
+	type Error = String;
+
+	fn try_from(results: grpc::TestResults) -> Result<Self, Self::Error> {
+		let operations = results
+			.operations
+			.into_iter()
+			.map(|stats| {
+				Ok(LatencyStats {
+					operation: stats.operation.parse()?,
+					count: stats.count as usize,
+					p50_milli: stats.p50_milli.into(),
+					p95_milli: stats.p95_milli.into(),
+					p99_milli: stats.p99_milli.into(),
+					max_milli: stats.max_milli.into(),
+				})
+			})
+			.collect::<Result<_, String>>()?;
+		Ok(TestResults {
+			duration_milli: results.duration_milli.into(),
+			average_execution_time_milli: results.average_execution_time_milli.into(),
+			scenarios: results.scenarios as usize,
+			failed_scenarios: results.failed_scenarios as usize,
+			longest_stall_milli: results.longest_stall_milli.into(),
+			scenario_types: results
+				.scenario_types
+				.into_iter()
+				.map(|stats| ScenarioTypeStats {
+					name: stats.name,
+					weight: stats.weight,
+					scenarios: stats.scenarios as usize,
+					failed: stats.failed as usize,
+					average_execution_time_milli: stats.average_execution_time_milli.into(),
+				})
+				.collect(),
+			operations,
+			violations: vec![],
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_assign_scenarios() {
+		let assignments = assign_scenarios(&TestKind::Load { number_scenarios: 10 }, 3).unwrap();
+		assert_eq!(
+			assignments,
+			[
+				WorkerAssignment {
+					first_scenario_id: 1,
+					kind: TestKind::Load { number_scenarios: 4 }
+				},
+				WorkerAssignment {
+					first_scenario_id: 5,
+					kind: TestKind::Load { number_scenarios: 3 }
+				},
+				WorkerAssignment {
+					first_scenario_id: 8,
+					kind: TestKind::Load { number_scenarios: 3 }
+				},
+			]
+		);
+
+		let soak = TestKind::build_soak_test(5, 10, Duration::from_secs(60), 2);
+		let assignments = assign_scenarios(&soak, 3).unwrap();
+		let first_ids: Vec<_> = assignments.iter().map(|worker| worker.first_scenario_id).collect();
+		assert_eq!(first_ids, [1, 5, 8]);
+		assert_eq!(
+			assignments[2].kind,
+			TestKind::build_soak_test(1, 3, Duration::from_secs(60), 2)
+		);
+
+		assert!(assign_scenarios(&TestKind::Load { number_scenarios: 2 }, 3).is_err());
+		assert!(assign_scenarios(&TestKind::Load { number_scenarios: 2 }, 0).is_err());
+	}
+}
@@ -0,0 +1,180 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The operations of the scenarios whose latency is reported at the end of the test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+	/// Funding an account with the faucet.
+	Fund,
+	/// Submitting a transaction.
+	Submit,
+	/// Waiting for a submitted transaction to be executed.
+	WaitForTransaction,
+}
+
+impl fmt::Display for Operation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Operation::Fund => write!(f, "fund"),
+			Operation::Submit => write!(f, "submit"),
+			Operation::WaitForTransaction => write!(f, "wait_for_transaction"),
+		}
+	}
+}
+
+impl FromStr for Operation {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"fund" => Ok(Operation::Fund),
+			"submit" => Ok(Operation::Submit),
+			"wait_for_transaction" => Ok(Operation::WaitForTransaction),
+			_ => Err(format!("Unknown operation {s}")),
+		}
+	}
+}
+
+/// The latencies recorded by all the running scenarios, which run in different threads.
+static LATENCIES: Lazy<Mutex<BTreeMap<Operation, Vec<Duration>>>> =
+	Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// The progress of the running scenarios: the last time an operation or a scenario completed,
+/// and the longest time without any completing.
+static PROGRESS: Lazy<Mutex<Progress>> = Lazy::new(|| Mutex::new(Progress::new()));
+
+struct Progress {
+	last: Instant,
+	longest_stall: Duration,
+}
+
+impl Progress {
+	fn new() -> Self {
+		Progress { last: Instant::now(), longest_stall: Duration::ZERO }
+	}
+
+	fn record(&mut self) {
+		let now = Instant::now();
+		self.longest_stall = self.longest_stall.max(now - self.last);
+		self.last = now;
+	}
+}
+
+/// Records the latency of an operation run by a scenario.
+pub fn record_latency(operation: Operation, latency: Duration) {
+	let mut latencies = LATENCIES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	latencies.entry(operation).or_default().push(latency);
+	drop(latencies);
+	record_progress();
+}
+
+/// Records that an operation or a scenario completed, the test is not stalled.
+pub(super) fn record_progress() {
+	PROGRESS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).record();
+}
+
+/// Starts tracking the progress of a new test.
+pub(super) fn start_progress() {
+	*PROGRESS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Progress::new();
+}
+
+/// The longest time without any operation or scenario completing since the test started,
+/// including the time since the last one completed.
+pub(super) fn longest_stall() -> Duration {
+	let mut progress = PROGRESS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	progress.record();
+	progress.longest_stall
+}
+
+/// Runs the operation of a scenario and records its latency, whether it succeeds or not.
+///
+/// ```ignore
+/// let txn_hash = measure_latency(Operation::Submit, coin_client.transfer(&mut alice, bob.address(), 1_000, None)).await?;
+/// ```
+pub async fn measure_latency<F: Future>(operation: Operation, future: F) -> F::Output {
+	let start_time = Instant::now();
+	let output = future.await;
+	record_latency(operation, start_time.elapsed());
+	output
+}
+
+/// Removes the latencies recorded so far.
+pub(super) fn take_latencies() -> BTreeMap<Operation, Vec<Duration>> {
+	let mut latencies = LATENCIES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	std::mem::take(&mut *latencies)
+}
+
+/// The latency percentiles of an operation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+	pub operation: Operation,
+	pub count: usize,
+	pub p50_milli: u128,
+	pub p95_milli: u128,
+	pub p99_milli: u128,
+	pub max_milli: u128,
+}
+
+impl LatencyStats {
+	pub fn new(operation: Operation, mut latencies: Vec<Duration>) -> Self {
+		latencies.sort_unstable();
+		LatencyStats {
+			operation,
+			count: latencies.len(),
+			p50_milli: percentile(&latencies, 50.0).as_millis(),
+			p95_milli: percentile(&latencies, 95.0).as_millis(),
+			p99_milli: percentile(&latencies, 99.0).as_millis(),
+			max_milli: latencies.last().copied().unwrap_or_default().as_millis(),
+		}
+	}
+
+	/// The stats of the latencies recorded for each operation.
+	pub(super) fn from_latencies(latencies: BTreeMap<Operation, Vec<Duration>>) -> Vec<Self> {
+		latencies
+			.into_iter()
+			.map(|(operation, latencies)| LatencyStats::new(operation, latencies))
+			.collect()
+	}
+}
+
+/// The nearest-rank percentile of the sorted latencies, 0 if there are none.
+fn percentile(sorted_latencies: &[Duration], percentile: f64) -> Duration {
+	if sorted_latencies.is_empty() {
+		return Duration::ZERO;
+	}
+	let rank = (percentile / 100.0 * sorted_latencies.len() as f64).ceil() as usize;
+	sorted_latencies[rank.clamp(1, sorted_latencies.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_latency_percentiles() {
+		let latencies = (1..=200).rev().map(Duration::from_millis).collect();
+		let stats = LatencyStats::new(Operation::Submit, latencies);
+		assert_eq!(
+			stats,
+			LatencyStats {
+				operation: Operation::Submit,
+				count: 200,
+				p50_milli: 100,
+				p95_milli: 190,
+				p99_milli: 198,
+				max_milli: 200,
+			}
+		);
+
+		let stats = LatencyStats::new(Operation::Fund, vec![Duration::from_millis(7)]);
+		assert_eq!((stats.p50_milli, stats.p99_milli), (7, 7));
+		assert_eq!(LatencyStats::new(Operation::Fund, vec![]).p50_milli, 0);
+	}
+}
@@ -5,8 +5,20 @@ use std::time::Duration;
 use std::{fs::File, sync::Arc};
 use tracing_subscriber::{filter, prelude::*};
 
+mod account_pool;
+mod criteria;
+mod distributed;
+mod latency;
+mod profile;
+mod results;
 mod scenario;
-pub use scenario::Scenario;
+pub use account_pool::{AccountPool, PooledAccount};
+pub use criteria::SuccessCriteria;
+pub use distributed::{assign_scenarios, run_coordinator, CoordinatorConfig, WorkerAssignment};
+pub use latency::{measure_latency, record_latency, LatencyStats, Operation};
+pub use profile::{LoadProfile, TpsController};
+pub use results::{ScenarioTypeStats, TestResults};
+pub use scenario::{Scenario, ScenarioMix};
 
 const EXEC_LOG_FILTER: &str = "exec";
 
@@ -60,8 +72,18 @@ pub struct ExecutionConfig {
 	pub logfile: String,
 	/// The path to the file where execution data are written to be processed later.
 	pub execfile: String,
+	/// The path to the file where the results of the test are written at the end of the test: CSV if the file ends with .csv, JSON otherwise.
+	pub resultfile: String,
 	/// The number of started scenarios per client. number_scenarios / number_scenario_per_client defines the number of clients.
+	/// Not used by the profile test, whose scenarios are all run by the same client.
 	pub number_scenario_per_client: usize,
+	/// The criteria the results must meet for the test to pass.
+	pub criteria: SuccessCriteria,
+	/// The id of the first scenario. The workers of a distributed test run different ranges of ids.
+	pub first_scenario_id: usize,
+	/// The URL of the coordinator of a distributed test. When set, the test is run as a worker:
+	/// its scenarios and start time are assigned by the coordinator, which aggregates the results.
+	pub coordinator_url: Option<String>,
 }
 
 impl ExecutionConfig {
@@ -80,6 +102,11 @@ impl ExecutionConfig {
 					"Number of min running scenario less than the number of scenario per client."
 				);
 			}
+			TestKind::Profile { min_scenarios, max_scenarios, adjust_interval, .. } => {
+				assert!(min_scenarios > 0, "min scenarios is 0");
+				assert!(max_scenarios >= min_scenarios, "max scenarios less than min scenarios");
+				assert!(!adjust_interval.is_zero(), "adjust interval is 0");
+			}
 		}
 	}
 }
@@ -99,13 +126,17 @@ impl Default for ExecutionConfig {
 			kind: TestKind::build_load_test(number_scenarios),
 			logfile: "log_file.txt".to_string(),
 			execfile: "test_result.txt".to_string(),
+			resultfile: "test_summary.json".to_string(),
 			number_scenario_per_client,
+			criteria: SuccessCriteria::from_env(),
+			first_scenario_id: 1,
+			coordinator_url: std::env::var("LOADTEST_COORDINATOR_URL").ok(),
 		}
 	}
 }
 
 /// Define the type of test to run:
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum TestKind {
 	/// Load: try to run all scenario (number_scenarios) concurrently
 	Load { number_scenarios: usize },
@@ -116,6 +147,14 @@ pub enum TestKind {
 		duration: std::time::Duration,
 		number_cycle: u32,
 	},
+	/// Profile: run the number of scenarios, between min_scenarios and max_scenarios, needed to follow the scenario runs per second of the profile.
+	/// The number of scenarios is adjusted every adjust_interval from the measured scenario runs per second.
+	Profile {
+		profile: LoadProfile,
+		min_scenarios: usize,
+		max_scenarios: usize,
+		adjust_interval: std::time::Duration,
+	},
 }
 
 impl TestKind {
@@ -130,38 +169,91 @@ impl TestKind {
 	) -> Self {
 		TestKind::Soak { min_scenarios, max_scenarios, duration, number_cycle }
 	}
+	pub fn build_profile_test(
+		profile: LoadProfile,
+		min_scenarios: usize,
+		max_scenarios: usize,
+		adjust_interval: std::time::Duration,
+	) -> Self {
+		TestKind::Profile { profile, min_scenarios, max_scenarios, adjust_interval }
+	}
 }
 
 /// Execute the test scenarios defined in the specified configuration.
 /// scenarios are executed by chunk. Each chunk of execution is done by a client.
 /// All clients are executed in a different thread in parallel.
 /// Clients execute scenarios in a Tokio runtime concurrently.
-pub fn execute_test(config: ExecutionConfig, create_scenario: Arc<scenario::CreateScenarioFn>) {
-	tracing::info!("Start test scenario execution.");
+/// The profile test is run by a single client, in a multi-thread Tokio runtime.
+/// The results of the test are returned and written to the result file, with the success criteria
+/// they don't meet. Use TestResults::exit_code to fail the test process.
+pub fn execute_test(
+	config: ExecutionConfig,
+	create_scenario: Arc<scenario::CreateScenarioFn>,
+) -> TestResults {
+	execute_mix_test(config, create_scenario.into())
+}
 
-	let number_scenarios = match config.kind {
-		TestKind::Load { number_scenarios } => number_scenarios,
-		TestKind::Soak { max_scenarios, .. } => max_scenarios,
+/// Execute the test like execute_test, with the scenarios created by type from the mix.
+/// The results are detailed by scenario type.
+pub fn execute_mix_test(config: ExecutionConfig, mix: ScenarioMix) -> TestResults {
+	if let Some(coordinator_url) = config.coordinator_url.clone() {
+		return distributed::run_worker(coordinator_url, config, mix);
+	}
+	assert!(mix.total_weight() > 0, "no scenario type with a weight in the scenario mix");
+	let mix = Arc::new(mix);
+	let create_scenario: Arc<scenario::CreateScenarioFn> = {
+		let mix = mix.clone();
+		Arc::new(move |id| mix.create_scenario(id))
 	};
 
-	//build chunk of ids. Start at 1. 0 mean in result execution fail before scenario can execute.
-	let ids: Vec<_> = (1..=number_scenarios).collect();
-	let chunks: Vec<_> = ids
-		.into_iter()
-		.chunks(config.number_scenario_per_client)
-		.into_iter()
-		.map(|chunk| {
-			(config.kind.clone(), chunk.into_iter().collect::<Vec<_>>(), create_scenario.clone())
-		})
-		.collect();
-	// Execute the client by id's chunk.
-	let exec_results: Vec<_> = chunks
-		.into_par_iter()
-		.map(|(kind, chunk, create_scenario)| {
-			let client = TestClient::new(chunk);
-			client.run_scenarios(kind.clone(), create_scenario.clone())
-		})
-		.collect();
+	tracing::info!("Start test scenario execution.");
+	let start_time = std::time::Instant::now();
+	// Drop the latencies recorded by a previous test.
+	latency::take_latencies();
+	latency::start_progress();
+
+	let resultfile = config.resultfile.clone();
+	let exec_results = match config.kind {
+		TestKind::Load { number_scenarios } => {
+			execute_clients(&config, number_scenarios, create_scenario)
+		}
+		TestKind::Soak { max_scenarios, .. } => {
+			execute_clients(&config, max_scenarios, create_scenario)
+		}
+		TestKind::Profile { profile, min_scenarios, max_scenarios, adjust_interval } => {
+			vec![profile::run_profile_test(
+				profile,
+				TpsController::new(min_scenarios, max_scenarios),
+				adjust_interval,
+				create_scenario,
+			)]
+		}
+	};
+
+	let longest_stall = latency::longest_stall();
+	let scenarios = exec_results.iter().map(|res| res.scenarios.len()).sum();
+	let failed_scenarios = exec_results
+		.iter()
+		.flat_map(|res| res.scenarios.iter())
+		.filter(|scenario| !scenario.is_ok())
+		.count();
+
+	let scenario_types = ScenarioTypeStats::from_metrics(
+		&mix,
+		exec_results.iter().flat_map(|res| res.scenarios.iter()),
+	);
+	for stats in &scenario_types {
+		let metrics_scenario_type = serde_json::to_string(stats)
+			.unwrap_or("Metric scenario type serialization error.".to_string());
+		tracing::info!(target:EXEC_LOG_FILTER, metrics_scenario_type);
+		tracing::info!(
+			"Scenario type {} scenarios:{} failed:{} average_exec_time:{}",
+			stats.name,
+			stats.scenarios,
+			stats.failed,
+			stats.average_execution_time_milli
+		);
+	}
 
 	let no_zero_exec_time: Vec<_> = exec_results
 		.into_iter()
@@ -182,7 +274,82 @@ pub fn execute_test(config: ExecutionConfig, create_scenario: Arc<scenario::Crea
 	tracing::info!(target:EXEC_LOG_FILTER, metrics_average_exec_time);
 	tracing::info!("Scenarios execution average_exec_time:{average_exec_time}");
 
-	tracing::info!("End test scenario execution.");
+	let operations = LatencyStats::from_latencies(latency::take_latencies());
+	for stats in &operations {
+		let metrics_latency = serde_json::to_string(stats)
+			.unwrap_or("Metric latency serialization error.".to_string());
+		tracing::info!(target:EXEC_LOG_FILTER, metrics_latency);
+		tracing::info!(
+			"Operation {} count:{} p50:{}ms p95:{}ms p99:{}ms",
+			stats.operation,
+			stats.count,
+			stats.p50_milli,
+			stats.p95_milli,
+			stats.p99_milli
+		);
+	}
+	let results = TestResults {
+		duration_milli: start_time.elapsed().as_millis(),
+		average_execution_time_milli: average_exec_time,
+		scenarios,
+		failed_scenarios,
+		longest_stall_milli: longest_stall.as_millis(),
+		scenario_types,
+		operations,
+		violations: vec![],
+	};
+	let results = finish_test(&resultfile, &config.criteria, results);
+	tracing::info!("End test scenario execution. Passed:{}", results.passed());
+	results
+}
+
+/// Checks the results against the success criteria then writes them to the result file.
+fn finish_test(
+	resultfile: &str,
+	criteria: &SuccessCriteria,
+	mut results: TestResults,
+) -> TestResults {
+	results.violations = criteria.violations(&results);
+	for violation in &results.violations {
+		tracing::info!(target:EXEC_LOG_FILTER, violation);
+		tracing::warn!("Test success criterion not met: {violation}");
+	}
+	if let Err(err) = results.write(resultfile) {
+		tracing::warn!("Test results fail to be written to {resultfile} because: {err}");
+	}
+	results
+}
+
+/// Execute number_scenarios scenarios by chunk of number_scenario_per_client, each chunk by a client.
+fn execute_clients(
+	config: &ExecutionConfig,
+	number_scenarios: usize,
+	create_scenario: Arc<scenario::CreateScenarioFn>,
+) -> Vec<ClientExecResult> {
+	//build chunk of ids. Start at 1. 0 mean in result execution fail before scenario can execute.
+	let first_id = config.first_scenario_id.max(1);
+	let ids: Vec<_> = (first_id..first_id + number_scenarios).collect();
+	let chunks: Vec<_> = ids
+		.into_iter()
+		.chunks(config.number_scenario_per_client)
+		.into_iter()
+		.map(|chunk| {
+			(
+				config.kind.clone(),
+				chunk.into_iter().collect::<Vec<_>>(),
+				create_scenario.clone(),
+				first_id,
+			)
+		})
+		.collect();
+	// Execute the client by id's chunk.
+	chunks
+		.into_par_iter()
+		.map(|(kind, chunk, create_scenario, first_id)| {
+			let client = TestClient::new(chunk);
+			client.run_scenarios(kind.clone(), create_scenario.clone(), first_id)
+		})
+		.collect()
 }
 
 /// Runs the specified scenarios concurrently using Tokio.
@@ -200,6 +367,7 @@ impl TestClient {
 		self,
 		kind: TestKind,
 		create_scanario: Arc<scenario::CreateScenarioFn>,
+		first_id: usize,
 	) -> ClientExecResult {
 		// Start the Tokio runtime on the current thread
 		let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
@@ -211,8 +379,10 @@ impl TestClient {
 			TestKind::Soak { min_scenarios, max_scenarios, duration, number_cycle } => {
 				// The scenario that run all the time and part time are divided using the client.
 				// min_scenarios first ids are run permanently, the others client run part time.
-				//ids start at 1.
-				if *self.scenario_chunk.last().unwrap_or(&min_scenarios) <= min_scenarios {
+				//ids start at first_id.
+				let last_index =
+					self.scenario_chunk.last().map_or(min_scenarios, |id| id + 1 - first_id);
+				if last_index <= min_scenarios {
 					// Start scenarios that run all the time.
 					rt.block_on(self.soak_runner_in_a_loop(create_scanario.clone(), duration))
 				} else {
@@ -229,13 +399,10 @@ impl TestClient {
 					vec![]
 				}
 			}
+			TestKind::Profile { .. } => unreachable!("the profile test is not run by chunk"),
 		};
 
-		let exec_results = ClientExecResult::new(scenario_results);
-		let metrics_client_execution = serde_json::to_string(&exec_results)
-			.unwrap_or("Metric client result serialization error.".to_string());
-		tracing::info!(target:EXEC_LOG_FILTER, metrics_client_execution);
-		exec_results
+		ClientExecResult::log_new(scenario_results)
 	}
 
 	async fn load_runner(
@@ -251,6 +418,7 @@ impl TestClient {
 		});
 		let mut scenario_results = vec![];
 		while let Some(res) = set.join_next().await {
+			latency::record_progress();
 			let elapse = start_time.elapsed().as_millis();
 			let metrics = match res {
 				Ok((id, Ok(()))) => ScenarioExecMetric::new(id, elapse, ScenarioExecResult::Ok),
@@ -292,30 +460,38 @@ impl TestClient {
 
 		let mut scenario_results = vec![];
 		while let Some(res) = set.join_next().await {
-			let metrics = match res {
-				Ok((id, Ok(elapse))) => ScenarioExecMetric::new(id, elapse, ScenarioExecResult::Ok),
-				Ok((id, Err(err))) => {
-					let log = format!("Scenario:{id} execution failed because: {err}");
-					tracing::info!(target:EXEC_LOG_FILTER, log);
-					tracing::warn!(log);
-					let elapse = initial_start_time.elapsed().as_millis();
-					ScenarioExecMetric::new(id, elapse, ScenarioExecResult::Fail)
-				}
-				Err(err) => {
-					tracing::warn!("Error during scenario spawning: {err}");
-					let elapse = initial_start_time.elapsed().as_millis();
-					ScenarioExecMetric::new(0, elapse, ScenarioExecResult::Fail)
-				}
-			};
-			let metrics_scenario = serde_json::to_string(&metrics)
-				.unwrap_or("Metric serialization error.".to_string());
-			tracing::info!(target:EXEC_LOG_FILTER, metrics_scenario);
-			scenario_results.push(metrics);
+			scenario_results.push(loop_exec_metric(res, initial_start_time));
 		}
 		scenario_results
 	}
 }
 
+/// Converts the result of a scenario run in a loop to its metric and logs it.
+fn loop_exec_metric(
+	res: Result<(usize, Result<u128, anyhow::Error>), tokio::task::JoinError>,
+	initial_start_time: std::time::Instant,
+) -> ScenarioExecMetric {
+	let metrics = match res {
+		Ok((id, Ok(elapse))) => ScenarioExecMetric::new(id, elapse, ScenarioExecResult::Ok),
+		Ok((id, Err(err))) => {
+			let log = format!("Scenario:{id} execution failed because: {err}");
+			tracing::info!(target:EXEC_LOG_FILTER, log);
+			tracing::warn!(log);
+			let elapse = initial_start_time.elapsed().as_millis();
+			ScenarioExecMetric::new(id, elapse, ScenarioExecResult::Fail)
+		}
+		Err(err) => {
+			tracing::warn!("Error during scenario spawning: {err}");
+			let elapse = initial_start_time.elapsed().as_millis();
+			ScenarioExecMetric::new(0, elapse, ScenarioExecResult::Fail)
+		}
+	};
+	let metrics_scenario =
+		serde_json::to_string(&metrics).unwrap_or("Metric serialization error.".to_string());
+	tracing::info!(target:EXEC_LOG_FILTER, metrics_scenario);
+	metrics
+}
+
 async fn run_scenarion_in_loop(
 	id: usize,
 	create_scanario: Arc<scenario::CreateScenarioFn>,
@@ -333,6 +509,7 @@ async fn run_scenarion_in_loop(
 		let exec_start_time = std::time::Instant::now();
 		let scenario = create_scanario(id);
 		scenario.run().await?;
+		latency::record_progress();
 		let exec_elapse = exec_start_time.elapsed().as_millis();
 		if average_time == 0 {
 			average_time = exec_elapse;
@@ -344,7 +521,7 @@ async fn run_scenarion_in_loop(
 	Ok(average_time)
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug)]
 struct ScenarioExecMetric {
 	scenario_id: usize,
 	elapse_millli: u128,
@@ -364,7 +541,7 @@ impl ScenarioExecMetric {
 	}
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug)]
 enum ScenarioExecResult {
 	Ok,
 	Fail,
@@ -373,15 +550,28 @@ enum ScenarioExecResult {
 #[derive(Serialize, Deserialize, Debug)]
 struct ClientExecResult {
 	average_execution_time_milli: u128,
+	/// The metrics of the client's scenarios, summarized by scenario type at the end of the test.
+	#[serde(skip)]
+	scenarios: Vec<ScenarioExecMetric>,
 }
 
 impl ClientExecResult {
 	fn new(scenarios: Vec<ScenarioExecMetric>) -> Self {
 		ClientExecResult {
 			average_execution_time_milli: Self::calculate_average_exec_time_milli(&scenarios),
+			scenarios,
 		}
 	}
 
+	/// Builds the result of a client and logs it.
+	fn log_new(scenarios: Vec<ScenarioExecMetric>) -> Self {
+		let exec_results = ClientExecResult::new(scenarios);
+		let metrics_client_execution = serde_json::to_string(&exec_results)
+			.unwrap_or("Metric client result serialization error.".to_string());
+		tracing::info!(target:EXEC_LOG_FILTER, metrics_client_execution);
+		exec_results
+	}
+
 	pub fn calculate_average_exec_time_milli(scenarios: &[ScenarioExecMetric]) -> u128 {
 		let ok_scenario: Vec<_> = scenarios
 			.into_iter()
@@ -0,0 +1,321 @@
+use super::{latency, loop_exec_metric, scenario, ClientExecResult, EXEC_LOG_FILTER};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Shape of the traffic of a profile test: the number of scenario runs per second targeted over
+/// the time of the test.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LoadProfile {
+	/// The target goes linearly from `from_tps` to `to_tps` during `duration`.
+	LinearRamp { from_tps: f64, to_tps: f64, duration: Duration },
+	/// The target starts at `start_tps` and rises by `step_tps` every `step_duration`, for
+	/// `steps` steps.
+	Step { start_tps: f64, step_tps: f64, step_duration: Duration, steps: u32 },
+	/// The target is `base_tps`, except for `spike_duration` in the middle of `duration` where it
+	/// is `spike_tps`.
+	Spike { base_tps: f64, spike_tps: f64, duration: Duration, spike_duration: Duration },
+	/// The target is held at `tps` during `duration`, usually hours.
+	Soak { tps: f64, duration: Duration },
+}
+
+impl LoadProfile {
+	/// A soak profile holding `tps` during `hours`.
+	pub fn soak_hours(tps: f64, hours: f64) -> Self {
+		LoadProfile::Soak { tps, duration: Duration::from_secs_f64(hours * 3600.0) }
+	}
+
+	/// The duration of the test.
+	pub fn duration(&self) -> Duration {
+		match self {
+			LoadProfile::LinearRamp { duration, .. }
+			| LoadProfile::Spike { duration, .. }
+			| LoadProfile::Soak { duration, .. } => *duration,
+			LoadProfile::Step { step_duration, steps, .. } => *step_duration * *steps,
+		}
+	}
+
+	/// The target scenario runs per second once `elapsed` of the test has passed.
+	pub fn target_tps(&self, elapsed: Duration) -> f64 {
+		let elapsed = elapsed.min(self.duration());
+		match self {
+			LoadProfile::LinearRamp { from_tps, to_tps, duration } => {
+				if duration.is_zero() {
+					return *to_tps;
+				}
+				let progress = elapsed.as_secs_f64() / duration.as_secs_f64();
+				from_tps + (to_tps - from_tps) * progress
+			}
+			LoadProfile::Step { start_tps, step_tps, step_duration, steps } => {
+				let step = if step_duration.is_zero() {
+					0
+				} else {
+					// the last step lasts until the end of the test
+					((elapsed.as_secs_f64() / step_duration.as_secs_f64()) as u32)
+						.min(steps.saturating_sub(1))
+				};
+				start_tps + step_tps * step as f64
+			}
+			LoadProfile::Spike { base_tps, spike_tps, duration, spike_duration } => {
+				let spike_start = duration.saturating_sub(*spike_duration) / 2;
+				if elapsed >= spike_start && elapsed < spike_start + *spike_duration {
+					*spike_tps
+				} else {
+					*base_tps
+				}
+			}
+			LoadProfile::Soak { tps, .. } => *tps,
+		}
+	}
+}
+
+/// Adjusts the number of concurrently running scenarios for the measured scenario runs per second
+/// to reach the target of the profile.
+///
+/// The number of scenarios is scaled by the ratio between the target and the measured throughput
+/// of a scenario, at most doubled or halved at each adjustment so that a noisy measure does not
+/// make the load swing, and kept between `min_scenarios` and `max_scenarios`.
+#[derive(Clone, Debug)]
+pub struct TpsController {
+	min_scenarios: usize,
+	max_scenarios: usize,
+	scenarios: usize,
+}
+
+impl TpsController {
+	pub fn new(min_scenarios: usize, max_scenarios: usize) -> Self {
+		let min_scenarios = min_scenarios.max(1);
+		TpsController {
+			min_scenarios,
+			max_scenarios: max_scenarios.max(min_scenarios),
+			scenarios: min_scenarios,
+		}
+	}
+
+	/// The number of scenarios to run.
+	pub fn scenarios(&self) -> usize {
+		self.scenarios
+	}
+
+	/// Computes the number of scenarios to run for the target, given the scenario runs per second
+	/// measured with the current number of scenarios.
+	pub fn adjust(&mut self, target_tps: f64, measured_tps: f64) -> usize {
+		let desired = if target_tps <= 0.0 {
+			self.min_scenarios
+		} else if measured_tps <= 0.0 {
+			// no scenario run completed yet, the scenarios may take longer than an adjustment
+			self.scenarios * 2
+		} else {
+			let tps_per_scenario = measured_tps / self.scenarios as f64;
+			(target_tps / tps_per_scenario).ceil() as usize
+		};
+		self.scenarios = desired
+			.clamp((self.scenarios / 2).max(1), self.scenarios * 2)
+			.clamp(self.min_scenarios, self.max_scenarios);
+		self.scenarios
+	}
+}
+
+/// The adjustment of the number of scenarios, logged in the execution file.
+#[derive(Serialize)]
+struct ProfileAdjustMetric {
+	elapse_millli: u128,
+	target_tps: f64,
+	measured_tps: f64,
+	scenarios: usize,
+}
+
+/// A scenario run in a loop by the profile test.
+struct ProfileWorker {
+	/// Set to end the loop once the current scenario run is done.
+	stop: Arc<AtomicBool>,
+	/// Unset when the loop has ended, because it has been stopped or a scenario run failed.
+	running: Arc<AtomicBool>,
+}
+
+impl ProfileWorker {
+	fn is_active(&self) -> bool {
+		self.running.load(Ordering::Relaxed) && !self.stop.load(Ordering::Relaxed)
+	}
+}
+
+/// Runs the profile test: scenarios are started and stopped every `adjust_interval` so that the
+/// number of completed scenario runs per second follows the target of the profile.
+pub(super) fn run_profile_test(
+	profile: LoadProfile,
+	mut controller: TpsController,
+	adjust_interval: Duration,
+	create_scenario: Arc<scenario::CreateScenarioFn>,
+) -> ClientExecResult {
+	let rt = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+		Ok(rt) => rt,
+		Err(err) => panic!("Tokio RT runtime fail to start because of this error:{err}"),
+	};
+	let scenario_results = rt.block_on(async move {
+		let initial_start_time = std::time::Instant::now();
+		let completed = Arc::new(AtomicU64::new(0));
+		let mut workers: Vec<ProfileWorker> = vec![];
+		let mut set = tokio::task::JoinSet::new();
+		//ids start at 1.
+		let mut next_id = 1;
+
+		let mut interval = tokio::time::interval(adjust_interval);
+		interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+		// the first tick completes immediately
+		interval.tick().await;
+		let mut last_completed = 0;
+		let mut last_adjust_time = std::time::Instant::now();
+		loop {
+			// Start or stop scenarios to reach the number set by the controller.
+			workers.retain(ProfileWorker::is_active);
+			while workers.len() < controller.scenarios() {
+				let id = next_id;
+				next_id += 1;
+				let stop = Arc::new(AtomicBool::new(false));
+				let running = Arc::new(AtomicBool::new(true));
+				let scenario_loop = run_scenario_until_stopped(
+					id,
+					create_scenario.clone(),
+					stop.clone(),
+					completed.clone(),
+				);
+				let worker_running = running.clone();
+				set.spawn(async move {
+					let res = scenario_loop.await;
+					worker_running.store(false, Ordering::Relaxed);
+					(id, res)
+				});
+				workers.push(ProfileWorker { stop, running });
+			}
+			while workers.len() > controller.scenarios() {
+				if let Some(worker) = workers.pop() {
+					worker.stop.store(true, Ordering::Relaxed);
+				}
+			}
+
+			interval.tick().await;
+			let elapsed = initial_start_time.elapsed();
+			if elapsed >= profile.duration() {
+				break;
+			}
+			let now_completed = completed.load(Ordering::Relaxed);
+			let measured_tps =
+				(now_completed - last_completed) as f64 / last_adjust_time.elapsed().as_secs_f64();
+			last_completed = now_completed;
+			last_adjust_time = std::time::Instant::now();
+
+			let target_tps = profile.target_tps(elapsed);
+			let scenarios = controller.adjust(target_tps, measured_tps);
+			let metrics = ProfileAdjustMetric {
+				elapse_millli: elapsed.as_millis(),
+				target_tps,
+				measured_tps,
+				scenarios,
+			};
+			let metrics_load_profile = serde_json::to_string(&metrics)
+				.unwrap_or("Metric serialization error.".to_string());
+			tracing::info!(target:EXEC_LOG_FILTER, metrics_load_profile);
+			tracing::info!(
+				"Load profile target_tps:{target_tps:.2} measured_tps:{measured_tps:.2} scenarios:{scenarios}"
+			);
+		}
+
+		// Let the running scenarios end their current run.
+		workers.iter().for_each(|worker| worker.stop.store(true, Ordering::Relaxed));
+		let mut scenario_results = vec![];
+		while let Some(res) = set.join_next().await {
+			scenario_results.push(loop_exec_metric(res, initial_start_time));
+		}
+		scenario_results
+	});
+
+	ClientExecResult::log_new(scenario_results)
+}
+
+/// Runs the scenario in a loop until stopped, counting the completed runs.
+/// Returns the average execution time of the runs.
+async fn run_scenario_until_stopped(
+	id: usize,
+	create_scanario: Arc<scenario::CreateScenarioFn>,
+	stop: Arc<AtomicBool>,
+	completed: Arc<AtomicU64>,
+) -> Result<u128, anyhow::Error> {
+	let mut average_time = 0;
+	while !stop.load(Ordering::Relaxed) {
+		tracing::info!("{id} start new test");
+		let exec_start_time = std::time::Instant::now();
+		let scenario = create_scanario(id);
+		scenario.run().await?;
+		latency::record_progress();
+		completed.fetch_add(1, Ordering::Relaxed);
+		let exec_elapse = exec_start_time.elapsed().as_millis();
+		if average_time == 0 {
+			average_time = exec_elapse;
+		} else {
+			average_time = (exec_elapse + average_time) / 2;
+		}
+		tracing::info!("{id} end test exec_elapse:{exec_elapse} average_time:{average_time}");
+	}
+	Ok(average_time)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_profile_targets() {
+		let ramp = LoadProfile::LinearRamp {
+			from_tps: 10.0,
+			to_tps: 110.0,
+			duration: Duration::from_secs(100),
+		};
+		assert_eq!(ramp.target_tps(Duration::from_secs(0)), 10.0);
+		assert_eq!(ramp.target_tps(Duration::from_secs(50)), 60.0);
+		assert_eq!(ramp.target_tps(Duration::from_secs(500)), 110.0);
+
+		let step = LoadProfile::Step {
+			start_tps: 10.0,
+			step_tps: 5.0,
+			step_duration: Duration::from_secs(60),
+			steps: 3,
+		};
+		assert_eq!(step.duration(), Duration::from_secs(180));
+		assert_eq!(step.target_tps(Duration::from_secs(59)), 10.0);
+		assert_eq!(step.target_tps(Duration::from_secs(179)), 20.0);
+
+		let spike = LoadProfile::Spike {
+			base_tps: 10.0,
+			spike_tps: 100.0,
+			duration: Duration::from_secs(100),
+			spike_duration: Duration::from_secs(20),
+		};
+		assert_eq!(spike.target_tps(Duration::from_secs(39)), 10.0);
+		assert_eq!(spike.target_tps(Duration::from_secs(40)), 100.0);
+		assert_eq!(spike.target_tps(Duration::from_secs(60)), 10.0);
+
+		assert_eq!(LoadProfile::soak_hours(50.0, 2.0).duration(), Duration::from_secs(7200));
+	}
+
+	#[test]
+	fn test_controller_converges_on_the_target() {
+		let mut controller = TpsController::new(2, 50);
+		// nothing measured yet: grow
+		assert_eq!(controller.adjust(20.0, 0.0), 4);
+		// each scenario runs once per second: 20 scenarios are needed, doubled at most
+		assert_eq!(controller.adjust(20.0, 4.0), 8);
+		assert_eq!(controller.adjust(20.0, 8.0), 16);
+		assert_eq!(controller.adjust(20.0, 16.0), 20);
+		assert_eq!(controller.adjust(20.0, 20.0), 20);
+		// the target drops: halved at most, never under the min
+		assert_eq!(controller.adjust(1.0, 20.0), 10);
+		assert_eq!(controller.adjust(0.0, 10.0), 5);
+		assert_eq!(controller.adjust(0.0, 5.0), 2);
+		// never over the max
+		assert_eq!(controller.adjust(1000.0, 2.0), 4);
+		let mut controller = TpsController::new(2, 5);
+		assert_eq!(controller.adjust(1000.0, 2.0), 4);
+		assert_eq!(controller.adjust(1000.0, 4.0), 5);
+	}
+}
@@ -0,0 +1,276 @@
+use super::latency::{LatencyStats, Operation};
+use super::{ScenarioExecMetric, ScenarioMix};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// The results of a test, written to the result file at the end of the test so that they can be
+/// tracked over the runs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TestResults {
+	/// The duration of the whole test.
+	pub duration_milli: u128,
+	/// The average execution time of the scenarios which succeeded, over the clients.
+	pub average_execution_time_milli: u128,
+	/// The number of scenarios which were executed.
+	pub scenarios: usize,
+	pub failed_scenarios: usize,
+	/// The longest time without any operation or scenario completing.
+	pub longest_stall_milli: u128,
+	/// The results of each type of scenario of the mix.
+	pub scenario_types: Vec<ScenarioTypeStats>,
+	/// The latency percentiles of the operations run by the scenarios.
+	pub operations: Vec<LatencyStats>,
+	/// The success criteria of the test the results don't meet.
+	pub violations: Vec<String>,
+}
+
+impl TestResults {
+	/// Aggregates the results of the workers of a distributed test. The latency percentiles of an
+	/// operation are the highest of the workers': no more than 1% of all the latencies are above
+	/// the highest p99 of the workers, so they are upper bounds of the percentiles over all the
+	/// latencies. The success criteria are checked again on the aggregated results.
+	pub fn aggregate(results: &[TestResults]) -> Self {
+		let mut scenario_types: Vec<ScenarioTypeStats> = vec![];
+		let mut operations: BTreeMap<Operation, LatencyStats> = BTreeMap::new();
+		for worker in results {
+			for stats in &worker.scenario_types {
+				match scenario_types.iter_mut().find(|types| types.name == stats.name) {
+					Some(types) => types.merge(stats),
+					None => scenario_types.push(stats.clone()),
+				}
+			}
+			for stats in &worker.operations {
+				operations
+					.entry(stats.operation)
+					.and_modify(|operation| {
+						operation.count += stats.count;
+						operation.p50_milli = operation.p50_milli.max(stats.p50_milli);
+						operation.p95_milli = operation.p95_milli.max(stats.p95_milli);
+						operation.p99_milli = operation.p99_milli.max(stats.p99_milli);
+						operation.max_milli = operation.max_milli.max(stats.max_milli);
+					})
+					.or_insert_with(|| stats.clone());
+			}
+		}
+		let ok_scenarios =
+			|results: &TestResults| (results.scenarios - results.failed_scenarios) as u128;
+		let total_exec_time: u128 = results
+			.iter()
+			.map(|res| res.average_execution_time_milli * ok_scenarios(res))
+			.sum();
+		TestResults {
+			duration_milli: results.iter().map(|res| res.duration_milli).max().unwrap_or(0),
+			average_execution_time_milli: total_exec_time
+				.checked_div(results.iter().map(ok_scenarios).sum())
+				.unwrap_or(0),
+			scenarios: results.iter().map(|res| res.scenarios).sum(),
+			failed_scenarios: results.iter().map(|res| res.failed_scenarios).sum(),
+			longest_stall_milli: results
+				.iter()
+				.map(|res| res.longest_stall_milli)
+				.max()
+				.unwrap_or(0),
+			scenario_types,
+			operations: operations.into_values().collect(),
+			violations: vec![],
+		}
+	}
+
+	/// The ratio of failed scenarios, 0 if no scenario was executed.
+	pub fn error_rate(&self) -> f64 {
+		if self.scenarios == 0 {
+			return 0.0;
+		}
+		self.failed_scenarios as f64 / self.scenarios as f64
+	}
+
+	/// Whether the results meet the success criteria of the test.
+	pub fn passed(&self) -> bool {
+		self.violations.is_empty()
+	}
+
+	/// The exit code of the test process: non-zero when the success criteria are not met.
+	pub fn exit_code(&self) -> i32 {
+		if self.passed() {
+			0
+		} else {
+			1
+		}
+	}
+
+	/// The latencies of the operations, one line per operation. The scenario types are only in
+	/// the JSON results.
+	pub fn to_csv(&self) -> String {
+		let mut csv = "operation,count,p50_milli,p95_milli,p99_milli,max_milli\n".to_string();
+		for stats in &self.operations {
+			// writing to a String does not fail
+			let _ = writeln!(
+				csv,
+				"{},{},{},{},{},{}",
+				stats.operation,
+				stats.count,
+				stats.p50_milli,
+				stats.p95_milli,
+				stats.p99_milli,
+				stats.max_milli
+			);
+		}
+		csv
+	}
+
+	/// Writes the results as CSV if the file has the csv extension, as JSON otherwise.
+	pub fn write(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+		let path = path.as_ref();
+		let contents = if path.extension().is_some_and(|extension| extension == "csv") {
+			self.to_csv()
+		} else {
+			serde_json::to_string_pretty(self)?
+		};
+		std::fs::write(path, contents)?;
+		Ok(())
+	}
+}
+
+/// The results of a type of scenario of the mix.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioTypeStats {
+	pub name: String,
+	pub weight: u32,
+	/// The number of scenarios of the type which were executed.
+	pub scenarios: usize,
+	pub failed: usize,
+	/// The average execution time of the scenarios of the type which succeeded.
+	pub average_execution_time_milli: u128,
+}
+
+impl ScenarioTypeStats {
+	/// Summarizes the metrics of the scenarios by type. The scenarios which failed before they
+	/// could execute have no type.
+	pub(super) fn from_metrics<'a>(
+		mix: &ScenarioMix,
+		scenarios: impl Iterator<Item = &'a ScenarioExecMetric>,
+	) -> Vec<Self> {
+		let mut stats: Vec<_> = mix
+			.types()
+			.map(|(name, weight)| ScenarioTypeStats {
+				name: name.to_string(),
+				weight,
+				scenarios: 0,
+				failed: 0,
+				average_execution_time_milli: 0,
+			})
+			.collect();
+		let mut total_exec_time = vec![0; stats.len()];
+		for scenario in scenarios {
+			let Some(index) = mix.type_index(scenario.scenario_id) else {
+				continue;
+			};
+			stats[index].scenarios += 1;
+			if scenario.is_ok() {
+				total_exec_time[index] += scenario.elapse_millli;
+			} else {
+				stats[index].failed += 1;
+			}
+		}
+		for (stats, total_exec_time) in stats.iter_mut().zip(total_exec_time) {
+			let ok_scenarios = (stats.scenarios - stats.failed) as u128;
+			stats.average_execution_time_milli =
+				total_exec_time.checked_div(ok_scenarios).unwrap_or(0);
+		}
+		stats
+	}
+
+	/// Adds the scenarios of the same type run by another worker.
+	fn merge(&mut self, other: &ScenarioTypeStats) {
+		let ok_scenarios = (self.scenarios - self.failed) as u128;
+		let other_ok_scenarios = (other.scenarios - other.failed) as u128;
+		let total_exec_time = self.average_execution_time_milli * ok_scenarios
+			+ other.average_execution_time_milli * other_ok_scenarios;
+		self.scenarios += other.scenarios;
+		self.failed += other.failed;
+		self.average_execution_time_milli =
+			total_exec_time.checked_div(ok_scenarios + other_ok_scenarios).unwrap_or(0);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_results_to_csv() {
+		let results = TestResults {
+			duration_milli: 60_000,
+			average_execution_time_milli: 1_500,
+			scenarios: 10,
+			failed_scenarios: 0,
+			longest_stall_milli: 2_000,
+			scenario_types: vec![],
+			operations: vec![LatencyStats {
+				operation: Operation::WaitForTransaction,
+				count: 10,
+				p50_milli: 400,
+				p95_milli: 900,
+				p99_milli: 950,
+				max_milli: 1000,
+			}],
+			violations: vec![],
+		};
+		assert_eq!(
+			results.to_csv(),
+			"operation,count,p50_milli,p95_milli,p99_milli,max_milli\nwait_for_transaction,10,400,900,950,1000\n"
+		);
+	}
+
+	#[test]
+	fn test_aggregate_worker_results() {
+		let worker = |scenarios, failed, average, p99_milli| TestResults {
+			duration_milli: 60_000 + p99_milli,
+			average_execution_time_milli: average,
+			scenarios,
+			failed_scenarios: failed,
+			longest_stall_milli: p99_milli * 2,
+			scenario_types: vec![ScenarioTypeStats {
+				name: "transfer".to_string(),
+				weight: 1,
+				scenarios,
+				failed,
+				average_execution_time_milli: average,
+			}],
+			operations: vec![LatencyStats {
+				operation: Operation::Submit,
+				count: 100,
+				p50_milli: p99_milli / 2,
+				p95_milli: p99_milli - 10,
+				p99_milli,
+				max_milli: p99_milli + 10,
+			}],
+			violations: vec!["error rate".to_string()],
+		};
+		let results =
+			TestResults::aggregate(&[worker(10, 2, 1_000, 300), worker(6, 0, 2_000, 200)]);
+
+		assert_eq!(results.duration_milli, 60_300);
+		assert_eq!((results.scenarios, results.failed_scenarios), (16, 2));
+		// weighted by the 8 and 6 scenarios which succeeded
+		assert_eq!(results.average_execution_time_milli, 1_428);
+		assert_eq!(results.longest_stall_milli, 600);
+		assert_eq!(results.scenario_types.len(), 1);
+		assert_eq!(results.scenario_types[0].scenarios, 16);
+		assert_eq!(results.scenario_types[0].average_execution_time_milli, 1_428);
+		assert_eq!(
+			results.operations,
+			[LatencyStats {
+				operation: Operation::Submit,
+				count: 200,
+				p50_milli: 150,
+				p95_milli: 290,
+				p99_milli: 300,
+				max_milli: 310,
+			}]
+		);
+		assert!(results.violations.is_empty());
+	}
+}
@@ -1,4 +1,5 @@
 use super::EXEC_LOG_FILTER;
+use std::sync::Arc;
 
 /// A scenario is any struct that implements the Scenario trait.
 /// To ease scenario execution and logs, an id (usize) is provided during creation.
@@ -22,3 +23,118 @@ pub trait Scenario {
 
 /// Type definition that is used by the test executor to create scenario to execute.
 pub type CreateScenarioFn = (dyn Fn(usize) -> Box<dyn Scenario> + Send + Sync);
+
+/// A type of scenario of a mix.
+#[derive(Clone)]
+struct ScenarioType {
+	name: String,
+	weight: u32,
+	create_scenario: Arc<CreateScenarioFn>,
+}
+
+/// A mix of scenario types, each run for a share of the scenarios proportional to its weight.
+///
+/// ```ignore
+/// let mix = ScenarioMix::default()
+///     .with("transfer", 70, Arc::new(create_transfer_scenario))
+///     .with("publish", 20, Arc::new(create_publish_scenario))
+///     .with("view", 10, Arc::new(create_view_scenario));
+/// ```
+#[derive(Clone, Default)]
+pub struct ScenarioMix {
+	types: Vec<ScenarioType>,
+}
+
+impl ScenarioMix {
+	/// Adds a type of scenario, created with create_scenario for a share of weight / total weight
+	/// of the scenario ids.
+	pub fn with(
+		mut self,
+		name: impl Into<String>,
+		weight: u32,
+		create_scenario: Arc<CreateScenarioFn>,
+	) -> Self {
+		self.types.push(ScenarioType { name: name.into(), weight, create_scenario });
+		self
+	}
+
+	pub fn total_weight(&self) -> u64 {
+		self.types.iter().map(|scenario_type| scenario_type.weight as u64).sum()
+	}
+
+	/// The names and weights of the scenario types, by index.
+	pub fn types(&self) -> impl Iterator<Item = (&str, u32)> {
+		self.types
+			.iter()
+			.map(|scenario_type| (scenario_type.name.as_str(), scenario_type.weight))
+	}
+
+	/// The index of the type of the scenario id, None for the id 0 of the failed executions.
+	///
+	/// The ids are spread over the weights with the golden ratio sequence, so that the mix is
+	/// respected whatever the number of scenarios, and a scenario id keeps its type between runs.
+	pub fn type_index(&self, id: usize) -> Option<usize> {
+		let total_weight = self.total_weight();
+		if id == 0 || total_weight == 0 {
+			return None;
+		}
+		const INVERSE_GOLDEN_RATIO: f64 = 0.618_033_988_749_895;
+		let position = (id as f64 * INVERSE_GOLDEN_RATIO).fract() * total_weight as f64;
+		let mut cumulative_weight = 0;
+		self.types.iter().position(|scenario_type| {
+			cumulative_weight += scenario_type.weight as u64;
+			position < cumulative_weight as f64
+		})
+	}
+
+	/// Creates the scenario of the type of the id.
+	pub fn create_scenario(&self, id: usize) -> Box<dyn Scenario> {
+		let index = self.type_index(id).unwrap_or(0);
+		(self.types[index].create_scenario)(id)
+	}
+}
+
+impl From<Arc<CreateScenarioFn>> for ScenarioMix {
+	fn from(create_scenario: Arc<CreateScenarioFn>) -> Self {
+		ScenarioMix::default().with("scenario", 1, create_scenario)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct NoopScenario;
+
+	#[async_trait::async_trait]
+	impl Scenario for NoopScenario {
+		async fn run(self: Box<Self>) -> Result<(), anyhow::Error> {
+			Ok(())
+		}
+	}
+
+	fn create_noop_scenario(_id: usize) -> Box<dyn Scenario> {
+		Box::new(NoopScenario)
+	}
+
+	#[test]
+	fn test_scenario_mix_follows_the_weights() {
+		let mix = ScenarioMix::default()
+			.with("transfer", 70, Arc::new(create_noop_scenario))
+			.with("disabled", 0, Arc::new(create_noop_scenario))
+			.with("publish", 20, Arc::new(create_noop_scenario))
+			.with("view", 10, Arc::new(create_noop_scenario));
+
+		assert_eq!(mix.type_index(0), None);
+		for number_scenarios in [10, 1000] {
+			let mut counts = [0usize; 4];
+			for id in 1..=number_scenarios {
+				counts[mix.type_index(id).unwrap()] += 1;
+			}
+			let expected = [70, 0, 20, 10].map(|weight| weight * number_scenarios / 100);
+			for (count, expected) in counts.into_iter().zip(expected) {
+				assert!(count.abs_diff(expected) <= 1 + number_scenarios / 100, "{counts:?}");
+			}
+		}
+	}
+}
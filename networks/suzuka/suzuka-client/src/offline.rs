@@ -0,0 +1,158 @@
+//! Construction of transactions without a connection to a node, so that they can be signed on
+//! an air-gapped machine or by a hardware signer and submitted later with
+//! [crate::SuzukaClient::submit_bcs].
+//! The transactions are carried between the machines as hex encoded BCS.
+use crate::{
+	client::DEFAULT_MAX_GAS_AMOUNT,
+	crypto::{
+		ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+		signing_message,
+	},
+	transaction_builder::TransactionBuilder,
+	types::{
+		account_address::AccountAddress,
+		chain_id::ChainId,
+		transaction::{RawTransaction, SignedTransaction, TransactionPayload},
+	},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The default gas unit price of the transactions built offline.
+const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OfflineTransactionError {
+	#[error("Failed to sign the transaction: {0}")]
+	Signing(String),
+	#[error("Failed to encode or decode the transaction: {0}")]
+	Bcs(#[from] bcs::Error),
+	#[error("Transaction is not hex encoded: {0}")]
+	Hex(#[from] hex::FromHexError),
+}
+
+/// The parameters of a transaction which are usually fetched from the node, supplied explicitly
+/// to build the transaction offline.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionParams {
+	pub sender: AccountAddress,
+	pub sequence_number: u64,
+	pub chain_id: ChainId,
+	pub expiration_timestamp_secs: u64,
+	pub max_gas_amount: u64,
+	pub gas_unit_price: u64,
+}
+
+impl TransactionParams {
+	pub fn new(
+		sender: AccountAddress,
+		sequence_number: u64,
+		chain_id: ChainId,
+		expiration_timestamp_secs: u64,
+	) -> Self {
+		TransactionParams {
+			sender,
+			sequence_number,
+			chain_id,
+			expiration_timestamp_secs,
+			max_gas_amount: DEFAULT_MAX_GAS_AMOUNT,
+			gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
+		}
+	}
+
+	pub fn with_max_gas_amount(mut self, max_gas_amount: u64) -> Self {
+		self.max_gas_amount = max_gas_amount;
+		self
+	}
+
+	pub fn with_gas_unit_price(mut self, gas_unit_price: u64) -> Self {
+		self.gas_unit_price = gas_unit_price;
+		self
+	}
+
+	/// Builds the unsigned transaction of the payload.
+	pub fn build(&self, payload: TransactionPayload) -> RawTransaction {
+		TransactionBuilder::new(payload, self.expiration_timestamp_secs, self.chain_id)
+			.sender(self.sender)
+			.sequence_number(self.sequence_number)
+			.max_gas_amount(self.max_gas_amount)
+			.gas_unit_price(self.gas_unit_price)
+			.build()
+	}
+}
+
+/// The message to sign for the transaction, for the signers which don't take a transaction.
+pub fn transaction_signing_message(
+	raw_transaction: &RawTransaction,
+) -> Result<Vec<u8>, OfflineTransactionError> {
+	signing_message(raw_transaction)
+		.map_err(|err| OfflineTransactionError::Signing(err.to_string()))
+}
+
+/// Signs the transaction with the private key.
+pub fn sign_transaction(
+	raw_transaction: RawTransaction,
+	private_key: &Ed25519PrivateKey,
+) -> Result<SignedTransaction, OfflineTransactionError> {
+	let signed = raw_transaction
+		.sign(private_key, Ed25519PublicKey::from(private_key))
+		.map_err(|err| OfflineTransactionError::Signing(err.to_string()))?;
+	Ok(signed.into_inner())
+}
+
+/// Attaches the signature of the message of [transaction_signing_message] made by an external
+/// signer to the transaction.
+pub fn attach_signature(
+	raw_transaction: RawTransaction,
+	public_key: Ed25519PublicKey,
+	signature: Ed25519Signature,
+) -> SignedTransaction {
+	SignedTransaction::new(raw_transaction, public_key, signature)
+}
+
+/// Encodes a raw or signed transaction to carry it to another machine.
+pub fn encode_transaction<T: Serialize>(
+	transaction: &T,
+) -> Result<String, OfflineTransactionError> {
+	Ok(hex::encode(bcs::to_bytes(transaction)?))
+}
+
+/// Decodes a transaction encoded by [encode_transaction].
+pub fn decode_transaction<T: DeserializeOwned>(
+	encoded: &str,
+) -> Result<T, OfflineTransactionError> {
+	Ok(bcs::from_bytes(&hex::decode(encoded.trim_start_matches("0x"))?)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{crypto::SigningKey, transaction_builder::aptos_stdlib, types::LocalAccount};
+
+	#[test]
+	fn test_offline_signing() -> Result<(), OfflineTransactionError> {
+		let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+		let params = TransactionParams::new(account.address(), 7, ChainId::new(27), 1_900_000_000)
+			.with_gas_unit_price(150);
+		let raw_transaction =
+			params.build(aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1_000));
+		assert_eq!(raw_transaction.sequence_number(), 7);
+		assert_eq!(raw_transaction.chain_id(), ChainId::new(27));
+		assert_eq!(raw_transaction.gas_unit_price(), 150);
+
+		// The raw transaction goes to the air-gapped machine and the signed one comes back.
+		let raw_transaction: RawTransaction =
+			decode_transaction(&encode_transaction(&raw_transaction)?)?;
+		let signed = sign_transaction(raw_transaction.clone(), account.private_key())?;
+		let decoded: SignedTransaction = decode_transaction(&encode_transaction(&signed)?)?;
+		assert_eq!(decoded, signed);
+		assert!(decoded.check_signature().is_ok());
+
+		// A hardware signer signs the message and gives the signature back.
+		let signature = account
+			.private_key()
+			.sign_arbitrary_message(&transaction_signing_message(&raw_transaction)?);
+		let attached = attach_signature(raw_transaction, account.public_key().clone(), signature);
+		assert_eq!(attached.committed_hash(), signed.committed_hash());
+		Ok(())
+	}
+}
@@ -9,6 +9,7 @@ use crate::{
 	},
 	transaction_builder::TransactionBuilder,
 	types::{chain_id::ChainId, LocalAccount},
+	SuzukaClient,
 };
 use anyhow::Context;
 use aptos_sdk::crypto::ed25519::Ed25519PrivateKey;
@@ -185,6 +186,38 @@ async fn test_example_interaction() -> Result<(), anyhow::Error> {
 	Ok(())
 }
 
+/// The data of the deposit and withdraw events of a coin store.
+#[derive(Debug, Deserialize)]
+struct CoinEvent {
+	amount: String,
+}
+
+#[tokio::test]
+async fn test_suzuka_client_transfer() -> Result<(), anyhow::Error> {
+	let client = SuzukaClient::new(NODE_URL.clone(), FAUCET_URL.clone());
+
+	let alice = LocalAccount::generate(&mut rand::rngs::OsRng);
+	let bob = LocalAccount::generate(&mut rand::rngs::OsRng);
+	client.fund(alice.address(), 100_000_000).await?;
+	client.fund(bob.address(), 0).await?;
+
+	let pending = client.transfer(&alice, bob.address(), 1_000).await?;
+	client.wait(&pending).await?;
+
+	let deposits: Vec<CoinEvent> = client
+		.get_events(
+			bob.address(),
+			"0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+			"deposit_events",
+			None,
+			None,
+		)
+		.await?;
+	assert!(deposits.iter().any(|deposit| deposit.amount == "1000"), "{deposits:?}");
+
+	Ok(())
+}
+
 #[derive(Debug, Deserialize)]
 struct Config {
 	profiles: Profiles,
@@ -0,0 +1,17 @@
+use godfig::env_default;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+	/// The number of blocks read from the DA and deserialized ahead of the execution
+	#[serde(default = "default_pipeline_depth")]
+	pub pipeline_depth: usize,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self { pipeline_depth: default_pipeline_depth() }
+	}
+}
+
+env_default!(default_pipeline_depth, "SUZUKA_EXECUTION_PIPELINE_DEPTH", usize, 16);
@@ -1,4 +1,6 @@
 pub mod da_db;
+pub mod execution_pipeline;
+pub mod state_sync;
 
 use serde::{Deserialize, Serialize};
 
@@ -21,6 +23,12 @@ pub struct Config {
 
 	#[serde(default)]
 	pub da_db: da_db::Config,
+
+	#[serde(default)]
+	pub execution_pipeline: execution_pipeline::Config,
+
+	#[serde(default)]
+	pub state_sync: state_sync::Config,
 }
 
 impl Default for Config {
@@ -30,6 +38,8 @@ impl Default for Config {
 			m1_da_light_node: M1DaLightNodeConfig::default(),
 			mcr: McrConfig::default(),
 			da_db: da_db::Config::default(),
+			execution_pipeline: execution_pipeline::Config::default(),
+			state_sync: state_sync::Config::default(),
 		}
 	}
 }
@@ -0,0 +1,47 @@
+use godfig::env_default;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+	/// The base URLs of the peers or object stores serving a state snapshot, tried in order.
+	/// A fresh node restores the snapshot instead of replaying the DA from genesis when set.
+	#[serde(default)]
+	pub state_sync_sources: Vec<String>,
+
+	/// The SHA-256 hash of the manifest of the trusted snapshot, required with the sources
+	#[serde(default)]
+	pub state_sync_manifest_hash: Option<String>,
+
+	/// The directory the snapshot is downloaded to before being restored
+	#[serde(default = "default_state_sync_staging_dir")]
+	pub state_sync_staging_dir: String,
+
+	/// The number of chunks of the snapshot downloaded at the same time
+	#[serde(default = "default_state_sync_max_concurrent_downloads")]
+	pub state_sync_max_concurrent_downloads: usize,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			state_sync_sources: Vec::new(),
+			state_sync_manifest_hash: None,
+			state_sync_staging_dir: default_state_sync_staging_dir(),
+			state_sync_max_concurrent_downloads: default_state_sync_max_concurrent_downloads(),
+		}
+	}
+}
+
+env_default!(
+	default_state_sync_staging_dir,
+	"SUZUKA_STATE_SYNC_STAGING_DIR",
+	String,
+	"suzuka-state-sync".to_string()
+);
+
+env_default!(
+	default_state_sync_max_concurrent_downloads,
+	"SUZUKA_STATE_SYNC_MAX_CONCURRENT_DOWNLOADS",
+	usize,
+	4
+);
@@ -8,12 +8,14 @@ const TIMING_LOG_ENV: &str = "SUZUKA_TIMING_LOG";
 
 #[tokio::main]
 async fn main() -> Result<ExitCode, anyhow::Error> {
-	let tracing_config =
-		movement_tracing::Config { timing_log_path: env::var_os(TIMING_LOG_ENV).map(Into::into) };
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let tracing_config = movement_tracing::Config {
+		timing_log_path: env::var_os(TIMING_LOG_ENV).map(Into::into),
+		..movement_tracing::Config::with_telemetry_from_config(&dot_movement, "suzuka-full-node")?
+	};
 	let _guard = movement_tracing::init_tracing_subscriber(tracing_config);
 
 	// get the config file
-	let dot_movement = dot_movement::DotMovement::try_from_env()?;
 	let config_file = dot_movement.try_get_or_create_config_file().await?;
 
 	let manager = Manager::<SuzukaPartialNode<Executor>>::new(config_file).await?;
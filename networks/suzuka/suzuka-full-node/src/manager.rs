@@ -49,6 +49,13 @@ impl Manager<SuzukaPartialNode<Executor>> {
 
 		let config = self.godfig.try_wait_for_ready().await?;
 
+		// cross-check the config tree written by the setup before starting the services
+		let report = movement_config::validate(&dot_movement::DotMovement::try_from_env()?)?;
+		report.log();
+		if report.has_errors() {
+			anyhow::bail!("Invalid config, see `movement-config validate` for the full report");
+		}
+
 		let (executor, background_task) = SuzukaPartialNode::try_from_config(config)
 			.await
 			.context("Failed to create the executor")?;
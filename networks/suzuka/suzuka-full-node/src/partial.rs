@@ -1,17 +1,15 @@
 use crate::SuzukaFullNode;
-use m1_da_light_node_client::{
-	blob_response, BatchWriteRequest, BlobWrite, LightNodeServiceClient,
-	StreamReadFromHeightRequest,
-};
+use m1_da_light_node_client::{Blob, BlobKind, LightNodeClient};
 use maptos_dof_execution::{
 	v1::Executor, DynOptFinExecutor, ExecutableBlock, ExecutableTransactions, HashValue,
 	SignatureVerifiedTransaction, SignedTransaction, Transaction,
 };
-use mcr_settlement_client::{McrSettlementClient, McrSettlementClientOperations};
+use mcr_settlement_client::McrSettlementClientOperations;
 use mcr_settlement_manager::CommitmentEventStream;
 use mcr_settlement_manager::{McrSettlementManager, McrSettlementManagerOperations};
 use movement_rest::MovementRest;
-use movement_types::{Block, BlockCommitment, BlockCommitmentEvent};
+use movement_types::{Block, BlockCommitment, BlockCommitmentEvent, Id};
+use suzuka_state_sync::StateSync;
 
 use anyhow::Context;
 use async_channel::{Receiver, Sender};
@@ -20,13 +18,15 @@ use rocksdb::{ColumnFamilyDescriptor, Options, DB};
 use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio_stream::StreamExt;
 use tracing::{debug, error, info, info_span, warn, Instrument};
 pub struct SuzukaPartialNode<T> {
 	executor: T,
 	transaction_sender: Sender<SignedTransaction>,
 	pub transaction_receiver: Receiver<SignedTransaction>,
-	light_node_client: LightNodeServiceClient<tonic::transport::Channel>,
+	light_node_client: LightNodeClient,
 	settlement_manager: McrSettlementManager,
 	movement_rest: MovementRest,
 	pub config: suzuka_config::Config,
@@ -35,13 +35,16 @@ pub struct SuzukaPartialNode<T> {
 
 const LOGGING_UID: AtomicU64 = AtomicU64::new(0);
 
+/// The deserialization of a block read from the DA, running on a blocking task.
+type BlockDecoding = JoinHandle<Result<DecodedBlock, anyhow::Error>>;
+
 impl<T> SuzukaPartialNode<T>
 where
 	T: DynOptFinExecutor + Clone + Send + Sync,
 {
 	pub fn new<C>(
 		executor: T,
-		light_node_client: LightNodeServiceClient<tonic::transport::Channel>,
+		light_node_client: LightNodeClient,
 		settlement_client: C,
 		movement_rest: MovementRest,
 		config: &suzuka_config::Config,
@@ -75,7 +78,7 @@ where
 
 	pub fn bound<C>(
 		executor: T,
-		light_node_client: LightNodeServiceClient<tonic::transport::Channel>,
+		light_node_client: LightNodeClient,
 		settlement_client: C,
 		movement_rest: MovementRest,
 		config: &suzuka_config::Config,
@@ -134,7 +137,7 @@ where
 							transaction.sequence_number(),
 						);
 						let serialized_transaction = serde_json::to_vec(&movement_transaction)?;
-						transactions.push(BlobWrite { data: serialized_transaction });
+						transactions.push(serialized_transaction);
 					}
 					Err(_) => {
 						break;
@@ -153,10 +156,9 @@ where
 				transaction_count = transactions.len(),
 				"built_batch_write"
 			);
-			let batch_write = BatchWriteRequest { blobs: transactions };
-			let mut light_node_client = self.light_node_client.clone();
+			let light_node_client = self.light_node_client.clone();
 			tokio::task::spawn(async move {
-				light_node_client.batch_write(batch_write).await?;
+				light_node_client.submit_blobs(transactions).await?;
 				Ok::<(), anyhow::Error>(())
 			});
 		}
@@ -170,80 +172,97 @@ where
 		}
 	}
 
-	// receive transactions from the transaction channel and send them to be executed
-	// ! This assumes the m1 da light node is running sequencer mode
+	/// Reads the blocks from the DA and executes them in a pipeline: the blobs are fetched and
+	/// deserialized in parallel up to the pipeline depth ahead of the execution, and executed in
+	/// the order of the DA.
 	pub async fn read_blocks_from_da(&self) -> Result<(), anyhow::Error> {
-		let mut stream = {
-			let mut light_node_client = self.light_node_client.clone();
-			light_node_client
-				.stream_read_from_height(StreamReadFromHeightRequest {
-					height: self.get_synced_height().await?,
-				})
-				.await?
-		}
-		.into_inner();
+		let pipeline_depth = self.config.execution_pipeline.pipeline_depth.max(1);
+		let (sender, receiver) = mpsc::channel(pipeline_depth);
+		tokio::try_join!(self.fetch_blocks_from_da(sender), self.execute_blocks_from_da(receiver))?;
+		Ok(())
+	}
+
+	/// Streams the blobs from the DA and deserializes each of them on a blocking task, the
+	/// deserializations are sent in order to the execution. The blocks already executed are
+	/// skipped before their deserialization, whether or not they are well formed.
+	async fn fetch_blocks_from_da(
+		&self,
+		sender: mpsc::Sender<BlockDecoding>,
+	) -> Result<(), anyhow::Error> {
+		let mut stream = self.light_node_client.stream_from_height(self.get_synced_height().await?);
 
 		while let Some(blob) = stream.next().await {
 			debug!("Got blob: {:?}", blob);
 
 			// get the block
-			let (block_bytes, block_timestamp, block_id, da_height) = match blob?
-				.blob
-				.ok_or(anyhow::anyhow!("No blob in response"))?
-				.blob_type
-				.ok_or(anyhow::anyhow!("No blob type in response"))?
-			{
-				blob_response::BlobType::SequencedBlobBlock(blob) => {
-					(blob.data, blob.timestamp, blob.blob_id, blob.height)
-				}
-				_ => {
-					anyhow::bail!("Invalid blob type in response")
-				}
-			};
+			let blob = blob?;
+			if blob.kind != BlobKind::SequencedBlock {
+				anyhow::bail!("Invalid blob type in response")
+			}
 
 			// check if the block has already been executed
-			if self.has_executed_block(block_id.clone()).await? {
-				warn!("Block already executed: {:#?}. It will be skipped", block_id);
+			if self.has_executed_block(blob.blob.blob_id.clone()).await? {
+				warn!("Block already executed: {:#?}. It will be skipped", blob.blob.blob_id);
 				continue;
 			}
 
-			// the da height must be greater than 1
-			if da_height < 2 {
-				anyhow::bail!("Invalid DA height: {:?}", da_height);
+			let decoding =
+				tokio::task::spawn_blocking(move || DecodedBlock::try_from_blob(blob.blob));
+			if sender.send(decoding).await.is_err() {
+				// the execution has stopped
+				break;
 			}
+		}
 
-			// decompress the block bytes
-			let block = tokio::task::spawn_blocking(move || {
-				let decompressed_block_bytes = zstd::decode_all(&block_bytes[..])?;
-				let block: Block = bcs::from_bytes(&decompressed_block_bytes)?;
-				Ok::<Block, anyhow::Error>(block)
-			})
-			.await??;
-
-			// get the transactions
-			let span = info_span!(target: "movement_timing", "execute_block", id = %block_id);
-			let commitment =
-				self.execute_block_with_retries(block, block_timestamp).instrument(span).await?;
-
-			// mark the da_height - 1 as synced
-			// we can't mark this height as synced because we must allow for the possibility of multiple blocks at the same height according to the m1 da specifications (which currently is built on celestia which itself allows more than one block at the same height)
-			self.set_synced_height(da_height - 1).await?;
-
-			// set the block as executed
-			self.add_executed_block(block_id.to_string()).await?;
-
-			// todo: this needs defaults
-			if self.config.mcr.should_settle() {
-				info!("Posting block commitment via settlement manager");
-				match self.settlement_manager.post_block_commitment(commitment).await {
-					Ok(_) => {}
-					Err(e) => {
-						error!("Failed to post block commitment: {:?}", e);
-					}
+		Ok(())
+	}
+
+	/// Executes the deserialized blocks in the order they were read from the DA.
+	async fn execute_blocks_from_da(
+		&self,
+		receiver: mpsc::Receiver<BlockDecoding>,
+	) -> Result<(), anyhow::Error> {
+		execute_in_order(receiver, |block| self.execute_decoded_block(block)).await
+	}
+
+	async fn execute_decoded_block(&self, block: DecodedBlock) -> Result<(), anyhow::Error> {
+		let DecodedBlock { block_id, da_height, block_timestamp, id, transactions } = block;
+
+		// the block may have been executed since it was read, when the DA sent it twice
+		if self.has_executed_block(block_id.clone()).await? {
+			warn!("Block already executed: {:#?}. It will be skipped", block_id);
+			return Ok(());
+		}
+
+		// get the transactions
+		let span = info_span!(target: "movement_timing", "execute_block", id = %block_id);
+		let commitment = self
+			.execute_block_with_retries(id, transactions, block_timestamp)
+			.instrument(span)
+			.await?;
+
+		// mark the da_height - 1 as synced
+		// we can't mark this height as synced because we must allow for the possibility of multiple blocks at the same height according to the m1 da specifications (which currently is built on celestia which itself allows more than one block at the same height)
+		self.set_synced_height(da_height - 1).await?;
+		// acknowledge the synced height, so the light node checkpoint follows the execution
+		if let Err(e) = self.light_node_client.acknowledge_height(da_height - 1).await {
+			warn!("Failed to acknowledge DA height {}: {}", da_height - 1, e);
+		}
+
+		// set the block as executed
+		self.add_executed_block(block_id.to_string()).await?;
+
+		// todo: this needs defaults
+		if self.config.mcr.should_settle() {
+			info!("Posting block commitment via settlement manager");
+			match self.settlement_manager.post_block_commitment(commitment).await {
+				Ok(_) => {}
+				Err(e) => {
+					error!("Failed to post block commitment: {:?}", e);
 				}
-			} else {
-				info!("Skipping settlement");
 			}
+		} else {
+			info!("Skipping settlement");
 		}
 
 		Ok(())
@@ -254,12 +273,13 @@ where
 	/// However, this has to be deterministic, otherwise nodes will not be able to agree on the block commitment.
 	async fn execute_block_with_retries(
 		&self,
-		block: Block,
+		id: Id,
+		transactions: Vec<SignedTransaction>,
 		mut block_timestamp: u64,
 	) -> anyhow::Result<BlockCommitment> {
 		for _ in 0..5 {
-			// we have to clone here because the block is supposed to be consumed by the executor
-			match self.execute_block(block.clone(), block_timestamp).await {
+			// we have to clone here because the transactions are supposed to be consumed by the executor
+			match self.execute_block(id.clone(), transactions.clone(), block_timestamp).await {
 				Ok(commitment) => return Ok(commitment),
 				Err(e) => {
 					error!("Failed to execute block: {:?}. Retrying", e);
@@ -273,11 +293,11 @@ where
 
 	async fn execute_block(
 		&self,
-		block: Block,
+		block_id: Id,
+		transactions: Vec<SignedTransaction>,
 		block_timestamp: u64,
 	) -> anyhow::Result<BlockCommitment> {
-		let block_id = block.id();
-		let block_hash = HashValue::from_slice(block.id())?;
+		let block_hash = HashValue::from_slice(&block_id)?;
 
 		// get the transactions
 		let mut block_transactions = Vec::new();
@@ -289,8 +309,7 @@ where
 			SignatureVerifiedTransaction::Valid(Transaction::BlockMetadata(block_metadata));
 		block_transactions.push(block_metadata_transaction);
 
-		for transaction in block.transactions {
-			let signed_transaction: SignedTransaction = serde_json::from_slice(&transaction.data)?;
+		for signed_transaction in transactions {
 			let signature_verified_transaction = SignatureVerifiedTransaction::Valid(
 				Transaction::UserTransaction(signed_transaction),
 			);
@@ -311,6 +330,54 @@ where
 	}
 }
 
+/// A block read from the DA with its transactions deserialized, ready to be executed.
+struct DecodedBlock {
+	block_id: String,
+	da_height: u64,
+	block_timestamp: u64,
+	id: Id,
+	transactions: Vec<SignedTransaction>,
+}
+
+impl DecodedBlock {
+	fn try_from_blob(blob: Blob) -> Result<Self, anyhow::Error> {
+		let (block_bytes, block_timestamp, block_id, da_height) =
+			(blob.data, blob.timestamp, blob.blob_id, blob.height);
+
+		// the da height must be greater than 1
+		if da_height < 2 {
+			anyhow::bail!("Invalid DA height: {:?}", da_height);
+		}
+
+		// the light node has already decoded the block bytes
+		let block: Block = bcs::from_bytes(&block_bytes[..])?;
+		let transactions = block
+			.transactions
+			.iter()
+			.map(|transaction| serde_json::from_slice(&transaction.data))
+			.collect::<Result<Vec<SignedTransaction>, _>>()?;
+
+		Ok(Self { block_id, da_height, block_timestamp, id: block.id(), transactions })
+	}
+}
+
+/// Executes the decoded blocks in the order their decodings were sent, whatever the order the
+/// decodings finish in. Stops at the first failed decoding or execution, which drops the receiver
+/// so that the reading of the DA stops too.
+async fn execute_in_order<B, F, Fut>(
+	mut receiver: mpsc::Receiver<JoinHandle<Result<B, anyhow::Error>>>,
+	mut execute: F,
+) -> Result<(), anyhow::Error>
+where
+	F: FnMut(B) -> Fut,
+	Fut: Future<Output = Result<(), anyhow::Error>>,
+{
+	while let Some(decoding) = receiver.recv().await {
+		execute(decoding.await??).await?;
+	}
+	Ok(())
+}
+
 pub async fn read_commitment_events<T>(
 	mut stream: CommitmentEventStream,
 	executor: T,
@@ -488,21 +555,24 @@ impl SuzukaPartialNode<Executor> {
 			"Connecting to light node at {}:{}",
 			light_node_connection_hostname, light_node_connection_port
 		);
-		let light_node_client = LightNodeServiceClient::connect(format!(
-			"http://{}:{}",
-			light_node_connection_hostname, light_node_connection_port
-		))
-		.await
-		.context("Failed to connect to light node")?;
+		let light_node_client = LightNodeClient::for_address(
+			&light_node_connection_hostname,
+			light_node_connection_port,
+		)
+		.context("Failed to create the light node client")?;
+
+		// a fresh node restores the state of a snapshot instead of replaying the DA from genesis
+		Self::maybe_sync_state(&config).await.context("Failed to sync the state")?;
 
 		debug!("Creating the executor");
 		let executor = Executor::try_from_config(tx, config.execution_config.maptos_config.clone())
 			.context("Failed to create the inner executor")?;
 
 		debug!("Creating the settlement client");
-		let settlement_client = McrSettlementClient::build_with_config(config.mcr.clone())
-			.await
-			.context("Failed to build MCR settlement client with config")?;
+		let settlement_client =
+			mcr_settlement_client::backend::build_with_config(config.mcr.clone())
+				.await
+				.context("Failed to build MCR settlement client with config")?;
 
 		debug!("Creating the movement rest service");
 		let movement_rest = MovementRest::try_from_env(Some(executor.executor.context.clone()))
@@ -518,4 +588,80 @@ impl SuzukaPartialNode<Executor> {
 			"Failed to bind the executor, light node client, settlement client, and movement rest",
 		)
 	}
+
+	/// Syncs the state from the snapshot sources of the config, if any, when the node has no
+	/// state yet or a previous sync was interrupted.
+	async fn maybe_sync_state(config: &suzuka_config::Config) -> Result<(), anyhow::Error> {
+		let Some(state_sync) = StateSync::try_from_config(config)? else {
+			return Ok(());
+		};
+		let roots = suzuka_state_sync::snapshot_roots(config)?;
+		if state_sync.is_interrupted().await? {
+			info!("Resuming the interrupted state sync");
+		} else if roots.values().any(|path| path.exists()) {
+			info!("The node has a state already, skipping the state sync");
+			return Ok(());
+		}
+		let manifest = state_sync.sync(&roots).await?;
+		info!("Synced the state of a snapshot created at {}", manifest.created_at_secs);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Mutex;
+
+	/// Decodes `block` on a blocking task after `delay_ms`, or fails to.
+	fn decoding(block: u64, delay_ms: u64, ok: bool) -> JoinHandle<Result<u64, anyhow::Error>> {
+		tokio::task::spawn_blocking(move || {
+			std::thread::sleep(Duration::from_millis(delay_ms));
+			if !ok {
+				anyhow::bail!("Failed to decode block {block}");
+			}
+			Ok(block)
+		})
+	}
+
+	#[tokio::test]
+	async fn test_blocks_execute_in_da_order() -> Result<(), anyhow::Error> {
+		let (sender, receiver) = mpsc::channel(4);
+		// the later blocks finish decoding first
+		for (block, delay_ms) in [(1, 60), (2, 30), (3, 0)] {
+			sender.send(decoding(block, delay_ms, true)).await?;
+		}
+		drop(sender);
+
+		let executed = Mutex::new(Vec::new());
+		execute_in_order(receiver, |block| {
+			executed.lock().unwrap().push(block);
+			async { Ok(()) }
+		})
+		.await?;
+
+		assert_eq!(*executed.lock().unwrap(), [1, 2, 3]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_failed_decoding_stops_the_pipeline() -> Result<(), anyhow::Error> {
+		let (sender, receiver) = mpsc::channel(4);
+		sender.send(decoding(1, 0, true)).await?;
+		sender.send(decoding(2, 30, false)).await?;
+		sender.send(decoding(3, 0, true)).await?;
+
+		let executed = Mutex::new(Vec::new());
+		let result = execute_in_order(receiver, |block| {
+			executed.lock().unwrap().push(block);
+			async { Ok(()) }
+		})
+		.await;
+
+		assert!(result.is_err());
+		assert_eq!(*executed.lock().unwrap(), [1]);
+		// the reading of the DA stops when it sends the next decoding
+		assert!(sender.send(decoding(4, 0, true)).await.is_err());
+		Ok(())
+	}
 }
@@ -0,0 +1,29 @@
+use clap::Parser;
+use std::path::PathBuf;
+use suzuka_state_sync::{create_snapshot, manifest::DEFAULT_CHUNK_SIZE, snapshot_roots};
+
+/// Creates a snapshot of the state of the stopped node of the .movement directory, to serve it
+/// to the nodes syncing their state.
+#[derive(Debug, Parser)]
+struct Args {
+	/// The directory the snapshot is written to
+	#[clap(long)]
+	out: PathBuf,
+
+	/// The size of the chunks the files are split in, in bytes
+	#[clap(long, default_value_t = DEFAULT_CHUNK_SIZE)]
+	chunk_size: u64,
+}
+
+fn main() -> Result<(), anyhow::Error> {
+	tracing_subscriber::fmt()
+		.with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+		.init();
+	let args = Args::parse();
+
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let config = dot_movement.try_get_config_from_json::<suzuka_config::Config>()?;
+	let manifest = create_snapshot(&snapshot_roots(&config)?, args.chunk_size, &args.out)?;
+	println!("Created a snapshot of {} files in {}", manifest.files.len(), args.out.display());
+	Ok(())
+}
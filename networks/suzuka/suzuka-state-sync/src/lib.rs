@@ -0,0 +1,53 @@
+//! State sync of the Suzuka nodes: a fresh node downloads a recent snapshot of the state of
+//! another node instead of replaying the DA from genesis, then reads the DA blobs from the height
+//! the snapshot was synced up to.
+//!
+//! A snapshot is a directory with a [Manifest] and the chunks of the snapshotted files, named by
+//! their SHA-256 hash. It is served over HTTP by a peer or an object store, and the chunks are
+//! verified against the manifest as they are downloaded.
+pub mod manifest;
+pub mod sync;
+
+pub use manifest::{create_snapshot, Manifest, SnapshotFile};
+pub use sync::StateSync;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The root of the snapshot holding the Aptos DB of the node.
+pub const MAPTOS_DB_ROOT: &str = "maptos-db";
+/// The root of the snapshot holding the DA DB of the node, with the synced DA height.
+pub const DA_DB_ROOT: &str = "da-db";
+
+#[derive(Debug, thiserror::Error)]
+pub enum StateSyncError {
+	#[error("State sync request failed: {0}")]
+	Http(#[from] reqwest::Error),
+	#[error("State sync IO failed: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("Invalid manifest: {0}")]
+	InvalidManifest(String),
+	#[error("Hash mismatch of {what}: expected {expected}, got {actual}")]
+	HashMismatch { what: String, expected: String, actual: String },
+	#[error("No source served the {what}: {errors}")]
+	Unavailable { what: String, errors: String },
+	#[error("Invalid config: {0}")]
+	Config(String),
+}
+
+/// The directories of the node snapshotted and restored by the state sync, by snapshot root.
+pub fn snapshot_roots(
+	config: &suzuka_config::Config,
+) -> Result<BTreeMap<String, PathBuf>, StateSyncError> {
+	let maptos_db_path = config
+		.execution_config
+		.maptos_config
+		.chain
+		.maptos_db_path
+		.clone()
+		.ok_or(StateSyncError::Config("No maptos db path".to_string()))?;
+	Ok(BTreeMap::from([
+		(MAPTOS_DB_ROOT.to_string(), maptos_db_path),
+		(DA_DB_ROOT.to_string(), PathBuf::from(&config.da_db.da_db_path)),
+	]))
+}
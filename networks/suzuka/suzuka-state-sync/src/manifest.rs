@@ -0,0 +1,224 @@
+use crate::StateSyncError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The name of the manifest in the snapshot directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// The directory of the chunks in the snapshot directory.
+pub const CHUNKS_DIR: &str = "chunks";
+/// The default size of the chunks, small enough to retry a chunk without losing much.
+pub const DEFAULT_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The description of a snapshot: its files and the hashes of their chunks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+	/// The time the snapshot was created, to tell how recent it is.
+	pub created_at_secs: u64,
+	pub chunk_size: u64,
+	pub files: Vec<SnapshotFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+	/// The path of the file, starting with the root of the snapshot it belongs to.
+	pub path: String,
+	pub size: u64,
+	/// The hex encoded SHA-256 hashes of the chunks of the file, which are also their names.
+	pub chunks: Vec<String>,
+}
+
+impl Manifest {
+	/// The hex encoded SHA-256 hash of the serialized manifest, pinned in the config to trust a
+	/// snapshot.
+	pub fn hash(bytes: &[u8]) -> String {
+		sha256_hex(bytes)
+	}
+
+	/// Resolves the path of the file in the directory of its root, rejecting the paths leaving it.
+	pub fn target_path(
+		file: &SnapshotFile,
+		roots: &BTreeMap<String, PathBuf>,
+	) -> Result<PathBuf, StateSyncError> {
+		let invalid = || StateSyncError::InvalidManifest(format!("invalid path {}", file.path));
+		let mut components = Path::new(&file.path).components();
+		let root = match components.next() {
+			Some(Component::Normal(root)) => root.to_str().ok_or_else(invalid)?,
+			_ => return Err(invalid()),
+		};
+		let mut path = roots
+			.get(root)
+			.ok_or_else(|| StateSyncError::InvalidManifest(format!("unknown root {root}")))?
+			.clone();
+		for component in components {
+			match component {
+				Component::Normal(component) => path.push(component),
+				_ => return Err(invalid()),
+			}
+		}
+		Ok(path)
+	}
+
+	/// Checks the paths and the chunks of the files, before downloading anything.
+	///
+	/// The chunk hashes name the files of the staging directory, so anything but a hex encoded
+	/// SHA-256 hash is rejected.
+	pub fn validate(&self, roots: &BTreeMap<String, PathBuf>) -> Result<(), StateSyncError> {
+		for file in &self.files {
+			Manifest::target_path(file, roots)?;
+			if let Some(hash) = file.chunks.iter().find(|hash| !is_sha256_hex(hash)) {
+				return Err(StateSyncError::InvalidManifest(format!(
+					"{} has an invalid chunk hash {hash}",
+					file.path
+				)));
+			}
+			let expected_chunks = file.size.div_ceil(self.chunk_size.max(1));
+			if file.chunks.len() as u64 != expected_chunks {
+				return Err(StateSyncError::InvalidManifest(format!(
+					"{} has {} chunks instead of {expected_chunks}",
+					file.path,
+					file.chunks.len()
+				)));
+			}
+		}
+		Ok(())
+	}
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+	hex::encode(Sha256::digest(bytes))
+}
+
+/// Whether the string is a SHA-256 hash encoded like [sha256_hex]: 64 lowercase hex characters.
+fn is_sha256_hex(hash: &str) -> bool {
+	hash.len() == 64 && hash.bytes().all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Creates a snapshot of the directories of the roots in `out_dir`.
+///
+/// The files are split in chunks written under their hash, so that the chunks shared by several
+/// files or snapshots are stored once. The directories must not be written while the snapshot is
+/// created, the node has to be stopped.
+pub fn create_snapshot(
+	roots: &BTreeMap<String, PathBuf>,
+	chunk_size: u64,
+	out_dir: &Path,
+) -> Result<Manifest, StateSyncError> {
+	let chunks_dir = out_dir.join(CHUNKS_DIR);
+	fs::create_dir_all(&chunks_dir)?;
+
+	let mut files = Vec::new();
+	for (root, dir) in roots {
+		for path in list_files(dir)? {
+			let relative = path.strip_prefix(dir).map_err(|_| {
+				StateSyncError::InvalidManifest(format!("{} is not in {root}", path.display()))
+			})?;
+			let relative = relative.to_str().ok_or_else(|| {
+				StateSyncError::InvalidManifest(format!("{} is not UTF-8", path.display()))
+			})?;
+
+			let mut file = File::open(&path)?;
+			let mut size = 0;
+			let mut chunks = Vec::new();
+			loop {
+				let mut chunk = Vec::new();
+				(&mut file).take(chunk_size).read_to_end(&mut chunk)?;
+				if chunk.is_empty() {
+					break;
+				}
+				size += chunk.len() as u64;
+				let hash = sha256_hex(&chunk);
+				let chunk_path = chunks_dir.join(&hash);
+				if !chunk_path.exists() {
+					fs::write(chunk_path, &chunk)?;
+				}
+				chunks.push(hash);
+			}
+			files.push(SnapshotFile {
+				path: format!("{root}/{}", relative.replace('\\', "/")),
+				size,
+				chunks,
+			});
+		}
+	}
+
+	let manifest = Manifest {
+		created_at_secs: SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|now| now.as_secs())
+			.unwrap_or_default(),
+		chunk_size,
+		files,
+	};
+	let bytes = serde_json::to_vec_pretty(&manifest)
+		.map_err(|err| StateSyncError::InvalidManifest(err.to_string()))?;
+	fs::write(out_dir.join(MANIFEST_FILE), &bytes)?;
+	tracing::info!(
+		"Created a snapshot of {} files in {}, manifest hash {}",
+		manifest.files.len(),
+		out_dir.display(),
+		Manifest::hash(&bytes)
+	);
+	Ok(manifest)
+}
+
+/// The files of the directory and its subdirectories, in a stable order.
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>, StateSyncError> {
+	let mut files = Vec::new();
+	let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+	entries.sort_by_key(|entry| entry.file_name());
+	for entry in entries {
+		let path = entry.path();
+		if entry.file_type()?.is_dir() {
+			files.extend(list_files(&path)?);
+		} else {
+			files.push(path);
+		}
+	}
+	Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_target_path_stays_in_root() {
+		let roots = BTreeMap::from([("da-db".to_string(), PathBuf::from("/data/da-db"))]);
+		let file = |path: &str| SnapshotFile { path: path.to_string(), size: 0, chunks: vec![] };
+
+		assert_eq!(
+			Manifest::target_path(&file("da-db/cf/000001.sst"), &roots).unwrap(),
+			PathBuf::from("/data/da-db/cf/000001.sst")
+		);
+		assert!(Manifest::target_path(&file("da-db/../etc/passwd"), &roots).is_err());
+		assert!(Manifest::target_path(&file("/etc/passwd"), &roots).is_err());
+		assert!(Manifest::target_path(&file("maptos-db/CURRENT"), &roots).is_err());
+	}
+
+	#[test]
+	fn test_validate_rejects_invalid_chunk_hashes() {
+		let roots = BTreeMap::from([("da-db".to_string(), PathBuf::from("/data/da-db"))]);
+		let manifest = |chunk: &str| Manifest {
+			created_at_secs: 0,
+			chunk_size: 64,
+			files: vec![SnapshotFile {
+				path: "da-db/CURRENT".to_string(),
+				size: 10,
+				chunks: vec![chunk.to_string()],
+			}],
+		};
+
+		assert!(manifest(&sha256_hex(b"chunk")).validate(&roots).is_ok());
+		assert!(manifest(&sha256_hex(b"chunk").to_uppercase()).validate(&roots).is_err());
+		assert!(manifest("../../maptos-db/CURRENT").validate(&roots).is_err());
+		assert!(manifest("").validate(&roots).is_err());
+		assert!(manifest(&format!("{}.part", &sha256_hex(b"chunk")[..59]))
+			.validate(&roots)
+			.is_err());
+	}
+}
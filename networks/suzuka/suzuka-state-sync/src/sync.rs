@@ -0,0 +1,467 @@
+use crate::manifest::{Manifest, CHUNKS_DIR, MANIFEST_FILE};
+use crate::StateSyncError;
+use futures::{StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use url::Url;
+
+/// The default number of chunks downloaded at the same time.
+const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+/// Downloads a snapshot from the peers or object stores serving it and restores it in the
+/// directories of the node.
+///
+/// The sources are tried in order for the manifest and for each chunk. Only the manifest with
+/// the pinned hash is trusted, as the sources are not: the chunks are verified against it, so a
+/// source cannot get the node to restore a state of its own. The chunks are kept in the staging
+/// directory once verified, and a partially downloaded chunk is resumed with a range request, so
+/// an interrupted sync picks up where it stopped.
+pub struct StateSync {
+	sources: Vec<Url>,
+	staging_dir: PathBuf,
+	manifest_hash: String,
+	max_concurrent_downloads: usize,
+	http_client: reqwest::Client,
+}
+
+impl StateSync {
+	pub fn new(sources: Vec<Url>, manifest_hash: &str, staging_dir: impl Into<PathBuf>) -> Self {
+		let sources = sources
+			.into_iter()
+			.map(|mut source| {
+				// the base url must end with a slash for the files to be joined to it
+				if !source.path().ends_with('/') {
+					source.set_path(&format!("{}/", source.path()));
+				}
+				source
+			})
+			.collect();
+		Self {
+			sources,
+			staging_dir: staging_dir.into(),
+			manifest_hash: manifest_hash.to_lowercase(),
+			max_concurrent_downloads: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+			http_client: reqwest::Client::new(),
+		}
+	}
+
+	/// Creates the state sync of the config, or none when no source is configured.
+	/// The sources are rejected without the hash of the manifest to trust.
+	pub fn try_from_config(config: &suzuka_config::Config) -> Result<Option<Self>, StateSyncError> {
+		let config = &config.state_sync;
+		if config.state_sync_sources.is_empty() {
+			return Ok(None);
+		}
+		let manifest_hash = config.state_sync_manifest_hash.as_deref().ok_or_else(|| {
+			StateSyncError::Config(
+				"The state sync sources are set without the manifest hash to trust".to_string(),
+			)
+		})?;
+		let sources = config
+			.state_sync_sources
+			.iter()
+			.map(|source| Url::parse(source))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|err| StateSyncError::Config(err.to_string()))?;
+		Ok(Some(
+			StateSync::new(sources, manifest_hash, &config.state_sync_staging_dir)
+				.with_max_concurrent_downloads(config.state_sync_max_concurrent_downloads),
+		))
+	}
+
+	pub fn with_max_concurrent_downloads(mut self, max_concurrent_downloads: usize) -> Self {
+		self.max_concurrent_downloads = max_concurrent_downloads.max(1);
+		self
+	}
+
+	/// Whether a previous sync was interrupted: the staging directory is only removed once the
+	/// snapshot is fully restored, so the directories of the roots may be partial.
+	pub async fn is_interrupted(&self) -> Result<bool, StateSyncError> {
+		Ok(fs::try_exists(&self.staging_dir).await?)
+	}
+
+	/// Downloads the snapshot and restores it in the directories of the roots, then removes the
+	/// staging directory.
+	pub async fn sync(
+		&self,
+		roots: &BTreeMap<String, PathBuf>,
+	) -> Result<Manifest, StateSyncError> {
+		let manifest = self.fetch_manifest().await?;
+		manifest.validate(roots)?;
+		tracing::info!(
+			"State sync: downloading a snapshot of {} files created at {}",
+			manifest.files.len(),
+			manifest.created_at_secs
+		);
+		self.download(&manifest).await?;
+		self.restore(&manifest, roots).await?;
+		fs::remove_dir_all(&self.staging_dir).await?;
+		tracing::info!("State sync: restored the snapshot");
+		Ok(manifest)
+	}
+
+	/// Fetches the manifest from the first source serving the one with the pinned hash.
+	pub async fn fetch_manifest(&self) -> Result<Manifest, StateSyncError> {
+		let mut errors = Vec::new();
+		for source in &self.sources {
+			match self.fetch_manifest_from(source).await {
+				Ok(manifest) => return Ok(manifest),
+				Err(err) => {
+					tracing::warn!("State sync: failed to fetch the manifest from {source}: {err}");
+					errors.push(format!("{source}: {err}"));
+				}
+			}
+		}
+		Err(StateSyncError::Unavailable { what: "manifest".to_string(), errors: errors.join(", ") })
+	}
+
+	async fn fetch_manifest_from(&self, source: &Url) -> Result<Manifest, StateSyncError> {
+		let url = join(source, MANIFEST_FILE)?;
+		let bytes = self.http_client.get(url).send().await?.error_for_status()?.bytes().await?;
+		let actual = Manifest::hash(&bytes);
+		if actual != self.manifest_hash {
+			return Err(StateSyncError::HashMismatch {
+				what: MANIFEST_FILE.to_string(),
+				expected: self.manifest_hash.clone(),
+				actual,
+			});
+		}
+		serde_json::from_slice(&bytes)
+			.map_err(|err| StateSyncError::InvalidManifest(err.to_string()))
+	}
+
+	/// Downloads the chunks of the manifest missing in the staging directory.
+	pub async fn download(&self, manifest: &Manifest) -> Result<(), StateSyncError> {
+		fs::create_dir_all(self.staging_dir.join(CHUNKS_DIR)).await?;
+		let chunks: BTreeSet<&String> =
+			manifest.files.iter().flat_map(|file| &file.chunks).collect();
+		futures::stream::iter(chunks)
+			.map(|hash| self.download_chunk(hash))
+			.buffer_unordered(self.max_concurrent_downloads)
+			.try_collect::<()>()
+			.await
+	}
+
+	async fn download_chunk(&self, hash: &str) -> Result<(), StateSyncError> {
+		let path = self.chunk_path(hash);
+		if fs::try_exists(&path).await? {
+			return Ok(());
+		}
+		let mut errors = Vec::new();
+		for source in &self.sources {
+			match self.download_chunk_from(source, hash, &path).await {
+				Ok(()) => return Ok(()),
+				Err(err) => {
+					tracing::warn!(
+						"State sync: failed to download chunk {hash} from {source}: {err}"
+					);
+					errors.push(format!("{source}: {err}"));
+				}
+			}
+		}
+		Err(StateSyncError::Unavailable {
+			what: format!("chunk {hash}"),
+			errors: errors.join(", "),
+		})
+	}
+
+	/// Downloads the chunk to a partial file, resuming the previous download if any, and moves
+	/// it in place once its hash is verified.
+	async fn download_chunk_from(
+		&self,
+		source: &Url,
+		hash: &str,
+		path: &Path,
+	) -> Result<(), StateSyncError> {
+		let partial_path = path.with_extension("part");
+		let downloaded = match fs::metadata(&partial_path).await {
+			Ok(metadata) => metadata.len(),
+			Err(_) => 0,
+		};
+
+		let mut request = self.http_client.get(join(source, &format!("{CHUNKS_DIR}/{hash}"))?);
+		if downloaded > 0 {
+			request = request.header(reqwest::header::RANGE, format!("bytes={downloaded}-"));
+		}
+		let mut response = request.send().await?;
+		let status = response.status();
+		// the partial file already holds the whole chunk
+		if status != reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+			response = response.error_for_status()?;
+			// the source may ignore the range and send the whole chunk
+			let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+			let mut file = OpenOptions::new()
+				.create(true)
+				.write(true)
+				.append(resumed)
+				.truncate(!resumed)
+				.open(&partial_path)
+				.await?;
+			while let Some(bytes) = response.chunk().await? {
+				file.write_all(&bytes).await?;
+			}
+			file.flush().await?;
+		}
+
+		let actual = hash_file(&partial_path).await?;
+		if actual != hash {
+			fs::remove_file(&partial_path).await?;
+			return Err(StateSyncError::HashMismatch {
+				what: format!("chunk {hash}"),
+				expected: hash.to_string(),
+				actual,
+			});
+		}
+		fs::rename(&partial_path, path).await?;
+		Ok(())
+	}
+
+	/// Writes the files of the manifest from the downloaded chunks, replacing the directories of
+	/// the roots.
+	///
+	/// Each root is restored next to its directory, which is only replaced once all the roots are
+	/// restored, so a failed restore leaves the directories as they were.
+	pub async fn restore(
+		&self,
+		manifest: &Manifest,
+		roots: &BTreeMap<String, PathBuf>,
+	) -> Result<(), StateSyncError> {
+		let restoring_roots: BTreeMap<String, PathBuf> =
+			roots.iter().map(|(root, dir)| (root.clone(), restoring_path(dir))).collect();
+		for dir in restoring_roots.values() {
+			if fs::try_exists(dir).await? {
+				fs::remove_dir_all(dir).await?;
+			}
+			fs::create_dir_all(dir).await?;
+		}
+
+		for file in &manifest.files {
+			let target = Manifest::target_path(file, &restoring_roots)?;
+			if let Some(parent) = target.parent() {
+				fs::create_dir_all(parent).await?;
+			}
+			let mut output = fs::File::create(&target).await?;
+			for hash in &file.chunks {
+				output.write_all(&fs::read(self.chunk_path(hash)).await?).await?;
+			}
+			output.flush().await?;
+			let size = output.metadata().await?.len();
+			if size != file.size {
+				return Err(StateSyncError::InvalidManifest(format!(
+					"{} restored with {size} bytes instead of {}",
+					file.path, file.size
+				)));
+			}
+		}
+
+		for (root, dir) in roots {
+			if fs::try_exists(dir).await? {
+				fs::remove_dir_all(dir).await?;
+			}
+			fs::rename(&restoring_roots[root], dir).await?;
+		}
+		Ok(())
+	}
+
+	fn chunk_path(&self, hash: &str) -> PathBuf {
+		self.staging_dir.join(CHUNKS_DIR).join(hash)
+	}
+}
+
+/// The directory a root is restored to before replacing its directory.
+fn restoring_path(dir: &Path) -> PathBuf {
+	let mut path = dir.as_os_str().to_owned();
+	path.push(".restoring");
+	PathBuf::from(path)
+}
+
+fn join(source: &Url, path: &str) -> Result<Url, StateSyncError> {
+	source.join(path).map_err(|err| StateSyncError::Config(err.to_string()))
+}
+
+async fn hash_file(path: &Path) -> Result<String, StateSyncError> {
+	let mut file = fs::File::open(path).await?;
+	let mut hasher = Sha256::new();
+	let mut buffer = vec![0u8; 1 << 20];
+	loop {
+		let read = file.read(&mut buffer).await?;
+		if read == 0 {
+			break;
+		}
+		hasher.update(&buffer[..read]);
+	}
+	Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::create_snapshot;
+	use std::sync::Arc;
+	use tokio::net::{TcpListener, TcpStream};
+
+	/// Serves the files of the directory, with the range requests, like a static file server.
+	async fn serve(dir: PathBuf) -> Result<Url, anyhow::Error> {
+		let listener = TcpListener::bind("127.0.0.1:0").await?;
+		let url = Url::parse(&format!("http://{}/snapshot", listener.local_addr()?))?;
+		let dir = Arc::new(dir);
+		tokio::spawn(async move {
+			while let Ok((stream, _)) = listener.accept().await {
+				tokio::spawn(respond(stream, dir.clone()));
+			}
+		});
+		Ok(url)
+	}
+
+	async fn respond(mut stream: TcpStream, dir: Arc<PathBuf>) -> std::io::Result<()> {
+		let mut buffer = [0u8; 4096];
+		let read = stream.read(&mut buffer).await?;
+		let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+		let path = request.split_whitespace().nth(1).unwrap_or_default();
+		let range_start = request
+			.lines()
+			.find_map(|line| line.to_lowercase().strip_prefix("range: bytes=").map(str::to_string))
+			.and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+
+		let file = path.strip_prefix("/snapshot/").map(|path| dir.join(path));
+		let response = match (file.map(std::fs::read), range_start) {
+			(Some(Ok(body)), Some(start)) if start >= body.len() => {
+				"HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\n\r\n"
+					.as_bytes()
+					.to_vec()
+			}
+			(Some(Ok(body)), Some(start)) => {
+				let mut response = format!(
+					"HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\n\r\n",
+					body.len() - start
+				)
+				.into_bytes();
+				response.extend_from_slice(&body[start..]);
+				response
+			}
+			(Some(Ok(body)), None) => {
+				let mut response =
+					format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len())
+						.into_bytes();
+				response.extend(body);
+				response
+			}
+			_ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".as_bytes().to_vec(),
+		};
+		stream.write_all(&response).await?;
+		stream.shutdown().await
+	}
+
+	/// Creates the directories of a node and a snapshot of them.
+	fn snapshot(
+		node_dir: &Path,
+		snapshot_dir: &Path,
+	) -> Result<(BTreeMap<String, PathBuf>, Manifest), anyhow::Error> {
+		let roots = BTreeMap::from([
+			("maptos-db".to_string(), node_dir.join("maptos-db")),
+			("da-db".to_string(), node_dir.join("da-db")),
+		]);
+		std::fs::create_dir_all(node_dir.join("maptos-db/ledger"))?;
+		std::fs::create_dir_all(node_dir.join("da-db"))?;
+		std::fs::write(node_dir.join("maptos-db/ledger/000001.sst"), vec![7u8; 100])?;
+		std::fs::write(node_dir.join("maptos-db/CURRENT"), b"MANIFEST-000001")?;
+		std::fs::write(node_dir.join("da-db/000002.sst"), (0..250u8).collect::<Vec<_>>())?;
+		let manifest = create_snapshot(&roots, 64, snapshot_dir)?;
+		Ok((roots, manifest))
+	}
+
+	fn restored_roots(dir: &Path) -> BTreeMap<String, PathBuf> {
+		BTreeMap::from([
+			("maptos-db".to_string(), dir.join("maptos-db")),
+			("da-db".to_string(), dir.join("da-db")),
+		])
+	}
+
+	#[tokio::test]
+	async fn test_sync_resumes_and_verifies() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let snapshot_dir = dir.path().join("snapshot");
+		let (_, manifest) = snapshot(&dir.path().join("node"), &snapshot_dir)?;
+		assert_eq!(manifest.files.len(), 3);
+		assert_eq!(manifest.files[0].path, "da-db/000002.sst");
+		assert_eq!(manifest.files[0].chunks.len(), 4);
+		let url = serve(snapshot_dir.clone()).await?;
+		let manifest_hash = Manifest::hash(&std::fs::read(snapshot_dir.join(MANIFEST_FILE))?);
+
+		// a previous sync was interrupted in the middle of a chunk
+		let staging_dir = dir.path().join("staging");
+		let interrupted = &manifest.files[0].chunks[1];
+		std::fs::create_dir_all(staging_dir.join(CHUNKS_DIR))?;
+		let chunk = std::fs::read(snapshot_dir.join(CHUNKS_DIR).join(interrupted))?;
+		std::fs::write(
+			staging_dir.join(CHUNKS_DIR).join(format!("{interrupted}.part")),
+			&chunk[..10],
+		)?;
+
+		let unreachable = Url::parse("http://127.0.0.1:1/snapshot")?;
+		let state_sync = StateSync::new(vec![unreachable, url], &manifest_hash, &staging_dir);
+		assert!(state_sync.is_interrupted().await?);
+		// and left a partial root
+		let restored_dir = dir.path().join("restored");
+		std::fs::create_dir_all(restored_dir.join("maptos-db"))?;
+		std::fs::write(restored_dir.join("maptos-db/stale.sst"), b"stale")?;
+		assert_eq!(state_sync.sync(&restored_roots(&restored_dir)).await?, manifest);
+
+		assert_eq!(
+			std::fs::read(restored_dir.join("da-db/000002.sst"))?,
+			(0..250u8).collect::<Vec<_>>()
+		);
+		assert_eq!(
+			std::fs::read(restored_dir.join("maptos-db/ledger/000001.sst"))?,
+			vec![7u8; 100]
+		);
+		assert_eq!(std::fs::read(restored_dir.join("maptos-db/CURRENT"))?, b"MANIFEST-000001");
+		assert!(!restored_dir.join("maptos-db/stale.sst").exists());
+		assert!(!restored_dir.join("maptos-db.restoring").exists());
+		assert!(!state_sync.is_interrupted().await?);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_sync_rejects_corrupted_snapshot() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let snapshot_dir = dir.path().join("snapshot");
+		let (_, manifest) = snapshot(&dir.path().join("node"), &snapshot_dir)?;
+		let url = serve(snapshot_dir.clone()).await?;
+		let manifest_hash = Manifest::hash(&std::fs::read(snapshot_dir.join(MANIFEST_FILE))?);
+
+		// a manifest other than the pinned one is rejected
+		let another_hash = Manifest::hash(b"another manifest");
+		let state_sync =
+			StateSync::new(vec![url.clone()], &another_hash, dir.path().join("staging"));
+		assert!(matches!(
+			state_sync.fetch_manifest().await,
+			Err(StateSyncError::Unavailable { .. })
+		));
+
+		// a corrupted chunk is not restored
+		let corrupted = &manifest.files[1].chunks[0];
+		std::fs::write(snapshot_dir.join(CHUNKS_DIR).join(corrupted), b"corrupted")?;
+		let state_sync = StateSync::new(vec![url], &manifest_hash, dir.path().join("staging"));
+		let result = state_sync.sync(&restored_roots(&dir.path().join("restored"))).await;
+		assert!(matches!(result, Err(StateSyncError::Unavailable { .. })), "{result:?}");
+		assert!(!dir.path().join("restored").exists());
+		Ok(())
+	}
+
+	#[test]
+	fn test_sources_require_a_manifest_hash() -> Result<(), anyhow::Error> {
+		let mut config = suzuka_config::Config::default();
+		assert!(StateSync::try_from_config(&config)?.is_none());
+
+		config.state_sync.state_sync_sources = vec!["http://127.0.0.1:1/snapshot".to_string()];
+		assert!(matches!(StateSync::try_from_config(&config), Err(StateSyncError::Config(_))));
+
+		config.state_sync.state_sync_manifest_hash = Some(Manifest::hash(b"manifest"));
+		assert!(StateSync::try_from_config(&config)?.is_some());
+		Ok(())
+	}
+}
@@ -1,3 +1,52 @@
-fn main() {
-	println!("Hello, world!");
+use bridge_shared::types::{HashLockPreImage, Keccak256};
+use clap::{Parser, Subcommand};
+
+/// Tools for running the atomic swap protocol of the bridge by hand, against a devnet.
+#[derive(Debug, Clone, Parser)]
+pub struct Cli {
+	#[clap(subcommand)]
+	command: Command,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+	/// Secrets of the hash locks
+	#[clap(subcommand)]
+	Secret(SecretCommand),
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum SecretCommand {
+	/// Generates a random secret and its Keccak-256 hash lock
+	Gen,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+	match Cli::parse().command {
+		Command::Secret(SecretCommand::Gen) => {
+			let (secret, hash_lock) = HashLockPreImage::generate::<Keccak256>();
+			println!("secret: 0x{}", hex::encode(&secret.0));
+			println!("hash_lock: 0x{}", hex::encode(hash_lock.0));
+			Ok(())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::CommandFactory;
+
+	#[test]
+	fn verify_cli() {
+		Cli::command().debug_assert();
+	}
+
+	#[test]
+	fn test_parse_secret_command() {
+		let cli = Cli::parse_from(["bridge-cli", "secret", "gen"]);
+		assert!(matches!(cli.command, Command::Secret(SecretCommand::Gen)));
+		assert!(Cli::try_parse_from(["bridge-cli", "eth", "refund"]).is_err());
+	}
 }
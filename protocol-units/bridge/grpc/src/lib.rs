@@ -0,0 +1,3 @@
+tonic::include_proto!("movementlabs.protocol_units.bridge.admin.v1beta1");
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+	tonic::include_file_descriptor_set!("bridge-grpc-descriptor");
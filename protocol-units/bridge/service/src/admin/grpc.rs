@@ -0,0 +1,453 @@
+use std::{fmt, net::SocketAddr, sync::Arc};
+
+use bridge_grpc::{
+	bridge_admin_service_server::{BridgeAdminService, BridgeAdminServiceServer},
+	ApproveTransferRequest, ApproveTransferResponse, Blockchain, ForceRefundRequest,
+	ForceRefundResponse, GetTransferRequest, GetTransferResponse, ListInFlightTransfersRequest,
+	ListInFlightTransfersResponse, PauseProcessingRequest, PauseProcessingResponse,
+	ResumeProcessingRequest, ResumeProcessingResponse, RotateSignerRequest, RotateSignerResponse,
+	Transfer,
+};
+use bridge_shared::{
+	approval::Approval,
+	transfer_store::{TransferRecord, TransferState},
+	types::{BridgeHashType, BridgeTransferId},
+};
+use tonic::{service::Interceptor, transport::Server, Request, Response, Status};
+
+use super::{RelayerCommandError, RelayerHandle};
+
+/// Hashes of the transfer ids which can be exchanged as bytes over the admin API.
+pub trait AdminHash: BridgeHashType + AsRef<[u8]> + for<'a> TryFrom<&'a [u8]> + 'static {}
+
+impl<H> AdminHash for H where H: BridgeHashType + AsRef<[u8]> + for<'a> TryFrom<&'a [u8]> + 'static {}
+
+/// gRPC admin service of the relayer, forwarding the requests of the operators to a running
+/// relayer through its [`RelayerHandle`].
+#[derive(Debug)]
+pub struct AdminService<H1, H2> {
+	handle: RelayerHandle<H1, H2>,
+}
+
+impl<H1, H2> AdminService<H1, H2>
+where
+	H1: AdminHash,
+	H2: AdminHash,
+{
+	pub fn new(handle: RelayerHandle<H1, H2>) -> Self {
+		Self { handle }
+	}
+}
+
+/// Rejects the requests which don't carry the token of the operators, as an
+/// `authorization: Bearer <token>` header.
+#[derive(Clone)]
+pub struct TokenAuth {
+	token: Arc<str>,
+}
+
+impl TokenAuth {
+	pub fn new(token: impl Into<Arc<str>>) -> Result<Self, anyhow::Error> {
+		let token = token.into();
+		if token.trim().is_empty() {
+			anyhow::bail!("The admin API token is empty");
+		}
+		Ok(Self { token })
+	}
+}
+
+impl fmt::Debug for TokenAuth {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("TokenAuth").finish_non_exhaustive()
+	}
+}
+
+impl Interceptor for TokenAuth {
+	fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+		let token = request
+			.metadata()
+			.get("authorization")
+			.and_then(|value| value.to_str().ok())
+			.and_then(|value| value.strip_prefix("Bearer "));
+		match token {
+			Some(token) if constant_time_eq(token.as_bytes(), self.token.as_bytes()) => Ok(request),
+			Some(_) => Err(Status::unauthenticated("Invalid admin token")),
+			None => Err(Status::unauthenticated("Missing admin token")),
+		}
+	}
+}
+
+/// Compares the tokens in a time which doesn't depend on the position of their first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Serves the admin API of the relayer on `address`, with reflection.
+///
+/// The API can refund and approve transfers and replace signers, every request must carry `token`. It is served
+/// without TLS, and must only be reachable by the operators.
+pub async fn serve<H1, H2>(
+	handle: RelayerHandle<H1, H2>,
+	address: SocketAddr,
+	token: String,
+) -> Result<(), anyhow::Error>
+where
+	H1: AdminHash,
+	H2: AdminHash,
+{
+	let auth = TokenAuth::new(token)?;
+	let reflection = tonic_reflection::server::Builder::configure()
+		.register_encoded_file_descriptor_set(bridge_grpc::FILE_DESCRIPTOR_SET)
+		.build()?;
+
+	tracing::info!("Admin: serving on {address}");
+	Server::builder()
+		.add_service(BridgeAdminServiceServer::with_interceptor(AdminService::new(handle), auth))
+		.add_service(reflection)
+		.serve(address)
+		.await?;
+
+	Ok(())
+}
+
+fn status(error: RelayerCommandError) -> Status {
+	match error {
+		RelayerCommandError::Stopped => Status::unavailable(error.to_string()),
+		RelayerCommandError::TransferNotFound => Status::not_found(error.to_string()),
+		RelayerCommandError::Store(_) => Status::internal(error.to_string()),
+		RelayerCommandError::Refund(_)
+		| RelayerCommandError::SignerRotation(_)
+		| RelayerCommandError::NotPendingApproval
+		| RelayerCommandError::TimeLockElapsed(_) => Status::failed_precondition(error.to_string()),
+		RelayerCommandError::UnknownApprover(_) | RelayerCommandError::InvalidApproval => {
+			Status::permission_denied(error.to_string())
+		}
+	}
+}
+
+fn blockchain(blockchain: i32) -> Result<Blockchain, String> {
+	match Blockchain::try_from(blockchain) {
+		Ok(Blockchain::Unspecified) | Err(_) => Err(format!("Invalid blockchain {blockchain}")),
+		Ok(blockchain) => Ok(blockchain),
+	}
+}
+
+fn bridge_transfer_id<H: AdminHash>(bytes: &[u8]) -> Result<BridgeTransferId<H>, String> {
+	H::try_from(bytes)
+		.map(BridgeTransferId)
+		.map_err(|_| format!("Invalid bridge transfer id 0x{}", hex::encode(bytes)))
+}
+
+fn transfer<H: AdminHash>(
+	blockchain: Blockchain,
+	bridge_transfer_id: &BridgeTransferId<H>,
+	record: TransferRecord,
+) -> Transfer {
+	let state = match record.state {
+		TransferState::Initiated => bridge_grpc::TransferState::Initiated,
+		TransferState::PendingApproval => bridge_grpc::TransferState::PendingApproval,
+		TransferState::InsufficientLiquidity => bridge_grpc::TransferState::InsufficientLiquidity,
+		TransferState::Locked => bridge_grpc::TransferState::Locked,
+		TransferState::Completed => bridge_grpc::TransferState::Completed,
+		TransferState::Refunded => bridge_grpc::TransferState::Refunded,
+	};
+	Transfer {
+		blockchain: blockchain.into(),
+		bridge_transfer_id: bridge_transfer_id.0.as_ref().to_vec(),
+		state: state.into(),
+		initiator_time_lock: record.initiator_time_lock.0,
+		counterparty_time_lock: record.counterparty_time_lock.map(|time_lock| time_lock.0),
+	}
+}
+
+#[tonic::async_trait]
+impl<H1, H2> BridgeAdminService for AdminService<H1, H2>
+where
+	H1: AdminHash,
+	H2: AdminHash,
+{
+	async fn list_in_flight_transfers(
+		&self,
+		_request: Request<ListInFlightTransfersRequest>,
+	) -> Result<Response<ListInFlightTransfersResponse>, Status> {
+		let in_flight = self.handle.in_flight().await.map_err(status)?;
+		let transfers = in_flight
+			.b1
+			.into_iter()
+			.map(|(id, record)| transfer(Blockchain::Blockchain1, &id, record))
+			.chain(
+				in_flight
+					.b2
+					.into_iter()
+					.map(|(id, record)| transfer(Blockchain::Blockchain2, &id, record)),
+			)
+			.collect();
+		Ok(Response::new(ListInFlightTransfersResponse { transfers }))
+	}
+
+	async fn get_transfer(
+		&self,
+		request: Request<GetTransferRequest>,
+	) -> Result<Response<GetTransferResponse>, Status> {
+		let request = request.into_inner();
+		let transfer = match blockchain(request.blockchain).map_err(Status::invalid_argument)? {
+			Blockchain::Blockchain2 => {
+				let id = bridge_transfer_id::<H2>(&request.bridge_transfer_id)
+					.map_err(Status::invalid_argument)?;
+				let record = self.handle.transfer_b2(id.clone()).await.map_err(status)?;
+				transfer(Blockchain::Blockchain2, &id, record)
+			}
+			_ => {
+				let id = bridge_transfer_id::<H1>(&request.bridge_transfer_id)
+					.map_err(Status::invalid_argument)?;
+				let record = self.handle.transfer_b1(id.clone()).await.map_err(status)?;
+				transfer(Blockchain::Blockchain1, &id, record)
+			}
+		};
+		Ok(Response::new(GetTransferResponse { transfer: Some(transfer) }))
+	}
+
+	async fn force_refund(
+		&self,
+		request: Request<ForceRefundRequest>,
+	) -> Result<Response<ForceRefundResponse>, Status> {
+		let request = request.into_inner();
+		match blockchain(request.blockchain).map_err(Status::invalid_argument)? {
+			Blockchain::Blockchain2 => {
+				let id = bridge_transfer_id(&request.bridge_transfer_id)
+					.map_err(Status::invalid_argument)?;
+				self.handle.refund_b2(id).await.map_err(status)?;
+			}
+			_ => {
+				let id = bridge_transfer_id(&request.bridge_transfer_id)
+					.map_err(Status::invalid_argument)?;
+				self.handle.refund_b1(id).await.map_err(status)?;
+			}
+		}
+		Ok(Response::new(ForceRefundResponse {}))
+	}
+
+	async fn pause_processing(
+		&self,
+		_request: Request<PauseProcessingRequest>,
+	) -> Result<Response<PauseProcessingResponse>, Status> {
+		self.handle.pause().await.map_err(status)?;
+		Ok(Response::new(PauseProcessingResponse {}))
+	}
+
+	async fn resume_processing(
+		&self,
+		_request: Request<ResumeProcessingRequest>,
+	) -> Result<Response<ResumeProcessingResponse>, Status> {
+		self.handle.resume().await.map_err(status)?;
+		Ok(Response::new(ResumeProcessingResponse {}))
+	}
+
+	async fn rotate_signer(
+		&self,
+		request: Request<RotateSignerRequest>,
+	) -> Result<Response<RotateSignerResponse>, Status> {
+		let request = request.into_inner();
+		match blockchain(request.blockchain).map_err(Status::invalid_argument)? {
+			Blockchain::Blockchain2 => self.handle.rotate_signer_b2(request.signer).await,
+			_ => self.handle.rotate_signer_b1(request.signer).await,
+		}
+		.map_err(status)?;
+		Ok(Response::new(RotateSignerResponse {}))
+	}
+
+	async fn approve_transfer(
+		&self,
+		request: Request<ApproveTransferRequest>,
+	) -> Result<Response<ApproveTransferResponse>, Status> {
+		let request = request.into_inner();
+		let approval = Approval::from_parts(&request.public_key, &request.signature)
+			.map_err(|error| Status::invalid_argument(error.to_string()))?;
+		let approval = match blockchain(request.blockchain).map_err(Status::invalid_argument)? {
+			Blockchain::Blockchain2 => {
+				let id = bridge_transfer_id(&request.bridge_transfer_id)
+					.map_err(Status::invalid_argument)?;
+				self.handle.approve_b2(id, approval).await
+			}
+			_ => {
+				let id = bridge_transfer_id(&request.bridge_transfer_id)
+					.map_err(Status::invalid_argument)?;
+				self.handle.approve_b1(id, approval).await
+			}
+		}
+		.map_err(status)?;
+		Ok(Response::new(ApproveTransferResponse {
+			approvals: approval.approvals.try_into().unwrap_or(u32::MAX),
+			approvals_required: approval.approvals_required.try_into().unwrap_or(u32::MAX),
+			approved: approval.approved,
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::admin::{ApprovalStatus, RelayerCommand};
+	use bridge_shared::types::TimeLock;
+	use tokio::sync::mpsc;
+
+	fn record() -> TransferRecord {
+		TransferRecord {
+			state: TransferState::Locked,
+			initiator_time_lock: TimeLock(100),
+			counterparty_time_lock: Some(TimeLock(50)),
+		}
+	}
+
+	fn service() -> AdminService<[u8; 32], [u8; 32]> {
+		let (sender, mut commands) = mpsc::channel(1);
+		tokio::spawn(async move {
+			while let Some(command) = commands.recv().await {
+				match command {
+					RelayerCommand::TransferB2(id, reply) if id.0 == [1; 32] => {
+						let _ = reply.send(Ok(record()));
+					}
+					RelayerCommand::TransferB1(_, reply) | RelayerCommand::TransferB2(_, reply) => {
+						let _ = reply.send(Err(RelayerCommandError::TransferNotFound));
+					}
+					RelayerCommand::Pause(reply) | RelayerCommand::RotateSignerB1(_, reply) => {
+						let _ = reply.send(Ok(()));
+					}
+					RelayerCommand::RotateSignerB2(signer, reply) => {
+						let _ = reply.send(Err(RelayerCommandError::SignerRotation(signer)));
+					}
+					RelayerCommand::ApproveB1(_, approval, reply)
+						if approval.public_key == [0; 32] =>
+					{
+						let _ = reply.send(Err(RelayerCommandError::InvalidApproval));
+					}
+					RelayerCommand::ApproveB1(_, _, reply) => {
+						let _ = reply.send(Ok(ApprovalStatus {
+							approvals: 1,
+							approvals_required: 2,
+							approved: false,
+						}));
+					}
+					_ => {}
+				}
+			}
+		});
+		AdminService::new(RelayerHandle::new(sender))
+	}
+
+	#[tokio::test]
+	async fn test_get_transfer() {
+		let service = service();
+		let response = service
+			.get_transfer(Request::new(GetTransferRequest {
+				blockchain: Blockchain::Blockchain2.into(),
+				bridge_transfer_id: vec![1; 32],
+			}))
+			.await
+			.unwrap();
+		let transfer = response.into_inner().transfer.unwrap();
+		assert_eq!(transfer.blockchain(), Blockchain::Blockchain2);
+		assert_eq!(transfer.state(), bridge_grpc::TransferState::Locked);
+		assert_eq!(transfer.initiator_time_lock, 100);
+		assert_eq!(transfer.counterparty_time_lock, Some(50));
+
+		let error = service
+			.get_transfer(Request::new(GetTransferRequest {
+				blockchain: Blockchain::Blockchain1.into(),
+				bridge_transfer_id: vec![1; 32],
+			}))
+			.await
+			.unwrap_err();
+		assert_eq!(error.code(), tonic::Code::NotFound);
+	}
+
+	#[tokio::test]
+	async fn test_invalid_requests() {
+		let service = service();
+		let error = service
+			.get_transfer(Request::new(GetTransferRequest {
+				blockchain: Blockchain::Blockchain1.into(),
+				bridge_transfer_id: vec![1; 4],
+			}))
+			.await
+			.unwrap_err();
+		assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+		let error = service
+			.force_refund(Request::new(ForceRefundRequest {
+				blockchain: Blockchain::Unspecified.into(),
+				bridge_transfer_id: vec![1; 32],
+			}))
+			.await
+			.unwrap_err();
+		assert_eq!(error.code(), tonic::Code::InvalidArgument);
+	}
+
+	#[tokio::test]
+	async fn test_stopped_relayer() {
+		let (sender, commands) = mpsc::channel(1);
+		drop(commands);
+		let service = AdminService::<[u8; 32], [u8; 32]>::new(RelayerHandle::new(sender));
+		let error = service.pause_processing(Request::new(PauseProcessingRequest {})).await;
+		assert_eq!(error.unwrap_err().code(), tonic::Code::Unavailable);
+
+		let service = self::service();
+		assert!(service.pause_processing(Request::new(PauseProcessingRequest {})).await.is_ok());
+	}
+
+	#[tokio::test]
+	async fn test_rotate_signer() {
+		let service = service();
+		let request = |blockchain: Blockchain| {
+			Request::new(RotateSignerRequest {
+				blockchain: blockchain.into(),
+				signer: "0x01".to_string(),
+			})
+		};
+		assert!(service.rotate_signer(request(Blockchain::Blockchain1)).await.is_ok());
+
+		let error = service.rotate_signer(request(Blockchain::Blockchain2)).await.unwrap_err();
+		assert_eq!(error.code(), tonic::Code::FailedPrecondition);
+		let error = service.rotate_signer(request(Blockchain::Unspecified)).await.unwrap_err();
+		assert_eq!(error.code(), tonic::Code::InvalidArgument);
+	}
+
+	#[tokio::test]
+	async fn test_approve_transfer() {
+		let service = service();
+		let request = |public_key: Vec<u8>| {
+			Request::new(ApproveTransferRequest {
+				blockchain: Blockchain::Blockchain1.into(),
+				bridge_transfer_id: vec![1; 32],
+				public_key,
+				signature: vec![2; 64],
+			})
+		};
+		let response = service.approve_transfer(request(vec![1; 32])).await.unwrap().into_inner();
+		assert_eq!((response.approvals, response.approvals_required), (1, 2));
+		assert!(!response.approved);
+
+		let error = service.approve_transfer(request(vec![0; 32])).await.unwrap_err();
+		assert_eq!(error.code(), tonic::Code::PermissionDenied);
+		let error = service.approve_transfer(request(Vec::new())).await.unwrap_err();
+		assert_eq!(error.code(), tonic::Code::InvalidArgument);
+	}
+
+	#[test]
+	fn test_token_auth() {
+		let mut auth = TokenAuth::new("secret").unwrap();
+		let request = |authorization: Option<&str>| {
+			let mut request = Request::new(());
+			if let Some(authorization) = authorization {
+				request.metadata_mut().insert("authorization", authorization.parse().unwrap());
+			}
+			request
+		};
+		assert!(auth.call(request(Some("Bearer secret"))).is_ok());
+		for authorization in [None, Some("Bearer secreT"), Some("Bearer secret2"), Some("secret")] {
+			let error = auth.call(request(authorization)).unwrap_err();
+			assert_eq!(error.code(), tonic::Code::Unauthenticated);
+		}
+		assert!(TokenAuth::new(" ").is_err());
+	}
+}
@@ -0,0 +1,210 @@
+use bridge_shared::{
+	approval::Approval,
+	attestation::Attestation,
+	transfer_store::{TransferRecord, TransferStateStoreError},
+	types::{BridgeTransferId, TimeLockPolicyError},
+};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::health::HealthCheck;
+
+pub mod grpc;
+pub mod rest;
+
+/// Number of commands waiting for the relayer before the senders are slowed down.
+pub const COMMAND_BUFFER: usize = 16;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RelayerCommandError {
+	#[error("The relayer is not running")]
+	Stopped,
+	#[error("Transfer not found")]
+	TransferNotFound,
+	#[error("Transfer state store error: {0}")]
+	Store(#[from] TransferStateStoreError),
+	#[error("Failed to refund transfer: {0}")]
+	Refund(String),
+	#[error("Failed to rotate signer: {0}")]
+	SignerRotation(String),
+	#[error("Transfer is not pending approval")]
+	NotPendingApproval,
+	#[error("0x{0} is not an approver")]
+	UnknownApprover(String),
+	#[error("Invalid approval signature")]
+	InvalidApproval,
+	#[error("Transfer approved too late to lock its assets: {0}")]
+	TimeLockElapsed(TimeLockPolicyError),
+}
+
+pub type RelayerCommandResult<T> = Result<T, RelayerCommandError>;
+
+type Reply<T> = oneshot::Sender<RelayerCommandResult<T>>;
+
+/// Transfers which are neither completed nor refunded, by blockchain they were initiated on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InFlightTransfers<H1, H2> {
+	pub b1: Vec<(BridgeTransferId<H1>, TransferRecord)>,
+	pub b2: Vec<(BridgeTransferId<H2>, TransferRecord)>,
+}
+
+/// Approvals collected for a transfer pending approval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApprovalStatus {
+	pub approvals: usize,
+	pub approvals_required: usize,
+	/// Whether the transfer got enough approvals, and its assets are being locked.
+	pub approved: bool,
+}
+
+/// Operator commands handled by a running [`Relayer`](crate::Relayer), between two bridge events.
+///
+/// Transfers are identified on the blockchain they were initiated on.
+#[derive(Debug)]
+pub enum RelayerCommand<H1, H2> {
+	InFlight(Reply<InFlightTransfers<H1, H2>>),
+	TransferB1(BridgeTransferId<H1>, Reply<TransferRecord>),
+	TransferB2(BridgeTransferId<H2>, Reply<TransferRecord>),
+	/// Attestations signed by the relayer of the transitions of a transfer.
+	AttestationsB1(BridgeTransferId<H1>, Reply<Vec<Attestation>>),
+	AttestationsB2(BridgeTransferId<H2>, Reply<Vec<Attestation>>),
+	RefundB1(BridgeTransferId<H1>, Reply<()>),
+	RefundB2(BridgeTransferId<H2>, Reply<()>),
+	Pause(Reply<()>),
+	Resume(Reply<()>),
+	RotateSignerB1(String, Reply<()>),
+	RotateSignerB2(String, Reply<()>),
+	/// Approval of a transfer pending approval, signed by an approver.
+	ApproveB1(BridgeTransferId<H1>, Approval, Reply<ApprovalStatus>),
+	ApproveB2(BridgeTransferId<H2>, Approval, Reply<ApprovalStatus>),
+	/// Checks of the transfer stores of the relayer.
+	Health(Reply<Vec<HealthCheck>>),
+}
+
+/// Sends [`RelayerCommand`]s to a running relayer and waits for their results.
+#[derive(Debug)]
+pub struct RelayerHandle<H1, H2> {
+	sender: mpsc::Sender<RelayerCommand<H1, H2>>,
+}
+
+impl<H1, H2> Clone for RelayerHandle<H1, H2> {
+	fn clone(&self) -> Self {
+		Self { sender: self.sender.clone() }
+	}
+}
+
+impl<H1, H2> RelayerHandle<H1, H2> {
+	pub fn new(sender: mpsc::Sender<RelayerCommand<H1, H2>>) -> Self {
+		Self { sender }
+	}
+
+	/// Whether the relayer still accepts commands.
+	pub fn is_running(&self) -> bool {
+		!self.sender.is_closed()
+	}
+
+	async fn send<T>(
+		&self,
+		command: impl FnOnce(Reply<T>) -> RelayerCommand<H1, H2>,
+	) -> RelayerCommandResult<T> {
+		let (reply, result) = oneshot::channel();
+		self.sender
+			.send(command(reply))
+			.await
+			.map_err(|_| RelayerCommandError::Stopped)?;
+		result.await.map_err(|_| RelayerCommandError::Stopped)?
+	}
+
+	pub async fn in_flight(&self) -> RelayerCommandResult<InFlightTransfers<H1, H2>> {
+		self.send(RelayerCommand::InFlight).await
+	}
+
+	pub async fn transfer_b1(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H1>,
+	) -> RelayerCommandResult<TransferRecord> {
+		self.send(|reply| RelayerCommand::TransferB1(bridge_transfer_id, reply)).await
+	}
+
+	pub async fn transfer_b2(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H2>,
+	) -> RelayerCommandResult<TransferRecord> {
+		self.send(|reply| RelayerCommand::TransferB2(bridge_transfer_id, reply)).await
+	}
+
+	pub async fn attestations_b1(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H1>,
+	) -> RelayerCommandResult<Vec<Attestation>> {
+		self.send(|reply| RelayerCommand::AttestationsB1(bridge_transfer_id, reply))
+			.await
+	}
+
+	pub async fn attestations_b2(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H2>,
+	) -> RelayerCommandResult<Vec<Attestation>> {
+		self.send(|reply| RelayerCommand::AttestationsB2(bridge_transfer_id, reply))
+			.await
+	}
+
+	/// Refunds a transfer initiated on the first blockchain, whether or not its time lock is
+	/// known to have expired. The refund is confirmed by the contract event.
+	pub async fn refund_b1(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H1>,
+	) -> RelayerCommandResult<()> {
+		self.send(|reply| RelayerCommand::RefundB1(bridge_transfer_id, reply)).await
+	}
+
+	pub async fn refund_b2(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H2>,
+	) -> RelayerCommandResult<()> {
+		self.send(|reply| RelayerCommand::RefundB2(bridge_transfer_id, reply)).await
+	}
+
+	/// Stops processing the bridge events and the expired time locks. The events are not lost,
+	/// they are processed once resumed.
+	pub async fn pause(&self) -> RelayerCommandResult<()> {
+		self.send(RelayerCommand::Pause).await
+	}
+
+	pub async fn resume(&self) -> RelayerCommandResult<()> {
+		self.send(RelayerCommand::Resume).await
+	}
+
+	pub async fn rotate_signer_b1(&self, signer: String) -> RelayerCommandResult<()> {
+		self.send(|reply| RelayerCommand::RotateSignerB1(signer, reply)).await
+	}
+
+	pub async fn rotate_signer_b2(&self, signer: String) -> RelayerCommandResult<()> {
+		self.send(|reply| RelayerCommand::RotateSignerB2(signer, reply)).await
+	}
+
+	/// Approves a transfer initiated on the first blockchain which is pending approval, with the
+	/// approval signed by one of the approvers of the relayer. Its assets are locked once it is
+	/// approved by the number of approvers the relayer requires.
+	pub async fn approve_b1(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H1>,
+		approval: Approval,
+	) -> RelayerCommandResult<ApprovalStatus> {
+		self.send(|reply| RelayerCommand::ApproveB1(bridge_transfer_id, approval, reply))
+			.await
+	}
+
+	pub async fn approve_b2(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H2>,
+		approval: Approval,
+	) -> RelayerCommandResult<ApprovalStatus> {
+		self.send(|reply| RelayerCommand::ApproveB2(bridge_transfer_id, approval, reply))
+			.await
+	}
+
+	pub async fn health(&self) -> RelayerCommandResult<Vec<HealthCheck>> {
+		self.send(RelayerCommand::Health).await
+	}
+}
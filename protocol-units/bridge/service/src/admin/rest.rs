@@ -0,0 +1,737 @@
+use std::{fmt::Debug, net::SocketAddr, sync::Arc};
+
+use axum::{
+	extract::{Path, Query, State},
+	http::StatusCode,
+	routing::get,
+	Json, Router,
+};
+use bridge_shared::{
+	attestation::Attestation,
+	blockchain_service::BlockchainService,
+	transfer_index::{
+		IndexedEvent, IndexedEventKind, IndexedTransfer, TransferIndex, TransferQuery,
+		DEFAULT_QUERY_LIMIT,
+	},
+	transfer_store::{TransferRecord, TransferState},
+	transfer_view::{OnChain, TransferView},
+	types::{BridgeTransferDetails, BridgeTransferId, LockDetails},
+};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use super::{grpc::AdminHash, RelayerCommandError, RelayerHandle};
+use crate::health::{HealthCheck, HealthProbes, HealthReport};
+
+/// Contracts queried for the on-chain state of the transfers, cloned from the blockchain services
+/// of the relayer.
+pub struct BridgeContracts<B1: BlockchainService, B2: BlockchainService> {
+	pub initiator_1: B1::InitiatorContract,
+	pub counterparty_1: B1::CounterpartyContract,
+	pub initiator_2: B2::InitiatorContract,
+	pub counterparty_2: B2::CounterpartyContract,
+}
+
+impl<B1: BlockchainService, B2: BlockchainService> Clone for BridgeContracts<B1, B2> {
+	fn clone(&self) -> Self {
+		Self {
+			initiator_1: self.initiator_1.clone(),
+			counterparty_1: self.counterparty_1.clone(),
+			initiator_2: self.initiator_2.clone(),
+			counterparty_2: self.counterparty_2.clone(),
+		}
+	}
+}
+
+/// State of a transfer on one of the contracts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum OnChainTransfer {
+	Found {
+		/// Only known on the initiator contract.
+		#[serde(skip_serializing_if = "Option::is_none")]
+		initiator_address: Option<String>,
+		recipient_address: String,
+		hash_lock: String,
+		time_lock: u64,
+		/// The amount, as a string as it may not fit the numbers of JSON parsers.
+		amount: String,
+	},
+	NotFound,
+	Error {
+		error: String,
+	},
+}
+
+impl OnChainTransfer {
+	fn from_initiator<A: Debug, H: AsRef<[u8]>>(
+		details: OnChain<BridgeTransferDetails<A, H>>,
+	) -> Self {
+		match details {
+			OnChain::Found(details) => OnChainTransfer::Found {
+				initiator_address: Some(format!("{:?}", details.initiator_address.0)),
+				recipient_address: format!("0x{}", hex::encode(&details.recipient_address.0)),
+				hash_lock: format!("0x{}", hex::encode(details.hash_lock.0.as_ref())),
+				time_lock: details.time_lock.0,
+				amount: details.amount.0.to_string(),
+			},
+			OnChain::NotFound => OnChainTransfer::NotFound,
+			OnChain::Error(error) => OnChainTransfer::Error { error },
+		}
+	}
+
+	fn from_counterparty<H: AsRef<[u8]>>(details: OnChain<LockDetails<H>>) -> Self {
+		match details {
+			OnChain::Found(details) => OnChainTransfer::Found {
+				initiator_address: None,
+				recipient_address: format!("0x{}", hex::encode(&details.recipient_address.0)),
+				hash_lock: format!("0x{}", hex::encode(details.hash_lock.0.as_ref())),
+				time_lock: details.time_lock.0,
+				amount: details.amount.0.to_string(),
+			},
+			OnChain::NotFound => OnChainTransfer::NotFound,
+			OnChain::Error(error) => OnChainTransfer::Error { error },
+		}
+	}
+}
+
+/// A transfer stored by the relayer, merged with its state on the contracts of both blockchains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransferStatus {
+	/// Blockchain the transfer was initiated on, `B1` or `B2`.
+	pub blockchain: &'static str,
+	pub bridge_transfer_id: String,
+	pub state: &'static str,
+	pub initiator_time_lock: u64,
+	pub counterparty_time_lock: Option<u64>,
+	/// The transfer on the initiator contract, of the blockchain it was initiated on.
+	pub initiator: OnChainTransfer,
+	/// The assets locked on the counterparty contract, of the other blockchain.
+	pub counterparty: OnChainTransfer,
+}
+
+impl TransferStatus {
+	pub fn new<A, H1, H2>(blockchain: &'static str, view: TransferView<A, H1, H2>) -> Self
+	where
+		A: Debug,
+		H1: AsRef<[u8]>,
+		H2: AsRef<[u8]>,
+	{
+		let TransferView { bridge_transfer_id, record, initiator, counterparty } = view;
+		Self {
+			blockchain,
+			bridge_transfer_id: format!("0x{}", hex::encode(bridge_transfer_id.0.as_ref())),
+			state: state_name(record.state),
+			initiator_time_lock: record.initiator_time_lock.0,
+			counterparty_time_lock: record.counterparty_time_lock.map(|time_lock| time_lock.0),
+			initiator: OnChainTransfer::from_initiator(initiator),
+			counterparty: OnChainTransfer::from_counterparty(counterparty),
+		}
+	}
+}
+
+fn state_name(state: TransferState) -> &'static str {
+	match state {
+		TransferState::Initiated => "initiated",
+		TransferState::PendingApproval => "pending_approval",
+		TransferState::InsufficientLiquidity => "insufficient_liquidity",
+		TransferState::Locked => "locked",
+		TransferState::Completed => "completed",
+		TransferState::Refunded => "refunded",
+	}
+}
+
+/// Transition of a transfer attested by the relayer, which is verified by checking `signature`
+/// over `message` with `public_key`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransferAttestation {
+	/// Blockchain the transition was observed on, `B1` or `B2`.
+	pub blockchain: String,
+	pub bridge_transfer_id: String,
+	pub state: &'static str,
+	/// Time of the blockchain the transition was observed at, in the unit of its time locks.
+	pub observed_at: u64,
+	/// The signed message, hex encoded.
+	pub message: String,
+	pub public_key: String,
+	pub signature: String,
+}
+
+impl From<Attestation> for TransferAttestation {
+	fn from(attestation: Attestation) -> Self {
+		let statement = &attestation.statement;
+		Self {
+			blockchain: statement.chain.clone(),
+			bridge_transfer_id: format!("0x{}", hex::encode(&statement.bridge_transfer_id)),
+			state: state_name(statement.state),
+			observed_at: statement.observed_at,
+			message: format!("0x{}", hex::encode(statement.message())),
+			public_key: format!("0x{}", hex::encode(attestation.public_key)),
+			signature: format!("0x{}", hex::encode(attestation.signature)),
+		}
+	}
+}
+
+/// A transfer of the transfer index, joined from its events on both blockchains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchivedTransfer {
+	/// Blockchain the transfer was initiated on, `B1` or `B2`.
+	pub blockchain: String,
+	pub bridge_transfer_id: String,
+	pub initiator_address: String,
+	pub recipient_address: String,
+	pub amount: String,
+	pub time_lock: u64,
+	/// Kind of the last event which moved the transfer forward.
+	pub status: &'static str,
+	pub initiated_at: u64,
+	pub updated_at: u64,
+}
+
+impl From<IndexedTransfer> for ArchivedTransfer {
+	fn from(transfer: IndexedTransfer) -> Self {
+		Self {
+			blockchain: transfer.chain,
+			bridge_transfer_id: format!("0x{}", hex::encode(&transfer.bridge_transfer_id)),
+			initiator_address: transfer.initiator_address,
+			recipient_address: transfer.recipient_address,
+			amount: transfer.amount.0.to_string(),
+			time_lock: transfer.time_lock.0,
+			status: transfer.status.name(),
+			initiated_at: transfer.initiated_at,
+			updated_at: transfer.updated_at,
+		}
+	}
+}
+
+/// A contract event of the transfer index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchivedEvent {
+	/// Blockchain the event was observed on, `B1` or `B2`.
+	pub blockchain: String,
+	pub kind: &'static str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub initiator_address: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub recipient_address: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub amount: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub time_lock: Option<u64>,
+	pub observed_at: u64,
+}
+
+impl From<IndexedEvent> for ArchivedEvent {
+	fn from(event: IndexedEvent) -> Self {
+		Self {
+			blockchain: event.chain,
+			kind: event.kind.name(),
+			initiator_address: event.initiator_address,
+			recipient_address: event.recipient_address,
+			amount: event.amount.map(|amount| amount.0.to_string()),
+			time_lock: event.time_lock.map(|time_lock| time_lock.0),
+			observed_at: event.observed_at,
+		}
+	}
+}
+
+/// An archived transfer with its events on both blockchains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchivedTransferHistory {
+	#[serde(flatten)]
+	pub transfer: ArchivedTransfer,
+	pub events: Vec<ArchivedEvent>,
+}
+
+/// Maximum number of archived transfers returned by a query.
+pub const MAX_ARCHIVE_LIMIT: usize = 1_000;
+
+/// Filters of `GET /archive/transfers`, matching [`TransferQuery`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ArchiveQuery {
+	pub address: Option<String>,
+	pub status: Option<String>,
+	pub from: Option<u64>,
+	pub to: Option<u64>,
+	pub limit: Option<usize>,
+}
+
+impl ArchiveQuery {
+	fn to_transfer_query(&self) -> Result<TransferQuery, ApiError> {
+		let status = self
+			.status
+			.as_deref()
+			.map(|status| {
+				IndexedEventKind::from_name(status).ok_or_else(|| {
+					api_error(StatusCode::BAD_REQUEST, format!("Invalid status {status}"))
+				})
+			})
+			.transpose()?;
+		Ok(TransferQuery {
+			address: self.address.clone(),
+			status,
+			from: self.from,
+			to: self.to,
+			limit: self.limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_ARCHIVE_LIMIT),
+		})
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorResponse {
+	pub error: String,
+}
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn api_error(status: StatusCode, error: impl ToString) -> ApiError {
+	(status, Json(ErrorResponse { error: error.to_string() }))
+}
+
+fn relayer_error(error: RelayerCommandError) -> ApiError {
+	match error {
+		RelayerCommandError::Stopped => api_error(StatusCode::SERVICE_UNAVAILABLE, error),
+		RelayerCommandError::TransferNotFound => api_error(StatusCode::NOT_FOUND, error),
+		_ => api_error(StatusCode::INTERNAL_SERVER_ERROR, error),
+	}
+}
+
+struct StatusState<B1: BlockchainService, B2: BlockchainService> {
+	handle: RelayerHandle<B1::Hash, B2::Hash>,
+	contracts: BridgeContracts<B1, B2>,
+	probes: HealthProbes,
+	transfer_index: Option<Arc<dyn TransferIndex>>,
+}
+
+impl<B1: BlockchainService, B2: BlockchainService> Clone for StatusState<B1, B2> {
+	fn clone(&self) -> Self {
+		Self {
+			handle: self.handle.clone(),
+			contracts: self.contracts.clone(),
+			probes: self.probes.clone(),
+			transfer_index: self.transfer_index.clone(),
+		}
+	}
+}
+
+impl<B1: BlockchainService, B2: BlockchainService> StatusState<B1, B2> {
+	fn transfer_index(&self) -> Result<&dyn TransferIndex, ApiError> {
+		self.transfer_index
+			.as_deref()
+			.ok_or_else(|| api_error(StatusCode::NOT_FOUND, "The transfer index is disabled"))
+	}
+}
+
+impl<B1, B2> StatusState<B1, B2>
+where
+	B1: BlockchainService + 'static,
+	B2: BlockchainService + 'static,
+	B1::Hash: AdminHash + From<B2::Hash>,
+	B2::Hash: AdminHash + From<B1::Hash>,
+{
+	async fn status_b1(
+		&self,
+		bridge_transfer_id: BridgeTransferId<B1::Hash>,
+		record: TransferRecord,
+	) -> TransferStatus {
+		let (mut initiator, mut counterparty) =
+			(self.contracts.initiator_1.clone(), self.contracts.counterparty_2.clone());
+		let view =
+			TransferView::query(bridge_transfer_id, record, &mut initiator, &mut counterparty)
+				.await;
+		TransferStatus::new("B1", view)
+	}
+
+	async fn status_b2(
+		&self,
+		bridge_transfer_id: BridgeTransferId<B2::Hash>,
+		record: TransferRecord,
+	) -> TransferStatus {
+		let (mut initiator, mut counterparty) =
+			(self.contracts.initiator_2.clone(), self.contracts.counterparty_1.clone());
+		let view =
+			TransferView::query(bridge_transfer_id, record, &mut initiator, &mut counterparty)
+				.await;
+		TransferStatus::new("B2", view)
+	}
+}
+
+/// Returns the router of the status API:
+///
+/// - `GET /health` or `GET /healthz`: whether the relayer is running.
+/// - `GET /readyz`: the checks of the transfer stores and of the `probes`, failing with a 503
+///   unless all of them pass.
+/// - `GET /transfers`: the in-flight transfers.
+/// - `GET /transfers/:id`: a transfer by its hex encoded id, looked up on both blockchains.
+/// - `GET /transfers/:id/attestations`: the attestations of the transitions of a transfer.
+/// - `GET /archive/transfers`: the transfers of the `transfer_index`, filtered by the
+///   `address` of their initiator or recipient, their `status` and the `from` and `to` bounds of
+///   their initiation time, returning at most `limit` transfers, most recent first.
+/// - `GET /archive/transfers/:id`: an archived transfer with its events on both blockchains.
+pub fn router<B1, B2>(
+	handle: RelayerHandle<B1::Hash, B2::Hash>,
+	contracts: BridgeContracts<B1, B2>,
+	probes: HealthProbes,
+	transfer_index: Option<Arc<dyn TransferIndex>>,
+) -> Router
+where
+	B1: BlockchainService + 'static,
+	B2: BlockchainService + 'static,
+	B1::Hash: AdminHash + From<B2::Hash>,
+	B2::Hash: AdminHash + From<B1::Hash>,
+{
+	Router::new()
+		.route("/health", get(health::<B1, B2>))
+		.route("/healthz", get(health::<B1, B2>))
+		.route("/readyz", get(ready::<B1, B2>))
+		.route("/transfers", get(list_transfers::<B1, B2>))
+		.route("/transfers/:id", get(get_transfer::<B1, B2>))
+		.route("/transfers/:id/attestations", get(get_attestations::<B1, B2>))
+		.route("/archive/transfers", get(list_archived_transfers::<B1, B2>))
+		.route("/archive/transfers/:id", get(get_archived_transfer::<B1, B2>))
+		.with_state(StatusState { handle, contracts, probes, transfer_index })
+}
+
+/// Serves the status API of the relayer on `address`.
+pub async fn serve<B1, B2>(
+	handle: RelayerHandle<B1::Hash, B2::Hash>,
+	contracts: BridgeContracts<B1, B2>,
+	probes: HealthProbes,
+	transfer_index: Option<Arc<dyn TransferIndex>>,
+	address: SocketAddr,
+) -> Result<(), anyhow::Error>
+where
+	B1: BlockchainService + 'static,
+	B2: BlockchainService + 'static,
+	B1::Hash: AdminHash + From<B2::Hash>,
+	B2::Hash: AdminHash + From<B1::Hash>,
+{
+	tracing::info!("Status: serving on http://{address}");
+	axum::Server::bind(&address)
+		.serve(router(handle, contracts, probes, transfer_index).into_make_service())
+		.await?;
+	Ok(())
+}
+
+async fn health<B1, B2>(State(state): State<StatusState<B1, B2>>) -> (StatusCode, &'static str)
+where
+	B1: BlockchainService,
+	B2: BlockchainService,
+{
+	if state.handle.is_running() {
+		(StatusCode::OK, "OK")
+	} else {
+		(StatusCode::SERVICE_UNAVAILABLE, "Relayer stopped")
+	}
+}
+
+async fn ready<B1, B2>(State(state): State<StatusState<B1, B2>>) -> (StatusCode, Json<HealthReport>)
+where
+	B1: BlockchainService,
+	B2: BlockchainService,
+{
+	let (relayer, probes) = futures::join!(state.handle.health(), state.probes.check());
+	let mut checks =
+		relayer.unwrap_or_else(|error| vec![HealthCheck::new("relayer", Err(error.to_string()))]);
+	checks.extend(probes);
+	let report = HealthReport::new(checks);
+	let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+	(status, Json(report))
+}
+
+async fn list_transfers<B1, B2>(
+	State(state): State<StatusState<B1, B2>>,
+) -> Result<Json<Vec<TransferStatus>>, ApiError>
+where
+	B1: BlockchainService + 'static,
+	B2: BlockchainService + 'static,
+	B1::Hash: AdminHash + From<B2::Hash>,
+	B2::Hash: AdminHash + From<B1::Hash>,
+{
+	let in_flight = state.handle.in_flight().await.map_err(relayer_error)?;
+	let (mut transfers, transfers_2) = futures::join!(
+		join_all(in_flight.b1.into_iter().map(|(id, record)| state.status_b1(id, record))),
+		join_all(in_flight.b2.into_iter().map(|(id, record)| state.status_b2(id, record))),
+	);
+	transfers.extend(transfers_2);
+	Ok(Json(transfers))
+}
+
+async fn get_transfer<B1, B2>(
+	State(state): State<StatusState<B1, B2>>,
+	Path(id): Path<String>,
+) -> Result<Json<TransferStatus>, ApiError>
+where
+	B1: BlockchainService + 'static,
+	B2: BlockchainService + 'static,
+	B1::Hash: AdminHash + From<B2::Hash>,
+	B2::Hash: AdminHash + From<B1::Hash>,
+{
+	let (id_1, id_2) = parse_transfer_id::<B1, B2>(&id)?;
+	if let Some(id) = id_1 {
+		match state.handle.transfer_b1(id.clone()).await {
+			Ok(record) => return Ok(Json(state.status_b1(id, record).await)),
+			Err(RelayerCommandError::TransferNotFound) => {}
+			Err(error) => return Err(relayer_error(error)),
+		}
+	}
+	if let Some(id) = id_2 {
+		match state.handle.transfer_b2(id.clone()).await {
+			Ok(record) => return Ok(Json(state.status_b2(id, record).await)),
+			Err(RelayerCommandError::TransferNotFound) => {}
+			Err(error) => return Err(relayer_error(error)),
+		}
+	}
+	Err(relayer_error(RelayerCommandError::TransferNotFound))
+}
+
+async fn get_attestations<B1, B2>(
+	State(state): State<StatusState<B1, B2>>,
+	Path(id): Path<String>,
+) -> Result<Json<Vec<TransferAttestation>>, ApiError>
+where
+	B1: BlockchainService + 'static,
+	B2: BlockchainService + 'static,
+	B1::Hash: AdminHash + From<B2::Hash>,
+	B2::Hash: AdminHash + From<B1::Hash>,
+{
+	let (id_1, id_2) = parse_transfer_id::<B1, B2>(&id)?;
+	let mut attestations = Err(RelayerCommandError::TransferNotFound);
+	if let Some(id) = id_1 {
+		attestations = state.handle.attestations_b1(id).await;
+	}
+	if let (Err(RelayerCommandError::TransferNotFound), Some(id)) = (&attestations, id_2) {
+		attestations = state.handle.attestations_b2(id).await;
+	}
+	let attestations = attestations.map_err(relayer_error)?;
+	Ok(Json(attestations.into_iter().map(TransferAttestation::from).collect()))
+}
+
+async fn list_archived_transfers<B1, B2>(
+	State(state): State<StatusState<B1, B2>>,
+	Query(query): Query<ArchiveQuery>,
+) -> Result<Json<Vec<ArchivedTransfer>>, ApiError>
+where
+	B1: BlockchainService,
+	B2: BlockchainService,
+{
+	let query = query.to_transfer_query()?;
+	let transfers = state
+		.transfer_index()?
+		.transfers(&query)
+		.await
+		.map_err(|error| api_error(StatusCode::INTERNAL_SERVER_ERROR, error))?;
+	Ok(Json(transfers.into_iter().map(ArchivedTransfer::from).collect()))
+}
+
+async fn get_archived_transfer<B1, B2>(
+	State(state): State<StatusState<B1, B2>>,
+	Path(id): Path<String>,
+) -> Result<Json<ArchivedTransferHistory>, ApiError>
+where
+	B1: BlockchainService,
+	B2: BlockchainService,
+{
+	let bridge_transfer_id = hex::decode(id.trim_start_matches("0x")).map_err(|_| {
+		api_error(StatusCode::BAD_REQUEST, format!("Invalid bridge transfer id {id}"))
+	})?;
+	let transfer_index = state.transfer_index()?;
+	let internal_error = |error| api_error(StatusCode::INTERNAL_SERVER_ERROR, error);
+	let transfer = transfer_index
+		.transfer(&bridge_transfer_id)
+		.await
+		.map_err(internal_error)?
+		.ok_or_else(|| relayer_error(RelayerCommandError::TransferNotFound))?;
+	let events = transfer_index.events(&bridge_transfer_id).await.map_err(internal_error)?;
+	Ok(Json(ArchivedTransferHistory {
+		transfer: transfer.into(),
+		events: events.into_iter().map(ArchivedEvent::from).collect(),
+	}))
+}
+
+/// Bridge transfer id parsed as an id of each blockchain, if valid on it.
+type TransferIds<H1, H2> = (Option<BridgeTransferId<H1>>, Option<BridgeTransferId<H2>>);
+
+/// Parses a hex encoded bridge transfer id, as an id of either blockchain.
+fn parse_transfer_id<B1: BlockchainService, B2: BlockchainService>(
+	id: &str,
+) -> Result<TransferIds<B1::Hash, B2::Hash>, ApiError>
+where
+	B1::Hash: AdminHash,
+	B2::Hash: AdminHash,
+{
+	let invalid_id =
+		|| api_error(StatusCode::BAD_REQUEST, format!("Invalid bridge transfer id {id}"));
+	let bytes = hex::decode(id.trim_start_matches("0x")).map_err(|_| invalid_id())?;
+	let id_1 = B1::Hash::try_from(bytes.as_slice()).map(BridgeTransferId).ok();
+	let id_2 = B2::Hash::try_from(bytes.as_slice()).map(BridgeTransferId).ok();
+	if id_1.is_none() && id_2.is_none() {
+		return Err(invalid_id());
+	}
+	Ok((id_1, id_2))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bridge_shared::attestation::{AttestationStatement, Attestor};
+	use bridge_shared::types::{Amount, HashLock, InitiatorAddress, RecipientAddress, TimeLock};
+
+	#[test]
+	fn test_transfer_status_json() {
+		let details = BridgeTransferDetails {
+			bridge_transfer_id: BridgeTransferId([1u8; 2]),
+			initiator_address: InitiatorAddress("initiator"),
+			recipient_address: RecipientAddress(vec![0xab]),
+			hash_lock: HashLock([2u8; 2]),
+			time_lock: TimeLock(100),
+			amount: Amount(u128::MAX),
+			token: None,
+		};
+		let record = TransferRecord {
+			state: TransferState::Initiated,
+			initiator_time_lock: TimeLock(100),
+			counterparty_time_lock: None,
+		};
+		let status = TransferStatus::new(
+			"B1",
+			TransferView {
+				bridge_transfer_id: BridgeTransferId([1u8; 2]),
+				record,
+				initiator: OnChain::Found(details),
+				counterparty: OnChain::<LockDetails<[u8; 2]>>::from_result(Err::<_, &str>(
+					"timed out",
+				)),
+			},
+		);
+
+		assert_eq!(
+			serde_json::to_value(status).unwrap(),
+			serde_json::json!({
+				"blockchain": "B1",
+				"bridge_transfer_id": "0x0101",
+				"state": "initiated",
+				"initiator_time_lock": 100,
+				"counterparty_time_lock": null,
+				"initiator": {
+					"status": "found",
+					"initiator_address": "\"initiator\"",
+					"recipient_address": "0xab",
+					"hash_lock": "0x0202",
+					"time_lock": 100,
+					"amount": u128::MAX.to_string(),
+				},
+				"counterparty": { "status": "error", "error": "timed out" },
+			})
+		);
+		assert_eq!(
+			serde_json::to_value(OnChainTransfer::from_counterparty(OnChain::Found(LockDetails {
+				bridge_transfer_id: BridgeTransferId([1u8; 2]),
+				recipient_address: RecipientAddress(vec![0xab]),
+				hash_lock: HashLock([2u8; 2]),
+				time_lock: TimeLock(50),
+				amount: Amount(1000),
+			})))
+			.unwrap(),
+			serde_json::json!({
+				"status": "found",
+				"recipient_address": "0xab",
+				"hash_lock": "0x0202",
+				"time_lock": 50,
+				"amount": "1000",
+			})
+		);
+		assert_eq!(
+			serde_json::to_value(OnChainTransfer::from_counterparty(
+				OnChain::<LockDetails<[u8; 2]>>::NotFound
+			))
+			.unwrap(),
+			serde_json::json!({ "status": "not_found" })
+		);
+	}
+
+	#[test]
+	fn test_transfer_attestation_json() {
+		let attestor = Attestor::new(&[7; 32]);
+		let attestation = attestor.attest(AttestationStatement {
+			chain: "B2".to_string(),
+			bridge_transfer_id: vec![1, 1],
+			state: TransferState::Locked,
+			observed_at: 42,
+		});
+		let json = serde_json::to_value(TransferAttestation::from(attestation.clone())).unwrap();
+
+		assert_eq!(json["blockchain"], "B2");
+		assert_eq!(json["bridge_transfer_id"], "0x0101");
+		assert_eq!(json["state"], "locked");
+		assert_eq!(json["observed_at"], 42);
+		assert_eq!(json["message"], format!("0x{}", hex::encode(attestation.statement.message())));
+		assert_eq!(json["public_key"], format!("0x{}", hex::encode(attestor.public_key())));
+		assert_eq!(json["signature"], format!("0x{}", hex::encode(attestation.signature)));
+	}
+
+	#[test]
+	fn test_archived_transfer_json() {
+		let history = ArchivedTransferHistory {
+			transfer: ArchivedTransfer::from(IndexedTransfer {
+				chain: "B1".to_string(),
+				bridge_transfer_id: vec![1, 1],
+				initiator_address: "\"initiator\"".to_string(),
+				recipient_address: "0xab".to_string(),
+				amount: Amount(u128::MAX),
+				time_lock: TimeLock(100),
+				status: IndexedEventKind::Locked,
+				initiated_at: 10,
+				updated_at: 12,
+			}),
+			events: vec![ArchivedEvent::from(IndexedEvent {
+				chain: "B2".to_string(),
+				kind: IndexedEventKind::Locked,
+				bridge_transfer_id: vec![1, 1],
+				initiator_address: None,
+				recipient_address: Some("0xab".to_string()),
+				amount: Some(Amount(1000)),
+				time_lock: Some(TimeLock(50)),
+				observed_at: 12,
+			})],
+		};
+
+		assert_eq!(
+			serde_json::to_value(history).unwrap(),
+			serde_json::json!({
+				"blockchain": "B1",
+				"bridge_transfer_id": "0x0101",
+				"initiator_address": "\"initiator\"",
+				"recipient_address": "0xab",
+				"amount": u128::MAX.to_string(),
+				"time_lock": 100,
+				"status": "locked",
+				"initiated_at": 10,
+				"updated_at": 12,
+				"events": [{
+					"blockchain": "B2",
+					"kind": "locked",
+					"recipient_address": "0xab",
+					"amount": "1000",
+					"time_lock": 50,
+					"observed_at": 12,
+				}],
+			})
+		);
+	}
+
+	#[test]
+	fn test_archive_query() {
+		let query = ArchiveQuery {
+			status: Some("completed".to_string()),
+			limit: Some(usize::MAX),
+			..Default::default()
+		};
+		let query = query.to_transfer_query().unwrap();
+		assert_eq!(query.status, Some(IndexedEventKind::Completed));
+		assert_eq!(query.limit, MAX_ARCHIVE_LIMIT);
+
+		let query = ArchiveQuery { status: Some("done".to_string()), ..Default::default() };
+		assert_eq!(query.to_transfer_query().unwrap_err().0, StatusCode::BAD_REQUEST);
+	}
+}
@@ -0,0 +1,318 @@
+use std::{
+	collections::BTreeSet,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+use bridge_shared::metrics::BridgeMetrics;
+use futures::future::{BoxFuture, FutureExt};
+use godfig::Secret;
+use reqwest::Url;
+use serde::Serialize;
+
+use crate::health::Probe;
+
+/// Time after which an alert the webhook did not respond to is given up.
+const ALERT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns the balance of the signer of a blockchain, in the smallest unit of its native token.
+pub type BalanceQuery = Box<dyn Fn() -> BoxFuture<'static, Result<u128, String>> + Send + Sync>;
+
+/// Balance of a signer which fell below the threshold of its blockchain, posted to the alert
+/// webhook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LowBalance {
+	pub chain: String,
+	/// The balances, as strings as they may not fit the numbers of JSON parsers.
+	pub balance: String,
+	pub threshold: String,
+}
+
+struct Signer {
+	chain: String,
+	threshold: u128,
+	query: BalanceQuery,
+}
+
+/// Checks the balances of the signers of the relayer at an interval, so that the operators top
+/// them up before the bridge transactions start failing for lack of gas.
+///
+/// A balance below its threshold is logged and posted to the alert webhook once, when it falls
+/// below, and again if it falls below after being topped up.
+pub struct BalanceMonitor {
+	signers: Vec<Signer>,
+	interval: Duration,
+	metrics: Option<BridgeMetrics>,
+	/// The webhook URL, secret as it may carry a token, only its host being logged.
+	webhook: Option<Secret<Url>>,
+	client: reqwest::Client,
+	/// Blockchains whose signer balance is below the threshold.
+	low: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl BalanceMonitor {
+	pub fn new(interval: Duration) -> Self {
+		Self {
+			signers: Vec::new(),
+			interval,
+			metrics: None,
+			webhook: None,
+			client: reqwest::Client::builder()
+				.timeout(ALERT_TIMEOUT)
+				.build()
+				.expect("failed to build the alert client"),
+			low: Arc::new(Mutex::new(BTreeSet::new())),
+		}
+	}
+
+	/// Checks the balance of the signer of `chain`, returned by `query`, against `threshold`.
+	pub fn with_signer(
+		mut self,
+		chain: impl Into<String>,
+		threshold: u128,
+		query: BalanceQuery,
+	) -> Self {
+		self.signers.push(Signer { chain: chain.into(), threshold, query });
+		self
+	}
+
+	/// Exports the balances in `metrics`.
+	pub fn with_metrics(mut self, metrics: BridgeMetrics) -> Self {
+		self.metrics = Some(metrics);
+		self
+	}
+
+	/// Posts the [`LowBalance`]s as JSON to the `http://` or `https://` URL `webhook`.
+	pub fn with_webhook(mut self, webhook: &Secret) -> Result<Self, anyhow::Error> {
+		let url = Url::parse(webhook.expose())
+			.map_err(|error| anyhow::anyhow!("Invalid balance alert webhook: {error}"))?;
+		if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+			anyhow::bail!("Invalid balance alert webhook, expected an http:// or https:// URL");
+		}
+		self.webhook = Some(Secret::new(url));
+		Ok(self)
+	}
+
+	/// Readiness probe failing while the balance of a signer is below its threshold.
+	pub fn probe(&self) -> Probe {
+		let low = self.low.clone();
+		Box::new(move || {
+			let low = low.lock().expect("balance monitor lock poisoned").clone();
+			async move {
+				if low.is_empty() {
+					Ok(())
+				} else {
+					let chains = low.into_iter().collect::<Vec<_>>().join(", ");
+					Err(format!("signer balance below threshold on {chains}"))
+				}
+			}
+			.boxed()
+		})
+	}
+
+	/// Checks the balances of all the signers, returning the ones which fell below their
+	/// threshold since the last check.
+	pub async fn check(&self) -> Vec<LowBalance> {
+		let mut fell_low = Vec::new();
+		for signer in &self.signers {
+			let balance = match (signer.query)().await {
+				Ok(balance) => balance,
+				Err(error) => {
+					tracing::warn!(
+						"Balance[{}]: failed to query signer balance: {error}",
+						signer.chain
+					);
+					if let Some(metrics) = &self.metrics {
+						metrics.rpc_error(&signer.chain);
+					}
+					continue;
+				}
+			};
+			let is_low = balance < signer.threshold;
+			if let Some(metrics) = &self.metrics {
+				metrics.signer_balance(&signer.chain, balance, is_low);
+			}
+			let mut low = self.low.lock().expect("balance monitor lock poisoned");
+			if !is_low {
+				low.remove(&signer.chain);
+			} else if low.insert(signer.chain.clone()) {
+				tracing::warn!(
+					"Balance[{}]: signer balance {balance} below threshold {}",
+					signer.chain,
+					signer.threshold
+				);
+				fell_low.push(LowBalance {
+					chain: signer.chain.clone(),
+					balance: balance.to_string(),
+					threshold: signer.threshold.to_string(),
+				});
+			}
+		}
+		fell_low
+	}
+
+	/// Checks the balances at the interval of the monitor, forever.
+	///
+	/// The alerts are posted in the background, so that a slow webhook doesn't delay the next
+	/// checks.
+	pub async fn run(self) {
+		// a zero interval, which the timer rejects, checks as often as it can instead
+		let mut interval = tokio::time::interval(self.interval.max(Duration::from_millis(1)));
+		loop {
+			interval.tick().await;
+			for low_balance in self.check().await {
+				if let Some(webhook) = &self.webhook {
+					let client = self.client.clone();
+					let webhook = webhook.clone();
+					tokio::spawn(async move {
+						if let Err(error) = post_alert(&client, &webhook, &low_balance).await {
+							tracing::warn!(
+								"Balance: failed to post alert to {}: {error}",
+								webhook_host(&webhook)
+							);
+						}
+					});
+				}
+			}
+		}
+	}
+}
+
+/// The scheme and host of the webhook, logged instead of its URL.
+fn webhook_host(webhook: &Secret<Url>) -> String {
+	let url = webhook.expose();
+	format!("{}://{}", url.scheme(), url.host_str().unwrap_or_default())
+}
+
+/// Posts the alert, the errors leaving out the URL of the webhook.
+async fn post_alert(
+	client: &reqwest::Client,
+	webhook: &Secret<Url>,
+	low_balance: &LowBalance,
+) -> Result<(), reqwest::Error> {
+	client
+		.post(webhook.expose().clone())
+		.json(low_balance)
+		.send()
+		.await
+		.and_then(reqwest::Response::error_for_status)
+		.map_err(reqwest::Error::without_url)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicU64, Ordering};
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	fn query(balance: Arc<AtomicU64>) -> BalanceQuery {
+		Box::new(move || {
+			let balance = balance.load(Ordering::SeqCst);
+			async move { Ok(u128::from(balance)) }.boxed()
+		})
+	}
+
+	#[tokio::test]
+	async fn test_balance_alerts() -> Result<(), anyhow::Error> {
+		let metrics = BridgeMetrics::new()?;
+		let balance = Arc::new(AtomicU64::new(100));
+		let monitor = BalanceMonitor::new(Duration::from_secs(60))
+			.with_signer("ethereum", 50, query(balance.clone()))
+			.with_signer("movement", 10, Box::new(|| async { Err("unreachable".into()) }.boxed()))
+			.with_metrics(metrics.clone());
+		let probe = monitor.probe();
+
+		assert_eq!(monitor.check().await, vec![]);
+		assert_eq!(probe().await, Ok(()));
+
+		balance.store(20, Ordering::SeqCst);
+		let low_balance = LowBalance {
+			chain: "ethereum".to_string(),
+			balance: "20".to_string(),
+			threshold: "50".to_string(),
+		};
+		assert_eq!(monitor.check().await, vec![low_balance]);
+		// Alerted once while the balance stays low
+		assert_eq!(monitor.check().await, vec![]);
+		assert!(probe().await.is_err());
+		let encoded = metrics.encode()?;
+		assert!(encoded.contains(r#"bridge_signer_balance{chain="ethereum"} 20"#));
+		assert!(encoded.contains(r#"bridge_signer_balance_low{chain="ethereum"} 1"#));
+		assert!(encoded.contains(r#"bridge_rpc_errors_total{chain="movement"} 3"#));
+
+		balance.store(50, Ordering::SeqCst);
+		assert_eq!(monitor.check().await, vec![]);
+		assert_eq!(probe().await, Ok(()));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_post_alert() -> Result<(), anyhow::Error> {
+		let listener = TcpListener::bind("127.0.0.1:0").await?;
+		let webhook =
+			Secret::new(format!("http://{}/alerts?token=hunter2", listener.local_addr()?));
+		let server = tokio::spawn(async move {
+			let (mut stream, _) = listener.accept().await?;
+			let mut request = Vec::new();
+			let mut buffer = [0u8; 1024];
+			while !request.ends_with(b"}") {
+				let read = stream.read(&mut buffer).await?;
+				if read == 0 {
+					break;
+				}
+				request.extend_from_slice(&buffer[..read]);
+			}
+			stream.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await?;
+			Ok::<_, std::io::Error>(String::from_utf8_lossy(&request).to_string())
+		});
+
+		let monitor = BalanceMonitor::new(Duration::from_secs(60)).with_webhook(&webhook)?;
+		let low_balance = LowBalance {
+			chain: "ethereum".to_string(),
+			balance: "20".to_string(),
+			threshold: "50".to_string(),
+		};
+		post_alert(&monitor.client, monitor.webhook.as_ref().unwrap(), &low_balance).await?;
+
+		let request = server.await??;
+		assert!(request.starts_with("POST /alerts?token=hunter2 HTTP/1.1"));
+		assert!(request.ends_with(r#"{"chain":"ethereum","balance":"20","threshold":"50"}"#));
+		assert!(BalanceMonitor::new(Duration::from_secs(60))
+			.with_webhook(&Secret::new("https://hooks.example.com/services/alerts".to_string()))
+			.is_ok());
+		assert!(BalanceMonitor::new(Duration::from_secs(60))
+			.with_webhook(&Secret::new("ftp://hooks.example.com".to_string()))
+			.is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_webhook_url_is_not_logged() -> Result<(), anyhow::Error> {
+		let listener = TcpListener::bind("127.0.0.1:0").await?;
+		let address = listener.local_addr()?;
+		// the webhook refuses the connections
+		drop(listener);
+		let monitor = BalanceMonitor::new(Duration::from_secs(60))
+			.with_webhook(&Secret::new(format!("http://{address}/alerts/hunter2")))?;
+		let webhook = monitor.webhook.as_ref().unwrap();
+		let low_balance = LowBalance {
+			chain: "ethereum".to_string(),
+			balance: "20".to_string(),
+			threshold: "50".to_string(),
+		};
+
+		let error = post_alert(&monitor.client, webhook, &low_balance).await.unwrap_err();
+		assert!(!error.to_string().contains("hunter2"));
+		assert_eq!(webhook_host(webhook), "http://127.0.0.1");
+		let error = BalanceMonitor::new(Duration::from_secs(60))
+			.with_webhook(&Secret::new("ftp://hooks.example.com/hunter2".to_string()))
+			.err()
+			.unwrap();
+		assert!(!error.to_string().contains("hunter2"));
+		Ok(())
+	}
+}
@@ -0,0 +1,374 @@
+use std::time::Duration;
+
+use bridge_shared::{
+	secret::{SecretLoader, SecretLoaderError},
+	types::{AddressParseError, EthAddress, TimeLockUnit},
+};
+use godfig::{env_default, Secret};
+use movement_retry::RetryPolicy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const DEFAULT_ETH_RPC_CONNECTION_URL: &str = "http://localhost:8545";
+const DEFAULT_ETH_WS_CONNECTION_URL: &str = "ws://localhost:8545";
+const DEFAULT_CONTRACT_ADDRESS: &str = "0x0";
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+	#[error("Invalid Ethereum address for `{0}`: {1}")]
+	InvalidAddress(&'static str, AddressParseError),
+	#[error("Ethereum address for `{0}` must not be the zero address")]
+	ZeroAddress(&'static str),
+	#[error("Initiator and counterparty contracts must be deployed at different addresses")]
+	SameContractAddress,
+	#[error("Signer configuration is missing `{0}`")]
+	MissingSignerField(&'static str),
+}
+
+/// Signer of the bridge transactions on Ethereum.
+///
+/// Besides a local private key, the transactions can be signed by a key held in AWS KMS, on a
+/// Ledger, or by an external JSON-RPC signing service, so that no hot key is in the config file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerConfig {
+	/// Signs with `signer_private_key`.
+	#[default]
+	Local,
+	AwsKms {
+		key_id: String,
+		#[serde(default)]
+		region: Option<String>,
+	},
+	Ledger {
+		/// Index of the account in the Ledger Live derivation path.
+		#[serde(default)]
+		account_index: usize,
+	},
+	/// Service implementing `eth_signTransaction` for `address`, whose checksum is validated
+	/// when the config is read.
+	Remote { url: String, address: EthAddress },
+}
+
+/// Ethereum side of the bridge.
+///
+/// The contract addresses are configured independently from the signer so that the relayer can
+/// target any deployment of the bridge contracts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+	#[serde(default = "default_eth_rpc_connection_url")]
+	pub rpc_connection_url: String,
+	/// Without a WebSocket URL, the contract events are polled over the RPC connection.
+	#[serde(default = "default_eth_ws_connection_url")]
+	pub ws_connection_url: Option<String>,
+	/// Interval at which the contract events are polled in HTTP-only mode, in milliseconds.
+	#[serde(default = "default_eth_event_poll_interval")]
+	pub event_poll_interval: u64,
+	/// Chain id of the Ethereum network, which the `eth_chainId` of the node is checked against
+	/// when the client is built. Not checked when 0.
+	#[serde(default = "default_eth_chain_id")]
+	pub chain_id: u64,
+	/// Private key of the local signer, or a reference to it resolved by a [`SecretLoader`]:
+	/// `env:NAME`, `file:PATH` or `keystore:PATH`.
+	#[serde(default = "default_eth_signer_private_key")]
+	pub signer_private_key: Secret,
+	/// Password of the keystore of the signer private key, itself a secret reference.
+	#[serde(default = "default_eth_signer_keystore_password")]
+	pub signer_keystore_password: Option<Secret>,
+	#[serde(default)]
+	pub signer: SignerConfig,
+	/// Balance of the signer, in wei, below which the relayer alerts the operators to top it
+	/// up. Not monitored when unset.
+	#[serde(default = "default_eth_min_signer_balance")]
+	pub min_signer_balance: Option<u128>,
+	#[serde(default = "default_eth_initiator_contract_address")]
+	pub initiator_contract_address: String,
+	#[serde(default = "default_eth_counterparty_contract_address")]
+	pub counterparty_contract_address: String,
+	/// Average time between two blocks, in seconds, which the time locks of the bridge contracts
+	/// are counted in.
+	#[serde(default = "default_eth_block_time")]
+	pub block_time: u64,
+	/// Retries of the bridge transactions whose sending fails.
+	#[serde(default)]
+	pub retry: RetryPolicy,
+	/// Address credited with the relayer fees of the transfers initiated on this chain.
+	#[serde(default = "default_eth_fee_collector_address")]
+	pub fee_collector_address: String,
+}
+
+env_default!(
+	default_eth_rpc_connection_url,
+	"BRIDGE_ETH_RPC_CONNECTION_URL",
+	String,
+	DEFAULT_ETH_RPC_CONNECTION_URL.to_string()
+);
+
+env_default!(
+	default_eth_ws_connection_url_env,
+	"BRIDGE_ETH_WS_CONNECTION_URL",
+	String,
+	DEFAULT_ETH_WS_CONNECTION_URL.to_string()
+);
+
+/// An empty `BRIDGE_ETH_WS_CONNECTION_URL` selects the HTTP-only mode.
+pub fn default_eth_ws_connection_url() -> Option<String> {
+	Some(default_eth_ws_connection_url_env()).filter(|url| !url.is_empty())
+}
+
+env_default!(default_eth_event_poll_interval, "BRIDGE_ETH_EVENT_POLL_INTERVAL", u64, 2_000);
+
+env_default!(default_eth_chain_id, "BRIDGE_ETH_CHAIN_ID", u64, 0);
+
+env_default!(
+	default_eth_signer_private_key,
+	"BRIDGE_ETH_SIGNER_PRIVATE_KEY",
+	Secret,
+	Secret::default()
+);
+
+env_default!(default_eth_signer_keystore_password, "BRIDGE_ETH_SIGNER_KEYSTORE_PASSWORD", Secret);
+
+env_default!(
+	default_eth_initiator_contract_address,
+	"BRIDGE_ETH_INITIATOR_CONTRACT_ADDRESS",
+	String,
+	DEFAULT_CONTRACT_ADDRESS.to_string()
+);
+
+env_default!(
+	default_eth_counterparty_contract_address,
+	"BRIDGE_ETH_COUNTERPARTY_CONTRACT_ADDRESS",
+	String,
+	DEFAULT_CONTRACT_ADDRESS.to_string()
+);
+
+env_default!(default_eth_min_signer_balance, "BRIDGE_ETH_MIN_SIGNER_BALANCE", u128);
+
+env_default!(default_eth_block_time, "BRIDGE_ETH_BLOCK_TIME", u64, 12);
+
+env_default!(
+	default_eth_fee_collector_address,
+	"BRIDGE_ETH_FEE_COLLECTOR_ADDRESS",
+	String,
+	String::new()
+);
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			rpc_connection_url: default_eth_rpc_connection_url(),
+			ws_connection_url: default_eth_ws_connection_url(),
+			event_poll_interval: default_eth_event_poll_interval(),
+			chain_id: default_eth_chain_id(),
+			signer_private_key: default_eth_signer_private_key(),
+			signer_keystore_password: default_eth_signer_keystore_password(),
+			signer: SignerConfig::default(),
+			min_signer_balance: default_eth_min_signer_balance(),
+			initiator_contract_address: default_eth_initiator_contract_address(),
+			counterparty_contract_address: default_eth_counterparty_contract_address(),
+			block_time: default_eth_block_time(),
+			retry: RetryPolicy::default(),
+			fee_collector_address: default_eth_fee_collector_address(),
+		}
+	}
+}
+
+/// How the events of the bridge contracts are received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventMonitoring {
+	/// Subscription to the contract logs over a WebSocket connection.
+	WebSocket(String),
+	/// `eth_getLogs` requests on the RPC connection, sent on an interval.
+	Polling { rpc_connection_url: String, interval: Duration },
+}
+
+impl Config {
+	/// The chain id the node must be on, if one is configured.
+	pub fn expected_chain_id(&self) -> Option<u64> {
+		Some(self.chain_id).filter(|chain_id| *chain_id != 0)
+	}
+
+	/// Unit of the time locks of the bridge contracts, which are counted in blocks.
+	pub fn time_lock_unit(&self) -> TimeLockUnit {
+		TimeLockUnit(self.block_time.max(1))
+	}
+
+	/// Subscribes to the events when a WebSocket URL is configured, polls them otherwise.
+	pub fn event_monitoring(&self) -> EventMonitoring {
+		match &self.ws_connection_url {
+			Some(ws_connection_url) => EventMonitoring::WebSocket(ws_connection_url.clone()),
+			None => EventMonitoring::Polling {
+				rpc_connection_url: self.rpc_connection_url.clone(),
+				interval: Duration::from_millis(self.event_poll_interval),
+			},
+		}
+	}
+
+	/// Checks that both contract addresses are well formed, non-zero and distinct, and that the
+	/// signer is fully configured.
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		let initiator =
+			parse_address("initiator_contract_address", &self.initiator_contract_address)?;
+		let counterparty =
+			parse_address("counterparty_contract_address", &self.counterparty_contract_address)?;
+		if initiator == counterparty {
+			return Err(ConfigError::SameContractAddress);
+		}
+		self.validate_signer()
+	}
+}
+
+impl Config {
+	/// Resolves the private key of the local signer.
+	pub fn load_signer_private_key(&self) -> Result<String, SecretLoaderError> {
+		let mut loader = SecretLoader::new();
+		if let Some(password) = &self.signer_keystore_password {
+			loader = loader.with_keystore_password(password.expose().clone());
+		}
+		loader.load(self.signer_private_key.expose())
+	}
+
+	fn validate_signer(&self) -> Result<(), ConfigError> {
+		match &self.signer {
+			SignerConfig::Local if self.signer_private_key.is_empty() => {
+				Err(ConfigError::MissingSignerField("signer_private_key"))
+			}
+			SignerConfig::AwsKms { key_id, .. } if key_id.is_empty() => {
+				Err(ConfigError::MissingSignerField("key_id"))
+			}
+			SignerConfig::Remote { url, .. } if url.is_empty() => {
+				Err(ConfigError::MissingSignerField("url"))
+			}
+			_ => Ok(()),
+		}
+	}
+}
+
+fn parse_address(field: &'static str, address: &str) -> Result<EthAddress, ConfigError> {
+	address.parse().map_err(|error| match error {
+		AddressParseError::ZeroAddress => ConfigError::ZeroAddress(field),
+		error => ConfigError::InvalidAddress(field, error),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const INITIATOR: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+	const COUNTERPARTY: &str = "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512";
+	const SIGNER_PRIVATE_KEY: &str =
+		"0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+	fn config(initiator: &str, counterparty: &str) -> Config {
+		Config {
+			initiator_contract_address: initiator.to_string(),
+			counterparty_contract_address: counterparty.to_string(),
+			signer_private_key: SIGNER_PRIVATE_KEY.into(),
+			..Config::default()
+		}
+	}
+
+	#[test]
+	fn test_validate_contract_addresses() {
+		assert_eq!(config(INITIATOR, COUNTERPARTY).validate(), Ok(()));
+		assert!(matches!(
+			config("0x0", COUNTERPARTY).validate(),
+			Err(ConfigError::InvalidAddress("initiator_contract_address", _))
+		));
+		assert_eq!(
+			config(INITIATOR, "0x0000000000000000000000000000000000000000").validate(),
+			Err(ConfigError::ZeroAddress("counterparty_contract_address"))
+		);
+		assert_eq!(config(INITIATOR, INITIATOR).validate(), Err(ConfigError::SameContractAddress));
+		assert!(matches!(
+			config(INITIATOR, "0xE7f1725E7734CE288F8367e1Bb143E90bb3F0512").validate(),
+			Err(ConfigError::InvalidAddress(
+				"counterparty_contract_address",
+				AddressParseError::InvalidChecksum(_)
+			))
+		));
+		assert_eq!(config(INITIATOR, &COUNTERPARTY.to_lowercase()).validate(), Ok(()));
+	}
+
+	#[test]
+	fn test_validate_signer() {
+		let config =
+			Config { signer_private_key: Secret::default(), ..config(INITIATOR, COUNTERPARTY) };
+		assert_eq!(config.validate(), Err(ConfigError::MissingSignerField("signer_private_key")));
+
+		let config = Config {
+			signer: SignerConfig::Remote {
+				url: "http://localhost:9000".to_string(),
+				address: "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse().unwrap(),
+			},
+			..config
+		};
+		assert_eq!(config.validate(), Ok(()));
+
+		let config = Config {
+			signer: SignerConfig::AwsKms { key_id: String::new(), region: None },
+			..config
+		};
+		assert_eq!(config.validate(), Err(ConfigError::MissingSignerField("key_id")));
+	}
+
+	#[test]
+	fn test_deserialize_remote_signer_address() {
+		let remote = |address: &str| {
+			serde_json::from_value::<SignerConfig>(serde_json::json!({
+				"type": "remote",
+				"url": "http://localhost:9000",
+				"address": address,
+			}))
+		};
+		assert!(remote("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").is_ok());
+		let error = remote("0xF39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap_err();
+		assert!(error.to_string().contains("Invalid EIP-55 checksum"), "{error}");
+		assert!(remote("0x0000000000000000000000000000000000000000").is_err());
+	}
+
+	#[test]
+	fn test_load_signer_private_key_from_env() {
+		std::env::set_var("BRIDGE_ETH_TEST_SIGNER_PRIVATE_KEY", SIGNER_PRIVATE_KEY);
+		let config = Config {
+			signer_private_key: "env:BRIDGE_ETH_TEST_SIGNER_PRIVATE_KEY".into(),
+			..Config::default()
+		};
+		assert_eq!(config.load_signer_private_key().unwrap(), SIGNER_PRIVATE_KEY);
+	}
+
+	#[test]
+	fn test_expected_chain_id() {
+		assert_eq!(Config { chain_id: 0, ..Config::default() }.expected_chain_id(), None);
+		assert_eq!(
+			Config { chain_id: 31337, ..Config::default() }.expected_chain_id(),
+			Some(31337)
+		);
+	}
+
+	#[test]
+	fn test_polling_without_ws_connection_url() {
+		let config = Config {
+			rpc_connection_url: "https://rpc.example.org".to_string(),
+			ws_connection_url: None,
+			event_poll_interval: 500,
+			..Config::default()
+		};
+		assert_eq!(
+			config.event_monitoring(),
+			EventMonitoring::Polling {
+				rpc_connection_url: "https://rpc.example.org".to_string(),
+				interval: Duration::from_millis(500),
+			}
+		);
+
+		let config =
+			Config { ws_connection_url: Some("ws://localhost:8545".to_string()), ..config };
+		assert_eq!(
+			config.event_monitoring(),
+			EventMonitoring::WebSocket("ws://localhost:8545".to_string())
+		);
+	}
+}
@@ -0,0 +1,300 @@
+use std::{collections::BTreeSet, path::Path, time::Duration};
+
+use bridge_shared::{
+	approval::{parse_public_key, ApprovalError},
+	asset_registry::{AssetRegistry, AssetRegistryError},
+	attestation::Attestor,
+	bridge_service::{active_swap::ActiveSwapConfig, BridgeServiceConfig},
+	secret::SecretLoader,
+	transfer_limits::TransferLimits,
+	types::{Fee, TimeLockPolicy},
+};
+use godfig::{env_default, env_short_default, Secret};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub mod eth;
+pub mod movement;
+
+/// Configuration of the bridge relayer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+	/// Number of times a failing contract call is retried before the swap is aborted.
+	#[serde(default = "default_error_attempts")]
+	pub error_attempts: usize,
+	/// Delay between two attempts of a failing contract call, in milliseconds.
+	#[serde(default = "default_error_delay")]
+	pub error_delay: u64,
+	/// Timeout of a single contract call, in milliseconds.
+	#[serde(default = "default_contract_call_timeout")]
+	pub contract_call_timeout: u64,
+	/// Refund expired transfers and abort expired locks automatically.
+	#[serde(default = "default_auto_refund")]
+	pub auto_refund: bool,
+	/// Interval at which the time locks of the pending transfers are checked, in milliseconds.
+	#[serde(default = "default_refund_check_interval")]
+	pub refund_check_interval: u64,
+	/// Flat relayer fee deducted from the amount of each transfer.
+	#[serde(default = "default_fee_flat")]
+	pub fee_flat: u64,
+	/// Relayer fee deducted from the amount of each transfer, in basis points of the amount.
+	#[serde(default = "default_fee_basis_points")]
+	pub fee_basis_points: u64,
+	/// Minimum time left to the recipient to complete a transfer once the assets are locked, in
+	/// seconds.
+	#[serde(default = "default_time_lock_min_duration")]
+	pub time_lock_min_duration: u64,
+	/// Maximum time the assets of an initiator stay locked, in seconds.
+	#[serde(default = "default_time_lock_max_duration")]
+	pub time_lock_max_duration: u64,
+	/// Time between the expiry of the counterparty lock and of the initiator time lock of a
+	/// transfer, in seconds, for the relayer to complete the transfer before it can be refunded.
+	#[serde(default = "default_time_lock_counterparty_margin")]
+	pub time_lock_counterparty_margin: u64,
+	/// Window the locked values are capped over, in seconds.
+	#[serde(default = "default_transfer_limit_window")]
+	pub transfer_limit_window: u64,
+	/// Maximum value locked by the relayer within the window. Unlimited when unset.
+	#[serde(default = "default_max_value_locked")]
+	pub max_value_locked: Option<u128>,
+	/// Maximum value locked for the transfers of a single initiator within the window.
+	/// Unlimited when unset.
+	#[serde(default = "default_max_value_locked_per_initiator")]
+	pub max_value_locked_per_initiator: Option<u128>,
+	/// Amount above which the transfers wait for the approval of the operators, through the admin
+	/// API, before their assets are locked. Transfers are not held when unset.
+	#[serde(default = "default_approval_threshold")]
+	pub approval_threshold: Option<u128>,
+	/// Number of distinct approvers a transfer pending approval must be approved by.
+	#[serde(default = "default_approvals_required")]
+	pub approvals_required: usize,
+	/// Hex encoded ed25519 public keys of the operators allowed to approve transfers, whose
+	/// approvals are signed with the matching private keys. Transfers pending approval can't be
+	/// approved when empty.
+	#[serde(default)]
+	pub approver_keys: Vec<String>,
+	/// Interval at which the liquidity of the counterparty contract is checked again for the
+	/// transfers parked for lack of liquidity, in milliseconds.
+	#[serde(default = "default_liquidity_check_interval")]
+	pub liquidity_check_interval: u64,
+	/// Number of contract calls run at once for the transfers initiated on each blockchain.
+	#[serde(default = "default_workers")]
+	pub workers: usize,
+	/// Time the relayer waits on shutdown for the contract calls in flight to confirm, in
+	/// milliseconds.
+	#[serde(default = "default_shutdown_drain_timeout")]
+	pub shutdown_drain_timeout: u64,
+	/// Interval at which the balances of the signers are checked against the
+	/// `min_signer_balance` of their blockchain, in milliseconds.
+	#[serde(default = "default_balance_check_interval")]
+	pub balance_check_interval: u64,
+	/// `http://` or `https://` URL the signer balances falling below their minimum are posted to,
+	/// as JSON.
+	/// They are only logged when unset.
+	#[serde(default = "default_balance_alert_webhook")]
+	pub balance_alert_webhook: Option<Secret>,
+	/// TOML file of the registry of the bridged assets, relative to the `.movement` directory.
+	/// Transfers in any asset are relayed when unset.
+	#[serde(default = "default_asset_registry")]
+	pub asset_registry: Option<String>,
+	/// Address the Prometheus metrics are served on, at `/metrics`. Disabled when unset.
+	#[serde(default = "default_metrics_listen_address")]
+	pub metrics_listen_address: Option<String>,
+	/// Address the gRPC admin API is served on, which must only be reachable by the operators.
+	/// Disabled when unset.
+	#[serde(default = "default_admin_listen_address")]
+	pub admin_listen_address: Option<String>,
+	/// Token the requests to the admin API must carry, or a reference to it resolved by a
+	/// [`SecretLoader`]. Required when the admin API is served.
+	#[serde(default = "default_admin_token")]
+	pub admin_token: Option<Secret>,
+	/// Address the REST status API of the transfers is served on. Disabled when unset.
+	#[serde(default = "default_status_listen_address")]
+	pub status_listen_address: Option<String>,
+	/// URL of the SQLite or Postgres database archiving the bridge events of both blockchains,
+	/// e.g. `sqlite://bridge_index.db?mode=rwc`, queried on the status API. Disabled when unset.
+	#[serde(default = "default_transfer_index_url")]
+	pub transfer_index_url: Option<Secret>,
+	/// Ed25519 private key the relayer signs the attestations of the transitions of the
+	/// transfers with, hex encoded, or a reference to it resolved by a [`SecretLoader`].
+	/// Transitions are not attested when unset.
+	#[serde(default = "default_attestation_key")]
+	pub attestation_key: Option<Secret>,
+
+	/// The Ethereum side of the bridge.
+	#[serde(default)]
+	pub eth: eth::Config,
+	/// The Movement side of the bridge.
+	#[serde(default)]
+	pub movement: movement::Config,
+}
+
+env_short_default!(default_error_attempts, usize, 3usize);
+
+env_short_default!(default_error_delay, u64, 5_000u64);
+
+env_short_default!(default_contract_call_timeout, u64, 30_000u64);
+
+env_short_default!(default_auto_refund, bool, false);
+
+env_short_default!(default_refund_check_interval, u64, 10_000u64);
+
+env_short_default!(default_fee_flat, u64, 0u64);
+
+env_short_default!(default_fee_basis_points, u64, 0u64);
+
+env_short_default!(default_time_lock_min_duration, u64, 3_600u64);
+
+env_short_default!(default_time_lock_max_duration, u64, 172_800u64);
+
+env_short_default!(default_time_lock_counterparty_margin, u64, 1_800u64);
+
+env_short_default!(default_transfer_limit_window, u64, 3_600u64);
+
+env_default!(default_max_value_locked, "BRIDGE_MAX_VALUE_LOCKED", u128);
+
+env_default!(default_max_value_locked_per_initiator, "BRIDGE_MAX_VALUE_LOCKED_PER_INITIATOR", u128);
+
+env_default!(default_approval_threshold, "BRIDGE_APPROVAL_THRESHOLD", u128);
+
+env_short_default!(default_approvals_required, usize, 1usize);
+
+env_short_default!(default_liquidity_check_interval, u64, 60_000u64);
+
+env_short_default!(default_workers, usize, 16usize);
+
+env_short_default!(default_shutdown_drain_timeout, u64, 60_000u64);
+
+env_short_default!(default_balance_check_interval, u64, 60_000u64);
+
+env_default!(default_balance_alert_webhook, "BRIDGE_BALANCE_ALERT_WEBHOOK", Secret);
+
+env_default!(default_asset_registry, "BRIDGE_ASSET_REGISTRY", String);
+
+env_default!(default_metrics_listen_address, "BRIDGE_METRICS_LISTEN_ADDRESS", String);
+
+env_default!(default_admin_listen_address, "BRIDGE_ADMIN_LISTEN_ADDRESS", String);
+
+env_default!(default_admin_token, "BRIDGE_ADMIN_TOKEN", Secret);
+
+env_default!(default_status_listen_address, "BRIDGE_STATUS_LISTEN_ADDRESS", String);
+
+env_default!(default_transfer_index_url, "BRIDGE_TRANSFER_INDEX_URL", Secret);
+
+env_default!(default_attestation_key, "BRIDGE_ATTESTATION_KEY", Secret);
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			error_attempts: default_error_attempts(),
+			error_delay: default_error_delay(),
+			contract_call_timeout: default_contract_call_timeout(),
+			auto_refund: default_auto_refund(),
+			refund_check_interval: default_refund_check_interval(),
+			fee_flat: default_fee_flat(),
+			fee_basis_points: default_fee_basis_points(),
+			time_lock_min_duration: default_time_lock_min_duration(),
+			time_lock_max_duration: default_time_lock_max_duration(),
+			time_lock_counterparty_margin: default_time_lock_counterparty_margin(),
+			transfer_limit_window: default_transfer_limit_window(),
+			max_value_locked: default_max_value_locked(),
+			max_value_locked_per_initiator: default_max_value_locked_per_initiator(),
+			approval_threshold: default_approval_threshold(),
+			approvals_required: default_approvals_required(),
+			approver_keys: Vec::new(),
+			liquidity_check_interval: default_liquidity_check_interval(),
+			workers: default_workers(),
+			shutdown_drain_timeout: default_shutdown_drain_timeout(),
+			balance_check_interval: default_balance_check_interval(),
+			balance_alert_webhook: default_balance_alert_webhook(),
+			asset_registry: default_asset_registry(),
+			metrics_listen_address: default_metrics_listen_address(),
+			admin_listen_address: default_admin_listen_address(),
+			admin_token: default_admin_token(),
+			status_listen_address: default_status_listen_address(),
+			transfer_index_url: default_transfer_index_url(),
+			attestation_key: default_attestation_key(),
+			eth: eth::Config::default(),
+			movement: movement::Config::default(),
+		}
+	}
+}
+
+/// A periodic check of the relayer configured to run every 0 milliseconds.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("`{0}` must be greater than 0")]
+pub struct ZeroIntervalError(pub &'static str);
+
+impl Config {
+	/// Checks that the intervals of the periodic checks are not zero, which their timers reject.
+	pub fn validate_intervals(&self) -> Result<(), ZeroIntervalError> {
+		for (field, interval) in [
+			("refund_check_interval", self.refund_check_interval),
+			("balance_check_interval", self.balance_check_interval),
+		] {
+			if interval == 0 {
+				return Err(ZeroIntervalError(field));
+			}
+		}
+		Ok(())
+	}
+
+	/// Loads the registry of the bridged assets from the `.movement` directory at `dot_movement`,
+	/// if one is configured.
+	pub fn load_asset_registry(
+		&self,
+		dot_movement: &Path,
+	) -> Result<Option<AssetRegistry>, AssetRegistryError> {
+		self.asset_registry
+			.as_ref()
+			.map(|file| AssetRegistry::load(dot_movement.join(file)))
+			.transpose()
+	}
+
+	/// Loads the attestor of the relayer, if an attestation key is configured.
+	pub fn load_attestor(&self) -> Result<Option<Attestor>, anyhow::Error> {
+		let Some(key) = &self.attestation_key else {
+			return Ok(None);
+		};
+		let key = SecretLoader::new().load(key.expose())?;
+		Ok(Some(Attestor::try_from_hex_key(&key)?))
+	}
+
+	/// Parses the public keys of the approvers of the transfers pending approval.
+	pub fn approvers(&self) -> Result<BTreeSet<[u8; 32]>, ApprovalError> {
+		self.approver_keys.iter().map(|key| parse_public_key(key)).collect()
+	}
+
+	/// Loads the token of the admin API, which is required when `admin_listen_address` is set.
+	pub fn load_admin_token(&self) -> Result<Option<String>, anyhow::Error> {
+		match (&self.admin_listen_address, &self.admin_token) {
+			(None, _) => Ok(None),
+			(Some(_), None) => anyhow::bail!("The admin API is served without an admin_token"),
+			(Some(_), Some(token)) => Ok(Some(SecretLoader::new().load(token.expose())?)),
+		}
+	}
+
+	pub fn bridge_service_config(&self) -> BridgeServiceConfig {
+		BridgeServiceConfig {
+			active_swap: ActiveSwapConfig {
+				error_attempts: self.error_attempts,
+				error_delay: Duration::from_millis(self.error_delay),
+				contract_call_timeout: Duration::from_millis(self.contract_call_timeout),
+				fee: Fee { flat: self.fee_flat, basis_points: self.fee_basis_points },
+				time_lock_policy: TimeLockPolicy {
+					min_duration: self.time_lock_min_duration,
+					max_duration: self.time_lock_max_duration,
+					counterparty_margin: self.time_lock_counterparty_margin,
+				},
+				limits: TransferLimits {
+					window: Duration::from_secs(self.transfer_limit_window),
+					max_value: self.max_value_locked,
+					max_value_per_initiator: self.max_value_locked_per_initiator,
+				},
+				approval_threshold: self.approval_threshold,
+				liquidity_check_interval: Duration::from_millis(self.liquidity_check_interval),
+				workers: self.workers,
+			},
+		}
+	}
+}
@@ -0,0 +1,164 @@
+use bridge_shared::secret::{SecretLoader, SecretLoaderError};
+use godfig::{env_default, Secret};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const DEFAULT_MOVEMENT_REST_CONNECTION_URL: &str = "http://localhost:30731";
+const DEFAULT_COUNTERPARTY_MODULE_ADDRESS: &str = "0x1";
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+	#[error("Invalid Movement address for `{0}`: {1}")]
+	InvalidAddress(&'static str, String),
+	#[error("Movement signer private key is not configured")]
+	MissingSignerPrivateKey,
+}
+
+/// Movement side of the bridge.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+	#[serde(default = "default_movement_rest_connection_url")]
+	pub rest_connection_url: String,
+	/// Chain id of the Movement network, which the chain id of the ledger info of the node is
+	/// checked against when the client is built. Not checked when unset.
+	#[serde(default = "default_movement_chain_id")]
+	pub chain_id: Option<u64>,
+	/// Private key of the signer, or a reference to it resolved by a [`SecretLoader`]:
+	/// `env:NAME`, `file:PATH` for a key file, or `keystore:PATH`.
+	#[serde(default = "default_movement_signer_private_key")]
+	pub signer_private_key: Secret,
+	/// Password of the keystore of the signer private key, itself a secret reference.
+	#[serde(default = "default_movement_signer_keystore_password")]
+	pub signer_keystore_password: Option<Secret>,
+	/// Balance of the signer, in octas, below which the relayer alerts the operators to top it
+	/// up. Not monitored when unset.
+	#[serde(default = "default_movement_min_signer_balance")]
+	pub min_signer_balance: Option<u128>,
+	/// Fetch the sequence number of the signer account from the REST API on start, instead of
+	/// starting from 0, so that a restarted client does not reuse sequence numbers.
+	#[serde(default = "default_movement_recover_sequence_number")]
+	pub recover_sequence_number: bool,
+	/// Address of the account which published the `atomic_bridge_counterparty` module.
+	#[serde(default = "default_movement_counterparty_module_address")]
+	pub counterparty_module_address: String,
+	/// Address credited with the relayer fees of the transfers initiated on this chain.
+	#[serde(default = "default_movement_fee_collector_address")]
+	pub fee_collector_address: String,
+}
+
+env_default!(
+	default_movement_rest_connection_url,
+	"BRIDGE_MOVEMENT_REST_CONNECTION_URL",
+	String,
+	DEFAULT_MOVEMENT_REST_CONNECTION_URL.to_string()
+);
+
+env_default!(default_movement_chain_id, "BRIDGE_MOVEMENT_CHAIN_ID", u64);
+
+env_default!(
+	default_movement_signer_private_key,
+	"BRIDGE_MOVEMENT_SIGNER_PRIVATE_KEY",
+	Secret,
+	Secret::default()
+);
+
+env_default!(
+	default_movement_signer_keystore_password,
+	"BRIDGE_MOVEMENT_SIGNER_KEYSTORE_PASSWORD",
+	Secret
+);
+
+env_default!(default_movement_min_signer_balance, "BRIDGE_MOVEMENT_MIN_SIGNER_BALANCE", u128);
+
+env_default!(
+	default_movement_recover_sequence_number,
+	"BRIDGE_MOVEMENT_RECOVER_SEQUENCE_NUMBER",
+	bool,
+	true
+);
+
+env_default!(
+	default_movement_counterparty_module_address,
+	"BRIDGE_MOVEMENT_COUNTERPARTY_MODULE_ADDRESS",
+	String,
+	DEFAULT_COUNTERPARTY_MODULE_ADDRESS.to_string()
+);
+
+env_default!(
+	default_movement_fee_collector_address,
+	"BRIDGE_MOVEMENT_FEE_COLLECTOR_ADDRESS",
+	String,
+	String::new()
+);
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			rest_connection_url: default_movement_rest_connection_url(),
+			chain_id: default_movement_chain_id(),
+			signer_private_key: default_movement_signer_private_key(),
+			signer_keystore_password: default_movement_signer_keystore_password(),
+			min_signer_balance: default_movement_min_signer_balance(),
+			recover_sequence_number: default_movement_recover_sequence_number(),
+			counterparty_module_address: default_movement_counterparty_module_address(),
+			fee_collector_address: default_movement_fee_collector_address(),
+		}
+	}
+}
+
+impl Config {
+	/// Checks that a signer is configured and that the module address is well formed.
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		if self.signer_private_key.is_empty() {
+			return Err(ConfigError::MissingSignerPrivateKey);
+		}
+		parse_account_address("counterparty_module_address", &self.counterparty_module_address)?;
+		Ok(())
+	}
+
+	/// Resolves the private key of the signer.
+	pub fn load_signer_private_key(&self) -> Result<String, SecretLoaderError> {
+		let mut loader = SecretLoader::new();
+		if let Some(password) = &self.signer_keystore_password {
+			loader = loader.with_keystore_password(password.expose().clone());
+		}
+		loader.load(self.signer_private_key.expose())
+	}
+}
+
+/// Parses a Movement account address, whose leading zeros can be omitted.
+fn parse_account_address(field: &'static str, address: &str) -> Result<[u8; 32], ConfigError> {
+	let hex_address = address.strip_prefix("0x").unwrap_or(address);
+	if hex_address.is_empty() || hex_address.len() > 64 {
+		return Err(ConfigError::InvalidAddress(field, address.to_string()));
+	}
+	let padded = format!("{hex_address:0>64}");
+	let mut bytes = [0u8; 32];
+	hex::decode_to_slice(padded, &mut bytes)
+		.map_err(|e| ConfigError::InvalidAddress(field, e.to_string()))?;
+	Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_validate() {
+		let config = Config {
+			signer_private_key: "file:/etc/movement/bridge.key".into(),
+			counterparty_module_address: "0x1".to_string(),
+			..Config::default()
+		};
+		assert_eq!(config.validate(), Ok(()));
+
+		let config = Config { counterparty_module_address: "0xzz".to_string(), ..config };
+		assert!(matches!(
+			config.validate(),
+			Err(ConfigError::InvalidAddress("counterparty_module_address", _))
+		));
+
+		let config = Config { signer_private_key: Secret::default(), ..config };
+		assert_eq!(config.validate(), Err(ConfigError::MissingSignerPrivateKey));
+	}
+}
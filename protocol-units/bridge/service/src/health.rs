@@ -0,0 +1,111 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::future::{join_all, BoxFuture};
+use serde::Serialize;
+
+/// Checks a dependency of the relayer outside of it, such as the connectivity of an RPC node,
+/// the liveness of an event subscription or the balance of a signer.
+pub type Probe = Box<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Result of the check of one dependency of the relayer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthCheck {
+	pub name: String,
+	pub healthy: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+impl HealthCheck {
+	pub fn new(name: impl Into<String>, result: Result<(), String>) -> Self {
+		let error = result.err();
+		Self { name: name.into(), healthy: error.is_none(), error }
+	}
+}
+
+/// Checks of the dependencies of the relayer, which is ready to relay transfers if all of them
+/// pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+	pub ready: bool,
+	pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+	pub fn new(checks: Vec<HealthCheck>) -> Self {
+		Self { ready: checks.iter().all(|check| check.healthy), checks }
+	}
+}
+
+/// Named [`Probe`]s checked concurrently on each readiness request, each failing if it does not
+/// complete within the timeout.
+#[derive(Clone)]
+pub struct HealthProbes {
+	probes: Arc<Vec<(String, Probe)>>,
+	timeout: Duration,
+}
+
+impl std::fmt::Debug for HealthProbes {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("HealthProbes")
+			.field("probes", &self.probes.iter().map(|(name, _)| name).collect::<Vec<_>>())
+			.field("timeout", &self.timeout)
+			.finish()
+	}
+}
+
+impl HealthProbes {
+	pub fn new(probes: Vec<(String, Probe)>, timeout: Duration) -> Self {
+		Self { probes: Arc::new(probes), timeout }
+	}
+
+	pub async fn check(&self) -> Vec<HealthCheck> {
+		join_all(self.probes.iter().map(|(name, probe)| async move {
+			let result = tokio::time::timeout(self.timeout, probe())
+				.await
+				.unwrap_or_else(|_| Err(format!("timed out after {:?}", self.timeout)));
+			HealthCheck::new(name.clone(), result)
+		}))
+		.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::FutureExt;
+
+	#[tokio::test]
+	async fn test_health_probes() {
+		let probes = HealthProbes::new(
+			vec![
+				("rpc".to_string(), Box::new(|| async { Ok(()) }.boxed())),
+				("balance".to_string(), Box::new(|| async { Err("too low".to_string()) }.boxed())),
+				("events".to_string(), Box::new(|| futures::future::pending().boxed())),
+			],
+			Duration::from_millis(10),
+		);
+
+		let report = HealthReport::new(probes.check().await);
+		assert!(!report.ready);
+		assert_eq!(
+			report.checks,
+			vec![
+				HealthCheck { name: "rpc".to_string(), healthy: true, error: None },
+				HealthCheck::new("balance", Err("too low".to_string())),
+				HealthCheck::new("events", Err("timed out after 10ms".to_string())),
+			]
+		);
+		assert!(HealthReport::new(vec![HealthCheck::new("rpc", Ok(()))]).ready);
+		assert_eq!(
+			serde_json::to_value(HealthReport::new(report.checks[..2].to_vec())).unwrap(),
+			serde_json::json!({
+				"ready": false,
+				"checks": [
+					{ "name": "rpc", "healthy": true },
+					{ "name": "balance", "healthy": false, "error": "too low" },
+				],
+			})
+		);
+	}
+}
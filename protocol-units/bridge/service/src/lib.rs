@@ -0,0 +1,12 @@
+pub mod admin;
+pub mod balance_monitor;
+pub mod config;
+pub mod health;
+pub mod metrics;
+pub mod relayer;
+pub mod shutdown;
+pub mod transfer_index;
+pub mod transfer_store;
+
+pub use config::Config;
+pub use relayer::Relayer;
@@ -0,0 +1,8 @@
+use std::net::SocketAddr;
+
+use bridge_shared::metrics::BridgeMetrics;
+
+/// Serves the metrics of the relayer on `GET /metrics`, for Prometheus to scrape.
+pub async fn serve(metrics: BridgeMetrics, address: SocketAddr) -> Result<(), anyhow::Error> {
+	movement_metrics::serve(metrics.registry().clone(), address).await
+}
@@ -0,0 +1,1471 @@
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
+	fmt::Debug,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bridge_shared::{
+	approval::Approval,
+	asset_registry::AssetRegistry,
+	attestation::{Attestation, AttestationStatement, Attestor},
+	blockchain_service::BlockchainService,
+	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
+	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
+	bridge_service::{
+		active_swap::{ActiveSwapMap, ActiveSwapState, ApproveSwapError},
+		events::{CEvent, CWarn, Event, IEvent, IWarn},
+		BridgeService,
+	},
+	metrics::{BridgeMetrics, TransferStep},
+	refund_monitor::{RefundMonitor, RefundMonitorEvent, RefundSide},
+	secret::SecretLoader,
+	transfer_index::{IndexedEvent, TransferIndex},
+	transfer_store::{
+		InMemoryTransferStateStore, TransferRecord, TransferState, TransferStateStore,
+		TransferStateStoreError,
+	},
+	types::{Amount, BridgeHashType, BridgeTransferId, RecipientAddress, TimeLockUnit},
+	work_queue::WorkQueue,
+};
+use futures::{
+	future::{self, BoxFuture, FutureExt},
+	stream::FuturesUnordered,
+	Future, StreamExt,
+};
+use tokio::{
+	sync::{mpsc, oneshot},
+	task::JoinHandle,
+};
+use tracing::{Instrument, Span};
+
+use crate::{
+	admin::{
+		rest::BridgeContracts, ApprovalStatus, InFlightTransfers, RelayerCommand,
+		RelayerCommandError, RelayerCommandResult, RelayerHandle, COMMAND_BUFFER,
+	},
+	health::HealthCheck,
+	Config,
+};
+
+pub use bridge_shared::types::ChainClock;
+
+/// Clock returning the unix timestamp in seconds, for chains whose time locks are timestamps.
+pub fn system_clock() -> ChainClock {
+	Arc::new(|| {
+		SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|now| now.as_secs())
+			.unwrap_or_default()
+	})
+}
+
+/// Clock returning the last block height stored in `height` by the monitoring of a blockchain
+/// whose time locks are counted in blocks.
+pub fn block_height_clock(height: Arc<AtomicU64>) -> ChainClock {
+	Arc::new(move || height.load(Ordering::Relaxed))
+}
+
+/// Replaces the signer of the transactions sent to a blockchain, from a signer in the format of
+/// the blockchain.
+pub type SignerRotation = Box<dyn FnMut(String) -> Result<(), String> + Send>;
+
+/// Signer rotation of the blockchains whose services do not support replacing their signer.
+pub fn unsupported_signer_rotation() -> SignerRotation {
+	Box::new(|_| Err("signer rotation is not supported by the blockchain service".to_string()))
+}
+
+/// Replaces a signer with `signer`, sent over the admin API.
+///
+/// The signer must be the key itself: the references resolved by a [`SecretLoader`] are rejected,
+/// so that the holders of the admin token can't read the environment or the files of the
+/// relayer host.
+fn rotate_signer(rotation: &mut SignerRotation, signer: &str) -> RelayerCommandResult<()> {
+	if SecretLoader::is_reference(signer) {
+		return Err(RelayerCommandError::SignerRotation(
+			"the signer must be sent as a key, not as a reference to a secret of the relayer host"
+				.to_string(),
+		));
+	}
+	rotation(signer.to_string()).map_err(RelayerCommandError::SignerRotation)
+}
+
+/// Number of events the transfer index may fall behind by before the relayer waits for it.
+const INDEX_BUFFER: usize = 1024;
+
+/// Store of the transfers initiated on one blockchain.
+pub type BoxedTransferStateStore<H> = Box<dyn TransferStateStore<Hash = H>>;
+
+/// Counters of the protocol steps observed by the relayer while running.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RelayerStats {
+	pub initiated: u64,
+	pub locked: u64,
+	pub completed: u64,
+	pub refunded: u64,
+	pub expired: u64,
+	pub reverted: u64,
+	pub pending_approval: u64,
+	/// Contract calls still unconfirmed when the relayer stopped, once the drain timeout of the
+	/// shutdown expired.
+	pub unconfirmed: u64,
+	/// Transfers parked because the counterparty contract lacked the liquidity to lock them.
+	pub insufficient_liquidity: u64,
+	/// Initiated transfers whose assets were not locked, rejected by the fee, the time lock
+	/// policy or the transfer limits.
+	pub rejected: u64,
+	pub warnings: u64,
+	/// Relayer fees collected by each fee collector, in the unit of the chain of the collector.
+	pub fees_collected: BTreeMap<String, u128>,
+}
+
+/// Drives the atomic swap protocol between two blockchains.
+///
+/// Transfers initiated on one chain are locked on the counterparty contract of the other chain,
+/// completed on the initiator contract once the secret is revealed by the recipient, and dropped
+/// when the initiator gets refunded.
+///
+/// The state of the transfers initiated on each chain is persisted in a [`TransferStateStore`],
+/// the in-flight transfers are tracked again when the relayer is restarted.
+///
+/// The time locks of the pending transfers are watched on both chains. With `auto_refund`
+/// enabled, expired transfers are refunded and expired locks are aborted by the relayer.
+///
+/// Operators control the running relayer with the [`RelayerCommand`]s sent by its [`handle`].
+/// Transfers above the approval threshold are only locked once approved by the number of
+/// approvers the relayer requires.
+///
+/// [`handle`]: Relayer::handle
+pub struct Relayer<B1, B2>
+where
+	B1: BlockchainService,
+	B2: BlockchainService,
+{
+	bridge_service: BridgeService<B1, B2>,
+	refund_monitor_1: RefundMonitor<B1::Hash>,
+	refund_monitor_2: RefundMonitor<B2::Hash>,
+	store_1: BoxedTransferStateStore<B1::Hash>,
+	store_2: BoxedTransferStateStore<B2::Hash>,
+	clock_1: ChainClock,
+	clock_2: ChainClock,
+	auto_refund: bool,
+	refund_check_interval: Duration,
+	contract_call_timeout: Duration,
+	/// Time the contract calls in flight are given to confirm on shutdown.
+	drain_timeout: Duration,
+	fee_collector_1: String,
+	fee_collector_2: String,
+	stats: RelayerStats,
+	metrics: Option<BridgeMetrics>,
+	/// Gas spent on each blockchain when it was last recorded in the metrics.
+	gas_spent_1: u64,
+	gas_spent_2: u64,
+	/// Initiation time of the transfers initiated on each blockchain, for the step latencies.
+	started_1: HashMap<BridgeTransferId<B1::Hash>, Instant>,
+	started_2: HashMap<BridgeTransferId<B2::Hash>, Instant>,
+	approvals_required: usize,
+	/// Ed25519 public keys of the operators allowed to approve transfers.
+	approvers: BTreeSet<[u8; 32]>,
+	rotate_signer_1: SignerRotation,
+	rotate_signer_2: SignerRotation,
+	/// Refunds of the expired or forced transfers initiated on each blockchain, run on a bounded
+	/// number of workers without blocking the processing of the bridge events.
+	refund_queue_1: WorkQueue<BridgeTransferId<B1::Hash>>,
+	refund_queue_2: WorkQueue<BridgeTransferId<B2::Hash>>,
+	refunds: FuturesUnordered<BoxFuture<'static, (&'static str, bool)>>,
+	/// Bridge events and expired time locks are not processed while paused.
+	paused: bool,
+	/// Signs the transitions of the transfers, which are not attested when unset.
+	attestor: Option<Attestor>,
+	/// Archive of the contract events of both blockchains, which are not indexed when unset.
+	transfer_index: Option<TransferIndexing>,
+	commands: mpsc::Receiver<RelayerCommand<B1::Hash, B2::Hash>>,
+	command_sender: mpsc::Sender<RelayerCommand<B1::Hash, B2::Hash>>,
+}
+
+impl<B1, B2> Relayer<B1, B2>
+where
+	B1: BlockchainService + 'static,
+	B2: BlockchainService + 'static,
+	B1::Address: Into<RecipientAddress>,
+	B2::Address: Into<RecipientAddress>,
+	B1::Hash: From<B2::Hash> + AsRef<[u8]>,
+	B2::Hash: From<B1::Hash> + AsRef<[u8]>,
+{
+	pub fn new(blockchain_1: B1, blockchain_2: B2, config: &Config) -> Self {
+		let (command_sender, commands) = mpsc::channel(COMMAND_BUFFER);
+		Self {
+			bridge_service: BridgeService::new(
+				blockchain_1,
+				blockchain_2,
+				config.bridge_service_config(),
+			)
+			.with_clocks(system_clock(), system_clock()),
+			refund_monitor_1: RefundMonitor::new(),
+			refund_monitor_2: RefundMonitor::new(),
+			store_1: Box::new(InMemoryTransferStateStore::new()),
+			store_2: Box::new(InMemoryTransferStateStore::new()),
+			clock_1: system_clock(),
+			clock_2: system_clock(),
+			auto_refund: config.auto_refund,
+			refund_check_interval: Duration::from_millis(config.refund_check_interval),
+			contract_call_timeout: Duration::from_millis(config.contract_call_timeout),
+			drain_timeout: Duration::from_millis(config.shutdown_drain_timeout),
+			fee_collector_1: "B1".to_string(),
+			fee_collector_2: "B2".to_string(),
+			stats: RelayerStats::default(),
+			metrics: None,
+			gas_spent_1: 0,
+			gas_spent_2: 0,
+			started_1: HashMap::new(),
+			started_2: HashMap::new(),
+			approvals_required: config.approvals_required.max(1),
+			approvers: BTreeSet::new(),
+			rotate_signer_1: unsupported_signer_rotation(),
+			rotate_signer_2: unsupported_signer_rotation(),
+			refund_queue_1: WorkQueue::new(config.workers),
+			refund_queue_2: WorkQueue::new(config.workers),
+			refunds: FuturesUnordered::new(),
+			paused: false,
+			attestor: None,
+			transfer_index: None,
+			commands,
+			command_sender,
+		}
+	}
+
+	/// Sets the clocks the time locks of each blockchain are counted from and compared with,
+	/// which default to the [`system_clock`].
+	pub fn with_clocks(mut self, clock_1: ChainClock, clock_2: ChainClock) -> Self {
+		self.bridge_service = self.bridge_service.with_clocks(clock_1.clone(), clock_2.clone());
+		self.clock_1 = clock_1;
+		self.clock_2 = clock_2;
+		self
+	}
+
+	/// Sets the units of the relative time locks of each blockchain, which default to seconds.
+	pub fn with_time_lock_units(mut self, unit_1: TimeLockUnit, unit_2: TimeLockUnit) -> Self {
+		self.bridge_service = self.bridge_service.with_time_lock_units(unit_1, unit_2);
+		self
+	}
+
+	/// Sets the stores of the transfers initiated on each blockchain.
+	pub fn with_transfer_stores(
+		mut self,
+		store_1: BoxedTransferStateStore<B1::Hash>,
+		store_2: BoxedTransferStateStore<B2::Hash>,
+	) -> Self {
+		self.store_1 = store_1;
+		self.store_2 = store_2;
+		self
+	}
+
+	/// Only relays the transfers in the assets of `registry`, in which the blockchains are named
+	/// `chain_1` and `chain_2`.
+	pub fn with_assets(
+		mut self,
+		registry: Arc<AssetRegistry>,
+		chain_1: &str,
+		chain_2: &str,
+	) -> Self {
+		self.bridge_service = self.bridge_service.with_assets(registry, chain_1, chain_2);
+		self
+	}
+
+	/// Sets the addresses the fees of the transfers initiated on each blockchain are attributed
+	/// to, which default to the name of the blockchain.
+	pub fn with_fee_collectors(
+		mut self,
+		fee_collector_1: impl Into<String>,
+		fee_collector_2: impl Into<String>,
+	) -> Self {
+		self.fee_collector_1 = fee_collector_1.into();
+		self.fee_collector_2 = fee_collector_2.into();
+		self
+	}
+
+	/// Records the progress of the transfers, the failed contract calls and the gas spent by the
+	/// contract clients in `metrics`.
+	pub fn with_metrics(mut self, metrics: BridgeMetrics) -> Self {
+		self.metrics = Some(metrics);
+		self
+	}
+
+	/// Sets the public keys of the operators allowed to approve the transfers pending approval,
+	/// which stay pending until refunded without any.
+	pub fn with_approvers(mut self, approvers: BTreeSet<[u8; 32]>) -> Self {
+		self.approvers = approvers;
+		self
+	}
+
+	/// Sets how the signers of each blockchain are replaced on request of the operators.
+	pub fn with_signer_rotations(
+		mut self,
+		rotate_signer_1: SignerRotation,
+		rotate_signer_2: SignerRotation,
+	) -> Self {
+		self.rotate_signer_1 = rotate_signer_1;
+		self.rotate_signer_2 = rotate_signer_2;
+		self
+	}
+
+	/// Signs an [`Attestation`] of each transition of the transfers, at the time of the
+	/// blockchain it was observed on, stored with the transfer.
+	pub fn with_attestor(mut self, attestor: Attestor) -> Self {
+		self.attestor = Some(attestor);
+		self
+	}
+
+	/// Writes the contract events observed on both blockchains to `transfer_index`, at the block
+	/// height or ledger version returned by `height_1` and `height_2` when they are observed, such
+	/// as a [`block_height_clock`] updated by the monitoring of each blockchain.
+	pub fn with_transfer_index(
+		mut self,
+		transfer_index: Arc<dyn TransferIndex>,
+		height_1: ChainClock,
+		height_2: ChainClock,
+	) -> Self {
+		self.transfer_index =
+			Some(TransferIndexing { index: transfer_index, height_1, height_2, writer: None });
+		self
+	}
+
+	/// Returns a handle sending commands to the relayer once it runs.
+	pub fn handle(&self) -> RelayerHandle<B1::Hash, B2::Hash> {
+		RelayerHandle::new(self.command_sender.clone())
+	}
+
+	/// Returns clones of the contracts of both blockchains, to query the state of the transfers
+	/// without going through the relayer.
+	pub fn contracts(&self) -> BridgeContracts<B1, B2> {
+		let (blockchain_1, blockchain_2) =
+			(&self.bridge_service.blockchain_1, &self.bridge_service.blockchain_2);
+		BridgeContracts {
+			initiator_1: blockchain_1.initiator_contract().clone(),
+			counterparty_1: blockchain_1.counterparty_contract().clone(),
+			initiator_2: blockchain_2.initiator_contract().clone(),
+			counterparty_2: blockchain_2.counterparty_contract().clone(),
+		}
+	}
+
+	pub fn stats(&self) -> &RelayerStats {
+		&self.stats
+	}
+
+	/// Processes bridge events until both blockchain services are exhausted.
+	pub async fn run(self) -> RelayerStats {
+		self.run_until(future::pending()).await
+	}
+
+	/// Processes bridge events until both blockchain services are exhausted or `shutdown`
+	/// resolves.
+	///
+	/// On shutdown, the swaps of the transfers initiated from then on are not started but the
+	/// transfers are stored, so that their swaps are started once the relayer restarts. The
+	/// contract calls in flight are given the drain timeout to confirm, before the stores are
+	/// flushed.
+	pub async fn run_until(mut self, shutdown: impl Future<Output = ()>) -> RelayerStats {
+		tracing::info!("Relayer: started");
+		let approvals = Approvals { approvers: &self.approvers, required: self.approvals_required };
+		restore_in_flight(
+			"B1",
+			&*self.store_1,
+			&mut self.refund_monitor_1,
+			&mut self.refund_monitor_2,
+			&mut self.bridge_service.active_swaps_b1_to_b2,
+			approvals,
+		)
+		.await;
+		restore_in_flight(
+			"B2",
+			&*self.store_2,
+			&mut self.refund_monitor_2,
+			&mut self.refund_monitor_1,
+			&mut self.bridge_service.active_swaps_b2_to_b1,
+			approvals,
+		)
+		.await;
+		if let Some(transfer_index) = &mut self.transfer_index {
+			transfer_index.start();
+		}
+		// a zero interval, which the timer rejects, checks as often as it can instead
+		let mut refund_check =
+			tokio::time::interval(self.refund_check_interval.max(Duration::from_millis(1)));
+		let shutdown = shutdown.fuse();
+		tokio::pin!(shutdown);
+		let mut drain_deadline = None;
+		loop {
+			if drain_deadline.is_some() && self.in_flight_calls() == 0 {
+				tracing::info!("Relayer: contract calls in flight drained");
+				break;
+			}
+			let drain =
+				tokio::time::sleep_until(drain_deadline.unwrap_or_else(tokio::time::Instant::now));
+			tokio::select! {
+				event = self.bridge_service.next(), if !self.paused => match event {
+					Some(event) => self.handle_event(event).await,
+					None => break,
+				},
+				Some(event) = self.refund_monitor_1.next(), if !self.paused => {
+					let timeout = self.contract_call_timeout;
+					self.stats.expired += 1;
+					if self.auto_refund {
+						let blockchain = &self.bridge_service.blockchain_1;
+						let queue = &self.refund_queue_1;
+						self.refunds.push(refund_expired("B1", blockchain, queue, event, timeout));
+					} else {
+						tracing::warn!("Relayer[B1]: {:?}", event);
+					}
+				}
+				Some(event) = self.refund_monitor_2.next(), if !self.paused => {
+					let timeout = self.contract_call_timeout;
+					self.stats.expired += 1;
+					if self.auto_refund {
+						let blockchain = &self.bridge_service.blockchain_2;
+						let queue = &self.refund_queue_2;
+						self.refunds.push(refund_expired("B2", blockchain, queue, event, timeout));
+					} else {
+						tracing::warn!("Relayer[B2]: {:?}", event);
+					}
+				}
+				Some((chain, refunded)) = self.refunds.next(), if !self.refunds.is_empty() => {
+					if !refunded {
+						self.rpc_error(chain);
+					}
+				}
+				Some(command) = self.commands.recv() => self.handle_command(command).await,
+				_ = refund_check.tick() => {
+					self.refund_monitor_1.update_time((self.clock_1)());
+					self.refund_monitor_2.update_time((self.clock_2)());
+					self.record_gas_spent();
+				}
+				_ = &mut shutdown, if drain_deadline.is_none() => {
+					tracing::info!(
+						"Relayer: shutting down, draining {} contract calls in flight",
+						self.in_flight_calls()
+					);
+					self.bridge_service.stop_accepting();
+					drain_deadline = Some(tokio::time::Instant::now() + self.drain_timeout);
+				}
+				_ = drain, if drain_deadline.is_some() => {
+					tracing::warn!(
+						"Relayer: drain timeout expired with {} contract calls in flight",
+						self.in_flight_calls()
+					);
+					break;
+				}
+			}
+		}
+		self.stats.unconfirmed = self.in_flight_calls() as u64;
+		self.record_gas_spent();
+		if let Some(transfer_index) = &mut self.transfer_index {
+			transfer_index.stop().await;
+		}
+		flush("B1", &*self.store_1).await;
+		flush("B2", &*self.store_2).await;
+		tracing::info!("Relayer: stopped {:?}", self.stats);
+		self.stats
+	}
+
+	/// Contract calls of the swaps and refunds which are not confirmed yet.
+	fn in_flight_calls(&self) -> usize {
+		self.bridge_service.submitting() + self.refunds.len()
+	}
+
+	fn rpc_error(&self, chain: &str) {
+		if let Some(metrics) = &self.metrics {
+			metrics.rpc_error(chain);
+		}
+	}
+
+	/// Records the gas the contract clients of both blockchains spent since the last call.
+	fn record_gas_spent(&mut self) {
+		let Some(metrics) = &self.metrics else {
+			return;
+		};
+		let gas_spent_1 = gas_spent(&self.bridge_service.blockchain_1);
+		let gas_spent_2 = gas_spent(&self.bridge_service.blockchain_2);
+		metrics.gas_spent("B1", gas_spent_1.saturating_sub(self.gas_spent_1));
+		metrics.gas_spent("B2", gas_spent_2.saturating_sub(self.gas_spent_2));
+		self.gas_spent_1 = gas_spent_1;
+		self.gas_spent_2 = gas_spent_2;
+	}
+
+	async fn handle_command(&mut self, command: RelayerCommand<B1::Hash, B2::Hash>) {
+		let timeout = self.contract_call_timeout;
+		match command {
+			RelayerCommand::InFlight(reply) => {
+				let in_flight = async {
+					Ok(InFlightTransfers {
+						b1: self.store_1.in_flight().await?,
+						b2: self.store_2.in_flight().await?,
+					})
+				};
+				let _ = reply.send(in_flight.await);
+			}
+			RelayerCommand::TransferB1(bridge_transfer_id, reply) => {
+				let _ = reply.send(stored_transfer(&*self.store_1, &bridge_transfer_id).await);
+			}
+			RelayerCommand::TransferB2(bridge_transfer_id, reply) => {
+				let _ = reply.send(stored_transfer(&*self.store_2, &bridge_transfer_id).await);
+			}
+			RelayerCommand::AttestationsB1(bridge_transfer_id, reply) => {
+				let _ = reply.send(attestations(&*self.store_1, &bridge_transfer_id).await);
+			}
+			RelayerCommand::AttestationsB2(bridge_transfer_id, reply) => {
+				let _ = reply.send(attestations(&*self.store_2, &bridge_transfer_id).await);
+			}
+			RelayerCommand::RefundB1(bridge_transfer_id, reply) => {
+				let blockchain = &self.bridge_service.blockchain_1;
+				let queue = &self.refund_queue_1;
+				self.refunds.push(force_refund(
+					"B1",
+					blockchain,
+					queue,
+					bridge_transfer_id,
+					timeout,
+					reply,
+				));
+			}
+			RelayerCommand::RefundB2(bridge_transfer_id, reply) => {
+				let blockchain = &self.bridge_service.blockchain_2;
+				let queue = &self.refund_queue_2;
+				self.refunds.push(force_refund(
+					"B2",
+					blockchain,
+					queue,
+					bridge_transfer_id,
+					timeout,
+					reply,
+				));
+			}
+			RelayerCommand::Pause(reply) => {
+				tracing::info!("Relayer: processing paused");
+				self.paused = true;
+				let _ = reply.send(Ok(()));
+			}
+			RelayerCommand::Resume(reply) => {
+				tracing::info!("Relayer: processing resumed");
+				self.paused = false;
+				let _ = reply.send(Ok(()));
+			}
+			RelayerCommand::RotateSignerB1(signer, reply) => {
+				tracing::info!("Relayer[B1]: rotating signer");
+				let _ = reply.send(rotate_signer(&mut self.rotate_signer_1, &signer));
+			}
+			RelayerCommand::RotateSignerB2(signer, reply) => {
+				tracing::info!("Relayer[B2]: rotating signer");
+				let _ = reply.send(rotate_signer(&mut self.rotate_signer_2, &signer));
+			}
+			RelayerCommand::ApproveB1(bridge_transfer_id, approval, reply) => {
+				let result = approve(
+					"B1",
+					&mut self.bridge_service.active_swaps_b1_to_b2,
+					&*self.store_1,
+					&self.approvers,
+					self.approvals_required,
+					bridge_transfer_id,
+					approval,
+				)
+				.await;
+				let _ = reply.send(result);
+			}
+			RelayerCommand::ApproveB2(bridge_transfer_id, approval, reply) => {
+				let result = approve(
+					"B2",
+					&mut self.bridge_service.active_swaps_b2_to_b1,
+					&*self.store_2,
+					&self.approvers,
+					self.approvals_required,
+					bridge_transfer_id,
+					approval,
+				)
+				.await;
+				let _ = reply.send(result);
+			}
+			RelayerCommand::Health(reply) => {
+				let (store_1, store_2) =
+					futures::join!(self.store_1.check_writable(), self.store_2.check_writable());
+				let checks = vec![
+					HealthCheck::new("store_b1", store_1.map_err(|error| error.to_string())),
+					HealthCheck::new("store_b2", store_2.map_err(|error| error.to_string())),
+				];
+				let _ = reply.send(Ok(checks));
+			}
+		}
+	}
+
+	async fn handle_event(&mut self, event: Event<B1, B2>) {
+		if let Some(metrics) = &self.metrics {
+			match &event {
+				Event::B1I(event) => {
+					observe_initiator_event(metrics, &mut self.started_1, "B1", event);
+				}
+				Event::B2I(event) => {
+					observe_initiator_event(metrics, &mut self.started_2, "B2", event);
+				}
+				Event::B1C(event) => {
+					observe_counterparty_event(metrics, &self.started_2, "B1", event);
+				}
+				Event::B2C(event) => {
+					observe_counterparty_event(metrics, &self.started_1, "B2", event);
+				}
+			}
+		}
+		if let Some(transfer_index) = &self.transfer_index {
+			let (height_1, height_2) = (&transfer_index.height_1, &transfer_index.height_2);
+			let indexed = match &event {
+				Event::B1I(IEvent::ContractEvent(event)) => {
+					Some(IndexedEvent::from_initiator_event("B1", event, height_1()))
+				}
+				Event::B2I(IEvent::ContractEvent(event)) => {
+					Some(IndexedEvent::from_initiator_event("B2", event, height_2()))
+				}
+				Event::B1C(CEvent::ContractEvent(event)) => {
+					Some(IndexedEvent::from_counterparty_event("B1", event, height_1()))
+				}
+				Event::B2C(CEvent::ContractEvent(event)) => {
+					Some(IndexedEvent::from_counterparty_event("B2", event, height_2()))
+				}
+				_ => None,
+			};
+			if let Some(indexed) = indexed {
+				transfer_index.index(indexed).await;
+			}
+		}
+
+		let stats = &mut self.stats;
+		let attestor = self.attestor.as_ref();
+		match event {
+			Event::B1I(event) => {
+				let (monitor, store) = (&mut self.refund_monitor_1, &*self.store_1);
+				monitor.update_time((self.clock_1)());
+				let fee_collector = &self.fee_collector_1;
+				let attesting = Attesting::now(attestor, &self.clock_1);
+				let span = event.bridge_transfer_id().span();
+				handle_initiator_event(
+					stats,
+					monitor,
+					store,
+					fee_collector,
+					attesting,
+					"B1",
+					event,
+				)
+				.instrument(span)
+				.await;
+			}
+			Event::B2I(event) => {
+				let (monitor, store) = (&mut self.refund_monitor_2, &*self.store_2);
+				monitor.update_time((self.clock_2)());
+				let fee_collector = &self.fee_collector_2;
+				let attesting = Attesting::now(attestor, &self.clock_2);
+				let span = event.bridge_transfer_id().span();
+				handle_initiator_event(
+					stats,
+					monitor,
+					store,
+					fee_collector,
+					attesting,
+					"B2",
+					event,
+				)
+				.instrument(span)
+				.await;
+			}
+			// Assets are locked on the counterparty contract of B1 for transfers initiated on B2
+			Event::B1C(event) => {
+				let (monitor, store) = (&mut self.refund_monitor_1, &*self.store_2);
+				monitor.update_time((self.clock_1)());
+				let attesting = Attesting::now(attestor, &self.clock_1);
+				let span = event.bridge_transfer_id().map_or_else(Span::none, |id| id.span());
+				handle_counterparty_event(stats, monitor, store, attesting, "B1", event)
+					.instrument(span)
+					.await;
+			}
+			Event::B2C(event) => {
+				let (monitor, store) = (&mut self.refund_monitor_2, &*self.store_1);
+				monitor.update_time((self.clock_2)());
+				let attesting = Attesting::now(attestor, &self.clock_2);
+				let span = event.bridge_transfer_id().map_or_else(Span::none, |id| id.span());
+				handle_counterparty_event(stats, monitor, store, attesting, "B2", event)
+					.instrument(span)
+					.await;
+			}
+		}
+	}
+}
+
+/// Writes the contract events to a [`TransferIndex`] in the order they are observed, from a task
+/// of its own so that the bridge events are not held by the writes.
+struct TransferIndexing {
+	index: Arc<dyn TransferIndex>,
+	/// Current block height or ledger version of each blockchain.
+	height_1: ChainClock,
+	height_2: ChainClock,
+	/// Sender of the events to the task writing them, while the relayer runs.
+	writer: Option<(mpsc::Sender<IndexedEvent>, JoinHandle<()>)>,
+}
+
+impl TransferIndexing {
+	fn start(&mut self) {
+		let (sender, mut events) = mpsc::channel::<IndexedEvent>(INDEX_BUFFER);
+		let index = self.index.clone();
+		let task = tokio::spawn(async move {
+			while let Some(event) = events.recv().await {
+				let chain = event.chain.clone();
+				if let Err(error) = index.index(event).await {
+					tracing::warn!("Relayer[{chain}]: failed to index event: {error}");
+				}
+			}
+		});
+		self.writer = Some((sender, task));
+	}
+
+	/// Queues `event` for the writing task, waiting only when the index is [`INDEX_BUFFER`]
+	/// events behind.
+	async fn index(&self, event: IndexedEvent) {
+		let Some((sender, _)) = &self.writer else {
+			return;
+		};
+		if sender.send(event).await.is_err() {
+			tracing::warn!("Relayer: the transfer index writer stopped, event not indexed");
+		}
+	}
+
+	/// Waits for the queued events to be written.
+	async fn stop(&mut self) {
+		let Some((sender, task)) = self.writer.take() else {
+			return;
+		};
+		drop(sender);
+		if let Err(error) = task.await {
+			tracing::warn!("Relayer: the transfer index writer failed: {error}");
+		}
+	}
+}
+
+/// Signs the attestations of the transitions observed on a blockchain, at its time.
+#[derive(Clone, Copy)]
+struct Attesting<'a> {
+	attestor: &'a Attestor,
+	observed_at: u64,
+}
+
+impl<'a> Attesting<'a> {
+	/// Attests the transitions at the current time of `clock`, if the relayer has an attestor.
+	fn now(attestor: Option<&'a Attestor>, clock: &ChainClock) -> Option<Self> {
+		attestor.map(|attestor| Self { attestor, observed_at: clock() })
+	}
+}
+
+/// Gas spent by the transactions sent to the contracts of a blockchain.
+fn gas_spent<B: BlockchainService>(blockchain: &B) -> u64 {
+	blockchain
+		.initiator_contract()
+		.gas_spent()
+		.saturating_add(blockchain.counterparty_contract().gas_spent())
+}
+
+async fn flush<H: BridgeHashType>(chain: &str, store: &dyn TransferStateStore<Hash = H>) {
+	if let Err(error) = store.flush().await {
+		tracing::warn!("Relayer[{chain}]: failed to flush the transfer store: {error}");
+	}
+}
+
+/// Approvers of the transfers pending approval, and the number of their approvals a transfer
+/// requires to be locked.
+#[derive(Clone, Copy)]
+struct Approvals<'a> {
+	approvers: &'a BTreeSet<[u8; 32]>,
+	required: usize,
+}
+
+/// Tracks again the time locks of the in-flight transfers initiated on `chain`, and restores
+/// their swaps. The swaps of the locked
+/// transfers wait for the completion of the recipient, so that their assets are not locked twice
+/// and the initiator side is completed once the recipient is.
+///
+/// The swaps pending approval wait for their approval again, and are approved right away if the
+/// approvals kept in the store already suffice. The swaps parked for insufficient liquidity check
+/// the liquidity of the counterparty contract again.
+///
+/// The details of a transfer are read from the initiator contract. If they can't be, its swap is
+/// restored when the initiated event of the transfer is delivered again.
+async fn restore_in_flight<BFrom, BTo>(
+	chain: &str,
+	store: &dyn TransferStateStore<Hash = BFrom::Hash>,
+	initiator_monitor: &mut RefundMonitor<BFrom::Hash>,
+	counterparty_monitor: &mut RefundMonitor<BTo::Hash>,
+	active_swaps: &mut ActiveSwapMap<BFrom, BTo>,
+	approvals: Approvals<'_>,
+) where
+	BFrom: BlockchainService + 'static,
+	BTo: BlockchainService + 'static,
+	BTo::Hash: From<BFrom::Hash>,
+{
+	let transfers = match store.in_flight().await {
+		Ok(transfers) => transfers,
+		Err(error) => {
+			tracing::warn!("Relayer[{chain}]: failed to load in-flight transfers: {error}");
+			return;
+		}
+	};
+	tracing::info!("Relayer[{chain}]: restoring {} in-flight transfers", transfers.len());
+
+	for (bridge_transfer_id, record) in transfers {
+		if matches!(
+			record.state,
+			TransferState::Initiated
+				| TransferState::PendingApproval
+				| TransferState::InsufficientLiquidity
+				| TransferState::Locked
+		) {
+			restore_swap(chain, active_swaps, bridge_transfer_id.clone(), &record).await;
+		}
+		if record.state == TransferState::PendingApproval {
+			approve_restored(chain, store, active_swaps, approvals, &bridge_transfer_id).await;
+		}
+		if let Some(expiry) = record.counterparty_time_lock {
+			counterparty_monitor.track(
+				RefundSide::Counterparty,
+				BridgeTransferId(BTo::Hash::from(bridge_transfer_id.0.clone())),
+				expiry,
+			);
+		}
+		initiator_monitor.track(
+			RefundSide::Initiator,
+			bridge_transfer_id,
+			record.initiator_time_lock,
+		);
+	}
+}
+
+/// Restores the swap of a transfer in flight before the restart.
+///
+/// The swap of a transfer stored as initiated is started from the start, the swap of a transfer
+/// pending approval waits for its approval and the swap of a transfer parked for insufficient
+/// liquidity waits for the next check of the liquidity, unless the counterparty contract holds its
+/// lock already, confirmed after the relayer stopped.
+async fn restore_swap<BFrom, BTo>(
+	chain: &str,
+	active_swaps: &mut ActiveSwapMap<BFrom, BTo>,
+	bridge_transfer_id: BridgeTransferId<BFrom::Hash>,
+	record: &TransferRecord,
+) where
+	BFrom: BlockchainService + 'static,
+	BTo: BlockchainService + 'static,
+	BTo::Hash: From<BFrom::Hash>,
+{
+	let state = record.state;
+	let timeout = active_swaps.config.contract_call_timeout;
+	let locked = match state {
+		TransferState::Locked => true,
+		_ => {
+			let mut counterparty_contract = active_swaps.counterparty_contract.clone();
+			let lock_id = BridgeTransferId(BTo::Hash::from(bridge_transfer_id.0.clone()));
+			let lock = tokio::time::timeout(
+				timeout,
+				counterparty_contract.get_bridge_transfer_details(lock_id),
+			)
+			.await;
+			match lock {
+				Ok(Ok(lock)) => lock.is_some(),
+				Ok(Err(error)) => {
+					tracing::warn!(
+						"Relayer[{chain}]: failed to read the lock of transfer {:?}: {error}",
+						bridge_transfer_id
+					);
+					return;
+				}
+				Err(_) => {
+					tracing::warn!(
+						"Relayer[{chain}]: timed out reading the lock of transfer {:?}",
+						bridge_transfer_id
+					);
+					return;
+				}
+			}
+		}
+	};
+	if locked {
+		active_swaps.restore_locked(bridge_transfer_id.clone());
+	} else if state == TransferState::PendingApproval {
+		let initiator_expiry = record.initiator_time_lock.clone();
+		active_swaps.restore_pending_approval(bridge_transfer_id.clone(), initiator_expiry);
+	} else if state == TransferState::InsufficientLiquidity {
+		let initiator_expiry = record.initiator_time_lock.clone();
+		active_swaps.restore_insufficient_liquidity(bridge_transfer_id.clone(), initiator_expiry);
+	}
+	let mut initiator_contract = active_swaps.initiator_contract.clone();
+	let details = tokio::time::timeout(
+		timeout,
+		initiator_contract.get_bridge_transfer_details(bridge_transfer_id.clone()),
+	)
+	.await;
+	match details {
+		Ok(Ok(Some(details))) => {
+			if let Err(error) = active_swaps.start_bridge_transfer(details) {
+				tracing::warn!(
+					"Relayer[{chain}]: failed to restore the {:?} transfer {:?}: {error}",
+					state,
+					bridge_transfer_id
+				);
+			}
+		}
+		Ok(Ok(None)) => {
+			tracing::warn!(
+				"Relayer[{chain}]: {:?} transfer {:?} not found, waiting for its initiated event",
+				state,
+				bridge_transfer_id
+			);
+		}
+		Ok(Err(error)) => {
+			tracing::warn!(
+				"Relayer[{chain}]: failed to read the {:?} transfer {:?}: {error}",
+				state,
+				bridge_transfer_id
+			);
+		}
+		Err(_) => {
+			tracing::warn!(
+				"Relayer[{chain}]: timed out reading the {:?} transfer {:?}",
+				state,
+				bridge_transfer_id
+			);
+		}
+	}
+}
+
+/// Approves a transfer restored pending approval if the approvals kept in `store` by the current
+/// approvers already suffice, such as when the relayer stopped before it could lock it.
+async fn approve_restored<BFrom, BTo>(
+	chain: &str,
+	store: &dyn TransferStateStore<Hash = BFrom::Hash>,
+	active_swaps: &mut ActiveSwapMap<BFrom, BTo>,
+	approvals: Approvals<'_>,
+	bridge_transfer_id: &BridgeTransferId<BFrom::Hash>,
+) where
+	BFrom: BlockchainService + 'static,
+	BTo: BlockchainService + 'static,
+	BTo::Hash: From<BFrom::Hash>,
+{
+	let is_pending = active_swaps
+		.get(bridge_transfer_id)
+		.is_some_and(|swap| matches!(swap.state, ActiveSwapState::PendingApproval));
+	if !is_pending {
+		return;
+	}
+	let stored = match store.approvals(bridge_transfer_id).await {
+		Ok(stored) => stored,
+		Err(error) => {
+			tracing::warn!(
+				"Relayer[{chain}]: failed to load the approvals of transfer {:?}: {error}",
+				bridge_transfer_id
+			);
+			return;
+		}
+	};
+	let approvals_count = stored
+		.iter()
+		.filter(|approval| approvals.approvers.contains(&approval.public_key))
+		.count();
+	tracing::info!(
+		"Relayer[{chain}]: restored transfer {:?} with {approvals_count} of {} approvals",
+		bridge_transfer_id,
+		approvals.required
+	);
+	if approvals_count < approvals.required {
+		return;
+	}
+	if let Err(error) = active_swaps.approve_bridge_transfer(bridge_transfer_id) {
+		tracing::warn!(
+			"Relayer[{chain}]: failed to approve restored transfer {:?}: {error}",
+			bridge_transfer_id
+		);
+	}
+}
+
+async fn handle_initiator_event<A: Debug, H: BridgeHashType + AsRef<[u8]>>(
+	stats: &mut RelayerStats,
+	refund_monitor: &mut RefundMonitor<H>,
+	store: &dyn TransferStateStore<Hash = H>,
+	fee_collector: &str,
+	attesting: Option<Attesting<'_>>,
+	chain: &str,
+	event: IEvent<A, H>,
+) {
+	match event {
+		IEvent::ContractEvent(BridgeContractInitiatorEvent::Initiated(details)) => {
+			stats.initiated += 1;
+			tracing::info!("Relayer[{chain}]: transfer initiated {:?}", details);
+			let expiry = refund_monitor.expiry(&details.time_lock);
+			let result = store.initiate(details.bridge_transfer_id.clone(), expiry.clone()).await;
+			match result {
+				Ok(()) => {
+					let (bridge_transfer_id, state) =
+						(details.bridge_transfer_id.clone(), TransferState::Initiated);
+					attest(store, attesting, chain, bridge_transfer_id, state).await;
+				}
+				// Replayed after a restart, the transfer and its expiry were restored from the
+				// store
+				Err(TransferStateStoreError::AlreadyStored) => {
+					tracing::debug!("Relayer[{chain}]: initiated transfer already stored");
+					return;
+				}
+				Err(error) => {
+					tracing::warn!("Relayer[{chain}]: failed to store initiated transfer: {error}");
+				}
+			}
+			refund_monitor.track(RefundSide::Initiator, details.bridge_transfer_id, expiry);
+		}
+		IEvent::ContractEvent(BridgeContractInitiatorEvent::Completed(bridge_transfer_id)) => {
+			stats.completed += 1;
+			tracing::info!("Relayer[{chain}]: transfer completed {:?}", bridge_transfer_id);
+			refund_monitor.untrack(RefundSide::Initiator, &bridge_transfer_id);
+			let state = TransferState::Completed;
+			store_transition(store, attesting, chain, bridge_transfer_id, state).await;
+		}
+		IEvent::ContractEvent(BridgeContractInitiatorEvent::Refunded(bridge_transfer_id)) => {
+			stats.refunded += 1;
+			tracing::info!("Relayer[{chain}]: transfer refunded {:?}", bridge_transfer_id);
+			refund_monitor.untrack(RefundSide::Initiator, &bridge_transfer_id);
+			let state = TransferState::Refunded;
+			store_transition(store, attesting, chain, bridge_transfer_id, state).await;
+		}
+		IEvent::ContractEvent(BridgeContractInitiatorEvent::Reverted(bridge_transfer_id)) => {
+			stats.reverted += 1;
+			tracing::warn!(
+				"Relayer[{chain}]: transfer reverted by a reorg {:?}",
+				bridge_transfer_id
+			);
+			refund_monitor.untrack(RefundSide::Initiator, &bridge_transfer_id);
+			if let Err(error) = store.remove(&bridge_transfer_id).await {
+				tracing::warn!("Relayer[{chain}]: failed to remove reverted transfer: {error}");
+			}
+		}
+		IEvent::FeeCollected(bridge_transfer_id, Amount(fee)) => {
+			tracing::info!(
+				"Relayer[{chain}]: fee {fee} of transfer {:?} collected for {fee_collector}",
+				bridge_transfer_id
+			);
+			*stats.fees_collected.entry(fee_collector.to_string()).or_default() += fee;
+		}
+		IEvent::PendingApproval(bridge_transfer_id) => {
+			stats.pending_approval += 1;
+			tracing::info!("Relayer[{chain}]: transfer pending approval {:?}", bridge_transfer_id);
+			let state = TransferState::PendingApproval;
+			store_transition(store, attesting, chain, bridge_transfer_id, state).await;
+		}
+		IEvent::InsufficientLiquidity(bridge_transfer_id, error) => {
+			stats.insufficient_liquidity += 1;
+			// Logged as an error to alert the operators, who have to provide the liquidity
+			tracing::error!(
+				"Relayer[{chain}]: transfer {:?} parked, the counterparty contract lacks liquidity: {error}",
+				bridge_transfer_id
+			);
+			let state = TransferState::InsufficientLiquidity;
+			store_transition(store, attesting, chain, bridge_transfer_id, state).await;
+		}
+		IEvent::RetryCompletingTransfer(bridge_transfer_id) => {
+			tracing::debug!(
+				"Relayer[{chain}]: retrying to complete transfer {:?}",
+				bridge_transfer_id
+			);
+		}
+		IEvent::Warn(IWarn::ShuttingDown(details)) => {
+			stats.warnings += 1;
+			tracing::warn!(
+				"Relayer[{chain}]: transfer initiated while shutting down, stored to start its swap after the restart {:?}",
+				details
+			);
+			let expiry = refund_monitor.expiry(&details.time_lock);
+			match store.initiate(details.bridge_transfer_id, expiry).await {
+				Ok(()) | Err(TransferStateStoreError::AlreadyStored) => {}
+				Err(error) => {
+					tracing::warn!("Relayer[{chain}]: failed to store initiated transfer: {error}");
+				}
+			}
+		}
+		IEvent::Warn(warn) => {
+			stats.warnings += 1;
+			if let Some(reason) = warn.rejection_reason() {
+				stats.rejected += 1;
+				tracing::warn!("Relayer[{chain}]: transfer rejected by {reason} {:?}", warn);
+			} else {
+				tracing::warn!("Relayer[{chain}]: initiator warning {:?}", warn);
+			}
+		}
+	}
+}
+
+async fn handle_counterparty_event<H, HFrom>(
+	stats: &mut RelayerStats,
+	refund_monitor: &mut RefundMonitor<H>,
+	store: &dyn TransferStateStore<Hash = HFrom>,
+	attesting: Option<Attesting<'_>>,
+	chain: &str,
+	event: CEvent<H>,
+) where
+	H: BridgeHashType,
+	HFrom: BridgeHashType + From<H> + AsRef<[u8]>,
+{
+	match event {
+		CEvent::ContractEvent(BridgeContractCounterpartyEvent::Locked(details)) => {
+			stats.locked += 1;
+			tracing::info!("Relayer[{chain}]: assets locked {:?}", details);
+			let bridge_transfer_id =
+				BridgeTransferId(HFrom::from(details.bridge_transfer_id.0.clone()));
+			let expiry = refund_monitor.expiry(&details.time_lock);
+			match store.lock(bridge_transfer_id.clone(), expiry.clone()).await {
+				Ok(_) => {
+					let state = TransferState::Locked;
+					attest(store, attesting, chain, bridge_transfer_id, state).await;
+				}
+				// Replayed after a restart, the lock and its expiry were restored from the store
+				Err(TransferStateStoreError::InvalidTransition { from, .. }) => {
+					tracing::debug!("Relayer[{chain}]: locked transfer already {:?}", from);
+					return;
+				}
+				Err(error) => {
+					tracing::warn!("Relayer[{chain}]: failed to store locked transfer: {error}");
+				}
+			}
+			refund_monitor.track(RefundSide::Counterparty, details.bridge_transfer_id, expiry);
+		}
+		CEvent::ContractEvent(BridgeContractCounterpartyEvent::Completed(details)) => {
+			tracing::info!(
+				"Relayer[{chain}]: secret revealed for transfer {:?}",
+				details.bridge_transfer_id
+			);
+			refund_monitor.untrack(RefundSide::Counterparty, &details.bridge_transfer_id);
+		}
+		CEvent::ContractEvent(BridgeContractCounterpartyEvent::Aborted(bridge_transfer_id)) => {
+			tracing::info!("Relayer[{chain}]: lock aborted for transfer {:?}", bridge_transfer_id);
+			refund_monitor.untrack(RefundSide::Counterparty, &bridge_transfer_id);
+		}
+		CEvent::RetryLockingAssets(bridge_transfer_id) => {
+			tracing::debug!(
+				"Relayer[{chain}]: retrying to lock assets for transfer {:?}",
+				bridge_transfer_id
+			);
+		}
+		CEvent::Warn(warn) => {
+			stats.warnings += 1;
+			tracing::warn!("Relayer[{chain}]: counterparty warning {:?}", warn);
+		}
+	}
+}
+
+/// Counts the steps of the transfers initiated on `chain` and their latency.
+fn observe_initiator_event<A, H: BridgeHashType>(
+	metrics: &BridgeMetrics,
+	started: &mut HashMap<BridgeTransferId<H>, Instant>,
+	chain: &str,
+	event: &IEvent<A, H>,
+) {
+	let (step, bridge_transfer_id) = match event {
+		IEvent::ContractEvent(BridgeContractInitiatorEvent::Initiated(details)) => {
+			metrics.transfer_step(chain, TransferStep::Initiated);
+			started.insert(details.bridge_transfer_id.clone(), Instant::now());
+			return;
+		}
+		IEvent::ContractEvent(BridgeContractInitiatorEvent::Completed(bridge_transfer_id)) => {
+			(TransferStep::Completed, bridge_transfer_id)
+		}
+		IEvent::ContractEvent(BridgeContractInitiatorEvent::Refunded(bridge_transfer_id)) => {
+			(TransferStep::Refunded, bridge_transfer_id)
+		}
+		IEvent::ContractEvent(BridgeContractInitiatorEvent::Reverted(bridge_transfer_id)) => {
+			started.remove(bridge_transfer_id);
+			return;
+		}
+		IEvent::Warn(IWarn::CompleteTransferError(_)) => {
+			metrics.rpc_error(chain);
+			return;
+		}
+		IEvent::InsufficientLiquidity(..) => {
+			metrics.insufficient_liquidity(chain);
+			return;
+		}
+		IEvent::Warn(warn) => {
+			if let Some(reason) = warn.rejection_reason() {
+				metrics.transfer_rejected(chain, reason);
+			}
+			return;
+		}
+		_ => return,
+	};
+	metrics.transfer_step(chain, step);
+	if let Some(started_at) = started.remove(bridge_transfer_id) {
+		metrics.observe_step_latency(chain, step, started_at.elapsed());
+	}
+}
+
+/// Counts the assets locked on `chain` for transfers initiated on the other blockchain.
+fn observe_counterparty_event<H, HFrom>(
+	metrics: &BridgeMetrics,
+	started: &HashMap<BridgeTransferId<HFrom>, Instant>,
+	chain: &str,
+	event: &CEvent<H>,
+) where
+	H: BridgeHashType,
+	HFrom: BridgeHashType + From<H>,
+{
+	match event {
+		CEvent::ContractEvent(BridgeContractCounterpartyEvent::Locked(details)) => {
+			metrics.transfer_step(chain, TransferStep::Locked);
+			let bridge_transfer_id =
+				BridgeTransferId(HFrom::from(details.bridge_transfer_id.0.clone()));
+			if let Some(started_at) = started.get(&bridge_transfer_id) {
+				metrics.observe_step_latency(chain, TransferStep::Locked, started_at.elapsed());
+			}
+		}
+		CEvent::Warn(CWarn::BridgeAssetsLockingError(_)) => metrics.rpc_error(chain),
+		_ => {}
+	}
+}
+
+/// Records the approval of a transfer pending approval, once its signature is verified against
+/// the public key of one of the `approvers`, and locks its assets once it has the required number
+/// of approvers. The approvals are kept in `store`, so that they survive a restart.
+async fn approve<BFrom, BTo>(
+	chain: &str,
+	active_swaps: &mut ActiveSwapMap<BFrom, BTo>,
+	store: &dyn TransferStateStore<Hash = BFrom::Hash>,
+	approvers: &BTreeSet<[u8; 32]>,
+	approvals_required: usize,
+	bridge_transfer_id: BridgeTransferId<BFrom::Hash>,
+	approval: Approval,
+) -> RelayerCommandResult<ApprovalStatus>
+where
+	BFrom: BlockchainService + 'static,
+	BTo: BlockchainService + 'static,
+	BFrom::Hash: AsRef<[u8]>,
+	BTo::Hash: From<BFrom::Hash>,
+{
+	let approver = hex::encode(approval.public_key);
+	if !approvers.contains(&approval.public_key) {
+		return Err(RelayerCommandError::UnknownApprover(approver));
+	}
+	approval
+		.verify(chain, bridge_transfer_id.0.as_ref())
+		.map_err(|_| RelayerCommandError::InvalidApproval)?;
+	if !active_swaps.already_executing(&bridge_transfer_id) {
+		return Err(RelayerCommandError::TransferNotFound);
+	}
+	let is_pending = active_swaps
+		.get(&bridge_transfer_id)
+		.is_some_and(|swap| matches!(swap.state, ActiveSwapState::PendingApproval));
+	if !is_pending {
+		return Err(RelayerCommandError::NotPendingApproval);
+	}
+
+	tracing::info!("Relayer[{chain}]: transfer {:?} approved by 0x{approver}", bridge_transfer_id);
+	store.put_approval(bridge_transfer_id.clone(), approval).await?;
+	let approvals_count = store
+		.approvals(&bridge_transfer_id)
+		.await?
+		.iter()
+		.filter(|approval| approvers.contains(&approval.public_key))
+		.count();
+	let approved = approvals_count >= approvals_required;
+	if approved {
+		active_swaps
+			.approve_bridge_transfer(&bridge_transfer_id)
+			.map_err(|error| match error {
+				ApproveSwapError::NonExistingSwap => RelayerCommandError::TransferNotFound,
+				ApproveSwapError::NotPendingApproval => RelayerCommandError::NotPendingApproval,
+				ApproveSwapError::TimeLockElapsed(error) => {
+					RelayerCommandError::TimeLockElapsed(error)
+				}
+			})?;
+	}
+	Ok(ApprovalStatus { approvals: approvals_count, approvals_required, approved })
+}
+
+async fn stored_transfer<H: BridgeHashType>(
+	store: &dyn TransferStateStore<Hash = H>,
+	bridge_transfer_id: &BridgeTransferId<H>,
+) -> RelayerCommandResult<TransferRecord> {
+	store
+		.get(bridge_transfer_id)
+		.await?
+		.ok_or(RelayerCommandError::TransferNotFound)
+}
+
+async fn attestations<H: BridgeHashType>(
+	store: &dyn TransferStateStore<Hash = H>,
+	bridge_transfer_id: &BridgeTransferId<H>,
+) -> RelayerCommandResult<Vec<Attestation>> {
+	stored_transfer(store, bridge_transfer_id).await?;
+	Ok(store.attestations(bridge_transfer_id).await?)
+}
+
+async fn store_transition<H: BridgeHashType + AsRef<[u8]>>(
+	store: &dyn TransferStateStore<Hash = H>,
+	attesting: Option<Attesting<'_>>,
+	chain: &str,
+	bridge_transfer_id: BridgeTransferId<H>,
+	state: TransferState,
+) {
+	match store.transition(bridge_transfer_id.clone(), state).await {
+		Ok(_) => attest(store, attesting, chain, bridge_transfer_id, state).await,
+		// The event was delivered again
+		Err(TransferStateStoreError::InvalidTransition { from, to }) if from == to => {
+			tracing::debug!("Relayer[{chain}]: transfer state {state:?} already stored");
+		}
+		Err(error) => {
+			tracing::warn!("Relayer[{chain}]: failed to store transfer state {state:?}: {error}");
+		}
+	}
+}
+
+/// Signs and stores the attestation of the transition of a transfer to `state`, observed on
+/// `chain`.
+async fn attest<H: BridgeHashType + AsRef<[u8]>>(
+	store: &dyn TransferStateStore<Hash = H>,
+	attesting: Option<Attesting<'_>>,
+	chain: &str,
+	bridge_transfer_id: BridgeTransferId<H>,
+	state: TransferState,
+) {
+	let Some(Attesting { attestor, observed_at }) = attesting else {
+		return;
+	};
+	let attestation = attestor.attest(AttestationStatement {
+		chain: chain.to_string(),
+		bridge_transfer_id: bridge_transfer_id.0.as_ref().to_vec(),
+		state,
+		observed_at,
+	});
+	if let Err(error) = store.put_attestation(bridge_transfer_id, attestation).await {
+		tracing::warn!("Relayer[{chain}]: failed to store attestation of state {state:?}: {error}");
+	}
+}
+
+/// Refunds an expired transfer on the initiator contract, or aborts an expired lock on the
+/// counterparty contract, on a worker of `queue`. Failures are logged and reported by resolving
+/// to `false`, the contract event confirms the refund.
+fn refund_expired<B: BlockchainService + 'static>(
+	chain: &'static str,
+	blockchain: &B,
+	queue: &WorkQueue<BridgeTransferId<B::Hash>>,
+	event: RefundMonitorEvent<B::Hash>,
+	timeout: Duration,
+) -> BoxFuture<'static, (&'static str, bool)> {
+	let RefundMonitorEvent::Refundable(side, bridge_transfer_id) = event;
+	tracing::info!("Relayer[{chain}]: refunding expired transfer {:?}", bridge_transfer_id);
+
+	let refund = refund(chain, blockchain, side, bridge_transfer_id.clone(), timeout);
+	let refund = async move {
+		match refund.await {
+			Ok(()) => (chain, true),
+			Err(error) => {
+				tracing::warn!("Relayer[{chain}]: failed to refund expired transfer: {error}");
+				(chain, false)
+			}
+		}
+	};
+	queue.run(bridge_transfer_id, refund).boxed()
+}
+
+/// Refunds a transfer on the initiator contract on request of the operators, on a worker of
+/// `queue`, whether or not its time lock is known to have expired. The result is sent to `reply`
+/// once the call returns, without blocking the relayer in the meantime.
+fn force_refund<B: BlockchainService + 'static>(
+	chain: &'static str,
+	blockchain: &B,
+	queue: &WorkQueue<BridgeTransferId<B::Hash>>,
+	bridge_transfer_id: BridgeTransferId<B::Hash>,
+	timeout: Duration,
+	reply: oneshot::Sender<RelayerCommandResult<()>>,
+) -> BoxFuture<'static, (&'static str, bool)> {
+	tracing::info!("Relayer[{chain}]: refund of transfer {:?} forced", bridge_transfer_id);
+
+	let refund =
+		refund(chain, blockchain, RefundSide::Initiator, bridge_transfer_id.clone(), timeout);
+	let refund = async move {
+		let result = refund.await;
+		let refunded = result.is_ok();
+		let _ = reply.send(result.map_err(RelayerCommandError::Refund));
+		(chain, refunded)
+	};
+	queue.run(bridge_transfer_id, refund).boxed()
+}
+
+/// Refunds a transfer on the initiator contract, or aborts a lock on the counterparty contract.
+fn refund<B: BlockchainService + 'static>(
+	chain: &'static str,
+	blockchain: &B,
+	side: RefundSide,
+	bridge_transfer_id: BridgeTransferId<B::Hash>,
+	timeout: Duration,
+) -> impl Future<Output = Result<(), String>> + Send + 'static {
+	let span = bridge_transfer_id.span();
+	let call = match side {
+		RefundSide::Initiator => {
+			let mut contract = blockchain.initiator_contract().clone();
+			async move {
+				contract
+					.refund_bridge_transfer(bridge_transfer_id)
+					.await
+					.map_err(|e| e.to_string())
+			}
+			.boxed()
+		}
+		RefundSide::Counterparty => {
+			let mut contract = blockchain.counterparty_contract().clone();
+			async move {
+				contract
+					.abort_bridge_transfer(bridge_transfer_id)
+					.await
+					.map_err(|e| e.to_string())
+			}
+			.boxed()
+		}
+	};
+	async move {
+		tokio::time::timeout(timeout, call.instrument(span))
+			.await
+			.unwrap_or_else(|_| Err(format!("refund on {chain} timed out")))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_rotate_signer() {
+		let rotated = Arc::new(std::sync::Mutex::new(Vec::new()));
+		let mut rotation: SignerRotation = {
+			let rotated = rotated.clone();
+			Box::new(move |signer| {
+				rotated.lock().unwrap().push(signer);
+				Ok(())
+			})
+		};
+		let path = std::env::temp_dir().join(format!("bridge-signer-{}", std::process::id()));
+		std::fs::write(&path, "0x02\n").unwrap();
+
+		assert_eq!(rotate_signer(&mut rotation, "0x01"), Ok(()));
+		// the secrets of the relayer host are not resolved for the admin API
+		for signer in [format!("file:{}", path.display()), "env:HOME".to_string()] {
+			let error = rotate_signer(&mut rotation, &signer).unwrap_err();
+			assert!(matches!(error, RelayerCommandError::SignerRotation(_)));
+			assert!(!error.to_string().contains("0x02"));
+		}
+		assert!(rotate_signer(&mut rotation, "keystore:/etc/movement/bridge.json").is_err());
+		assert_eq!(*rotated.lock().unwrap(), ["0x01"]);
+		std::fs::remove_file(path).unwrap();
+
+		assert!(matches!(
+			rotate_signer(&mut unsupported_signer_rotation(), "0x01"),
+			Err(RelayerCommandError::SignerRotation(_))
+		));
+	}
+}
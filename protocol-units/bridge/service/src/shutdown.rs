@@ -0,0 +1,80 @@
+use std::{future::Future, sync::Arc};
+
+use tokio::{
+	signal::unix::{signal, SignalKind},
+	sync::watch,
+};
+
+/// Triggers the shutdown of the relayer, on SIGINT or SIGTERM or when requested, and notifies
+/// each of the tasks waiting on a [`signal`](ShutdownController::signal).
+#[derive(Debug, Clone)]
+pub struct ShutdownController {
+	sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for ShutdownController {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl ShutdownController {
+	pub fn new() -> Self {
+		Self { sender: Arc::new(watch::channel(false).0) }
+	}
+
+	/// Shuts down once the process receives SIGINT or SIGTERM.
+	pub fn listen_for_signals(&self) -> Result<(), std::io::Error> {
+		let mut sigterm = signal(SignalKind::terminate())?;
+		let mut sigint = signal(SignalKind::interrupt())?;
+		let controller = self.clone();
+		tokio::spawn(async move {
+			let name = tokio::select! {
+				_ = sigterm.recv() => "SIGTERM",
+				_ = sigint.recv() => "SIGINT",
+			};
+			tracing::info!("Shutdown: received {name}");
+			controller.shutdown();
+		});
+		Ok(())
+	}
+
+	pub fn shutdown(&self) {
+		self.sender.send_replace(true);
+	}
+
+	pub fn is_shutting_down(&self) -> bool {
+		*self.sender.borrow()
+	}
+
+	/// Resolves once the shutdown is triggered, immediately if it already was.
+	pub fn signal(&self) -> impl Future<Output = ()> + Send + 'static {
+		// The sender is kept alive, so that the receiver waits for the shutdown
+		let sender = self.sender.clone();
+		let mut receiver = sender.subscribe();
+		async move {
+			let _ = receiver.wait_for(|shutting_down| *shutting_down).await;
+			drop(sender);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	#[tokio::test]
+	async fn test_shutdown_signal() {
+		let controller = ShutdownController::new();
+		let signal = tokio::spawn(controller.signal());
+		tokio::time::sleep(Duration::from_millis(10)).await;
+		assert!(!signal.is_finished());
+
+		controller.shutdown();
+		signal.await.unwrap();
+		assert!(controller.is_shutting_down());
+		// Triggered before waiting
+		controller.signal().await;
+	}
+}
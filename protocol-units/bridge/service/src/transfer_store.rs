@@ -0,0 +1,449 @@
+use std::{marker::PhantomData, path::Path, sync::Arc};
+
+use bridge_shared::{
+	approval::Approval,
+	attestation::Attestation,
+	transfer_store::{
+		TransferRecord, TransferState, TransferStateStore, TransferStateStoreError,
+		TransferStateStoreResult,
+	},
+	types::{BridgeHashType, BridgeTransferId, TimeLock},
+};
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, DB};
+
+const TRANSFERS_CF: &str = "bridge_transfers";
+const CHECKPOINTS_CF: &str = "bridge_checkpoints";
+const SECRETS_CF: &str = "bridge_secrets";
+const ATTESTATIONS_CF: &str = "bridge_attestations";
+const APPROVALS_CF: &str = "bridge_approvals";
+const MONITORING_CHECKPOINT_KEY: &[u8] = b"monitoring";
+const HEALTH_CHECK_KEY: &[u8] = b"health";
+
+/// Transfer state store persisted in RocksDB, keyed by the bytes of the bridge transfer id.
+#[derive(Debug, Clone)]
+pub struct RocksdbTransferStateStore<H> {
+	db: Arc<DB>,
+	_phantom: PhantomData<H>,
+}
+
+impl<H> RocksdbTransferStateStore<H> {
+	pub fn try_new(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+		let mut options = Options::default();
+		options.create_if_missing(true);
+		options.create_missing_column_families(true);
+
+		let transfers_cf = ColumnFamilyDescriptor::new(TRANSFERS_CF, Options::default());
+		let checkpoints_cf = ColumnFamilyDescriptor::new(CHECKPOINTS_CF, Options::default());
+		let secrets_cf = ColumnFamilyDescriptor::new(SECRETS_CF, Options::default());
+		let attestations_cf = ColumnFamilyDescriptor::new(ATTESTATIONS_CF, Options::default());
+		let approvals_cf = ColumnFamilyDescriptor::new(APPROVALS_CF, Options::default());
+		let db = DB::open_cf_descriptors(
+			&options,
+			path,
+			vec![transfers_cf, checkpoints_cf, secrets_cf, attestations_cf, approvals_cf],
+		)?;
+
+		Ok(Self { db: Arc::new(db), _phantom: PhantomData })
+	}
+}
+
+fn storage_error(error: impl std::fmt::Display) -> TransferStateStoreError {
+	TransferStateStoreError::Storage(error.to_string())
+}
+
+fn encode_record(record: &TransferRecord) -> Vec<u8> {
+	let mut bytes = Vec::with_capacity(18);
+	bytes.push(match record.state {
+		TransferState::Initiated => 0,
+		TransferState::Locked => 1,
+		TransferState::Completed => 2,
+		TransferState::Refunded => 3,
+		TransferState::PendingApproval => 4,
+		TransferState::InsufficientLiquidity => 5,
+	});
+	bytes.extend_from_slice(&record.initiator_time_lock.0.to_be_bytes());
+	if let Some(time_lock) = &record.counterparty_time_lock {
+		bytes.push(1);
+		bytes.extend_from_slice(&time_lock.0.to_be_bytes());
+	} else {
+		bytes.push(0);
+	}
+	bytes
+}
+
+fn decode_record(bytes: &[u8]) -> TransferStateStoreResult<TransferRecord> {
+	let invalid = || storage_error(format!("invalid transfer record {bytes:?}"));
+	let read_u64 = |offset: usize| -> TransferStateStoreResult<u64> {
+		let time_lock = bytes.get(offset..offset + 8).ok_or_else(invalid)?;
+		Ok(u64::from_be_bytes(time_lock.try_into().map_err(|_| invalid())?))
+	};
+
+	let state = match bytes.first() {
+		Some(0) => TransferState::Initiated,
+		Some(1) => TransferState::Locked,
+		Some(2) => TransferState::Completed,
+		Some(3) => TransferState::Refunded,
+		Some(4) => TransferState::PendingApproval,
+		Some(5) => TransferState::InsufficientLiquidity,
+		_ => return Err(invalid()),
+	};
+	let initiator_time_lock = TimeLock(read_u64(1)?);
+	let counterparty_time_lock = match bytes.get(9) {
+		Some(0) => None,
+		Some(1) => Some(TimeLock(read_u64(10)?)),
+		_ => return Err(invalid()),
+	};
+
+	Ok(TransferRecord { state, initiator_time_lock, counterparty_time_lock })
+}
+
+/// Encoded values of a transfer in the column family `cf`, keyed by its id followed by a suffix:
+/// the big endian index of the attestations, the public key of the approvers.
+fn stored_values(
+	db: &DB,
+	cf: &str,
+	bridge_transfer_id: &[u8],
+) -> TransferStateStoreResult<Vec<Box<[u8]>>> {
+	let cf_handle = db.cf_handle(cf).ok_or_else(|| storage_error("CF handle not found"))?;
+	let mut values = Vec::new();
+	let mode = IteratorMode::From(bridge_transfer_id, Direction::Forward);
+	for entry in db.iterator_cf(&cf_handle, mode) {
+		let (key, value) = entry.map_err(storage_error)?;
+		if !key.starts_with(bridge_transfer_id) {
+			break;
+		}
+		values.push(value);
+	}
+	Ok(values)
+}
+
+#[async_trait::async_trait]
+impl<H> TransferStateStore for RocksdbTransferStateStore<H>
+where
+	H: BridgeHashType + AsRef<[u8]> + for<'a> TryFrom<&'a [u8]> + 'static,
+{
+	type Hash = H;
+
+	async fn get(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<Option<TransferRecord>> {
+		let db = self.db.clone();
+		let key = bridge_transfer_id.0.as_ref().to_vec();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(TRANSFERS_CF).ok_or_else(|| storage_error("CF handle not found"))?;
+			db.get_cf(&cf_handle, key)
+				.map_err(storage_error)?
+				.map(|bytes| decode_record(&bytes))
+				.transpose()
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn put(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H>,
+		record: TransferRecord,
+	) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(TRANSFERS_CF).ok_or_else(|| storage_error("CF handle not found"))?;
+			db.put_cf(&cf_handle, bridge_transfer_id.0.as_ref(), encode_record(&record))
+				.map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn remove(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		let key = bridge_transfer_id.0.as_ref().to_vec();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(TRANSFERS_CF).ok_or_else(|| storage_error("CF handle not found"))?;
+			db.delete_cf(&cf_handle, key).map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn in_flight(
+		&self,
+	) -> TransferStateStoreResult<Vec<(BridgeTransferId<H>, TransferRecord)>> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(TRANSFERS_CF).ok_or_else(|| storage_error("CF handle not found"))?;
+			let mut transfers = Vec::new();
+			for entry in db.iterator_cf(&cf_handle, rocksdb::IteratorMode::Start) {
+				let (key, value) = entry.map_err(storage_error)?;
+				let record = decode_record(&value)?;
+				if record.state.is_final() {
+					continue;
+				}
+				let hash = H::try_from(&key[..])
+					.map_err(|_| storage_error(format!("invalid bridge transfer id {key:?}")))?;
+				transfers.push((BridgeTransferId(hash), record));
+			}
+			Ok(transfers)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn checkpoint(&self) -> TransferStateStoreResult<Option<u64>> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle = db
+				.cf_handle(CHECKPOINTS_CF)
+				.ok_or_else(|| storage_error("CF handle not found"))?;
+			db.get_cf(&cf_handle, MONITORING_CHECKPOINT_KEY)
+				.map_err(storage_error)?
+				.map(|bytes| {
+					let height = bytes.try_into().map_err(|bytes| {
+						storage_error(format!("invalid monitoring checkpoint {bytes:?}"))
+					})?;
+					Ok(u64::from_be_bytes(height))
+				})
+				.transpose()
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn set_checkpoint(&self, height: u64) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle = db
+				.cf_handle(CHECKPOINTS_CF)
+				.ok_or_else(|| storage_error("CF handle not found"))?;
+			db.put_cf(&cf_handle, MONITORING_CHECKPOINT_KEY, height.to_be_bytes())
+				.map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn put_secret(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H>,
+		sealed_secret: Vec<u8>,
+	) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(SECRETS_CF).ok_or_else(|| storage_error("CF handle not found"))?;
+			db.put_cf(&cf_handle, bridge_transfer_id.0.as_ref(), sealed_secret)
+				.map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn secret(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<Option<Vec<u8>>> {
+		let db = self.db.clone();
+		let key = bridge_transfer_id.0.as_ref().to_vec();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(SECRETS_CF).ok_or_else(|| storage_error("CF handle not found"))?;
+			db.get_cf(&cf_handle, key).map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn remove_secret(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		let key = bridge_transfer_id.0.as_ref().to_vec();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(SECRETS_CF).ok_or_else(|| storage_error("CF handle not found"))?;
+			db.delete_cf(&cf_handle, key).map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn put_attestation(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H>,
+		attestation: Attestation,
+	) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let index =
+				stored_values(&db, ATTESTATIONS_CF, bridge_transfer_id.0.as_ref())?.len() as u32;
+			let mut key = bridge_transfer_id.0.as_ref().to_vec();
+			key.extend_from_slice(&index.to_be_bytes());
+			let cf_handle = db
+				.cf_handle(ATTESTATIONS_CF)
+				.ok_or_else(|| storage_error("CF handle not found"))?;
+			db.put_cf(&cf_handle, key, attestation.to_bytes()).map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn attestations(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<Vec<Attestation>> {
+		let db = self.db.clone();
+		let key = bridge_transfer_id.0.as_ref().to_vec();
+		tokio::task::spawn_blocking(move || {
+			stored_values(&db, ATTESTATIONS_CF, &key)?
+				.iter()
+				.map(|bytes| Attestation::from_bytes(bytes).map_err(storage_error))
+				.collect()
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn put_approval(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H>,
+		approval: Approval,
+	) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let mut key = bridge_transfer_id.0.as_ref().to_vec();
+			key.extend_from_slice(&approval.public_key);
+			let cf_handle =
+				db.cf_handle(APPROVALS_CF).ok_or_else(|| storage_error("CF handle not found"))?;
+			db.put_cf(&cf_handle, key, approval.to_bytes()).map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn approvals(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<Vec<Approval>> {
+		let db = self.db.clone();
+		let key = bridge_transfer_id.0.as_ref().to_vec();
+		tokio::task::spawn_blocking(move || {
+			stored_values(&db, APPROVALS_CF, &key)?
+				.iter()
+				.map(|bytes| Approval::from_bytes(bytes).map_err(storage_error))
+				.collect()
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn check_writable(&self) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle = db
+				.cf_handle(CHECKPOINTS_CF)
+				.ok_or_else(|| storage_error("CF handle not found"))?;
+			db.put_cf(&cf_handle, HEALTH_CHECK_KEY, []).map_err(storage_error)?;
+			db.delete_cf(&cf_handle, HEALTH_CHECK_KEY).map_err(storage_error)
+		})
+		.await
+		.map_err(storage_error)?
+	}
+
+	async fn flush(&self) -> TransferStateStoreResult<()> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			for name in [TRANSFERS_CF, CHECKPOINTS_CF, SECRETS_CF, ATTESTATIONS_CF, APPROVALS_CF] {
+				let cf_handle =
+					db.cf_handle(name).ok_or_else(|| storage_error("CF handle not found"))?;
+				db.flush_cf(&cf_handle).map_err(storage_error)?;
+			}
+			Ok(())
+		})
+		.await
+		.map_err(storage_error)?
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bridge_shared::{
+		approval::Approver,
+		attestation::{AttestationStatement, Attestor},
+	};
+
+	#[tokio::test]
+	async fn test_rocksdb_store_survives_reopen() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let bridge_transfer_id = BridgeTransferId([1u8; 32]);
+		let attestor = Attestor::new(&[7; 32]);
+		let approver = Approver::new(&[8; 32]);
+
+		{
+			let store = RocksdbTransferStateStore::<[u8; 32]>::try_new(dir.path())?;
+			store.initiate(bridge_transfer_id.clone(), TimeLock(100)).await?;
+			store.lock(bridge_transfer_id.clone(), TimeLock(50)).await?;
+			store.initiate(BridgeTransferId([2u8; 32]), TimeLock(100)).await?;
+			store.transition(BridgeTransferId([2u8; 32]), TransferState::Refunded).await?;
+			store.set_checkpoint(42).await?;
+			store.put_secret(bridge_transfer_id.clone(), b"sealed".to_vec()).await?;
+			for state in [TransferState::Initiated, TransferState::Locked] {
+				let statement = AttestationStatement {
+					chain: "B1".to_string(),
+					bridge_transfer_id: vec![1; 32],
+					state,
+					observed_at: 7,
+				};
+				store
+					.put_attestation(bridge_transfer_id.clone(), attestor.attest(statement))
+					.await?;
+			}
+			let approval = approver.approve("B1", &[1; 32]);
+			store.put_approval(bridge_transfer_id.clone(), approval.clone()).await?;
+			store.put_approval(bridge_transfer_id.clone(), approval).await?;
+			store.check_writable().await?;
+			store.flush().await?;
+		}
+
+		let store = RocksdbTransferStateStore::<[u8; 32]>::try_new(dir.path())?;
+		assert_eq!(store.checkpoint().await?, Some(42));
+		assert_eq!(store.secret(&bridge_transfer_id).await?, Some(b"sealed".to_vec()));
+		let attestations = store.attestations(&bridge_transfer_id).await?;
+		assert_eq!(
+			attestations
+				.iter()
+				.map(|attestation| attestation.statement.state)
+				.collect::<Vec<_>>(),
+			vec![TransferState::Initiated, TransferState::Locked]
+		);
+		assert!(attestations.iter().all(|attestation| attestation.verify().is_ok()));
+		assert!(store.attestations(&BridgeTransferId([2u8; 32])).await?.is_empty());
+		assert_eq!(
+			store.approvals(&bridge_transfer_id).await?,
+			vec![approver.approve("B1", &[1; 32])]
+		);
+		assert!(store.approvals(&BridgeTransferId([2u8; 32])).await?.is_empty());
+		assert_eq!(
+			store.in_flight().await?,
+			vec![(
+				bridge_transfer_id,
+				TransferRecord {
+					state: TransferState::Locked,
+					initiator_time_lock: TimeLock(100),
+					counterparty_time_lock: Some(TimeLock(50)),
+				}
+			)]
+		);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,88 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use futures::executor::block_on;
+
+use bridge_shared::{
+	bridge_contracts::{abi_selector, AtomicBridgeError, ContractRevert},
+	transfer_store::{
+		InMemoryTransferStateStore, TransferState, TransferStateMachine, TransferStateStore,
+	},
+	types::{BridgeTransferId, HashLockAlgorithm, HashLockPreImage, Keccak256, TimeLock},
+};
+
+const TRANSFERS: usize = 1_000;
+
+fn state_machine(c: &mut Criterion) {
+	c.bench_function("state_machine/complete", |b| {
+		b.iter(|| {
+			let mut machine = TransferStateMachine::new();
+			machine.transition(black_box(TransferState::Locked)).unwrap();
+			machine.transition(black_box(TransferState::Completed)).unwrap();
+			machine
+		});
+	});
+	c.bench_function("state_machine/rejected", |b| {
+		let machine = TransferStateMachine::from(TransferState::Completed);
+		b.iter(|| black_box(machine).transition(black_box(TransferState::Locked)));
+	});
+}
+
+/// Transfers recorded by the relayer from their initiation to their completion.
+fn transfer_store(c: &mut Criterion) {
+	let ids: Vec<_> = (0..TRANSFERS)
+		.map(|index| BridgeTransferId(Keccak256::hash_lock(&pre_image(index)).0))
+		.collect();
+	c.bench_function("transfer_store/lifecycle", |b| {
+		b.iter_batched(
+			InMemoryTransferStateStore::<[u8; 32]>::new,
+			|store| {
+				block_on(async {
+					for id in &ids {
+						store.initiate(id.clone(), TimeLock(100)).await.unwrap();
+						store.lock(id.clone(), TimeLock(50)).await.unwrap();
+						store.transition(id.clone(), TransferState::Completed).await.unwrap();
+					}
+				});
+				store
+			},
+			BatchSize::SmallInput,
+		);
+	});
+}
+
+fn hash_lock(c: &mut Criterion) {
+	let secret = pre_image(0);
+	let hash_lock = Keccak256::hash_lock(&secret);
+	c.bench_function("hash_lock/verify", |b| {
+		b.iter(|| Keccak256::verify(black_box(&secret), black_box(&hash_lock)));
+	});
+}
+
+fn contract_revert(c: &mut Criterion) {
+	let custom = abi_selector(&format!("{}()", AtomicBridgeError::TimelockExpired)).to_vec();
+	// Error(string) with its offset, its length and its padded bytes
+	let message = b"Transfer expired";
+	let mut reason = abi_selector("Error(string)").to_vec();
+	reason.extend(abi_word(32));
+	reason.extend(abi_word(message.len() as u8));
+	reason.extend(message);
+	reason.resize(reason.len() + 32 - message.len(), 0);
+	c.bench_function("contract_revert/custom", |b| {
+		b.iter(|| ContractRevert::decode(black_box(&custom)));
+	});
+	c.bench_function("contract_revert/reason", |b| {
+		b.iter(|| ContractRevert::decode(black_box(&reason)));
+	});
+}
+
+fn abi_word(value: u8) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	word[31] = value;
+	word
+}
+
+fn pre_image(index: usize) -> HashLockPreImage {
+	HashLockPreImage(format!("secret_{index}").into_bytes())
+}
+
+criterion_group!(benches, state_machine, transfer_store, hash_lock, contract_revert);
+criterion_main!(benches);
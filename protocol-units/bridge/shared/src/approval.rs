@@ -0,0 +1,122 @@
+use std::fmt;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use crate::attestation::write_prefixed;
+
+/// Prefix of the signed messages, so that the signature of an approval is never valid for
+/// another kind of message signed with the same key.
+const DOMAIN: &[u8] = b"MOVEMENT_BRIDGE_APPROVAL_V1";
+const PUBLIC_KEY_LENGTH: usize = 32;
+const SIGNATURE_LENGTH: usize = 64;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ApprovalError {
+	#[error("Invalid approver key, expected 32 hex encoded bytes")]
+	InvalidKey,
+	#[error("Invalid approval")]
+	InvalidFormat,
+	#[error("Invalid approval signature")]
+	InvalidSignature,
+}
+
+/// The message signed by an approver of a transfer: the domain, and the length prefixed
+/// blockchain the transfer was initiated on, `B1` or `B2`, and bridge transfer id.
+pub fn approval_message(chain: &str, bridge_transfer_id: &[u8]) -> Vec<u8> {
+	let mut message = DOMAIN.to_vec();
+	write_prefixed(&mut message, chain.as_bytes());
+	write_prefixed(&mut message, bridge_transfer_id);
+	message
+}
+
+/// Parses the hex encoded ed25519 public key of an approver.
+pub fn parse_public_key(key: &str) -> Result<[u8; PUBLIC_KEY_LENGTH], ApprovalError> {
+	let bytes =
+		hex::decode(key.trim().trim_start_matches("0x")).map_err(|_| ApprovalError::InvalidKey)?;
+	let public_key = bytes.try_into().map_err(|_| ApprovalError::InvalidKey)?;
+	VerifyingKey::from_bytes(&public_key).map_err(|_| ApprovalError::InvalidKey)?;
+	Ok(public_key)
+}
+
+/// Approval of a transfer pending approval, signed by an operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Approval {
+	/// Ed25519 public key of the approver, which the relayer compares with its approvers.
+	pub public_key: [u8; PUBLIC_KEY_LENGTH],
+	pub signature: [u8; SIGNATURE_LENGTH],
+}
+
+impl Approval {
+	pub fn from_parts(public_key: &[u8], signature: &[u8]) -> Result<Self, ApprovalError> {
+		Ok(Self {
+			public_key: public_key.try_into().map_err(|_| ApprovalError::InvalidFormat)?,
+			signature: signature.try_into().map_err(|_| ApprovalError::InvalidFormat)?,
+		})
+	}
+
+	/// Checks the signature of the approval of the transfer `bridge_transfer_id` initiated on
+	/// `chain` against the public key of the approval.
+	pub fn verify(&self, chain: &str, bridge_transfer_id: &[u8]) -> Result<(), ApprovalError> {
+		let public_key = VerifyingKey::from_bytes(&self.public_key)
+			.map_err(|_| ApprovalError::InvalidSignature)?;
+		public_key
+			.verify_strict(
+				&approval_message(chain, bridge_transfer_id),
+				&Signature::from_bytes(&self.signature),
+			)
+			.map_err(|_| ApprovalError::InvalidSignature)
+	}
+
+	/// Encodes the approval as the public key followed by the signature.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		[self.public_key.as_slice(), self.signature.as_slice()].concat()
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, ApprovalError> {
+		let (public_key, signature) = bytes.split_at(PUBLIC_KEY_LENGTH.min(bytes.len()));
+		Self::from_parts(public_key, signature)
+	}
+}
+
+/// Signs the approvals of an operator with its ed25519 key.
+#[derive(Clone)]
+pub struct Approver {
+	signing_key: SigningKey,
+}
+
+impl fmt::Debug for Approver {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Approver")
+			.field("public_key", &hex::encode(self.public_key()))
+			.finish()
+	}
+}
+
+impl Approver {
+	pub fn new(secret_key: &[u8; 32]) -> Self {
+		Self { signing_key: SigningKey::from_bytes(secret_key) }
+	}
+
+	/// Approver signing with a hex encoded 32 byte ed25519 private key.
+	pub fn try_from_hex_key(key: &str) -> Result<Self, ApprovalError> {
+		let mut bytes = hex::decode(key.trim().trim_start_matches("0x"))
+			.map_err(|_| ApprovalError::InvalidKey)?;
+		let approver = <&[u8; 32]>::try_from(bytes.as_slice())
+			.map(Self::new)
+			.map_err(|_| ApprovalError::InvalidKey);
+		bytes.zeroize();
+		approver
+	}
+
+	pub fn public_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+		self.signing_key.verifying_key().to_bytes()
+	}
+
+	/// Approves the transfer `bridge_transfer_id` initiated on `chain`.
+	pub fn approve(&self, chain: &str, bridge_transfer_id: &[u8]) -> Approval {
+		let signature = self.signing_key.sign(&approval_message(chain, bridge_transfer_id));
+		Approval { public_key: self.public_key(), signature: signature.to_bytes() }
+	}
+}
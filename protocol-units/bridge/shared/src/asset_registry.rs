@@ -0,0 +1,258 @@
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::types::{Amount, AmountError, TokenAddress};
+
+/// Canonical identifier of a bridged asset, shared by its tokens on every blockchain.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub struct AssetId(pub String);
+
+impl fmt::Display for AssetId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+/// A bridged asset and its token on each blockchain, by name of the blockchain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Asset {
+	pub id: AssetId,
+	/// Decimals of the tokens of the asset, unless set for the blockchain in `chain_decimals`.
+	pub decimals: u8,
+	/// Decimals of the token on a blockchain, by name of the blockchain, when they differ from
+	/// `decimals`.
+	#[serde(default)]
+	pub chain_decimals: BTreeMap<String, u8>,
+	/// Whether the asset is the one bridged by the contracts whose transfers don't name their
+	/// token. At most one asset is native.
+	#[serde(default)]
+	pub native: bool,
+	/// Address of the token contract on Ethereum, struct tag of the coin on Movement.
+	pub tokens: BTreeMap<String, String>,
+}
+
+impl Asset {
+	/// Decimals of the token of the asset on `chain`.
+	pub fn decimals_on(&self, chain: &str) -> u8 {
+		self.chain_decimals.get(chain).copied().unwrap_or(self.decimals)
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum AssetRegistryError {
+	#[error("Failed to read asset registry {0}: {1}")]
+	File(PathBuf, std::io::Error),
+	#[error("Invalid asset registry: {0}")]
+	Toml(#[from] toml::de::Error),
+	#[error("Asset {0} is registered twice")]
+	DuplicateAsset(AssetId),
+	#[error("Token {token} on {chain} is registered for both {registered} and {asset}")]
+	DuplicateToken { chain: String, token: String, registered: AssetId, asset: AssetId },
+	#[error("Assets {registered} and {asset} are both native")]
+	DuplicateNative { registered: AssetId, asset: AssetId },
+}
+
+/// Reasons the asset of a transfer is not found in the registry.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AssetError {
+	#[error("Token {token} on {chain} is not a registered asset")]
+	Unknown { chain: String, token: String },
+	#[error("Transfer on {0} does not name the token of its asset, and no asset is native")]
+	MissingToken(String),
+	#[error("Asset {asset} is not bridged to {chain}")]
+	NotBridged { asset: AssetId, chain: String },
+	#[error(transparent)]
+	Amount(#[from] AmountError),
+}
+
+#[derive(Deserialize)]
+struct AssetRegistryFile {
+	#[serde(default, rename = "asset")]
+	assets: Vec<Asset>,
+}
+
+/// Maps the tokens of the blockchains to the canonical assets they bridge, so that the relayer
+/// can handle more than one asset and reject the transfers in unknown ones.
+///
+/// The registry is loaded from a TOML file listing the assets:
+///
+/// ```toml
+/// [[asset]]
+/// id = "MOVE"
+/// decimals = 8
+/// native = true
+/// tokens = { ethereum = "0x3073f7aaa4db83f95e9fff17424f71d4751a3073", movement = "0x1::aptos_coin::AptosCoin" }
+///
+/// [[asset]]
+/// id = "ETH"
+/// decimals = 8
+/// chain_decimals = { ethereum = 18 }
+/// tokens = { ethereum = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", movement = "0xe::weth::WETH" }
+/// ```
+///
+/// The addresses in the tokens are not case sensitive.
+#[derive(Debug, Clone, Default)]
+pub struct AssetRegistry {
+	assets: BTreeMap<AssetId, Asset>,
+	tokens: HashMap<(String, String), AssetId>,
+	native: Option<AssetId>,
+}
+
+impl AssetRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn from_toml(toml: &str) -> Result<Self, AssetRegistryError> {
+		let file: AssetRegistryFile = toml::from_str(toml)?;
+		let mut registry = Self::new();
+		for asset in file.assets {
+			registry.register(asset)?;
+		}
+		Ok(registry)
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, AssetRegistryError> {
+		let path = path.as_ref();
+		let toml = std::fs::read_to_string(path)
+			.map_err(|error| AssetRegistryError::File(path.to_path_buf(), error))?;
+		Self::from_toml(&toml)
+	}
+
+	/// Adds an asset, unless its id or one of its tokens is already registered, or another
+	/// asset is already native.
+	pub fn register(&mut self, asset: Asset) -> Result<(), AssetRegistryError> {
+		if self.assets.contains_key(&asset.id) {
+			return Err(AssetRegistryError::DuplicateAsset(asset.id));
+		}
+		if let (true, Some(registered)) = (asset.native, &self.native) {
+			return Err(AssetRegistryError::DuplicateNative {
+				registered: registered.clone(),
+				asset: asset.id,
+			});
+		}
+		for (chain, token) in &asset.tokens {
+			let key = (chain.clone(), normalize_token(token));
+			if let Some(registered) = self.tokens.get(&key) {
+				return Err(AssetRegistryError::DuplicateToken {
+					chain: chain.clone(),
+					token: token.clone(),
+					registered: registered.clone(),
+					asset: asset.id.clone(),
+				});
+			}
+		}
+		for (chain, token) in &asset.tokens {
+			self.tokens.insert((chain.clone(), normalize_token(token)), asset.id.clone());
+		}
+		if asset.native {
+			self.native = Some(asset.id.clone());
+		}
+		self.assets.insert(asset.id.clone(), asset);
+		Ok(())
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.assets.is_empty()
+	}
+
+	pub fn assets(&self) -> impl Iterator<Item = &Asset> {
+		self.assets.values()
+	}
+
+	pub fn asset(&self, id: &AssetId) -> Option<&Asset> {
+		self.assets.get(id)
+	}
+
+	/// Asset bridged by the contracts whose transfers don't name their token.
+	pub fn native(&self) -> Option<&Asset> {
+		self.native.as_ref().and_then(|id| self.assets.get(id))
+	}
+
+	/// Asset bridged by `token` on `chain`.
+	pub fn resolve(&self, chain: &str, token: &str) -> Result<&Asset, AssetError> {
+		self.tokens
+			.get(&(chain.to_string(), normalize_token(token)))
+			.and_then(|id| self.assets.get(id))
+			.ok_or_else(|| AssetError::Unknown {
+				chain: chain.to_string(),
+				token: token.to_string(),
+			})
+	}
+
+	/// Token of the asset bridged by `token` on `chain`, on the blockchain `to`.
+	pub fn counterpart_token(
+		&self,
+		chain: &str,
+		token: &str,
+		to: &str,
+	) -> Result<&str, AssetError> {
+		let asset = self.resolve(chain, token)?;
+		asset.tokens.get(to).map(String::as_str).ok_or_else(|| AssetError::NotBridged {
+			asset: asset.id.clone(),
+			chain: to.to_string(),
+		})
+	}
+}
+
+/// Assets registered for the tokens of one blockchain.
+#[derive(Debug, Clone)]
+pub struct ChainAssets {
+	registry: Arc<AssetRegistry>,
+	chain: String,
+}
+
+impl ChainAssets {
+	pub fn new(registry: Arc<AssetRegistry>, chain: impl Into<String>) -> Self {
+		Self { registry, chain: chain.into() }
+	}
+
+	pub fn chain(&self) -> &str {
+		&self.chain
+	}
+
+	/// Asset of a transfer in `token`, the native asset when the transfer doesn't name its token.
+	pub fn resolve(&self, token: Option<&TokenAddress>) -> Result<&Asset, AssetError> {
+		match token {
+			Some(token) => self.registry.resolve(&self.chain, &token.0),
+			None => self
+				.registry
+				.native()
+				.ok_or_else(|| AssetError::MissingToken(self.chain.clone())),
+		}
+	}
+
+	/// Token on the blockchain `to` of the asset of a transfer of `amount` in `token`, and the
+	/// amount scaled to the decimals of that token.
+	pub fn counterpart(
+		&self,
+		token: Option<&TokenAddress>,
+		amount: Amount,
+		to: &str,
+	) -> Result<(TokenAddress, Amount), AssetError> {
+		let asset = self.resolve(token)?;
+		let counterpart_token = asset.tokens.get(to).ok_or_else(|| AssetError::NotBridged {
+			asset: asset.id.clone(),
+			chain: to.to_string(),
+		})?;
+		let amount = amount.scale(asset.decimals_on(&self.chain), asset.decimals_on(to))?;
+		Ok((TokenAddress(counterpart_token.clone()), amount))
+	}
+}
+
+/// Lowercases the hex addresses, including the address of a struct tag, whose module and type
+/// names are case sensitive.
+fn normalize_token(token: &str) -> String {
+	let token = token.trim();
+	match token.split_once("::") {
+		Some((address, path)) => format!("{}::{path}", address.to_lowercase()),
+		None => token.to_lowercase(),
+	}
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use crate::transfer_store::TransferState;
+
+/// Prefix of the signed messages, so that the signature of an attestation is never valid for
+/// another kind of message signed with the same key.
+const DOMAIN: &[u8] = b"MOVEMENT_BRIDGE_ATTESTATION_V1";
+const PUBLIC_KEY_LENGTH: usize = 32;
+const SIGNATURE_LENGTH: usize = 64;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AttestationError {
+	#[error("Invalid attestation key, expected 32 hex encoded bytes")]
+	InvalidKey,
+	#[error("Invalid attestation")]
+	InvalidFormat,
+	#[error("Invalid attestation signature")]
+	InvalidSignature,
+}
+
+/// Transition of a transfer, as observed by the relayer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttestationStatement {
+	/// Blockchain the transition was observed on, `B1` or `B2`.
+	pub chain: String,
+	/// Id of the transfer on the blockchain it was initiated on.
+	pub bridge_transfer_id: Vec<u8>,
+	pub state: TransferState,
+	/// Block height, ledger version or timestamp of the blockchain the transition was observed
+	/// at, in the unit of its time locks.
+	pub observed_at: u64,
+}
+
+impl AttestationStatement {
+	/// The signed message: the domain, the length prefixed chain and bridge transfer id, the
+	/// state and the big endian observation point.
+	pub fn message(&self) -> Vec<u8> {
+		let mut message = DOMAIN.to_vec();
+		write_prefixed(&mut message, self.chain.as_bytes());
+		write_prefixed(&mut message, &self.bridge_transfer_id);
+		message.push(state_code(self.state));
+		message.extend_from_slice(&self.observed_at.to_be_bytes());
+		message
+	}
+
+	/// Decodes a message, returning the statement and the bytes which follow it.
+	fn decode(bytes: &[u8]) -> Result<(Self, &[u8]), AttestationError> {
+		let bytes = bytes.strip_prefix(DOMAIN).ok_or(AttestationError::InvalidFormat)?;
+		let (chain, bytes) = read_prefixed(bytes)?;
+		let chain =
+			String::from_utf8(chain.to_vec()).map_err(|_| AttestationError::InvalidFormat)?;
+		let (bridge_transfer_id, bytes) = read_prefixed(bytes)?;
+		let (&state, bytes) = bytes.split_first().ok_or(AttestationError::InvalidFormat)?;
+		let (observed_at, bytes) = split_array::<8>(bytes)?;
+		let statement = Self {
+			chain,
+			bridge_transfer_id: bridge_transfer_id.to_vec(),
+			state: state_from_code(state)?,
+			observed_at: u64::from_be_bytes(observed_at),
+		};
+		Ok((statement, bytes))
+	}
+}
+
+pub(crate) fn write_prefixed(message: &mut Vec<u8>, bytes: &[u8]) {
+	message.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+	message.extend_from_slice(bytes);
+}
+
+fn read_prefixed(bytes: &[u8]) -> Result<(&[u8], &[u8]), AttestationError> {
+	let (length, bytes) = split_array::<2>(bytes)?;
+	let length = u16::from_be_bytes(length) as usize;
+	if bytes.len() < length {
+		return Err(AttestationError::InvalidFormat);
+	}
+	Ok(bytes.split_at(length))
+}
+
+fn split_array<const N: usize>(bytes: &[u8]) -> Result<([u8; N], &[u8]), AttestationError> {
+	if bytes.len() < N {
+		return Err(AttestationError::InvalidFormat);
+	}
+	let (array, bytes) = bytes.split_at(N);
+	Ok((array.try_into().map_err(|_| AttestationError::InvalidFormat)?, bytes))
+}
+
+fn state_code(state: TransferState) -> u8 {
+	match state {
+		TransferState::Initiated => 0,
+		TransferState::Locked => 1,
+		TransferState::Completed => 2,
+		TransferState::Refunded => 3,
+		TransferState::PendingApproval => 4,
+		TransferState::InsufficientLiquidity => 5,
+	}
+}
+
+fn state_from_code(code: u8) -> Result<TransferState, AttestationError> {
+	TransferState::ALL
+		.into_iter()
+		.find(|state| state_code(*state) == code)
+		.ok_or(AttestationError::InvalidFormat)
+}
+
+/// Statement signed by the relayer, which anyone knowing the public key of the relayer can
+/// verify.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attestation {
+	pub statement: AttestationStatement,
+	/// Ed25519 public key of the relayer which signed the statement.
+	pub public_key: [u8; PUBLIC_KEY_LENGTH],
+	pub signature: [u8; SIGNATURE_LENGTH],
+}
+
+impl Attestation {
+	/// Checks the signature of the statement against the public key of the attestation, which
+	/// verifiers must also compare with the published key of the relayer.
+	pub fn verify(&self) -> Result<(), AttestationError> {
+		let public_key = VerifyingKey::from_bytes(&self.public_key)
+			.map_err(|_| AttestationError::InvalidSignature)?;
+		public_key
+			.verify_strict(&self.statement.message(), &Signature::from_bytes(&self.signature))
+			.map_err(|_| AttestationError::InvalidSignature)
+	}
+
+	/// Encodes the attestation as its signed message followed by the public key and the
+	/// signature.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = self.statement.message();
+		bytes.extend_from_slice(&self.public_key);
+		bytes.extend_from_slice(&self.signature);
+		bytes
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, AttestationError> {
+		let (statement, bytes) = AttestationStatement::decode(bytes)?;
+		let (public_key, bytes) = split_array(bytes)?;
+		let (signature, bytes) = split_array(bytes)?;
+		if !bytes.is_empty() {
+			return Err(AttestationError::InvalidFormat);
+		}
+		Ok(Self { statement, public_key, signature })
+	}
+}
+
+/// Signs the attestations of the relayer with its ed25519 key.
+#[derive(Clone)]
+pub struct Attestor {
+	signing_key: SigningKey,
+}
+
+impl fmt::Debug for Attestor {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Attestor")
+			.field("public_key", &hex::encode(self.public_key()))
+			.finish()
+	}
+}
+
+impl Attestor {
+	pub fn new(secret_key: &[u8; 32]) -> Self {
+		Self { signing_key: SigningKey::from_bytes(secret_key) }
+	}
+
+	/// Attestor signing with a hex encoded 32 byte ed25519 private key, which can be loaded with
+	/// a [`SecretLoader`](crate::secret::SecretLoader).
+	pub fn try_from_hex_key(key: &str) -> Result<Self, AttestationError> {
+		let mut bytes = hex::decode(key.trim().trim_start_matches("0x"))
+			.map_err(|_| AttestationError::InvalidKey)?;
+		let attestor = <&[u8; 32]>::try_from(bytes.as_slice())
+			.map(Self::new)
+			.map_err(|_| AttestationError::InvalidKey);
+		bytes.zeroize();
+		attestor
+	}
+
+	pub fn public_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+		self.signing_key.verifying_key().to_bytes()
+	}
+
+	pub fn attest(&self, statement: AttestationStatement) -> Attestation {
+		let signature = self.signing_key.sign(&statement.message());
+		Attestation { statement, public_key: self.public_key(), signature: signature.to_bytes() }
+	}
+}
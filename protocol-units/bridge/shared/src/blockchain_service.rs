@@ -1,9 +1,12 @@
 use std::{
+	fmt::Display,
+	future::Future,
 	pin::Pin,
 	task::{Context, Poll},
 };
 
 use futures::{Stream, StreamExt};
+use thiserror::Error;
 
 use crate::{
 	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
@@ -14,6 +17,32 @@ use crate::{
 	types::{BridgeAddressType, BridgeHashType},
 };
 
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ChainIdError {
+	#[error("{chain} node is on chain {actual}, expected chain {expected}")]
+	Mismatch { chain: &'static str, expected: u64, actual: u64 },
+	#[error("Failed to query the chain id of the {chain} node: {error}")]
+	Query { chain: &'static str, error: String },
+}
+
+/// Checks that the node of `chain` is on the configured chain, with the chain id it returns from
+/// `query`, so that no bridge transaction is sent to the wrong network. Run when the client of a
+/// blockchain is built, before any transaction is sent.
+pub async fn verify_chain_id<E: Display>(
+	chain: &'static str,
+	expected: u64,
+	query: impl Future<Output = Result<u64, E>>,
+) -> Result<(), ChainIdError> {
+	let actual = query
+		.await
+		.map_err(|error| ChainIdError::Query { chain, error: error.to_string() })?;
+	if actual != expected {
+		return Err(ChainIdError::Mismatch { chain, expected, actual });
+	}
+	tracing::info!("{chain}: connected to chain {actual}");
+	Ok(())
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ContractEvent<A, H> {
 	InitiatorEvent(BridgeContractInitiatorEvent<A, H>),
@@ -43,16 +72,13 @@ pub trait BlockchainService:
 	fn counterparty_monitoring(&mut self) -> &mut Self::CounterpartyMonitoring;
 
 	fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-		match (
-			self.initiator_monitoring().poll_next_unpin(cx),
-			self.counterparty_monitoring().poll_next_unpin(cx),
-		) {
-			(Poll::Ready(Some(event)), _) => {
-				Poll::Ready(Some(ContractEvent::InitiatorEvent(event)))
-			}
-			(_, Poll::Ready(Some(event))) => {
-				Poll::Ready(Some(ContractEvent::CounterpartyEvent(event)))
-			}
+		// the counterparty monitoring is only polled when the initiator one has no event, an event
+		// it yields would be lost otherwise
+		if let Poll::Ready(Some(event)) = self.initiator_monitoring().poll_next_unpin(cx) {
+			return Poll::Ready(Some(ContractEvent::InitiatorEvent(event)));
+		}
+		match self.counterparty_monitoring().poll_next_unpin(cx) {
+			Poll::Ready(Some(event)) => Poll::Ready(Some(ContractEvent::CounterpartyEvent(event))),
 			_ => Poll::Pending,
 		}
 	}
@@ -1,16 +1,136 @@
 use thiserror::Error;
 
 use crate::types::{
-	Amount, BridgeAddressType, BridgeHashType, BridgeTransferDetails, BridgeTransferId, HashLock,
-	HashLockPreImage, InitiatorAddress, RecipientAddress, TimeLock,
+	Amount, BridgeAddressType, BridgeHashType, BridgeTransferDetails, BridgeTransferId,
+	ConversionError, HashLock, HashLockPreImage, InitiatorAddress, LockDetails, RecipientAddress,
+	TimeLock, TokenAddress,
 };
 
+/// Custom errors of the AtomicBridge Solidity contracts.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtomicBridgeError {
+	#[error("ZeroAmount")]
+	ZeroAmount,
+	#[error("WETHTransferFailed")]
+	WETHTransferFailed,
+	#[error("BridgeTransferInvalid")]
+	BridgeTransferInvalid,
+	#[error("InvalidSecret")]
+	InvalidSecret,
+	#[error("BridgeTransferHasBeenCompleted")]
+	BridgeTransferHasBeenCompleted,
+	#[error("BridgeTransferStateNotInitialized")]
+	BridgeTransferStateNotInitialized,
+	#[error("TimeLockNotExpired")]
+	TimeLockNotExpired,
+	#[error("TimelockExpired")]
+	TimelockExpired,
+	#[error("ZeroAddress")]
+	ZeroAddress,
+	#[error("Unauthorized")]
+	Unauthorized,
+}
+
+impl AtomicBridgeError {
+	pub const ALL: [AtomicBridgeError; 10] = [
+		AtomicBridgeError::ZeroAmount,
+		AtomicBridgeError::WETHTransferFailed,
+		AtomicBridgeError::BridgeTransferInvalid,
+		AtomicBridgeError::InvalidSecret,
+		AtomicBridgeError::BridgeTransferHasBeenCompleted,
+		AtomicBridgeError::BridgeTransferStateNotInitialized,
+		AtomicBridgeError::TimeLockNotExpired,
+		AtomicBridgeError::TimelockExpired,
+		AtomicBridgeError::ZeroAddress,
+		AtomicBridgeError::Unauthorized,
+	];
+
+	/// The 4 bytes identifying the error in the revert data.
+	pub fn selector(&self) -> [u8; 4] {
+		abi_selector(&format!("{self}()"))
+	}
+}
+
+/// Returns the ABI selector of a function or error signature, such as `Error(string)`.
+pub fn abi_selector(signature: &str) -> [u8; 4] {
+	use tiny_keccak::{Hasher, Keccak};
+
+	let mut hasher = Keccak::v256();
+	hasher.update(signature.as_bytes());
+	let mut hash = [0u8; 32];
+	hasher.finalize(&mut hash);
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Reason of a reverted contract call, decoded from the revert data.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ContractRevert {
+	#[error("{0}")]
+	Custom(AtomicBridgeError),
+	#[error("{0}")]
+	Reason(String),
+	#[error("unknown revert data 0x{}", hex::encode(.0))]
+	Unknown(Vec<u8>),
+}
+
+impl ContractRevert {
+	/// Decodes a custom error of the AtomicBridge contracts or an `Error(string)` reason.
+	pub fn decode(data: &[u8]) -> Self {
+		if data.len() < 4 {
+			return ContractRevert::Unknown(data.to_vec());
+		}
+		let (selector, payload) = data.split_at(4);
+		if let Some(error) =
+			AtomicBridgeError::ALL.iter().find(|error| error.selector() == selector)
+		{
+			return ContractRevert::Custom(*error);
+		}
+		if selector == abi_selector("Error(string)") {
+			if let Some(reason) = decode_abi_string(payload) {
+				return ContractRevert::Reason(reason);
+			}
+		}
+		ContractRevert::Unknown(data.to_vec())
+	}
+}
+
+/// Decodes a single ABI encoded `string`: its offset, its length and its padded bytes.
+fn decode_abi_string(payload: &[u8]) -> Option<String> {
+	let read_usize = |offset: usize| -> Option<usize> {
+		let word = payload.get(offset..offset.checked_add(32)?)?;
+		if word[..24].iter().any(|byte| *byte != 0) {
+			return None;
+		}
+		usize::try_from(u64::from_be_bytes(word[24..].try_into().ok()?)).ok()
+	};
+	let offset = read_usize(0)?;
+	let length = read_usize(offset)?;
+	let start = offset.checked_add(32)?;
+	let bytes = payload.get(start..start.checked_add(length)?)?;
+	String::from_utf8(bytes.to_vec()).ok()
+}
+
 #[derive(Error, Debug, Clone)]
 pub enum BridgeContractInitiatorError {
 	#[error("Failed to initiate bridge transfer")]
 	InitiateTransferError,
 	#[error("Failed to complete bridge transfer")]
 	CompleteTransferError,
+	#[error("Contract call reverted: {0}")]
+	Revert(ContractRevert),
+	#[error("Contract call timed out")]
+	Timeout,
+	#[error("Insufficient funds: {0}")]
+	InsufficientFunds(String),
+	#[error("Bridge transfer already completed")]
+	AlreadyCompleted,
+	#[error("Bridge transfer not found")]
+	NotFound,
+	#[error("RPC transport error: {0}")]
+	RpcTransport(String),
+	/// A value of the call or of its response has an invalid encoding.
+	#[error(transparent)]
+	Conversion(#[from] ConversionError),
 	#[error("Generic error: {0}")]
 	GenericError(String),
 }
@@ -19,6 +139,17 @@ impl BridgeContractInitiatorError {
 	pub fn generic<E: std::error::Error>(e: E) -> Self {
 		Self::GenericError(e.to_string())
 	}
+
+	/// Classifies the revert data of a call to the initiator contract.
+	pub fn from_revert_data(data: &[u8]) -> Self {
+		match ContractRevert::decode(data) {
+			ContractRevert::Custom(AtomicBridgeError::BridgeTransferHasBeenCompleted) => {
+				Self::AlreadyCompleted
+			}
+			ContractRevert::Custom(AtomicBridgeError::BridgeTransferInvalid) => Self::NotFound,
+			revert => Self::Revert(revert),
+		}
+	}
 }
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
@@ -29,6 +160,21 @@ pub enum BridgeContractCounterpartyError {
 	CompleteTransferError,
 	#[error("Failed to abort bridge transfer")]
 	AbortTransferError,
+	#[error("Contract call reverted: {0}")]
+	Revert(ContractRevert),
+	#[error("Contract call timed out")]
+	Timeout,
+	#[error("Insufficient funds: {0}")]
+	InsufficientFunds(String),
+	#[error("Bridge transfer already completed")]
+	AlreadyCompleted,
+	#[error("Bridge transfer not found")]
+	NotFound,
+	#[error("RPC transport error: {0}")]
+	RpcTransport(String),
+	/// A value of the call or of its response has an invalid encoding.
+	#[error(transparent)]
+	Conversion(#[from] ConversionError),
 	#[error("Generic error: {0}")]
 	GenericError(String),
 }
@@ -37,6 +183,17 @@ impl BridgeContractCounterpartyError {
 	pub fn generic<E: std::error::Error>(e: E) -> Self {
 		Self::GenericError(e.to_string())
 	}
+
+	/// Classifies the revert data of a call to the counterparty contract.
+	pub fn from_revert_data(data: &[u8]) -> Self {
+		match ContractRevert::decode(data) {
+			ContractRevert::Custom(AtomicBridgeError::BridgeTransferHasBeenCompleted) => {
+				Self::AlreadyCompleted
+			}
+			ContractRevert::Custom(AtomicBridgeError::BridgeTransferInvalid) => Self::NotFound,
+			revert => Self::Revert(revert),
+		}
+	}
 }
 
 pub type BridgeContractInitiatorResult<T> = Result<T, BridgeContractInitiatorError>;
@@ -70,7 +227,11 @@ pub trait BridgeContractInitiator: Clone + Unpin + Send + Sync {
 	async fn get_bridge_transfer_details(
 		&mut self,
 		bridge_transfer_id: BridgeTransferId<Self::Hash>,
-	) -> BridgeContractInitiatorResult<Option<BridgeTransferDetails<Self::Hash, Self::Address>>>;
+	) -> BridgeContractInitiatorResult<Option<BridgeTransferDetails<Self::Address, Self::Hash>>>;
+
+	/// Gas spent by the mined transactions sent through the contract, shared by the clones of the
+	/// client.
+	fn gas_spent(&self) -> u64;
 }
 
 #[async_trait::async_trait]
@@ -78,6 +239,8 @@ pub trait BridgeContractCounterparty: Clone + Unpin + Send + Sync {
 	type Address: BridgeAddressType;
 	type Hash: BridgeHashType;
 
+	/// Locks `amount` of `token` for the recipient, of the single asset bridged by the contract
+	/// when `token` is unset.
 	async fn lock_bridge_transfer_assets(
 		&mut self,
 		bridge_transfer_id: BridgeTransferId<Self::Hash>,
@@ -85,6 +248,7 @@ pub trait BridgeContractCounterparty: Clone + Unpin + Send + Sync {
 		time_lock: TimeLock,
 		recipient: RecipientAddress,
 		amount: Amount,
+		token: Option<TokenAddress>,
 	) -> BridgeContractCounterpartyResult<()>;
 
 	async fn complete_bridge_transfer(
@@ -98,8 +262,16 @@ pub trait BridgeContractCounterparty: Clone + Unpin + Send + Sync {
 		bridge_transfer_id: BridgeTransferId<Self::Hash>,
 	) -> BridgeContractCounterpartyResult<()>;
 
+	/// Balance the counterparty contract can lock for new transfers, checked before locking the
+	/// assets of a transfer. `None` only if the contract can't expose it, which skips the check.
+	async fn available_liquidity(&mut self) -> BridgeContractCounterpartyResult<Option<Amount>>;
+
 	async fn get_bridge_transfer_details(
 		&mut self,
 		bridge_transfer_id: BridgeTransferId<Self::Hash>,
-	) -> BridgeContractCounterpartyResult<Option<BridgeTransferDetails<Self::Hash, Self::Address>>>;
+	) -> BridgeContractCounterpartyResult<Option<LockDetails<Self::Hash>>>;
+
+	/// Gas spent by the mined transactions sent through the contract, shared by the clones of the
+	/// client.
+	fn gas_spent(&self) -> u64;
 }
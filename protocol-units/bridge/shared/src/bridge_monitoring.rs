@@ -2,26 +2,40 @@ use futures::Stream;
 
 use crate::types::{BridgeTransferDetails, BridgeTransferId, CompletedDetails, LockDetails};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BridgeContractInitiatorEvent<A, H> {
 	Initiated(BridgeTransferDetails<A, H>),
 	Completed(BridgeTransferId<H>),
 	Refunded(BridgeTransferId<H>),
+	/// The block which initiated the transfer was removed from the chain by a reorg.
+	Reverted(BridgeTransferId<H>),
 }
 
 impl<A, H> BridgeContractInitiatorEvent<A, H> {
 	pub fn bridge_transfer_id(&self) -> &BridgeTransferId<H> {
 		match self {
 			Self::Initiated(details) => &details.bridge_transfer_id,
-			Self::Completed(id) | Self::Refunded(id) => id,
+			Self::Completed(id) | Self::Refunded(id) | Self::Reverted(id) => id,
 		}
 	}
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BridgeContractCounterpartyEvent<H> {
 	Locked(LockDetails<H>),
 	Completed(CompletedDetails<H>),
+	/// The lock expired and was aborted, the recipient can no longer complete it.
+	Aborted(BridgeTransferId<H>),
+}
+
+impl<H> BridgeContractCounterpartyEvent<H> {
+	pub fn bridge_transfer_id(&self) -> &BridgeTransferId<H> {
+		match self {
+			Self::Locked(details) => &details.bridge_transfer_id,
+			Self::Completed(details) => &details.bridge_transfer_id,
+			Self::Aborted(id) => id,
+		}
+	}
 }
 
 pub trait BridgeContractInitiatorMonitoring:
@@ -1,16 +1,17 @@
 use futures::{Stream, StreamExt};
 use std::task::{Context, Poll};
-use std::{convert::From, pin::Pin};
+use std::{convert::From, pin::Pin, sync::Arc};
 use tracing::{trace, warn};
 
 use crate::{
+	asset_registry::{AssetRegistry, ChainAssets},
 	blockchain_service::{BlockchainService, ContractEvent},
 	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
 	bridge_service::{
-		active_swap::ActiveSwapEvent,
+		active_swap::{ActiveSwapEvent, StartSwapError},
 		events::{CEvent, CWarn, IEvent, IWarn},
 	},
-	types::BridgeTransferId,
+	types::{convert_bridge_transfer_id, BridgeTransferId, ChainClock, TimeLockUnit},
 };
 
 pub mod active_swap;
@@ -58,6 +59,50 @@ where
 			blockchain_2,
 		}
 	}
+
+	/// Restricts the transfers to the assets of `registry`, in which the blockchains are named
+	/// `chain_1` and `chain_2`, and locks them in the token of their asset on the other
+	/// blockchain.
+	pub fn with_assets(
+		mut self,
+		registry: Arc<AssetRegistry>,
+		chain_1: &str,
+		chain_2: &str,
+	) -> Self {
+		self.active_swaps_b1_to_b2
+			.set_assets(ChainAssets::new(registry.clone(), chain_1), chain_2);
+		self.active_swaps_b2_to_b1
+			.set_assets(ChainAssets::new(registry, chain_2), chain_1);
+		self
+	}
+
+	/// Sets the units of the time locks of both blockchains, which default to seconds.
+	pub fn with_time_lock_units(mut self, unit_1: TimeLockUnit, unit_2: TimeLockUnit) -> Self {
+		self.active_swaps_b1_to_b2.set_time_lock_units(unit_1, unit_2);
+		self.active_swaps_b2_to_b1.set_time_lock_units(unit_2, unit_1);
+		self
+	}
+
+	/// Sets the clocks of both blockchains, in the unit of their time locks, which bound the
+	/// counterparty locks of the transfers whose assets are locked late.
+	pub fn with_clocks(mut self, clock_1: ChainClock, clock_2: ChainClock) -> Self {
+		self.active_swaps_b1_to_b2.set_initiator_clock(clock_1);
+		self.active_swaps_b2_to_b1.set_initiator_clock(clock_2);
+		self
+	}
+
+	/// Ignores the transfers initiated from now on, in both directions, to drain the swaps in
+	/// flight before shutting down.
+	pub fn stop_accepting(&mut self) {
+		self.active_swaps_b1_to_b2.stop_accepting();
+		self.active_swaps_b2_to_b1.stop_accepting();
+	}
+
+	/// Number of swaps with a contract call in progress or waiting to be retried, in both
+	/// directions.
+	pub fn submitting(&self) -> usize {
+		self.active_swaps_b1_to_b2.submitting() + self.active_swaps_b2_to_b1.submitting()
+	}
 }
 
 fn handle_initiator_event<BFrom, BTo>(
@@ -69,17 +114,63 @@ where
 	BTo: BlockchainService + 'static,
 	BTo::Hash: From<BFrom::Hash>,
 {
+	let _span = initiator_event.bridge_transfer_id().span().entered();
 	match initiator_event {
 		BridgeContractInitiatorEvent::Initiated(ref details) => {
-			if active_swaps.already_executing(&details.bridge_transfer_id) {
-				warn!("BridgeService: Bridge transfer {:?} already present, monitoring should only return event once", details.bridge_transfer_id);
+			if active_swaps.already_executing(&details.bridge_transfer_id)
+				|| active_swaps.already_finished(&details.bridge_transfer_id)
+			{
+				warn!(
+					"BridgeService: Bridge transfer {:?} already present, ignoring the event delivered again",
+					details.bridge_transfer_id
+				);
 				return Some(IEvent::Warn(IWarn::AlreadyPresent(details.clone())));
 			}
-			active_swaps.start_bridge_transfer(details.clone());
+			if !active_swaps.is_accepting() {
+				warn!(
+					"BridgeService: Bridge transfer {:?} not started, shutting down",
+					details.bridge_transfer_id
+				);
+				return Some(IEvent::Warn(IWarn::ShuttingDown(details.clone())));
+			}
+			if let Err(error) = active_swaps.start_bridge_transfer(details.clone()) {
+				warn!(
+					"BridgeService: Bridge transfer {:?} not started: {error}",
+					details.bridge_transfer_id
+				);
+				return Some(IEvent::Warn(match error {
+					StartSwapError::Fee(_) => IWarn::FeeExceedsAmount(details.clone()),
+					StartSwapError::TimeLock(error) => {
+						IWarn::TimeLockRejected(details.clone(), error)
+					}
+					StartSwapError::Limit(error) => {
+						IWarn::TransferLimitExceeded(details.clone(), error)
+					}
+					StartSwapError::Asset(error) => IWarn::UnknownAsset(details.clone(), error),
+				}));
+			}
 			Some(IEvent::ContractEvent(initiator_event))
 		}
 		BridgeContractInitiatorEvent::Completed(_) => Some(IEvent::ContractEvent(initiator_event)),
-		BridgeContractInitiatorEvent::Refunded(_) => todo!(),
+		BridgeContractInitiatorEvent::Refunded(ref bridge_transfer_id) => {
+			if active_swaps.refund_bridge_transfer(bridge_transfer_id).is_err() {
+				trace!(
+					"BridgeService: Refunded bridge transfer {:?} is not tracked",
+					bridge_transfer_id
+				);
+			}
+			Some(IEvent::ContractEvent(initiator_event))
+		}
+		BridgeContractInitiatorEvent::Reverted(ref bridge_transfer_id) => {
+			// The transfer does not exist anymore, the swap is aborted like a refunded one.
+			if active_swaps.refund_bridge_transfer(bridge_transfer_id).is_err() {
+				trace!(
+					"BridgeService: Reverted bridge transfer {:?} is not tracked",
+					bridge_transfer_id
+				);
+			}
+			Some(IEvent::ContractEvent(initiator_event))
+		}
 	}
 }
 
@@ -93,6 +184,7 @@ where
 	BFrom::Hash: From<BTo::Hash>,
 {
 	use BridgeContractCounterpartyEvent::*;
+	let _span = event.bridge_transfer_id().span().entered();
 	match event {
 		Locked(ref _details) => Some(CEvent::ContractEvent(event)),
 		Completed(ref details) => match active_swaps.complete_bridge_transfer(details.clone()) {
@@ -106,9 +198,24 @@ where
 					active_swap::ActiveSwapMapError::NonExistingSwap => {
 						Some(CEvent::Warn(CWarn::CannotCompleteUnexistingSwap(details.clone())))
 					}
+					active_swap::ActiveSwapMapError::AlreadyCompleting => {
+						Some(CEvent::Warn(CWarn::AlreadyCompleting(details.clone())))
+					}
 				}
 			}
 		},
+		Aborted(ref bridge_transfer_id) => {
+			// The swap can no longer complete, the initiator gets refunded
+			let bridge_transfer_id: BridgeTransferId<BFrom::Hash> =
+				convert_bridge_transfer_id(bridge_transfer_id.clone());
+			if active_swaps.refund_bridge_transfer(&bridge_transfer_id).is_err() {
+				trace!(
+					"BridgeService: Aborted bridge transfer {:?} is not tracked",
+					bridge_transfer_id
+				);
+			}
+			Some(CEvent::ContractEvent(event))
+		}
 	}
 }
 
@@ -241,7 +348,22 @@ where
 		Poll::Ready(Some(event)) => {
 			trace!("BridgeService: Received event from active swaps: {:?}", event);
 			match event {
+				BridgeAssetsPendingApproval(bridge_transfer_id) => {
+					return Some(HandleActiveSwapEvent::InitiatorEvent(IEvent::PendingApproval(
+						bridge_transfer_id,
+					)));
+				}
+
 				// Locking
+				BridgeAssetsInsufficientLiquidity(bridge_transfer_id, error) => {
+					warn!(
+						"BridgeService: Bridge transfer {:?} parked: {error}",
+						bridge_transfer_id
+					);
+					return Some(HandleActiveSwapEvent::InitiatorEvent(
+						IEvent::InsufficientLiquidity(bridge_transfer_id, error),
+					));
+				}
 				BridgeAssetsLocked(bridge_transfer_id) => {
 					trace!(
 						"BridgeService: Bridge assets locked for transfer {:?}",
@@ -268,6 +390,15 @@ where
 						))),
 					));
 				}
+				BridgeAssetsTimeLockElapsed(bridge_transfer_id, error) => {
+					warn!(
+						"BridgeService: Bridge transfer {:?} aborted before its lock: {error}",
+						bridge_transfer_id
+					);
+					return Some(HandleActiveSwapEvent::InitiatorEvent(IEvent::Warn(
+						IWarn::TimeLockElapsed(bridge_transfer_id, error),
+					)));
+				}
 				BridgeAssetsLockingAbortedTooManyAttempts(bride_transfer_id) => {
 					warn!(
 						"BridgeService: Aborted bridge transfer due to too many attempts: {:?}",
@@ -281,11 +412,17 @@ where
 				}
 
 				// Completing
-				BridgeAssetsCompleted(bridge_transfer_id) => {
+				BridgeAssetsCompleted(bridge_transfer_id, fee) => {
 					trace!(
 						"BridgeService: Bridge assets completed for transfer {:?}",
 						bridge_transfer_id
 					);
+					if fee.0 > 0 {
+						return Some(HandleActiveSwapEvent::InitiatorEvent(IEvent::FeeCollected(
+							bridge_transfer_id,
+							fee,
+						)));
+					}
 				}
 				BridgeAssetsCompletingError(bridge_transfer_id, error) => {
 					warn!("BridgeService: Error completing bridge assets: {:?}", error);
@@ -1,27 +1,35 @@
 use std::{
-	collections::HashMap,
+	collections::{HashMap, HashSet, VecDeque},
 	convert::From,
 	pin::Pin,
 	task::{Context, Poll},
-	time::Duration,
+	time::{Duration, Instant},
 };
 
 use futures::{task::AtomicWaker, Future, FutureExt, Stream};
-use futures_time::future::{FutureExt as TimeoutFutureExt, Timeout};
+use futures_time::future::FutureExt as TimeoutFutureExt;
 use futures_timer::Delay;
 use thiserror::Error;
+use tracing::Instrument;
 
 use crate::bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator};
 use crate::{
+	asset_registry::{AssetError, ChainAssets},
 	blockchain_service::BlockchainService,
 	bridge_contracts::{BridgeContractCounterpartyError, BridgeContractInitiatorError},
+	transfer_limits::{TransferLimitError, TransferLimiter, TransferLimits},
 	types::{
-		convert_bridge_transfer_id, BridgeTransferDetails, BridgeTransferId, CompletedDetails,
-		HashLock,
+		convert_bridge_transfer_id, Amount, BridgeHashType, BridgeTransferDetails,
+		BridgeTransferId, ChainClock, CompletedDetails, Fee, FeeError, HashLock, TimeLock,
+		TimeLockPolicy, TimeLockPolicyError, TimeLockUnit, TokenAddress,
 	},
+	work_queue::WorkQueue,
 };
 
-pub type BoxedFuture<R, E> = Timeout<Pin<Box<dyn Future<Output = Result<R, E>> + Send>>, Delay>;
+/// Number of finished swaps remembered to ignore the events of their transfers delivered again.
+const FINISHED_SWAPS: usize = 100_000;
+
+pub type BoxedFuture<R, E> = Pin<Box<dyn Future<Output = Result<R, E>> + Send>>;
 
 pub struct ActiveSwap<BFrom, BTo>
 where
@@ -29,9 +37,46 @@ where
 	BTo: BlockchainService,
 {
 	pub details: BridgeTransferDetails<BFrom::Address, BFrom::Hash>,
+	/// Relayer fee deducted from the amount locked on the counterparty contract.
+	pub fee: Amount,
+	/// Assets locked on the counterparty contract for the recipient.
+	pub locked: LockedAssets,
+	/// Time lock of the assets locked on the counterparty contract, relative to the lock, in the
+	/// unit of the counterparty blockchain.
+	pub time_lock: TimeLock,
+	/// Expiry of the time lock of the initiator, in the time of the initiator blockchain, which
+	/// bounds the counterparty lock when the assets are locked late.
+	pub initiator_expiry: TimeLock,
+	/// Whether the lack of liquidity of the counterparty contract was reported for the swap.
+	pub insufficient_liquidity: bool,
 	pub state: ActiveSwapState<BTo>,
 }
 
+/// Assets locked on the counterparty contract for the recipient of a transfer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedAssets {
+	/// Amount of the transfer, the fee deducted, in the decimals of the counterparty token.
+	pub amount: Amount,
+	/// Token of the asset on the counterparty blockchain, unset when the transfers are not
+	/// restricted to registered assets.
+	pub token: Option<TokenAddress>,
+}
+
+/// Details of the transfer with the assets to lock for the recipient, and the time lock of the
+/// counterparty lock.
+fn lock_details<A: Clone, H: Clone>(
+	details: &BridgeTransferDetails<A, H>,
+	locked: &LockedAssets,
+	time_lock: TimeLock,
+) -> BridgeTransferDetails<A, H> {
+	BridgeTransferDetails {
+		amount: locked.amount,
+		token: locked.token.clone(),
+		time_lock,
+		..details.clone()
+	}
+}
+
 impl<BFrom, BTo> std::fmt::Debug for ActiveSwap<BFrom, BTo>
 where
 	BFrom: BlockchainService,
@@ -40,6 +85,11 @@ where
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		f.debug_struct("ActiveSwap")
 			.field("details", &self.details)
+			.field("fee", &self.fee)
+			.field("locked", &self.locked)
+			.field("time_lock", &self.time_lock)
+			.field("initiator_expiry", &self.initiator_expiry)
+			.field("insufficient_liquidity", &self.insufficient_liquidity)
 			.field("state", &self.state)
 			.finish()
 	}
@@ -47,12 +97,26 @@ where
 
 type Attempts = usize;
 
+/// Progress of a swap before a restart of the relayer, which it resumes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RestoredSwap {
+	Locked,
+	/// Pending approval, with the expiry of the initiator time lock.
+	PendingApproval(TimeLock),
+	/// Parked for insufficient liquidity, with the expiry of the initiator time lock.
+	InsufficientLiquidity(TimeLock),
+}
+
 pub enum ActiveSwapState<BTo>
 where
 	BTo: BlockchainService,
 {
+	/// The assets are locked once the transfer is approved by the operators.
+	PendingApproval,
 	LockingTokens(BoxedFuture<(), LockBridgeTransferAssetsError>, Attempts),
 	LockingTokensError(Delay, Attempts),
+	/// The liquidity of the counterparty contract is checked again once the delay expired.
+	InsufficientLiquidity(Delay, Attempts),
 	WaitingForUnlockedEvent,
 	CompletingBridging(
 		BoxedFuture<(), CompleteBridgeTransferError>,
@@ -70,12 +134,16 @@ where
 {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
+			ActiveSwapState::PendingApproval => f.debug_tuple("PendingApproval").finish(),
 			ActiveSwapState::LockingTokens(_, attempts) => {
 				f.debug_struct("LockingTokens").field("attempts", attempts).finish()
 			}
 			ActiveSwapState::LockingTokensError(_, attempts) => {
 				f.debug_struct("LockingTokensError").field("attempts", attempts).finish()
 			}
+			ActiveSwapState::InsufficientLiquidity(_, attempts) => {
+				f.debug_struct("InsufficientLiquidity").field("attempts", attempts).finish()
+			}
 			ActiveSwapState::WaitingForUnlockedEvent => {
 				f.debug_tuple("WaitingForUnlockedEvent").finish()
 			}
@@ -96,6 +164,18 @@ pub struct ActiveSwapConfig {
 	pub error_attempts: usize,
 	pub error_delay: Duration,
 	pub contract_call_timeout: Duration,
+	pub fee: Fee,
+	pub time_lock_policy: TimeLockPolicy,
+	pub limits: TransferLimits,
+	/// Amount above which the transfers wait for the approval of the operators before their
+	/// assets are locked. All transfers are locked right away when unset.
+	pub approval_threshold: Option<u128>,
+	/// Delay between two checks of the liquidity of the counterparty contract, while it lacks the
+	/// liquidity to lock the assets of a transfer.
+	pub liquidity_check_interval: Duration,
+	/// Number of contract calls run at once for the swaps of the transfers initiated on a
+	/// blockchain. The calls of a swap run one at a time.
+	pub workers: usize,
 }
 impl Default for ActiveSwapConfig {
 	fn default() -> Self {
@@ -103,6 +183,12 @@ impl Default for ActiveSwapConfig {
 			error_attempts: 3,
 			error_delay: Duration::from_secs(5),
 			contract_call_timeout: Duration::from_secs(30),
+			fee: Fee::default(),
+			time_lock_policy: TimeLockPolicy::default(),
+			limits: TransferLimits::default(),
+			approval_threshold: None,
+			liquidity_check_interval: Duration::from_secs(60),
+			workers: 16,
 		}
 	}
 }
@@ -116,6 +202,28 @@ where
 	pub initiator_contract: BFrom::InitiatorContract,
 	pub counterparty_contract: BTo::CounterpartyContract,
 	swaps: HashMap<BridgeTransferId<BFrom::Hash>, ActiveSwap<BFrom, BTo>>,
+	/// Units of the time locks of the initiator and of the counterparty blockchains.
+	time_lock_units: (TimeLockUnit, TimeLockUnit),
+	/// Clock of the initiator blockchain, the time elapsed since the map was built when unset.
+	initiator_clock: Option<ChainClock>,
+	built_at: Instant,
+	/// Value locked for the transfers of the initiator blockchain.
+	limiter: TransferLimiter<BFrom::Address>,
+	/// Transfers whose swap finished.
+	finished: FinishedSwaps<BridgeTransferId<BFrom::Hash>>,
+	/// Transfers in flight before a restart, whose swaps resume from where they were once
+	/// started again.
+	restored: HashMap<BridgeTransferId<BFrom::Hash>, RestoredSwap>,
+	/// Contract calls of the swaps, run on a bounded number of workers.
+	calls: WorkQueue<BridgeTransferId<BFrom::Hash>>,
+	/// Events of the swaps raised outside of their contract calls, such as the transfers which
+	/// started to wait for their approval, reported on the next polls.
+	queued_events: VecDeque<ActiveSwapEvent<BFrom::Hash>>,
+	/// Assets of the initiator blockchain the transfers are accepted in, any when unset, and the
+	/// name of the counterparty blockchain in the registry.
+	assets: Option<(ChainAssets, String)>,
+	/// Swaps are no longer started once the relayer shuts down.
+	accepting: bool,
 	waker: AtomicWaker,
 }
 
@@ -128,6 +236,9 @@ where
 		f.debug_struct("ActiveSwapMap")
 			.field("swaps", &self.swaps)
 			.field("config", &self.config)
+			.field("time_lock_units", &self.time_lock_units)
+			.field("assets", &self.assets)
+			.field("calls", &self.calls)
 			.finish()
 	}
 }
@@ -136,6 +247,32 @@ where
 pub enum ActiveSwapMapError {
 	#[error("Non existing swap")]
 	NonExistingSwap,
+	#[error("Swap is already completing or finished")]
+	AlreadyCompleting,
+}
+
+/// Reasons a swap is not started for an initiated transfer.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum StartSwapError {
+	#[error(transparent)]
+	Fee(#[from] FeeError),
+	#[error(transparent)]
+	TimeLock(#[from] TimeLockPolicyError),
+	#[error(transparent)]
+	Limit(#[from] TransferLimitError),
+	#[error(transparent)]
+	Asset(#[from] AssetError),
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ApproveSwapError {
+	#[error("Non existing swap")]
+	NonExistingSwap,
+	#[error("Swap is not pending approval")]
+	NotPendingApproval,
+	/// The swap is aborted, too little of the initiator time lock is left to lock the assets.
+	#[error("Time lock no longer leaves the time to lock the assets: {0}")]
+	TimeLockElapsed(TimeLockPolicyError),
 }
 
 impl<BTo, BFrom> ActiveSwapMap<BFrom, BTo>
@@ -152,7 +289,17 @@ where
 			initiator_contract,
 			counterparty_contract,
 			swaps: HashMap::new(),
+			limiter: TransferLimiter::new(config.limits),
+			calls: WorkQueue::new(config.workers),
+			finished: FinishedSwaps::new(FINISHED_SWAPS),
+			restored: HashMap::new(),
+			queued_events: VecDeque::new(),
+			assets: None,
+			accepting: true,
 			config,
+			time_lock_units: (TimeLockUnit::SECONDS, TimeLockUnit::SECONDS),
+			initiator_clock: None,
+			built_at: Instant::now(),
 			waker: AtomicWaker::new(),
 		}
 	}
@@ -168,37 +315,326 @@ where
 		self.swaps.get_mut(key)
 	}
 
+	/// Sets the units of the time locks of the initiator and of the counterparty blockchains,
+	/// which default to seconds.
+	pub fn set_time_lock_units(&mut self, initiator: TimeLockUnit, counterparty: TimeLockUnit) {
+		self.time_lock_units = (initiator, counterparty);
+	}
+
+	/// Sets the clock of the initiator blockchain the time locks of the transfers are counted
+	/// with. The time elapsed since the map was built is used when unset.
+	pub fn set_initiator_clock(&mut self, clock: ChainClock) {
+		self.initiator_clock = Some(clock);
+	}
+
+	/// Current time of the initiator blockchain, in the unit of its time locks.
+	fn initiator_now(&self) -> u64 {
+		match &self.initiator_clock {
+			Some(clock) => clock(),
+			None => self.time_lock_units.0.from_secs(self.built_at.elapsed().as_secs()).0,
+		}
+	}
+
+	/// Restricts the transfers to the assets registered for the tokens of the initiator
+	/// blockchain, locked in their token on the blockchain named `counterparty_chain`.
+	pub fn set_assets(&mut self, assets: ChainAssets, counterparty_chain: impl Into<String>) {
+		self.assets = Some((assets, counterparty_chain.into()));
+	}
+
+	/// Assets to lock for `amount` of the transfer, in the token of its asset on the counterparty
+	/// blockchain when the transfers are restricted to registered assets.
+	fn locked_assets(
+		&self,
+		details: &BridgeTransferDetails<BFrom::Address, BFrom::Hash>,
+		amount: Amount,
+	) -> Result<LockedAssets, AssetError> {
+		match &self.assets {
+			Some((assets, counterparty_chain)) => {
+				let (token, amount) =
+					assets.counterpart(details.token.as_ref(), amount, counterparty_chain)?;
+				Ok(LockedAssets { amount, token: Some(token) })
+			}
+			None => Ok(LockedAssets { amount, token: None }),
+		}
+	}
+
 	pub fn already_executing(&self, key: &BridgeTransferId<BFrom::Hash>) -> bool {
 		self.swaps.contains_key(key)
 	}
 
+	/// Whether the swap of the transfer finished, so that an event of the transfer delivered
+	/// again doesn't start it twice.
+	pub fn already_finished(&self, key: &BridgeTransferId<BFrom::Hash>) -> bool {
+		self.finished.contains(key)
+	}
+
+	/// Stops starting the swaps of the initiated transfers, which are reported as
+	/// [`IWarn::ShuttingDown`](super::events::IWarn::ShuttingDown) instead. The swaps already
+	/// started keep running.
+	pub fn stop_accepting(&mut self) {
+		self.accepting = false;
+	}
+
+	pub fn is_accepting(&self) -> bool {
+		self.accepting
+	}
+
+	/// Number of swaps with a contract call in progress or waiting to be retried.
+	pub fn submitting(&self) -> usize {
+		use ActiveSwapState::*;
+		self.swaps
+			.values()
+			.filter(|swap| {
+				matches!(
+					swap.state,
+					LockingTokens(..)
+						| LockingTokensError(..)
+						| CompletingBridging(..)
+						| CompletingBridgingError(..)
+				)
+			})
+			.count()
+	}
+
+	/// Records a transfer whose assets were already locked, by the relayer before a restart, so
+	/// that its swap waits for the completion of the recipient once started again instead of
+	/// locking the assets twice.
+	pub fn restore_locked(&mut self, key: BridgeTransferId<BFrom::Hash>) {
+		self.restored.insert(key, RestoredSwap::Locked);
+	}
+
+	/// Records a transfer which was pending approval before a restart, so that its swap waits
+	/// for the approval again once started, without reporting it as pending approval twice. The
+	/// initiator time lock expires at `initiator_expiry`, in the time of the initiator blockchain.
+	pub fn restore_pending_approval(
+		&mut self,
+		key: BridgeTransferId<BFrom::Hash>,
+		initiator_expiry: TimeLock,
+	) {
+		self.restored.insert(key, RestoredSwap::PendingApproval(initiator_expiry));
+	}
+
+	/// Records a transfer which was parked for insufficient liquidity before a restart, so that
+	/// its swap checks the liquidity of the counterparty contract again once started, without
+	/// reporting it twice. The initiator time lock expires at `initiator_expiry`, in the time of
+	/// the initiator blockchain.
+	pub fn restore_insufficient_liquidity(
+		&mut self,
+		key: BridgeTransferId<BFrom::Hash>,
+		initiator_expiry: TimeLock,
+	) {
+		self.restored.insert(key, RestoredSwap::InsufficientLiquidity(initiator_expiry));
+	}
+
+	/// Starts the swap by locking the transferred amount, minus the relayer fee, on the
+	/// counterparty contract, until the counterparty time lock of the [`TimeLockPolicy`].
+	///
+	/// The amount is locked in the token of the asset of the transfer on the counterparty
+	/// blockchain, scaled to its decimals, when the transfers are restricted to registered assets.
+	///
+	/// Fails without starting the swap if the transfer is in an asset which is not registered or
+	/// not representable on the counterparty blockchain, if the fee exceeds the amount, if the time lock of the
+	/// transfer does not leave the recipient and the relayer enough time to complete it safely, or
+	/// if the locked amount exceeds the [`TransferLimits`].
+	///
+	/// Transfers above the approval threshold are not locked until
+	/// [`approve_bridge_transfer`](Self::approve_bridge_transfer) is called.
+	///
+	/// The swap of a transfer restored with [`restore_locked`](Self::restore_locked) waits for
+	/// the completion of the recipient, its assets being already locked. The swap of a transfer
+	/// restored with [`restore_pending_approval`](Self::restore_pending_approval) waits for its
+	/// approval again, and the swap of a transfer restored with
+	/// [`restore_insufficient_liquidity`](Self::restore_insufficient_liquidity) waits for the
+	/// next check of the liquidity.
 	pub fn start_bridge_transfer(
 		&mut self,
 		details: BridgeTransferDetails<BFrom::Address, BFrom::Hash>,
-	) where
+	) -> Result<(), StartSwapError>
+	where
 		BTo::Hash: From<BFrom::Hash>,
 	{
 		assert!(self.swaps.get(&details.bridge_transfer_id).is_none());
 
-		let counterparty_contract = self.counterparty_contract.clone();
-		let bridge_transfer_id = details.bridge_transfer_id.clone();
+		let (initiator_unit, counterparty_unit) = self.time_lock_units;
+		let now = self.initiator_now();
+		let restored = self.restored.remove(&details.bridge_transfer_id);
+		let initiator_expiry = match &restored {
+			Some(
+				RestoredSwap::PendingApproval(initiator_expiry)
+				| RestoredSwap::InsufficientLiquidity(initiator_expiry),
+			) => initiator_expiry.clone(),
+			_ => TimeLock(now.saturating_add(details.time_lock.0)),
+		};
+		if let Some(RestoredSwap::Locked) = restored {
+			let (amount, fee) = self.config.fee.deduct(details.amount)?;
+			// The counterparty lock is not sent again, it is only recorded, as computed when the
+			// assets were locked unless the policy or the registry changed since
+			let locked = self
+				.locked_assets(&details, amount)
+				.unwrap_or(LockedAssets { amount, token: None });
+			let time_lock = self
+				.config
+				.time_lock_policy
+				.counterparty_time_lock(&details.time_lock, initiator_unit, counterparty_unit)
+				.unwrap_or_else(|_| details.time_lock.clone());
+			tracing::info!(
+				"Restored locked bridge transfer {:?}, waiting for its completion",
+				details.bridge_transfer_id
+			);
+			self.swaps.insert(
+				details.bridge_transfer_id.clone(),
+				ActiveSwap {
+					state: ActiveSwapState::WaitingForUnlockedEvent,
+					details,
+					fee,
+					locked,
+					time_lock,
+					initiator_expiry,
+					insufficient_liquidity: false,
+				},
+			);
+			self.waker.wake();
+			return Ok(());
+		}
 
-		tracing::trace!("Starting active swap for bridge transfer {:?}", bridge_transfer_id);
+		let (amount, fee) = self.config.fee.deduct(details.amount)?;
+		let locked = self.locked_assets(&details, amount)?;
+		let time_lock = remaining_time_lock(
+			&self.config.time_lock_policy,
+			self.time_lock_units,
+			&initiator_expiry,
+			now,
+		)?;
+		let details_to_lock = lock_details(&details, &locked, time_lock.clone());
+		self.limiter.try_lock(&details.initiator_address, amount, Instant::now())?;
+		let bridge_transfer_id = details.bridge_transfer_id.clone();
 
+		let state = if let Some(RestoredSwap::PendingApproval(_)) = restored {
+			tracing::info!(
+				"Restored bridge transfer {:?}, waiting for its approval",
+				bridge_transfer_id
+			);
+			ActiveSwapState::PendingApproval
+		} else if let Some(RestoredSwap::InsufficientLiquidity(_)) = restored {
+			tracing::info!(
+				"Restored bridge transfer {:?}, waiting for the liquidity to lock it",
+				bridge_transfer_id
+			);
+			ActiveSwapState::InsufficientLiquidity(
+				Delay::new(self.config.liquidity_check_interval),
+				0,
+			)
+		} else if self
+			.config
+			.approval_threshold
+			.is_some_and(|threshold| details.amount.0 > threshold)
+		{
+			tracing::info!("Bridge transfer {:?} is pending approval", bridge_transfer_id);
+			self.queued_events.push_back(ActiveSwapEvent::BridgeAssetsPendingApproval(
+				bridge_transfer_id.clone(),
+			));
+			ActiveSwapState::PendingApproval
+		} else {
+			tracing::trace!("Starting active swap for bridge transfer {:?}", bridge_transfer_id);
+			self.lock_assets(details_to_lock)
+		};
 		self.swaps.insert(
 			bridge_transfer_id,
 			ActiveSwap {
-				details: details.clone(),
-				state: ActiveSwapState::LockingTokens(
-					call_lock_bridge_transfer_assets::<BFrom, BTo>(counterparty_contract, details)
-						.boxed()
-						.timeout(Delay::new(self.config.contract_call_timeout)),
-					0,
+				state,
+				details,
+				fee,
+				locked,
+				time_lock,
+				initiator_expiry,
+				// Reported before the restart
+				insufficient_liquidity: matches!(
+					restored,
+					Some(RestoredSwap::InsufficientLiquidity(_))
 				),
 			},
 		);
 
 		self.waker.wake();
+		Ok(())
+	}
+
+	/// Locks the assets of a swap pending approval, until a counterparty lock computed from what
+	/// is left of the initiator time lock.
+	///
+	/// The swap is aborted instead if too little of the initiator time lock is left to lock the
+	/// assets with the margin of the [`TimeLockPolicy`], the initiator being left to get refunded.
+	pub fn approve_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: &BridgeTransferId<BFrom::Hash>,
+	) -> Result<(), ApproveSwapError>
+	where
+		BTo::Hash: From<BFrom::Hash>,
+	{
+		let active_swap =
+			self.swaps.get(bridge_transfer_id).ok_or(ApproveSwapError::NonExistingSwap)?;
+		if !matches!(active_swap.state, ActiveSwapState::PendingApproval) {
+			return Err(ApproveSwapError::NotPendingApproval);
+		}
+
+		tracing::info!("Bridge transfer {:?} approved", bridge_transfer_id);
+
+		let time_lock = match remaining_time_lock(
+			&self.config.time_lock_policy,
+			self.time_lock_units,
+			&active_swap.initiator_expiry,
+			self.initiator_now(),
+		) {
+			Ok(time_lock) => time_lock,
+			Err(error) => {
+				tracing::warn!(
+					"Bridge transfer {:?} approved too late to be locked: {error}",
+					bridge_transfer_id
+				);
+				if let Some(active_swap) = self.swaps.get_mut(bridge_transfer_id) {
+					active_swap.state = ActiveSwapState::Aborted;
+				}
+				self.queued_events.push_back(ActiveSwapEvent::BridgeAssetsTimeLockElapsed(
+					bridge_transfer_id.clone(),
+					error.clone(),
+				));
+				self.waker.wake();
+				return Err(ApproveSwapError::TimeLockElapsed(error));
+			}
+		};
+		let details = lock_details(&active_swap.details, &active_swap.locked, time_lock.clone());
+		let state = self.lock_assets(details);
+		if let Some(active_swap) = self.swaps.get_mut(bridge_transfer_id) {
+			active_swap.time_lock = time_lock;
+			active_swap.state = state;
+		}
+
+		self.waker.wake();
+		Ok(())
+	}
+
+	fn lock_assets(
+		&self,
+		details: BridgeTransferDetails<BFrom::Address, BFrom::Hash>,
+	) -> ActiveSwapState<BTo>
+	where
+		BTo::Hash: From<BFrom::Hash>,
+	{
+		let bridge_transfer_id = details.bridge_transfer_id.clone();
+		let span = bridge_transfer_id.span();
+		ActiveSwapState::LockingTokens(
+			queue_call(
+				&self.calls,
+				bridge_transfer_id,
+				call_lock_bridge_transfer_assets::<BFrom, BTo>(
+					self.counterparty_contract.clone(),
+					details,
+				)
+				.instrument(span),
+				self.config.contract_call_timeout,
+			),
+			0,
+		)
 	}
 
 	pub fn complete_bridge_transfer(
@@ -208,11 +644,24 @@ where
 	where
 		BFrom::Hash: From<BTo::Hash>,
 	{
+		let bridge_transfer_id = convert_bridge_transfer_id(details.bridge_transfer_id.clone());
+		if self.finished.contains(&bridge_transfer_id) {
+			return Err(ActiveSwapMapError::AlreadyCompleting);
+		}
 		let active_swap = self
 			.swaps
-			.get_mut(&convert_bridge_transfer_id(details.bridge_transfer_id.clone()))
+			.get_mut(&bridge_transfer_id)
 			.ok_or(ActiveSwapMapError::NonExistingSwap)?;
 
+		// The completion of the recipient may be delivered more than once
+		if matches!(
+			active_swap.state,
+			ActiveSwapState::CompletingBridging(..)
+				| ActiveSwapState::CompletingBridgingError(..)
+				| ActiveSwapState::Completed
+		) {
+			return Err(ActiveSwapMapError::AlreadyCompleting);
+		}
 		debug_assert!(matches!(active_swap.state, ActiveSwapState::WaitingForUnlockedEvent));
 
 		let initiator_contract = self.initiator_contract.clone();
@@ -223,9 +672,13 @@ where
 		);
 
 		active_swap.state = ActiveSwapState::CompletingBridging(
-			call_complete_bridge_transfer::<BFrom, BTo>(initiator_contract, details.clone())
-				.boxed()
-				.timeout(Delay::new(self.config.contract_call_timeout)),
+			queue_call(
+				&self.calls,
+				bridge_transfer_id,
+				call_complete_bridge_transfer::<BFrom, BTo>(initiator_contract, details.clone())
+					.instrument(details.bridge_transfer_id.span()),
+				self.config.contract_call_timeout,
+			),
 			details.clone(),
 			0,
 		);
@@ -234,30 +687,118 @@ where
 
 		Ok(())
 	}
+
+	/// Stops tracking a swap whose assets were refunded to the initiator, the swap is cleaned up
+	/// on the next poll.
+	pub fn refund_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: &BridgeTransferId<BFrom::Hash>,
+	) -> Result<(), ActiveSwapMapError> {
+		let active_swap = self
+			.swaps
+			.get_mut(bridge_transfer_id)
+			.ok_or(ActiveSwapMapError::NonExistingSwap)?;
+
+		tracing::trace!("Refunded active swap for bridge transfer {:?}", bridge_transfer_id);
+
+		active_swap.state = ActiveSwapState::Aborted;
+
+		self.waker.wake();
+
+		Ok(())
+	}
 }
 
 #[derive(Debug)]
 pub enum ActiveSwapEvent<H> {
+	/// The swap waits for the approval of the operators before locking the assets.
+	BridgeAssetsPendingApproval(BridgeTransferId<H>),
 	BridgeAssetsLocked(BridgeTransferId<H>),
 	BridgeAssetsLockingError(LockBridgeTransferAssetsError),
+	/// The counterparty contract lacks the liquidity to lock the assets, the swap is parked until
+	/// it has enough, or until too little of the initiator time lock is left to lock them. Reported
+	/// once per swap.
+	BridgeAssetsInsufficientLiquidity(BridgeTransferId<H>, LockBridgeTransferAssetsError),
 	BridgeAssetsRetryLocking(BridgeTransferId<H>),
-	BridgeAssetsCompleted(BridgeTransferId<H>),
+	/// Too little of the initiator time lock is left to lock the assets with the margin of the
+	/// time lock policy, the swap is aborted.
+	BridgeAssetsTimeLockElapsed(BridgeTransferId<H>, TimeLockPolicyError),
+	/// The swap is completed and the fee is collected on the initiator contract.
+	BridgeAssetsCompleted(BridgeTransferId<H>, Amount),
 	BridgeAssetsCompletingError(BridgeTransferId<H>, CompleteBridgeTransferError),
 	BridgeAssetsRetryCompleting(BridgeTransferId<H>),
 	BridgeAssetsLockingAbortedTooManyAttempts(BridgeTransferId<H>),
 	BridgeAssetsCompletingAbortedTooManyAttempts(BridgeTransferId<H>),
 }
 
-fn catch_timeout_error<T, E: HasTimeoutError>(
-	result: Poll<Result<Result<T, E>, std::io::Error>>,
-) -> Poll<Result<T, E>> {
-	match result {
-		Poll::Ready(Ok(result)) => Poll::Ready(result),
-		Poll::Ready(Err(_)) => Poll::Ready(Err(E::timeout_error())),
-		Poll::Pending => Poll::Pending,
+/// Set of the most recent finished swaps, the oldest ones are forgotten past its capacity.
+#[derive(Debug)]
+struct FinishedSwaps<K> {
+	capacity: usize,
+	keys: HashSet<K>,
+	order: VecDeque<K>,
+}
+
+impl<K> FinishedSwaps<K>
+where
+	K: Clone + Eq + std::hash::Hash,
+{
+	fn new(capacity: usize) -> Self {
+		Self { capacity, keys: HashSet::new(), order: VecDeque::new() }
+	}
+
+	fn contains(&self, key: &K) -> bool {
+		self.keys.contains(key)
+	}
+
+	fn insert(&mut self, key: K) {
+		if !self.keys.insert(key.clone()) {
+			return;
+		}
+		self.order.push_back(key);
+		if self.order.len() > self.capacity {
+			if let Some(oldest) = self.order.pop_front() {
+				self.keys.remove(&oldest);
+			}
+		}
 	}
 }
 
+/// Counterparty lock of a transfer whose assets are locked at `now`, computed from what is left of
+/// the initiator time lock expiring at `initiator_expiry`. Fails once less than the margin and the
+/// minimum duration of the [`TimeLockPolicy`] are left.
+fn remaining_time_lock(
+	policy: &TimeLockPolicy,
+	(initiator_unit, counterparty_unit): (TimeLockUnit, TimeLockUnit),
+	initiator_expiry: &TimeLock,
+	now: u64,
+) -> Result<TimeLock, TimeLockPolicyError> {
+	let remaining = TimeLock(initiator_expiry.0.saturating_sub(now));
+	policy.counterparty_time_lock(&remaining, initiator_unit, counterparty_unit)
+}
+
+/// Runs a contract call of the swap of `bridge_transfer_id` on a worker of `calls`, the timeout
+/// of the call starts once it runs.
+fn queue_call<H, R, E>(
+	calls: &WorkQueue<BridgeTransferId<H>>,
+	bridge_transfer_id: BridgeTransferId<H>,
+	call: impl Future<Output = Result<R, E>> + Send + 'static,
+	timeout: Duration,
+) -> BoxedFuture<R, E>
+where
+	H: BridgeHashType + 'static,
+	R: Send + 'static,
+	E: HasTimeoutError + Send + 'static,
+{
+	calls
+		.run(bridge_transfer_id, async move {
+			call.timeout(Delay::new(timeout))
+				.await
+				.unwrap_or_else(|_| Err(E::timeout_error()))
+		})
+		.boxed()
+}
+
 impl<BFrom, BTo> Stream for ActiveSwapMap<BFrom, BTo>
 where
 	BFrom: BlockchainService + 'static,
@@ -274,18 +815,46 @@ where
 		tracing::trace!("Polling active swap map");
 
 		// remove all swaps that are completed or aborted
-		this.swaps.retain(|_, swap| {
-			!matches!(swap.state, ActiveSwapState::Completed | ActiveSwapState::Aborted)
+		let finished = &mut this.finished;
+		this.swaps.retain(|bridge_transfer_id, swap| {
+			let done = matches!(swap.state, ActiveSwapState::Completed | ActiveSwapState::Aborted);
+			if done {
+				finished.insert(bridge_transfer_id.clone());
+			}
+			!done
 		});
 
-		for (bridge_transfer_id, ActiveSwap { details: bridge_transfer, state, .. }) in
-			this.swaps.iter_mut()
+		if let Some(event) = this.queued_events.pop_front() {
+			this.waker.wake();
+			return Poll::Ready(Some(event));
+		}
+
+		let now = this.initiator_now();
+
+		for (
+			bridge_transfer_id,
+			ActiveSwap {
+				details: bridge_transfer,
+				fee,
+				locked,
+				time_lock,
+				initiator_expiry,
+				insufficient_liquidity,
+				state,
+			},
+		) in this.swaps.iter_mut()
 		{
 			use ActiveSwapState::*;
 			match state {
+				PendingApproval => {
+					tracing::trace!(
+						"Bridge transfer {:?} is waiting for approval",
+						bridge_transfer_id
+					);
+				}
 				LockingTokens(future, attempts) => {
 					tracing::trace!("Polling locking_tokens {:?}", bridge_transfer_id);
-					match catch_timeout_error(future.poll_unpin(cx)) {
+					match future.poll_unpin(cx) {
 						Poll::Ready(Ok(())) => {
 							*state = ActiveSwapState::WaitingForUnlockedEvent;
 
@@ -293,6 +862,31 @@ where
 								bridge_transfer_id.clone(),
 							)));
 						}
+						Poll::Ready(Err(
+							error @ LockBridgeTransferAssetsError::InsufficientLiquidity { .. },
+						)) => {
+							tracing::trace!(
+								"Not enough liquidity to lock bridge transfer {:?}: {error}",
+								bridge_transfer_id
+							);
+							// Parked until the liquidity is checked again, without counting
+							// as a failed attempt
+							*state = ActiveSwapState::InsufficientLiquidity(
+								Delay::new(this.config.liquidity_check_interval),
+								*attempts,
+							);
+							if !*insufficient_liquidity {
+								*insufficient_liquidity = true;
+								return Poll::Ready(Some(
+									ActiveSwapEvent::BridgeAssetsInsufficientLiquidity(
+										bridge_transfer_id.clone(),
+										error,
+									),
+								));
+							}
+							// Poll the delay of the new state
+							cx.waker().wake_by_ref();
+						}
 						Poll::Ready(Err(error)) => {
 							tracing::trace!(
 								"Locking brige_transfer {:?} failed, error: {:?} attempts: {}",
@@ -321,6 +915,51 @@ where
 						Poll::Pending => {}
 					}
 				}
+				InsufficientLiquidity(delay, attempts) => {
+					if let Poll::Ready(()) = delay.poll_unpin(cx) {
+						tracing::trace!(
+							"Checking liquidity again for bridge transfer {:?}",
+							bridge_transfer_id
+						);
+						// The lock is shortened by the time the swap was parked, and given up
+						// once it can no longer be locked safely
+						match remaining_time_lock(
+							&this.config.time_lock_policy,
+							this.time_lock_units,
+							initiator_expiry,
+							now,
+						) {
+							Ok(remaining) => *time_lock = remaining,
+							Err(error) => {
+								tracing::warn!(
+									"Bridge transfer {:?} parked too long to be locked: {error}",
+									bridge_transfer_id
+								);
+								*state = ActiveSwapState::Aborted;
+								return Poll::Ready(Some(
+									ActiveSwapEvent::BridgeAssetsTimeLockElapsed(
+										bridge_transfer_id.clone(),
+										error,
+									),
+								));
+							}
+						}
+						*state = ActiveSwapState::LockingTokens(
+							queue_call(
+								&this.calls,
+								bridge_transfer_id.clone(),
+								call_lock_bridge_transfer_assets::<BFrom, BTo>(
+									this.counterparty_contract.clone(),
+									lock_details(bridge_transfer, locked, time_lock.clone()),
+								)
+								.instrument(bridge_transfer_id.span()),
+								this.config.contract_call_timeout,
+							),
+							*attempts,
+						);
+						cx.waker().wake_by_ref();
+					}
+				}
 				LockingTokensError(delay, attempts) => {
 					// test if the delay has expired
 					// if it has, retry the lock
@@ -330,12 +969,16 @@ where
 							bridge_transfer_id
 						);
 						*state = ActiveSwapState::LockingTokens(
-							call_lock_bridge_transfer_assets::<BFrom, BTo>(
-								this.counterparty_contract.clone(),
-								bridge_transfer.clone(),
-							)
-							.boxed()
-							.timeout(Delay::new(this.config.contract_call_timeout)),
+							queue_call(
+								&this.calls,
+								bridge_transfer_id.clone(),
+								call_lock_bridge_transfer_assets::<BFrom, BTo>(
+									this.counterparty_contract.clone(),
+									lock_details(bridge_transfer, locked, time_lock.clone()),
+								)
+								.instrument(bridge_transfer_id.span()),
+								this.config.contract_call_timeout,
+							),
 							*attempts + 1,
 						);
 						return Poll::Ready(Some(ActiveSwapEvent::BridgeAssetsRetryLocking(
@@ -347,12 +990,13 @@ where
 					continue;
 				}
 				CompletingBridging(future, details, attempts) => {
-					match catch_timeout_error(future.poll_unpin(cx)) {
+					match future.poll_unpin(cx) {
 						Poll::Ready(Ok(())) => {
 							*state = ActiveSwapState::Completed;
 
 							return Poll::Ready(Some(ActiveSwapEvent::BridgeAssetsCompleted(
 								bridge_transfer_id.clone(),
+								*fee,
 							)));
 						}
 						Poll::Ready(Err(error)) => {
@@ -399,12 +1043,16 @@ where
 					// if it has, retry the lock
 					if let Poll::Ready(()) = delay.poll_unpin(cx) {
 						*state = ActiveSwapState::CompletingBridging(
-							call_complete_bridge_transfer::<BFrom, BTo>(
-								this.initiator_contract.clone(),
-								details.clone(),
-							)
-							.boxed()
-							.timeout(Delay::new(this.config.contract_call_timeout)),
+							queue_call(
+								&this.calls,
+								bridge_transfer_id.clone(),
+								call_complete_bridge_transfer::<BFrom, BTo>(
+									this.initiator_contract.clone(),
+									details.clone(),
+								)
+								.instrument(bridge_transfer_id.span()),
+								this.config.contract_call_timeout,
+							),
 							details.clone(),
 							*attempts + 1,
 						);
@@ -445,6 +1093,8 @@ pub enum LockBridgeTransferAssetsError {
 	LockingError,
 	#[error("Timeout while performing contract call")]
 	ContractCallTimeoutError,
+	#[error("Insufficient liquidity, {available:?} available to lock {required:?}")]
+	InsufficientLiquidity { available: Amount, required: Amount },
 	#[error(transparent)]
 	ContractCallError(#[from] BridgeContractCounterpartyError),
 }
@@ -463,6 +1113,7 @@ async fn call_lock_bridge_transfer_assets<BFrom: BlockchainService, BTo: Blockch
 		time_lock,
 		recipient_address,
 		amount,
+		token,
 		..
 	}: BridgeTransferDetails<BFrom::Address, BFrom::Hash>,
 ) -> Result<(), LockBridgeTransferAssetsError>
@@ -472,6 +1123,19 @@ where
 	let bridge_transfer_id = BridgeTransferId(From::from(bridge_transfer_id.0));
 	let hash_lock = HashLock(From::from(hash_lock.0));
 
+	match counterparty_contract.available_liquidity().await {
+		Ok(Some(available)) if available.0 < amount.0 => {
+			return Err(LockBridgeTransferAssetsError::InsufficientLiquidity {
+				available,
+				required: amount,
+			});
+		}
+		Ok(_) => {}
+		Err(error) => {
+			tracing::warn!("Failed to check the liquidity of the counterparty contract: {error}");
+		}
+	}
+
 	tracing::trace!(
 		"Calling lock_bridge_transfer_assets on counterparty contract for bridge transfer {:?}",
 		bridge_transfer_id
@@ -484,6 +1148,7 @@ where
 			time_lock,
 			recipient_address,
 			amount,
+			token,
 		)
 		.await?;
 
@@ -1,7 +1,11 @@
 use crate::{
+	asset_registry::AssetError,
 	blockchain_service::BlockchainService,
 	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
-	types::{BridgeTransferDetails, BridgeTransferId, CompletedDetails},
+	transfer_limits::TransferLimitError,
+	types::{
+		Amount, BridgeTransferDetails, BridgeTransferId, CompletedDetails, TimeLockPolicyError,
+	},
 };
 
 use super::active_swap::LockBridgeTransferAssetsError;
@@ -11,6 +15,51 @@ pub enum IWarn<A, H> {
 	AlreadyPresent(BridgeTransferDetails<A, H>),
 	CompleteTransferError(BridgeTransferId<H>),
 	CompletionAbortedTooManyAttempts(BridgeTransferId<H>),
+	/// The relayer fee exceeds the transferred amount, the swap is not started.
+	FeeExceedsAmount(BridgeTransferDetails<A, H>),
+	/// The time lock of the transfer is rejected by the time lock policy, the swap is not
+	/// started.
+	TimeLockRejected(BridgeTransferDetails<A, H>, TimeLockPolicyError),
+	/// The amount of the transfer exceeds the transfer limits, the swap is not started.
+	TransferLimitExceeded(BridgeTransferDetails<A, H>, TransferLimitError),
+	/// The transfer is in an asset which is not registered, the swap is not started.
+	UnknownAsset(BridgeTransferDetails<A, H>, AssetError),
+	/// The relayer is shutting down, the swap is not started. The relayer stores the transfer,
+	/// so that its swap is started after the restart.
+	ShuttingDown(BridgeTransferDetails<A, H>),
+	/// Too little of the initiator time lock is left to lock the assets with the margin of the
+	/// time lock policy, the swap is aborted and the initiator left to get refunded.
+	TimeLockElapsed(BridgeTransferId<H>, TimeLockPolicyError),
+}
+
+impl<A, H> IWarn<A, H> {
+	pub fn bridge_transfer_id(&self) -> &BridgeTransferId<H> {
+		match self {
+			IWarn::AlreadyPresent(details)
+			| IWarn::FeeExceedsAmount(details)
+			| IWarn::TimeLockRejected(details, _)
+			| IWarn::TransferLimitExceeded(details, _)
+			| IWarn::UnknownAsset(details, _)
+			| IWarn::ShuttingDown(details) => &details.bridge_transfer_id,
+			IWarn::CompleteTransferError(id)
+			| IWarn::CompletionAbortedTooManyAttempts(id)
+			| IWarn::TimeLockElapsed(id, _) => id,
+		}
+	}
+
+	/// Why the swap of an initiated transfer was not started, if the warning rejects it.
+	pub fn rejection_reason(&self) -> Option<&'static str> {
+		match self {
+			IWarn::FeeExceedsAmount(_) => Some("fee"),
+			IWarn::TimeLockRejected(..) | IWarn::TimeLockElapsed(..) => Some("time_lock"),
+			IWarn::TransferLimitExceeded(..) => Some("transfer_limit"),
+			IWarn::UnknownAsset(..) => Some("asset"),
+			IWarn::AlreadyPresent(_)
+			| IWarn::ShuttingDown(_)
+			| IWarn::CompleteTransferError(_)
+			| IWarn::CompletionAbortedTooManyAttempts(_) => None,
+		}
+	}
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -18,9 +67,27 @@ pub enum IEvent<A, H> {
 	ContractEvent(BridgeContractInitiatorEvent<A, H>),
 	Warn(IWarn<A, H>),
 	RetryCompletingTransfer(BridgeTransferId<H>),
+	/// The counterparty contract lacks the liquidity to lock the assets of the transfer, which
+	/// is parked until it has enough.
+	InsufficientLiquidity(BridgeTransferId<H>, LockBridgeTransferAssetsError),
+	/// The transfer waits for the approval of the operators before its assets are locked.
+	PendingApproval(BridgeTransferId<H>),
+	/// The relayer fee of a completed transfer was collected on the initiator contract.
+	FeeCollected(BridgeTransferId<H>, Amount),
 }
 
 impl<A, H> IEvent<A, H> {
+	pub fn bridge_transfer_id(&self) -> &BridgeTransferId<H> {
+		match self {
+			IEvent::ContractEvent(event) => event.bridge_transfer_id(),
+			IEvent::Warn(warn) => warn.bridge_transfer_id(),
+			IEvent::RetryCompletingTransfer(id)
+			| IEvent::PendingApproval(id)
+			| IEvent::InsufficientLiquidity(id, _)
+			| IEvent::FeeCollected(id, _) => id,
+		}
+	}
+
 	pub fn contract_event(&self) -> Option<&BridgeContractInitiatorEvent<A, H>> {
 		match self {
 			IEvent::ContractEvent(event) => Some(event),
@@ -39,9 +106,25 @@ impl<A, H> IEvent<A, H> {
 pub enum CWarn<H> {
 	BridgeAssetsLockingError(LockBridgeTransferAssetsError),
 	CannotCompleteUnexistingSwap(CompletedDetails<H>),
+	/// The completion of the recipient was delivered again, once the swap is completing or
+	/// finished.
+	AlreadyCompleting(CompletedDetails<H>),
 	LockingAbortedTooManyAttempts(BridgeTransferId<H>),
 }
 
+impl<H> CWarn<H> {
+	/// Locking errors do not carry the transfer they pertain to.
+	pub fn bridge_transfer_id(&self) -> Option<&BridgeTransferId<H>> {
+		match self {
+			CWarn::BridgeAssetsLockingError(_) => None,
+			CWarn::CannotCompleteUnexistingSwap(details) | CWarn::AlreadyCompleting(details) => {
+				Some(&details.bridge_transfer_id)
+			}
+			CWarn::LockingAbortedTooManyAttempts(id) => Some(id),
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum CEvent<H> {
 	RetryLockingAssets(BridgeTransferId<H>),
@@ -50,6 +133,14 @@ pub enum CEvent<H> {
 }
 
 impl<H> CEvent<H> {
+	pub fn bridge_transfer_id(&self) -> Option<&BridgeTransferId<H>> {
+		match self {
+			CEvent::RetryLockingAssets(id) => Some(id),
+			CEvent::ContractEvent(event) => Some(event.bridge_transfer_id()),
+			CEvent::Warn(warn) => warn.bridge_transfer_id(),
+		}
+	}
+
 	pub fn contract_event(&self) -> Option<&BridgeContractCounterpartyEvent<H>> {
 		match self {
 			CEvent::ContractEvent(event) => Some(event),
@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+
+use crate::{
+	bridge_contracts::{
+		BridgeContractCounterparty, BridgeContractCounterpartyResult, BridgeContractInitiator,
+		BridgeContractInitiatorResult,
+	},
+	types::{
+		Amount, BridgeTransferDetails, BridgeTransferId, HashLock, HashLockPreImage,
+		InitiatorAddress, LockDetails, RecipientAddress, TimeLock, TokenAddress,
+	},
+};
+
+/// Initiator or counterparty contract which logs the transactions the relayer would send instead
+/// of sending them, and reports them as successful. The queries reach the wrapped contract, so
+/// that a relayer running on dry run contracts processes the events and the transfers of the
+/// blockchains as it would when armed.
+#[derive(Debug, Clone)]
+pub struct DryRunContract<C> {
+	inner: C,
+	chain: &'static str,
+}
+
+impl<C> DryRunContract<C> {
+	pub fn new(inner: C, chain: &'static str) -> Self {
+		Self { inner, chain }
+	}
+
+	pub fn into_inner(self) -> C {
+		self.inner
+	}
+}
+
+#[async_trait]
+impl<C> BridgeContractInitiator for DryRunContract<C>
+where
+	C: BridgeContractInitiator,
+{
+	type Address = C::Address;
+	type Hash = C::Hash;
+
+	async fn initiate_bridge_transfer(
+		&mut self,
+		initiator_address: InitiatorAddress<Self::Address>,
+		recipient_address: RecipientAddress,
+		hash_lock: HashLock<Self::Hash>,
+		time_lock: TimeLock,
+		amount: Amount,
+	) -> BridgeContractInitiatorResult<()> {
+		tracing::info!(
+			"{}: dry run, not initiating transfer of {} from {:?} to {:?}, {:?}, time lock {}",
+			self.chain,
+			amount.0,
+			initiator_address,
+			recipient_address,
+			hash_lock,
+			time_lock.0
+		);
+		Ok(())
+	}
+
+	async fn complete_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		_secret: HashLockPreImage,
+	) -> BridgeContractInitiatorResult<()> {
+		tracing::info!("{}: dry run, not completing transfer {:?}", self.chain, bridge_transfer_id);
+		Ok(())
+	}
+
+	async fn refund_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+	) -> BridgeContractInitiatorResult<()> {
+		tracing::info!("{}: dry run, not refunding transfer {:?}", self.chain, bridge_transfer_id);
+		Ok(())
+	}
+
+	async fn get_bridge_transfer_details(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+	) -> BridgeContractInitiatorResult<Option<BridgeTransferDetails<Self::Address, Self::Hash>>> {
+		BridgeContractInitiator::get_bridge_transfer_details(&mut self.inner, bridge_transfer_id)
+			.await
+	}
+
+	/// No transaction is sent in a dry run.
+	fn gas_spent(&self) -> u64 {
+		0
+	}
+}
+
+#[async_trait]
+impl<C> BridgeContractCounterparty for DryRunContract<C>
+where
+	C: BridgeContractCounterparty,
+{
+	type Address = C::Address;
+	type Hash = C::Hash;
+
+	async fn lock_bridge_transfer_assets(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		hash_lock: HashLock<Self::Hash>,
+		time_lock: TimeLock,
+		recipient: RecipientAddress,
+		amount: Amount,
+		token: Option<TokenAddress>,
+	) -> BridgeContractCounterpartyResult<()> {
+		tracing::info!(
+			"{}: dry run, not locking transfer {:?} of {} {:?} for {:?}, {:?}, time lock {}",
+			self.chain,
+			bridge_transfer_id,
+			amount.0,
+			token,
+			recipient,
+			hash_lock,
+			time_lock.0
+		);
+		Ok(())
+	}
+
+	async fn complete_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		_secret: HashLockPreImage,
+	) -> BridgeContractCounterpartyResult<()> {
+		tracing::info!("{}: dry run, not completing lock {:?}", self.chain, bridge_transfer_id);
+		Ok(())
+	}
+
+	async fn abort_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+	) -> BridgeContractCounterpartyResult<()> {
+		tracing::info!("{}: dry run, not aborting lock {:?}", self.chain, bridge_transfer_id);
+		Ok(())
+	}
+
+	async fn available_liquidity(&mut self) -> BridgeContractCounterpartyResult<Option<Amount>> {
+		self.inner.available_liquidity().await
+	}
+
+	async fn get_bridge_transfer_details(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+	) -> BridgeContractCounterpartyResult<Option<LockDetails<Self::Hash>>> {
+		BridgeContractCounterparty::get_bridge_transfer_details(&mut self.inner, bridge_transfer_id)
+			.await
+	}
+
+	/// No transaction is sent in a dry run.
+	fn gas_spent(&self) -> u64 {
+		0
+	}
+}
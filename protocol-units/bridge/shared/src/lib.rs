@@ -1,5 +1,20 @@
+pub mod approval;
+pub mod asset_registry;
+pub mod attestation;
 pub mod blockchain_service;
 pub mod bridge_contracts;
 pub mod bridge_monitoring;
 pub mod bridge_service;
+pub mod dry_run;
+pub mod metrics;
+pub mod pre_image;
+pub mod refund_monitor;
+pub mod reorg;
+pub mod secret;
+pub mod swap_soak;
+pub mod transfer_index;
+pub mod transfer_limits;
+pub mod transfer_store;
+pub mod transfer_view;
 pub mod types;
+pub mod work_queue;
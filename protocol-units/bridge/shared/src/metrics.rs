@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use prometheus::{
+	Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+	TextEncoder,
+};
+
+/// Buckets of the step latencies, in seconds: from a few blocks to the usual time locks.
+const LATENCY_BUCKETS: [f64; 10] =
+	[1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1_800.0, 3_600.0];
+
+/// Step of a transfer counted in the metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferStep {
+	Initiated,
+	Locked,
+	Completed,
+	Refunded,
+}
+
+impl TransferStep {
+	pub fn as_str(self) -> &'static str {
+		match self {
+			TransferStep::Initiated => "initiated",
+			TransferStep::Locked => "locked",
+			TransferStep::Completed => "completed",
+			TransferStep::Refunded => "refunded",
+		}
+	}
+}
+
+/// Prometheus metrics of the bridge, labelled by the blockchain they were observed on.
+///
+/// The metrics are registered in their own registry, rendered in the Prometheus text format by
+/// [`BridgeMetrics::encode`]. Clones share the same metrics.
+#[derive(Debug, Clone)]
+pub struct BridgeMetrics {
+	registry: Registry,
+	transfers: IntCounterVec,
+	rejected: IntCounterVec,
+	step_latency: HistogramVec,
+	rpc_errors: IntCounterVec,
+	insufficient_liquidity: IntCounterVec,
+	gas_spent: IntCounterVec,
+	signer_balance: GaugeVec,
+	signer_balance_low: IntGaugeVec,
+}
+
+impl BridgeMetrics {
+	pub fn new() -> Result<Self, prometheus::Error> {
+		let registry = Registry::new();
+		let transfers = IntCounterVec::new(
+			Opts::new("bridge_transfers_total", "Transfers which reached a step"),
+			&["chain", "step"],
+		)?;
+		let rejected = IntCounterVec::new(
+			Opts::new("bridge_transfers_rejected_total", "Transfers the relayer refused to lock"),
+			&["chain", "reason"],
+		)?;
+		let step_latency = HistogramVec::new(
+			HistogramOpts::new(
+				"bridge_transfer_step_latency_seconds",
+				"Time from the initiation of a transfer to a later step",
+			)
+			.buckets(LATENCY_BUCKETS.to_vec()),
+			&["chain", "step"],
+		)?;
+		let rpc_errors = IntCounterVec::new(
+			Opts::new("bridge_rpc_errors_total", "Failed or timed out contract calls"),
+			&["chain"],
+		)?;
+		let insufficient_liquidity = IntCounterVec::new(
+			Opts::new(
+				"bridge_insufficient_liquidity_total",
+				"Transfers parked because the counterparty contract lacked liquidity",
+			),
+			&["chain"],
+		)?;
+		let gas_spent = IntCounterVec::new(
+			Opts::new("bridge_gas_spent_total", "Gas spent by the bridge transactions"),
+			&["chain"],
+		)?;
+
+		// Balances in wei overflow the integer gauges, they are only exported approximately
+		let signer_balance = GaugeVec::new(
+			Opts::new(
+				"bridge_signer_balance",
+				"Balance of the relayer signer, in the smallest unit of the native token",
+			),
+			&["chain"],
+		)?;
+		let signer_balance_low = IntGaugeVec::new(
+			Opts::new(
+				"bridge_signer_balance_low",
+				"Whether the balance of the relayer signer is below its threshold",
+			),
+			&["chain"],
+		)?;
+
+		registry.register(Box::new(transfers.clone()))?;
+		registry.register(Box::new(rejected.clone()))?;
+		registry.register(Box::new(step_latency.clone()))?;
+		registry.register(Box::new(rpc_errors.clone()))?;
+		registry.register(Box::new(insufficient_liquidity.clone()))?;
+		registry.register(Box::new(gas_spent.clone()))?;
+		registry.register(Box::new(signer_balance.clone()))?;
+		registry.register(Box::new(signer_balance_low.clone()))?;
+
+		Ok(Self {
+			registry,
+			transfers,
+			rejected,
+			step_latency,
+			rpc_errors,
+			insufficient_liquidity,
+			gas_spent,
+			signer_balance,
+			signer_balance_low,
+		})
+	}
+
+	pub fn registry(&self) -> &Registry {
+		&self.registry
+	}
+
+	pub fn transfer_step(&self, chain: &str, step: TransferStep) {
+		self.transfers.with_label_values(&[chain, step.as_str()]).inc();
+	}
+
+	/// Counts a transfer initiated on `chain` whose assets were not locked, for `reason`.
+	pub fn transfer_rejected(&self, chain: &str, reason: &str) {
+		self.rejected.with_label_values(&[chain, reason]).inc();
+	}
+
+	/// Records the time elapsed between the initiation of a transfer and `step`.
+	pub fn observe_step_latency(&self, chain: &str, step: TransferStep, latency: Duration) {
+		self.step_latency
+			.with_label_values(&[chain, step.as_str()])
+			.observe(latency.as_secs_f64());
+	}
+
+	pub fn rpc_error(&self, chain: &str) {
+		self.rpc_errors.with_label_values(&[chain]).inc();
+	}
+
+	/// Counts a transfer initiated on `chain` whose assets could not be locked for lack of
+	/// liquidity on the other blockchain.
+	pub fn insufficient_liquidity(&self, chain: &str) {
+		self.insufficient_liquidity.with_label_values(&[chain]).inc();
+	}
+
+	pub fn gas_spent(&self, chain: &str, gas: u64) {
+		self.gas_spent.with_label_values(&[chain]).inc_by(gas);
+	}
+
+	/// Records the balance of the signer of `chain`, and whether it is below its threshold.
+	pub fn signer_balance(&self, chain: &str, balance: u128, low: bool) {
+		self.signer_balance.with_label_values(&[chain]).set(balance as f64);
+		self.signer_balance_low.with_label_values(&[chain]).set(low.into());
+	}
+
+	/// Renders the metrics in the Prometheus text exposition format.
+	pub fn encode(&self) -> Result<String, prometheus::Error> {
+		let mut buffer = Vec::new();
+		TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+		String::from_utf8(buffer).map_err(|error| prometheus::Error::Msg(error.to_string()))
+	}
+}
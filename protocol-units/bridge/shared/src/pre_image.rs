@@ -0,0 +1,138 @@
+use std::fmt;
+
+use aes_gcm::{
+	aead::{Aead, KeyInit},
+	Aes256Gcm, Nonce,
+};
+use rand::RngCore;
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use crate::{
+	transfer_store::{TransferStateStore, TransferStateStoreError},
+	types::{BridgeHashType, BridgeTransferId, HashLockPreImage},
+};
+
+const PLAIN: u8 = 0;
+const ENCRYPTED: u8 = 1;
+const NONCE_LENGTH: usize = 12;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum PreImageError {
+	#[error("Invalid secret encryption key, expected 32 hex encoded bytes")]
+	InvalidKey,
+	#[error("Failed to encrypt secret")]
+	Encryption,
+	#[error("Failed to decrypt secret")]
+	Decryption,
+	#[error("Secret is encrypted but no encryption key is set")]
+	MissingKey,
+	#[error("Invalid sealed secret")]
+	InvalidFormat,
+	#[error("Transfer state store error: {0}")]
+	Store(#[from] TransferStateStoreError),
+}
+
+/// Seals the secrets of the transfers kept in a [`TransferStateStore`] until they are revealed.
+///
+/// Secrets are encrypted with AES-256-GCM when the vault has a key, and stored in plaintext
+/// otherwise. Encrypted secrets can only be opened with the key they were sealed with.
+#[derive(Clone, Default)]
+pub struct PreImageVault {
+	cipher: Option<Aes256Gcm>,
+}
+
+impl fmt::Debug for PreImageVault {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("PreImageVault")
+			.field("encrypted", &self.cipher.is_some())
+			.finish()
+	}
+}
+
+impl PreImageVault {
+	/// Vault storing the secrets in plaintext.
+	pub fn plain() -> Self {
+		Self::default()
+	}
+
+	pub fn encrypted(key: &[u8; 32]) -> Self {
+		Self { cipher: Some(Aes256Gcm::new(key.into())) }
+	}
+
+	/// Vault encrypting the secrets with a hex encoded 32 byte key, which can be loaded with a
+	/// [`SecretLoader`](crate::secret::SecretLoader).
+	pub fn try_from_hex_key(key: &str) -> Result<Self, PreImageError> {
+		let mut bytes = hex::decode(key.trim().trim_start_matches("0x"))
+			.map_err(|_| PreImageError::InvalidKey)?;
+		let vault = Aes256Gcm::new_from_slice(&bytes)
+			.map(|cipher| Self { cipher: Some(cipher) })
+			.map_err(|_| PreImageError::InvalidKey);
+		bytes.zeroize();
+		vault
+	}
+
+	pub fn is_encrypted(&self) -> bool {
+		self.cipher.is_some()
+	}
+
+	pub fn seal(&self, pre_image: &HashLockPreImage) -> Result<Vec<u8>, PreImageError> {
+		let Some(cipher) = &self.cipher else {
+			let mut sealed = Vec::with_capacity(1 + pre_image.0.len());
+			sealed.push(PLAIN);
+			sealed.extend_from_slice(&pre_image.0);
+			return Ok(sealed);
+		};
+
+		let mut nonce = [0u8; NONCE_LENGTH];
+		rand::rngs::OsRng.fill_bytes(&mut nonce);
+		let ciphertext = cipher
+			.encrypt(Nonce::from_slice(&nonce), pre_image.0.as_slice())
+			.map_err(|_| PreImageError::Encryption)?;
+
+		let mut sealed = Vec::with_capacity(1 + NONCE_LENGTH + ciphertext.len());
+		sealed.push(ENCRYPTED);
+		sealed.extend_from_slice(&nonce);
+		sealed.extend_from_slice(&ciphertext);
+		Ok(sealed)
+	}
+
+	pub fn open(&self, sealed: &[u8]) -> Result<HashLockPreImage, PreImageError> {
+		match sealed.split_first() {
+			Some((&PLAIN, secret)) => Ok(HashLockPreImage(secret.to_vec())),
+			Some((&ENCRYPTED, encrypted)) if encrypted.len() > NONCE_LENGTH => {
+				let cipher = self.cipher.as_ref().ok_or(PreImageError::MissingKey)?;
+				let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+				cipher
+					.decrypt(Nonce::from_slice(nonce), ciphertext)
+					.map(HashLockPreImage)
+					.map_err(|_| PreImageError::Decryption)
+			}
+			_ => Err(PreImageError::InvalidFormat),
+		}
+	}
+
+	/// Seals the secret of a transfer and keeps it in `store` until it is revealed.
+	pub async fn store<H: BridgeHashType>(
+		&self,
+		store: &dyn TransferStateStore<Hash = H>,
+		bridge_transfer_id: BridgeTransferId<H>,
+		pre_image: &HashLockPreImage,
+	) -> Result<(), PreImageError> {
+		let sealed = self.seal(pre_image)?;
+		store.put_secret(bridge_transfer_id, sealed).await?;
+		Ok(())
+	}
+
+	/// Opens the secret of a transfer kept in `store`, to reveal it when completing the transfer.
+	pub async fn reveal<H: BridgeHashType>(
+		&self,
+		store: &dyn TransferStateStore<Hash = H>,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> Result<Option<HashLockPreImage>, PreImageError> {
+		match store.secret(bridge_transfer_id).await? {
+			Some(sealed) => self.open(&sealed).map(Some),
+			None => Ok(None),
+		}
+	}
+}
@@ -0,0 +1,147 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+use futures::{task::AtomicWaker, Stream};
+
+use crate::types::{BridgeHashType, BridgeTransferId, TimeLock};
+
+/// The contract a tracked transfer is pending on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RefundSide {
+	/// The transfer was initiated, the assets can be refunded to the initiator.
+	Initiator,
+	/// The assets were locked, the lock can be aborted.
+	Counterparty,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefundMonitorEvent<H> {
+	Refundable(RefundSide, BridgeTransferId<H>),
+}
+
+/// Tracks the time locks of the pending transfers of one blockchain and emits a
+/// [`RefundMonitorEvent::Refundable`] event once a time lock expired.
+///
+/// The time of the blockchain, in the unit of the time locks of its contracts (block number or
+/// timestamp), is fed to the monitor through [`RefundMonitor::update_time`]. The contracts count
+/// the time locks from the transaction of the transfer, so the monitor tracks their absolute
+/// expiry, from [`RefundMonitor::expiry`] at the time the event of the transfer is observed.
+/// A transfer is reported at most once, and is no longer tracked after it is reported.
+#[derive(Debug)]
+pub struct RefundMonitor<H> {
+	time_locks: HashMap<(RefundSide, BridgeTransferId<H>), TimeLock>,
+	current_time: u64,
+	refundable: VecDeque<(RefundSide, BridgeTransferId<H>)>,
+	waker: AtomicWaker,
+}
+
+impl<H> Default for RefundMonitor<H> {
+	fn default() -> Self {
+		Self {
+			time_locks: HashMap::new(),
+			current_time: 0,
+			refundable: VecDeque::new(),
+			waker: AtomicWaker::new(),
+		}
+	}
+}
+
+impl<H> RefundMonitor<H>
+where
+	H: BridgeHashType,
+{
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn current_time(&self) -> u64 {
+		self.current_time
+	}
+
+	pub fn is_tracked(&self, side: RefundSide, bridge_transfer_id: &BridgeTransferId<H>) -> bool {
+		self.time_locks.contains_key(&(side, bridge_transfer_id.clone()))
+	}
+
+	/// Absolute expiry of a time lock, relative to the current time of the blockchain.
+	pub fn expiry(&self, time_lock: &TimeLock) -> TimeLock {
+		TimeLock(self.current_time.saturating_add(time_lock.0))
+	}
+
+	/// Starts tracking the absolute `expiry` of the time lock of a transfer, a transfer that
+	/// already expired is reported on the next poll.
+	pub fn track(
+		&mut self,
+		side: RefundSide,
+		bridge_transfer_id: BridgeTransferId<H>,
+		expiry: TimeLock,
+	) {
+		if self.is_expired(&expiry) {
+			self.refundable.push_back((side, bridge_transfer_id));
+			self.waker.wake();
+		} else {
+			self.time_locks.insert((side, bridge_transfer_id), expiry);
+		}
+	}
+
+	/// Stops tracking a transfer that was completed or refunded. Returns the expiry of its time
+	/// lock if the transfer was tracked.
+	pub fn untrack(
+		&mut self,
+		side: RefundSide,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> Option<TimeLock> {
+		let key = (side, bridge_transfer_id.clone());
+		self.refundable.retain(|refundable| *refundable != key);
+		self.time_locks.remove(&key)
+	}
+
+	/// Advances the time of the blockchain and queues the transfers whose time lock expired.
+	pub fn update_time(&mut self, current_time: u64) {
+		if current_time <= self.current_time {
+			return;
+		}
+		self.current_time = current_time;
+
+		let expired: Vec<_> = self
+			.time_locks
+			.iter()
+			.filter(|(_, expiry)| self.is_expired(expiry))
+			.map(|(key, _)| key.clone())
+			.collect();
+		if expired.is_empty() {
+			return;
+		}
+
+		for key in expired {
+			tracing::trace!("RefundMonitor: time lock expired for {:?}", key);
+			self.time_locks.remove(&key);
+			self.refundable.push_back(key);
+		}
+		self.waker.wake();
+	}
+
+	fn is_expired(&self, expiry: &TimeLock) -> bool {
+		self.current_time > expiry.0
+	}
+}
+
+impl<H> Stream for RefundMonitor<H>
+where
+	H: BridgeHashType,
+{
+	type Item = RefundMonitorEvent<H>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		this.waker.register(cx.waker());
+		match this.refundable.pop_front() {
+			Some((side, bridge_transfer_id)) => {
+				Poll::Ready(Some(RefundMonitorEvent::Refundable(side, bridge_transfer_id)))
+			}
+			None => Poll::Pending,
+		}
+	}
+}
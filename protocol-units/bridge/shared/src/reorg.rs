@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use crate::types::BridgeTransferId;
+
+/// Detects the initiated transfers removed from the chain by a reorg.
+///
+/// The monitor of a chain records the number and hash of the block of each initiation event it
+/// emits. On every new head, the hashes of the tracked blocks are compared with the ones of the
+/// canonical chain: the transfers of the blocks whose hash changed are reverted. Blocks deeper than
+/// `finality_depth` below the head are considered final and are no longer tracked.
+#[derive(Debug)]
+pub struct ReorgTracker<B, H> {
+	finality_depth: u64,
+	blocks: BTreeMap<u64, TrackedBlock<B, H>>,
+}
+
+#[derive(Debug)]
+struct TrackedBlock<B, H> {
+	hash: B,
+	bridge_transfer_ids: Vec<BridgeTransferId<H>>,
+}
+
+impl<B, H> ReorgTracker<B, H>
+where
+	B: PartialEq,
+{
+	pub fn new(finality_depth: u64) -> Self {
+		Self { finality_depth, blocks: BTreeMap::new() }
+	}
+
+	/// Records the block in which the transfer was initiated.
+	pub fn track(
+		&mut self,
+		block_number: u64,
+		block_hash: B,
+		bridge_transfer_id: BridgeTransferId<H>,
+	) {
+		let block = self
+			.blocks
+			.entry(block_number)
+			.or_insert_with(|| TrackedBlock { hash: block_hash, bridge_transfer_ids: Vec::new() });
+		block.bridge_transfer_ids.push(bridge_transfer_id);
+	}
+
+	/// Numbers of the blocks whose canonical hash must be provided on the next head.
+	pub fn tracked_blocks(&self) -> impl Iterator<Item = u64> + '_ {
+		self.blocks.keys().copied()
+	}
+
+	/// Compares the tracked blocks with the canonical chain ending at `head_number`, and returns
+	/// the transfers of the blocks which are no longer part of it.
+	///
+	/// `canonical_hash` returns the hash of the canonical block at a height, `None` if the chain
+	/// has no block at that height anymore.
+	pub fn new_head(
+		&mut self,
+		head_number: u64,
+		canonical_hash: impl Fn(u64) -> Option<B>,
+	) -> Vec<BridgeTransferId<H>> {
+		let mut reverted = Vec::new();
+		self.blocks.retain(|block_number, block| {
+			if canonical_hash(*block_number).as_ref() == Some(&block.hash) {
+				return true;
+			}
+			reverted.append(&mut block.bridge_transfer_ids);
+			false
+		});
+
+		let final_number = head_number.saturating_sub(self.finality_depth);
+		self.blocks = self.blocks.split_off(&final_number);
+		reverted
+	}
+}
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+const ENV_PREFIX: &str = "env:";
+const FILE_PREFIX: &str = "file:";
+const KEYSTORE_PREFIX: &str = "keystore:";
+
+#[derive(Debug, Error)]
+pub enum SecretLoaderError {
+	#[error("Environment variable {0} is not set")]
+	MissingEnvVar(String),
+	#[error("Failed to read secret file {0}: {1}")]
+	File(PathBuf, std::io::Error),
+	#[error("Keystore {0} requires a keystore password")]
+	MissingKeystorePassword(PathBuf),
+	#[error("Failed to decrypt keystore {0}: {1}")]
+	Keystore(PathBuf, eth_keystore::KeystoreError),
+}
+
+/// Resolves the secrets referenced in the configuration of the bridge clients, so that private
+/// keys are not embedded in plaintext in the serialized configuration.
+///
+/// A secret is one of:
+/// - `env:NAME`, the value of the environment variable `NAME`,
+/// - `file:PATH`, the content of the file at `PATH`, without surrounding whitespace,
+/// - `keystore:PATH`, the private key decrypted from the encrypted JSON keystore at `PATH`, hex
+///   encoded with a `0x` prefix,
+/// - any other value, which is the secret itself.
+#[derive(Debug, Clone, Default)]
+pub struct SecretLoader {
+	keystore_password: Option<String>,
+}
+
+impl SecretLoader {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Password of the keystores, itself a secret which can reference an environment variable or
+	/// a file.
+	pub fn with_keystore_password(mut self, keystore_password: impl Into<String>) -> Self {
+		self.keystore_password = Some(keystore_password.into());
+		self
+	}
+
+	/// Whether the secret references a value on the host, rather than being the secret itself.
+	pub fn is_reference(secret: &str) -> bool {
+		[ENV_PREFIX, FILE_PREFIX, KEYSTORE_PREFIX]
+			.iter()
+			.any(|prefix| secret.starts_with(prefix))
+	}
+
+	pub fn load(&self, secret: &str) -> Result<String, SecretLoaderError> {
+		if let Some(path) = secret.strip_prefix(KEYSTORE_PREFIX) {
+			let path = PathBuf::from(path);
+			let password = match &self.keystore_password {
+				Some(password) => Self::load_plain(password)?,
+				None => return Err(SecretLoaderError::MissingKeystorePassword(path)),
+			};
+			let private_key = eth_keystore::decrypt_key(&path, password)
+				.map_err(|error| SecretLoaderError::Keystore(path, error))?;
+			return Ok(format!("0x{}", hex::encode(private_key)));
+		}
+		Self::load_plain(secret)
+	}
+
+	fn load_plain(secret: &str) -> Result<String, SecretLoaderError> {
+		if let Some(name) = secret.strip_prefix(ENV_PREFIX) {
+			return std::env::var(name)
+				.map_err(|_| SecretLoaderError::MissingEnvVar(name.to_string()));
+		}
+		if let Some(path) = secret.strip_prefix(FILE_PREFIX) {
+			return std::fs::read_to_string(path)
+				.map(|content| content.trim().to_string())
+				.map_err(|error| SecretLoaderError::File(PathBuf::from(path), error));
+		}
+		Ok(secret.to_string())
+	}
+}
@@ -0,0 +1,603 @@
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt,
+	marker::PhantomData,
+	time::{Duration, Instant},
+};
+
+use futures::{future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
+use futures_timer::Delay;
+
+use crate::{
+	blockchain_service::{BlockchainService, ContractEvent},
+	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
+	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
+	types::{
+		Amount, BridgeTransferId, HashLock, HashLockAlgorithm, HashLockPreImage, InitiatorAddress,
+		RecipientAddress, TimeLock,
+	},
+};
+
+/// Interval the timeouts of the swaps in flight are checked at when no event is received.
+const TIMEOUT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The blockchain a swap is initiated on, and the one its assets are received on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SwapDirection {
+	B1ToB2,
+	B2ToB1,
+}
+
+impl fmt::Display for SwapDirection {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::B1ToB2 => f.write_str("B1->B2"),
+			Self::B2ToB1 => f.write_str("B2->B1"),
+		}
+	}
+}
+
+/// The step at which a swap failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SwapFailure {
+	/// The initiation of the transfer failed.
+	Initiate,
+	/// The relayer did not lock the assets on the counterparty contract in time.
+	LockTimeout,
+	/// The lock was aborted before the user completed it.
+	Aborted,
+	/// The completion of the transfer on the counterparty contract failed.
+	Complete,
+	/// The relayer did not complete the transfer on the initiator contract in time.
+	CompletionTimeout,
+	/// The transfer was refunded to the initiator.
+	Refunded,
+	/// The initiation of the transfer was removed from the chain by a reorg.
+	Reverted,
+	/// The events of a blockchain ended while the swap was in flight.
+	Interrupted,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwapSoakConfig {
+	/// Total number of swaps to run.
+	pub swaps: usize,
+	/// Number of swaps in flight at once.
+	pub concurrency: usize,
+	/// Directions of the swaps, the swap `n` uses `directions[n % directions.len()]`.
+	pub directions: Vec<SwapDirection>,
+	pub amount: Amount,
+	/// Time lock of the transfers initiated on blockchain 1, in the unit of its contracts.
+	pub time_lock_1: TimeLock,
+	/// Time lock of the transfers initiated on blockchain 2, in the unit of its contracts.
+	pub time_lock_2: TimeLock,
+	/// Time a contract call or a step of the relayer may take before the swap is failed.
+	pub step_timeout: Duration,
+}
+
+impl Default for SwapSoakConfig {
+	fn default() -> Self {
+		Self {
+			swaps: 100,
+			concurrency: 10,
+			directions: vec![SwapDirection::B1ToB2, SwapDirection::B2ToB1],
+			amount: Amount(1000),
+			time_lock_1: TimeLock(100),
+			time_lock_2: TimeLock(100),
+			step_timeout: Duration::from_secs(60),
+		}
+	}
+}
+
+/// The accounts of the user running the swaps, on both blockchains.
+#[derive(Debug, Clone)]
+pub struct SwapAccounts<A1, A2> {
+	pub initiator_1: InitiatorAddress<A1>,
+	/// Receives the assets of the swaps initiated on blockchain 2.
+	pub recipient_1: RecipientAddress,
+	pub initiator_2: InitiatorAddress<A2>,
+	/// Receives the assets of the swaps initiated on blockchain 1.
+	pub recipient_2: RecipientAddress,
+}
+
+/// Outcome of the swaps of a soak run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SwapSoakReport {
+	/// End-to-end latencies of the completed swaps, from their initiation to the completion of
+	/// the transfer on the initiator contract, sorted.
+	pub latencies: BTreeMap<SwapDirection, Vec<Duration>>,
+	pub failures: BTreeMap<SwapFailure, usize>,
+}
+
+impl SwapSoakReport {
+	pub fn completed(&self) -> usize {
+		self.latencies.values().map(Vec::len).sum()
+	}
+
+	pub fn failed(&self) -> usize {
+		self.failures.values().sum()
+	}
+
+	/// Latency of the completed swaps of `direction` at `percentile`, between 0 and 100, by
+	/// nearest rank.
+	pub fn percentile(&self, direction: SwapDirection, percentile: f64) -> Option<Duration> {
+		let latencies = self.latencies.get(&direction).filter(|latencies| !latencies.is_empty())?;
+		let rank = (percentile / 100.0 * latencies.len() as f64).ceil() as usize;
+		latencies.get(rank.clamp(1, latencies.len()) - 1).copied()
+	}
+
+	fn record_completed(&mut self, direction: SwapDirection, latency: Duration) {
+		let latencies = self.latencies.entry(direction).or_default();
+		let index = latencies.partition_point(|other| *other <= latency);
+		latencies.insert(index, latency);
+	}
+
+	fn record_failure(&mut self, failure: SwapFailure) {
+		*self.failures.entry(failure).or_default() += 1;
+	}
+}
+
+impl fmt::Display for SwapSoakReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} swaps completed, {} failed", self.completed(), self.failed())?;
+		for (direction, latencies) in &self.latencies {
+			write!(
+				f,
+				"; {direction}: {} swaps, p50 {:?}, p95 {:?}, p99 {:?}",
+				latencies.len(),
+				self.percentile(*direction, 50.0).unwrap_or_default(),
+				self.percentile(*direction, 95.0).unwrap_or_default(),
+				self.percentile(*direction, 99.0).unwrap_or_default(),
+			)?;
+		}
+		for (failure, count) in &self.failures {
+			write!(f, "; {failure:?}: {count}")?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SwapStep {
+	/// The user initiates the transfer on the initiator contract.
+	Initiating,
+	/// The relayer locks the assets on the counterparty contract.
+	WaitingLock,
+	/// The user completes the transfer on the counterparty contract, revealing the secret.
+	Completing,
+	/// The relayer completes the transfer on the initiator contract with the secret.
+	WaitingCompletion,
+}
+
+impl SwapStep {
+	fn timeout_failure(&self) -> SwapFailure {
+		match self {
+			Self::Initiating => SwapFailure::Initiate,
+			Self::WaitingLock => SwapFailure::LockTimeout,
+			Self::Completing => SwapFailure::Complete,
+			Self::WaitingCompletion => SwapFailure::CompletionTimeout,
+		}
+	}
+}
+
+/// The hash lock and the transfer id of a swap on one blockchain, the id is known once the
+/// transfer is initiated or locked on it.
+#[derive(Debug)]
+struct SwapKeys<H> {
+	hash_lock: HashLock<H>,
+	bridge_transfer_id: Option<BridgeTransferId<H>>,
+}
+
+impl<H: PartialEq> SwapKeys<H> {
+	fn new(hash_lock: HashLock<H>) -> Self {
+		Self { hash_lock, bridge_transfer_id: None }
+	}
+
+	fn has_id(&self, bridge_transfer_id: &BridgeTransferId<H>) -> bool {
+		self.bridge_transfer_id.as_ref() == Some(bridge_transfer_id)
+	}
+}
+
+#[derive(Debug)]
+struct Swap<H1, H2> {
+	direction: SwapDirection,
+	secret: HashLockPreImage,
+	keys_1: SwapKeys<H1>,
+	keys_2: SwapKeys<H2>,
+	step: SwapStep,
+	started_at: Instant,
+	step_started_at: Instant,
+}
+
+impl<H1, H2> Swap<H1, H2> {
+	fn set_step(&mut self, step: SwapStep) {
+		self.step = step;
+		self.step_started_at = Instant::now();
+	}
+}
+
+/// A contract call of a swap, with the index of the swap and the step it was made for.
+type SwapCall = BoxFuture<'static, (usize, SwapStep, Result<(), String>)>;
+
+enum Next<E1, E2> {
+	Call((usize, SwapStep, Result<(), String>)),
+	Event1(Option<E1>),
+	Event2(Option<E2>),
+	Tick,
+}
+
+/// Drives full atomic swaps through a running relayer, as a user of the bridge would, and
+/// classifies how they end.
+///
+/// The user initiates a transfer on the initiator contract of one blockchain, waits for the
+/// relayer to lock the assets on the counterparty contract of the other one, completes the lock
+/// with the secret, then waits for the relayer to complete the transfer on the initiator
+/// contract. The two [`BlockchainService`]s are the user's view of the blockchains: their
+/// contracts are called with the user's accounts and their events must include the ones of the
+/// relayer. The Ethereum and Movement clients plug in as such services, to soak a local Anvil and
+/// suzuka stack.
+pub struct SwapSoak<B1, B2, A>
+where
+	B1: BlockchainService,
+	B2: BlockchainService,
+{
+	config: SwapSoakConfig,
+	accounts: SwapAccounts<B1::Address, B2::Address>,
+	blockchain_1: B1,
+	blockchain_2: B2,
+	swaps: HashMap<usize, Swap<B1::Hash, B2::Hash>>,
+	calls: FuturesUnordered<SwapCall>,
+	report: SwapSoakReport,
+	_hash_lock_algorithm: PhantomData<A>,
+}
+
+impl<B1, B2, A> SwapSoak<B1, B2, A>
+where
+	B1: BlockchainService + 'static,
+	B2: BlockchainService + 'static,
+	A: HashLockAlgorithm,
+	A::Hash: Clone,
+	B1::Hash: From<A::Hash>,
+	B2::Hash: From<A::Hash>,
+{
+	pub fn new(
+		blockchain_1: B1,
+		blockchain_2: B2,
+		accounts: SwapAccounts<B1::Address, B2::Address>,
+		config: SwapSoakConfig,
+	) -> Self {
+		Self {
+			config,
+			accounts,
+			blockchain_1,
+			blockchain_2,
+			swaps: HashMap::new(),
+			calls: FuturesUnordered::new(),
+			report: SwapSoakReport::default(),
+			_hash_lock_algorithm: PhantomData,
+		}
+	}
+
+	/// Runs the configured number of swaps, keeping `concurrency` of them in flight, and reports
+	/// their outcome.
+	pub async fn run(mut self) -> SwapSoakReport {
+		let concurrency = self.config.concurrency.max(1);
+		let mut started = 0;
+		loop {
+			while self.swaps.len() < concurrency && started < self.config.swaps {
+				self.start_swap(started);
+				started += 1;
+			}
+			if self.swaps.is_empty() {
+				break;
+			}
+
+			let next = {
+				let mut tick = Delay::new(TIMEOUT_CHECK_INTERVAL).fuse();
+				futures::select! {
+					call = self.calls.select_next_some() => Next::Call(call),
+					event = self.blockchain_1.next().fuse() => Next::Event1(event),
+					event = self.blockchain_2.next().fuse() => Next::Event2(event),
+					_ = tick => Next::Tick,
+				}
+			};
+			match next {
+				Next::Call((index, step, result)) => self.on_call(index, step, result),
+				Next::Event1(Some(event)) => self.on_event_1(event),
+				Next::Event2(Some(event)) => self.on_event_2(event),
+				Next::Event1(None) | Next::Event2(None) => {
+					tracing::warn!(
+						"Blockchain events ended, {} swaps interrupted",
+						self.swaps.len()
+					);
+					for _ in self.swaps.drain() {
+						self.report.record_failure(SwapFailure::Interrupted);
+					}
+					break;
+				}
+				Next::Tick => {}
+			}
+			self.check_timeouts();
+		}
+		tracing::info!("Swap soak ended: {}", self.report);
+		self.report
+	}
+
+	fn start_swap(&mut self, index: usize) {
+		let direction = self
+			.config
+			.directions
+			.get(index % self.config.directions.len().max(1))
+			.copied()
+			.unwrap_or(SwapDirection::B1ToB2);
+		let (secret, hash_lock) = HashLockPreImage::generate::<A>();
+		let keys_1 = SwapKeys::new(HashLock(B1::Hash::from(hash_lock.0.clone())));
+		let keys_2 = SwapKeys::new(HashLock(B2::Hash::from(hash_lock.0)));
+		let amount = self.config.amount;
+
+		let call = match direction {
+			SwapDirection::B1ToB2 => {
+				let mut contract = self.blockchain_1.initiator_contract().clone();
+				let initiator = self.accounts.initiator_1.clone();
+				let recipient = self.accounts.recipient_2.clone();
+				let (hash_lock, time_lock) =
+					(keys_1.hash_lock.clone(), self.config.time_lock_1.clone());
+				async move {
+					contract
+						.initiate_bridge_transfer(
+							initiator, recipient, hash_lock, time_lock, amount,
+						)
+						.await
+						.map_err(|e| e.to_string())
+				}
+				.boxed()
+			}
+			SwapDirection::B2ToB1 => {
+				let mut contract = self.blockchain_2.initiator_contract().clone();
+				let initiator = self.accounts.initiator_2.clone();
+				let recipient = self.accounts.recipient_1.clone();
+				let (hash_lock, time_lock) =
+					(keys_2.hash_lock.clone(), self.config.time_lock_2.clone());
+				async move {
+					contract
+						.initiate_bridge_transfer(
+							initiator, recipient, hash_lock, time_lock, amount,
+						)
+						.await
+						.map_err(|e| e.to_string())
+				}
+				.boxed()
+			}
+		};
+		self.push_call(index, SwapStep::Initiating, call);
+
+		let now = Instant::now();
+		self.swaps.insert(
+			index,
+			Swap {
+				direction,
+				secret,
+				keys_1,
+				keys_2,
+				step: SwapStep::Initiating,
+				started_at: now,
+				step_started_at: now,
+			},
+		);
+	}
+
+	fn push_call(
+		&mut self,
+		index: usize,
+		step: SwapStep,
+		call: BoxFuture<'static, Result<(), String>>,
+	) {
+		self.calls.push(call.map(move |result| (index, step, result)).boxed());
+	}
+
+	fn on_call(&mut self, index: usize, step: SwapStep, result: Result<(), String>) {
+		let Some(swap) = self.swaps.get_mut(&index) else {
+			return;
+		};
+		match result {
+			Err(error) => {
+				tracing::warn!("Swap {index} failed at {step:?}: {error}");
+				self.fail(index, step.timeout_failure());
+			}
+			// the events of the call may be received before the call returns
+			Ok(()) if swap.step == step => {
+				let next = match step {
+					SwapStep::Initiating => SwapStep::WaitingLock,
+					SwapStep::Completing => SwapStep::WaitingCompletion,
+					step => step,
+				};
+				swap.set_step(next);
+			}
+			Ok(()) => {}
+		}
+	}
+
+	fn on_event_1(&mut self, event: ContractEvent<B1::Address, B1::Hash>) {
+		match event {
+			ContractEvent::InitiatorEvent(event) => {
+				let found = self.find(|swap| {
+					swap.direction == SwapDirection::B1ToB2
+						&& match &event {
+							BridgeContractInitiatorEvent::Initiated(details) => {
+								swap.keys_1.hash_lock == details.hash_lock
+							}
+							event => swap.keys_1.has_id(event.bridge_transfer_id()),
+						}
+				});
+				if let Some(index) = found {
+					self.on_initiator_event(index, event, |swap| &mut swap.keys_1);
+				}
+			}
+			ContractEvent::CounterpartyEvent(event) => {
+				let found = self.find(|swap| {
+					swap.direction == SwapDirection::B2ToB1
+						&& match &event {
+							BridgeContractCounterpartyEvent::Locked(details) => {
+								swap.keys_1.hash_lock == details.hash_lock
+							}
+							event => swap.keys_1.has_id(event.bridge_transfer_id()),
+						}
+				});
+				if let Some(index) = found {
+					let call = match &event {
+						BridgeContractCounterpartyEvent::Locked(details) => {
+							let mut contract = self.blockchain_1.counterparty_contract().clone();
+							let bridge_transfer_id = details.bridge_transfer_id.clone();
+							let secret = self.swaps[&index].secret.clone();
+							Some(
+								async move {
+									contract
+										.complete_bridge_transfer(bridge_transfer_id, secret)
+										.await
+										.map_err(|e| e.to_string())
+								}
+								.boxed(),
+							)
+						}
+						_ => None,
+					};
+					self.on_counterparty_event(index, event, call, |swap| &mut swap.keys_1);
+				}
+			}
+		}
+	}
+
+	fn on_event_2(&mut self, event: ContractEvent<B2::Address, B2::Hash>) {
+		match event {
+			ContractEvent::InitiatorEvent(event) => {
+				let found = self.find(|swap| {
+					swap.direction == SwapDirection::B2ToB1
+						&& match &event {
+							BridgeContractInitiatorEvent::Initiated(details) => {
+								swap.keys_2.hash_lock == details.hash_lock
+							}
+							event => swap.keys_2.has_id(event.bridge_transfer_id()),
+						}
+				});
+				if let Some(index) = found {
+					self.on_initiator_event(index, event, |swap| &mut swap.keys_2);
+				}
+			}
+			ContractEvent::CounterpartyEvent(event) => {
+				let found = self.find(|swap| {
+					swap.direction == SwapDirection::B1ToB2
+						&& match &event {
+							BridgeContractCounterpartyEvent::Locked(details) => {
+								swap.keys_2.hash_lock == details.hash_lock
+							}
+							event => swap.keys_2.has_id(event.bridge_transfer_id()),
+						}
+				});
+				if let Some(index) = found {
+					let call = match &event {
+						BridgeContractCounterpartyEvent::Locked(details) => {
+							let mut contract = self.blockchain_2.counterparty_contract().clone();
+							let bridge_transfer_id = details.bridge_transfer_id.clone();
+							let secret = self.swaps[&index].secret.clone();
+							Some(
+								async move {
+									contract
+										.complete_bridge_transfer(bridge_transfer_id, secret)
+										.await
+										.map_err(|e| e.to_string())
+								}
+								.boxed(),
+							)
+						}
+						_ => None,
+					};
+					self.on_counterparty_event(index, event, call, |swap| &mut swap.keys_2);
+				}
+			}
+		}
+	}
+
+	fn find(&self, matches: impl Fn(&Swap<B1::Hash, B2::Hash>) -> bool) -> Option<usize> {
+		self.swaps.iter().find(|(_, swap)| matches(swap)).map(|(index, _)| *index)
+	}
+
+	/// Handles an event of the initiator contract the swap was initiated on.
+	fn on_initiator_event<H, AI>(
+		&mut self,
+		index: usize,
+		event: BridgeContractInitiatorEvent<AI, H>,
+		keys: impl Fn(&mut Swap<B1::Hash, B2::Hash>) -> &mut SwapKeys<H>,
+	) {
+		let Some(swap) = self.swaps.get_mut(&index) else {
+			return;
+		};
+		match event {
+			BridgeContractInitiatorEvent::Initiated(details) => {
+				keys(swap).bridge_transfer_id = Some(details.bridge_transfer_id);
+				if swap.step == SwapStep::Initiating {
+					swap.set_step(SwapStep::WaitingLock);
+				}
+			}
+			BridgeContractInitiatorEvent::Completed(_) => {
+				let latency = swap.started_at.elapsed();
+				tracing::debug!("Swap {index} {} completed in {latency:?}", swap.direction);
+				self.report.record_completed(swap.direction, latency);
+				self.swaps.remove(&index);
+			}
+			BridgeContractInitiatorEvent::Refunded(_) => self.fail(index, SwapFailure::Refunded),
+			BridgeContractInitiatorEvent::Reverted(_) => self.fail(index, SwapFailure::Reverted),
+		}
+	}
+
+	/// Handles an event of the counterparty contract the swap is received on, `complete` is the
+	/// completion of the lock when the event is the lock.
+	fn on_counterparty_event<H>(
+		&mut self,
+		index: usize,
+		event: BridgeContractCounterpartyEvent<H>,
+		complete: Option<BoxFuture<'static, Result<(), String>>>,
+		keys: impl Fn(&mut Swap<B1::Hash, B2::Hash>) -> &mut SwapKeys<H>,
+	) {
+		let Some(swap) = self.swaps.get_mut(&index) else {
+			return;
+		};
+		match event {
+			BridgeContractCounterpartyEvent::Locked(details) => {
+				if keys(swap).bridge_transfer_id.is_some() {
+					return;
+				}
+				keys(swap).bridge_transfer_id = Some(details.bridge_transfer_id);
+				swap.set_step(SwapStep::Completing);
+				if let Some(complete) = complete {
+					self.push_call(index, SwapStep::Completing, complete);
+				}
+			}
+			BridgeContractCounterpartyEvent::Completed(_) => {
+				if swap.step == SwapStep::Completing {
+					swap.set_step(SwapStep::WaitingCompletion);
+				}
+			}
+			BridgeContractCounterpartyEvent::Aborted(_) => self.fail(index, SwapFailure::Aborted),
+		}
+	}
+
+	fn fail(&mut self, index: usize, failure: SwapFailure) {
+		if let Some(swap) = self.swaps.remove(&index) {
+			tracing::warn!("Swap {index} {} failed: {failure:?}", swap.direction);
+			self.report.record_failure(failure);
+		}
+	}
+
+	fn check_timeouts(&mut self) {
+		let timeout = self.config.step_timeout;
+		let timed_out: Vec<(usize, SwapFailure)> = self
+			.swaps
+			.iter()
+			.filter(|(_, swap)| swap.step_started_at.elapsed() > timeout)
+			.map(|(index, swap)| (*index, swap.step.timeout_failure()))
+			.collect();
+		for (index, failure) in timed_out {
+			self.fail(index, failure);
+		}
+	}
+}
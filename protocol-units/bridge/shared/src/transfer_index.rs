@@ -0,0 +1,347 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use thiserror::Error;
+
+use crate::{
+	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
+	types::{Amount, RecipientAddress, TimeLock},
+};
+
+/// Bridge event observed on either blockchain, as recorded by a [`TransferIndex`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IndexedEventKind {
+	Initiated,
+	Locked,
+	/// The recipient completed the transfer on the counterparty contract, revealing the secret.
+	SecretRevealed,
+	Aborted,
+	Completed,
+	Refunded,
+	/// The block which initiated the transfer was removed from the chain by a reorg.
+	Reverted,
+}
+
+impl IndexedEventKind {
+	pub const ALL: [IndexedEventKind; 7] = [
+		IndexedEventKind::Initiated,
+		IndexedEventKind::Locked,
+		IndexedEventKind::SecretRevealed,
+		IndexedEventKind::Aborted,
+		IndexedEventKind::Completed,
+		IndexedEventKind::Refunded,
+		IndexedEventKind::Reverted,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			IndexedEventKind::Initiated => "initiated",
+			IndexedEventKind::Locked => "locked",
+			IndexedEventKind::SecretRevealed => "secret_revealed",
+			IndexedEventKind::Aborted => "aborted",
+			IndexedEventKind::Completed => "completed",
+			IndexedEventKind::Refunded => "refunded",
+			IndexedEventKind::Reverted => "reverted",
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|kind| kind.name() == name)
+	}
+
+	fn progress(self) -> u8 {
+		match self {
+			IndexedEventKind::Initiated => 0,
+			IndexedEventKind::Locked => 1,
+			IndexedEventKind::SecretRevealed | IndexedEventKind::Aborted => 2,
+			IndexedEventKind::Completed
+			| IndexedEventKind::Refunded
+			| IndexedEventKind::Reverted => 3,
+		}
+	}
+
+	/// Whether the status of a transfer moves from `self` to `next` when `next` is observed.
+	///
+	/// Statuses only move forward, so that replayed events don't move a transfer back. A
+	/// reverted transfer is initiated again when its initiation is included in a new block.
+	pub fn advances_to(self, next: IndexedEventKind) -> bool {
+		match (self, next) {
+			(current, IndexedEventKind::Initiated) => current == IndexedEventKind::Reverted,
+			(IndexedEventKind::Reverted, _) => false,
+			(current, next) => next.progress() > current.progress(),
+		}
+	}
+}
+
+/// A bridge event written to the index, with the details of the transfer carried by the event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedEvent {
+	/// Blockchain the event was observed on, `B1` or `B2`.
+	pub chain: String,
+	pub kind: IndexedEventKind,
+	pub bridge_transfer_id: Vec<u8>,
+	/// Only known from the initiated events. The addresses are hex encoded with a `0x` prefix.
+	pub initiator_address: Option<String>,
+	pub recipient_address: Option<String>,
+	pub amount: Option<Amount>,
+	pub time_lock: Option<TimeLock>,
+	/// Block height or ledger version of the blockchain the event was observed at.
+	pub observed_at: u64,
+}
+
+impl IndexedEvent {
+	fn new(
+		chain: &str,
+		kind: IndexedEventKind,
+		bridge_transfer_id: &[u8],
+		observed_at: u64,
+	) -> Self {
+		Self {
+			chain: chain.to_string(),
+			kind,
+			bridge_transfer_id: bridge_transfer_id.to_vec(),
+			initiator_address: None,
+			recipient_address: None,
+			amount: None,
+			time_lock: None,
+			observed_at,
+		}
+	}
+
+	pub fn from_initiator_event<A: Clone + Into<RecipientAddress>, H: AsRef<[u8]>>(
+		chain: &str,
+		event: &BridgeContractInitiatorEvent<A, H>,
+		observed_at: u64,
+	) -> Self {
+		let bridge_transfer_id = event.bridge_transfer_id().0.as_ref();
+		match event {
+			BridgeContractInitiatorEvent::Initiated(details) => Self {
+				initiator_address: Some(encode_address(details.initiator_address.0.clone().into())),
+				recipient_address: Some(encode_address(details.recipient_address.clone())),
+				amount: Some(details.amount),
+				time_lock: Some(details.time_lock.clone()),
+				..Self::new(chain, IndexedEventKind::Initiated, bridge_transfer_id, observed_at)
+			},
+			BridgeContractInitiatorEvent::Completed(_) => {
+				Self::new(chain, IndexedEventKind::Completed, bridge_transfer_id, observed_at)
+			}
+			BridgeContractInitiatorEvent::Refunded(_) => {
+				Self::new(chain, IndexedEventKind::Refunded, bridge_transfer_id, observed_at)
+			}
+			BridgeContractInitiatorEvent::Reverted(_) => {
+				Self::new(chain, IndexedEventKind::Reverted, bridge_transfer_id, observed_at)
+			}
+		}
+	}
+
+	/// The revealed secret of a completed lock is not indexed.
+	pub fn from_counterparty_event<H: AsRef<[u8]>>(
+		chain: &str,
+		event: &BridgeContractCounterpartyEvent<H>,
+		observed_at: u64,
+	) -> Self {
+		let bridge_transfer_id = event.bridge_transfer_id().0.as_ref();
+		match event {
+			BridgeContractCounterpartyEvent::Locked(details) => Self {
+				recipient_address: Some(encode_address(details.recipient_address.clone())),
+				amount: Some(details.amount),
+				time_lock: Some(details.time_lock.clone()),
+				..Self::new(chain, IndexedEventKind::Locked, bridge_transfer_id, observed_at)
+			},
+			BridgeContractCounterpartyEvent::Completed(details) => Self {
+				recipient_address: Some(encode_address(details.recipient_address.clone())),
+				amount: Some(details.amount),
+				..Self::new(
+					chain,
+					IndexedEventKind::SecretRevealed,
+					bridge_transfer_id,
+					observed_at,
+				)
+			},
+			BridgeContractCounterpartyEvent::Aborted(_) => {
+				Self::new(chain, IndexedEventKind::Aborted, bridge_transfer_id, observed_at)
+			}
+		}
+	}
+}
+
+/// Hex encodes the bytes of an address with a `0x` prefix, whichever blockchain it is on.
+fn encode_address(address: RecipientAddress) -> String {
+	format!("0x{}", hex::encode(address.0))
+}
+
+/// A transfer joined from its events on both blockchains, created by its initiated event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedTransfer {
+	/// Blockchain the transfer was initiated on.
+	pub chain: String,
+	pub bridge_transfer_id: Vec<u8>,
+	pub initiator_address: String,
+	pub recipient_address: String,
+	pub amount: Amount,
+	pub time_lock: TimeLock,
+	/// Kind of the last event which moved the transfer forward.
+	pub status: IndexedEventKind,
+	pub initiated_at: u64,
+	pub updated_at: u64,
+}
+
+impl IndexedTransfer {
+	/// The transfer created by an initiated event, `None` for the other events.
+	pub fn from_initiated(event: &IndexedEvent) -> Option<Self> {
+		if event.kind != IndexedEventKind::Initiated {
+			return None;
+		}
+		Some(Self {
+			chain: event.chain.clone(),
+			bridge_transfer_id: event.bridge_transfer_id.clone(),
+			initiator_address: event.initiator_address.clone()?,
+			recipient_address: event.recipient_address.clone()?,
+			amount: event.amount?,
+			time_lock: event.time_lock.clone()?,
+			status: IndexedEventKind::Initiated,
+			initiated_at: event.observed_at,
+			updated_at: event.observed_at,
+		})
+	}
+}
+
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// Filters of the transfers looked up in the index, which returns the transfers matching all of
+/// them, most recently initiated first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferQuery {
+	/// The initiator or the recipient of the transfers, hex encoded in either case.
+	pub address: Option<String>,
+	pub status: Option<IndexedEventKind>,
+	/// Lower bound of the time the transfers were initiated at, inclusive.
+	pub from: Option<u64>,
+	/// Upper bound of the time the transfers were initiated at, inclusive.
+	pub to: Option<u64>,
+	pub limit: usize,
+}
+
+impl Default for TransferQuery {
+	fn default() -> Self {
+		Self { address: None, status: None, from: None, to: None, limit: DEFAULT_QUERY_LIMIT }
+	}
+}
+
+impl TransferQuery {
+	pub fn matches(&self, transfer: &IndexedTransfer) -> bool {
+		self.address.as_ref().map_or(true, |address| {
+			transfer.initiator_address.eq_ignore_ascii_case(address)
+				|| transfer.recipient_address.eq_ignore_ascii_case(address)
+		}) && self.status.map_or(true, |status| transfer.status == status)
+			&& self.from.map_or(true, |from| transfer.initiated_at >= from)
+			&& self.to.map_or(true, |to| transfer.initiated_at <= to)
+	}
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TransferIndexError {
+	#[error("Index storage error: {0}")]
+	Storage(String),
+}
+
+pub type TransferIndexResult<T> = Result<T, TransferIndexError>;
+
+/// Archive of every bridge event observed on both blockchains, kept after the transfers are
+/// completed or refunded, for explorer-style lookups of the transfers by support.
+///
+/// Events are deduplicated by blockchain, kind and transfer, so that the events replayed on
+/// restart are indexed once.
+#[async_trait::async_trait]
+pub trait TransferIndex: Send + Sync {
+	/// Records `event`, and moves the status of its transfer forward.
+	async fn index(&self, event: IndexedEvent) -> TransferIndexResult<()>;
+
+	async fn transfer(
+		&self,
+		bridge_transfer_id: &[u8],
+	) -> TransferIndexResult<Option<IndexedTransfer>>;
+
+	async fn transfers(&self, query: &TransferQuery) -> TransferIndexResult<Vec<IndexedTransfer>>;
+
+	/// Returns the events of a transfer on both blockchains, in the order they were observed.
+	async fn events(&self, bridge_transfer_id: &[u8]) -> TransferIndexResult<Vec<IndexedEvent>>;
+}
+
+/// Index keeping the events in memory, which are lost on restart.
+#[derive(Debug, Default)]
+pub struct InMemoryTransferIndex {
+	events: Mutex<Vec<IndexedEvent>>,
+	transfers: Mutex<HashMap<Vec<u8>, IndexedTransfer>>,
+}
+
+impl InMemoryTransferIndex {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+#[async_trait::async_trait]
+impl TransferIndex for InMemoryTransferIndex {
+	async fn index(&self, event: IndexedEvent) -> TransferIndexResult<()> {
+		let mut transfers = self.transfers.lock().expect("lock poisoned");
+		match transfers.get_mut(&event.bridge_transfer_id) {
+			Some(transfer) if transfer.status.advances_to(event.kind) => {
+				if let Some(initiated) = IndexedTransfer::from_initiated(&event) {
+					*transfer = initiated;
+				} else {
+					transfer.status = event.kind;
+					transfer.updated_at = event.observed_at;
+				}
+			}
+			Some(_) => {}
+			None => {
+				if let Some(initiated) = IndexedTransfer::from_initiated(&event) {
+					transfers.insert(event.bridge_transfer_id.clone(), initiated);
+				}
+			}
+		}
+
+		let mut events = self.events.lock().expect("lock poisoned");
+		let is_indexed = events.iter().any(|indexed| {
+			indexed.chain == event.chain
+				&& indexed.kind == event.kind
+				&& indexed.bridge_transfer_id == event.bridge_transfer_id
+		});
+		if !is_indexed {
+			events.push(event);
+		}
+		Ok(())
+	}
+
+	async fn transfer(
+		&self,
+		bridge_transfer_id: &[u8],
+	) -> TransferIndexResult<Option<IndexedTransfer>> {
+		let transfers = self.transfers.lock().expect("lock poisoned");
+		Ok(transfers.get(bridge_transfer_id).cloned())
+	}
+
+	async fn transfers(&self, query: &TransferQuery) -> TransferIndexResult<Vec<IndexedTransfer>> {
+		let transfers = self.transfers.lock().expect("lock poisoned");
+		let mut matching: Vec<_> =
+			transfers.values().filter(|transfer| query.matches(transfer)).cloned().collect();
+		matching.sort_by(|a, b| {
+			b.initiated_at
+				.cmp(&a.initiated_at)
+				.then(a.bridge_transfer_id.cmp(&b.bridge_transfer_id))
+		});
+		matching.truncate(query.limit);
+		Ok(matching)
+	}
+
+	async fn events(&self, bridge_transfer_id: &[u8]) -> TransferIndexResult<Vec<IndexedEvent>> {
+		let events = self.events.lock().expect("lock poisoned");
+		let mut transfer_events: Vec<_> = events
+			.iter()
+			.filter(|event| event.bridge_transfer_id == bridge_transfer_id)
+			.cloned()
+			.collect();
+		transfer_events.sort_by_key(|event| (event.observed_at, event.kind));
+		Ok(transfer_events)
+	}
+}
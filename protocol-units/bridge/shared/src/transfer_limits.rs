@@ -0,0 +1,119 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	hash::Hash,
+	time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::types::{Amount, InitiatorAddress};
+
+/// Caps of the value the relayer locks on the counterparty contract within a sliding window, in
+/// total and for the transfers of each initiator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferLimits {
+	pub window: Duration,
+	/// Maximum value locked within the window, unlimited when unset.
+	pub max_value: Option<u128>,
+	/// Maximum value locked for the transfers of a single initiator within the window, unlimited
+	/// when unset.
+	pub max_value_per_initiator: Option<u128>,
+}
+
+impl Default for TransferLimits {
+	fn default() -> Self {
+		Self { window: Duration::from_secs(3_600), max_value: None, max_value_per_initiator: None }
+	}
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TransferLimitError {
+	#[error("Locking {amount} exceeds the limit of {limit}, {locked} already locked")]
+	Total { amount: u128, locked: u128, limit: u128 },
+	#[error(
+		"Locking {amount} exceeds the limit of {limit} per initiator, {locked} already locked"
+	)]
+	Initiator { amount: u128, locked: u128, limit: u128 },
+}
+
+/// Accounts the value locked for the transfers of one blockchain against its [`TransferLimits`].
+///
+/// Locked values are released when they leave the window, whether or not the transfer was
+/// completed, so that a burst of transfers can't drain the counterparty liquidity.
+#[derive(Debug)]
+pub struct TransferLimiter<A> {
+	limits: TransferLimits,
+	locked: VecDeque<(Instant, A, u128)>,
+	total: u128,
+	per_initiator: HashMap<A, u128>,
+}
+
+impl<A> TransferLimiter<A>
+where
+	A: Clone + Eq + Hash,
+{
+	pub fn new(limits: TransferLimits) -> Self {
+		Self { limits, locked: VecDeque::new(), total: 0, per_initiator: HashMap::new() }
+	}
+
+	pub fn limits(&self) -> &TransferLimits {
+		&self.limits
+	}
+
+	/// Value locked within the window ending at `now`.
+	pub fn locked(&mut self, now: Instant) -> u128 {
+		self.expire(now);
+		self.total
+	}
+
+	/// Value locked for the transfers of `initiator` within the window ending at `now`.
+	pub fn locked_by(&mut self, initiator: &InitiatorAddress<A>, now: Instant) -> u128 {
+		self.expire(now);
+		self.per_initiator.get(&initiator.0).copied().unwrap_or_default()
+	}
+
+	/// Accounts `amount` locked at `now` for a transfer of `initiator`, unless it exceeds one of
+	/// the limits.
+	pub fn try_lock(
+		&mut self,
+		initiator: &InitiatorAddress<A>,
+		amount: Amount,
+		now: Instant,
+	) -> Result<(), TransferLimitError> {
+		let amount = amount.0;
+		let locked = self.locked(now);
+		if let Some(limit) = self.limits.max_value {
+			if locked.saturating_add(amount) > limit {
+				return Err(TransferLimitError::Total { amount, locked, limit });
+			}
+		}
+		let locked = self.locked_by(initiator, now);
+		if let Some(limit) = self.limits.max_value_per_initiator {
+			if locked.saturating_add(amount) > limit {
+				return Err(TransferLimitError::Initiator { amount, locked, limit });
+			}
+		}
+
+		self.total = self.total.saturating_add(amount);
+		let locked = self.per_initiator.entry(initiator.0.clone()).or_default();
+		*locked = locked.saturating_add(amount);
+		self.locked.push_back((now, initiator.0.clone(), amount));
+		Ok(())
+	}
+
+	fn expire(&mut self, now: Instant) {
+		while let Some((locked_at, _, _)) = self.locked.front() {
+			if now.saturating_duration_since(*locked_at) < self.limits.window {
+				break;
+			}
+			let Some((_, initiator, amount)) = self.locked.pop_front() else { break };
+			self.total = self.total.saturating_sub(amount);
+			if let Some(locked) = self.per_initiator.get_mut(&initiator) {
+				*locked = locked.saturating_sub(amount);
+				if *locked == 0 {
+					self.per_initiator.remove(&initiator);
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,442 @@
+use std::{
+	collections::{BTreeMap, HashMap},
+	sync::Mutex,
+};
+
+use thiserror::Error;
+
+use crate::{
+	approval::Approval,
+	attestation::Attestation,
+	types::{BridgeHashType, BridgeTransferId, TimeLock},
+};
+
+/// Progress of a transfer, as seen from the chain it was initiated on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferState {
+	Initiated,
+	/// The amount of the transfer requires the approval of the operators before it is locked.
+	PendingApproval,
+	/// The counterparty contract lacks the liquidity to lock the assets of the transfer, which is
+	/// locked once the liquidity is provided.
+	InsufficientLiquidity,
+	Locked,
+	Completed,
+	Refunded,
+}
+
+impl TransferState {
+	pub const ALL: [TransferState; 6] = [
+		TransferState::Initiated,
+		TransferState::PendingApproval,
+		TransferState::InsufficientLiquidity,
+		TransferState::Locked,
+		TransferState::Completed,
+		TransferState::Refunded,
+	];
+
+	/// Completed and refunded transfers do not change anymore.
+	pub fn is_final(self) -> bool {
+		matches!(self, TransferState::Completed | TransferState::Refunded)
+	}
+
+	pub fn can_transition_to(self, next: TransferState) -> bool {
+		use TransferState::*;
+		matches!(
+			(self, next),
+			(Initiated, PendingApproval)
+				| (Initiated, InsufficientLiquidity)
+				| (Initiated, Locked)
+				| (Initiated, Refunded)
+				| (PendingApproval, InsufficientLiquidity)
+				| (PendingApproval, Locked)
+				| (PendingApproval, Refunded)
+				| (InsufficientLiquidity, Locked)
+				| (InsufficientLiquidity, Refunded)
+				| (Locked, Completed)
+				| (Locked, Refunded)
+		)
+	}
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("Invalid transfer state transition from {from:?} to {to:?}")]
+pub struct InvalidTransition {
+	pub from: TransferState,
+	pub to: TransferState,
+}
+
+/// State of a transfer which only moves along the transitions of the protocol, so that a
+/// transfer is never locked again once completed or refunded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransferStateMachine {
+	state: TransferState,
+}
+
+impl Default for TransferStateMachine {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl From<TransferState> for TransferStateMachine {
+	fn from(state: TransferState) -> Self {
+		Self { state }
+	}
+}
+
+impl TransferStateMachine {
+	/// Machine of a transfer just initiated.
+	pub fn new() -> Self {
+		Self { state: TransferState::Initiated }
+	}
+
+	pub fn state(&self) -> TransferState {
+		self.state
+	}
+
+	pub fn is_final(&self) -> bool {
+		self.state.is_final()
+	}
+
+	/// Moves the transfer to `next`, unless the transition is invalid, which leaves it unchanged.
+	pub fn transition(&mut self, next: TransferState) -> Result<(), InvalidTransition> {
+		if !self.state.can_transition_to(next) {
+			return Err(InvalidTransition { from: self.state, to: next });
+		}
+		self.state = next;
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferRecord {
+	pub state: TransferState,
+	/// Expiry of the time lock of the initiator, in the time of the initiator blockchain.
+	pub initiator_time_lock: TimeLock,
+	/// Expiry of the counterparty lock, in the time of the counterparty blockchain.
+	pub counterparty_time_lock: Option<TimeLock>,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TransferStateStoreError {
+	#[error("Transfer not found")]
+	TransferNotFound,
+	#[error("Transfer already stored")]
+	AlreadyStored,
+	#[error("Invalid transfer state transition from {from:?} to {to:?}")]
+	InvalidTransition { from: TransferState, to: TransferState },
+	#[error("Storage error: {0}")]
+	Storage(String),
+}
+
+impl From<InvalidTransition> for TransferStateStoreError {
+	fn from(InvalidTransition { from, to }: InvalidTransition) -> Self {
+		TransferStateStoreError::InvalidTransition { from, to }
+	}
+}
+
+pub type TransferStateStoreResult<T> = Result<T, TransferStateStoreError>;
+
+/// Persists the state of the transfers initiated on one blockchain, so that in-flight swaps
+/// survive a restart of the relayer.
+///
+/// The store also keeps the checkpoint of the event monitoring of the blockchain, the last block
+/// height or ledger version whose events were processed, from which events are replayed on restart.
+#[async_trait::async_trait]
+pub trait TransferStateStore: Send + Sync {
+	type Hash: BridgeHashType;
+
+	async fn get(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<Self::Hash>,
+	) -> TransferStateStoreResult<Option<TransferRecord>>;
+
+	/// Writes the record of a transfer without validating the state transition.
+	async fn put(
+		&self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		record: TransferRecord,
+	) -> TransferStateStoreResult<()>;
+
+	/// Deletes the record of a transfer, when the transfer no longer exists on chain.
+	async fn remove(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<Self::Hash>,
+	) -> TransferStateStoreResult<()>;
+
+	/// Returns the transfers which are neither completed nor refunded.
+	async fn in_flight(
+		&self,
+	) -> TransferStateStoreResult<Vec<(BridgeTransferId<Self::Hash>, TransferRecord)>>;
+
+	async fn checkpoint(&self) -> TransferStateStoreResult<Option<u64>>;
+
+	async fn set_checkpoint(&self, height: u64) -> TransferStateStoreResult<()>;
+
+	/// Keeps the secret of a transfer until it is revealed, sealed by a
+	/// [`PreImageVault`](crate::pre_image::PreImageVault).
+	async fn put_secret(
+		&self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		sealed_secret: Vec<u8>,
+	) -> TransferStateStoreResult<()>;
+
+	async fn secret(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<Self::Hash>,
+	) -> TransferStateStoreResult<Option<Vec<u8>>>;
+
+	async fn remove_secret(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<Self::Hash>,
+	) -> TransferStateStoreResult<()>;
+
+	/// Keeps an [`Attestation`] of a transition of a transfer, which is kept after the transfer
+	/// is completed or refunded.
+	async fn put_attestation(
+		&self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		attestation: Attestation,
+	) -> TransferStateStoreResult<()>;
+
+	/// Returns the attestations of a transfer, in the order they were stored.
+	async fn attestations(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<Self::Hash>,
+	) -> TransferStateStoreResult<Vec<Attestation>>;
+
+	/// Keeps the [`Approval`] of a transfer pending approval, replacing an earlier approval by
+	/// the same approver, so that the approvals survive a restart of the relayer.
+	async fn put_approval(
+		&self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		approval: Approval,
+	) -> TransferStateStoreResult<()>;
+
+	/// Returns the approvals of a transfer, one per approver.
+	async fn approvals(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<Self::Hash>,
+	) -> TransferStateStoreResult<Vec<Approval>>;
+
+	/// Checks that the store can be written to, for the readiness of the relayer.
+	async fn check_writable(&self) -> TransferStateStoreResult<()> {
+		Ok(())
+	}
+
+	/// Writes the state kept in memory to durable storage, before the relayer exits.
+	async fn flush(&self) -> TransferStateStoreResult<()> {
+		Ok(())
+	}
+
+	async fn initiate(
+		&self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		expiry: TimeLock,
+	) -> TransferStateStoreResult<()> {
+		if self.get(&bridge_transfer_id).await?.is_some() {
+			return Err(TransferStateStoreError::AlreadyStored);
+		}
+		let record = TransferRecord {
+			state: TransferState::Initiated,
+			initiator_time_lock: expiry,
+			counterparty_time_lock: None,
+		};
+		self.put(bridge_transfer_id, record).await
+	}
+
+	async fn lock(
+		&self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		expiry: TimeLock,
+	) -> TransferStateStoreResult<TransferRecord> {
+		let mut record =
+			self.validate_transition(&bridge_transfer_id, TransferState::Locked).await?;
+		record.counterparty_time_lock = Some(expiry);
+		self.put(bridge_transfer_id, record.clone()).await?;
+		Ok(record)
+	}
+
+	async fn transition(
+		&self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		state: TransferState,
+	) -> TransferStateStoreResult<TransferRecord> {
+		let record = self.validate_transition(&bridge_transfer_id, state).await?;
+		self.put(bridge_transfer_id, record.clone()).await?;
+		Ok(record)
+	}
+
+	/// Returns the record of the transfer moved to `state`, without writing it.
+	async fn validate_transition(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<Self::Hash>,
+		state: TransferState,
+	) -> TransferStateStoreResult<TransferRecord> {
+		let mut record = self
+			.get(bridge_transfer_id)
+			.await?
+			.ok_or(TransferStateStoreError::TransferNotFound)?;
+		let mut machine = TransferStateMachine::from(record.state);
+		machine.transition(state)?;
+		record.state = machine.state();
+		Ok(record)
+	}
+}
+
+/// Approvals of a transfer, by public key of their approver.
+type Approvals = BTreeMap<[u8; 32], Approval>;
+
+/// Store keeping the transfers in memory, which are lost on restart.
+#[derive(Debug)]
+pub struct InMemoryTransferStateStore<H> {
+	transfers: Mutex<HashMap<BridgeTransferId<H>, TransferRecord>>,
+	secrets: Mutex<HashMap<BridgeTransferId<H>, Vec<u8>>>,
+	attestations: Mutex<HashMap<BridgeTransferId<H>, Vec<Attestation>>>,
+	approvals: Mutex<HashMap<BridgeTransferId<H>, Approvals>>,
+	checkpoint: Mutex<Option<u64>>,
+}
+
+impl<H> Default for InMemoryTransferStateStore<H> {
+	fn default() -> Self {
+		Self {
+			transfers: Mutex::new(HashMap::new()),
+			secrets: Mutex::new(HashMap::new()),
+			attestations: Mutex::new(HashMap::new()),
+			approvals: Mutex::new(HashMap::new()),
+			checkpoint: Mutex::new(None),
+		}
+	}
+}
+
+impl<H> InMemoryTransferStateStore<H> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+#[async_trait::async_trait]
+impl<H> TransferStateStore for InMemoryTransferStateStore<H>
+where
+	H: BridgeHashType,
+{
+	type Hash = H;
+
+	async fn get(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<Option<TransferRecord>> {
+		let transfers = self.transfers.lock().expect("lock poisoned");
+		Ok(transfers.get(bridge_transfer_id).cloned())
+	}
+
+	async fn put(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H>,
+		record: TransferRecord,
+	) -> TransferStateStoreResult<()> {
+		let mut transfers = self.transfers.lock().expect("lock poisoned");
+		transfers.insert(bridge_transfer_id, record);
+		Ok(())
+	}
+
+	async fn remove(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<()> {
+		let mut transfers = self.transfers.lock().expect("lock poisoned");
+		transfers.remove(bridge_transfer_id);
+		Ok(())
+	}
+
+	async fn in_flight(
+		&self,
+	) -> TransferStateStoreResult<Vec<(BridgeTransferId<H>, TransferRecord)>> {
+		let transfers = self.transfers.lock().expect("lock poisoned");
+		Ok(transfers
+			.iter()
+			.filter(|(_, record)| !record.state.is_final())
+			.map(|(bridge_transfer_id, record)| (bridge_transfer_id.clone(), record.clone()))
+			.collect())
+	}
+
+	async fn checkpoint(&self) -> TransferStateStoreResult<Option<u64>> {
+		Ok(*self.checkpoint.lock().expect("lock poisoned"))
+	}
+
+	async fn set_checkpoint(&self, height: u64) -> TransferStateStoreResult<()> {
+		let mut checkpoint = self.checkpoint.lock().expect("lock poisoned");
+		*checkpoint = Some(height);
+		Ok(())
+	}
+
+	async fn put_secret(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H>,
+		sealed_secret: Vec<u8>,
+	) -> TransferStateStoreResult<()> {
+		let mut secrets = self.secrets.lock().expect("lock poisoned");
+		secrets.insert(bridge_transfer_id, sealed_secret);
+		Ok(())
+	}
+
+	async fn secret(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<Option<Vec<u8>>> {
+		let secrets = self.secrets.lock().expect("lock poisoned");
+		Ok(secrets.get(bridge_transfer_id).cloned())
+	}
+
+	async fn remove_secret(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<()> {
+		let mut secrets = self.secrets.lock().expect("lock poisoned");
+		secrets.remove(bridge_transfer_id);
+		Ok(())
+	}
+
+	async fn put_attestation(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H>,
+		attestation: Attestation,
+	) -> TransferStateStoreResult<()> {
+		let mut attestations = self.attestations.lock().expect("lock poisoned");
+		attestations.entry(bridge_transfer_id).or_default().push(attestation);
+		Ok(())
+	}
+
+	async fn attestations(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<Vec<Attestation>> {
+		let attestations = self.attestations.lock().expect("lock poisoned");
+		Ok(attestations.get(bridge_transfer_id).cloned().unwrap_or_default())
+	}
+
+	async fn put_approval(
+		&self,
+		bridge_transfer_id: BridgeTransferId<H>,
+		approval: Approval,
+	) -> TransferStateStoreResult<()> {
+		let mut approvals = self.approvals.lock().expect("lock poisoned");
+		approvals
+			.entry(bridge_transfer_id)
+			.or_default()
+			.insert(approval.public_key, approval);
+		Ok(())
+	}
+
+	async fn approvals(
+		&self,
+		bridge_transfer_id: &BridgeTransferId<H>,
+	) -> TransferStateStoreResult<Vec<Approval>> {
+		let approvals = self.approvals.lock().expect("lock poisoned");
+		Ok(approvals
+			.get(bridge_transfer_id)
+			.map(|approvals| approvals.values().cloned().collect())
+			.unwrap_or_default())
+	}
+}
@@ -0,0 +1,64 @@
+use crate::{
+	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
+	transfer_store::TransferRecord,
+	types::{BridgeTransferDetails, BridgeTransferId, LockDetails},
+};
+
+/// Result of the query of a transfer on a contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OnChain<T> {
+	Found(T),
+	NotFound,
+	/// The contract could not be queried.
+	Error(String),
+}
+
+impl<T> OnChain<T> {
+	pub fn from_result(result: Result<Option<T>, impl ToString>) -> Self {
+		match result {
+			Ok(Some(value)) => Self::Found(value),
+			Ok(None) => Self::NotFound,
+			Err(error) => Self::Error(error.to_string()),
+		}
+	}
+}
+
+/// A transfer tracked by the relayer, merged with its state on the initiator contract of the
+/// blockchain it was initiated on and on the counterparty contract of the other one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferView<A, H1, H2> {
+	pub bridge_transfer_id: BridgeTransferId<H1>,
+	pub record: TransferRecord,
+	pub initiator: OnChain<BridgeTransferDetails<A, H1>>,
+	pub counterparty: OnChain<LockDetails<H2>>,
+}
+
+impl<A, H1, H2> TransferView<A, H1, H2>
+where
+	H1: Clone,
+	H2: From<H1>,
+{
+	/// Queries both contracts for the transfer, concurrently.
+	pub async fn query<I, C>(
+		bridge_transfer_id: BridgeTransferId<H1>,
+		record: TransferRecord,
+		initiator: &mut I,
+		counterparty: &mut C,
+	) -> Self
+	where
+		I: BridgeContractInitiator<Address = A, Hash = H1>,
+		C: BridgeContractCounterparty<Hash = H2>,
+	{
+		let counterparty_id = BridgeTransferId(H2::from(bridge_transfer_id.0.clone()));
+		let (initiator, counterparty) = futures::join!(
+			initiator.get_bridge_transfer_details(bridge_transfer_id.clone()),
+			counterparty.get_bridge_transfer_details(counterparty_id),
+		);
+		Self {
+			bridge_transfer_id,
+			record,
+			initiator: OnChain::from_result(initiator),
+			counterparty: OnChain::from_result(counterparty),
+		}
+	}
+}
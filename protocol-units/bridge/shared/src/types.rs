@@ -1,7 +1,15 @@
-use std::{fmt::Debug, hash::Hash};
+use std::{
+	fmt::{self, Debug},
+	hash::Hash,
+	str::FromStr,
+	sync::Arc,
+};
 
 use derive_more::{Deref, DerefMut};
-use rand::Rng;
+use rand::{Rng, RngCore};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+use zeroize::Zeroize;
 
 #[derive(Deref, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BridgeTransferId<H>(pub H);
@@ -15,6 +23,14 @@ where
 	}
 }
 
+impl<H: Debug> BridgeTransferId<H> {
+	/// Span of the handling of the transfer, whose `bridge_transfer_id` field follows the transfer
+	/// from its initiation to its completion across both blockchains.
+	pub fn span(&self) -> tracing::Span {
+		tracing::info_span!("bridge_transfer", bridge_transfer_id = ?self.0)
+	}
+}
+
 impl<H> From<H> for BridgeTransferId<H> {
 	fn from(hash: H) -> Self {
 		BridgeTransferId(hash)
@@ -48,6 +64,225 @@ impl From<&str> for RecipientAddress {
 	}
 }
 
+/// Failed conversion of the bytes of a value read from the config or an RPC response.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+	#[error("Invalid length {actual} of {name}, expected {expected} bytes")]
+	InvalidLength { name: &'static str, expected: usize, actual: usize },
+	#[error("{name} is not hex: {error}")]
+	InvalidHex { name: &'static str, error: String },
+}
+
+/// Converts the bytes of the value `name` to an array, which they must fill exactly.
+pub fn vec_to_array<const N: usize>(
+	name: &'static str,
+	bytes: &[u8],
+) -> Result<[u8; N], ConversionError> {
+	bytes.try_into().map_err(|_| ConversionError::InvalidLength {
+		name,
+		expected: N,
+		actual: bytes.len(),
+	})
+}
+
+/// Decodes the value `name`, hex encoded with or without a `0x` prefix, to an array.
+pub fn hex_to_array<const N: usize>(
+	name: &'static str,
+	value: &str,
+) -> Result<[u8; N], ConversionError> {
+	let bytes = hex::decode(value.trim_start_matches("0x"))
+		.map_err(|error| ConversionError::InvalidHex { name, error: error.to_string() })?;
+	vec_to_array(name, &bytes)
+}
+
+impl RecipientAddress {
+	/// The recipient as an address of `N` bytes, of the chain it is sent to.
+	pub fn to_array<const N: usize>(&self) -> Result<[u8; N], ConversionError> {
+		vec_to_array("recipient address", &self.0)
+	}
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AddressError {
+	#[error("Invalid address length {actual}, expected {expected} bytes")]
+	InvalidLength { expected: usize, actual: usize },
+	#[error("Address {0:?} is not an address of the {1} chain")]
+	WrongChain(BridgeAddress, &'static str),
+}
+
+/// Address of an Ethereum account or contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EthAddress(pub [u8; 20]);
+
+/// Address of a Movement account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MovementAddress(pub [u8; 32]);
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AddressParseError {
+	#[error("Invalid hex address: {0}")]
+	InvalidHex(String),
+	#[error("Address must not be the zero address")]
+	ZeroAddress,
+	#[error("Invalid EIP-55 checksum, expected {0}")]
+	InvalidChecksum(String),
+}
+
+impl EthAddress {
+	/// The address hex encoded with the EIP-55 mixed case checksum, with a `0x` prefix.
+	pub fn to_checksum(&self) -> String {
+		use tiny_keccak::{Hasher, Keccak};
+
+		let hex_address = hex::encode(self.0);
+		let mut hasher = Keccak::v256();
+		hasher.update(hex_address.as_bytes());
+		let mut hash = [0u8; 32];
+		hasher.finalize(&mut hash);
+
+		// A letter is upper case when the matching nibble of the hash of the address is >= 8
+		let checksummed: String = hex_address
+			.chars()
+			.enumerate()
+			.map(|(i, c)| {
+				let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0x0f };
+				if nibble >= 8 {
+					c.to_ascii_uppercase()
+				} else {
+					c
+				}
+			})
+			.collect();
+		format!("0x{checksummed}")
+	}
+}
+
+impl fmt::Display for EthAddress {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.to_checksum())
+	}
+}
+
+impl FromStr for EthAddress {
+	type Err = AddressParseError;
+
+	/// Parses a hex encoded address, with or without a `0x` prefix. Mixed case addresses must
+	/// match their EIP-55 checksum, all lower or upper case addresses carry no checksum.
+	fn from_str(address: &str) -> Result<Self, Self::Err> {
+		let hex_address = address.strip_prefix("0x").unwrap_or(address);
+		let mut bytes = [0u8; 20];
+		hex::decode_to_slice(hex_address, &mut bytes)
+			.map_err(|e| AddressParseError::InvalidHex(e.to_string()))?;
+		if bytes == [0u8; 20] {
+			return Err(AddressParseError::ZeroAddress);
+		}
+
+		let address = EthAddress(bytes);
+		let is_mixed_case = hex_address.chars().any(|c| c.is_ascii_lowercase())
+			&& hex_address.chars().any(|c| c.is_ascii_uppercase());
+		let checksum = address.to_checksum();
+		if is_mixed_case && checksum[2..] != *hex_address {
+			return Err(AddressParseError::InvalidChecksum(checksum));
+		}
+		Ok(address)
+	}
+}
+
+impl Serialize for EthAddress {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_checksum())
+	}
+}
+
+impl<'de> Deserialize<'de> for EthAddress {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+	}
+}
+
+impl TryFrom<&[u8]> for EthAddress {
+	type Error = AddressError;
+
+	fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+		bytes
+			.try_into()
+			.map(EthAddress)
+			.map_err(|_| AddressError::InvalidLength { expected: 20, actual: bytes.len() })
+	}
+}
+
+impl TryFrom<&[u8]> for MovementAddress {
+	type Error = AddressError;
+
+	fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+		bytes
+			.try_into()
+			.map(MovementAddress)
+			.map_err(|_| AddressError::InvalidLength { expected: 32, actual: bytes.len() })
+	}
+}
+
+/// Address on either side of the bridge, with checked conversions to the address type of each
+/// chain. Addresses read from a contract without knowing their chain are kept `Raw`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BridgeAddress {
+	Eth(EthAddress),
+	Movement(MovementAddress),
+	Raw(Vec<u8>),
+}
+
+impl BridgeAddress {
+	pub fn as_bytes(&self) -> &[u8] {
+		match self {
+			BridgeAddress::Eth(address) => &address.0,
+			BridgeAddress::Movement(address) => &address.0,
+			BridgeAddress::Raw(bytes) => bytes,
+		}
+	}
+
+	pub fn to_eth(&self) -> Result<EthAddress, AddressError> {
+		match self {
+			BridgeAddress::Eth(address) => Ok(*address),
+			BridgeAddress::Raw(bytes) => EthAddress::try_from(bytes.as_slice()),
+			BridgeAddress::Movement(_) => Err(AddressError::WrongChain(self.clone(), "Ethereum")),
+		}
+	}
+
+	pub fn to_movement(&self) -> Result<MovementAddress, AddressError> {
+		match self {
+			BridgeAddress::Movement(address) => Ok(*address),
+			BridgeAddress::Raw(bytes) => MovementAddress::try_from(bytes.as_slice()),
+			BridgeAddress::Eth(_) => Err(AddressError::WrongChain(self.clone(), "Movement")),
+		}
+	}
+}
+
+impl From<EthAddress> for BridgeAddress {
+	fn from(address: EthAddress) -> Self {
+		BridgeAddress::Eth(address)
+	}
+}
+
+impl From<MovementAddress> for BridgeAddress {
+	fn from(address: MovementAddress) -> Self {
+		BridgeAddress::Movement(address)
+	}
+}
+
+impl From<RecipientAddress> for BridgeAddress {
+	fn from(recipient: RecipientAddress) -> Self {
+		BridgeAddress::Raw(recipient.0)
+	}
+}
+
+impl From<BridgeAddress> for RecipientAddress {
+	fn from(address: BridgeAddress) -> Self {
+		match address {
+			BridgeAddress::Raw(bytes) => RecipientAddress(bytes),
+			address => RecipientAddress(address.as_bytes().to_vec()),
+		}
+	}
+}
+
 #[derive(Deref, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct HashLock<H>(pub H);
 
@@ -55,14 +290,346 @@ pub fn convert_hash_lock<H: From<O>, O>(other: HashLock<O>) -> HashLock<H> {
 	HashLock(From::from(other.0))
 }
 
-#[derive(Deref, Debug, Clone, PartialEq, Eq)]
+/// Secret of a transfer, whose hash is the [`HashLock`]. The secret is zeroized when dropped.
+#[derive(Deref, Clone, PartialEq, Eq)]
 pub struct HashLockPreImage(pub Vec<u8>);
 
+/// The secret is redacted so that it is not leaked in the logs.
+impl Debug for HashLockPreImage {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("HashLockPreImage(<redacted>)")
+	}
+}
+
+impl HashLockPreImage {
+	/// Length of the generated secrets, the length of the hash locks.
+	pub const LENGTH: usize = 32;
+
+	/// Generates a secret from the random number generator of the operating system.
+	pub fn random() -> Self {
+		let mut secret = vec![0u8; Self::LENGTH];
+		rand::rngs::OsRng.fill_bytes(&mut secret);
+		HashLockPreImage(secret)
+	}
+
+	/// Generates a secret and its hash lock with the algorithm of the contract it is locked on.
+	pub fn generate<A: HashLockAlgorithm>() -> (Self, HashLock<A::Hash>) {
+		let pre_image = Self::random();
+		let hash_lock = A::hash_lock(&pre_image);
+		(pre_image, hash_lock)
+	}
+}
+
+impl Drop for HashLockPreImage {
+	fn drop(&mut self) {
+		self.0.zeroize();
+	}
+}
+
+/// Hash function used by a bridge contract to derive a [`HashLock`] from its [`HashLockPreImage`].
+pub trait HashLockAlgorithm {
+	type Hash: PartialEq;
+
+	fn hash_lock(pre_image: &HashLockPreImage) -> HashLock<Self::Hash>;
+
+	/// Returns true if the `pre_image` hashes to the given `hash_lock`.
+	fn verify(pre_image: &HashLockPreImage, hash_lock: &HashLock<Self::Hash>) -> bool {
+		Self::hash_lock(pre_image) == *hash_lock
+	}
+}
+
+/// Keccak-256, as used by the Ethereum and Movement atomic bridge contracts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keccak256;
+
+impl HashLockAlgorithm for Keccak256 {
+	type Hash = [u8; 32];
+
+	fn hash_lock(pre_image: &HashLockPreImage) -> HashLock<Self::Hash> {
+		use tiny_keccak::{Hasher, Keccak};
+
+		let mut hasher = Keccak::v256();
+		hasher.update(&pre_image.0);
+		let mut hash = [0u8; 32];
+		hasher.finalize(&mut hash);
+		HashLock(hash)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sha256;
+
+impl HashLockAlgorithm for Sha256 {
+	type Hash = [u8; 32];
+
+	fn hash_lock(pre_image: &HashLockPreImage) -> HashLock<Self::Hash> {
+		use sha2::Digest;
+
+		HashLock(sha2::Sha256::digest(&pre_image.0).into())
+	}
+}
+
 #[derive(Deref, Debug, Clone, PartialEq, Eq)]
 pub struct TimeLock(pub u64);
 
+/// Number of decimals of the amounts of Ether and ERC-20 tokens on Ethereum.
+pub const ETH_DECIMALS: u8 = 18;
+/// Number of decimals of the amounts of fungible assets on Movement.
+pub const MOVEMENT_DECIMALS: u8 = 8;
+
+/// Amount of a transfer, in the smallest unit of the asset on its chain.
+///
+/// The amount is 128 bits wide, so that large transfers of 18 decimal assets are not truncated.
+/// The contracts hold `uint256` amounts, but 128 bits already count over 10^20 whole tokens of
+/// 18 decimals, beyond the supply of any bridged asset. A contract amount above that bound is
+/// rejected with [`AmountError::ExceedsU128`] when decoded, never truncated.
 #[derive(Deref, DerefMut, Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Amount(pub u64);
+pub struct Amount(pub u128);
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AmountError {
+	#[error("Amount {0} overflows when scaled from {1} to {2} decimals")]
+	Overflow(u128, u8, u8),
+	#[error("Amount {0} loses precision when scaled from {1} to {2} decimals")]
+	PrecisionLoss(u128, u8, u8),
+	#[error("Amount {0} does not fit in 64 bits")]
+	TooLarge(u128),
+	#[error("Contract amount 0x{} does not fit in 128 bits", hex::encode(.0))]
+	ExceedsU128([u8; 32]),
+}
+
+impl Amount {
+	/// Converts the amount from an asset with `from_decimals` decimals to the same value of an
+	/// asset with `to_decimals` decimals, e.g. from Ethereum to Movement.
+	///
+	/// Fails instead of rounding when the amount has a non-zero part below the precision of the
+	/// target asset.
+	pub fn scale(self, from_decimals: u8, to_decimals: u8) -> Result<Amount, AmountError> {
+		let overflow = || AmountError::Overflow(self.0, from_decimals, to_decimals);
+		if to_decimals >= from_decimals {
+			let factor = 10u128
+				.checked_pow(u32::from(to_decimals - from_decimals))
+				.ok_or_else(overflow)?;
+			self.0.checked_mul(factor).map(Amount).ok_or_else(overflow)
+		} else {
+			let factor = 10u128
+				.checked_pow(u32::from(from_decimals - to_decimals))
+				.ok_or_else(overflow)?;
+			if self.0 % factor != 0 {
+				return Err(AmountError::PrecisionLoss(self.0, from_decimals, to_decimals));
+			}
+			Ok(Amount(self.0 / factor))
+		}
+	}
+
+	/// Decodes a `uint256` amount of a contract, from its big endian ABI encoding.
+	pub fn try_from_uint256(word: [u8; 32]) -> Result<Amount, AmountError> {
+		let (high, low) = word.split_at(16);
+		if high.iter().any(|byte| *byte != 0) {
+			return Err(AmountError::ExceedsU128(word));
+		}
+		let low: [u8; 16] = low.try_into().expect("half of a 32 byte word");
+		Ok(Amount(u128::from_be_bytes(low)))
+	}
+
+	/// Encodes the amount as a `uint256` of a contract, big endian as in the ABI.
+	pub fn to_uint256(self) -> [u8; 32] {
+		let mut word = [0u8; 32];
+		word[16..].copy_from_slice(&self.0.to_be_bytes());
+		word
+	}
+}
+
+impl From<u64> for Amount {
+	fn from(amount: u64) -> Self {
+		Amount(amount.into())
+	}
+}
+
+/// Move coins and fungible assets have 64-bit amounts.
+impl TryFrom<Amount> for u64 {
+	type Error = AmountError;
+
+	fn try_from(amount: Amount) -> Result<Self, Self::Error> {
+		u64::try_from(amount.0).map_err(|_| AmountError::TooLarge(amount.0))
+	}
+}
+
+/// Relayer fee deducted from the amount of a transfer: a flat amount plus basis points of the
+/// transferred amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Fee {
+	pub flat: u64,
+	pub basis_points: u64,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum FeeError {
+	#[error("Fee {fee} exceeds the transferred amount {amount}")]
+	ExceedsAmount { fee: u128, amount: u128 },
+	#[error("Fee of the transferred amount {amount} overflows")]
+	Overflow { amount: u128 },
+}
+
+impl Fee {
+	pub const MAX_BASIS_POINTS: u64 = 10_000;
+
+	/// Splits `amount` into the amount received by the recipient and the fee.
+	pub fn deduct(&self, amount: Amount) -> Result<(Amount, Amount), FeeError> {
+		let basis_points = u128::from(self.basis_points);
+		let max_basis_points = u128::from(Self::MAX_BASIS_POINTS);
+		// Splitting the amount at the maximum basis points keeps the products in range, and rounds
+		// down like the fee of the whole amount.
+		let fee = (amount.0 / max_basis_points)
+			.checked_mul(basis_points)
+			.and_then(|fee| {
+				fee.checked_add(amount.0 % max_basis_points * basis_points / max_basis_points)
+			})
+			.and_then(|fee| fee.checked_add(u128::from(self.flat)))
+			.ok_or(FeeError::Overflow { amount: amount.0 })?;
+		if fee > amount.0 {
+			return Err(FeeError::ExceedsAmount { fee, amount: amount.0 });
+		}
+		Ok((Amount(amount.0 - fee), Amount(fee)))
+	}
+}
+
+/// Unit of the time locks of a blockchain, in seconds.
+///
+/// The time locks of the contracts are relative to the time the transfer is initiated or locked
+/// at: in blocks on Ethereum, whose unit is the block time, and in seconds on Movement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLockUnit(pub u64);
+
+impl TimeLockUnit {
+	pub const SECONDS: TimeLockUnit = TimeLockUnit(1);
+
+	/// Duration of the time lock, in seconds.
+	pub fn to_secs(&self, time_lock: &TimeLock) -> u64 {
+		time_lock.0.saturating_mul(self.0)
+	}
+
+	/// Time lock of at most `secs`, rounded down to the unit.
+	pub fn from_secs(&self, secs: u64) -> TimeLock {
+		TimeLock(secs / self.0.max(1))
+	}
+}
+
+impl Default for TimeLockUnit {
+	fn default() -> Self {
+		Self::SECONDS
+	}
+}
+
+/// Returns the current time of a blockchain, in the unit of its time locks: the block height on
+/// Ethereum, the timestamp in seconds on Movement.
+pub type ChainClock = Arc<dyn Fn() -> u64 + Send + Sync>;
+
+/// Bounds of the relative time locks of a transfer, in seconds.
+///
+/// The counterparty lock lasts `counterparty_margin` less than the initiator time lock, so that
+/// the relayer has time to complete the transfer on the initiator contract once the recipient
+/// revealed the secret, before the initiator can get refunded. The margin covers the time
+/// between the initiation and the lock of the transfer too, as both time locks start when their
+/// transaction is executed. Assets locked later, such as once a transfer is approved, are locked
+/// for what is left of the initiator time lock less the margin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLockPolicy {
+	/// Minimum time left to the recipient to complete the transfer once the assets are locked.
+	pub min_duration: u64,
+	/// Maximum time the assets of the initiator stay locked.
+	pub max_duration: u64,
+	/// Difference between the durations of the initiator time lock and of the counterparty lock.
+	pub counterparty_margin: u64,
+}
+
+impl Default for TimeLockPolicy {
+	fn default() -> Self {
+		Self { min_duration: 0, max_duration: u64::MAX, counterparty_margin: 0 }
+	}
+}
+
+/// Time locks rejected by a [`TimeLockPolicy`], whose durations are in seconds.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TimeLockPolicyError {
+	#[error("Time lock of {duration}s leaves less than {min_duration}s to complete the transfer")]
+	TooShort { duration: u64, min_duration: u64 },
+	#[error("Time lock of {duration}s exceeds the maximum duration of {max_duration}s")]
+	TooLong { duration: u64, max_duration: u64 },
+	#[error(
+		"Counterparty lock of {counterparty}s is not {margin}s shorter than the {initiator}s lock"
+	)]
+	InsufficientMargin { initiator: u64, counterparty: u64, margin: u64 },
+}
+
+impl TimeLockPolicy {
+	/// Time lock, in `initiator_unit`, of a transfer locking the assets of the initiator for
+	/// `duration` seconds, whose counterparty lock is in `counterparty_unit`.
+	pub fn initiator_time_lock(
+		&self,
+		duration: u64,
+		initiator_unit: TimeLockUnit,
+		counterparty_unit: TimeLockUnit,
+	) -> Result<TimeLock, TimeLockPolicyError> {
+		let time_lock = initiator_unit.from_secs(duration);
+		self.counterparty_time_lock(&time_lock, initiator_unit, counterparty_unit)?;
+		Ok(time_lock)
+	}
+
+	/// Counterparty lock, in `counterparty_unit`, of a transfer initiated with
+	/// `initiator_time_lock`, in `initiator_unit`, which lasts `counterparty_margin` less.
+	pub fn counterparty_time_lock(
+		&self,
+		initiator_time_lock: &TimeLock,
+		initiator_unit: TimeLockUnit,
+		counterparty_unit: TimeLockUnit,
+	) -> Result<TimeLock, TimeLockPolicyError> {
+		let duration = initiator_unit.to_secs(initiator_time_lock);
+		if duration > self.max_duration {
+			return Err(TimeLockPolicyError::TooLong { duration, max_duration: self.max_duration });
+		}
+		let counterparty =
+			counterparty_unit.from_secs(duration.saturating_sub(self.counterparty_margin));
+		self.check_counterparty_time_lock(
+			initiator_time_lock,
+			initiator_unit,
+			&counterparty,
+			counterparty_unit,
+		)?;
+		Ok(counterparty)
+	}
+
+	/// Checks a counterparty lock chosen for a transfer initiated with `initiator_time_lock`.
+	pub fn check_counterparty_time_lock(
+		&self,
+		initiator_time_lock: &TimeLock,
+		initiator_unit: TimeLockUnit,
+		counterparty_time_lock: &TimeLock,
+		counterparty_unit: TimeLockUnit,
+	) -> Result<(), TimeLockPolicyError> {
+		let initiator = initiator_unit.to_secs(initiator_time_lock);
+		let counterparty = counterparty_unit.to_secs(counterparty_time_lock);
+		if counterparty == 0 || counterparty < self.min_duration {
+			return Err(TimeLockPolicyError::TooShort {
+				duration: counterparty,
+				min_duration: self.min_duration,
+			});
+		}
+		if counterparty.saturating_add(self.counterparty_margin) > initiator {
+			return Err(TimeLockPolicyError::InsufficientMargin {
+				initiator,
+				counterparty,
+				margin: self.counterparty_margin,
+			});
+		}
+		Ok(())
+	}
+}
+
+/// Token of the transferred assets, the address of the token contract on Ethereum or the struct
+/// tag of the coin on Movement.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct TokenAddress(pub String);
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct BridgeTransferDetails<A, H> {
@@ -72,6 +639,8 @@ pub struct BridgeTransferDetails<A, H> {
 	pub hash_lock: HashLock<H>,
 	pub time_lock: TimeLock,
 	pub amount: Amount,
+	/// Token of the transferred assets, unset when the contracts bridge a single asset.
+	pub token: Option<TokenAddress>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -0,0 +1,196 @@
+use std::{
+	collections::{BTreeMap, HashSet},
+	fmt,
+	future::Future,
+	hash::Hash,
+	pin::Pin,
+	sync::{Arc, Mutex, MutexGuard},
+	task::{Context, Poll, Waker},
+};
+
+/// Runs the contract calls of the relayer on a bounded number of workers.
+///
+/// At most `workers` calls run at once, so that hundreds of concurrent swaps don't flood the RPC
+/// nodes. Calls sharing a key run one at a time, in the order they were queued, so that the
+/// transactions of a transfer, or the ones sharing the nonce of a signer, are not submitted out of
+/// order. A call waiting for its key doesn't hold back the calls queued after it.
+#[derive(Clone)]
+pub struct WorkQueue<K> {
+	state: Arc<Mutex<State<K>>>,
+}
+
+struct State<K> {
+	workers: usize,
+	running: usize,
+	busy: HashSet<K>,
+	next_ticket: u64,
+	/// Calls waiting for a worker, by order of arrival.
+	waiting: BTreeMap<u64, (K, Option<Waker>)>,
+}
+
+impl<K> fmt::Debug for WorkQueue<K> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		f.debug_struct("WorkQueue")
+			.field("workers", &state.workers)
+			.field("running", &state.running)
+			.field("waiting", &state.waiting.len())
+			.finish()
+	}
+}
+
+impl<K> WorkQueue<K>
+where
+	K: Clone + Eq + Hash,
+{
+	/// Queue running at most `workers` calls at once, at least one.
+	pub fn new(workers: usize) -> Self {
+		Self {
+			state: Arc::new(Mutex::new(State {
+				workers: workers.max(1),
+				running: 0,
+				busy: HashSet::new(),
+				next_ticket: 0,
+				waiting: BTreeMap::new(),
+			})),
+		}
+	}
+
+	pub fn workers(&self) -> usize {
+		self.lock().workers
+	}
+
+	/// Number of calls running on a worker.
+	pub fn running(&self) -> usize {
+		self.lock().running
+	}
+
+	/// Number of calls waiting for a worker.
+	pub fn waiting(&self) -> usize {
+		self.lock().waiting.len()
+	}
+
+	/// Runs `call` once a worker is free and no other call with `key` runs.
+	///
+	/// The call is queued when the returned future is first polled, and leaves the queue when it
+	/// is dropped.
+	pub fn run<F>(&self, key: K, call: F) -> impl Future<Output = F::Output>
+	where
+		F: Future,
+	{
+		let acquire = Acquire { queue: self.clone(), key, ticket: None };
+		async move {
+			let _worker = acquire.await;
+			call.await
+		}
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State<K>> {
+		self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+impl<K> State<K>
+where
+	K: Clone + Eq + Hash,
+{
+	/// Whether the call holding `ticket` runs next, when no call queued before it could run.
+	fn can_run(&self, ticket: u64, key: &K) -> bool {
+		self.running < self.workers
+			&& !self.busy.contains(key)
+			&& self
+				.waiting
+				.range(..ticket)
+				.all(|(_, (waiting, _))| self.busy.contains(waiting))
+	}
+
+	fn wake_waiting(&mut self) {
+		for (_, waker) in self.waiting.values_mut() {
+			if let Some(waker) = waker.take() {
+				waker.wake();
+			}
+		}
+	}
+}
+
+struct Acquire<K>
+where
+	K: Clone + Eq + Hash,
+{
+	queue: WorkQueue<K>,
+	key: K,
+	ticket: Option<u64>,
+}
+
+// The fields of `Acquire` are never pinned.
+impl<K> Unpin for Acquire<K> where K: Clone + Eq + Hash {}
+
+impl<K> Future for Acquire<K>
+where
+	K: Clone + Eq + Hash,
+{
+	type Output = Worker<K>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		let mut state = this.queue.lock();
+		let ticket = *this.ticket.get_or_insert_with(|| {
+			let ticket = state.next_ticket;
+			state.next_ticket += 1;
+			state.waiting.insert(ticket, (this.key.clone(), None));
+			ticket
+		});
+
+		if state.can_run(ticket, &this.key) {
+			state.waiting.remove(&ticket);
+			state.running += 1;
+			state.busy.insert(this.key.clone());
+			// The calls queued after this one, which waited for it, may run on the free workers
+			if state.running < state.workers {
+				state.wake_waiting();
+			}
+			drop(state);
+			this.ticket = None;
+			return Poll::Ready(Worker { queue: this.queue.clone(), key: this.key.clone() });
+		}
+		if let Some((_, waker)) = state.waiting.get_mut(&ticket) {
+			*waker = Some(cx.waker().clone());
+		}
+		Poll::Pending
+	}
+}
+
+impl<K> Drop for Acquire<K>
+where
+	K: Clone + Eq + Hash,
+{
+	fn drop(&mut self) {
+		if let Some(ticket) = self.ticket {
+			let mut state = self.queue.lock();
+			state.waiting.remove(&ticket);
+			// The calls queued after this one may run now
+			state.wake_waiting();
+		}
+	}
+}
+
+/// Worker running a call, released when the call is done.
+struct Worker<K>
+where
+	K: Clone + Eq + Hash,
+{
+	queue: WorkQueue<K>,
+	key: K,
+}
+
+impl<K> Drop for Worker<K>
+where
+	K: Clone + Eq + Hash,
+{
+	fn drop(&mut self) {
+		let mut state = self.queue.lock();
+		state.running -= 1;
+		state.busy.remove(&self.key);
+		state.wake_waiting();
+	}
+}
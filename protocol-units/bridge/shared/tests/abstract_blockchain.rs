@@ -2,7 +2,7 @@ use bridge_shared::types::{
 	Amount, BridgeTransferDetails, BridgeTransferId, GenUniqueHash, HashLock, InitiatorAddress,
 	RecipientAddress, TimeLock,
 };
-use bridge_shared::types::{HashLockPreImage, LockDetails};
+use bridge_shared::types::{HashLockAlgorithm, HashLockPreImage, LockDetails};
 use futures::StreamExt;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
@@ -16,7 +16,7 @@ use shared::testing::blockchain::{
 };
 
 use crate::shared::testing::blockchain::{
-	counterparty_contract::SmartContractCounterpartyEvent,
+	counterparty_contract::{SmartContractCounterpartyError, SmartContractCounterpartyEvent},
 	initiator_contract::SmartContractInitiatorEvent,
 };
 
@@ -38,8 +38,10 @@ impl From<RecipientAddress> for TestAddress {
 	}
 }
 
-impl From<HashLockPreImage> for TestHash {
-	fn from(_value: HashLockPreImage) -> Self {
+impl HashLockAlgorithm for TestHash {
+	type Hash = Self;
+
+	fn hash_lock(_pre_image: &HashLockPreImage) -> HashLock<Self> {
 		todo!()
 	}
 }
@@ -90,6 +92,7 @@ async fn test_initiate_bridge_transfer() {
 				amount: amount.clone(),
 				time_lock: time_lock.clone(),
 				hash_lock: hash_lock.clone(),
+				token: None,
 			})
 		))
 	);
@@ -161,3 +164,91 @@ async fn test_lock_bridge_transfer() {
 	assert_eq!(details.time_lock, time_lock);
 	assert_eq!(details.amount, amount);
 }
+
+#[test(tokio::test)]
+async fn test_abort_after_time_lock_expiry() {
+	let rng = ChaChaRng::from_seed([0u8; 32]);
+	let mut blockchain = AbstractBlockchain::<TestAddress, TestHash, _>::new(rng, "TestBlockchain");
+	let clock = blockchain.clock();
+
+	let bridge_transfer_id = BridgeTransferId(TestHash("unique_hash"));
+	let abort = || {
+		Transaction::Counterparty(CounterpartyCall::AbortBridgeTransfer(bridge_transfer_id.clone()))
+	};
+	blockchain
+		.transaction_sender
+		.unbounded_send(Transaction::Counterparty(CounterpartyCall::LockBridgeTransfer(
+			bridge_transfer_id.clone(),
+			HashLock(TestHash("hash_lock")),
+			TimeLock(100),
+			RecipientAddress::from(TestAddress("recipient")),
+			Amount(1000),
+		)))
+		.unwrap();
+	blockchain.next().await;
+
+	clock.advance(99);
+	blockchain.transaction_sender.unbounded_send(abort()).unwrap();
+	assert_eq!(
+		blockchain.next().await,
+		Some(AbstractBlockchainEvent::CounterpartyContractEvent(Err(
+			SmartContractCounterpartyError::TimeLockNotExpired
+		)))
+	);
+
+	clock.advance(1);
+	blockchain.transaction_sender.unbounded_send(abort()).unwrap();
+	assert_eq!(
+		blockchain.next().await,
+		Some(AbstractBlockchainEvent::CounterpartyContractEvent(Ok(
+			SmartContractCounterpartyEvent::AbortedBridgeTransfer(bridge_transfer_id.clone())
+		)))
+	);
+	assert!(blockchain.counterparty_contract.locked_transfers.is_empty());
+}
+
+#[test]
+fn test_counterparty_escrow() {
+	let rng = ChaChaRng::from_seed([0u8; 32]);
+	let mut blockchain = AbstractBlockchain::<TestAddress, TestHash, _>::new(rng, "TestBlockchain");
+	blockchain.add_relayer(TestAddress("relayer"), Amount(1500));
+
+	let bridge_transfer_id = BridgeTransferId(TestHash("unique_hash"));
+	let lock = |amount| {
+		Transaction::Counterparty(CounterpartyCall::LockBridgeTransfer(
+			bridge_transfer_id.clone(),
+			HashLock(TestHash("hash_lock")),
+			TimeLock(100),
+			RecipientAddress::from(TestAddress("recipient")),
+			Amount(amount),
+		))
+	};
+
+	assert_eq!(
+		blockchain.apply_transaction(lock(2000)),
+		AbstractBlockchainEvent::CounterpartyContractEvent(Err(
+			SmartContractCounterpartyError::InsufficientBalance
+		))
+	);
+	assert!(blockchain.counterparty_contract.transfer_states.is_empty());
+
+	assert!(matches!(
+		blockchain.apply_transaction(lock(1000)),
+		AbstractBlockchainEvent::CounterpartyContractEvent(Ok(_))
+	));
+	assert_eq!(blockchain.get_balance(&TestAddress("relayer")), Some(&Amount(500)));
+	assert_eq!(blockchain.counterparty_contract.escrow, Amount(1000));
+	assert_eq!(blockchain.total_balance(), Amount(1500));
+
+	// The assets of an aborted lock return to the relayer
+	blockchain.forward_time(100);
+	assert!(matches!(
+		blockchain.apply_transaction(Transaction::Counterparty(
+			CounterpartyCall::AbortBridgeTransfer(bridge_transfer_id.clone())
+		)),
+		AbstractBlockchainEvent::CounterpartyContractEvent(Ok(_))
+	));
+	assert_eq!(blockchain.get_balance(&TestAddress("relayer")), Some(&Amount(1500)));
+	assert_eq!(blockchain.counterparty_contract.escrow, Amount(0));
+	assert_eq!(blockchain.get_balance(&TestAddress("recipient")), None);
+}
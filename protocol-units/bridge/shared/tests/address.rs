@@ -0,0 +1,46 @@
+use bridge_shared::types::{
+	AddressError, AddressParseError, BridgeAddress, EthAddress, MovementAddress, RecipientAddress,
+};
+
+#[test]
+fn test_checked_address_conversions() {
+	let eth_recipient = BridgeAddress::from(RecipientAddress(vec![1u8; 20]));
+	assert_eq!(eth_recipient.to_eth(), Ok(EthAddress([1u8; 20])));
+	assert_eq!(
+		eth_recipient.to_movement(),
+		Err(AddressError::InvalidLength { expected: 32, actual: 20 })
+	);
+
+	let movement_recipient = BridgeAddress::Movement(MovementAddress([2u8; 32]));
+	assert!(matches!(movement_recipient.to_eth(), Err(AddressError::WrongChain(_, "Ethereum"))));
+	assert_eq!(RecipientAddress::from(movement_recipient), RecipientAddress(vec![2u8; 32]));
+}
+
+#[test]
+fn test_eth_address_checksum() {
+	// Test vectors of EIP-55
+	for checksummed in [
+		"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+		"0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+		"0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+		"0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+	] {
+		let address: EthAddress = checksummed.parse().unwrap();
+		assert_eq!(address.to_string(), checksummed);
+		assert_eq!(checksummed.to_lowercase().parse(), Ok(address));
+		assert_eq!(checksummed[2..].to_uppercase().parse(), Ok(address));
+	}
+
+	assert_eq!(
+		"0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse::<EthAddress>(),
+		Err(AddressParseError::InvalidChecksum(
+			"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string()
+		))
+	);
+	assert_eq!(
+		"0x0000000000000000000000000000000000000000".parse::<EthAddress>(),
+		Err(AddressParseError::ZeroAddress)
+	);
+	assert!(matches!("0x0102".parse::<EthAddress>(), Err(AddressParseError::InvalidHex(_))));
+	assert!(matches!("address".parse::<EthAddress>(), Err(AddressParseError::InvalidHex(_))));
+}
@@ -0,0 +1,41 @@
+use bridge_shared::types::{Amount, AmountError, ETH_DECIMALS, MOVEMENT_DECIMALS};
+
+#[test]
+fn test_scale_between_eth_and_movement() {
+	let one_eth = Amount(1_000_000_000_000_000_000);
+	let one_moveth = Amount(100_000_000);
+
+	assert_eq!(one_eth.scale(ETH_DECIMALS, MOVEMENT_DECIMALS), Ok(one_moveth));
+	assert_eq!(one_moveth.scale(MOVEMENT_DECIMALS, ETH_DECIMALS), Ok(one_eth));
+	assert_eq!(
+		Amount(1).scale(ETH_DECIMALS, MOVEMENT_DECIMALS),
+		Err(AmountError::PrecisionLoss(1, ETH_DECIMALS, MOVEMENT_DECIMALS))
+	);
+	assert_eq!(
+		Amount(u128::MAX).scale(MOVEMENT_DECIMALS, ETH_DECIMALS),
+		Err(AmountError::Overflow(u128::MAX, MOVEMENT_DECIMALS, ETH_DECIMALS))
+	);
+}
+
+#[test]
+fn test_large_eth_amount_is_not_truncated() {
+	// 100 000 ETH does not fit in 64 bits
+	let amount = Amount(100_000 * 1_000_000_000_000_000_000);
+	assert!(matches!(u64::try_from(amount), Err(AmountError::TooLarge(_))));
+	assert_eq!(
+		u64::try_from(amount.scale(ETH_DECIMALS, MOVEMENT_DECIMALS).unwrap()),
+		Ok(10_000_000_000_000)
+	);
+}
+
+#[test]
+fn test_contract_amounts_above_128_bits_are_rejected() {
+	let amount = Amount(100_000 * 1_000_000_000_000_000_000);
+	assert_eq!(Amount::try_from_uint256(amount.to_uint256()), Ok(amount));
+	assert_eq!(Amount::try_from_uint256(Amount(u128::MAX).to_uint256()), Ok(Amount(u128::MAX)));
+
+	// u128::MAX + 1, as a contract could hold it
+	let mut word = [0u8; 32];
+	word[15] = 1;
+	assert_eq!(Amount::try_from_uint256(word), Err(AmountError::ExceedsU128(word)));
+}
@@ -0,0 +1,65 @@
+use bridge_shared::{
+	approval::{parse_public_key, Approval, ApprovalError, Approver},
+	transfer_store::{InMemoryTransferStateStore, TransferStateStore},
+	types::BridgeTransferId,
+};
+
+const KEY: &str = "0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+#[test]
+fn test_approval_verify() {
+	let approver = Approver::try_from_hex_key(KEY).unwrap();
+	let approval = approver.approve("B1", &[1; 32]);
+	assert_eq!(approval.public_key, approver.public_key());
+	assert_eq!(approval.verify("B1", &[1; 32]), Ok(()));
+	assert_eq!(approval.verify("B2", &[1; 32]), Err(ApprovalError::InvalidSignature));
+	assert_eq!(approval.verify("B1", &[2; 32]), Err(ApprovalError::InvalidSignature));
+
+	let mut forged = approval.clone();
+	forged.public_key = Approver::new(&[7; 32]).public_key();
+	assert_eq!(forged.verify("B1", &[1; 32]), Err(ApprovalError::InvalidSignature));
+}
+
+#[test]
+fn test_approval_bytes() {
+	let approval = Approver::new(&[7; 32]).approve("B2", &[1; 32]);
+	let bytes = approval.to_bytes();
+	assert_eq!(Approval::from_bytes(&bytes), Ok(approval));
+	assert_eq!(Approval::from_bytes(&bytes[..bytes.len() - 1]), Err(ApprovalError::InvalidFormat));
+	assert_eq!(Approval::from_bytes(b"approval"), Err(ApprovalError::InvalidFormat));
+	assert_eq!(Approval::from_parts(&[1; 32], &[2; 63]), Err(ApprovalError::InvalidFormat));
+}
+
+#[test]
+fn test_parse_public_key() {
+	let approver = Approver::try_from_hex_key(KEY).unwrap();
+	let public_key = format!("0x{}", hex::encode(approver.public_key()));
+	assert_eq!(parse_public_key(&public_key), Ok(approver.public_key()));
+	assert_eq!(parse_public_key("0x0102"), Err(ApprovalError::InvalidKey));
+	assert_eq!(parse_public_key("key"), Err(ApprovalError::InvalidKey));
+}
+
+#[tokio::test]
+async fn test_stored_approvals() {
+	let store = InMemoryTransferStateStore::new();
+	let bridge_transfer_id = BridgeTransferId("transfer_id");
+	let (alice, bob) = (Approver::new(&[1; 32]), Approver::new(&[2; 32]));
+
+	for approval in
+		[alice.approve("B1", b"id"), bob.approve("B1", b"id"), alice.approve("B1", b"id")]
+	{
+		store.put_approval(bridge_transfer_id.clone(), approval).await.unwrap();
+	}
+	let mut public_keys: Vec<_> = store
+		.approvals(&bridge_transfer_id)
+		.await
+		.unwrap()
+		.into_iter()
+		.map(|approval| approval.public_key)
+		.collect();
+	public_keys.sort();
+	let mut expected = vec![alice.public_key(), bob.public_key()];
+	expected.sort();
+	assert_eq!(public_keys, expected);
+	assert_eq!(store.approvals(&BridgeTransferId("other")).await.unwrap(), vec![]);
+}
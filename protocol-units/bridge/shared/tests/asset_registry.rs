@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use bridge_shared::{
+	asset_registry::{AssetError, AssetId, AssetRegistry, AssetRegistryError, ChainAssets},
+	types::{Amount, AmountError, TokenAddress},
+};
+
+const REGISTRY: &str = r#"
+[[asset]]
+id = "MOVE"
+decimals = 8
+tokens = { ethereum = "0x3073F7AAA4DB83F95E9FFF17424F71D4751A3073", movement = "0x1::aptos_coin::AptosCoin" }
+
+[[asset]]
+id = "USDC"
+decimals = 6
+tokens = { ethereum = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", movement = "0xA::usdc::USDC" }
+"#;
+
+#[test]
+fn test_asset_registry_resolve() {
+	let registry = AssetRegistry::from_toml(REGISTRY).expect("Invalid registry");
+
+	let asset = registry
+		.resolve("ethereum", "0x3073f7aaa4db83f95e9fff17424f71d4751a3073")
+		.expect("MOVE not found");
+	assert_eq!(asset.id, AssetId("MOVE".to_string()));
+	assert_eq!(asset.decimals, 8);
+	assert_eq!(registry.resolve("movement", "0xa::usdc::USDC").map(|asset| asset.decimals), Ok(6));
+	assert_eq!(
+		registry.counterpart_token("movement", "0x1::aptos_coin::AptosCoin", "ethereum"),
+		Ok("0x3073F7AAA4DB83F95E9FFF17424F71D4751A3073")
+	);
+
+	// Module and type names are case sensitive
+	assert_eq!(
+		registry.resolve("movement", "0x1::APTOS_COIN::AptosCoin"),
+		Err(AssetError::Unknown {
+			chain: "movement".to_string(),
+			token: "0x1::APTOS_COIN::AptosCoin".to_string(),
+		})
+	);
+	assert!(registry.resolve("aptos", "0x1::aptos_coin::AptosCoin").is_err());
+	assert_eq!(
+		registry.counterpart_token("movement", "0x1::aptos_coin::AptosCoin", "aptos"),
+		Err(AssetError::NotBridged {
+			asset: AssetId("MOVE".to_string()),
+			chain: "aptos".to_string(),
+		})
+	);
+}
+
+#[test]
+fn test_asset_registry_rejects_duplicates() {
+	let duplicate_token = r#"
+[[asset]]
+id = "MOVE"
+decimals = 8
+tokens = { ethereum = "0xabc" }
+
+[[asset]]
+id = "WMOVE"
+decimals = 8
+tokens = { ethereum = "0xABC" }
+"#;
+	assert!(matches!(
+		AssetRegistry::from_toml(duplicate_token),
+		Err(AssetRegistryError::DuplicateToken { .. })
+	));
+
+	let duplicate_asset = r#"
+[[asset]]
+id = "MOVE"
+decimals = 8
+tokens = { ethereum = "0xabc" }
+
+[[asset]]
+id = "MOVE"
+decimals = 8
+tokens = { movement = "0x1::aptos_coin::AptosCoin" }
+"#;
+	assert!(matches!(
+		AssetRegistry::from_toml(duplicate_asset),
+		Err(AssetRegistryError::DuplicateAsset(_))
+	));
+
+	let duplicate_native = r#"
+[[asset]]
+id = "MOVE"
+decimals = 8
+native = true
+tokens = { ethereum = "0xabc" }
+
+[[asset]]
+id = "ETH"
+decimals = 8
+native = true
+tokens = { ethereum = "0xdef" }
+"#;
+	assert!(matches!(
+		AssetRegistry::from_toml(duplicate_native),
+		Err(AssetRegistryError::DuplicateNative { .. })
+	));
+}
+
+#[test]
+fn test_chain_assets() {
+	let registry = Arc::new(AssetRegistry::from_toml(REGISTRY).expect("Invalid registry"));
+	let assets = ChainAssets::new(registry, "ethereum");
+
+	let token = TokenAddress("0xA0B86991C6218B36C1D19D4A2E9EB0CE3606EB48".to_string());
+	assert_eq!(assets.resolve(Some(&token)).map(|asset| asset.decimals), Ok(6));
+	assert_eq!(assets.resolve(None), Err(AssetError::MissingToken("ethereum".to_string())));
+}
+
+#[test]
+fn test_chain_assets_counterpart() {
+	let registry = r#"
+[[asset]]
+id = "MOVE"
+decimals = 8
+native = true
+tokens = { ethereum = "0x3073f7aaa4db83f95e9fff17424f71d4751a3073", movement = "0x1::aptos_coin::AptosCoin" }
+
+[[asset]]
+id = "ETH"
+decimals = 8
+chain_decimals = { ethereum = 18 }
+tokens = { ethereum = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", movement = "0xe::weth::WETH" }
+"#;
+	let registry = Arc::new(AssetRegistry::from_toml(registry).expect("Invalid registry"));
+	let ethereum = ChainAssets::new(registry.clone(), "ethereum");
+	let movement = ChainAssets::new(registry, "movement");
+
+	// Transfers which don't name their token are in the native asset
+	assert_eq!(ethereum.resolve(None).map(|asset| asset.id.0.as_str()), Ok("MOVE"));
+	assert_eq!(
+		ethereum.counterpart(None, Amount(1000), "movement"),
+		Ok((TokenAddress("0x1::aptos_coin::AptosCoin".to_string()), Amount(1000)))
+	);
+
+	let weth = TokenAddress("0xC02AAA39B223FE8D0A0E5C4F27EAD9083C756CC2".to_string());
+	assert_eq!(
+		ethereum.counterpart(Some(&weth), Amount(3 * 10u128.pow(10)), "movement"),
+		Ok((TokenAddress("0xe::weth::WETH".to_string()), Amount(3)))
+	);
+	assert_eq!(
+		movement.counterpart(
+			Some(&TokenAddress("0xe::weth::WETH".to_string())),
+			Amount(3),
+			"ethereum"
+		),
+		Ok((
+			TokenAddress("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".to_string()),
+			Amount(3 * 10u128.pow(10))
+		))
+	);
+	// Amounts below the precision of the counterparty token are not rounded
+	assert_eq!(
+		ethereum.counterpart(Some(&weth), Amount(1), "movement"),
+		Err(AssetError::Amount(AmountError::PrecisionLoss(1, 18, 8)))
+	);
+	assert_eq!(
+		ethereum.counterpart(None, Amount(1000), "aptos"),
+		Err(AssetError::NotBridged {
+			asset: AssetId("MOVE".to_string()),
+			chain: "aptos".to_string(),
+		})
+	);
+}
+
+#[test]
+fn test_asset_registry_load() {
+	let path = std::env::temp_dir().join(format!("bridge_assets_{}.toml", std::process::id()));
+	std::fs::write(&path, REGISTRY).expect("Failed to write registry");
+	let registry = AssetRegistry::load(&path);
+	std::fs::remove_file(&path).expect("Failed to remove registry");
+
+	assert_eq!(registry.expect("Failed to load registry").assets().count(), 2);
+	assert!(matches!(
+		AssetRegistry::load(&path),
+		Err(AssetRegistryError::File(missing, _)) if missing == path
+	));
+}
@@ -0,0 +1,367 @@
+use std::{hash::Hash, time::Duration};
+
+use futures::{
+	channel::{mpsc::UnboundedReceiver, oneshot},
+	Stream, StreamExt,
+};
+use test_log::test;
+use tokio::task::JoinHandle;
+
+use bridge_shared::{
+	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
+	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
+	bridge_service::{active_swap::ActiveSwapConfig, BridgeService, BridgeServiceConfig},
+	transfer_store::InvalidTransition,
+	types::{
+		Amount, BridgeTransferId, Convert, HashLock, HashLockPreImage, InitiatorAddress,
+		RecipientAddress, TimeLock,
+	},
+};
+
+use crate::shared::{
+	setup_bridge_service,
+	testing::{
+		blockchain::{
+			client::{CallConfig, ErrorConfig, MethodName},
+			initiator_contract::{SmartContractInitiatorError, SmartContractInitiatorEvent},
+			AbstractBlockchain, AbstractBlockchainEvent,
+		},
+		clock::Clock,
+		rng::TestRng,
+	},
+	B1Client, B1Service, B2Client, B2Service, BC1Address, BC1Hash, BC2Address, BC2Hash,
+	SetupBridgeServiceResult,
+};
+
+mod shared;
+
+const RELAYER_LIQUIDITY: u128 = 10_000;
+const AMOUNT: u128 = 1000;
+const TIME_LOCK: u64 = 100;
+
+/// Blockchain running in its own task until stopped, to read its balances at the end of a test.
+struct Running<B> {
+	stop: oneshot::Sender<()>,
+	task: JoinHandle<B>,
+}
+
+impl<B> Running<B>
+where
+	B: Stream + Unpin + Send + 'static,
+{
+	fn spawn(mut blockchain: B) -> Self {
+		let (stop, mut stopped) = oneshot::channel::<()>();
+		let task = tokio::spawn(async move {
+			loop {
+				tokio::select! {
+					event = blockchain.next() => if event.is_none() { break },
+					_ = &mut stopped => break,
+				}
+			}
+			blockchain
+		});
+		Self { stop, task }
+	}
+
+	async fn stop(self) -> B {
+		let _ = self.stop.send(());
+		self.task.await.expect("Blockchain task failed")
+	}
+}
+
+/// Bridge service between two blockchains, on which the initiator holds the amount of a transfer
+/// and the relayer its liquidity.
+struct Bridge {
+	service: BridgeService<B1Service, B2Service>,
+	client_1: B1Client,
+	client_2: B2Client,
+	clock_1: Clock,
+	clock_2: Clock,
+	/// Events of the first blockchain, including the failed calls the service doesn't report.
+	events_1: UnboundedReceiver<AbstractBlockchainEvent<BC1Address, BC1Hash>>,
+	blockchain_1: Running<AbstractBlockchain<BC1Address, BC1Hash, TestRng>>,
+	blockchain_2: Running<AbstractBlockchain<BC2Address, BC2Hash, TestRng>>,
+}
+
+impl Bridge {
+	fn start() -> Self {
+		let SetupBridgeServiceResult(
+			service,
+			client_1,
+			client_2,
+			mut blockchain_1,
+			mut blockchain_2,
+		) = setup_bridge_service(BridgeServiceConfig {
+			active_swap: ActiveSwapConfig {
+				liquidity_check_interval: Duration::from_millis(100),
+				..ActiveSwapConfig::default()
+			},
+		});
+		blockchain_1.add_relayer(BC1Address("relayer"), Amount(RELAYER_LIQUIDITY));
+		blockchain_1.add_account(BC1Address("initiator"), Amount(AMOUNT));
+		blockchain_2.add_relayer(BC2Address("relayer"), Amount(RELAYER_LIQUIDITY));
+		blockchain_2.add_account(BC2Address("initiator"), Amount(AMOUNT));
+
+		Self {
+			service,
+			client_1,
+			client_2,
+			clock_1: blockchain_1.clock(),
+			clock_2: blockchain_2.clock(),
+			events_1: blockchain_1.add_event_listener(),
+			blockchain_1: Running::spawn(blockchain_1),
+			blockchain_2: Running::spawn(blockchain_2),
+		}
+	}
+
+	async fn initiate_on_1(&mut self) -> BridgeTransferId<BC1Hash> {
+		self.client_1
+			.initiate_bridge_transfer(
+				InitiatorAddress(BC1Address("initiator")),
+				RecipientAddress::from(BC2Address("recipient")),
+				HashLock(BC1Hash::from("secret")),
+				TimeLock(TIME_LOCK),
+				Amount(AMOUNT),
+			)
+			.await
+			.expect("initiate_bridge_transfer failed");
+		let event = self.service.next().await.expect("No event");
+		event.B1I_ContractEvent().expect("Not a B1I event").bridge_transfer_id().clone()
+	}
+
+	async fn expect_locked_on_2(&mut self, bridge_transfer_id: &BridgeTransferId<BC1Hash>) {
+		let event = self.service.next().await.expect("No event");
+		assert!(matches!(
+			event.B2C_ContractEvent(),
+			Some(BridgeContractCounterpartyEvent::Locked(details))
+				if details.bridge_transfer_id == Convert::convert(bridge_transfer_id)
+		));
+	}
+
+	/// Refund of the initiator on the first blockchain, rejected with the returned error.
+	async fn refund_on_1(
+		&mut self,
+		bridge_transfer_id: &BridgeTransferId<BC1Hash>,
+	) -> Result<(), SmartContractInitiatorError> {
+		<B1Client as BridgeContractInitiator>::refund_bridge_transfer(
+			&mut self.client_1,
+			bridge_transfer_id.clone(),
+		)
+		.await
+		.expect("refund_bridge_transfer failed");
+		loop {
+			match self.events_1.next().await.expect("No event") {
+				AbstractBlockchainEvent::InitiatorContractEvent(Err(error)) => return Err(error),
+				AbstractBlockchainEvent::InitiatorContractEvent(Ok(
+					SmartContractInitiatorEvent::RefundedBridgeTransfer(_),
+				)) => return Ok(()),
+				_ => {}
+			}
+		}
+	}
+
+	/// Stops the blockchains and returns the balances of the initiator, the relayer and the
+	/// recipient on each of them, checking that no assets were created or lost.
+	async fn balances(self) -> [[u128; 3]; 2] {
+		let blockchain_1 = self.blockchain_1.stop().await;
+		let blockchain_2 = self.blockchain_2.stop().await;
+		assert_eq!(blockchain_1.total_balance(), Amount(RELAYER_LIQUIDITY + AMOUNT));
+		assert_eq!(blockchain_2.total_balance(), Amount(RELAYER_LIQUIDITY + AMOUNT));
+		[
+			["initiator", "relayer", "recipient"]
+				.map(|name| balance(&blockchain_1, BC1Address(name))),
+			["initiator", "relayer", "recipient"]
+				.map(|name| balance(&blockchain_2, BC2Address(name))),
+		]
+	}
+}
+
+fn balance<A, H, R>(blockchain: &AbstractBlockchain<A, H, R>, address: A) -> u128
+where
+	A: Eq + Hash,
+{
+	blockchain.accounts.get(&address).map_or(0, |amount| **amount)
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_happy_path_1_to_2() {
+	let mut bridge = Bridge::start();
+	let bridge_transfer_id = bridge.initiate_on_1().await;
+	bridge.expect_locked_on_2(&bridge_transfer_id).await;
+
+	<B2Client as BridgeContractCounterparty>::complete_bridge_transfer(
+		&mut bridge.client_2,
+		Convert::convert(&bridge_transfer_id),
+		HashLockPreImage(b"secret".to_vec()),
+	)
+	.await
+	.expect("complete_bridge_transfer failed");
+	let event = bridge.service.next().await.expect("No event");
+	assert!(matches!(
+		event.B2C_ContractEvent(),
+		Some(BridgeContractCounterpartyEvent::Completed(_))
+	));
+	let event = bridge.service.next().await.expect("No event");
+	assert_eq!(
+		event.B1I_ContractEvent(),
+		Some(&BridgeContractInitiatorEvent::Completed(bridge_transfer_id))
+	);
+
+	assert_eq!(
+		bridge.balances().await,
+		[[0, RELAYER_LIQUIDITY + AMOUNT, 0], [AMOUNT, RELAYER_LIQUIDITY - AMOUNT, AMOUNT]]
+	);
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_happy_path_2_to_1() {
+	let mut bridge = Bridge::start();
+	bridge
+		.client_2
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC2Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC2Hash::from("secret")),
+			TimeLock(TIME_LOCK),
+			Amount(AMOUNT),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+	let event = bridge.service.next().await.expect("No event");
+	let bridge_transfer_id =
+		event.B2I_ContractEvent().expect("Not a B2I event").bridge_transfer_id().clone();
+	let event = bridge.service.next().await.expect("No event");
+	assert!(matches!(event.B1C_ContractEvent(), Some(BridgeContractCounterpartyEvent::Locked(_))));
+
+	<B1Client as BridgeContractCounterparty>::complete_bridge_transfer(
+		&mut bridge.client_1,
+		Convert::convert(&bridge_transfer_id),
+		HashLockPreImage(b"secret".to_vec()),
+	)
+	.await
+	.expect("complete_bridge_transfer failed");
+	let event = bridge.service.next().await.expect("No event");
+	assert!(matches!(
+		event.B1C_ContractEvent(),
+		Some(BridgeContractCounterpartyEvent::Completed(_))
+	));
+	let event = bridge.service.next().await.expect("No event");
+	assert_eq!(
+		event.B2I_ContractEvent(),
+		Some(&BridgeContractInitiatorEvent::Completed(bridge_transfer_id))
+	);
+
+	assert_eq!(
+		bridge.balances().await,
+		[[AMOUNT, RELAYER_LIQUIDITY - AMOUNT, AMOUNT], [0, RELAYER_LIQUIDITY + AMOUNT, 0]]
+	);
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_refund_when_never_locked() {
+	let mut bridge = Bridge::start();
+	// The counterparty contract never has the liquidity to lock the transfer
+	bridge.client_2.set_liquidity(Some(Amount(0)));
+	let bridge_transfer_id = bridge.initiate_on_1().await;
+	let event = bridge.service.next().await.expect("No event");
+	assert!(event.B1I_ContractEvent().is_none(), "Unexpected event {event:?}");
+
+	assert_eq!(
+		bridge.refund_on_1(&bridge_transfer_id).await,
+		Err(SmartContractInitiatorError::TimeLockNotExpired)
+	);
+	bridge.clock_1.advance(TIME_LOCK);
+	assert_eq!(bridge.refund_on_1(&bridge_transfer_id).await, Ok(()));
+	let event = bridge.service.next().await.expect("No event");
+	assert_eq!(
+		event.B1I_ContractEvent(),
+		Some(&BridgeContractInitiatorEvent::Refunded(bridge_transfer_id))
+	);
+
+	assert_eq!(
+		bridge.balances().await,
+		[[AMOUNT, RELAYER_LIQUIDITY, 0], [AMOUNT, RELAYER_LIQUIDITY, 0]]
+	);
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_abort_after_time_lock() {
+	let mut bridge = Bridge::start();
+	let bridge_transfer_id = bridge.initiate_on_1().await;
+	bridge.expect_locked_on_2(&bridge_transfer_id).await;
+
+	// The recipient never reveals the secret, the relayer takes its liquidity back
+	bridge.clock_2.advance(TIME_LOCK);
+	<B2Client as BridgeContractCounterparty>::abort_bridge_transfer(
+		&mut bridge.client_2,
+		Convert::convert(&bridge_transfer_id),
+	)
+	.await
+	.expect("abort_bridge_transfer failed");
+	let event = bridge.service.next().await.expect("No event");
+	assert_eq!(
+		event.B2C_ContractEvent(),
+		Some(&BridgeContractCounterpartyEvent::Aborted(Convert::convert(&bridge_transfer_id)))
+	);
+
+	// The secret was never revealed, the initiator is refunded
+	bridge.clock_1.advance(TIME_LOCK);
+	assert_eq!(bridge.refund_on_1(&bridge_transfer_id).await, Ok(()));
+
+	assert_eq!(
+		bridge.balances().await,
+		[[AMOUNT, RELAYER_LIQUIDITY, 0], [AMOUNT, RELAYER_LIQUIDITY, 0]]
+	);
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_secret_reveal_race() {
+	let mut bridge = Bridge::start();
+	// The relayer is slow to complete the transfer once the secret is revealed
+	bridge.client_1.set_call_config(
+		MethodName::CompleteBridgeTransferInitiator,
+		1,
+		CallConfig { error: ErrorConfig::None, delay: Some(Duration::from_millis(200)) },
+	);
+	let bridge_transfer_id = bridge.initiate_on_1().await;
+	bridge.expect_locked_on_2(&bridge_transfer_id).await;
+
+	// The recipient reveals the secret at the last moment
+	bridge.clock_2.advance(TIME_LOCK - 1);
+	<B2Client as BridgeContractCounterparty>::complete_bridge_transfer(
+		&mut bridge.client_2,
+		Convert::convert(&bridge_transfer_id),
+		HashLockPreImage(b"secret".to_vec()),
+	)
+	.await
+	.expect("complete_bridge_transfer failed");
+	let event = bridge.service.next().await.expect("No event");
+	assert!(matches!(
+		event.B2C_ContractEvent(),
+		Some(BridgeContractCounterpartyEvent::Completed(_))
+	));
+
+	// The initiator races the relayer for the assets, while its time lock holds
+	bridge.clock_1.advance(TIME_LOCK - 1);
+	assert_eq!(
+		bridge.refund_on_1(&bridge_transfer_id).await,
+		Err(SmartContractInitiatorError::TimeLockNotExpired)
+	);
+	let event = bridge.service.next().await.expect("No event");
+	assert_eq!(
+		event.B1I_ContractEvent(),
+		Some(&BridgeContractInitiatorEvent::Completed(bridge_transfer_id.clone()))
+	);
+
+	// Once the relayer completed the transfer, it is never refunded
+	bridge.clock_1.advance(1);
+	assert!(matches!(
+		bridge.refund_on_1(&bridge_transfer_id).await,
+		Err(SmartContractInitiatorError::InvalidTransition(InvalidTransition { .. }))
+	));
+
+	assert_eq!(
+		bridge.balances().await,
+		[[0, RELAYER_LIQUIDITY + AMOUNT, 0], [AMOUNT, RELAYER_LIQUIDITY - AMOUNT, AMOUNT]]
+	);
+}
@@ -0,0 +1,68 @@
+use bridge_shared::{
+	attestation::{Attestation, AttestationError, AttestationStatement, Attestor},
+	transfer_store::{InMemoryTransferStateStore, TransferState, TransferStateStore},
+	types::BridgeTransferId,
+};
+
+const KEY: &str = "0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+fn statement(state: TransferState) -> AttestationStatement {
+	AttestationStatement {
+		chain: "B1".to_string(),
+		bridge_transfer_id: vec![1; 32],
+		state,
+		observed_at: 42,
+	}
+}
+
+#[test]
+fn test_attestation_verify() {
+	let attestor = Attestor::try_from_hex_key(KEY).unwrap();
+	let attestation = attestor.attest(statement(TransferState::Locked));
+	assert_eq!(attestation.public_key, attestor.public_key());
+	assert_eq!(attestation.verify(), Ok(()));
+
+	let mut tampered = attestation.clone();
+	tampered.statement.observed_at += 1;
+	assert_eq!(tampered.verify(), Err(AttestationError::InvalidSignature));
+
+	let mut forged = attestation.clone();
+	forged.public_key = Attestor::new(&[7; 32]).public_key();
+	assert_eq!(forged.verify(), Err(AttestationError::InvalidSignature));
+}
+
+#[test]
+fn test_attestation_bytes() {
+	let attestor = Attestor::new(&[7; 32]);
+	for state in TransferState::ALL {
+		let attestation = attestor.attest(statement(state));
+		let bytes = attestation.to_bytes();
+		assert_eq!(Attestation::from_bytes(&bytes), Ok(attestation));
+		assert_eq!(
+			Attestation::from_bytes(&bytes[..bytes.len() - 1]),
+			Err(AttestationError::InvalidFormat)
+		);
+	}
+	assert_eq!(Attestation::from_bytes(b"attestation"), Err(AttestationError::InvalidFormat));
+}
+
+#[test]
+fn test_invalid_key() {
+	assert_eq!(Attestor::try_from_hex_key("0x0102").unwrap_err(), AttestationError::InvalidKey);
+	assert_eq!(Attestor::try_from_hex_key("key").unwrap_err(), AttestationError::InvalidKey);
+}
+
+#[tokio::test]
+async fn test_stored_attestations() {
+	let store = InMemoryTransferStateStore::new();
+	let attestor = Attestor::new(&[7; 32]);
+	let bridge_transfer_id = BridgeTransferId("transfer_id");
+
+	let attestations = [TransferState::Initiated, TransferState::Locked, TransferState::Completed]
+		.map(|state| attestor.attest(statement(state)));
+	for attestation in attestations.clone() {
+		store.put_attestation(bridge_transfer_id.clone(), attestation).await.unwrap();
+	}
+	assert_eq!(store.attestations(&bridge_transfer_id).await.unwrap(), attestations.to_vec());
+	assert_eq!(store.attestations(&BridgeTransferId("other")).await.unwrap(), vec![]);
+}
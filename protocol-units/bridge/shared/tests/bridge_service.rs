@@ -1,15 +1,24 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use futures::StreamExt;
 use test_log::test;
 
 use bridge_shared::{
+	asset_registry::{AssetError, AssetRegistry},
 	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
 	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
-	bridge_service::{active_swap::ActiveSwapConfig, BridgeServiceConfig},
+	bridge_service::{
+		active_swap::{
+			ActiveSwapConfig, ActiveSwapMap, ApproveSwapError, LockBridgeTransferAssetsError,
+		},
+		events::{CEvent, CWarn, Event, IEvent, IWarn},
+		BridgeServiceConfig,
+	},
+	transfer_limits::{TransferLimitError, TransferLimits},
 	types::{
-		Amount, BridgeTransferDetails, CompletedDetails, Convert, HashLock, HashLockPreImage,
-		InitiatorAddress, LockDetails, RecipientAddress, TimeLock,
+		Amount, BridgeTransferDetails, CompletedDetails, Convert, Fee, HashLock, HashLockPreImage,
+		InitiatorAddress, LockDetails, RecipientAddress, TimeLock, TimeLockPolicy,
+		TimeLockPolicyError, TimeLockUnit, TokenAddress,
 	},
 };
 
@@ -33,6 +42,7 @@ async fn test_bridge_service_integration_a_to_b() {
 			error_attempts: 3,
 			error_delay: Duration::from_secs(1),
 			contract_call_timeout: Duration::from_secs(5),
+			..ActiveSwapConfig::default()
 		},
 	});
 
@@ -68,7 +78,8 @@ async fn test_bridge_service_integration_a_to_b() {
 			recipient_address: RecipientAddress::from(BC1Address("recipient")),
 			hash_lock: HashLock(BC1Hash::from("hash_lock")),
 			time_lock: TimeLock(100),
-			amount: Amount(1000)
+			amount: Amount(1000),
+			token: None,
 		})
 	);
 
@@ -156,6 +167,7 @@ async fn test_bridge_service_integration_b_to_a() {
 			error_attempts: 3,
 			error_delay: Duration::from_secs(1),
 			contract_call_timeout: Duration::from_secs(5),
+			..ActiveSwapConfig::default()
 		},
 	});
 
@@ -191,7 +203,8 @@ async fn test_bridge_service_integration_b_to_a() {
 			recipient_address: RecipientAddress::from(BC2Address("recipient")),
 			hash_lock: HashLock(BC2Hash::from("hash_lock")),
 			time_lock: TimeLock(100),
-			amount: Amount(1000)
+			amount: Amount(1000),
+			token: None,
 		})
 	);
 
@@ -265,3 +278,1000 @@ async fn test_bridge_service_integration_b_to_a() {
 		)
 	);
 }
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_deducts_fee() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		mut blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig {
+			fee: Fee { flat: 10, basis_points: 100 },
+			..ActiveSwapConfig::default()
+		},
+	});
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let transfer_initiated_event = bridge_service.next().await.expect("No event");
+	let transfer_initiated_event =
+		transfer_initiated_event.B1I_ContractEvent().expect("Not a B1I event");
+	let bridge_transfer_id = transfer_initiated_event.bridge_transfer_id().clone();
+
+	// The fee of 10 + 1% is deducted from the amount locked for the recipient
+	let counterparty_locked_event = bridge_service.next().await.expect("No event");
+	let counterparty_locked_event =
+		counterparty_locked_event.B2C_ContractEvent().expect("Not a B2C event");
+	assert_eq!(
+		counterparty_locked_event,
+		&BridgeContractCounterpartyEvent::Locked(LockDetails {
+			bridge_transfer_id: Convert::convert(&bridge_transfer_id),
+			hash_lock: HashLock(BC2Hash::from("hash_lock")),
+			time_lock: TimeLock(100),
+			recipient_address: RecipientAddress::from(BC2Address("recipient")),
+			amount: Amount(980),
+		})
+	);
+
+	<B2Client as BridgeContractCounterparty>::complete_bridge_transfer(
+		&mut blockchain_2_client,
+		Convert::convert(&bridge_transfer_id),
+		HashLockPreImage(b"hash_lock".to_vec()),
+	)
+	.await
+	.expect("complete_bridge_transfer failed");
+
+	let completed_event_counterparty = bridge_service.next().await.expect("No event");
+	assert!(completed_event_counterparty.B2C_ContractEvent().is_some());
+
+	// The initiator completion and the fee collection are reported in any order
+	let mut fee_collected = false;
+	for _ in 0..2 {
+		let event = bridge_service.next().await.expect("No event");
+		if let Event::B1I(IEvent::FeeCollected(id, fee)) = event {
+			assert_eq!((id, fee), (bridge_transfer_id.clone(), Amount(20)));
+			fee_collected = true;
+		}
+	}
+	assert!(fee_collected);
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_fee_exceeds_amount() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		_blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig {
+			fee: Fee { flat: 1000, basis_points: 100 },
+			..ActiveSwapConfig::default()
+		},
+	});
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(event, Event::B1I(IEvent::Warn(IWarn::FeeExceedsAmount(_)))));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_stop_accepting() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		_blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	bridge_service.stop_accepting();
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(event, Event::B1I(IEvent::Warn(IWarn::ShuttingDown(_)))));
+	assert_eq!(bridge_service.submitting(), 0);
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_time_lock_policy() {
+	let SetupBridgeServiceResult(
+		bridge_service,
+		mut blockchain_1_client,
+		_blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig {
+			time_lock_policy: TimeLockPolicy {
+				min_duration: 20,
+				max_duration: 1_000,
+				counterparty_margin: 30,
+			},
+			..ActiveSwapConfig::default()
+		},
+	});
+	// The time locks of blockchain 1 are counted in blocks of 2 seconds
+	let mut bridge_service = bridge_service.with_time_lock_units(TimeLockUnit(2), TimeLockUnit(1));
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	// The counterparty lock would last 18 seconds
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("racy")),
+			TimeLock(24),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		event,
+		Event::B1I(IEvent::Warn(IWarn::TimeLockRejected(
+			_,
+			TimeLockPolicyError::TooShort { duration: 18, min_duration: 20 }
+		)))
+	));
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	assert!(initiated_event.B1I_ContractEvent().is_some());
+
+	let locked_event = bridge_service.next().await.expect("No event");
+	let Some(BridgeContractCounterpartyEvent::Locked(details)) = locked_event.B2C_ContractEvent()
+	else {
+		panic!("Not a locked event: {locked_event:?}");
+	};
+	assert_eq!(details.time_lock, TimeLock(170));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_transfer_limit_per_initiator() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		_blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig {
+			limits: TransferLimits {
+				max_value_per_initiator: Some(1_500),
+				..TransferLimits::default()
+			},
+			..ActiveSwapConfig::default()
+		},
+	});
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("first")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	assert!(initiated_event.B1I_ContractEvent().is_some());
+	let locked_event = bridge_service.next().await.expect("No event");
+	assert!(locked_event.B2C_ContractEvent().is_some());
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("second")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		event,
+		Event::B1I(IEvent::Warn(IWarn::TransferLimitExceeded(
+			_,
+			TransferLimitError::Initiator { amount: 1000, locked: 1000, limit: 1_500 }
+		)))
+	));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_pending_approval() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		_blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig {
+			approval_threshold: Some(500),
+			..ActiveSwapConfig::default()
+		},
+	});
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	let bridge_transfer_id = initiated_event
+		.B1I_ContractEvent()
+		.expect("Not a B1I event")
+		.bridge_transfer_id()
+		.clone();
+
+	let event = bridge_service.next().await.expect("No event");
+	assert!(
+		matches!(event, Event::B1I(IEvent::PendingApproval(ref id)) if *id == bridge_transfer_id)
+	);
+	assert_eq!(
+		bridge_service
+			.active_swaps_b2_to_b1
+			.approve_bridge_transfer(&Convert::convert(&bridge_transfer_id)),
+		Err(ApproveSwapError::NonExistingSwap)
+	);
+
+	bridge_service
+		.active_swaps_b1_to_b2
+		.approve_bridge_transfer(&bridge_transfer_id)
+		.expect("approve_bridge_transfer failed");
+	assert_eq!(
+		bridge_service
+			.active_swaps_b1_to_b2
+			.approve_bridge_transfer(&bridge_transfer_id),
+		Err(ApproveSwapError::NotPendingApproval)
+	);
+
+	let locked_event = bridge_service.next().await.expect("No event");
+	assert!(locked_event.B2C_ContractEvent().is_some());
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_late_approval() {
+	let SetupBridgeServiceResult(
+		bridge_service,
+		mut blockchain_1_client,
+		_blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig {
+			time_lock_policy: TimeLockPolicy {
+				min_duration: 20,
+				max_duration: 1_000,
+				counterparty_margin: 30,
+			},
+			approval_threshold: Some(500),
+			..ActiveSwapConfig::default()
+		},
+	});
+	let (clock_1, clock_2) = (blockchain_1.clock(), blockchain_2.clock());
+	let mut bridge_service = bridge_service.with_clocks(
+		Arc::new({
+			let clock_1 = clock_1.clone();
+			move || clock_1.now()
+		}),
+		Arc::new(move || clock_2.now()),
+	);
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	let mut bridge_transfer_ids = Vec::new();
+	for hash_lock in ["first", "second"] {
+		blockchain_1_client
+			.initiate_bridge_transfer(
+				InitiatorAddress(BC1Address("initiator")),
+				RecipientAddress::from(BC1Address("recipient")),
+				HashLock(BC1Hash::from(hash_lock)),
+				TimeLock(100),
+				Amount(1000),
+			)
+			.await
+			.expect("initiate_bridge_transfer failed");
+		let initiated_event = bridge_service.next().await.expect("No event");
+		let bridge_transfer_id = initiated_event
+			.B1I_ContractEvent()
+			.expect("Not a B1I event")
+			.bridge_transfer_id()
+			.clone();
+		let event = bridge_service.next().await.expect("No event");
+		assert!(matches!(event, Event::B1I(IEvent::PendingApproval(_))));
+		bridge_transfer_ids.push(bridge_transfer_id);
+	}
+
+	// Approved 20 seconds later, the assets are locked for what is left less the margin
+	clock_1.advance(20);
+	bridge_service
+		.active_swaps_b1_to_b2
+		.approve_bridge_transfer(&bridge_transfer_ids[0])
+		.expect("approve_bridge_transfer failed");
+	let locked_event = bridge_service.next().await.expect("No event");
+	let Some(BridgeContractCounterpartyEvent::Locked(details)) = locked_event.B2C_ContractEvent()
+	else {
+		panic!("Not a locked event: {locked_event:?}");
+	};
+	assert_eq!(details.time_lock, TimeLock(50));
+
+	// Approved 60 seconds later, the lock would leave the recipient 10 seconds
+	clock_1.advance(40);
+	let expected = TimeLockPolicyError::TooShort { duration: 10, min_duration: 20 };
+	assert_eq!(
+		bridge_service
+			.active_swaps_b1_to_b2
+			.approve_bridge_transfer(&bridge_transfer_ids[1]),
+		Err(ApproveSwapError::TimeLockElapsed(expected.clone()))
+	);
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		event,
+		Event::B1I(IEvent::Warn(IWarn::TimeLockElapsed(ref id, ref error)))
+			if *id == bridge_transfer_ids[1] && *error == expected
+	));
+	assert_eq!(bridge_service.submitting(), 0);
+	assert!(!bridge_service.active_swaps_b1_to_b2.already_executing(&bridge_transfer_ids[1]));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_insufficient_liquidity() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig {
+			liquidity_check_interval: Duration::from_millis(100),
+			..ActiveSwapConfig::default()
+		},
+	});
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_2_client.set_liquidity(Some(Amount(999)));
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	let bridge_transfer_id = initiated_event
+		.B1I_ContractEvent()
+		.expect("Not a B1I event")
+		.bridge_transfer_id()
+		.clone();
+
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		event,
+		Event::B1I(IEvent::InsufficientLiquidity(ref id, LockBridgeTransferAssetsError::InsufficientLiquidity {
+			available: Amount(999),
+			required: Amount(1000),
+		})) if *id == bridge_transfer_id
+	));
+
+	blockchain_2_client.set_liquidity(Some(Amount(1000)));
+
+	let locked_event = bridge_service.next().await.expect("No event");
+	assert!(locked_event.B2C_ContractEvent().is_some());
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_insufficient_liquidity_past_margin() {
+	let SetupBridgeServiceResult(
+		bridge_service,
+		mut blockchain_1_client,
+		blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig {
+			time_lock_policy: TimeLockPolicy {
+				min_duration: 20,
+				max_duration: 1_000,
+				counterparty_margin: 30,
+			},
+			liquidity_check_interval: Duration::from_millis(100),
+			..ActiveSwapConfig::default()
+		},
+	});
+	let (clock_1, clock_2) = (blockchain_1.clock(), blockchain_2.clock());
+	let mut bridge_service = bridge_service.with_clocks(
+		Arc::new({
+			let clock_1 = clock_1.clone();
+			move || clock_1.now()
+		}),
+		Arc::new(move || clock_2.now()),
+	);
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_2_client.set_liquidity(Some(Amount(999)));
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	let bridge_transfer_id = initiated_event
+		.B1I_ContractEvent()
+		.expect("Not a B1I event")
+		.bridge_transfer_id()
+		.clone();
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(event, Event::B1I(IEvent::InsufficientLiquidity(..))));
+
+	// Parked for 60 seconds, the lock would leave the recipient 10 seconds
+	clock_1.advance(60);
+	blockchain_2_client.set_liquidity(Some(Amount(1000)));
+
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		event,
+		Event::B1I(IEvent::Warn(IWarn::TimeLockElapsed(
+			ref id,
+			TimeLockPolicyError::TooShort { duration: 10, min_duration: 20 }
+		))) if *id == bridge_transfer_id
+	));
+
+	// The assets are never locked, and the swap is dropped on the next poll
+	let next_event = tokio::time::timeout(Duration::from_millis(500), bridge_service.next()).await;
+	assert!(next_event.is_err(), "Unexpected event: {next_event:?}");
+	assert!(!bridge_service.active_swaps_b1_to_b2.already_executing(&bridge_transfer_id));
+	assert!(bridge_service.active_swaps_b1_to_b2.already_finished(&bridge_transfer_id));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_unknown_asset() {
+	let SetupBridgeServiceResult(
+		bridge_service,
+		mut blockchain_1_client,
+		_blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+	let registry = AssetRegistry::from_toml(
+		r#"
+[[asset]]
+id = "MOVE"
+decimals = 8
+tokens = { B1 = "0x1::aptos_coin::AptosCoin", B2 = "0x3073f7aaa4db83f95e9fff17424f71d4751a3073" }
+"#,
+	)
+	.expect("Invalid registry");
+	let mut bridge_service = bridge_service.with_assets(Arc::new(registry), "B1", "B2");
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	// The test contracts bridge a single asset, and don't name the token of their transfers
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		event,
+		Event::B1I(IEvent::Warn(IWarn::UnknownAsset(_, AssetError::MissingToken(ref chain))))
+			if chain == "B1"
+	));
+	let Event::B1I(IEvent::Warn(warn)) = event else { unreachable!() };
+	assert_eq!(warn.rejection_reason(), Some("asset"));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_native_asset() {
+	let SetupBridgeServiceResult(
+		bridge_service,
+		mut blockchain_1_client,
+		_blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+	let registry = AssetRegistry::from_toml(
+		r#"
+[[asset]]
+id = "MOVE"
+decimals = 8
+chain_decimals = { B1 = 6 }
+native = true
+tokens = { B1 = "0x1::aptos_coin::AptosCoin", B2 = "0x3073f7aaa4db83f95e9fff17424f71d4751a3073" }
+"#,
+	)
+	.expect("Invalid registry");
+	let mut bridge_service = bridge_service.with_assets(Arc::new(registry), "B1", "B2");
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	// The transfers which don't name their token are in the native asset
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let event = bridge_service.next().await.expect("No event");
+	let event = event.B1I_ContractEvent().expect("Not a B1I event");
+	let swap = bridge_service
+		.active_swaps_b1_to_b2
+		.get(event.bridge_transfer_id())
+		.expect("Swap not started");
+	assert_eq!(
+		swap.locked.token,
+		Some(TokenAddress("0x3073f7aaa4db83f95e9fff17424f71d4751a3073".to_string()))
+	);
+
+	// The amount is locked in the decimals of the token of B2
+	let event = bridge_service.next().await.expect("No event");
+	let Some(BridgeContractCounterpartyEvent::Locked(details)) = event.B2C_ContractEvent() else {
+		panic!("Not a B2C locked event: {event:?}");
+	};
+	assert_eq!(details.amount, Amount(100_000));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_duplicate_events() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		mut blockchain_2_client,
+		mut blockchain_1,
+		mut blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+
+	// Every contract event is delivered twice, as after a resubscription of the monitoring
+	blockchain_1.duplicate_delivery = true;
+	blockchain_2.duplicate_delivery = true;
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let mut events = Vec::new();
+	let mut completing = false;
+	while let Ok(Some(event)) =
+		tokio::time::timeout(Duration::from_millis(500), bridge_service.next()).await
+	{
+		if let Some(BridgeContractCounterpartyEvent::Locked(details)) = event.B2C_ContractEvent() {
+			if !completing {
+				completing = true;
+				<B2Client as BridgeContractCounterparty>::complete_bridge_transfer(
+					&mut blockchain_2_client,
+					details.bridge_transfer_id.clone(),
+					HashLockPreImage(b"hash_lock".to_vec()),
+				)
+				.await
+				.expect("complete_bridge_transfer failed");
+			}
+		}
+		events.push(event);
+	}
+
+	fn count<T>(events: &[T], matches: impl Fn(&T) -> bool) -> usize {
+		events.iter().filter(|event| matches(event)).count()
+	}
+	// The transfer is locked and completed once, each event being delivered twice
+	assert_eq!(
+		count(&events, |event| matches!(
+			event.B1I_ContractEvent(),
+			Some(BridgeContractInitiatorEvent::Initiated(_))
+		)),
+		1
+	);
+	assert_eq!(
+		count(&events, |event| matches!(event, Event::B1I(IEvent::Warn(IWarn::AlreadyPresent(_))))),
+		1
+	);
+	assert_eq!(
+		count(&events, |event| matches!(
+			event.B2C_ContractEvent(),
+			Some(BridgeContractCounterpartyEvent::Locked(_))
+		)),
+		2
+	);
+	assert_eq!(
+		count(&events, |event| matches!(
+			event.B2C_ContractEvent(),
+			Some(BridgeContractCounterpartyEvent::Completed(_))
+		)),
+		1
+	);
+	assert_eq!(
+		count(&events, |event| matches!(
+			event,
+			Event::B2C(CEvent::Warn(CWarn::AlreadyCompleting(_)))
+		)),
+		1
+	);
+	assert_eq!(
+		count(&events, |event| matches!(
+			event.B1I_ContractEvent(),
+			Some(BridgeContractInitiatorEvent::Completed(_))
+		)),
+		2
+	);
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_restores_locked_transfer() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		mut blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	let Some(BridgeContractInitiatorEvent::Initiated(details)) =
+		initiated_event.B1I_ContractEvent()
+	else {
+		panic!("Not a B1I initiated event: {initiated_event:?}");
+	};
+	let details = details.clone();
+	let locked_event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		locked_event.B2C_ContractEvent(),
+		Some(BridgeContractCounterpartyEvent::Locked(_))
+	));
+
+	// The relayer restarts with the swaps of the transfers it stored as locked
+	bridge_service.active_swaps_b1_to_b2 = ActiveSwapMap::build(
+		blockchain_1_client.clone(),
+		blockchain_2_client.clone(),
+		ActiveSwapConfig::default(),
+	);
+	bridge_service
+		.active_swaps_b1_to_b2
+		.restore_locked(details.bridge_transfer_id.clone());
+	bridge_service
+		.active_swaps_b1_to_b2
+		.start_bridge_transfer(details.clone())
+		.expect("start_bridge_transfer failed");
+
+	<B2Client as BridgeContractCounterparty>::complete_bridge_transfer(
+		&mut blockchain_2_client,
+		Convert::convert(&details.bridge_transfer_id),
+		HashLockPreImage(b"hash_lock".to_vec()),
+	)
+	.await
+	.expect("complete_bridge_transfer failed");
+
+	// The assets are not locked again, and the initiator side is completed
+	let completed_event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		completed_event.B2C_ContractEvent(),
+		Some(BridgeContractCounterpartyEvent::Completed(_))
+	));
+	let completed_event = bridge_service.next().await.expect("No event");
+	assert_eq!(
+		completed_event.B1I_ContractEvent(),
+		Some(&BridgeContractInitiatorEvent::Completed(details.bridge_transfer_id.clone()))
+	);
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_restores_transfer_pending_approval() {
+	let config = ActiveSwapConfig {
+		time_lock_policy: TimeLockPolicy {
+			min_duration: 20,
+			max_duration: 1_000,
+			counterparty_margin: 30,
+		},
+		approval_threshold: Some(500),
+		..ActiveSwapConfig::default()
+	};
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: config.clone() });
+	let clock_1 = blockchain_1.clock();
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	let Some(BridgeContractInitiatorEvent::Initiated(details)) =
+		initiated_event.B1I_ContractEvent()
+	else {
+		panic!("Not a B1I initiated event: {initiated_event:?}");
+	};
+	let details = details.clone();
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(event, Event::B1I(IEvent::PendingApproval(_))));
+
+	// The relayer restarts 20 seconds later with the swaps of the transfers it stored as pending
+	// approval, whose time lock expires at 100
+	clock_1.advance(20);
+	bridge_service.active_swaps_b1_to_b2 =
+		ActiveSwapMap::build(blockchain_1_client.clone(), blockchain_2_client.clone(), config);
+	bridge_service
+		.active_swaps_b1_to_b2
+		.set_initiator_clock(Arc::new(move || clock_1.now()));
+	bridge_service
+		.active_swaps_b1_to_b2
+		.restore_pending_approval(details.bridge_transfer_id.clone(), TimeLock(100));
+	bridge_service
+		.active_swaps_b1_to_b2
+		.start_bridge_transfer(details.clone())
+		.expect("start_bridge_transfer failed");
+
+	// The swap waits for its approval, without being reported as pending approval again
+	bridge_service
+		.active_swaps_b1_to_b2
+		.approve_bridge_transfer(&details.bridge_transfer_id)
+		.expect("approve_bridge_transfer failed");
+	let locked_event = bridge_service.next().await.expect("No event");
+	let Some(BridgeContractCounterpartyEvent::Locked(lock)) = locked_event.B2C_ContractEvent()
+	else {
+		panic!("Not a locked event: {locked_event:?}");
+	};
+	assert_eq!(lock.time_lock, TimeLock(50));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_restores_transfer_lacking_liquidity() {
+	let config = ActiveSwapConfig {
+		liquidity_check_interval: Duration::from_millis(100),
+		..ActiveSwapConfig::default()
+	};
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: config.clone() });
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_2_client.set_liquidity(Some(Amount(999)));
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	let Some(BridgeContractInitiatorEvent::Initiated(details)) =
+		initiated_event.B1I_ContractEvent()
+	else {
+		panic!("Not a B1I initiated event: {initiated_event:?}");
+	};
+	let details = details.clone();
+	let event = bridge_service.next().await.expect("No event");
+	assert!(matches!(event, Event::B1I(IEvent::InsufficientLiquidity(..))));
+
+	// The relayer restarts with the swaps of the transfers it stored as lacking liquidity
+	bridge_service.active_swaps_b1_to_b2 =
+		ActiveSwapMap::build(blockchain_1_client.clone(), blockchain_2_client.clone(), config);
+	bridge_service
+		.active_swaps_b1_to_b2
+		.restore_insufficient_liquidity(details.bridge_transfer_id.clone(), TimeLock(100));
+	bridge_service
+		.active_swaps_b1_to_b2
+		.start_bridge_transfer(details.clone())
+		.expect("start_bridge_transfer failed");
+
+	// The liquidity is checked again, without the transfer being reported twice
+	blockchain_2_client.set_liquidity(Some(Amount(1000)));
+	let locked_event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		locked_event.B2C_ContractEvent(),
+		Some(BridgeContractCounterpartyEvent::Locked(_))
+	));
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_refund_after_time_lock_expiry() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		mut blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+	let (clock_1, clock_2) = (blockchain_1.clock(), blockchain_2.clock());
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let initiated_event = bridge_service.next().await.expect("No event");
+	let bridge_transfer_id = initiated_event
+		.B1I_ContractEvent()
+		.expect("Not a B1I event")
+		.bridge_transfer_id()
+		.clone();
+	let locked_event = bridge_service.next().await.expect("No event");
+	assert!(matches!(
+		locked_event.B2C_ContractEvent(),
+		Some(BridgeContractCounterpartyEvent::Locked(_))
+	));
+
+	// The recipient never completes, the lock expires and is aborted
+	clock_2.advance(100);
+	<B2Client as BridgeContractCounterparty>::abort_bridge_transfer(
+		&mut blockchain_2_client,
+		Convert::convert(&bridge_transfer_id),
+	)
+	.await
+	.expect("abort_bridge_transfer failed");
+	let aborted_event = bridge_service.next().await.expect("No event");
+	assert_eq!(
+		aborted_event.B2C_ContractEvent(),
+		Some(&BridgeContractCounterpartyEvent::Aborted(Convert::convert(&bridge_transfer_id)))
+	);
+
+	clock_1.advance(100);
+	<B1Client as BridgeContractInitiator>::refund_bridge_transfer(
+		&mut blockchain_1_client,
+		bridge_transfer_id.clone(),
+	)
+	.await
+	.expect("refund_bridge_transfer failed");
+	let refunded_event = bridge_service.next().await.expect("No event");
+	assert_eq!(
+		refunded_event.B1I_ContractEvent(),
+		Some(&BridgeContractInitiatorEvent::Refunded(bridge_transfer_id))
+	);
+}
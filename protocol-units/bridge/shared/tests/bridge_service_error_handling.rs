@@ -42,6 +42,7 @@ async fn test_bridge_service_error_handling() {
 			error_attempts: 3,
 			error_delay: Duration::from_secs(1),
 			contract_call_timeout: Duration::from_secs(5),
+			..ActiveSwapConfig::default()
 		},
 	});
 
@@ -87,7 +88,8 @@ async fn test_bridge_service_error_handling() {
 			recipient_address: RecipientAddress::from(BC1Address("recipient")),
 			hash_lock: HashLock(BC1Hash::from("hash_lock")),
 			time_lock: TimeLock(100),
-			amount: Amount(1000)
+			amount: Amount(1000),
+			token: None,
 		})
 	);
 
@@ -200,6 +202,7 @@ async fn test_bridge_service_locking_termination_after_errors() {
 			error_attempts: 3,
 			error_delay: Duration::from_secs(1),
 			contract_call_timeout: Duration::from_secs(5),
+			..ActiveSwapConfig::default()
 		},
 	});
 
@@ -246,7 +249,8 @@ async fn test_bridge_service_locking_termination_after_errors() {
 			recipient_address: RecipientAddress::from(BC1Address("recipient")),
 			hash_lock: HashLock(BC1Hash::from("hash_lock")),
 			time_lock: TimeLock(100),
-			amount: Amount(1000)
+			amount: Amount(1000),
+			token: None,
 		})
 	);
 
@@ -295,6 +299,7 @@ async fn test_bridge_service_completion_abort_after_errors() {
 			error_attempts: 3,
 			error_delay: Duration::from_secs(1),
 			contract_call_timeout: Duration::from_secs(5),
+			..ActiveSwapConfig::default()
 		},
 	});
 
@@ -327,7 +332,8 @@ async fn test_bridge_service_completion_abort_after_errors() {
 			recipient_address: RecipientAddress::from(BC1Address("recipient")),
 			hash_lock: HashLock(BC1Hash::from("hash_lock")),
 			time_lock: TimeLock(100),
-			amount: Amount(1000)
+			amount: Amount(1000),
+			token: None,
 		})
 	);
 
@@ -422,6 +428,7 @@ async fn test_bridge_service_timeout_error_handling() {
 			error_attempts: 1,
 			error_delay: Duration::from_secs(1),
 			contract_call_timeout: Duration::from_millis(100), // Set a short timeout for testing
+			..ActiveSwapConfig::default()
 		},
 	});
 
@@ -462,7 +469,8 @@ async fn test_bridge_service_timeout_error_handling() {
 			recipient_address: RecipientAddress::from(BC1Address("recipient")),
 			hash_lock: HashLock(BC1Hash::from("hash_lock")),
 			time_lock: TimeLock(100),
-			amount: Amount(1000)
+			amount: Amount(1000),
+			token: None,
 		})
 	);
 
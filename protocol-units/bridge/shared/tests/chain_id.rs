@@ -0,0 +1,14 @@
+use bridge_shared::blockchain_service::{verify_chain_id, ChainIdError};
+
+#[tokio::test]
+async fn test_verify_chain_id() {
+	assert_eq!(verify_chain_id("ethereum", 1, async { Ok::<_, String>(1) }).await, Ok(()));
+	assert_eq!(
+		verify_chain_id("ethereum", 1, async { Ok::<_, String>(11155111) }).await,
+		Err(ChainIdError::Mismatch { chain: "ethereum", expected: 1, actual: 11155111 })
+	);
+	assert_eq!(
+		verify_chain_id("movement", 126, async { Err::<u64, _>("connection refused") }).await,
+		Err(ChainIdError::Query { chain: "movement", error: "connection refused".to_string() })
+	);
+}
@@ -0,0 +1,70 @@
+use bridge_shared::bridge_contracts::{
+	abi_selector, AtomicBridgeError, BridgeContractCounterpartyError, BridgeContractInitiatorError,
+	ContractRevert,
+};
+
+fn encode_error_string(reason: &str) -> Vec<u8> {
+	let mut data = abi_selector("Error(string)").to_vec();
+	let mut word = [0u8; 32];
+	word[31] = 0x20;
+	data.extend_from_slice(&word);
+	word[31] = reason.len() as u8;
+	data.extend_from_slice(&word);
+	let mut bytes = reason.as_bytes().to_vec();
+	bytes.resize(reason.len().div_ceil(32) * 32, 0);
+	data.extend_from_slice(&bytes);
+	data
+}
+
+#[test]
+fn test_abi_selector() {
+	assert_eq!(abi_selector("Error(string)"), [0x08, 0xc3, 0x79, 0xa0]);
+	assert_eq!(abi_selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+}
+
+#[test]
+fn test_decode_custom_errors() {
+	for error in AtomicBridgeError::ALL {
+		assert_eq!(ContractRevert::decode(&error.selector()), ContractRevert::Custom(error));
+	}
+}
+
+#[test]
+fn test_decode_error_string() {
+	assert_eq!(
+		ContractRevert::decode(&encode_error_string("not enough WETH")),
+		ContractRevert::Reason("not enough WETH".to_string())
+	);
+}
+
+#[test]
+fn test_decode_unknown_revert_data() {
+	assert_eq!(ContractRevert::decode(&[0x01, 0x02]), ContractRevert::Unknown(vec![0x01, 0x02]));
+	assert_eq!(
+		ContractRevert::decode(&[0xde, 0xad, 0xbe, 0xef]).to_string(),
+		"unknown revert data 0xdeadbeef"
+	);
+}
+
+#[test]
+fn test_classify_revert_data() {
+	let completed = AtomicBridgeError::BridgeTransferHasBeenCompleted.selector();
+	assert!(matches!(
+		BridgeContractInitiatorError::from_revert_data(&completed),
+		BridgeContractInitiatorError::AlreadyCompleted
+	));
+	assert_eq!(
+		BridgeContractCounterpartyError::from_revert_data(
+			&AtomicBridgeError::BridgeTransferInvalid.selector()
+		),
+		BridgeContractCounterpartyError::NotFound
+	);
+	assert!(matches!(
+		BridgeContractInitiatorError::from_revert_data(
+			&AtomicBridgeError::InvalidSecret.selector()
+		),
+		BridgeContractInitiatorError::Revert(ContractRevert::Custom(
+			AtomicBridgeError::InvalidSecret
+		))
+	));
+}
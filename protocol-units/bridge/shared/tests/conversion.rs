@@ -0,0 +1,39 @@
+use bridge_shared::{
+	bridge_contracts::{BridgeContractCounterpartyError, BridgeContractInitiatorError},
+	types::{hex_to_array, vec_to_array, ConversionError, RecipientAddress},
+};
+
+#[test]
+fn test_checked_conversions() {
+	assert_eq!(vec_to_array::<2>("hash lock", &[1, 2]), Ok([1, 2]));
+	assert_eq!(
+		vec_to_array::<32>("hash lock", &[1, 2]),
+		Err(ConversionError::InvalidLength { name: "hash lock", expected: 32, actual: 2 })
+	);
+	assert_eq!(hex_to_array::<2>("bridge transfer id", "0x0102"), Ok([1, 2]));
+	assert_eq!(hex_to_array::<2>("bridge transfer id", "0102"), Ok([1, 2]));
+	assert!(matches!(
+		hex_to_array::<2>("bridge transfer id", "0x01zz"),
+		Err(ConversionError::InvalidHex { name: "bridge transfer id", .. })
+	));
+
+	let recipient = RecipientAddress(vec![1; 20]);
+	assert_eq!(recipient.to_array::<20>(), Ok([1; 20]));
+	assert_eq!(
+		recipient.to_array::<32>(),
+		Err(ConversionError::InvalidLength { name: "recipient address", expected: 32, actual: 20 })
+	);
+}
+
+#[test]
+fn test_conversion_errors_of_contract_calls() {
+	let error = RecipientAddress(vec![]).to_array::<32>().unwrap_err();
+	assert!(matches!(
+		BridgeContractInitiatorError::from(error.clone()),
+		BridgeContractInitiatorError::Conversion(ConversionError::InvalidLength { .. })
+	));
+	assert_eq!(
+		BridgeContractCounterpartyError::from(error).to_string(),
+		"Invalid length 0 of recipient address, expected 32 bytes"
+	);
+}
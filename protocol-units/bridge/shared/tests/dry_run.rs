@@ -0,0 +1,75 @@
+use test_log::test;
+
+use bridge_shared::{
+	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
+	bridge_service::{active_swap::ActiveSwapConfig, BridgeServiceConfig},
+	dry_run::DryRunContract,
+	types::{
+		Amount, BridgeTransferId, HashLock, HashLockPreImage, InitiatorAddress, RecipientAddress,
+		TimeLock,
+	},
+};
+
+use crate::shared::{setup_bridge_service, BC1Address, BC1Hash, BC2Hash, SetupBridgeServiceResult};
+
+mod shared;
+
+#[test(tokio::test)]
+async fn test_dry_run_sends_no_transactions() {
+	// The blockchains are dropped, so any transaction sent by the clients fails
+	let SetupBridgeServiceResult(_, client_1, client_2, _, _) =
+		setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+	let (mut dry_run_1, mut dry_run_2) =
+		(DryRunContract::new(client_1, "B1"), DryRunContract::new(client_2, "B2"));
+
+	assert!(dry_run_1
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.is_ok());
+	assert!(BridgeContractInitiator::refund_bridge_transfer(
+		&mut dry_run_1,
+		BridgeTransferId(BC1Hash::from("transfer"))
+	)
+	.await
+	.is_ok());
+
+	let bridge_transfer_id = BridgeTransferId(BC2Hash::from("transfer"));
+	assert!(dry_run_2
+		.lock_bridge_transfer_assets(
+			bridge_transfer_id.clone(),
+			HashLock(BC2Hash::from("hash_lock")),
+			TimeLock(100),
+			RecipientAddress::from(BC1Address("recipient")),
+			Amount(1000),
+			None,
+		)
+		.await
+		.is_ok());
+	assert!(BridgeContractCounterparty::complete_bridge_transfer(
+		&mut dry_run_2,
+		bridge_transfer_id.clone(),
+		HashLockPreImage(b"hash_lock".to_vec()),
+	)
+	.await
+	.is_ok());
+	assert!(dry_run_2.abort_bridge_transfer(bridge_transfer_id.clone()).await.is_ok());
+	assert_eq!(BridgeContractInitiator::gas_spent(&dry_run_1), 0);
+	assert_eq!(BridgeContractCounterparty::gas_spent(&dry_run_2), 0);
+	assert!(dry_run_2.into_inner().abort_bridge_transfer(bridge_transfer_id).await.is_err());
+}
+
+#[test(tokio::test)]
+async fn test_dry_run_queries_the_contract() {
+	let SetupBridgeServiceResult(_, _, client_2, _, _) =
+		setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+	client_2.set_liquidity(Some(Amount(500)));
+
+	let mut dry_run = DryRunContract::new(client_2, "B2");
+	assert_eq!(dry_run.available_liquidity().await.unwrap(), Some(Amount(500)));
+}
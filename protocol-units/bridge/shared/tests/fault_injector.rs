@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use futures::{stream, StreamExt};
+use test_log::test;
+
+use bridge_shared::{
+	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
+	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
+	bridge_service::{active_swap::ActiveSwapConfig, BridgeServiceConfig},
+	types::{
+		Amount, Convert, HashLock, HashLockPreImage, InitiatorAddress, RecipientAddress, TimeLock,
+	},
+};
+
+use crate::shared::{
+	setup_faulty_bridge_service,
+	testing::fault_injector::{Fault, FaultInjector, FaultRates},
+	B2Client, BC1Address, BC1Hash, SetupFaultyBridgeServiceResult,
+};
+
+mod shared;
+
+async fn deliver(faults: &FaultInjector, events: u32) -> Vec<u32> {
+	faults.monitoring(stream::iter(1..=events)).collect().await
+}
+
+#[test(tokio::test)]
+async fn test_event_faults() {
+	let faults = |rates| FaultInjector::new(0, rates);
+
+	assert_eq!(
+		deliver(&faults(FaultRates { drop: 1.0, ..FaultRates::none() }), 4).await,
+		Vec::<u32>::new()
+	);
+	assert_eq!(
+		deliver(&faults(FaultRates { duplicate: 1.0, ..FaultRates::none() }), 2).await,
+		vec![1, 1, 2, 2]
+	);
+	assert_eq!(
+		deliver(&faults(FaultRates { reorder: 1.0, ..FaultRates::none() }), 5).await,
+		vec![2, 1, 4, 3, 5]
+	);
+	let delay =
+		FaultRates { delay: 1.0, max_delay: Duration::from_millis(5), ..FaultRates::none() };
+	assert_eq!(deliver(&faults(delay), 3).await, vec![1, 2, 3]);
+}
+
+#[test(tokio::test)]
+async fn test_fault_schedule_is_seeded() {
+	let rates = FaultRates {
+		drop: 0.1,
+		delay: 0.1,
+		duplicate: 0.2,
+		reorder: 0.2,
+		max_delay: Duration::from_millis(2),
+	};
+	let (faults_1, faults_2) = (FaultInjector::new(7, rates), FaultInjector::new(7, rates));
+
+	let events = deliver(&faults_1, 50).await;
+	assert_eq!(events, deliver(&faults_2, 50).await);
+	assert_eq!(faults_1.injected(), faults_2.injected());
+	assert!(faults_1.injected().contains(&Fault::Reorder));
+	assert_ne!(events, (1..=50).collect::<Vec<_>>());
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_with_faults() {
+	// Calls and events are delayed, duplicated and reordered, but never lost
+	let faults = FaultInjector::new(
+		42,
+		FaultRates {
+			delay: 0.2,
+			duplicate: 0.2,
+			reorder: 0.2,
+			max_delay: Duration::from_millis(50),
+			..FaultRates::none()
+		},
+	);
+	let SetupFaultyBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		mut blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_faulty_bridge_service(
+		BridgeServiceConfig { active_swap: ActiveSwapConfig::default() },
+		&faults,
+	);
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	blockchain_1_client
+		.initiate_bridge_transfer(
+			InitiatorAddress(BC1Address("initiator")),
+			RecipientAddress::from(BC1Address("recipient")),
+			HashLock(BC1Hash::from("hash_lock")),
+			TimeLock(100),
+			Amount(1000),
+		)
+		.await
+		.expect("initiate_bridge_transfer failed");
+
+	let mut bridge_transfer_id = None;
+	let mut completed = false;
+	while !completed {
+		let event = bridge_service.next().await.expect("No event");
+		if let Some(BridgeContractInitiatorEvent::Initiated(details)) = event.B1I_ContractEvent() {
+			bridge_transfer_id = Some(details.bridge_transfer_id.clone());
+		}
+		if let Some(BridgeContractCounterpartyEvent::Locked(details)) = event.B2C_ContractEvent() {
+			let id = bridge_transfer_id.clone().expect("Locked before initiated");
+			assert_eq!(details.bridge_transfer_id, Convert::convert(&id));
+			<B2Client as BridgeContractCounterparty>::complete_bridge_transfer(
+				&mut blockchain_2_client,
+				details.bridge_transfer_id.clone(),
+				HashLockPreImage(b"hash_lock".to_vec()),
+			)
+			.await
+			.expect("complete_bridge_transfer failed");
+		}
+		completed = matches!(
+			event.B1I_ContractEvent(),
+			Some(BridgeContractInitiatorEvent::Completed(id)) if Some(id) == bridge_transfer_id.as_ref()
+		);
+	}
+}
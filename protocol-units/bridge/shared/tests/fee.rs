@@ -0,0 +1,34 @@
+use bridge_shared::types::{Amount, Fee, FeeError};
+
+#[test]
+fn test_fee_deduction() {
+	let fee = Fee { flat: 10, basis_points: 25 };
+	assert_eq!(fee.deduct(Amount(10_000)), Ok((Amount(9_965), Amount(35))));
+	assert_eq!(Fee::default().deduct(Amount(1)), Ok((Amount(1), Amount(0))));
+	assert_eq!(fee.deduct(Amount(5)), Err(FeeError::ExceedsAmount { fee: 10, amount: 5 }));
+}
+
+#[test]
+fn test_fee_does_not_overflow() {
+	let fee = Fee { flat: 1, basis_points: Fee::MAX_BASIS_POINTS };
+	assert_eq!(
+		fee.deduct(Amount(u128::MAX / 2)),
+		Err(FeeError::ExceedsAmount { fee: u128::MAX / 2 + 1, amount: u128::MAX / 2 })
+	);
+}
+
+#[test]
+fn test_fee_deduction_near_the_maximum_amount() {
+	let fee = Fee { flat: 10, basis_points: 25 };
+	let expected = 850_705_917_302_346_158_658_436_518_579_420_528 + 10;
+	assert_eq!(fee.deduct(Amount(u128::MAX)), Ok((Amount(u128::MAX - expected), Amount(expected))));
+
+	let fee = Fee { flat: 0, basis_points: Fee::MAX_BASIS_POINTS };
+	assert_eq!(fee.deduct(Amount(u128::MAX)), Ok((Amount(0), Amount(u128::MAX))));
+
+	let fee = Fee { flat: 1, basis_points: Fee::MAX_BASIS_POINTS };
+	assert_eq!(fee.deduct(Amount(u128::MAX)), Err(FeeError::Overflow { amount: u128::MAX }));
+
+	let fee = Fee { flat: 0, basis_points: u64::MAX };
+	assert_eq!(fee.deduct(Amount(u128::MAX)), Err(FeeError::Overflow { amount: u128::MAX }));
+}
@@ -0,0 +1,50 @@
+use bridge_shared::types::{HashLock, HashLockAlgorithm, HashLockPreImage, Keccak256, Sha256};
+
+fn to_hex(hash: &[u8]) -> String {
+	hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[test]
+fn test_keccak256_hash_lock() {
+	let hash_lock = Keccak256::hash_lock(&HashLockPreImage(Vec::new()));
+	assert_eq!(
+		to_hex(&hash_lock.0),
+		"c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+	);
+}
+
+#[test]
+fn test_sha256_hash_lock() {
+	let hash_lock = Sha256::hash_lock(&HashLockPreImage(Vec::new()));
+	assert_eq!(
+		to_hex(&hash_lock.0),
+		"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+	);
+}
+
+#[test]
+fn test_verify_pre_image() {
+	let pre_image = HashLockPreImage(b"secret".to_vec());
+	let hash_lock = Keccak256::hash_lock(&pre_image);
+
+	assert!(Keccak256::verify(&pre_image, &hash_lock));
+	assert!(!Keccak256::verify(&HashLockPreImage(b"other".to_vec()), &hash_lock));
+	assert!(!Sha256::verify(&pre_image, &HashLock(hash_lock.0)));
+}
+
+#[test]
+fn test_generate_pre_image() {
+	let (pre_image, hash_lock) = HashLockPreImage::generate::<Keccak256>();
+
+	assert_eq!(pre_image.0.len(), HashLockPreImage::LENGTH);
+	assert!(Keccak256::verify(&pre_image, &hash_lock));
+	assert_ne!(HashLockPreImage::random(), pre_image);
+}
+
+#[test]
+fn test_pre_image_debug_is_redacted() {
+	let pre_image = HashLockPreImage(b"secret".to_vec());
+
+	assert_eq!(format!("{pre_image:?}"), "HashLockPreImage(<redacted>)");
+	assert_eq!(format!("{:?}", Some(&pre_image)), "Some(HashLockPreImage(<redacted>))");
+}
@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use test_log::test;
+
+use bridge_shared::{
+	bridge_contracts::BridgeContractInitiator,
+	bridge_service::{active_swap::ActiveSwapConfig, events::Event, BridgeServiceConfig},
+	types::{Amount, HashLock, InitiatorAddress, RecipientAddress, TimeLock},
+};
+
+use crate::shared::{
+	setup_bridge_service,
+	testing::blockchain::client::{CallConfig, MethodName},
+	BC1Address, BC1Hash, SetupBridgeServiceResult,
+};
+
+mod shared;
+
+const TRANSFERS: usize = 200;
+const WORKERS: usize = 8;
+const LOCK_LATENCY: Duration = Duration::from_millis(20);
+
+/// Locks the assets of hundreds of transfers initiated at once, each lock taking
+/// `LOCK_LATENCY`. The locks run on `WORKERS` workers: they are neither run one after the other,
+/// nor all at once.
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_bridge_service_locks_concurrently() {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		mut blockchain_1_client,
+		mut blockchain_2_client,
+		blockchain_1,
+		blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig {
+		active_swap: ActiveSwapConfig { workers: WORKERS, ..ActiveSwapConfig::default() },
+	});
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+
+	for call_index in 1..=TRANSFERS {
+		blockchain_2_client.set_call_config(
+			MethodName::LockBridgeTransferAssets,
+			call_index,
+			CallConfig { delay: Some(LOCK_LATENCY), ..CallConfig::default() },
+		);
+	}
+
+	let started = Instant::now();
+	for _ in 0..TRANSFERS {
+		blockchain_1_client
+			.initiate_bridge_transfer(
+				InitiatorAddress(BC1Address("initiator")),
+				RecipientAddress::from(BC1Address("recipient")),
+				HashLock(BC1Hash::from("hash_lock")),
+				TimeLock(100),
+				Amount(1000),
+			)
+			.await
+			.expect("initiate_bridge_transfer failed");
+	}
+
+	let (mut initiated, mut locked) = (0, 0);
+	while locked < TRANSFERS {
+		match bridge_service.next().await.expect("No event") {
+			Event::B1I(event) if event.contract_event().is_some() => initiated += 1,
+			Event::B2C(event) if event.contract_event().is_some() => locked += 1,
+			event => panic!("Unexpected event {event:?}"),
+		}
+	}
+	let elapsed = started.elapsed();
+	tracing::info!("Locked {TRANSFERS} transfers in {elapsed:?}");
+
+	assert_eq!(initiated, TRANSFERS);
+	let batches = (TRANSFERS / WORKERS) as u32;
+	assert!(elapsed >= LOCK_LATENCY * batches, "More than {WORKERS} locks ran at once");
+	assert!(elapsed < LOCK_LATENCY * TRANSFERS as u32 / 2, "Locks ran one after the other");
+}
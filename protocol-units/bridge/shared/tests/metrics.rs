@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use bridge_shared::metrics::{BridgeMetrics, TransferStep};
+
+#[test]
+fn test_encode_metrics() {
+	let metrics = BridgeMetrics::new().unwrap();
+	metrics.transfer_step("B1", TransferStep::Initiated);
+	metrics.transfer_step("B1", TransferStep::Initiated);
+	metrics.transfer_step("B2", TransferStep::Locked);
+	metrics.observe_step_latency("B2", TransferStep::Locked, Duration::from_secs(10));
+	metrics.rpc_error("B1");
+	metrics.transfer_rejected("B2", "transfer_limit");
+	metrics.insufficient_liquidity("B1");
+	metrics.gas_spent("B1", 21_000);
+	metrics.signer_balance("B2", 5_000, true);
+
+	let encoded = metrics.encode().unwrap();
+	assert!(encoded.contains(r#"bridge_transfers_total{chain="B1",step="initiated"} 2"#));
+	assert!(encoded.contains(r#"bridge_transfers_total{chain="B2",step="locked"} 1"#));
+	assert!(encoded.contains(
+		r#"bridge_transfer_step_latency_seconds_bucket{chain="B2",step="locked",le="15"} 1"#
+	));
+	assert!(encoded.contains(
+		r#"bridge_transfer_step_latency_seconds_bucket{chain="B2",step="locked",le="5"} 0"#
+	));
+	assert!(encoded.contains(r#"bridge_rpc_errors_total{chain="B1"} 1"#));
+	assert!(encoded
+		.contains(r#"bridge_transfers_rejected_total{chain="B2",reason="transfer_limit"} 1"#));
+	assert!(encoded.contains(r#"bridge_insufficient_liquidity_total{chain="B1"} 1"#));
+	assert!(encoded.contains(r#"bridge_gas_spent_total{chain="B1"} 21000"#));
+	assert!(encoded.contains(r#"bridge_signer_balance{chain="B2"} 5000"#));
+	assert!(encoded.contains(r#"bridge_signer_balance_low{chain="B2"} 1"#));
+}
+
+#[test]
+fn test_clones_share_metrics() {
+	let metrics = BridgeMetrics::new().unwrap();
+	metrics.clone().rpc_error("B2");
+
+	assert!(metrics.encode().unwrap().contains(r#"bridge_rpc_errors_total{chain="B2"} 1"#));
+}
@@ -0,0 +1,53 @@
+use bridge_shared::{
+	pre_image::{PreImageError, PreImageVault},
+	transfer_store::InMemoryTransferStateStore,
+	types::{BridgeTransferId, HashLockPreImage},
+};
+
+const KEY: &str = "0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+#[test]
+fn test_plain_vault() {
+	let vault = PreImageVault::plain();
+	let pre_image = HashLockPreImage(b"secret".to_vec());
+
+	let sealed = vault.seal(&pre_image).unwrap();
+	assert_eq!(sealed, b"\0secret");
+	assert_eq!(vault.open(&sealed).unwrap(), pre_image);
+	assert_eq!(vault.open(&[]), Err(PreImageError::InvalidFormat));
+}
+
+#[test]
+fn test_encrypted_vault() {
+	let vault = PreImageVault::try_from_hex_key(KEY).unwrap();
+	let pre_image = HashLockPreImage::random();
+
+	let sealed = vault.seal(&pre_image).unwrap();
+	assert!(!sealed.windows(pre_image.0.len()).any(|window| window == pre_image.0.as_slice()));
+	assert_ne!(vault.seal(&pre_image).unwrap(), sealed);
+	assert_eq!(vault.open(&sealed).unwrap(), pre_image);
+
+	let mut tampered = sealed.clone();
+	*tampered.last_mut().unwrap() ^= 1;
+	assert_eq!(vault.open(&tampered), Err(PreImageError::Decryption));
+	assert_eq!(PreImageVault::encrypted(&[7; 32]).open(&sealed), Err(PreImageError::Decryption));
+	assert_eq!(PreImageVault::plain().open(&sealed), Err(PreImageError::MissingKey));
+}
+
+#[test]
+fn test_invalid_key() {
+	assert_eq!(PreImageVault::try_from_hex_key("0x0102").unwrap_err(), PreImageError::InvalidKey);
+	assert_eq!(PreImageVault::try_from_hex_key("key").unwrap_err(), PreImageError::InvalidKey);
+}
+
+#[tokio::test]
+async fn test_reveal_stored_secret() {
+	let store = InMemoryTransferStateStore::new();
+	let vault = PreImageVault::try_from_hex_key(KEY).unwrap();
+	let bridge_transfer_id = BridgeTransferId("transfer_id");
+	let pre_image = HashLockPreImage::random();
+
+	assert_eq!(vault.reveal(&store, &bridge_transfer_id).await, Ok(None));
+	vault.store(&store, bridge_transfer_id.clone(), &pre_image).await.unwrap();
+	assert_eq!(vault.reveal(&store, &bridge_transfer_id).await, Ok(Some(pre_image)));
+}
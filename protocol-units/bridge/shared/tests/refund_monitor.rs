@@ -0,0 +1,94 @@
+use bridge_shared::{
+	refund_monitor::{RefundMonitor, RefundMonitorEvent, RefundSide},
+	types::{BridgeTransferId, TimeLock},
+};
+use futures::StreamExt;
+use std::task::{Context, Poll};
+
+#[test]
+fn test_refundable_after_time_lock_expired() {
+	let mut monitor = RefundMonitor::new();
+	let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+	monitor.track(RefundSide::Initiator, BridgeTransferId("initiated"), TimeLock(100));
+	monitor.track(RefundSide::Counterparty, BridgeTransferId("locked"), TimeLock(50));
+
+	monitor.update_time(50);
+	assert_eq!(monitor.poll_next_unpin(&mut cx), Poll::Pending);
+
+	monitor.update_time(51);
+	assert_eq!(
+		monitor.poll_next_unpin(&mut cx),
+		Poll::Ready(Some(RefundMonitorEvent::Refundable(
+			RefundSide::Counterparty,
+			BridgeTransferId("locked")
+		)))
+	);
+	assert_eq!(monitor.poll_next_unpin(&mut cx), Poll::Pending);
+	assert!(!monitor.is_tracked(RefundSide::Counterparty, &BridgeTransferId("locked")));
+
+	monitor.update_time(101);
+	assert_eq!(
+		monitor.poll_next_unpin(&mut cx),
+		Poll::Ready(Some(RefundMonitorEvent::Refundable(
+			RefundSide::Initiator,
+			BridgeTransferId("initiated")
+		)))
+	);
+}
+
+#[test]
+fn test_untracked_transfer_is_not_refundable() {
+	let mut monitor = RefundMonitor::new();
+	let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+	monitor.track(RefundSide::Initiator, BridgeTransferId("completed"), TimeLock(10));
+	assert_eq!(
+		monitor.untrack(RefundSide::Initiator, &BridgeTransferId("completed")),
+		Some(TimeLock(10))
+	);
+
+	monitor.update_time(100);
+	assert_eq!(monitor.poll_next_unpin(&mut cx), Poll::Pending);
+}
+
+#[test]
+fn test_expired_transfer_reported_on_track() {
+	let mut monitor = RefundMonitor::new();
+	let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+	monitor.update_time(100);
+	monitor.track(RefundSide::Initiator, BridgeTransferId("expired"), TimeLock(10));
+
+	assert_eq!(
+		monitor.poll_next_unpin(&mut cx),
+		Poll::Ready(Some(RefundMonitorEvent::Refundable(
+			RefundSide::Initiator,
+			BridgeTransferId("expired")
+		)))
+	);
+}
+
+#[test]
+fn test_time_lock_expires_relative_to_observation() {
+	let mut monitor = RefundMonitor::new();
+	let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+	// The transfer is observed at height 1000 with a time lock of 100 blocks
+	monitor.update_time(1_000);
+	let expiry = monitor.expiry(&TimeLock(100));
+	assert_eq!(expiry, TimeLock(1_100));
+	monitor.track(RefundSide::Initiator, BridgeTransferId("initiated"), expiry);
+
+	monitor.update_time(1_100);
+	assert_eq!(monitor.poll_next_unpin(&mut cx), Poll::Pending);
+
+	monitor.update_time(1_101);
+	assert_eq!(
+		monitor.poll_next_unpin(&mut cx),
+		Poll::Ready(Some(RefundMonitorEvent::Refundable(
+			RefundSide::Initiator,
+			BridgeTransferId("initiated")
+		)))
+	);
+}
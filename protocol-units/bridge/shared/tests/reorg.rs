@@ -0,0 +1,36 @@
+use bridge_shared::{reorg::ReorgTracker, types::BridgeTransferId};
+
+#[test]
+fn test_reorged_transfers_are_reverted() {
+	let mut tracker = ReorgTracker::new(10);
+	tracker.track(5, "a5", BridgeTransferId("kept"));
+	tracker.track(6, "a6", BridgeTransferId("reorged_1"));
+	tracker.track(6, "a6", BridgeTransferId("reorged_2"));
+	tracker.track(7, "a7", BridgeTransferId("dropped"));
+
+	let canonical = |block_number| match block_number {
+		5 => Some("a5"),
+		6 => Some("b6"),
+		_ => None,
+	};
+	assert_eq!(
+		tracker.new_head(6, canonical),
+		vec![
+			BridgeTransferId("reorged_1"),
+			BridgeTransferId("reorged_2"),
+			BridgeTransferId("dropped")
+		]
+	);
+	assert_eq!(tracker.tracked_blocks().collect::<Vec<_>>(), vec![5]);
+}
+
+#[test]
+fn test_final_blocks_are_no_longer_tracked() {
+	let mut tracker = ReorgTracker::new(2);
+	tracker.track(5, "a5", BridgeTransferId("final"));
+	tracker.track(7, "a7", BridgeTransferId("recent"));
+
+	let canonical = |block_number| Some(if block_number == 5 { "a5" } else { "a7" });
+	assert!(tracker.new_head(8, canonical).is_empty());
+	assert_eq!(tracker.tracked_blocks().collect::<Vec<_>>(), vec![7]);
+}
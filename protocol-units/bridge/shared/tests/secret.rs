@@ -0,0 +1,72 @@
+use bridge_shared::secret::{SecretLoader, SecretLoaderError};
+
+// Test vector of the Web3 Secret Storage Definition.
+const KEYSTORE: &str = r#"{
+	"crypto": {
+		"cipher": "aes-128-ctr",
+		"cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+		"ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+		"kdf": "pbkdf2",
+		"kdfparams": {
+			"c": 262144,
+			"dklen": 32,
+			"prf": "hmac-sha256",
+			"salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+		},
+		"mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+	},
+	"id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+	"version": 3
+}"#;
+
+fn write_temp_file(name: &str, content: &str) -> std::path::PathBuf {
+	let path = std::env::temp_dir().join(format!("bridge-secret-{}-{name}", std::process::id()));
+	std::fs::write(&path, content).unwrap();
+	path
+}
+
+#[test]
+fn test_load_plain_env_and_file_secrets() {
+	let loader = SecretLoader::new();
+	assert_eq!(loader.load("0x1234").unwrap(), "0x1234");
+
+	std::env::set_var("BRIDGE_SECRET_TEST_KEY", "0xabcd");
+	assert_eq!(loader.load("env:BRIDGE_SECRET_TEST_KEY").unwrap(), "0xabcd");
+	assert!(matches!(
+		loader.load("env:BRIDGE_SECRET_TEST_UNSET"),
+		Err(SecretLoaderError::MissingEnvVar(_))
+	));
+
+	let path = write_temp_file("key", "0xef01\n");
+	assert_eq!(loader.load(&format!("file:{}", path.display())).unwrap(), "0xef01");
+	std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_secret_references() {
+	for secret in ["env:KEY", "file:/etc/movement/key", "keystore:/etc/movement/key.json"] {
+		assert!(SecretLoader::is_reference(secret));
+	}
+	assert!(!SecretLoader::is_reference("0x1234"));
+}
+
+#[test]
+fn test_load_keystore_secret() {
+	let keystore = format!("keystore:{}", write_temp_file("keystore.json", KEYSTORE).display());
+
+	assert!(matches!(
+		SecretLoader::new().load(&keystore),
+		Err(SecretLoaderError::MissingKeystorePassword(_))
+	));
+
+	std::env::set_var("BRIDGE_SECRET_TEST_PASSWORD", "testpassword");
+	let loader = SecretLoader::new().with_keystore_password("env:BRIDGE_SECRET_TEST_PASSWORD");
+	assert_eq!(
+		loader.load(&keystore).unwrap(),
+		"0x7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d"
+	);
+	assert!(matches!(
+		SecretLoader::new().with_keystore_password("wrong").load(&keystore),
+		Err(SecretLoaderError::Keystore(..))
+	));
+}
@@ -7,7 +7,10 @@ use bridge_shared::{
 		BridgeContractInitiatorEvent, BridgeContractInitiatorMonitoring,
 	},
 	bridge_service::{BridgeService, BridgeServiceConfig},
-	types::{Convert, GenUniqueHash, HashLockPreImage, RecipientAddress},
+	types::{
+		Convert, GenUniqueHash, HashLock, HashLockAlgorithm, HashLockPreImage, Keccak256,
+		RecipientAddress,
+	},
 };
 
 use futures::{channel::mpsc::UnboundedReceiver, Stream, StreamExt};
@@ -15,7 +18,6 @@ use rand::Rng;
 use rand::SeedableRng;
 use std::{
 	fmt::{Debug, Formatter},
-	hash::{DefaultHasher, Hash, Hasher},
 	pin::Pin,
 	task::{Context, Poll},
 };
@@ -25,6 +27,7 @@ pub mod testing;
 use testing::{
 	blockchain::AbstractBlockchainEvent,
 	blockchain::{AbstractBlockchain, AbstractBlockchainClient},
+	fault_injector::{FaultInjector, FaultyContract, FaultyMonitoring},
 	rng::{RngSeededClone, TestRng},
 };
 
@@ -33,22 +36,30 @@ use crate::shared::testing::blockchain::{
 	initiator_contract::SmartContractInitiatorEvent,
 };
 
-pub fn hash_static_string(pre_image: &'static str) -> [u8; 8] {
+pub fn hash_static_string(pre_image: &'static str) -> [u8; 32] {
 	hash_vec_u8(pre_image.as_bytes())
 }
 
-pub fn hash_vec_u8(data: &[u8]) -> [u8; 8] {
-	let mut hasher = DefaultHasher::new();
-	data.hash(&mut hasher);
-	hasher.finish().to_be_bytes()
+pub fn hash_vec_u8(data: &[u8]) -> [u8; 32] {
+	Keccak256::hash_lock(&HashLockPreImage(data.to_vec())).0
+}
+
+fn fmt_hash(name: &str, hash: &[u8; 32], f: &mut Formatter<'_>) -> std::fmt::Result {
+	write!(f, "{name}(")?;
+	for byte in &hash[..8] {
+		write!(f, "{byte:02x}")?;
+	}
+	write!(f, ")")
 }
 
 #[derive(Clone, Eq, PartialEq, Hash)]
-pub struct BC1Hash([u8; 8]);
+pub struct BC1Hash([u8; 32]);
 
-impl From<HashLockPreImage> for BC1Hash {
-	fn from(value: HashLockPreImage) -> Self {
-		Self(hash_vec_u8(&value.0))
+impl HashLockAlgorithm for BC1Hash {
+	type Hash = Self;
+
+	fn hash_lock(pre_image: &HashLockPreImage) -> HashLock<Self> {
+		HashLock(Self(hash_vec_u8(&pre_image.0)))
 	}
 }
 
@@ -66,16 +77,18 @@ impl GenUniqueHash for BC1Hash {
 
 impl Debug for BC1Hash {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		write!(f, "Bc1Hash({:02x})", u64::from_be_bytes(self.0))
+		fmt_hash("BC1Hash", &self.0, f)
 	}
 }
 
 #[derive(Clone, Eq, PartialEq, Hash)]
-pub struct BC2Hash([u8; 8]);
+pub struct BC2Hash([u8; 32]);
+
+impl HashLockAlgorithm for BC2Hash {
+	type Hash = Self;
 
-impl From<HashLockPreImage> for BC2Hash {
-	fn from(value: HashLockPreImage) -> Self {
-		Self(hash_vec_u8(&value.0))
+	fn hash_lock(pre_image: &HashLockPreImage) -> HashLock<Self> {
+		HashLock(Self(hash_vec_u8(&pre_image.0)))
 	}
 }
 
@@ -93,7 +106,7 @@ impl From<&'static str> for BC2Hash {
 
 impl Debug for BC2Hash {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		write!(f, "BC2Hash({:02x})", u64::from_be_bytes(self.0))
+		fmt_hash("BC2Hash", &self.0, f)
 	}
 }
 
@@ -186,32 +199,40 @@ impl<A: Debug, H: Debug> Stream for InitiatorContractMonitoring<A, H> {
 
 	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
 		let this = self.get_mut();
-		if let Poll::Ready(Some(AbstractBlockchainEvent::InitiatorContractEvent(contract_result))) =
-			this.listener.poll_next_unpin(cx)
-		{
+		// Only listen to the initiator contract events, the other events are skipped
+		loop {
+			let contract_result = match this.listener.poll_next_unpin(cx) {
+				Poll::Ready(Some(AbstractBlockchainEvent::InitiatorContractEvent(result))) => {
+					result
+				}
+				Poll::Ready(Some(_)) => continue,
+				Poll::Ready(None) => return Poll::Ready(None),
+				Poll::Pending => return Poll::Pending,
+			};
 			tracing::trace!(
 				"InitiatorContractMonitoring: Received contract event: {:?}",
 				contract_result
 			);
-			// Only listen to the initiator contract events
 			use SmartContractInitiatorEvent::*;
 			match contract_result {
-				Ok(contract_event) => match contract_event {
-					InitiatedBridgeTransfer(details) => {
-						return Poll::Ready(Some(BridgeContractInitiatorEvent::Initiated(details)))
-					}
-					CompletedBridgeTransfer(bridge_transfer_id, _) => {
-						return Poll::Ready(Some(BridgeContractInitiatorEvent::Completed(
-							bridge_transfer_id,
-						)))
-					}
-				},
+				Ok(InitiatedBridgeTransfer(details)) => {
+					return Poll::Ready(Some(BridgeContractInitiatorEvent::Initiated(details)))
+				}
+				Ok(CompletedBridgeTransfer(bridge_transfer_id, _)) => {
+					return Poll::Ready(Some(BridgeContractInitiatorEvent::Completed(
+						bridge_transfer_id,
+					)))
+				}
+				Ok(RefundedBridgeTransfer(bridge_transfer_id)) => {
+					return Poll::Ready(Some(BridgeContractInitiatorEvent::Refunded(
+						bridge_transfer_id,
+					)))
+				}
 				Err(_) => {
 					// Handle error
 				}
 			}
 		}
-		Poll::Pending
 	}
 }
 
@@ -237,32 +258,38 @@ impl<A: Debug, H: Debug> Stream for CounterpartyContractMonitoring<A, H> {
 
 	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
 		let this = self.get_mut();
-		if let Poll::Ready(Some(AbstractBlockchainEvent::CounterpartyContractEvent(
-			contract_result,
-		))) = this.listener.poll_next_unpin(cx)
-		{
+		// Only listen to the counterparty contract events, the other events are skipped
+		loop {
+			let contract_result = match this.listener.poll_next_unpin(cx) {
+				Poll::Ready(Some(AbstractBlockchainEvent::CounterpartyContractEvent(result))) => {
+					result
+				}
+				Poll::Ready(Some(_)) => continue,
+				Poll::Ready(None) => return Poll::Ready(None),
+				Poll::Pending => return Poll::Pending,
+			};
 			tracing::trace!(
 				"CounterpartyContractMonitoring: Received contract event: {:?}",
 				contract_result
 			);
 			use SmartContractCounterpartyEvent::*;
 			match contract_result {
-				Ok(contract_event) => match contract_event {
-					LockedBridgeTransfer(details) => {
-						return Poll::Ready(Some(BridgeContractCounterpartyEvent::Locked(details)))
-					}
-					CompletedBridgeTransfer(details) => {
-						return Poll::Ready(Some(BridgeContractCounterpartyEvent::Completed(
-							details,
-						)))
-					}
-				},
+				Ok(LockedBridgeTransfer(details)) => {
+					return Poll::Ready(Some(BridgeContractCounterpartyEvent::Locked(details)))
+				}
+				Ok(CompletedBridgeTransfer(details)) => {
+					return Poll::Ready(Some(BridgeContractCounterpartyEvent::Completed(details)))
+				}
+				Ok(AbortedBridgeTransfer(bridge_transfer_id)) => {
+					return Poll::Ready(Some(BridgeContractCounterpartyEvent::Aborted(
+						bridge_transfer_id,
+					)))
+				}
 				Err(_) => {
 					// Handle error
 				}
 			}
 		}
-		Poll::Pending
 	}
 }
 
@@ -346,3 +373,71 @@ pub fn setup_bridge_service(config: BridgeServiceConfig) -> SetupBridgeServiceRe
 		blockchain_2,
 	)
 }
+
+pub type FaultyB1Service = AbstractBlockchainService<
+	FaultyContract<B1Client>,
+	FaultyMonitoring<InitiatorContractMonitoring<BC1Address, BC1Hash>>,
+	FaultyContract<B1Client>,
+	FaultyMonitoring<CounterpartyContractMonitoring<BC1Address, BC1Hash>>,
+	BC1Address,
+	BC1Hash,
+>;
+
+pub type FaultyB2Service = AbstractBlockchainService<
+	FaultyContract<B2Client>,
+	FaultyMonitoring<InitiatorContractMonitoring<BC2Address, BC2Hash>>,
+	FaultyContract<B2Client>,
+	FaultyMonitoring<CounterpartyContractMonitoring<BC2Address, BC2Hash>>,
+	BC2Address,
+	BC2Hash,
+>;
+
+pub struct SetupFaultyBridgeServiceResult(
+	pub BridgeService<FaultyB1Service, FaultyB2Service>,
+	pub B1Client,
+	pub B2Client,
+	pub AbstractBlockchain<BC1Address, BC1Hash, TestRng>,
+	pub AbstractBlockchain<BC2Address, BC2Hash, TestRng>,
+);
+
+/// Sets up a bridge service whose contract calls and monitoring go through `faults`. The
+/// returned clients, used by the test as the users, are not faulty.
+pub fn setup_faulty_bridge_service(
+	config: BridgeServiceConfig,
+	faults: &FaultInjector,
+) -> SetupFaultyBridgeServiceResult {
+	let mut rng = TestRng::from_seed([0u8; 32]);
+
+	let mut blockchain_1 =
+		AbstractBlockchain::<BC1Address, BC1Hash, _>::new(rng.seeded_clone(), "Blockchain1");
+	let mut blockchain_2 =
+		AbstractBlockchain::<BC2Address, BC2Hash, _>::new(rng.seeded_clone(), "Blockchain2");
+	let client_1 =
+		AbstractBlockchainClient::new(blockchain_1.connection(), rng.seeded_clone(), 0.0, 0.00);
+	let client_2 =
+		AbstractBlockchainClient::new(blockchain_2.connection(), rng.seeded_clone(), 0.0, 0.00);
+
+	let blockchain_1_service = AbstractBlockchainService {
+		initiator_contract: faults.contract(client_1.clone()),
+		initiator_monitoring: faults
+			.monitoring(InitiatorContractMonitoring::build(blockchain_1.add_event_listener())),
+		counterparty_contract: faults.contract(client_1.clone()),
+		counterparty_monitoring: faults
+			.monitoring(CounterpartyContractMonitoring::build(blockchain_1.add_event_listener())),
+		_phantom: Default::default(),
+	};
+
+	let blockchain_2_service = AbstractBlockchainService {
+		initiator_contract: faults.contract(client_2.clone()),
+		initiator_monitoring: faults
+			.monitoring(InitiatorContractMonitoring::build(blockchain_2.add_event_listener())),
+		counterparty_contract: faults.contract(client_2.clone()),
+		counterparty_monitoring: faults
+			.monitoring(CounterpartyContractMonitoring::build(blockchain_2.add_event_listener())),
+		_phantom: Default::default(),
+	};
+
+	let bridge_service = BridgeService::new(blockchain_1_service, blockchain_2_service, config);
+
+	SetupFaultyBridgeServiceResult(bridge_service, client_1, client_2, blockchain_1, blockchain_2)
+}
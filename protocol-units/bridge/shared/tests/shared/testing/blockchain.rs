@@ -13,9 +13,9 @@ pub use self::{
 };
 use self::{counterparty_contract::SCCResult, initiator_contract::SCIResult};
 
-use super::rng::RngSeededClone;
+use super::{clock::Clock, rng::RngSeededClone};
 use bridge_shared::types::{
-	Amount, BridgeAddressType, BridgeHashType, GenUniqueHash, HashLockPreImage, RecipientAddress,
+	Amount, BridgeAddressType, BridgeHashType, GenUniqueHash, HashLockAlgorithm, RecipientAddress,
 };
 
 pub mod client;
@@ -35,7 +35,7 @@ pub enum AbstractBlockchainEvent<A, H> {
 	Noop,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Transaction<A, H> {
 	Initiator(InitiatorCall<A, H>),
 	Counterparty(CounterpartyCall<H>),
@@ -44,7 +44,8 @@ pub enum Transaction<A, H> {
 #[derive(Debug)]
 pub struct AbstractBlockchain<A, H, R> {
 	pub name: String,
-	pub time: u64,
+	/// Time of the blocks, against which the time locks of the contracts expire.
+	pub clock: Clock,
 	pub accounts: HashMap<A, Amount>,
 	pub events: Vec<AbstractBlockchainEvent<A, H>>,
 	pub rng: R,
@@ -56,6 +57,9 @@ pub struct AbstractBlockchain<A, H, R> {
 	pub transaction_receiver: mpsc::UnboundedReceiver<Transaction<A, H>>,
 
 	pub event_listeners: Vec<mpsc::UnboundedSender<AbstractBlockchainEvent<A, H>>>,
+	/// Sends every event twice to the listeners, as a monitoring resubscribing or replaying
+	/// blocks would.
+	pub duplicate_delivery: bool,
 
 	waker: AtomicWaker,
 
@@ -67,7 +71,7 @@ where
 	A: BridgeAddressType + From<RecipientAddress>,
 	H: BridgeHashType + GenUniqueHash,
 	R: RngSeededClone,
-	H: From<HashLockPreImage>,
+	H: HashLockAlgorithm<Hash = H>,
 {
 	pub fn new(mut rng: R, name: impl Into<String>) -> Self {
 		let accounts = HashMap::new();
@@ -77,7 +81,7 @@ where
 
 		Self {
 			name: name.into(),
-			time: 0,
+			clock: Clock::default(),
 			accounts,
 			events,
 			initiator_contract: SmartContractInitiator::new(rng.seeded_clone()),
@@ -86,6 +90,7 @@ where
 			transaction_sender: event_sender,
 			transaction_receiver: event_receiver,
 			event_listeners,
+			duplicate_delivery: false,
 			waker: AtomicWaker::new(),
 			_phantom: std::marker::PhantomData,
 		}
@@ -97,8 +102,13 @@ where
 		receiver
 	}
 
+	/// Clock of the blockchain, for the test to advance once the blockchain is spawned.
+	pub fn clock(&self) -> Clock {
+		self.clock.clone()
+	}
+
 	pub fn forward_time(&mut self, duration: u64) {
-		self.time += duration;
+		self.clock.advance(duration);
 	}
 
 	pub fn add_account(&mut self, address: A, amount: Amount) {
@@ -109,6 +119,25 @@ where
 		self.accounts.get(address)
 	}
 
+	/// Funds the relayer's account, debited the assets locked on the counterparty contract and
+	/// credited the assets of the transfers completed on the initiator contract. The initiators
+	/// are debited the assets of their transfers from then on.
+	pub fn add_relayer(&mut self, address: A, amount: Amount) {
+		self.initiator_contract.relayer = Some(address.clone());
+		self.counterparty_contract.locker = Some(address.clone());
+		self.add_account(address, amount);
+	}
+
+	/// Assets of the accounts and held in escrow by the contracts, which the transfers don't
+	/// change.
+	pub fn total_balance(&self) -> Amount {
+		Amount(
+			self.accounts.values().map(|amount| **amount).sum::<u128>()
+				+ *self.initiator_contract.escrow
+				+ *self.counterparty_contract.escrow,
+		)
+	}
+
 	pub fn connection(&self) -> mpsc::UnboundedSender<Transaction<A, H>> {
 		self.transaction_sender.clone()
 	}
@@ -132,7 +161,7 @@ where
 	A: BridgeAddressType + From<RecipientAddress>,
 	H: BridgeHashType + GenUniqueHash,
 	R: Rng + Unpin,
-	H: From<HashLockPreImage>,
+	H: HashLockAlgorithm<Hash = H>,
 {
 	type Output = ();
 
@@ -151,12 +180,83 @@ where
 	}
 }
 
+impl<A, H, R> AbstractBlockchain<A, H, R>
+where
+	A: BridgeAddressType + From<RecipientAddress>,
+	H: BridgeHashType + GenUniqueHash,
+	R: Rng,
+	H: HashLockAlgorithm<Hash = H>,
+{
+	/// Executes a transaction on the contracts at the current time, and returns its event.
+	pub fn apply_transaction(
+		&mut self,
+		transaction: Transaction<A, H>,
+	) -> AbstractBlockchainEvent<A, H> {
+		let now = self.clock.now();
+		match transaction {
+			Transaction::Initiator(call) => {
+				AbstractBlockchainEvent::InitiatorContractEvent(match call {
+					InitiatorCall::InitiateBridgeTransfer(
+						initiator_address,
+						recipient_address,
+						amount,
+						time_lock,
+						hash_lock,
+					) => self.initiator_contract.initiate_bridge_transfer(
+						&mut self.accounts,
+						initiator_address,
+						recipient_address,
+						amount,
+						time_lock,
+						hash_lock,
+					),
+					InitiatorCall::CompleteBridgeTransfer(bridge_transfer_id, secret) => self
+						.initiator_contract
+						.complete_bridge_transfer(&mut self.accounts, bridge_transfer_id, secret),
+					InitiatorCall::RefundBridgeTransfer(bridge_transfer_id) => self
+						.initiator_contract
+						.refund_bridge_transfer(&mut self.accounts, bridge_transfer_id, now),
+				})
+			}
+			Transaction::Counterparty(call) => {
+				AbstractBlockchainEvent::CounterpartyContractEvent(match call {
+					CounterpartyCall::LockBridgeTransfer(
+						bridge_transfer_id,
+						hash_lock,
+						time_lock,
+						recipient_address,
+						amount,
+					) => self.counterparty_contract.lock_bridge_transfer(
+						&mut self.accounts,
+						bridge_transfer_id,
+						hash_lock,
+						time_lock,
+						recipient_address,
+						amount,
+					),
+					CounterpartyCall::CompleteBridgeTransfer(bridge_transfer_id, pre_image) => {
+						self.counterparty_contract.complete_bridge_transfer(
+							&mut self.accounts,
+							&bridge_transfer_id,
+							pre_image,
+							now,
+						)
+					}
+					CounterpartyCall::AbortBridgeTransfer(bridge_transfer_id) => self
+						.counterparty_contract
+						.abort_bridge_transfer(&mut self.accounts, &bridge_transfer_id, now),
+				})
+			}
+		}
+	}
+}
+
 impl<A, H, R> Stream for AbstractBlockchain<A, H, R>
 where
 	A: BridgeAddressType + From<RecipientAddress>,
 	H: BridgeHashType + GenUniqueHash,
 	R: Rng + Unpin,
-	H: From<HashLockPreImage>,
+	H: HashLockAlgorithm<Hash = H>,
 {
 	type Item = AbstractBlockchainEvent<A, H>;
 
@@ -171,64 +271,8 @@ where
 					this.name,
 					transaction
 				);
-				match transaction {
-					Transaction::Initiator(call) => match call {
-						InitiatorCall::InitiateBridgeTransfer(
-							initiator_address,
-							recipient_address,
-							amount,
-							time_lock,
-							hash_lock,
-						) => {
-							this.events.push(AbstractBlockchainEvent::InitiatorContractEvent(
-								this.initiator_contract.initiate_bridge_transfer(
-									initiator_address.clone(),
-									recipient_address.clone(),
-									amount,
-									time_lock.clone(),
-									hash_lock.clone(),
-								),
-							));
-						}
-						InitiatorCall::CompleteBridgeTransfer(bridge_transfer_id, secret) => {
-							this.events.push(AbstractBlockchainEvent::InitiatorContractEvent(
-								this.initiator_contract.complete_bridge_transfer(
-									&mut this.accounts,
-									bridge_transfer_id.clone(),
-									secret.clone(),
-								),
-							));
-						}
-					},
-					Transaction::Counterparty(call) => match call {
-						CounterpartyCall::LockBridgeTransfer(
-							bridge_transfer_id,
-							hash_lock,
-							time_lock,
-							recipient_address,
-							amount,
-						) => {
-							this.events.push(AbstractBlockchainEvent::CounterpartyContractEvent(
-								this.counterparty_contract.lock_bridge_transfer(
-									bridge_transfer_id.clone(),
-									hash_lock.clone(),
-									time_lock.clone(),
-									recipient_address.clone(),
-									amount,
-								),
-							));
-						}
-						CounterpartyCall::CompleteBridgeTransfer(bridge_transfer_id, pre_image) => {
-							this.events.push(AbstractBlockchainEvent::CounterpartyContractEvent(
-								this.counterparty_contract.complete_bridge_transfer(
-									&mut this.accounts,
-									&bridge_transfer_id,
-									pre_image,
-								),
-							));
-						}
-					},
-				}
+				let event = this.apply_transaction(transaction);
+				this.events.push(event);
 			}
 			Poll::Ready(None) => {
 				tracing::warn!("AbstractBlockchain[{}]: Transaction receiver dropped", this.name);
@@ -245,6 +289,9 @@ where
 			for listener in &mut this.event_listeners {
 				tracing::trace!("AbstractBlockchain[{}]: Sending event to listener", this.name);
 				listener.unbounded_send(event.clone()).expect("listener dropped");
+				if this.duplicate_delivery {
+					listener.unbounded_send(event.clone()).expect("listener dropped");
+				}
 			}
 
 			tracing::trace!("AbstractBlockchain[{}]: Poll::Ready({:?})", this.name, event);
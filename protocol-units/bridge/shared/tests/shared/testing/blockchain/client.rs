@@ -7,12 +7,13 @@ use bridge_shared::{
 	},
 	types::{
 		Amount, BridgeAddressType, BridgeHashType, BridgeTransferDetails, BridgeTransferId,
-		HashLock, HashLockPreImage, InitiatorAddress, RecipientAddress, TimeLock,
+		HashLock, HashLockPreImage, InitiatorAddress, LockDetails, RecipientAddress, TimeLock,
+		TokenAddress,
 	},
 };
 use dashmap::DashMap;
 use futures::channel::mpsc;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 use crate::shared::testing::rng::RngSeededClone;
@@ -144,6 +145,8 @@ pub struct AbstractBlockchainClient<A, H, R> {
 	pub failure_rate: f64,
 	pub false_positive_rate: f64,
 	pub call_configs: Arc<DashMap<MethodName, Vec<(usize, CallConfig)>>>,
+	/// Liquidity reported by the counterparty contract, unknown when unset.
+	pub liquidity: Arc<Mutex<Option<Amount>>>,
 }
 
 impl<A, H, R> AbstractBlockchainClient<A, H, R>
@@ -164,9 +167,14 @@ where
 			failure_rate,
 			false_positive_rate,
 			call_configs: Default::default(),
+			liquidity: Default::default(),
 		}
 	}
 
+	pub fn set_liquidity(&self, liquidity: Option<Amount>) {
+		*self.liquidity.lock().expect("liquidity lock poisoned") = liquidity;
+	}
+
 	pub fn send_transaction(
 		&mut self,
 		transaction: Transaction<A, H>,
@@ -255,17 +263,32 @@ where
 
 	async fn refund_bridge_transfer(
 		&mut self,
-		_bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
 	) -> BridgeContractInitiatorResult<()> {
-		unimplemented!()
+		self.register_call(MethodName::RefundBridgeTransfer);
+		if let Some(config) = self.have_call_config(MethodName::RefundBridgeTransfer) {
+			if let Some(delay) = config.delay {
+				tokio::time::sleep(delay).await;
+			}
+			config.get_initiator_error()?;
+		}
+
+		let transaction =
+			Transaction::Initiator(InitiatorCall::RefundBridgeTransfer(bridge_transfer_id));
+		self.send_transaction(transaction)
+			.map_err(BridgeContractInitiatorError::generic)
 	}
 
 	async fn get_bridge_transfer_details(
 		&mut self,
 		_bridge_transfer_id: BridgeTransferId<Self::Hash>,
-	) -> BridgeContractInitiatorResult<Option<BridgeTransferDetails<Self::Hash, Self::Address>>> {
+	) -> BridgeContractInitiatorResult<Option<BridgeTransferDetails<Self::Address, Self::Hash>>> {
 		unimplemented!()
 	}
+
+	fn gas_spent(&self) -> u64 {
+		0
+	}
 }
 
 #[async_trait]
@@ -278,6 +301,10 @@ where
 	type Address = A;
 	type Hash = H;
 
+	async fn available_liquidity(&mut self) -> BridgeContractCounterpartyResult<Option<Amount>> {
+		Ok(*self.liquidity.lock().expect("liquidity lock poisoned"))
+	}
+
 	async fn lock_bridge_transfer_assets(
 		&mut self,
 		bridge_transfer_id: BridgeTransferId<Self::Hash>,
@@ -285,6 +312,7 @@ where
 		time_lock: TimeLock,
 		recipient: RecipientAddress,
 		amount: Amount,
+		_token: Option<TokenAddress>,
 	) -> BridgeContractCounterpartyResult<()> {
 		self.register_call(MethodName::LockBridgeTransferAssets);
 		if let Some(config) = self.have_call_config(MethodName::LockBridgeTransferAssets) {
@@ -331,16 +359,30 @@ where
 
 	async fn abort_bridge_transfer(
 		&mut self,
-		_bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
 	) -> BridgeContractCounterpartyResult<()> {
-		unimplemented!()
+		self.register_call(MethodName::AbortBridgeTransfer);
+		if let Some(config) = self.have_call_config(MethodName::AbortBridgeTransfer) {
+			if let Some(delay) = config.delay {
+				tokio::time::sleep(delay).await;
+			}
+			config.get_counterparty_error()?;
+		}
+
+		let transaction =
+			Transaction::Counterparty(CounterpartyCall::AbortBridgeTransfer(bridge_transfer_id));
+		self.send_transaction(transaction)
+			.map_err(BridgeContractCounterpartyError::generic)
 	}
 
 	async fn get_bridge_transfer_details(
 		&mut self,
 		_bridge_transfer_id: BridgeTransferId<Self::Hash>,
-	) -> BridgeContractCounterpartyResult<Option<BridgeTransferDetails<Self::Hash, Self::Address>>>
-	{
+	) -> BridgeContractCounterpartyResult<Option<LockDetails<Self::Hash>>> {
 		unimplemented!()
 	}
+
+	fn gas_spent(&self) -> u64 {
+		0
+	}
 }
@@ -1,15 +1,22 @@
 use std::collections::HashMap;
 
-use bridge_shared::types::{
-	Amount, BridgeAddressType, BridgeHashType, BridgeTransferId, CompletedDetails, GenUniqueHash,
-	HashLock, HashLockPreImage, LockDetails, RecipientAddress, TimeLock,
+use bridge_shared::{
+	transfer_store::{InvalidTransition, TransferState, TransferStateMachine},
+	types::{
+		Amount, BridgeAddressType, BridgeHashType, BridgeTransferId, CompletedDetails,
+		GenUniqueHash, HashLock, HashLockAlgorithm, HashLockPreImage, LockDetails,
+		RecipientAddress, TimeLock,
+	},
 };
 use thiserror::Error;
 
+// Named after the functions of the contracts
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SmartContractCounterpartyEvent<H> {
 	LockedBridgeTransfer(LockDetails<H>),
 	CompletedBridgeTransfer(CompletedDetails<H>),
+	AbortedBridgeTransfer(BridgeTransferId<H>),
 }
 
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
@@ -18,18 +25,32 @@ pub enum SmartContractCounterpartyError {
 	TransferNotFound,
 	#[error("Invalid hash lock pre image (secret)")]
 	InvalidHashLockPreImage,
+	#[error("Time lock expired")]
+	TimeLockExpired,
+	#[error("Time lock not expired")]
+	TimeLockNotExpired,
+	#[error("Insufficient balance to lock the transfer")]
+	InsufficientBalance,
+	#[error(transparent)]
+	InvalidTransition(#[from] InvalidTransition),
 }
 
-#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone)]
 pub enum CounterpartyCall<H> {
 	CompleteBridgeTransfer(BridgeTransferId<H>, HashLockPreImage),
 	LockBridgeTransfer(BridgeTransferId<H>, HashLock<H>, TimeLock, RecipientAddress, Amount),
+	AbortBridgeTransfer(BridgeTransferId<H>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SmartContractCounterparty<A, H> {
 	pub locked_transfers: HashMap<BridgeTransferId<H>, LockDetails<H>>,
-	pub _phantom: std::marker::PhantomData<A>,
+	pub transfer_states: HashMap<BridgeTransferId<H>, TransferStateMachine>,
+	/// Account of the relayer, debited the assets it locks. The assets are minted when unset.
+	pub locker: Option<A>,
+	/// Assets held by the contract for the locked transfers.
+	pub escrow: Amount,
 }
 
 pub type SCCResult<H> = Result<SmartContractCounterpartyEvent<H>, SmartContractCounterpartyError>;
@@ -38,15 +59,20 @@ impl<A, H> SmartContractCounterparty<A, H>
 where
 	A: BridgeAddressType + From<RecipientAddress>,
 	H: BridgeHashType + GenUniqueHash,
-	H: From<HashLockPreImage>,
+	H: HashLockAlgorithm<Hash = H>,
 {
 	pub fn new() -> Self {
-		Self { locked_transfers: HashMap::new(), _phantom: std::marker::PhantomData }
+		Self {
+			locked_transfers: HashMap::new(),
+			transfer_states: HashMap::new(),
+			locker: None,
+			escrow: Amount(0),
+		}
 	}
 
 	pub fn lock_bridge_transfer(
 		&mut self,
-
+		accounts: &mut HashMap<A, Amount>,
 		bridge_transfer_id: BridgeTransferId<H>,
 		hash_lock: HashLock<H>,
 		time_lock: TimeLock,
@@ -57,6 +83,18 @@ where
 			"SmartContractCounterparty: Locking bridge transfer: {:?}",
 			bridge_transfer_id
 		);
+		// A transfer is locked once, even when the lock is submitted again
+		let mut state = self.transfer_states.get(&bridge_transfer_id).copied().unwrap_or_default();
+		state.transition(TransferState::Locked)?;
+		if let Some(locker) = &self.locker {
+			let balance = accounts
+				.get_mut(locker)
+				.filter(|balance| ***balance >= *amount)
+				.ok_or(SmartContractCounterpartyError::InsufficientBalance)?;
+			**balance -= *amount;
+		}
+		*self.escrow += *amount;
+		self.transfer_states.insert(bridge_transfer_id.clone(), state);
 		self.locked_transfers.insert(
 			bridge_transfer_id.clone(),
 			LockDetails {
@@ -82,25 +120,36 @@ where
 		accounts: &mut HashMap<A, Amount>,
 		bridge_transfer_id: &BridgeTransferId<H>,
 		pre_image: HashLockPreImage,
+		now: u64,
 	) -> SCCResult<H> {
 		let transfer = self
 			.locked_transfers
-			.remove(bridge_transfer_id)
+			.get(bridge_transfer_id)
 			.ok_or(SmartContractCounterpartyError::TransferNotFound)?;
 
 		tracing::trace!("SmartContractCounterparty: Completing bridge transfer: {:?}", transfer);
 
 		// check if the secret is correct
-		let secret_hash = H::from(pre_image.clone());
-		if transfer.hash_lock.0 != secret_hash {
+		if !H::verify(&pre_image, &transfer.hash_lock) {
 			tracing::warn!(
-				"Invalid hash lock pre image {pre_image:?} hash {secret_hash:?} != hash_lock {:?}",
+				"Invalid hash lock pre image {pre_image:?} for hash_lock {:?}",
 				transfer.hash_lock.0
 			);
 			return Err(SmartContractCounterpartyError::InvalidHashLockPreImage);
 		}
+		if now >= transfer.time_lock.0 {
+			return Err(SmartContractCounterpartyError::TimeLockExpired);
+		}
+		self.transfer_states
+			.get_mut(bridge_transfer_id)
+			.ok_or(SmartContractCounterpartyError::TransferNotFound)?
+			.transition(TransferState::Completed)?;
+		let transfer = self
+			.locked_transfers
+			.remove(bridge_transfer_id)
+			.ok_or(SmartContractCounterpartyError::TransferNotFound)?;
 
-		// TODO: fix this
+		*self.escrow -= *transfer.amount;
 		let account = A::from(transfer.recipient_address.clone());
 		let balance = accounts.entry(account).or_insert(Amount(0));
 		**balance += *transfer.amount;
@@ -109,4 +158,39 @@ where
 			CompletedDetails::from_lock_details(transfer, pre_image),
 		))
 	}
+
+	/// Aborts a lock whose time lock expired at `now`, the recipient can no longer complete it.
+	/// The assets are returned to the locker.
+	pub fn abort_bridge_transfer(
+		&mut self,
+		accounts: &mut HashMap<A, Amount>,
+		bridge_transfer_id: &BridgeTransferId<H>,
+		now: u64,
+	) -> SCCResult<H> {
+		let transfer = self
+			.locked_transfers
+			.get(bridge_transfer_id)
+			.ok_or(SmartContractCounterpartyError::TransferNotFound)?;
+		if now < transfer.time_lock.0 {
+			return Err(SmartContractCounterpartyError::TimeLockNotExpired);
+		}
+		self.transfer_states
+			.get_mut(bridge_transfer_id)
+			.ok_or(SmartContractCounterpartyError::TransferNotFound)?
+			.transition(TransferState::Refunded)?;
+		let transfer = self
+			.locked_transfers
+			.remove(bridge_transfer_id)
+			.ok_or(SmartContractCounterpartyError::TransferNotFound)?;
+		*self.escrow -= *transfer.amount;
+		if let Some(locker) = &self.locker {
+			**accounts.entry(locker.clone()).or_insert(Amount(0)) += *transfer.amount;
+		}
+
+		tracing::trace!(
+			"SmartContractCounterparty: Aborted bridge transfer: {:?}",
+			bridge_transfer_id
+		);
+		Ok(SmartContractCounterpartyEvent::AbortedBridgeTransfer(bridge_transfer_id.clone()))
+	}
 }
@@ -3,27 +3,42 @@ use std::collections::HashMap;
 use rand::Rng;
 use thiserror::Error;
 
-use bridge_shared::types::{
-	Amount, BridgeAddressType, BridgeHashType, BridgeTransferDetails, BridgeTransferId,
-	GenUniqueHash, HashLock, HashLockPreImage, InitiatorAddress, RecipientAddress, TimeLock,
+use bridge_shared::{
+	transfer_store::{InvalidTransition, TransferState, TransferStateMachine},
+	types::{
+		Amount, BridgeAddressType, BridgeHashType, BridgeTransferDetails, BridgeTransferId,
+		GenUniqueHash, HashLock, HashLockAlgorithm, HashLockPreImage, InitiatorAddress,
+		RecipientAddress, TimeLock,
+	},
 };
 
+// Named after the functions of the contracts
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SmartContractInitiatorEvent<A, H> {
 	InitiatedBridgeTransfer(BridgeTransferDetails<A, H>),
 	CompletedBridgeTransfer(BridgeTransferId<H>, HashLockPreImage),
+	RefundedBridgeTransfer(BridgeTransferId<H>),
 }
 
-#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone)]
 pub enum InitiatorCall<A, H> {
 	InitiateBridgeTransfer(InitiatorAddress<A>, RecipientAddress, Amount, TimeLock, HashLock<H>),
 	CompleteBridgeTransfer(BridgeTransferId<H>, HashLockPreImage),
+	RefundBridgeTransfer(BridgeTransferId<H>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SmartContractInitiator<A, H, R> {
 	pub initiated_transfers: HashMap<BridgeTransferId<H>, BridgeTransferDetails<A, H>>,
+	pub transfer_states: HashMap<BridgeTransferId<H>, TransferStateMachine>,
 	pub accounts: HashMap<A, Amount>,
+	/// Account of the relayer, credited the assets of the completed transfers. The assets of
+	/// the initiators are not debited when unset.
+	pub relayer: Option<A>,
+	/// Assets held by the contract for the initiated transfers.
+	pub escrow: Amount,
 	pub rng: R,
 }
 
@@ -35,6 +50,12 @@ pub enum SmartContractInitiatorError {
 	TransferNotFound,
 	#[error("Invalid hash lock pre image (secret)")]
 	InvalidHashLockPreImage,
+	#[error("Time lock not expired")]
+	TimeLockNotExpired,
+	#[error("Insufficient balance to initiate the transfer")]
+	InsufficientBalance,
+	#[error(transparent)]
+	InvalidTransition(#[from] InvalidTransition),
 }
 
 pub type SCIResult<A, H> = Result<SmartContractInitiatorEvent<A, H>, SmartContractInitiatorError>;
@@ -44,14 +65,22 @@ where
 	A: BridgeAddressType,
 	H: BridgeHashType + GenUniqueHash,
 	R: Rng,
-	H: From<HashLockPreImage>,
+	H: HashLockAlgorithm<Hash = H>,
 {
 	pub fn new(rng: R) -> Self {
-		Self { initiated_transfers: HashMap::new(), accounts: HashMap::default(), rng }
+		Self {
+			initiated_transfers: HashMap::new(),
+			transfer_states: HashMap::new(),
+			accounts: HashMap::default(),
+			relayer: None,
+			escrow: Amount(0),
+			rng,
+		}
 	}
 
 	pub fn initiate_bridge_transfer(
 		&mut self,
+		accounts: &mut HashMap<A, Amount>,
 		initiator: InitiatorAddress<A>,
 		recipient: RecipientAddress,
 		amount: Amount,
@@ -65,9 +94,14 @@ where
 			bridge_transfer_id
 		);
 
-		// // TODO: fix this
-		// let balance = self.accounts.entry(initiator.0.clone()).or_insert(Amount(0));
-		// **balance -= amount.0;
+		if self.relayer.is_some() {
+			let balance = accounts
+				.get_mut(&initiator.0)
+				.filter(|balance| ***balance >= *amount)
+				.ok_or(SmartContractInitiatorError::InsufficientBalance)?;
+			**balance -= *amount;
+			*self.escrow += *amount;
+		}
 
 		// initiate bridge transfer
 		self.initiated_transfers.insert(
@@ -79,8 +113,11 @@ where
 				hash_lock: hash_lock.clone(),
 				time_lock: time_lock.clone(),
 				amount,
+				token: None,
 			},
 		);
+		self.transfer_states
+			.insert(bridge_transfer_id.clone(), TransferStateMachine::new());
 
 		Ok(SmartContractInitiatorEvent::InitiatedBridgeTransfer(BridgeTransferDetails {
 			bridge_transfer_id,
@@ -89,6 +126,7 @@ where
 			hash_lock,
 			time_lock,
 			amount,
+			token: None,
 		}))
 	}
 
@@ -107,15 +145,61 @@ where
 			.ok_or(SmartContractInitiatorError::TransferNotFound)?;
 
 		// check if the secret is correct
-		let secret_hash = H::from(pre_image.clone());
-		if transfer.hash_lock.0 != secret_hash {
+		if !H::verify(&pre_image, &transfer.hash_lock) {
 			tracing::warn!(
-				"Invalid hash lock pre image {pre_image:?} hash {secret_hash:?} != hash_lock {:?}",
+				"Invalid hash lock pre image {pre_image:?} for hash_lock {:?}",
 				transfer.hash_lock.0
 			);
 			return Err(SmartContractInitiatorError::InvalidHashLockPreImage);
 		}
 
+		let state = self
+			.transfer_states
+			.get_mut(&transfer_id)
+			.ok_or(SmartContractInitiatorError::TransferNotFound)?;
+		// The secret is revealed once the assets are locked on the counterparty contract
+		let mut next = *state;
+		if next.state() != TransferState::Locked {
+			next.transition(TransferState::Locked)?;
+		}
+		next.transition(TransferState::Completed)?;
+		*state = next;
+
+		if let Some(relayer) = &self.relayer {
+			*self.escrow -= *transfer.amount;
+			**accounts.entry(relayer.clone()).or_insert(Amount(0)) += *transfer.amount;
+		}
+
 		Ok(SmartContractInitiatorEvent::CompletedBridgeTransfer(transfer_id, pre_image))
 	}
+
+	/// Refunds the initiator of a transfer whose time lock expired at `now`.
+	pub fn refund_bridge_transfer(
+		&mut self,
+		accounts: &mut HashMap<A, Amount>,
+		transfer_id: BridgeTransferId<H>,
+		now: u64,
+	) -> SCIResult<A, H> {
+		tracing::trace!("SmartContractInitiator: Refunding bridge transfer: {:?}", transfer_id);
+
+		let transfer = self
+			.initiated_transfers
+			.get(&transfer_id)
+			.ok_or(SmartContractInitiatorError::TransferNotFound)?;
+		if now < transfer.time_lock.0 {
+			return Err(SmartContractInitiatorError::TimeLockNotExpired);
+		}
+		self.transfer_states
+			.get_mut(&transfer_id)
+			.ok_or(SmartContractInitiatorError::TransferNotFound)?
+			.transition(TransferState::Refunded)?;
+
+		if self.relayer.is_some() {
+			*self.escrow -= *transfer.amount;
+			let initiator = transfer.initiator_address.0.clone();
+			**accounts.entry(initiator).or_insert(Amount(0)) += *transfer.amount;
+		}
+
+		Ok(SmartContractInitiatorEvent::RefundedBridgeTransfer(transfer_id))
+	}
 }
@@ -0,0 +1,25 @@
+use std::sync::{
+	atomic::{AtomicU64, Ordering},
+	Arc,
+};
+
+/// Time of an [`AbstractBlockchain`](super::blockchain::AbstractBlockchain), which only moves
+/// when a test advances it.
+///
+/// Clones share the same time, so that a test keeps control of the clock of a spawned blockchain.
+#[derive(Debug, Clone, Default)]
+pub struct Clock(Arc<AtomicU64>);
+
+impl Clock {
+	pub fn new(now: u64) -> Self {
+		Self(Arc::new(AtomicU64::new(now)))
+	}
+
+	pub fn now(&self) -> u64 {
+		self.0.load(Ordering::SeqCst)
+	}
+
+	pub fn advance(&self, duration: u64) {
+		self.0.fetch_add(duration, Ordering::SeqCst);
+	}
+}
@@ -0,0 +1,432 @@
+//! Faults injected in the contract calls and the monitoring streams of a blockchain service, so
+//! that the recovery of the bridge service can be exercised deterministically.
+//!
+//! A [`FaultInjector`] draws the fault of each call and event from a seeded schedule, shared by
+//! the contracts and monitoring it wraps: the same seed injects the same faults.
+
+use std::{
+	collections::VecDeque,
+	future::Future,
+	pin::Pin,
+	sync::{Arc, Mutex},
+	task::{Context, Poll},
+	time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use rand::{Rng, SeedableRng};
+
+use bridge_shared::{
+	bridge_contracts::{
+		BridgeContractCounterparty, BridgeContractCounterpartyResult, BridgeContractInitiator,
+		BridgeContractInitiatorResult,
+	},
+	bridge_monitoring::{BridgeContractCounterpartyMonitoring, BridgeContractInitiatorMonitoring},
+	types::{
+		Amount, BridgeTransferDetails, BridgeTransferId, HashLock, HashLockPreImage,
+		InitiatorAddress, LockDetails, RecipientAddress, TimeLock, TokenAddress,
+	},
+};
+
+use super::rng::TestRng;
+
+/// Fault of a call or an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+	None,
+	/// A dropped call reports success without reaching the contract, a dropped event is lost.
+	Drop,
+	Delay(Duration),
+	/// The call is sent twice, the event delivered twice.
+	Duplicate,
+	/// The call waits for the maximal delay, so that the calls made meanwhile overtake it. The
+	/// event is delivered after the next one.
+	Reorder,
+}
+
+/// Rates of the faults, each call or event has at most one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultRates {
+	pub drop: f64,
+	pub delay: f64,
+	pub duplicate: f64,
+	pub reorder: f64,
+	pub max_delay: Duration,
+}
+
+impl FaultRates {
+	/// No faults, to enable one kind of fault at a time.
+	pub fn none() -> Self {
+		Self { drop: 0.0, delay: 0.0, duplicate: 0.0, reorder: 0.0, max_delay: Duration::ZERO }
+	}
+}
+
+#[derive(Debug)]
+struct Schedule {
+	rng: TestRng,
+	rates: FaultRates,
+	injected: Vec<Fault>,
+}
+
+impl Schedule {
+	fn next(&mut self) -> Fault {
+		let FaultRates { drop, delay, duplicate, reorder, max_delay } = self.rates;
+		let draw: f64 = self.rng.gen();
+		let fault = if draw < drop {
+			Fault::Drop
+		} else if draw < drop + delay {
+			let millis = max_delay.as_millis().max(1) as u64;
+			Fault::Delay(Duration::from_millis(self.rng.gen_range(0, millis + 1)))
+		} else if draw < drop + delay + duplicate {
+			Fault::Duplicate
+		} else if draw < drop + delay + duplicate + reorder {
+			Fault::Reorder
+		} else {
+			Fault::None
+		};
+		self.injected.push(fault);
+		fault
+	}
+}
+
+/// Seeded schedule of the faults injected in the wrapped contracts and monitoring.
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+	schedule: Arc<Mutex<Schedule>>,
+}
+
+impl FaultInjector {
+	pub fn new(seed: u64, rates: FaultRates) -> Self {
+		Self {
+			schedule: Arc::new(Mutex::new(Schedule {
+				rng: TestRng::seed_from_u64(seed),
+				rates,
+				injected: Vec::new(),
+			})),
+		}
+	}
+
+	/// Wraps an initiator or counterparty contract.
+	pub fn contract<C>(&self, inner: C) -> FaultyContract<C> {
+		FaultyContract { inner, injector: self.clone() }
+	}
+
+	/// Wraps an initiator or counterparty monitoring.
+	pub fn monitoring<M>(&self, inner: M) -> FaultyMonitoring<M>
+	where
+		M: Stream,
+	{
+		FaultyMonitoring {
+			inner,
+			injector: self.clone(),
+			ready: VecDeque::new(),
+			held: None,
+			delay: None,
+		}
+	}
+
+	/// Faults drawn so far, in order, including the calls and events left untouched.
+	pub fn injected(&self) -> Vec<Fault> {
+		self.lock().injected.clone()
+	}
+
+	fn next_fault(&self) -> Fault {
+		self.lock().next()
+	}
+
+	fn max_delay(&self) -> Duration {
+		self.lock().rates.max_delay
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, Schedule> {
+		self.schedule.lock().expect("fault schedule lock poisoned")
+	}
+}
+
+/// Contract whose calls are dropped, delayed, duplicated or reordered.
+#[derive(Debug, Clone)]
+pub struct FaultyContract<C> {
+	pub inner: C,
+	injector: FaultInjector,
+}
+
+impl<C> FaultyContract<C> {
+	/// Applies the next fault to a call, made by `call` on the inner contract.
+	async fn call<T, E, F>(&mut self, mut call: impl FnMut(C) -> F) -> Result<T, E>
+	where
+		C: Clone,
+		T: Default,
+		F: Future<Output = Result<T, E>>,
+	{
+		match self.injector.next_fault() {
+			Fault::None => {}
+			Fault::Drop => return Ok(T::default()),
+			Fault::Delay(delay) => tokio::time::sleep(delay).await,
+			Fault::Duplicate => {
+				let _ = call(self.inner.clone()).await;
+			}
+			Fault::Reorder => tokio::time::sleep(self.injector.max_delay()).await,
+		}
+		call(self.inner.clone()).await
+	}
+}
+
+#[async_trait]
+impl<C> BridgeContractInitiator for FaultyContract<C>
+where
+	C: BridgeContractInitiator,
+{
+	type Address = C::Address;
+	type Hash = C::Hash;
+
+	async fn initiate_bridge_transfer(
+		&mut self,
+		initiator_address: InitiatorAddress<Self::Address>,
+		recipient_address: RecipientAddress,
+		hash_lock: HashLock<Self::Hash>,
+		time_lock: TimeLock,
+		amount: Amount,
+	) -> BridgeContractInitiatorResult<()> {
+		self.call(|mut inner| {
+			let (initiator_address, recipient_address, hash_lock, time_lock) = (
+				initiator_address.clone(),
+				recipient_address.clone(),
+				hash_lock.clone(),
+				time_lock.clone(),
+			);
+			async move {
+				inner
+					.initiate_bridge_transfer(
+						initiator_address,
+						recipient_address,
+						hash_lock,
+						time_lock,
+						amount,
+					)
+					.await
+			}
+		})
+		.await
+	}
+
+	async fn complete_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		secret: HashLockPreImage,
+	) -> BridgeContractInitiatorResult<()> {
+		self.call(|mut inner| {
+			let (bridge_transfer_id, secret) = (bridge_transfer_id.clone(), secret.clone());
+			async move {
+				BridgeContractInitiator::complete_bridge_transfer(
+					&mut inner,
+					bridge_transfer_id,
+					secret,
+				)
+				.await
+			}
+		})
+		.await
+	}
+
+	async fn refund_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+	) -> BridgeContractInitiatorResult<()> {
+		self.call(|mut inner| {
+			let bridge_transfer_id = bridge_transfer_id.clone();
+			async move { inner.refund_bridge_transfer(bridge_transfer_id).await }
+		})
+		.await
+	}
+
+	async fn get_bridge_transfer_details(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+	) -> BridgeContractInitiatorResult<Option<BridgeTransferDetails<Self::Address, Self::Hash>>> {
+		BridgeContractInitiator::get_bridge_transfer_details(&mut self.inner, bridge_transfer_id)
+			.await
+	}
+
+	fn gas_spent(&self) -> u64 {
+		BridgeContractInitiator::gas_spent(&self.inner)
+	}
+}
+
+#[async_trait]
+impl<C> BridgeContractCounterparty for FaultyContract<C>
+where
+	C: BridgeContractCounterparty,
+{
+	type Address = C::Address;
+	type Hash = C::Hash;
+
+	async fn lock_bridge_transfer_assets(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		hash_lock: HashLock<Self::Hash>,
+		time_lock: TimeLock,
+		recipient: RecipientAddress,
+		amount: Amount,
+		token: Option<TokenAddress>,
+	) -> BridgeContractCounterpartyResult<()> {
+		self.call(|mut inner| {
+			let (bridge_transfer_id, hash_lock, time_lock, recipient, token) = (
+				bridge_transfer_id.clone(),
+				hash_lock.clone(),
+				time_lock.clone(),
+				recipient.clone(),
+				token.clone(),
+			);
+			async move {
+				inner
+					.lock_bridge_transfer_assets(
+						bridge_transfer_id,
+						hash_lock,
+						time_lock,
+						recipient,
+						amount,
+						token,
+					)
+					.await
+			}
+		})
+		.await
+	}
+
+	async fn complete_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+		secret: HashLockPreImage,
+	) -> BridgeContractCounterpartyResult<()> {
+		self.call(|mut inner| {
+			let (bridge_transfer_id, secret) = (bridge_transfer_id.clone(), secret.clone());
+			async move {
+				BridgeContractCounterparty::complete_bridge_transfer(
+					&mut inner,
+					bridge_transfer_id,
+					secret,
+				)
+				.await
+			}
+		})
+		.await
+	}
+
+	async fn abort_bridge_transfer(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+	) -> BridgeContractCounterpartyResult<()> {
+		self.call(|mut inner| {
+			let bridge_transfer_id = bridge_transfer_id.clone();
+			async move { inner.abort_bridge_transfer(bridge_transfer_id).await }
+		})
+		.await
+	}
+
+	async fn available_liquidity(&mut self) -> BridgeContractCounterpartyResult<Option<Amount>> {
+		self.inner.available_liquidity().await
+	}
+
+	async fn get_bridge_transfer_details(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId<Self::Hash>,
+	) -> BridgeContractCounterpartyResult<Option<LockDetails<Self::Hash>>> {
+		BridgeContractCounterparty::get_bridge_transfer_details(&mut self.inner, bridge_transfer_id)
+			.await
+	}
+
+	fn gas_spent(&self) -> u64 {
+		BridgeContractCounterparty::gas_spent(&self.inner)
+	}
+}
+
+/// Monitoring whose events are dropped, delayed, duplicated or reordered.
+pub struct FaultyMonitoring<M>
+where
+	M: Stream,
+{
+	pub inner: M,
+	injector: FaultInjector,
+	/// Events to deliver, before polling the inner monitoring.
+	ready: VecDeque<M::Item>,
+	/// Event reordered after the next one, delivered once the inner monitoring has no other.
+	held: Option<M::Item>,
+	delay: Option<(Pin<Box<tokio::time::Sleep>>, M::Item)>,
+}
+
+impl<M> Stream for FaultyMonitoring<M>
+where
+	M: Stream + Unpin,
+	M::Item: Clone + Unpin,
+{
+	type Item = M::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		loop {
+			// A delayed event holds back the ones after it
+			if let Some((sleep, _)) = &mut this.delay {
+				if sleep.as_mut().poll(cx).is_pending() {
+					return Poll::Pending;
+				}
+				if let Some((_, event)) = this.delay.take() {
+					this.ready.push_back(event);
+				}
+			}
+			if let Some(event) = this.ready.pop_front() {
+				return Poll::Ready(Some(event));
+			}
+			let event = match this.inner.poll_next_unpin(cx) {
+				Poll::Ready(Some(event)) => event,
+				Poll::Ready(None) => return Poll::Ready(this.held.take()),
+				Poll::Pending => match this.held.take() {
+					Some(event) => return Poll::Ready(Some(event)),
+					None => return Poll::Pending,
+				},
+			};
+			match this.injector.next_fault() {
+				Fault::None => this.ready.push_back(event),
+				Fault::Drop => continue,
+				Fault::Delay(delay) => {
+					this.delay = Some((Box::pin(tokio::time::sleep(delay)), event));
+					continue;
+				}
+				Fault::Duplicate => {
+					this.ready.push_back(event.clone());
+					this.ready.push_back(event);
+				}
+				Fault::Reorder => {
+					// Two reordered events in a row are swapped
+					if this.held.is_none() {
+						this.held = Some(event);
+						continue;
+					}
+					this.ready.push_back(event);
+				}
+			}
+			// The held event goes after the one which overtook it
+			if let Some(held) = this.held.take() {
+				this.ready.push_back(held);
+			}
+		}
+	}
+}
+
+impl<M> BridgeContractInitiatorMonitoring for FaultyMonitoring<M>
+where
+	M: BridgeContractInitiatorMonitoring,
+	M::Item: Clone + Unpin,
+{
+	type Address = M::Address;
+	type Hash = M::Hash;
+}
+
+impl<M> BridgeContractCounterpartyMonitoring for FaultyMonitoring<M>
+where
+	M: BridgeContractCounterpartyMonitoring,
+	M::Item: Clone + Unpin,
+{
+	type Address = M::Address;
+	type Hash = M::Hash;
+}
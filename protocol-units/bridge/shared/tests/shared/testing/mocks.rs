@@ -9,11 +9,13 @@ use std::{
 use bridge_shared::{
 	blockchain_service::{BlockchainService, ContractEvent},
 	bridge_contracts::BridgeContractCounterpartyResult,
-	types::{HashLock, InitiatorAddress, RecipientAddress, TimeLock},
+	types::{HashLock, InitiatorAddress, RecipientAddress, TimeLock, TokenAddress},
 };
 use bridge_shared::{
 	bridge_contracts::BridgeContractInitiatorResult,
-	types::{BridgeAddressType, BridgeHashType, BridgeTransferDetails, BridgeTransferId},
+	types::{
+		BridgeAddressType, BridgeHashType, BridgeTransferDetails, BridgeTransferId, LockDetails,
+	},
 };
 use bridge_shared::{
 	bridge_contracts::{BridgeContractCounterparty, BridgeContractInitiator},
@@ -267,6 +269,7 @@ where
 				hash_lock,
 				time_lock,
 				amount,
+				token: None,
 			}));
 		Ok(())
 	}
@@ -289,9 +292,13 @@ where
 	async fn get_bridge_transfer_details(
 		&mut self,
 		_bridge_transfer_id: BridgeTransferId<Self::Hash>,
-	) -> BridgeContractInitiatorResult<Option<BridgeTransferDetails<Self::Hash, Self::Address>>> {
+	) -> BridgeContractInitiatorResult<Option<BridgeTransferDetails<Self::Address, Self::Hash>>> {
 		Ok(None)
 	}
+
+	fn gas_spent(&self) -> u64 {
+		0
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -321,6 +328,7 @@ where
 		_time_lock: TimeLock,
 		_recipient: RecipientAddress,
 		_amount: Amount,
+		_token: Option<TokenAddress>,
 	) -> BridgeContractCounterpartyResult<()> {
 		Ok(())
 	}
@@ -340,11 +348,18 @@ where
 		Ok(())
 	}
 
+	async fn available_liquidity(&mut self) -> BridgeContractCounterpartyResult<Option<Amount>> {
+		Ok(None)
+	}
+
 	async fn get_bridge_transfer_details(
 		&mut self,
 		_bridge_transfer_id: BridgeTransferId<Self::Hash>,
-	) -> BridgeContractCounterpartyResult<Option<BridgeTransferDetails<Self::Hash, Self::Address>>>
-	{
+	) -> BridgeContractCounterpartyResult<Option<LockDetails<Self::Hash>>> {
 		Ok(None)
 	}
+
+	fn gas_spent(&self) -> u64 {
+		0
+	}
 }
@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
 pub mod blockchain;
+pub mod clock;
+pub mod fault_injector;
 pub mod mocks;
 pub mod rng;
+pub mod simulator;
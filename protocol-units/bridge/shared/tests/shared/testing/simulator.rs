@@ -0,0 +1,584 @@
+//! Simulation of the bridge protocol between two [`AbstractBlockchain`]s, driven step by step so
+//! that the calls and events can be delayed, dropped, duplicated and reorged out of the chains.
+//!
+//! Transfers are initiated on the first blockchain and locked on the second one by a model of the
+//! relayer, which follows the protocol of the bridge service: lock on the initiated event,
+//! complete on the initiator contract with the secret revealed by the recipient, refund and abort
+//! once the time locks expired. The relayer only sees the events of the final blocks, the last
+//! [`REORG_DEPTH`] blocks of a chain may be reorged out.
+
+use std::collections::{HashMap, VecDeque};
+
+use rand::SeedableRng;
+
+use bridge_shared::{
+	transfer_store::{TransferState, TransferStateMachine},
+	types::{
+		Amount, BridgeAddressType, BridgeHashType, BridgeTransferDetails, BridgeTransferId,
+		GenUniqueHash, HashLock, HashLockAlgorithm, HashLockPreImage, InitiatorAddress,
+		RecipientAddress, TimeLock,
+	},
+};
+
+use super::{
+	blockchain::{
+		counterparty_contract::{SmartContractCounterparty, SmartContractCounterpartyEvent},
+		initiator_contract::{SmartContractInitiator, SmartContractInitiatorEvent},
+		AbstractBlockchain, AbstractBlockchainEvent, CounterpartyCall, InitiatorCall, Transaction,
+	},
+	rng::TestRng,
+};
+use crate::shared::{BC1Address, BC1Hash, BC2Address, BC2Hash};
+
+/// Number of the last blocks of a chain which may be reorged, the older ones are final.
+pub const REORG_DEPTH: usize = 2;
+/// Time lock of the transfers on the initiator contract, from the start of the simulation.
+pub const INITIATOR_TIME_LOCK: u64 = 100;
+/// Time lock of the locks on the counterparty contract, from the time they are made.
+pub const COUNTERPARTY_TIME_LOCK: u64 = 50;
+/// Time left to the relayer to complete a transfer on the initiator contract once its lock
+/// expired.
+pub const TIME_LOCK_MARGIN: u64 = 20;
+/// Balance of the relayer on the second blockchain, from which it locks the transfers.
+pub const RELAYER_LIQUIDITY: u128 = 1_000_000;
+/// Time the chains advance by on each round of settling.
+const SETTLE_TICK: u64 = 10;
+const SETTLE_ROUNDS: usize = 200;
+
+/// Blockchain of the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chain {
+	One,
+	Two,
+}
+
+/// Action of a step of the simulation.
+///
+/// The indexes select a pending message modulo their number, so that any of them can be
+/// delivered out of order.
+#[derive(Debug, Clone)]
+pub enum Step {
+	Deliver(usize),
+	/// Transactions are lost, events are delivered again after a resubscription.
+	Drop(usize),
+	/// Events are delivered twice, transactions are replay protected.
+	Duplicate(usize),
+	/// The last block of the chain is reorged out, its transaction returns to the mempool.
+	Reorg(Chain),
+	/// The relayer submits again the calls whose events it didn't see.
+	Retry,
+	/// The recipient of a transfer completes it with its secret, if it ever does.
+	Claim(usize),
+	Tick(u64),
+}
+
+#[derive(Debug, Clone)]
+enum Message {
+	Transaction1(Transaction<BC1Address, BC1Hash>),
+	Transaction2(Transaction<BC2Address, BC2Hash>),
+	Event1(AbstractBlockchainEvent<BC1Address, BC1Hash>),
+	Event2(AbstractBlockchainEvent<BC2Address, BC2Hash>),
+}
+
+impl Message {
+	fn is_event(&self) -> bool {
+		matches!(self, Message::Event1(_) | Message::Event2(_))
+	}
+}
+
+/// Block of a transaction, with the state of the contracts before it, restored when the block is
+/// reorged out.
+#[derive(Debug)]
+struct Block<A, H> {
+	initiator_contract: SmartContractInitiator<A, H, TestRng>,
+	counterparty_contract: SmartContractCounterparty<A, H>,
+	accounts: HashMap<A, Amount>,
+	transaction: Transaction<A, H>,
+	event: AbstractBlockchainEvent<A, H>,
+}
+
+/// Blockchain whose last blocks may be reorged.
+#[derive(Debug)]
+pub struct SimChain<A, H> {
+	pub blockchain: AbstractBlockchain<A, H, TestRng>,
+	blocks: VecDeque<Block<A, H>>,
+}
+
+impl<A, H> SimChain<A, H>
+where
+	A: BridgeAddressType + From<RecipientAddress>,
+	H: BridgeHashType + GenUniqueHash + HashLockAlgorithm<Hash = H>,
+{
+	fn new(blockchain: AbstractBlockchain<A, H, TestRng>) -> Self {
+		Self { blockchain, blocks: VecDeque::new() }
+	}
+
+	/// Includes the transaction in a new block, and returns the event of the block made final by
+	/// it.
+	fn execute(&mut self, transaction: Transaction<A, H>) -> Option<AbstractBlockchainEvent<A, H>> {
+		let initiator_contract = self.blockchain.initiator_contract.clone();
+		let counterparty_contract = self.blockchain.counterparty_contract.clone();
+		let accounts = self.blockchain.accounts.clone();
+		let event = self.blockchain.apply_transaction(transaction.clone());
+		self.blocks.push_back(Block {
+			initiator_contract,
+			counterparty_contract,
+			accounts,
+			transaction,
+			event,
+		});
+		if self.blocks.len() > REORG_DEPTH {
+			return self.blocks.pop_front().map(|block| block.event);
+		}
+		None
+	}
+
+	/// Makes every block final, as when empty blocks are produced, and returns their events.
+	fn finalize(&mut self) -> Vec<AbstractBlockchainEvent<A, H>> {
+		self.blocks.drain(..).map(|block| block.event).collect()
+	}
+
+	/// Reorgs the last block out of the chain, and returns its transaction, whose event is never
+	/// seen.
+	fn reorg(&mut self) -> Option<Transaction<A, H>> {
+		let block = self.blocks.pop_back()?;
+		self.blockchain.initiator_contract = block.initiator_contract;
+		self.blockchain.counterparty_contract = block.counterparty_contract;
+		self.blockchain.accounts = block.accounts;
+		Some(block.transaction)
+	}
+}
+
+/// Transfer initiated by a user, whose recipient completes it if `claims` is set.
+#[derive(Debug, Clone)]
+pub struct UserTransfer {
+	pub recipient: BC2Address,
+	pub secret: HashLockPreImage,
+	pub amount: Amount,
+	pub claims: bool,
+}
+
+impl UserTransfer {
+	pub fn hash_lock_1(&self) -> HashLock<BC1Hash> {
+		BC1Hash::hash_lock(&self.secret)
+	}
+
+	pub fn hash_lock_2(&self) -> HashLock<BC2Hash> {
+		BC2Hash::hash_lock(&self.secret)
+	}
+}
+
+#[derive(Debug)]
+struct RelayedSwap {
+	details: BridgeTransferDetails<BC1Address, BC1Hash>,
+	state: TransferStateMachine,
+	/// Time lock of the lock on the counterparty contract, unset when it was too late to lock.
+	counterparty_time_lock: Option<TimeLock>,
+	secret: Option<HashLockPreImage>,
+	aborted: bool,
+}
+
+impl RelayedSwap {
+	fn lock(&self) -> Option<Message> {
+		let time_lock = self.counterparty_time_lock.clone()?;
+		Some(Message::Transaction2(Transaction::Counterparty(
+			CounterpartyCall::LockBridgeTransfer(
+				self.counterparty_id(),
+				HashLock(BC2Hash::from(self.details.hash_lock.0.clone())),
+				time_lock,
+				self.details.recipient_address.clone(),
+				self.details.amount,
+			),
+		)))
+	}
+
+	fn counterparty_id(&self) -> BridgeTransferId<BC2Hash> {
+		BridgeTransferId(BC2Hash::from(self.details.bridge_transfer_id.0.clone()))
+	}
+}
+
+/// Model of the relayer, tracking each swap with the state machine of the transfers.
+#[derive(Debug, Default)]
+struct SimRelayer {
+	swaps: HashMap<BridgeTransferId<BC1Hash>, RelayedSwap>,
+}
+
+impl SimRelayer {
+	fn handle_event_1(
+		&mut self,
+		event: AbstractBlockchainEvent<BC1Address, BC1Hash>,
+		now: u64,
+		outbox: &mut Vec<Message>,
+	) {
+		let AbstractBlockchainEvent::InitiatorContractEvent(Ok(event)) = event else { return };
+		match event {
+			SmartContractInitiatorEvent::InitiatedBridgeTransfer(details) => {
+				if self.swaps.contains_key(&details.bridge_transfer_id) {
+					return;
+				}
+				// Too late to lock, the initiator is refunded instead
+				let time_lock = now + COUNTERPARTY_TIME_LOCK;
+				let counterparty_time_lock = (time_lock + TIME_LOCK_MARGIN <= details.time_lock.0)
+					.then_some(TimeLock(time_lock));
+				let swap = RelayedSwap {
+					details,
+					state: TransferStateMachine::new(),
+					counterparty_time_lock,
+					secret: None,
+					aborted: false,
+				};
+				outbox.extend(swap.lock());
+				self.swaps.insert(swap.details.bridge_transfer_id.clone(), swap);
+			}
+			SmartContractInitiatorEvent::CompletedBridgeTransfer(bridge_transfer_id, _) => {
+				if let Some(swap) = self.swaps.get_mut(&bridge_transfer_id) {
+					// The lock event may still be on its way
+					let _ = swap.state.transition(TransferState::Locked);
+					let _ = swap.state.transition(TransferState::Completed);
+				}
+			}
+			SmartContractInitiatorEvent::RefundedBridgeTransfer(bridge_transfer_id) => {
+				if let Some(swap) = self.swaps.get_mut(&bridge_transfer_id) {
+					let _ = swap.state.transition(TransferState::Refunded);
+				}
+			}
+		}
+	}
+
+	fn handle_event_2(
+		&mut self,
+		event: AbstractBlockchainEvent<BC2Address, BC2Hash>,
+		outbox: &mut Vec<Message>,
+	) {
+		let AbstractBlockchainEvent::CounterpartyContractEvent(Ok(event)) = event else { return };
+		match event {
+			SmartContractCounterpartyEvent::LockedBridgeTransfer(details) => {
+				let bridge_transfer_id =
+					BridgeTransferId(BC1Hash::from(details.bridge_transfer_id.0));
+				if let Some(swap) = self.swaps.get_mut(&bridge_transfer_id) {
+					let _ = swap.state.transition(TransferState::Locked);
+				}
+			}
+			SmartContractCounterpartyEvent::CompletedBridgeTransfer(details) => {
+				let bridge_transfer_id =
+					BridgeTransferId(BC1Hash::from(details.bridge_transfer_id.0));
+				if let Some(swap) = self.swaps.get_mut(&bridge_transfer_id) {
+					outbox.push(Message::Transaction1(Transaction::Initiator(
+						InitiatorCall::CompleteBridgeTransfer(
+							bridge_transfer_id,
+							details.secret.clone(),
+						),
+					)));
+					swap.secret = Some(details.secret);
+				}
+			}
+			SmartContractCounterpartyEvent::AbortedBridgeTransfer(bridge_transfer_id) => {
+				let bridge_transfer_id = BridgeTransferId(BC1Hash::from(bridge_transfer_id.0));
+				if let Some(swap) = self.swaps.get_mut(&bridge_transfer_id) {
+					swap.aborted = true;
+				}
+			}
+		}
+	}
+
+	/// Submits again the calls of the swaps whose events weren't seen, and refunds and aborts the
+	/// expired ones.
+	fn retry(&self, now_1: u64, now_2: u64, outbox: &mut Vec<Message>) {
+		for (bridge_transfer_id, swap) in &self.swaps {
+			if swap.state.is_final() {
+				continue;
+			}
+			if let Some(secret) = &swap.secret {
+				outbox.push(Message::Transaction1(Transaction::Initiator(
+					InitiatorCall::CompleteBridgeTransfer(
+						bridge_transfer_id.clone(),
+						secret.clone(),
+					),
+				)));
+				continue;
+			}
+			let lock_expired = swap
+				.counterparty_time_lock
+				.as_ref()
+				.map_or(true, |time_lock| now_2 >= time_lock.0);
+			if swap.state.state() == TransferState::Initiated && !lock_expired {
+				outbox.extend(swap.lock());
+			}
+			if lock_expired && swap.state.state() == TransferState::Locked && !swap.aborted {
+				outbox.push(Message::Transaction2(Transaction::Counterparty(
+					CounterpartyCall::AbortBridgeTransfer(swap.counterparty_id()),
+				)));
+			}
+			if now_1 >= swap.details.time_lock.0 {
+				outbox.push(Message::Transaction1(Transaction::Initiator(
+					InitiatorCall::RefundBridgeTransfer(bridge_transfer_id.clone()),
+				)));
+			}
+		}
+	}
+}
+
+/// Two blockchains bridged by a model of the relayer, over a network controlled by the steps.
+#[derive(Debug)]
+pub struct Simulation {
+	pub chain_1: SimChain<BC1Address, BC1Hash>,
+	pub chain_2: SimChain<BC2Address, BC2Hash>,
+	pub transfers: Vec<UserTransfer>,
+	/// Lock of each transfer on the counterparty contract.
+	counterparty_ids: HashMap<usize, BridgeTransferId<BC2Hash>>,
+	relayer: SimRelayer,
+	pending: Vec<Message>,
+}
+
+impl Simulation {
+	/// Simulation whose users initiate a transfer for each entry of `claims`, completed by its
+	/// recipient if set.
+	pub fn new(seed: u64, claims: &[bool]) -> Self {
+		let rng = TestRng::seed_from_u64(seed);
+		let mut simulation = Self {
+			chain_1: SimChain::new(AbstractBlockchain::new(rng.clone(), "Blockchain1")),
+			chain_2: SimChain::new(AbstractBlockchain::new(rng, "Blockchain2")),
+			transfers: Vec::new(),
+			counterparty_ids: HashMap::new(),
+			relayer: SimRelayer::default(),
+			pending: Vec::new(),
+		};
+		simulation
+			.chain_2
+			.blockchain
+			.add_relayer(BC2Address("relayer"), Amount(RELAYER_LIQUIDITY));
+		for (index, claims) in claims.iter().enumerate() {
+			let transfer = UserTransfer {
+				recipient: BC2Address(static_str_ops::staticize(&format!("recipient_{index}"))),
+				secret: HashLockPreImage(format!("secret_{index}").into_bytes()),
+				amount: Amount(1000 + index as u128),
+				claims: *claims,
+			};
+			simulation.pending.push(Message::Transaction1(Transaction::Initiator(
+				InitiatorCall::InitiateBridgeTransfer(
+					InitiatorAddress(BC1Address("initiator")),
+					RecipientAddress::from(transfer.recipient.clone()),
+					transfer.amount,
+					TimeLock(INITIATOR_TIME_LOCK),
+					transfer.hash_lock_1(),
+				),
+			)));
+			simulation.transfers.push(transfer);
+		}
+		simulation
+	}
+
+	pub fn pending(&self) -> usize {
+		self.pending.len()
+	}
+
+	pub fn step(&mut self, step: Step) {
+		match step {
+			Step::Deliver(index) => {
+				if let Some(message) = self.take(index) {
+					self.deliver(message);
+				}
+			}
+			Step::Drop(index) => {
+				if let Some(message) = self.take(index) {
+					if message.is_event() {
+						self.pending.push(message);
+					}
+				}
+			}
+			Step::Duplicate(index) => {
+				let events: Vec<_> =
+					self.pending.iter().filter(|message| message.is_event()).cloned().collect();
+				if !events.is_empty() {
+					self.pending.push(events[index % events.len()].clone());
+				}
+			}
+			Step::Reorg(Chain::One) => {
+				if let Some(transaction) = self.chain_1.reorg() {
+					self.pending.push(Message::Transaction1(transaction));
+				}
+			}
+			Step::Reorg(Chain::Two) => {
+				if let Some(transaction) = self.chain_2.reorg() {
+					self.pending.push(Message::Transaction2(transaction));
+				}
+			}
+			Step::Retry => self.relayer.retry(
+				self.chain_1.blockchain.clock.now(),
+				self.chain_2.blockchain.clock.now(),
+				&mut self.pending,
+			),
+			Step::Claim(index) => {
+				if !self.transfers.is_empty() {
+					let index = index % self.transfers.len();
+					if self.transfers[index].claims {
+						self.claim(index);
+					}
+				}
+			}
+			Step::Tick(duration) => {
+				self.chain_1.blockchain.forward_time(duration);
+				self.chain_2.blockchain.forward_time(duration);
+				let events_1 = self.chain_1.finalize().into_iter().map(Message::Event1);
+				self.pending.extend(events_1);
+				let events_2 = self.chain_2.finalize().into_iter().map(Message::Event2);
+				self.pending.extend(events_2);
+			}
+		}
+	}
+
+	/// Runs the simulation without faults until every transfer is completed or refunded.
+	pub fn settle(&mut self) -> Result<(), String> {
+		for _ in 0..SETTLE_ROUNDS {
+			while !self.pending.is_empty() {
+				let message = self.pending.remove(0);
+				self.deliver(message);
+			}
+			if self.is_settled() {
+				return Ok(());
+			}
+			for index in 0..self.transfers.len() {
+				if self.transfers[index].claims {
+					self.claim(index);
+				}
+			}
+			self.step(Step::Retry);
+			self.step(Step::Tick(SETTLE_TICK));
+		}
+		let unsettled: Vec<_> = self
+			.unsettled()
+			.into_iter()
+			.map(|index| (index, self.initiator_state(index), self.counterparty_state(index)))
+			.collect();
+		Err(format!(
+			"Transfers not settled, with their state on each chain: {unsettled:?}\n{:#?}",
+			self.relayer
+		))
+	}
+
+	/// State of the transfer on the initiator contract, unset if it isn't initiated.
+	pub fn initiator_state(&self, index: usize) -> Option<TransferState> {
+		let hash_lock = self.transfers[index].hash_lock_1();
+		let contract = &self.chain_1.blockchain.initiator_contract;
+		contract
+			.initiated_transfers
+			.values()
+			.find(|details| details.hash_lock == hash_lock)
+			.and_then(|details| contract.transfer_states.get(&details.bridge_transfer_id))
+			.map(TransferStateMachine::state)
+	}
+
+	/// State of the lock of the transfer on the counterparty contract, unset if it isn't locked.
+	pub fn counterparty_state(&self, index: usize) -> Option<TransferState> {
+		let bridge_transfer_id = self.counterparty_ids.get(&index)?;
+		self.chain_2
+			.blockchain
+			.counterparty_contract
+			.transfer_states
+			.get(bridge_transfer_id)
+			.map(TransferStateMachine::state)
+	}
+
+	/// Checks that no transfer is paid out twice: each recipient is credited the amount of its
+	/// transfer once, and only if the recipient completed it, out of the assets of the relayer.
+	pub fn check_safety(&self) -> Result<(), String> {
+		let total = self.chain_2.blockchain.total_balance();
+		if total != Amount(RELAYER_LIQUIDITY) {
+			return Err(format!("Assets of the second blockchain are {total:?}"));
+		}
+		for (index, transfer) in self.transfers.iter().enumerate() {
+			let balance = self
+				.chain_2
+				.blockchain
+				.accounts
+				.get(&transfer.recipient)
+				.copied()
+				.unwrap_or(Amount(0));
+			let completed = self.counterparty_state(index) == Some(TransferState::Completed);
+			let expected = if completed { transfer.amount } else { Amount(0) };
+			if balance != expected {
+				return Err(format!(
+					"Recipient of transfer {index} credited {balance:?}, expected {expected:?}"
+				));
+			}
+			if completed && !transfer.claims {
+				return Err(format!("Transfer {index} completed without its recipient"));
+			}
+		}
+		Ok(())
+	}
+
+	fn is_settled(&self) -> bool {
+		self.unsettled().is_empty()
+	}
+
+	/// Transfers initiated or locked which are neither completed nor refunded.
+	fn unsettled(&self) -> Vec<usize> {
+		(0..self.transfers.len())
+			.filter(|&index| {
+				!self.initiator_state(index).map_or(true, TransferState::is_final)
+					|| !self.counterparty_state(index).map_or(true, TransferState::is_final)
+			})
+			.collect()
+	}
+
+	/// The recipient completes the lock of its transfer before it expires.
+	fn claim(&mut self, index: usize) {
+		let now = self.chain_2.blockchain.clock.now();
+		let hash_lock = self.transfers[index].hash_lock_2();
+		let lock = self
+			.chain_2
+			.blockchain
+			.counterparty_contract
+			.locked_transfers
+			.values()
+			.find(|details| details.hash_lock == hash_lock && now < details.time_lock.0);
+		if let Some(lock) = lock {
+			self.pending.push(Message::Transaction2(Transaction::Counterparty(
+				CounterpartyCall::CompleteBridgeTransfer(
+					lock.bridge_transfer_id.clone(),
+					self.transfers[index].secret.clone(),
+				),
+			)));
+		}
+	}
+
+	fn take(&mut self, index: usize) -> Option<Message> {
+		if self.pending.is_empty() {
+			return None;
+		}
+		Some(self.pending.remove(index % self.pending.len()))
+	}
+
+	fn deliver(&mut self, message: Message) {
+		match message {
+			Message::Transaction1(transaction) => {
+				let event = self.chain_1.execute(transaction);
+				self.pending.extend(event.map(Message::Event1));
+			}
+			Message::Transaction2(transaction) => {
+				if let Transaction::Counterparty(CounterpartyCall::LockBridgeTransfer(
+					ref bridge_transfer_id,
+					ref hash_lock,
+					..,
+				)) = transaction
+				{
+					let index = self
+						.transfers
+						.iter()
+						.position(|transfer| transfer.hash_lock_2() == *hash_lock);
+					if let Some(index) = index {
+						self.counterparty_ids.insert(index, bridge_transfer_id.clone());
+					}
+				}
+				let event = self.chain_2.execute(transaction);
+				self.pending.extend(event.map(Message::Event2));
+			}
+			Message::Event1(event) => {
+				let now = self.chain_2.blockchain.clock.now();
+				self.relayer.handle_event_1(event, now, &mut self.pending);
+			}
+			Message::Event2(event) => self.relayer.handle_event_2(event, &mut self.pending),
+		}
+	}
+}
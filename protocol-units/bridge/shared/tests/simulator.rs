@@ -0,0 +1,53 @@
+use proptest::{collection::vec, prelude::*};
+
+use bridge_shared::transfer_store::TransferState;
+
+use crate::shared::testing::simulator::{Chain, Simulation, Step};
+
+mod shared;
+
+fn step() -> impl Strategy<Value = Step> {
+	prop_oneof![
+		6 => any::<usize>().prop_map(Step::Deliver),
+		1 => any::<usize>().prop_map(Step::Drop),
+		1 => any::<usize>().prop_map(Step::Duplicate),
+		1 => prop_oneof![Just(Chain::One), Just(Chain::Two)].prop_map(Step::Reorg),
+		1 => Just(Step::Retry),
+		1 => any::<usize>().prop_map(Step::Claim),
+		1 => (1..10u64).prop_map(Step::Tick),
+	]
+}
+
+#[test]
+fn test_simulation_without_faults() {
+	let mut simulation = Simulation::new(0, &[true, false]);
+	simulation.settle().expect("Simulation not settled");
+	simulation.check_safety().expect("Unsafe simulation");
+
+	assert_eq!(simulation.initiator_state(0), Some(TransferState::Completed));
+	assert_eq!(simulation.counterparty_state(0), Some(TransferState::Completed));
+	assert_eq!(simulation.initiator_state(1), Some(TransferState::Refunded));
+	assert_eq!(simulation.counterparty_state(1), Some(TransferState::Refunded));
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig::with_cases(128))]
+
+	/// Whatever the faults of the network and the chains, no transfer is paid out twice, and every
+	/// transfer is eventually completed or refunded.
+	#[test]
+	fn test_simulation_is_safe_and_settles(
+		seed in any::<u64>(),
+		claims in vec(any::<bool>(), 1..5),
+		steps in vec(step(), 0..200),
+	) {
+		let mut simulation = Simulation::new(seed, &claims);
+		for step in steps {
+			simulation.step(step);
+			simulation.check_safety().map_err(TestCaseError::fail)?;
+		}
+		simulation.settle().map_err(TestCaseError::fail)?;
+		simulation.check_safety().map_err(TestCaseError::fail)?;
+		prop_assert_eq!(simulation.pending(), 0);
+	}
+}
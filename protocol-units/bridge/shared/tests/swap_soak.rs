@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use test_log::test;
+
+use bridge_shared::{
+	blockchain_service::AbstractBlockchainService,
+	bridge_service::{active_swap::ActiveSwapConfig, BridgeServiceConfig},
+	swap_soak::{SwapAccounts, SwapDirection, SwapFailure, SwapSoak, SwapSoakConfig},
+	types::{InitiatorAddress, RecipientAddress},
+};
+
+use crate::shared::{
+	setup_bridge_service, B1Service, B2Service, BC1Address, BC1Hash, BC2Address,
+	CounterpartyContractMonitoring, InitiatorContractMonitoring, SetupBridgeServiceResult,
+};
+
+mod shared;
+
+fn accounts() -> SwapAccounts<BC1Address, BC2Address> {
+	SwapAccounts {
+		initiator_1: InitiatorAddress(BC1Address("initiator")),
+		recipient_1: RecipientAddress::from(BC1Address("recipient")),
+		initiator_2: InitiatorAddress(BC2Address("initiator")),
+		recipient_2: RecipientAddress::from(BC2Address("recipient")),
+	}
+}
+
+/// Sets up the simulated blockchains and the views of the user on them, the relayer is polled
+/// in the background when `relay` is set.
+fn setup_swap_soak(relay: bool, config: SwapSoakConfig) -> SwapSoak<B1Service, B2Service, BC1Hash> {
+	let SetupBridgeServiceResult(
+		mut bridge_service,
+		blockchain_1_client,
+		blockchain_2_client,
+		mut blockchain_1,
+		mut blockchain_2,
+	) = setup_bridge_service(BridgeServiceConfig { active_swap: ActiveSwapConfig::default() });
+
+	let user_blockchain_1 = AbstractBlockchainService {
+		initiator_contract: blockchain_1_client.clone(),
+		initiator_monitoring: InitiatorContractMonitoring::build(blockchain_1.add_event_listener()),
+		counterparty_contract: blockchain_1_client,
+		counterparty_monitoring: CounterpartyContractMonitoring::build(
+			blockchain_1.add_event_listener(),
+		),
+		_phantom: Default::default(),
+	};
+	let user_blockchain_2 = AbstractBlockchainService {
+		initiator_contract: blockchain_2_client.clone(),
+		initiator_monitoring: InitiatorContractMonitoring::build(blockchain_2.add_event_listener()),
+		counterparty_contract: blockchain_2_client,
+		counterparty_monitoring: CounterpartyContractMonitoring::build(
+			blockchain_2.add_event_listener(),
+		),
+		_phantom: Default::default(),
+	};
+
+	tokio::spawn(blockchain_1);
+	tokio::spawn(blockchain_2);
+	if relay {
+		tokio::spawn(async move { while bridge_service.next().await.is_some() {} });
+	} else {
+		// the relayer is kept alive without being polled
+		tokio::spawn(async move {
+			tokio::time::sleep(Duration::from_secs(60)).await;
+			drop(bridge_service);
+		});
+	}
+
+	SwapSoak::new(user_blockchain_1, user_blockchain_2, accounts(), config)
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_swap_soak_completes_swaps_in_both_directions() {
+	let config = SwapSoakConfig {
+		swaps: 20,
+		concurrency: 5,
+		step_timeout: Duration::from_secs(10),
+		..SwapSoakConfig::default()
+	};
+	let report = setup_swap_soak(true, config).run().await;
+
+	assert_eq!(report.failures, Default::default(), "{report}");
+	assert_eq!(report.completed(), 20);
+	for direction in [SwapDirection::B1ToB2, SwapDirection::B2ToB1] {
+		assert_eq!(report.latencies[&direction].len(), 10);
+		assert!(report.percentile(direction, 50.0) <= report.percentile(direction, 99.0));
+	}
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn test_swap_soak_classifies_missing_locks() {
+	let config = SwapSoakConfig {
+		swaps: 4,
+		concurrency: 4,
+		step_timeout: Duration::from_millis(300),
+		..SwapSoakConfig::default()
+	};
+	let report = setup_swap_soak(false, config).run().await;
+
+	assert_eq!(report.completed(), 0);
+	assert_eq!(report.failures.into_iter().collect::<Vec<_>>(), [(SwapFailure::LockTimeout, 4)]);
+}
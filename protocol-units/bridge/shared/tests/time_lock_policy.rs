@@ -0,0 +1,68 @@
+use bridge_shared::types::{TimeLock, TimeLockPolicy, TimeLockPolicyError, TimeLockUnit};
+
+const POLICY: TimeLockPolicy =
+	TimeLockPolicy { min_duration: 100, max_duration: 1_000, counterparty_margin: 50 };
+
+const SECONDS: TimeLockUnit = TimeLockUnit::SECONDS;
+/// Blocks of 12 seconds.
+const BLOCKS: TimeLockUnit = TimeLockUnit(12);
+
+#[test]
+fn test_counterparty_time_lock_expires_before_initiator() {
+	assert_eq!(POLICY.counterparty_time_lock(&TimeLock(200), SECONDS, SECONDS), Ok(TimeLock(150)));
+	assert_eq!(POLICY.initiator_time_lock(150, SECONDS, SECONDS), Ok(TimeLock(150)));
+	assert_eq!(
+		TimeLockPolicy::default().counterparty_time_lock(&TimeLock(1), SECONDS, SECONDS),
+		Ok(TimeLock(1))
+	);
+}
+
+#[test]
+fn test_counterparty_time_lock_converts_units() {
+	// 20 blocks last 240 seconds
+	assert_eq!(POLICY.counterparty_time_lock(&TimeLock(20), BLOCKS, SECONDS), Ok(TimeLock(190)));
+	// 150 seconds are 12 whole blocks, the lock is rounded down to keep the margin
+	assert_eq!(POLICY.counterparty_time_lock(&TimeLock(200), SECONDS, BLOCKS), Ok(TimeLock(12)));
+	assert_eq!(POLICY.initiator_time_lock(240, BLOCKS, SECONDS), Ok(TimeLock(20)));
+}
+
+#[test]
+fn test_time_lock_policy_rejects_racy_time_locks() {
+	assert_eq!(
+		POLICY.counterparty_time_lock(&TimeLock(0), SECONDS, SECONDS),
+		Err(TimeLockPolicyError::TooShort { duration: 0, min_duration: 100 })
+	);
+	// The counterparty lock would leave the recipient 99 seconds to reveal the secret
+	assert_eq!(
+		POLICY.counterparty_time_lock(&TimeLock(149), SECONDS, SECONDS),
+		Err(TimeLockPolicyError::TooShort { duration: 99, min_duration: 100 })
+	);
+	assert_eq!(
+		POLICY.initiator_time_lock(1_001, SECONDS, SECONDS),
+		Err(TimeLockPolicyError::TooLong { duration: 1_001, max_duration: 1_000 })
+	);
+	assert_eq!(
+		POLICY.counterparty_time_lock(&TimeLock(84), BLOCKS, SECONDS),
+		Err(TimeLockPolicyError::TooLong { duration: 1_008, max_duration: 1_000 })
+	);
+	assert_eq!(
+		POLICY.check_counterparty_time_lock(&TimeLock(200), SECONDS, &TimeLock(200), SECONDS),
+		Err(TimeLockPolicyError::InsufficientMargin {
+			initiator: 200,
+			counterparty: 200,
+			margin: 50
+		})
+	);
+	assert_eq!(
+		POLICY.check_counterparty_time_lock(&TimeLock(20), BLOCKS, &TimeLock(200), SECONDS),
+		Err(TimeLockPolicyError::InsufficientMargin {
+			initiator: 240,
+			counterparty: 200,
+			margin: 50
+		})
+	);
+	assert_eq!(
+		POLICY.check_counterparty_time_lock(&TimeLock(200), SECONDS, &TimeLock(150), SECONDS),
+		Ok(())
+	);
+}
@@ -0,0 +1,122 @@
+use bridge_shared::{
+	bridge_monitoring::{BridgeContractCounterpartyEvent, BridgeContractInitiatorEvent},
+	transfer_index::{
+		InMemoryTransferIndex, IndexedEvent, IndexedEventKind, TransferIndex, TransferQuery,
+	},
+	types::{
+		Amount, BridgeTransferDetails, BridgeTransferId, HashLock, InitiatorAddress, LockDetails,
+		RecipientAddress, TimeLock,
+	},
+};
+
+fn initiated(id: [u8; 2], initiator: &'static str, observed_at: u64) -> IndexedEvent {
+	let event = BridgeContractInitiatorEvent::Initiated(BridgeTransferDetails {
+		bridge_transfer_id: BridgeTransferId(id),
+		initiator_address: InitiatorAddress(initiator),
+		recipient_address: RecipientAddress(vec![0xab]),
+		hash_lock: HashLock([0; 2]),
+		time_lock: TimeLock(100),
+		amount: Amount(1000),
+		token: None,
+	});
+	IndexedEvent::from_initiator_event("B1", &event, observed_at)
+}
+
+fn locked(id: [u8; 2], observed_at: u64) -> IndexedEvent {
+	let event = BridgeContractCounterpartyEvent::Locked(LockDetails {
+		bridge_transfer_id: BridgeTransferId(id),
+		recipient_address: RecipientAddress(vec![0xab]),
+		hash_lock: HashLock([0; 2]),
+		time_lock: TimeLock(50),
+		amount: Amount(1000),
+	});
+	IndexedEvent::from_counterparty_event("B2", &event, observed_at)
+}
+
+fn completed(id: [u8; 2], observed_at: u64) -> IndexedEvent {
+	let event = BridgeContractInitiatorEvent::<&str, _>::Completed(BridgeTransferId(id));
+	IndexedEvent::from_initiator_event("B1", &event, observed_at)
+}
+
+#[tokio::test]
+async fn test_transfer_joins_events_of_both_chains() {
+	let index = InMemoryTransferIndex::new();
+
+	index.index(initiated([1, 1], "alice", 10)).await.unwrap();
+	index.index(locked([1, 1], 12)).await.unwrap();
+	index.index(completed([1, 1], 20)).await.unwrap();
+	// Replayed on restart
+	index.index(locked([1, 1], 30)).await.unwrap();
+
+	let transfer = index.transfer(&[1, 1]).await.unwrap().unwrap();
+	assert_eq!(transfer.chain, "B1");
+	assert_eq!(transfer.initiator_address, "0x616c696365");
+	assert_eq!(transfer.recipient_address, "0xab");
+	assert_eq!(transfer.status, IndexedEventKind::Completed);
+	assert_eq!((transfer.initiated_at, transfer.updated_at), (10, 20));
+
+	let events = index.events(&[1, 1]).await.unwrap();
+	assert_eq!(
+		events.iter().map(|event| (event.chain.as_str(), event.kind)).collect::<Vec<_>>(),
+		vec![
+			("B1", IndexedEventKind::Initiated),
+			("B2", IndexedEventKind::Locked),
+			("B1", IndexedEventKind::Completed),
+		]
+	);
+}
+
+#[tokio::test]
+async fn test_query_transfers() {
+	let index = InMemoryTransferIndex::new();
+	index.index(initiated([1, 1], "alice", 10)).await.unwrap();
+	index.index(initiated([2, 2], "bob", 20)).await.unwrap();
+	index.index(initiated([3, 3], "alice", 30)).await.unwrap();
+	index.index(locked([3, 3], 31)).await.unwrap();
+
+	let ids = |transfers: Vec<bridge_shared::transfer_index::IndexedTransfer>| {
+		transfers.into_iter().map(|transfer| transfer.bridge_transfer_id).collect::<Vec<_>>()
+	};
+	let query = TransferQuery { address: Some("0x616C696365".to_string()), ..Default::default() };
+	assert_eq!(ids(index.transfers(&query).await.unwrap()), vec![vec![3, 3], vec![1, 1]]);
+
+	let query = TransferQuery { address: Some("0xAB".to_string()), limit: 2, ..Default::default() };
+	assert_eq!(ids(index.transfers(&query).await.unwrap()), vec![vec![3, 3], vec![2, 2]]);
+
+	let query = TransferQuery { from: Some(15), to: Some(30), ..Default::default() };
+	assert_eq!(ids(index.transfers(&query).await.unwrap()), vec![vec![3, 3], vec![2, 2]]);
+
+	let query = TransferQuery { status: Some(IndexedEventKind::Initiated), ..Default::default() };
+	assert_eq!(ids(index.transfers(&query).await.unwrap()), vec![vec![2, 2], vec![1, 1]]);
+}
+
+#[tokio::test]
+async fn test_reverted_transfer_is_initiated_again() {
+	let index = InMemoryTransferIndex::new();
+	index.index(initiated([1, 1], "alice", 10)).await.unwrap();
+	let reverted = BridgeContractInitiatorEvent::<&str, _>::Reverted(BridgeTransferId([1, 1]));
+	index.index(IndexedEvent::from_initiator_event("B1", &reverted, 11)).await.unwrap();
+	assert_eq!(
+		index.transfer(&[1, 1]).await.unwrap().unwrap().status,
+		IndexedEventKind::Reverted
+	);
+
+	index.index(initiated([1, 1], "alice", 12)).await.unwrap();
+	let transfer = index.transfer(&[1, 1]).await.unwrap().unwrap();
+	assert_eq!((transfer.status, transfer.initiated_at), (IndexedEventKind::Initiated, 12));
+}
+
+#[test]
+fn test_statuses_only_move_forward() {
+	use IndexedEventKind::*;
+
+	assert!(Initiated.advances_to(Locked));
+	assert!(Locked.advances_to(Completed));
+	assert!(!Completed.advances_to(Locked));
+	assert!(!Locked.advances_to(Initiated));
+	assert!(!Reverted.advances_to(Completed));
+	assert!(Reverted.advances_to(Initiated));
+	for kind in IndexedEventKind::ALL {
+		assert_eq!(IndexedEventKind::from_name(kind.name()), Some(kind));
+	}
+}
@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+
+use bridge_shared::{
+	transfer_limits::{TransferLimitError, TransferLimiter, TransferLimits},
+	types::{Amount, InitiatorAddress},
+};
+
+const LIMITS: TransferLimits = TransferLimits {
+	window: Duration::from_secs(3_600),
+	max_value: Some(1_000),
+	max_value_per_initiator: Some(600),
+};
+
+#[test]
+fn test_transfer_limits() {
+	let mut limiter = TransferLimiter::new(LIMITS);
+	let now = Instant::now();
+
+	limiter.try_lock(&InitiatorAddress("alice"), Amount(500), now).unwrap();
+	assert_eq!(
+		limiter.try_lock(&InitiatorAddress("alice"), Amount(200), now),
+		Err(TransferLimitError::Initiator { amount: 200, locked: 500, limit: 600 })
+	);
+	limiter.try_lock(&InitiatorAddress("bob"), Amount(500), now).unwrap();
+	assert_eq!(
+		limiter.try_lock(&InitiatorAddress("carol"), Amount(1), now),
+		Err(TransferLimitError::Total { amount: 1, locked: 1_000, limit: 1_000 })
+	);
+	assert_eq!(limiter.locked(now), 1_000);
+	assert_eq!(limiter.locked_by(&InitiatorAddress("alice"), now), 500);
+}
+
+#[test]
+fn test_transfer_limits_window_slides() {
+	let mut limiter = TransferLimiter::new(LIMITS);
+	let now = Instant::now();
+
+	limiter.try_lock(&InitiatorAddress("alice"), Amount(600), now).unwrap();
+	let later = now + Duration::from_secs(1_800);
+	limiter.try_lock(&InitiatorAddress("bob"), Amount(400), later).unwrap();
+
+	let expired = now + LIMITS.window;
+	assert_eq!(limiter.locked(expired), 400);
+	assert_eq!(limiter.locked_by(&InitiatorAddress("alice"), expired), 0);
+	limiter.try_lock(&InitiatorAddress("alice"), Amount(600), expired).unwrap();
+}
+
+#[test]
+fn test_transfer_limits_unlimited() {
+	let mut limiter = TransferLimiter::new(TransferLimits::default());
+	let now = Instant::now();
+
+	limiter.try_lock(&InitiatorAddress("alice"), Amount(u128::MAX), now).unwrap();
+	limiter.try_lock(&InitiatorAddress("alice"), Amount(u128::MAX), now).unwrap();
+}
@@ -0,0 +1,61 @@
+use bridge_shared::transfer_store::{InvalidTransition, TransferState, TransferStateMachine};
+use proptest::{collection::vec, prelude::*, sample::select};
+
+fn transfer_state() -> impl Strategy<Value = TransferState> {
+	select(TransferState::ALL.to_vec())
+}
+
+#[test]
+fn test_state_machine_rejects_invalid_transitions() {
+	let mut machine = TransferStateMachine::new();
+	machine.transition(TransferState::Locked).unwrap();
+	machine.transition(TransferState::Completed).unwrap();
+
+	assert_eq!(
+		machine.transition(TransferState::Locked),
+		Err(InvalidTransition { from: TransferState::Completed, to: TransferState::Locked })
+	);
+	assert_eq!(machine.state(), TransferState::Completed);
+	assert!(machine.is_final());
+}
+
+proptest! {
+	#[test]
+	fn test_state_machine_follows_transitions(
+		start in transfer_state(),
+		transitions in vec(transfer_state(), 0..32),
+	) {
+		let mut machine = TransferStateMachine::from(start);
+		let mut moves = 0;
+		for next in transitions {
+			let from = machine.state();
+			match machine.transition(next) {
+				Ok(()) => {
+					prop_assert!(from.can_transition_to(next));
+					prop_assert_eq!(machine.state(), next);
+					moves += 1;
+				}
+				Err(error) => {
+					prop_assert_eq!(error, InvalidTransition { from, to: next });
+					prop_assert_eq!(machine.state(), from);
+				}
+			}
+		}
+		// Initiated, pending approval, insufficient liquidity, locked and completed
+		prop_assert!(moves <= 4);
+	}
+
+	#[test]
+	fn test_final_state_machine_never_moves(
+		transitions in vec(transfer_state(), 1..32),
+	) {
+		let mut machine = TransferStateMachine::new();
+		for next in transitions {
+			let was_final = machine.is_final();
+			let from = machine.state();
+			let moved = machine.transition(next).is_ok();
+			prop_assert!(!(was_final && moved));
+			prop_assert!(moved || machine.state() == from);
+		}
+	}
+}
@@ -0,0 +1,138 @@
+use bridge_shared::{
+	transfer_store::{
+		InMemoryTransferStateStore, TransferRecord, TransferState, TransferStateStore,
+		TransferStateStoreError,
+	},
+	types::{BridgeTransferId, TimeLock},
+};
+
+#[tokio::test]
+async fn test_transfer_state_transitions() {
+	let store = InMemoryTransferStateStore::new();
+	let bridge_transfer_id = BridgeTransferId("transfer_id");
+
+	store.initiate(bridge_transfer_id.clone(), TimeLock(100)).await.unwrap();
+	assert_eq!(
+		store.initiate(bridge_transfer_id.clone(), TimeLock(100)).await,
+		Err(TransferStateStoreError::AlreadyStored)
+	);
+
+	let record = store.lock(bridge_transfer_id.clone(), TimeLock(50)).await.unwrap();
+	assert_eq!(
+		record,
+		TransferRecord {
+			state: TransferState::Locked,
+			initiator_time_lock: TimeLock(100),
+			counterparty_time_lock: Some(TimeLock(50)),
+		}
+	);
+	assert_eq!(store.in_flight().await.unwrap(), vec![(bridge_transfer_id.clone(), record)]);
+
+	store
+		.transition(bridge_transfer_id.clone(), TransferState::Completed)
+		.await
+		.unwrap();
+	assert!(store.in_flight().await.unwrap().is_empty());
+
+	assert_eq!(
+		store.transition(bridge_transfer_id.clone(), TransferState::Refunded).await,
+		Err(TransferStateStoreError::InvalidTransition {
+			from: TransferState::Completed,
+			to: TransferState::Refunded
+		})
+	);
+}
+
+#[tokio::test]
+async fn test_transition_of_unknown_transfer() {
+	let store = InMemoryTransferStateStore::new();
+
+	assert_eq!(
+		store.transition(BridgeTransferId("unknown"), TransferState::Refunded).await,
+		Err(TransferStateStoreError::TransferNotFound)
+	);
+}
+
+#[test]
+fn test_completed_requires_locked() {
+	assert!(!TransferState::Initiated.can_transition_to(TransferState::Completed));
+	assert!(TransferState::Locked.can_transition_to(TransferState::Completed));
+	assert!(!TransferState::Refunded.can_transition_to(TransferState::Initiated));
+	assert!(TransferState::Initiated.can_transition_to(TransferState::PendingApproval));
+	assert!(TransferState::PendingApproval.can_transition_to(TransferState::Locked));
+	assert!(!TransferState::PendingApproval.can_transition_to(TransferState::Completed));
+	assert!(TransferState::Initiated.can_transition_to(TransferState::InsufficientLiquidity));
+	assert!(TransferState::InsufficientLiquidity.can_transition_to(TransferState::Locked));
+	assert!(!TransferState::InsufficientLiquidity.can_transition_to(TransferState::Completed));
+}
+
+#[test]
+fn test_transition_table() {
+	use TransferState::*;
+	let states = TransferState::ALL;
+	let allowed = [
+		(Initiated, PendingApproval),
+		(Initiated, InsufficientLiquidity),
+		(Initiated, Locked),
+		(Initiated, Refunded),
+		(PendingApproval, InsufficientLiquidity),
+		(PendingApproval, Locked),
+		(PendingApproval, Refunded),
+		(InsufficientLiquidity, Locked),
+		(InsufficientLiquidity, Refunded),
+		(Locked, Completed),
+		(Locked, Refunded),
+	];
+
+	for from in states {
+		for to in states {
+			assert_eq!(
+				from.can_transition_to(to),
+				allowed.contains(&(from, to)),
+				"transition from {from:?} to {to:?}"
+			);
+		}
+		// A state delivered again is never a transition
+		assert!(!from.can_transition_to(from));
+		assert_eq!(from.is_final(), states.iter().all(|&to| !from.can_transition_to(to)));
+	}
+}
+
+#[tokio::test]
+async fn test_duplicate_transition_is_rejected() {
+	let store = InMemoryTransferStateStore::new();
+	let bridge_transfer_id = BridgeTransferId("transfer_id");
+
+	store.initiate(bridge_transfer_id.clone(), TimeLock(100)).await.unwrap();
+	store.lock(bridge_transfer_id.clone(), TimeLock(50)).await.unwrap();
+	assert_eq!(
+		store.transition(bridge_transfer_id.clone(), TransferState::Locked).await,
+		Err(TransferStateStoreError::InvalidTransition {
+			from: TransferState::Locked,
+			to: TransferState::Locked
+		})
+	);
+	assert_eq!(
+		store.get(&bridge_transfer_id).await.unwrap().map(|record| record.state),
+		Some(TransferState::Locked)
+	);
+}
+
+#[tokio::test]
+async fn test_monitoring_checkpoint() {
+	let store = InMemoryTransferStateStore::<&str>::new();
+
+	assert_eq!(store.checkpoint().await, Ok(None));
+	store.set_checkpoint(42).await.unwrap();
+	assert_eq!(store.checkpoint().await, Ok(Some(42)));
+}
+
+#[tokio::test]
+async fn test_removed_transfer_is_not_in_flight() {
+	let store = InMemoryTransferStateStore::new();
+	store.initiate(BridgeTransferId("reverted"), TimeLock(100)).await.unwrap();
+
+	store.remove(&BridgeTransferId("reverted")).await.unwrap();
+	assert_eq!(store.get(&BridgeTransferId("reverted")).await, Ok(None));
+	assert!(store.in_flight().await.unwrap().is_empty());
+}
@@ -0,0 +1,67 @@
+use std::{
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+	time::Duration,
+};
+
+use bridge_shared::work_queue::WorkQueue;
+use futures::future::join_all;
+
+#[tokio::test]
+async fn test_work_queue_bounds_running_calls() {
+	let queue = WorkQueue::new(3);
+	let running = Arc::new(AtomicUsize::new(0));
+	let max_running = Arc::new(AtomicUsize::new(0));
+
+	let calls = (0..10).map(|key| {
+		let (running, max_running) = (running.clone(), max_running.clone());
+		queue.run(key, async move {
+			let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+			max_running.fetch_max(now_running, Ordering::SeqCst);
+			tokio::time::sleep(Duration::from_millis(10)).await;
+			running.fetch_sub(1, Ordering::SeqCst);
+			key
+		})
+	});
+
+	assert_eq!(join_all(calls).await, (0..10).collect::<Vec<_>>());
+	assert_eq!(max_running.load(Ordering::SeqCst), 3);
+	assert_eq!((queue.running(), queue.waiting()), (0, 0));
+}
+
+#[tokio::test]
+async fn test_work_queue_serializes_calls_by_key() {
+	let queue = WorkQueue::new(4);
+	let order = Arc::new(Mutex::new(Vec::new()));
+
+	let call = |key: &'static str, index: usize, delay: u64| {
+		let order = order.clone();
+		queue.run(key, async move {
+			order.lock().unwrap().push(format!("{key}{index} start"));
+			tokio::time::sleep(Duration::from_millis(delay)).await;
+			order.lock().unwrap().push(format!("{key}{index} end"));
+		})
+	};
+
+	// The calls of the nonce `a` run one after the other, `b` doesn't wait for them
+	join_all([call("a", 1, 30), call("a", 2, 0), call("b", 1, 10)]).await;
+
+	assert_eq!(
+		*order.lock().unwrap(),
+		["a1 start", "b1 start", "b1 end", "a1 end", "a2 start", "a2 end"]
+	);
+}
+
+#[tokio::test]
+async fn test_work_queue_releases_dropped_calls() {
+	let queue = WorkQueue::new(1);
+
+	let pending = queue.run("a", futures::future::pending::<()>());
+	let timed_out = tokio::time::timeout(Duration::from_millis(10), pending).await;
+	assert!(timed_out.is_err());
+
+	assert_eq!(queue.run("a", async { 1 }).await, 1);
+	assert_eq!((queue.running(), queue.waiting()), (0, 0));
+}
@@ -0,0 +1,314 @@
+use std::pin::Pin;
+
+use m1_da_light_node_grpc::light_node_service_client::LightNodeServiceClient;
+use m1_da_light_node_grpc::*;
+pub use movement_retry::RetryPolicy;
+use thiserror::Error;
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::{Channel, Endpoint};
+use tonic::Code;
+use tracing::warn;
+
+#[derive(Debug, Error)]
+pub enum LightNodeClientError {
+	#[error("Invalid light node endpoint {endpoint}: {source}")]
+	InvalidEndpoint { endpoint: String, source: tonic::transport::Error },
+	#[error("Light node request failed: {0}")]
+	Status(Box<tonic::Status>),
+	#[error("Invalid light node response: {0}")]
+	InvalidResponse(&'static str),
+}
+
+impl From<tonic::Status> for LightNodeClientError {
+	fn from(status: tonic::Status) -> Self {
+		Self::Status(Box::new(status))
+	}
+}
+
+impl LightNodeClientError {
+	/// Whether the request may succeed if retried, once the light node is reachable again.
+	pub fn is_retryable(&self) -> bool {
+		match self {
+			Self::Status(status) => matches!(
+				status.code(),
+				Code::Unavailable | Code::Unknown | Code::DeadlineExceeded | Code::Aborted
+			),
+			Self::InvalidEndpoint { .. } | Self::InvalidResponse(_) => false,
+		}
+	}
+}
+
+/// Rule of the [RetryPolicy] retrying the requests failing while the light node is unreachable.
+pub const TRANSPORT_RETRY_RULE: &str = "transport";
+
+/// The retry policy of a client, unless set with [LightNodeClient::with_retry_policy].
+pub fn default_retry_policy() -> RetryPolicy {
+	RetryPolicy { max_attempts: 5, backoff_base: 200, backoff_max: 5_000, ..RetryPolicy::default() }
+}
+
+/// What a blob read from or written to the light node is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobKind {
+	/// A blob passed through to the DA as is.
+	PassedThrough,
+	/// A block of transactions built by the sequencer.
+	SequencedBlock,
+	/// A transaction accepted by the sequencer, to be included in a block.
+	SequencedIntent,
+}
+
+/// A blob of the light node, with its kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightNodeBlob {
+	pub kind: BlobKind,
+	pub blob: Blob,
+}
+
+impl TryFrom<BlobResponse> for LightNodeBlob {
+	type Error = LightNodeClientError;
+
+	fn try_from(response: BlobResponse) -> Result<Self, Self::Error> {
+		let (kind, blob) = match response
+			.blob_type
+			.ok_or(LightNodeClientError::InvalidResponse("No blob type in response"))?
+		{
+			blob_response::BlobType::PassedThroughBlob(blob) => (BlobKind::PassedThrough, blob),
+			blob_response::BlobType::SequencedBlobBlock(blob) => (BlobKind::SequencedBlock, blob),
+			blob_response::BlobType::SequencedBlobIntent(blob) => (BlobKind::SequencedIntent, blob),
+		};
+		Ok(Self { kind, blob })
+	}
+}
+
+pub type LightNodeBlobStream =
+	Pin<Box<dyn Stream<Item = Result<LightNodeBlob, LightNodeClientError>> + Send>>;
+
+/// Typed client of the light node service.
+///
+/// The channel connects lazily and reconnects on its own, the requests failing while the light
+/// node is unreachable are retried following the [RetryPolicy]. Retried submissions may be written
+/// more than once.
+#[derive(Debug, Clone)]
+pub struct LightNodeClient {
+	client: LightNodeServiceClient<Channel>,
+	retry_policy: RetryPolicy,
+}
+
+impl LightNodeClient {
+	/// Creates a client of the light node at `endpoint`, e.g. `http://0.0.0.0:30730`.
+	pub fn new(endpoint: impl Into<String>) -> Result<Self, LightNodeClientError> {
+		let endpoint = endpoint.into();
+		let channel = Endpoint::from_shared(endpoint.clone())
+			.map_err(|source| LightNodeClientError::InvalidEndpoint { endpoint, source })?
+			.connect_lazy();
+		Ok(Self {
+			client: LightNodeServiceClient::new(channel),
+			retry_policy: default_retry_policy(),
+		})
+	}
+
+	/// Creates a client of the light node listening at `hostname:port`.
+	pub fn for_address(hostname: &str, port: u16) -> Result<Self, LightNodeClientError> {
+		Self::new(format!("http://{}:{}", hostname, port))
+	}
+
+	pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+		self.retry_policy = retry_policy;
+		self
+	}
+
+	/// The raw gRPC client, for the endpoints without a typed method.
+	pub fn raw(&self) -> LightNodeServiceClient<Channel> {
+		self.client.clone()
+	}
+
+	/// Runs the request, retrying it while it fails with a retryable error.
+	async fn with_retries<T, F, Fut>(&self, request: F) -> Result<T, LightNodeClientError>
+	where
+		F: Fn(LightNodeServiceClient<Channel>) -> Fut,
+		Fut: std::future::Future<Output = Result<T, LightNodeClientError>>,
+	{
+		let mut attempt = 1;
+		loop {
+			match request(self.client.clone()).await {
+				Err(e)
+					if e.is_retryable()
+						&& attempt < self.retry_policy.max_attempts_for(TRANSPORT_RETRY_RULE) =>
+				{
+					warn!("Light node request failed, attempt {}: {}", attempt, e);
+					let delay = self.retry_policy.jittered_delay(TRANSPORT_RETRY_RULE, attempt - 1);
+					tokio::time::sleep(delay).await;
+					attempt += 1;
+				}
+				result => return result,
+			}
+		}
+	}
+
+	/// Submits a blob, returning it as written.
+	pub async fn submit(&self, data: Vec<u8>) -> Result<LightNodeBlob, LightNodeClientError> {
+		self.submit_blobs(vec![data])
+			.await?
+			.pop()
+			.ok_or(LightNodeClientError::InvalidResponse("No blob in response"))
+	}
+
+	/// Submits blobs, each on its own, returning them as written.
+	pub async fn submit_blobs(
+		&self,
+		blobs: Vec<Vec<u8>>,
+	) -> Result<Vec<LightNodeBlob>, LightNodeClientError> {
+		let request = BatchWriteRequest {
+			blobs: blobs
+				.into_iter()
+				.map(|data| BlobWrite { data, namespace: String::new() })
+				.collect(),
+		};
+		let response = self
+			.with_retries(|mut client| {
+				let request = request.clone();
+				async move { Ok(client.batch_write(request).await?.into_inner()) }
+			})
+			.await?;
+		response.blobs.into_iter().map(LightNodeBlob::try_from).collect()
+	}
+
+	/// Submits transactions packed together into blobs, returning the inclusion handle of each.
+	pub async fn submit_batch(
+		&self,
+		transactions: Vec<Vec<u8>>,
+	) -> Result<Vec<InclusionHandle>, LightNodeClientError> {
+		let request = SubmitBatchRequest {
+			transactions: transactions
+				.into_iter()
+				.map(|data| BlobWrite { data, namespace: String::new() })
+				.collect(),
+			namespace: String::new(),
+		};
+		let response = self
+			.with_retries(|mut client| {
+				let request = request.clone();
+				async move { Ok(client.submit_batch(request).await?.into_inner()) }
+			})
+			.await?;
+		Ok(response.handles)
+	}
+
+	/// Gets the blobs at the height, none if there are no blobs at the height.
+	pub async fn get_by_height(
+		&self,
+		height: u64,
+	) -> Result<Vec<LightNodeBlob>, LightNodeClientError> {
+		let request = ReadAtHeightRequest { height, namespaces: Vec::new() };
+		let response = self
+			.with_retries(|mut client| {
+				let request = request.clone();
+				async move {
+					match client.read_at_height(request).await {
+						Ok(response) => Ok(response.into_inner().blobs),
+						Err(status) if status.code() == Code::NotFound => Ok(Vec::new()),
+						Err(status) => Err(status.into()),
+					}
+				}
+			})
+			.await?;
+		response.into_iter().map(LightNodeBlob::try_from).collect()
+	}
+
+	/// Acknowledges that all the blobs up to the height have been processed, returning the
+	/// checkpoint of the light node.
+	pub async fn acknowledge_height(&self, height: u64) -> Result<u64, LightNodeClientError> {
+		let request = AcknowledgeHeightRequest { height };
+		let response = self
+			.with_retries(|mut client| {
+				let request = request.clone();
+				async move { Ok(client.acknowledge_height(request).await?.into_inner()) }
+			})
+			.await?;
+		Ok(response.height)
+	}
+
+	/// Gets the last height acknowledged with [LightNodeClient::acknowledge_height], if any.
+	pub async fn checkpoint(&self) -> Result<Option<u64>, LightNodeClientError> {
+		let response = self
+			.with_retries(|mut client| async move {
+				Ok(client.get_checkpoint(GetCheckpointRequest {}).await?.into_inner())
+			})
+			.await?;
+		Ok(response.height)
+	}
+
+	/// Streams the blobs from the height on.
+	///
+	/// When the stream breaks with a retryable error, it is reopened after the last height read,
+	/// skipping the blobs of that height already streamed.
+	pub fn stream_from_height(&self, height: u64) -> LightNodeBlobStream {
+		let client = self.client.clone();
+		let retry_policy = self.retry_policy.clone();
+
+		let stream = async_stream::stream! {
+			let mut next_height = height;
+			let mut streamed_at_height: Vec<String> = Vec::new();
+			let mut attempt = 1;
+			loop {
+				let request = StreamReadFromHeightRequest { height: next_height, namespaces: Vec::new() };
+				let error = match client.clone().stream_read_from_height(request).await {
+					Ok(response) => {
+						let mut responses = response.into_inner();
+						loop {
+							match responses.next().await {
+								Some(Ok(response)) => {
+									attempt = 1;
+									let blob = response
+										.blob
+										.ok_or(LightNodeClientError::InvalidResponse("No blob in response"))
+										.and_then(LightNodeBlob::try_from);
+									let blob = match blob {
+										Ok(blob) => blob,
+										Err(e) => {
+											yield Err(e);
+											return;
+										}
+									};
+									if blob.blob.height != next_height {
+										next_height = blob.blob.height;
+										streamed_at_height.clear();
+									} else if streamed_at_height.contains(&blob.blob.blob_id) {
+										continue;
+									}
+									streamed_at_height.push(blob.blob.blob_id.clone());
+									yield Ok(blob);
+								}
+								Some(Err(status)) => break LightNodeClientError::from(status),
+								None => return,
+							}
+						}
+					}
+					Err(status) => LightNodeClientError::from(status),
+				};
+
+				if !error.is_retryable() || attempt >= retry_policy.max_attempts_for(TRANSPORT_RETRY_RULE) {
+					yield Err(error);
+					return;
+				}
+				warn!("Light node stream broke at height {}, attempt {}: {}", next_height, attempt, error);
+				tokio::time::sleep(retry_policy.jittered_delay(TRANSPORT_RETRY_RULE, attempt - 1)).await;
+				attempt += 1;
+			}
+		};
+
+		Box::pin(stream)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_only_transport_failures_are_retried() {
+		assert!(LightNodeClientError::from(tonic::Status::unavailable("down")).is_retryable());
+		assert!(!LightNodeClientError::from(tonic::Status::internal("bad blob")).is_retryable());
+		assert!(!LightNodeClientError::InvalidResponse("No blob type in response").is_retryable());
+	}
+}
@@ -1,5 +1,8 @@
 #[cfg(test)]
 pub mod test;
 
+pub mod client;
+
+pub use client::*;
 pub use m1_da_light_node_grpc::light_node_service_client::LightNodeServiceClient;
 pub use m1_da_light_node_grpc::*;
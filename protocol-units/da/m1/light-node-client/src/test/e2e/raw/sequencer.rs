@@ -7,14 +7,16 @@ async fn test_light_node_submits_blob_over_stream() -> Result<(), anyhow::Error>
 	let mut client = LightNodeServiceClient::connect("http://0.0.0.0:30730").await?;
 
 	let data = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
-	let blob_write = BlobWrite { data: data.clone() };
+	let blob_write = BlobWrite { data: data.clone(), namespace: String::new() };
 	let batch_write_request = BatchWriteRequest { blobs: vec![blob_write.clone()] };
 	client.batch_write(batch_write_request).await?;
 
 	let mut log_lines = Vec::new();
 
 	for _ in 0..16 {
-		let stream = client.stream_read_latest(StreamReadLatestRequest {}).await?;
+		let stream = client
+			.stream_read_latest(StreamReadLatestRequest { namespaces: Vec::new() })
+			.await?;
 
 		let back = stream
 			.into_inner()
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use celestia_rpc::{BlobClient, Client, HeaderClient};
+use celestia_types::{blob::GasPrice, nmt::Namespace, Blob as CelestiaBlob};
+use m1_da_light_node_util::config::local::m1_da_light_node::CelestiaGasConfig;
+use tokio_stream::StreamExt;
+use tracing::{info, warn};
+
+use super::{DaBackend, HeaderStream, Submission};
+
+/// The min gas price accepted by the Celestia validators by default, in utia, escalated from when
+/// the estimate of the Celestia node is rejected.
+const MIN_GAS_PRICE: f64 = 0.002;
+
+/// Whether Celestia rejected a PayForBlobs for a fee under the min gas price.
+fn is_insufficient_fee(error: &str) -> bool {
+	let error = error.to_lowercase();
+	error.contains("insufficient fee") || error.contains("insufficient minimum gas price")
+}
+
+/// Whether the Celestia node found no blob of the namespaces at the height, which it reports as an
+/// error rather than as no blobs.
+fn is_blob_not_found(error: &str) -> bool {
+	error.contains("blob: not found")
+}
+
+/// The gas price of the submissions and how it is escalated when Celestia rejects a submission
+/// for insufficient fees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GasEscalation {
+	pub gas_price: Option<f64>,
+	pub max_gas_price: Option<f64>,
+	pub escalation_percent: u32,
+	pub max_escalations: u32,
+}
+
+impl GasEscalation {
+	pub fn try_from_config(config: &CelestiaGasConfig) -> Result<Self, anyhow::Error> {
+		Ok(Self {
+			gas_price: config.gas_price()?,
+			max_gas_price: config.max_gas_price()?,
+			escalation_percent: config.gas_price_escalation_percent,
+			max_escalations: config.max_gas_price_escalations,
+		})
+	}
+
+	/// The gas price to retry with once `gas_price` is rejected, `None` once at the max gas price.
+	pub fn escalate(&self, gas_price: Option<f64>) -> Option<f64> {
+		let gas_price = gas_price.unwrap_or(MIN_GAS_PRICE);
+		let escalated = gas_price * (100 + self.escalation_percent) as f64 / 100.0;
+		match self.max_gas_price {
+			Some(max_gas_price) if gas_price >= max_gas_price => None,
+			Some(max_gas_price) => Some(escalated.min(max_gas_price)),
+			None => Some(escalated),
+		}
+	}
+}
+
+/// A Celestia node as DA backend.
+#[derive(Clone)]
+pub struct CelestiaBackend {
+	pub client: Arc<Client>,
+	pub gas: GasEscalation,
+}
+
+impl CelestiaBackend {
+	pub fn new(client: Arc<Client>, gas: GasEscalation) -> Self {
+		Self { client, gas }
+	}
+}
+
+#[tonic::async_trait]
+impl DaBackend for CelestiaBackend {
+	async fn submit_blobs(&self, blobs: &[CelestiaBlob]) -> Result<Submission, anyhow::Error> {
+		let mut gas_price = self.gas.gas_price;
+		let mut escalations = 0;
+		loop {
+			let submitted = self
+				.client
+				.blob_submit(blobs, gas_price.map_or_else(GasPrice::default, GasPrice::from))
+				.await;
+			let error = match submitted {
+				Ok(height) => {
+					if escalations > 0 {
+						info!(target: "movement_timing", height, ?gas_price, escalations, "blob_submission_gas_escalated");
+					}
+					return Ok(Submission { height, gas_price });
+				}
+				Err(e) => e.to_string(),
+			};
+
+			let escalated = match self.gas.escalate(gas_price) {
+				Some(escalated)
+					if is_insufficient_fee(&error) && escalations < self.gas.max_escalations =>
+				{
+					escalated
+				}
+				_ => anyhow::bail!("Failed submitting the blob: {}", error),
+			};
+			warn!(
+				?gas_price,
+				escalated, "Blob submission rejected for insufficient fees: {}", error
+			);
+			gas_price = Some(escalated);
+			escalations += 1;
+		}
+	}
+
+	async fn retrieve_blobs(
+		&self,
+		height: u64,
+		namespaces: &[Namespace],
+	) -> Result<Vec<CelestiaBlob>, anyhow::Error> {
+		match self.client.blob_get_all(height, namespaces).await {
+			Ok(blobs) => Ok(blobs),
+			Err(e) if is_blob_not_found(&e.to_string()) => Ok(Vec::new()),
+			Err(e) => Err(anyhow::anyhow!("Failed getting the blobs: {}", e)),
+		}
+	}
+
+	async fn stream_headers(&self) -> Result<HeaderStream, anyhow::Error> {
+		let mut subscription = self.client.header_subscribe().await?;
+
+		let stream = async_stream::try_stream! {
+			while let Some(header_res) = subscription.next().await {
+				let header = header_res?;
+				let height: u64 = header.height().into();
+				yield height;
+			}
+		};
+
+		Ok(Box::pin(stream) as HeaderStream)
+	}
+
+	async fn network_head(&self) -> Result<u64, anyhow::Error> {
+		Ok(self.client.header_network_head().await?.height().into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_gas_price_escalates_up_to_the_max() {
+		let gas = GasEscalation {
+			gas_price: None,
+			max_gas_price: Some(0.004),
+			escalation_percent: 50,
+			max_escalations: 3,
+		};
+		assert_eq!(gas.escalate(None), Some(0.003));
+		assert_eq!(gas.escalate(Some(0.003)), Some(0.004));
+		assert_eq!(gas.escalate(Some(0.004)), None);
+	}
+
+	#[test]
+	fn test_insufficient_fee_errors() {
+		assert!(is_insufficient_fee(
+			"insufficient fees; got: 100utia required: 200utia: insufficient fee"
+		));
+		assert!(is_insufficient_fee("Insufficient minimum gas price for this node"));
+		assert!(!is_insufficient_fee("blob size exceeds the max blob size"));
+	}
+
+	#[test]
+	fn test_blob_not_found_errors() {
+		assert!(is_blob_not_found(
+			"ErrorObject { code: ServerError(1), message: \"blob: not found\" }"
+		));
+		assert!(!is_blob_not_found("error sending request for url (http://localhost:26658/)"));
+	}
+}
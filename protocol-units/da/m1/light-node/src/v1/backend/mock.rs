@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use celestia_types::{nmt::Namespace, Blob as CelestiaBlob};
+use m1_da_light_node_grpc::VerificationMode;
+use m1_da_light_node_verifier::Verifier;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::{DaBackend, HeaderStream, Submission};
+
+/// An in-process DA backend, producing a header for each submission.
+///
+/// Used to run the light node for development without a Celestia network.
+pub struct MockBackend {
+	blobs: Mutex<BTreeMap<u64, Vec<CelestiaBlob>>>,
+	headers: broadcast::Sender<u64>,
+}
+
+impl Default for MockBackend {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MockBackend {
+	pub fn new() -> Self {
+		let (headers, _) = broadcast::channel(64);
+		Self { blobs: Mutex::new(BTreeMap::new()), headers }
+	}
+}
+
+#[tonic::async_trait]
+impl DaBackend for MockBackend {
+	async fn submit_blobs(&self, blobs: &[CelestiaBlob]) -> Result<Submission, anyhow::Error> {
+		let height = {
+			let mut stored = self.blobs.lock().unwrap();
+			let height = stored.keys().next_back().map_or(1, |height| height + 1);
+			stored.insert(height, blobs.to_vec());
+			height
+		};
+		// Sending fails only when there are no subscribers, which is fine.
+		let _ = self.headers.send(height);
+		Ok(Submission { height, gas_price: None })
+	}
+
+	async fn retrieve_blobs(
+		&self,
+		height: u64,
+		namespaces: &[Namespace],
+	) -> Result<Vec<CelestiaBlob>, anyhow::Error> {
+		let stored = self.blobs.lock().unwrap();
+		Ok(stored
+			.get(&height)
+			.map(|blobs| {
+				blobs
+					.iter()
+					.filter(|blob| namespaces.contains(&blob.namespace))
+					.cloned()
+					.collect()
+			})
+			.unwrap_or_default())
+	}
+
+	async fn stream_headers(&self) -> Result<HeaderStream, anyhow::Error> {
+		let mut headers = self.headers.subscribe();
+
+		let stream = async_stream::stream! {
+			loop {
+				match headers.recv().await {
+					Ok(height) => {
+						yield Ok(height);
+					}
+					Err(RecvError::Lagged(skipped)) => {
+						yield Err(anyhow::anyhow!("Header stream lagged by {} headers", skipped));
+					}
+					Err(RecvError::Closed) => break,
+				}
+			}
+		};
+
+		Ok(Box::pin(stream) as HeaderStream)
+	}
+
+	async fn network_head(&self) -> Result<u64, anyhow::Error> {
+		Ok(self.blobs.lock().unwrap().keys().next_back().copied().unwrap_or_default())
+	}
+}
+
+/// Accepts every blob: the blobs of the mock backend are not backed by any proof.
+pub struct MockVerifier;
+
+#[tonic::async_trait]
+impl Verifier for MockVerifier {
+	async fn verifiy_validator_in(
+		&self,
+		_verification_mode: VerificationMode,
+		_blob: &[u8],
+		_height: u64,
+	) -> Result<bool, anyhow::Error> {
+		Ok(true)
+	}
+
+	async fn verify_m_of_n(
+		&self,
+		_verification_mode: VerificationMode,
+		_blob: &[u8],
+		_height: u64,
+	) -> Result<bool, anyhow::Error> {
+		Ok(true)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tokio_stream::StreamExt;
+
+	#[tokio::test]
+	async fn test_mock_backend_round_trip() -> Result<(), anyhow::Error> {
+		let namespace = Namespace::new_v0(b"movement")?;
+		let other_namespace = Namespace::new_v0(b"other")?;
+		let backend = MockBackend::new();
+		let mut headers = backend.stream_headers().await?;
+
+		let blob = CelestiaBlob::new(namespace, vec![1, 2, 3])?;
+		let other_blob = CelestiaBlob::new(other_namespace, vec![4])?;
+		assert_eq!(backend.submit_blobs(&[blob.clone(), other_blob]).await?.height, 1);
+		assert_eq!(backend.submit_blobs(&[blob.clone()]).await?.height, 2);
+
+		assert_eq!(headers.next().await.expect("stream has ended")?, 1);
+		assert_eq!(headers.next().await.expect("stream has ended")?, 2);
+		assert_eq!(backend.network_head().await?, 2);
+		let retrieved = backend.retrieve_blobs(1, &[namespace]).await?;
+		assert_eq!(
+			retrieved.into_iter().map(|blob| blob.data).collect::<Vec<_>>(),
+			vec![blob.data.clone()]
+		);
+		assert_eq!(backend.retrieve_blobs(1, &[namespace, other_namespace]).await?.len(), 2);
+		assert!(backend.retrieve_blobs(3, &[namespace]).await?.is_empty());
+		Ok(())
+	}
+}
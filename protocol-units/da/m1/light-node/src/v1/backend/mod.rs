@@ -0,0 +1,41 @@
+pub mod celestia;
+pub mod mock;
+
+pub use celestia::CelestiaBackend;
+pub use mock::MockBackend;
+
+use celestia_types::{nmt::Namespace, Blob as CelestiaBlob};
+use tokio_stream::Stream;
+
+/// The outcome of a submission of blobs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Submission {
+	/// The height the blobs are included at.
+	pub height: u64,
+	/// The gas price paid in utia, unless left to the estimate of the DA node.
+	pub gas_price: Option<f64>,
+}
+
+/// Stream of the heights of the new headers of a DA backend.
+pub type HeaderStream = std::pin::Pin<Box<dyn Stream<Item = Result<u64, anyhow::Error>> + Send>>;
+
+/// A data availability layer the light node submits blobs to and reads them from, in the
+/// namespaces of the blobs.
+#[tonic::async_trait]
+pub trait DaBackend: Send + Sync {
+	/// Submits the blobs and returns the height they are included at, with the fee paid.
+	async fn submit_blobs(&self, blobs: &[CelestiaBlob]) -> Result<Submission, anyhow::Error>;
+
+	/// Retrieves the blobs of the namespaces included at the given height.
+	async fn retrieve_blobs(
+		&self,
+		height: u64,
+		namespaces: &[Namespace],
+	) -> Result<Vec<CelestiaBlob>, anyhow::Error>;
+
+	/// Streams the heights of the headers as they are produced.
+	async fn stream_headers(&self) -> Result<HeaderStream, anyhow::Error>;
+
+	/// Gets the height of the latest header.
+	async fn network_head(&self) -> Result<u64, anyhow::Error>;
+}
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::sync::Mutex;
+
+/// The last DA height the client of the light node has acknowledged processing all the blobs of.
+///
+/// With a path, the checkpoint is written to disk each time it advances, so the client can
+/// resume streaming after the checkpoint across restarts.
+#[derive(Debug)]
+pub struct HeightCheckpoint {
+	height: Mutex<Option<u64>>,
+	path: Option<PathBuf>,
+}
+
+impl HeightCheckpoint {
+	pub fn in_memory() -> Self {
+		Self { height: Mutex::new(None), path: None }
+	}
+
+	/// Opens the checkpoint stored at `path`, left by the previous run if any.
+	pub async fn open(path: impl Into<PathBuf>) -> Result<Self, anyhow::Error> {
+		let path = path.into();
+		let height = match fs::read(&path).await {
+			Ok(bytes) => Some(serde_json::from_slice::<u64>(&bytes)?),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+			Err(e) => return Err(e.into()),
+		};
+		Ok(Self { height: Mutex::new(height), path: Some(path) })
+	}
+
+	pub async fn height(&self) -> Option<u64> {
+		*self.height.lock().await
+	}
+
+	/// Moves the checkpoint to `height`, unless it is already past it, and returns the
+	/// checkpoint.
+	pub async fn advance(&self, height: u64) -> Result<u64, anyhow::Error> {
+		let mut current = self.height.lock().await;
+		if let Some(current) = current.filter(|current| *current >= height) {
+			return Ok(current);
+		}
+		if let Some(path) = &self.path {
+			// Write then rename, so a crash never leaves a truncated checkpoint.
+			let tmp_path = path.with_extension("tmp");
+			fs::write(&tmp_path, serde_json::to_vec(&height)?).await?;
+			fs::rename(&tmp_path, path).await?;
+		}
+		*current = Some(height);
+		Ok(height)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_checkpoint_only_advances_and_survives_reopen() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("checkpoint.json");
+
+		let checkpoint = HeightCheckpoint::open(&path).await?;
+		assert_eq!(checkpoint.height().await, None);
+		assert_eq!(checkpoint.advance(5).await?, 5);
+		assert_eq!(checkpoint.advance(3).await?, 5);
+		assert_eq!(checkpoint.height().await, Some(5));
+		drop(checkpoint);
+
+		assert_eq!(HeightCheckpoint::open(&path).await?.height().await, Some(5));
+		Ok(())
+	}
+}
@@ -0,0 +1,123 @@
+//! Encoding of the data of the blobs written by the light node.
+//!
+//! An encoded blob starts with [CODEC_MAGIC] and a codec byte, followed by the data encoded with
+//! the codec. Blobs written before the codec byte was introduced have no header: those starting with
+//! [ZSTD_MAGIC] were compressed by the sequencer and are decompressed, the others are read as is.
+
+use m1_da_light_node_util::config::local::m1_da_light_node::BlobCodec;
+
+/// Marks the blobs encoded with a codec.
+pub const CODEC_MAGIC: [u8; 4] = *b"M1CD";
+
+/// The magic number of a zstd frame, starting the blobs compressed before the codec header.
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// The length of the header of an encoded blob.
+pub const CODEC_HEADER_LEN: usize = CODEC_MAGIC.len() + 1;
+
+/// The zstd compression level of the blobs, zero selecting the zstd default.
+const ZSTD_LEVEL: i32 = 0;
+
+fn codec_byte(codec: BlobCodec) -> u8 {
+	match codec {
+		BlobCodec::None => 0,
+		BlobCodec::Zstd => 1,
+	}
+}
+
+fn codec_from_byte(byte: u8) -> Result<BlobCodec, anyhow::Error> {
+	match byte {
+		0 => Ok(BlobCodec::None),
+		1 => Ok(BlobCodec::Zstd),
+		_ => Err(anyhow::anyhow!("Unknown blob codec byte: {}", byte)),
+	}
+}
+
+/// Encodes the data of a blob with the codec.
+pub fn encode(data: &[u8], codec: BlobCodec) -> Result<Vec<u8>, anyhow::Error> {
+	let mut encoded = Vec::with_capacity(CODEC_HEADER_LEN + data.len());
+	encoded.extend_from_slice(&CODEC_MAGIC);
+	encoded.push(codec_byte(codec));
+	match codec {
+		BlobCodec::None => encoded.extend_from_slice(data),
+		BlobCodec::Zstd => zstd::stream::copy_encode(data, &mut encoded, ZSTD_LEVEL)?,
+	}
+	Ok(encoded)
+}
+
+/// The length of the largest data whose encoding with the codec is at most `max_len` bytes,
+/// whatever the data: zstd can expand data it cannot compress, up to its compression bound.
+pub fn max_data_len(max_len: usize, codec: BlobCodec) -> usize {
+	let max_payload_len = max_len.saturating_sub(CODEC_HEADER_LEN);
+	match codec {
+		BlobCodec::None => max_payload_len,
+		BlobCodec::Zstd => {
+			// the bound grows with the length of the data, the largest length within it is searched
+			let (mut low, mut high) = (0, max_payload_len);
+			while low < high {
+				let mid = low + (high - low + 1) / 2;
+				if zstd::zstd_safe::compress_bound(mid) <= max_payload_len {
+					low = mid;
+				} else {
+					high = mid - 1;
+				}
+			}
+			low
+		}
+	}
+}
+
+/// Decodes the data of a blob with the codec of its header.
+pub fn decode(data: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+	if data.starts_with(&ZSTD_MAGIC) {
+		return Ok(zstd::decode_all(data)?);
+	}
+	if !data.starts_with(&CODEC_MAGIC) {
+		return Ok(data.to_vec());
+	}
+	let codec_byte = data
+		.get(CODEC_MAGIC.len())
+		.copied()
+		.ok_or(anyhow::anyhow!("Missing blob codec byte"))?;
+	let payload = &data[CODEC_HEADER_LEN..];
+	match codec_from_byte(codec_byte)? {
+		BlobCodec::None => Ok(payload.to_vec()),
+		BlobCodec::Zstd => Ok(zstd::decode_all(payload)?),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_codecs_round_trip() -> Result<(), anyhow::Error> {
+		let data = [7u8; 1024];
+		for codec in [BlobCodec::None, BlobCodec::Zstd] {
+			let encoded = encode(&data, codec)?;
+			assert_eq!(encoded[CODEC_MAGIC.len()], codec_byte(codec));
+			assert_eq!(decode(&encoded)?, data);
+		}
+		assert!(encode(&data, BlobCodec::Zstd)?.len() < data.len());
+		Ok(())
+	}
+
+	#[test]
+	fn test_blobs_without_header_are_read_as_is() -> Result<(), anyhow::Error> {
+		assert_eq!(decode(&[1, 2, 3])?, vec![1, 2, 3]);
+		assert!(decode(&[b'M', b'1', b'C', b'D', 9]).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_legacy_zstd_blobs_are_decompressed() -> Result<(), anyhow::Error> {
+		// `zstd::encode_all(&b"legacy block"[..], 0)`, as the sequencer wrote the blocks before
+		// the codec header
+		let legacy = [
+			0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x58, 0x61, 0x00, 0x00, 0x6C, 0x65, 0x67, 0x61, 0x63,
+			0x79, 0x20, 0x62, 0x6C, 0x6F, 0x63, 0x6B,
+		];
+		assert_eq!(decode(&legacy)?, b"legacy block");
+		Ok(())
+	}
+}
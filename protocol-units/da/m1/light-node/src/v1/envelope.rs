@@ -0,0 +1,200 @@
+//! Signed envelopes of the blobs written by the light node.
+//!
+//! A sealed blob starts with [ENVELOPE_MAGIC] and a version byte, followed by the ed25519 public
+//! key of the signer, the signature and the payload. The signature covers the payload and the
+//! namespace of the blob, so a sealed payload cannot be replayed into another namespace. Readers
+//! authenticating the blobs drop those not sealed by one of the authorized sequencer keys.
+
+use std::fmt;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+/// Marks the blobs sealed in an envelope.
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"M1SG";
+
+/// The version of the envelope.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+pub const PUBLIC_KEY_LEN: usize = 32;
+pub const SIGNATURE_LEN: usize = 64;
+
+/// The length of the header of a sealed blob.
+pub const ENVELOPE_HEADER_LEN: usize = ENVELOPE_MAGIC.len() + 1 + PUBLIC_KEY_LEN + SIGNATURE_LEN;
+
+/// Prefix of the signed messages, so that the signature of a blob is never valid for another kind
+/// of message signed with the same key.
+const DOMAIN: &[u8] = b"MOVEMENT_DA_BLOB_ENVELOPE_V1";
+
+/// The message signed for a payload in a namespace.
+fn message(namespace: &[u8], payload: &[u8]) -> Vec<u8> {
+	let mut message = Vec::with_capacity(DOMAIN.len() + namespace.len() + payload.len());
+	message.extend_from_slice(DOMAIN);
+	message.extend_from_slice(namespace);
+	message.extend_from_slice(payload);
+	message
+}
+
+/// Parses a hex encoded 32 byte key, with or without a `0x` prefix.
+fn parse_hex_key(key: &str) -> Result<[u8; 32], anyhow::Error> {
+	let bytes = hex::decode(key.trim().trim_start_matches("0x"))
+		.map_err(|e| anyhow::anyhow!("Invalid hex key: {}", e))?;
+	<[u8; 32]>::try_from(bytes.as_slice())
+		.map_err(|_| anyhow::anyhow!("Invalid key length {}, expected 32 bytes", bytes.len()))
+}
+
+/// Parses the hex encoded ed25519 public keys of the sequencers authorized to write blobs.
+pub fn parse_public_keys(keys: &[String]) -> Result<Vec<[u8; PUBLIC_KEY_LEN]>, anyhow::Error> {
+	keys.iter()
+		.map(|key| {
+			let bytes = parse_hex_key(key)?;
+			VerifyingKey::from_bytes(&bytes)
+				.map_err(|e| anyhow::anyhow!("Invalid sequencer public key {}: {}", key, e))?;
+			Ok(bytes)
+		})
+		.collect()
+}
+
+/// Seals the blobs of the sequencer with its ed25519 key.
+#[derive(Clone)]
+pub struct BlobSigner {
+	signing_key: SigningKey,
+}
+
+impl fmt::Debug for BlobSigner {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("BlobSigner")
+			.field("public_key", &hex::encode(self.public_key()))
+			.finish()
+	}
+}
+
+impl BlobSigner {
+	pub fn new(secret_key: &[u8; 32]) -> Self {
+		Self { signing_key: SigningKey::from_bytes(secret_key) }
+	}
+
+	/// Signer with a hex encoded 32 byte ed25519 private key.
+	pub fn try_from_hex_key(key: &str) -> Result<Self, anyhow::Error> {
+		Ok(Self::new(&parse_hex_key(key)?))
+	}
+
+	/// Signer with the hex encoded private key stored at `path`.
+	pub fn try_from_key_file(path: &str) -> Result<Self, anyhow::Error> {
+		let key = std::fs::read_to_string(path)
+			.map_err(|e| anyhow::anyhow!("Failed to read the blob signing key {}: {}", path, e))?;
+		Self::try_from_hex_key(&key)
+	}
+
+	pub fn public_key(&self) -> [u8; PUBLIC_KEY_LEN] {
+		self.signing_key.verifying_key().to_bytes()
+	}
+
+	/// Seals the payload of a blob of the namespace.
+	pub fn seal(&self, namespace: &[u8], payload: &[u8]) -> Vec<u8> {
+		let signature = self.signing_key.sign(&message(namespace, payload));
+		let mut sealed = Vec::with_capacity(ENVELOPE_HEADER_LEN + payload.len());
+		sealed.extend_from_slice(&ENVELOPE_MAGIC);
+		sealed.push(ENVELOPE_VERSION);
+		sealed.extend_from_slice(&self.public_key());
+		sealed.extend_from_slice(&signature.to_bytes());
+		sealed.extend_from_slice(payload);
+		sealed
+	}
+}
+
+/// A blob read as an envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope<'a> {
+	pub public_key: [u8; PUBLIC_KEY_LEN],
+	pub signature: [u8; SIGNATURE_LEN],
+	pub payload: &'a [u8],
+}
+
+impl<'a> Envelope<'a> {
+	/// Reads the envelope of a blob, or `None` if the blob is not sealed.
+	pub fn read(data: &'a [u8]) -> Result<Option<Self>, anyhow::Error> {
+		if !data.starts_with(&ENVELOPE_MAGIC) {
+			return Ok(None);
+		}
+		let version = data.get(ENVELOPE_MAGIC.len()).copied();
+		if version != Some(ENVELOPE_VERSION) {
+			anyhow::bail!("Unsupported blob envelope version: {:?}", version);
+		}
+		if data.len() < ENVELOPE_HEADER_LEN {
+			anyhow::bail!("Truncated blob envelope");
+		}
+		let (public_key, rest) = data[ENVELOPE_MAGIC.len() + 1..].split_at(PUBLIC_KEY_LEN);
+		let (signature, payload) = rest.split_at(SIGNATURE_LEN);
+		Ok(Some(Self {
+			public_key: public_key.try_into()?,
+			signature: signature.try_into()?,
+			payload,
+		}))
+	}
+
+	/// Checks the signature of the payload in the namespace against the public key of the
+	/// envelope.
+	pub fn verify(&self, namespace: &[u8]) -> Result<(), anyhow::Error> {
+		VerifyingKey::from_bytes(&self.public_key)?
+			.verify_strict(
+				&message(namespace, self.payload),
+				&Signature::from_bytes(&self.signature),
+			)
+			.map_err(|e| anyhow::anyhow!("Invalid blob signature: {}", e))
+	}
+}
+
+/// Checks that the blob of the namespace is sealed by one of the authorized keys, and returns its
+/// payload.
+pub fn open<'a>(
+	data: &'a [u8],
+	namespace: &[u8],
+	authorized_keys: &[[u8; PUBLIC_KEY_LEN]],
+) -> Result<&'a [u8], anyhow::Error> {
+	let envelope = Envelope::read(data)?.ok_or(anyhow::anyhow!("Blob is not sealed"))?;
+	if !authorized_keys.contains(&envelope.public_key) {
+		anyhow::bail!("Blob sealed by unauthorized key {}", hex::encode(envelope.public_key));
+	}
+	envelope.verify(namespace)?;
+	Ok(envelope.payload)
+}
+
+/// Gets the payload of a blob without authenticating it, the blob itself if it is not sealed.
+pub fn payload(data: &[u8]) -> Result<&[u8], anyhow::Error> {
+	Ok(Envelope::read(data)?.map_or(data, |envelope| envelope.payload))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const NAMESPACE: &[u8] = b"movement";
+
+	#[test]
+	fn test_open_sealed_blob() -> Result<(), anyhow::Error> {
+		let signer = BlobSigner::new(&[7; 32]);
+		let sealed = signer.seal(NAMESPACE, b"block");
+
+		assert_eq!(open(&sealed, NAMESPACE, &[signer.public_key()])?, b"block");
+		assert_eq!(payload(&sealed)?, b"block");
+		assert_eq!(payload(b"unsealed")?, b"unsealed");
+		Ok(())
+	}
+
+	#[test]
+	fn test_open_rejects_forged_blobs() -> Result<(), anyhow::Error> {
+		let signer = BlobSigner::new(&[7; 32]);
+		let other_signer = BlobSigner::new(&[8; 32]);
+		let authorized_keys = [signer.public_key()];
+		let sealed = signer.seal(NAMESPACE, b"block");
+
+		// unsealed, sealed by another key, tampered with or replayed into another namespace
+		assert!(open(b"garbage", NAMESPACE, &authorized_keys).is_err());
+		assert!(open(&other_signer.seal(NAMESPACE, b"block"), NAMESPACE, &authorized_keys).is_err());
+		let mut tampered = sealed.clone();
+		*tampered.last_mut().unwrap() ^= 1;
+		assert!(open(&tampered, NAMESPACE, &authorized_keys).is_err());
+		assert!(open(&sealed, b"other", &authorized_keys).is_err());
+		Ok(())
+	}
+}
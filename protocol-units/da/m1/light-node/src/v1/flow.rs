@@ -0,0 +1,92 @@
+use std::pin::Pin;
+use std::time::Instant;
+
+use m1_da_light_node_util::config::local::m1_da_light_node::StreamLagPolicy;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tracing::{info, warn};
+
+pub type FlowStream<T> = Pin<Box<dyn Stream<Item = Result<T, anyhow::Error>> + Send>>;
+
+/// Reads the stream ahead into a buffer of `buffer_size` items, so the reader only holds back the
+/// stream once the buffer is full.
+///
+/// With [StreamLagPolicy::Park] the stream then waits for the reader. With [StreamLagPolicy::Drop]
+/// the stream ends with an error once the reader has taken the buffered items. The time spent
+/// parked and the dropped streams are reported under the `movement_timing` target.
+pub fn flow_controlled<T: Send + 'static>(
+	mut stream: FlowStream<T>,
+	buffer_size: usize,
+	policy: StreamLagPolicy,
+) -> FlowStream<T> {
+	let (sender, receiver) = mpsc::channel(buffer_size.max(1));
+
+	tokio::spawn(async move {
+		while let Some(item) = stream.next().await {
+			if sender.capacity() == 0 {
+				match policy {
+					StreamLagPolicy::Park => {
+						let start = Instant::now();
+						if sender.send(item).await.is_err() {
+							break;
+						}
+						let parked_ms = start.elapsed().as_millis() as u64;
+						info!(target: "movement_timing", buffer_size, parked_ms, "blob_stream_parked");
+						continue;
+					}
+					StreamLagPolicy::Drop => {
+						warn!(target: "movement_timing", buffer_size, "blob_stream_dropped");
+						let _ = sender
+							.send(Err(anyhow::anyhow!(
+								"Reader lagged behind {} buffered items, resume from the last height read",
+								buffer_size
+							)))
+							.await;
+						break;
+					}
+				}
+			}
+			if sender.send(item).await.is_err() {
+				// the reader is gone
+				break;
+			}
+		}
+	});
+
+	Box::pin(ReceiverStream::new(receiver))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	fn numbers(count: u64) -> FlowStream<u64> {
+		Box::pin(tokio_stream::iter((0..count).map(Ok)))
+	}
+
+	#[tokio::test]
+	async fn test_park_streams_every_item() -> Result<(), anyhow::Error> {
+		let mut stream = flow_controlled(numbers(10), 2, StreamLagPolicy::Park);
+		tokio::time::sleep(Duration::from_millis(20)).await;
+
+		let mut items = Vec::new();
+		while let Some(item) = stream.next().await {
+			items.push(item?);
+		}
+		assert_eq!(items, (0..10).collect::<Vec<_>>());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_drop_ends_the_stream_of_a_lagging_reader() -> Result<(), anyhow::Error> {
+		let mut stream = flow_controlled(numbers(10), 2, StreamLagPolicy::Drop);
+		tokio::time::sleep(Duration::from_millis(20)).await;
+
+		assert_eq!(stream.next().await.expect("stream has ended")?, 0);
+		assert_eq!(stream.next().await.expect("stream has ended")?, 1);
+		assert!(stream.next().await.expect("stream has ended").is_err());
+		assert!(stream.next().await.is_none());
+		Ok(())
+	}
+}
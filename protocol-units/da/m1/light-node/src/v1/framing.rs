@@ -0,0 +1,193 @@
+//! Framing of the blobs submitted with `SubmitBatch`.
+//!
+//! A batch blob starts with [BATCH_BLOB_MAGIC] and a version byte, followed by frames. Each frame
+//! carries a part of an item of the batch, with a header of the index of the item in the batch,
+//! the index of the part, the number of parts and the length of the data. Items fitting in a blob
+//! are sent as a single part, larger ones are split across consecutive blobs.
+
+use std::collections::BTreeMap;
+
+/// Marks the blobs holding framed batch items.
+pub const BATCH_BLOB_MAGIC: [u8; 4] = *b"M1BT";
+
+/// The version of the framing.
+pub const BATCH_BLOB_VERSION: u8 = 1;
+
+/// The length of the header of a batch blob.
+pub const BLOB_HEADER_LEN: usize = BATCH_BLOB_MAGIC.len() + 1;
+
+/// The length of the header of a frame: item index (u32), part index (u16), part count (u16) and
+/// data length (u32), all big endian.
+pub const FRAME_HEADER_LEN: usize = 12;
+
+/// A blob packed from the items of a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedBlob {
+	pub data: Vec<u8>,
+	/// The indices of the items with a part in the blob.
+	pub items: Vec<u32>,
+}
+
+impl PackedBlob {
+	fn new() -> Self {
+		let mut data = Vec::with_capacity(BLOB_HEADER_LEN);
+		data.extend_from_slice(&BATCH_BLOB_MAGIC);
+		data.push(BATCH_BLOB_VERSION);
+		Self { data, items: Vec::new() }
+	}
+
+	fn has_frames(&self) -> bool {
+		!self.items.is_empty()
+	}
+
+	fn remaining(&self, max_blob_size: usize) -> usize {
+		max_blob_size.saturating_sub(self.data.len())
+	}
+
+	fn push_frame(&mut self, index: u32, part: u16, part_count: u16, data: &[u8]) {
+		self.data.extend_from_slice(&index.to_be_bytes());
+		self.data.extend_from_slice(&part.to_be_bytes());
+		self.data.extend_from_slice(&part_count.to_be_bytes());
+		self.data.extend_from_slice(&(data.len() as u32).to_be_bytes());
+		self.data.extend_from_slice(data);
+		if self.items.last() != Some(&index) {
+			self.items.push(index);
+		}
+	}
+}
+
+/// A part of an item of a batch, read from a batch blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+	pub index: u32,
+	pub part: u16,
+	pub part_count: u16,
+	pub data: Vec<u8>,
+}
+
+/// Packs the items, in order, into blobs of at most `max_blob_size` bytes.
+pub fn pack(items: &[Vec<u8>], max_blob_size: usize) -> Result<Vec<PackedBlob>, anyhow::Error> {
+	let max_part_len = max_blob_size
+		.checked_sub(BLOB_HEADER_LEN + FRAME_HEADER_LEN)
+		.filter(|len| *len > 0)
+		.ok_or(anyhow::anyhow!("Max blob size {} is too small for a frame", max_blob_size))?;
+
+	let mut blobs = Vec::new();
+	let mut blob = PackedBlob::new();
+	for (index, item) in items.iter().enumerate() {
+		let index = u32::try_from(index)?;
+		let parts =
+			if item.is_empty() { vec![&item[..]] } else { item.chunks(max_part_len).collect() };
+		let part_count = u16::try_from(parts.len())
+			.map_err(|_| anyhow::anyhow!("Item {} is too large to be split", index))?;
+
+		for (part, data) in parts.into_iter().enumerate() {
+			if blob.has_frames() && blob.remaining(max_blob_size) < FRAME_HEADER_LEN + data.len() {
+				blobs.push(std::mem::replace(&mut blob, PackedBlob::new()));
+			}
+			blob.push_frame(index, part as u16, part_count, data);
+		}
+	}
+	if blob.has_frames() {
+		blobs.push(blob);
+	}
+
+	Ok(blobs)
+}
+
+/// Reads the frames of a batch blob, or `None` if the blob is not a batch blob.
+pub fn unpack(data: &[u8]) -> Result<Option<Vec<Frame>>, anyhow::Error> {
+	if !data.starts_with(&BATCH_BLOB_MAGIC) {
+		return Ok(None);
+	}
+	let version = data.get(BATCH_BLOB_MAGIC.len()).copied();
+	if version != Some(BATCH_BLOB_VERSION) {
+		anyhow::bail!("Unsupported batch blob version: {:?}", version);
+	}
+
+	let mut frames = Vec::new();
+	let mut rest = &data[BLOB_HEADER_LEN..];
+	while !rest.is_empty() {
+		if rest.len() < FRAME_HEADER_LEN {
+			anyhow::bail!("Truncated frame header");
+		}
+		let (header, body) = rest.split_at(FRAME_HEADER_LEN);
+		let index = u32::from_be_bytes(header[0..4].try_into()?);
+		let part = u16::from_be_bytes(header[4..6].try_into()?);
+		let part_count = u16::from_be_bytes(header[6..8].try_into()?);
+		let len = u32::from_be_bytes(header[8..12].try_into()?) as usize;
+		if body.len() < len {
+			anyhow::bail!("Truncated frame data for item {}", index);
+		}
+		let (frame_data, next) = body.split_at(len);
+		frames.push(Frame { index, part, part_count, data: frame_data.to_vec() });
+		rest = next;
+	}
+
+	Ok(Some(frames))
+}
+
+/// Joins the parts of the frames into the items of the batch, in order of their index.
+pub fn reassemble(frames: impl IntoIterator<Item = Frame>) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+	let mut parts_by_item: BTreeMap<u32, BTreeMap<u16, Frame>> = BTreeMap::new();
+	for frame in frames {
+		parts_by_item.entry(frame.index).or_default().insert(frame.part, frame);
+	}
+
+	parts_by_item
+		.into_iter()
+		.map(|(index, parts)| {
+			let part_count = parts.values().next().map_or(0, |frame| frame.part_count);
+			if parts.len() != part_count as usize
+				|| parts.keys().copied().ne(0..part_count)
+				|| parts.values().any(|frame| frame.part_count != part_count)
+			{
+				anyhow::bail!("Missing parts of item {}", index);
+			}
+			Ok(parts.into_values().flat_map(|frame| frame.data).collect())
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_small_items_share_a_blob() -> Result<(), anyhow::Error> {
+		let items = vec![vec![1; 10], vec![], vec![2; 20]];
+		let blobs = pack(&items, 100)?;
+		assert_eq!(blobs.len(), 1);
+		assert_eq!(blobs[0].items, vec![0, 1, 2]);
+
+		let frames = unpack(&blobs[0].data)?.expect("not a batch blob");
+		assert_eq!(reassemble(frames)?, items);
+		Ok(())
+	}
+
+	#[test]
+	fn test_oversize_item_is_split_across_blobs() -> Result<(), anyhow::Error> {
+		let max_blob_size = 64;
+		let items = vec![vec![1; 10], (0..150).collect::<Vec<u8>>(), vec![3; 5]];
+		let blobs = pack(&items, max_blob_size)?;
+		assert!(blobs.iter().all(|blob| blob.data.len() <= max_blob_size));
+		assert!(blobs.iter().filter(|blob| blob.items.contains(&1)).count() > 1);
+
+		let mut frames = Vec::new();
+		for blob in &blobs {
+			frames.extend(unpack(&blob.data)?.expect("not a batch blob"));
+		}
+		assert_eq!(reassemble(frames.clone())?, items);
+
+		// dropping a part of the split item fails the reassembly
+		let missing = frames.into_iter().filter(|frame| !(frame.index == 1 && frame.part == 1));
+		assert!(reassemble(missing).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_unpack_ignores_other_blobs() -> Result<(), anyhow::Error> {
+		assert_eq!(unpack(&[0, 1, 2, 3, 4, 5])?, None);
+		Ok(())
+	}
+}
@@ -0,0 +1,184 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntGauge, Opts, Registry};
+
+/// Buckets of the blob sizes, in bytes: from a single transaction to the max blob size.
+const BLOB_SIZE_BUCKETS: [f64; 8] =
+	[1_000.0, 10_000.0, 50_000.0, 100_000.0, 250_000.0, 500_000.0, 1_000_000.0, 2_000_000.0];
+
+/// Buckets of the submission latencies, in seconds: from a block to a few Celestia blocks.
+const SUBMISSION_LATENCY_BUCKETS: [f64; 8] = [0.5, 1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0];
+
+/// Buckets of the proof verification times, in seconds.
+const VERIFICATION_BUCKETS: [f64; 7] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+
+/// Prometheus metrics of the light node.
+///
+/// The metrics are registered in their own registry, rendered in the Prometheus text format by
+/// [`LightNodeMetrics::encode`]. Clones share the same metrics.
+#[derive(Debug, Clone)]
+pub struct LightNodeMetrics {
+	registry: Registry,
+	blobs_submitted: IntCounter,
+	blobs_retrieved: IntCounter,
+	blob_bytes: Histogram,
+	submission_latency: Histogram,
+	submission_failures: IntCounter,
+	proof_verification: Histogram,
+	height_lag: IntGauge,
+	mempool_transactions: IntGauge,
+	mempool_bytes: IntGauge,
+	mempool_rejected: IntCounter,
+	mempool_evicted: IntCounter,
+}
+
+impl LightNodeMetrics {
+	pub fn new() -> Result<Self, prometheus::Error> {
+		let registry = Registry::new();
+		let blobs_submitted = IntCounter::with_opts(Opts::new(
+			"m1_da_light_node_blobs_submitted_total",
+			"Blobs submitted to the DA",
+		))?;
+		let blobs_retrieved = IntCounter::with_opts(Opts::new(
+			"m1_da_light_node_blobs_retrieved_total",
+			"Blobs retrieved from the DA and handed out",
+		))?;
+		let blob_bytes = Histogram::with_opts(
+			HistogramOpts::new("m1_da_light_node_blob_bytes", "Size of the submitted blobs")
+				.buckets(BLOB_SIZE_BUCKETS.to_vec()),
+		)?;
+		let submission_latency = Histogram::with_opts(
+			HistogramOpts::new(
+				"m1_da_light_node_submission_latency_seconds",
+				"Time for a submission to be included by the DA",
+			)
+			.buckets(SUBMISSION_LATENCY_BUCKETS.to_vec()),
+		)?;
+		let submission_failures = IntCounter::with_opts(Opts::new(
+			"m1_da_light_node_submission_failures_total",
+			"Failed submissions, retried by the sequencer with smaller groups of blocks",
+		))?;
+		let proof_verification = Histogram::with_opts(
+			HistogramOpts::new(
+				"m1_da_light_node_proof_verification_seconds",
+				"Time to verify the inclusion proof of a blob",
+			)
+			.buckets(VERIFICATION_BUCKETS.to_vec()),
+		)?;
+		let height_lag = IntGauge::with_opts(Opts::new(
+			"m1_da_light_node_namespace_height_lag",
+			"Heights between the last streamed height and the DA head",
+		))?;
+		let mempool_transactions = IntGauge::with_opts(Opts::new(
+			"m1_da_light_node_mempool_transactions",
+			"Transactions waiting in the mempool of the sequencer",
+		))?;
+		let mempool_bytes = IntGauge::with_opts(Opts::new(
+			"m1_da_light_node_mempool_bytes",
+			"Bytes of the transactions waiting in the mempool of the sequencer",
+		))?;
+		let mempool_rejected = IntCounter::with_opts(Opts::new(
+			"m1_da_light_node_mempool_rejected_total",
+			"Transactions not admitted into the mempool",
+		))?;
+		let mempool_evicted = IntCounter::with_opts(Opts::new(
+			"m1_da_light_node_mempool_evicted_total",
+			"Transactions evicted from the mempool by transactions with higher fees",
+		))?;
+
+		registry.register(Box::new(blobs_submitted.clone()))?;
+		registry.register(Box::new(blobs_retrieved.clone()))?;
+		registry.register(Box::new(blob_bytes.clone()))?;
+		registry.register(Box::new(submission_latency.clone()))?;
+		registry.register(Box::new(submission_failures.clone()))?;
+		registry.register(Box::new(proof_verification.clone()))?;
+		registry.register(Box::new(height_lag.clone()))?;
+		registry.register(Box::new(mempool_transactions.clone()))?;
+		registry.register(Box::new(mempool_bytes.clone()))?;
+		registry.register(Box::new(mempool_rejected.clone()))?;
+		registry.register(Box::new(mempool_evicted.clone()))?;
+
+		Ok(Self {
+			registry,
+			blobs_submitted,
+			blobs_retrieved,
+			blob_bytes,
+			submission_latency,
+			submission_failures,
+			proof_verification,
+			height_lag,
+			mempool_transactions,
+			mempool_bytes,
+			mempool_rejected,
+			mempool_evicted,
+		})
+	}
+
+	/// Records a submission of blobs of the given sizes, included after `latency`.
+	pub fn blobs_submitted(&self, blob_sizes: impl IntoIterator<Item = usize>, latency: Duration) {
+		for size in blob_sizes {
+			self.blobs_submitted.inc();
+			self.blob_bytes.observe(size as f64);
+		}
+		self.submission_latency.observe(latency.as_secs_f64());
+	}
+
+	pub fn submission_failed(&self) {
+		self.submission_failures.inc();
+	}
+
+	pub fn blobs_retrieved(&self, count: usize) {
+		self.blobs_retrieved.inc_by(count as u64);
+	}
+
+	pub fn observe_proof_verification(&self, elapsed: Duration) {
+		self.proof_verification.observe(elapsed.as_secs_f64());
+	}
+
+	pub fn height_lag(&self, lag: u64) {
+		self.height_lag.set(lag as i64);
+	}
+
+	/// Records the size of the mempool and its total counts of rejected and evicted
+	/// transactions since the start of the node.
+	pub fn mempool(&self, transactions: usize, bytes: usize, rejected: u64, evicted: u64) {
+		self.mempool_transactions.set(transactions as i64);
+		self.mempool_bytes.set(bytes as i64);
+		self.mempool_rejected
+			.inc_by(rejected.saturating_sub(self.mempool_rejected.get()));
+		self.mempool_evicted.inc_by(evicted.saturating_sub(self.mempool_evicted.get()));
+	}
+
+	/// Renders the metrics in the Prometheus text exposition format.
+	pub fn encode(&self) -> Result<String, prometheus::Error> {
+		movement_metrics::encode(&self.registry)
+	}
+}
+
+/// Serves the metrics of the light node on `GET /metrics`, for Prometheus to scrape.
+pub async fn serve(metrics: LightNodeMetrics, address: SocketAddr) -> Result<(), anyhow::Error> {
+	movement_metrics::serve(metrics.registry, address).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_encode_metrics() -> Result<(), anyhow::Error> {
+		let metrics = LightNodeMetrics::new()?;
+		metrics.blobs_submitted([100, 200_000], Duration::from_secs(3));
+		metrics.height_lag(4);
+		metrics.mempool(12, 3_000, 5, 2);
+		metrics.mempool(10, 2_500, 7, 2);
+
+		let encoded = metrics.encode()?;
+		assert!(encoded.contains("m1_da_light_node_blobs_submitted_total 2"));
+		assert!(encoded.contains("m1_da_light_node_namespace_height_lag 4"));
+		assert!(encoded.contains("m1_da_light_node_mempool_transactions 10"));
+		assert!(encoded.contains("m1_da_light_node_mempool_rejected_total 7"));
+		assert!(encoded.contains(r#"m1_da_light_node_blob_bytes_bucket{le="1000"} 1"#));
+		Ok(())
+	}
+}
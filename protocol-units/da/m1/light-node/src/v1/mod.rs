@@ -1,3 +1,10 @@
+pub mod backend;
+pub mod checkpoint;
+pub mod codec;
+pub mod envelope;
+pub mod flow;
+pub mod metrics;
+pub mod framing;
 pub mod passthrough;
 #[cfg(feature = "sequencer")]
 pub mod sequencer;
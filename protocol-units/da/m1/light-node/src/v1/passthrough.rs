@@ -1,35 +1,58 @@
 use anyhow::Context;
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
+use std::time::Instant;
 
 use tokio::sync::RwLock;
 use tokio_stream::{Stream, StreamExt};
-use tracing::debug;
+use tracing::{debug, info, warn};
 
-use celestia_rpc::{BlobClient, Client, HeaderClient};
-use celestia_types::{blob::GasPrice, nmt::Namespace, Blob as CelestiaBlob};
+use celestia_types::{nmt::Namespace, Blob as CelestiaBlob};
 
 // FIXME: glob imports are bad style
 use m1_da_light_node_grpc::light_node_service_server::LightNodeService;
 use m1_da_light_node_grpc::*;
-use m1_da_light_node_util::config::Config;
+use m1_da_light_node_util::config::{
+	local::m1_da_light_node::{BlobCodec, DaBackend as DaBackendKind, ReadVerification},
+	Config, DEFAULT_NAMESPACE_LANE,
+};
 use m1_da_light_node_verifier::{v1::V1Verifier, Verifier};
 
+use crate::v1::backend::{
+	celestia::GasEscalation, mock::MockVerifier, CelestiaBackend, DaBackend, MockBackend,
+	Submission,
+};
+use crate::v1::checkpoint::HeightCheckpoint;
+use crate::v1::envelope::{self, BlobSigner};
+use crate::v1::flow::{self, FlowStream};
+use crate::v1::metrics::{self, LightNodeMetrics};
 use crate::v1::LightNodeV1Operations;
+use crate::v1::{codec, framing};
 
 #[derive(Clone)]
 pub struct LightNodeV1 {
 	pub config: Config,
 	pub celestia_namespace: Namespace,
-	pub default_client: Arc<Client>,
+	/// The namespaces of the lanes, by lane key, including the default lane.
+	pub namespaces: BTreeMap<String, Namespace>,
+	pub backend: Arc<dyn DaBackend>,
 	pub verification_mode: Arc<RwLock<VerificationMode>>,
-	pub verifier: Arc<Box<dyn Verifier + Send + Sync>>,
+	/// The verifiers of the blobs of each lane, by lane key.
+	pub verifiers: Arc<BTreeMap<String, Box<dyn Verifier + Send + Sync>>>,
+	pub checkpoint: Arc<HeightCheckpoint>,
+	pub metrics: LightNodeMetrics,
+	/// Signs the blobs written, if a signing key is configured.
+	pub signer: Option<Arc<BlobSigner>>,
+	/// The public keys of the sequencers whose blobs are read, every blob being read if empty.
+	pub authorized_sequencer_keys: Arc<Vec<[u8; envelope::PUBLIC_KEY_LEN]>>,
 }
 
 impl Debug for LightNodeV1 {
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
 		f.debug_struct("LightNodeV1")
 			.field("celestia_namespace", &self.config.celestia_namespace())
+			.field("namespace_lanes", &self.namespaces.keys().collect::<Vec<_>>())
 			.finish()
 	}
 }
@@ -37,20 +60,63 @@ impl Debug for LightNodeV1 {
 impl LightNodeV1Operations for LightNodeV1 {
 	/// Tries to create a new LightNodeV1 instance from the toml config file.
 	async fn try_from_config(config: Config) -> Result<Self, anyhow::Error> {
-		let client = Arc::new(config.connect_celestia().await?);
+		let namespace = config.celestia_namespace();
+		let namespaces = config.celestia_namespaces();
+		let (backend, verifiers): (Arc<dyn DaBackend>, BTreeMap<_, _>) = match config.da_backend() {
+			DaBackendKind::Celestia => {
+				let client = Arc::new(config.connect_celestia().await?);
+				let verifiers = namespaces
+					.iter()
+					.map(|(lane, namespace)| {
+						let verifier: Box<dyn Verifier + Send + Sync> =
+							Box::new(V1Verifier { client: client.clone(), namespace: *namespace });
+						(lane.clone(), verifier)
+					})
+					.collect();
+				let gas = GasEscalation::try_from_config(&config.celestia_gas())?;
+				(Arc::new(CelestiaBackend::new(client, gas)), verifiers)
+			}
+			DaBackendKind::Mock => {
+				let verifiers = namespaces
+					.keys()
+					.map(|lane| {
+						let verifier: Box<dyn Verifier + Send + Sync> = Box::new(MockVerifier);
+						(lane.clone(), verifier)
+					})
+					.collect();
+				(Arc::new(MockBackend::new()), verifiers)
+			}
+		};
+
+		let checkpoint = match config.m1_da_light_node_checkpoint_path() {
+			Some(path) => HeightCheckpoint::open(path).await?,
+			None => HeightCheckpoint::in_memory(),
+		};
+
+		let signer = match config.blob_signing_key_path() {
+			Some(path) => Some(Arc::new(BlobSigner::try_from_key_file(&path)?)),
+			None => None,
+		};
+		let authorized_sequencer_keys =
+			envelope::parse_public_keys(&config.authorized_sequencer_keys())?;
+		if let Some(signer) = &signer {
+			info!("Signing the blobs with key {}", hex::encode(signer.public_key()));
+		}
 
 		Ok(Self {
 			config: config.clone(),
-			celestia_namespace: config.celestia_namespace(),
-			default_client: client.clone(),
+			celestia_namespace: namespace,
+			namespaces,
+			backend,
 			verification_mode: Arc::new(RwLock::new(
 				VerificationMode::from_str_name("M_OF_N")
 					.context("Failed to parse verification mode")?,
 			)),
-			verifier: Arc::new(Box::new(V1Verifier {
-				client,
-				namespace: config.celestia_namespace(),
-			})),
+			verifiers: Arc::new(verifiers),
+			checkpoint: Arc::new(checkpoint),
+			metrics: LightNodeMetrics::new()?,
+			signer,
+			authorized_sequencer_keys: Arc::new(authorized_sequencer_keys),
 		})
 	}
 
@@ -60,98 +126,244 @@ impl LightNodeV1Operations for LightNodeV1 {
 
 	/// Runs background tasks for the LightNodeV1 instance.
 	async fn run_background_tasks(&self) -> Result<(), anyhow::Error> {
-		Ok(())
+		self.run_metrics_server().await
 	}
 }
 
 impl LightNodeV1 {
-	/// Creates a new blob instance with the provided data.
-	pub fn create_new_celestia_blob(&self, data: Vec<u8>) -> Result<CelestiaBlob, anyhow::Error> {
-		CelestiaBlob::new(self.celestia_namespace, data)
+	/// Resolves the lane keys of a request to their namespaces, the default namespace if there
+	/// are none. Fails on unknown lanes.
+	pub fn resolve_namespaces(&self, lanes: &[String]) -> Result<Vec<Namespace>, anyhow::Error> {
+		if lanes.is_empty() {
+			return Ok(vec![self.celestia_namespace]);
+		}
+		let mut namespaces = Vec::with_capacity(lanes.len());
+		for lane in lanes {
+			let namespace = self.resolve_namespace(lane)?;
+			if !namespaces.contains(&namespace) {
+				namespaces.push(namespace);
+			}
+		}
+		Ok(namespaces)
+	}
+
+	/// Resolves the lane key of a request to its namespace, the default namespace if empty.
+	pub fn resolve_namespace(&self, lane: &str) -> Result<Namespace, anyhow::Error> {
+		if lane.is_empty() {
+			return Ok(self.celestia_namespace);
+		}
+		self.namespaces
+			.get(lane)
+			.copied()
+			.ok_or(anyhow::anyhow!("Unknown namespace lane: {}", lane))
+	}
+
+	/// Gets the lane key of a namespace, empty for a namespace outside the lanes.
+	pub fn lane_of(&self, namespace: &Namespace) -> String {
+		self.namespaces
+			.iter()
+			.find(|(_, lane_namespace)| *lane_namespace == namespace)
+			.map(|(lane, _)| lane.clone())
+			.unwrap_or_default()
+	}
+
+	/// Creates a new blob instance with the provided data, encoded with the configured codec and
+	/// sealed with the signing key if any.
+	pub fn create_new_celestia_blob(
+		&self,
+		namespace: Namespace,
+		data: Vec<u8>,
+	) -> Result<CelestiaBlob, anyhow::Error> {
+		let data =
+			encode_blob_data(namespace, &data, self.config.blob_codec(), self.signer.as_deref())?;
+		CelestiaBlob::new(namespace, data)
 			.map_err(|e| anyhow::anyhow!("Failed to create a blob: {}", e))
 	}
 
-	/// Submits a CelestiaNlob to the Celestia node.
+	/// Submits a CelestiaNlob to the DA backend.
 	pub async fn submit_celestia_blob(&self, blob: CelestiaBlob) -> Result<u64, anyhow::Error> {
-		let height = self
-			.default_client
-			.blob_submit(&[blob], GasPrice::default())
-			.await
-			.map_err(|e| anyhow::anyhow!("Failed submitting the blob: {}", e))?;
-
-		Ok(height)
+		Ok(self.submit_celestia_blobs(&[blob]).await?.height)
 	}
 
-	/// Submits Celestia blobs to the Celestia node.
+	/// Submits Celestia blobs to the DA backend.
 	pub async fn submit_celestia_blobs(
 		&self,
 		blobs: &[CelestiaBlob],
-	) -> Result<u64, anyhow::Error> {
-		let height = self
-			.default_client
-			.blob_submit(blobs, GasPrice::default())
-			.await
-			.map_err(|e| anyhow::anyhow!("Failed submitting the blob: {}", e))?;
+	) -> Result<Submission, anyhow::Error> {
+		let start = Instant::now();
+		match self.backend.submit_blobs(blobs).await {
+			Ok(submission) => {
+				self.metrics
+					.blobs_submitted(blobs.iter().map(|blob| blob.data.len()), start.elapsed());
+				Ok(submission)
+			}
+			Err(e) => {
+				self.metrics.submission_failed();
+				Err(e)
+			}
+		}
+	}
 
-		Ok(height)
+	/// Serves the metrics on the configured address, if any.
+	pub async fn run_metrics_server(&self) -> Result<(), anyhow::Error> {
+		match self.config.metrics_listen_address() {
+			Some(address) => metrics::serve(self.metrics.clone(), address.parse()?).await,
+			None => Ok(()),
+		}
 	}
 
-	/// Submits a blob to the Celestia node.
-	pub async fn submit_blob(&self, data: Vec<u8>) -> Result<Blob, anyhow::Error> {
-		let celestia_blob = self.create_new_celestia_blob(data)?;
+	/// Submits a blob to the Celestia node, in the namespace of the lane.
+	pub async fn submit_blob(&self, lane: &str, data: Vec<u8>) -> Result<Blob, anyhow::Error> {
+		let celestia_blob = self.create_new_celestia_blob(self.resolve_namespace(lane)?, data)?;
 		let height = self.submit_celestia_blob(celestia_blob.clone()).await?;
-		Ok(Self::celestia_blob_to_blob(celestia_blob, height)?)
+		Ok(self.celestia_blob_to_blob(celestia_blob, height)?)
+	}
+
+	/// Packs the transactions into blobs up to the max blob size and submits them together, in
+	/// the namespace of the lane.
+	///
+	/// Returns the inclusion handle of each transaction, in order.
+	pub async fn submit_transaction_batch(
+		&self,
+		lane: &str,
+		transactions: Vec<Vec<u8>>,
+	) -> Result<Vec<InclusionHandle>, anyhow::Error> {
+		let namespace = self.resolve_namespace(lane)?;
+		if transactions.is_empty() {
+			return Ok(Vec::new());
+		}
+
+		let max_blob_size = usize::try_from(self.config.max_blob_size())?;
+		let (packed_blobs, blob_data): (Vec<_>, Vec<_>) = pack_blob_data(
+			namespace,
+			&transactions,
+			max_blob_size,
+			self.config.blob_codec(),
+			self.signer.as_deref(),
+		)?
+		.into_iter()
+		.unzip();
+		let celestia_blobs = blob_data
+			.into_iter()
+			.map(|data| {
+				CelestiaBlob::new(namespace, data)
+					.map_err(|e| anyhow::anyhow!("Failed to create a blob: {}", e))
+			})
+			.collect::<Result<Vec<_>, anyhow::Error>>()?;
+		debug!(
+			transaction_count = transactions.len(),
+			blob_count = celestia_blobs.len(),
+			"submitting batch"
+		);
+
+		let Submission { height, gas_price } = self.submit_celestia_blobs(&celestia_blobs).await?;
+
+		let mut handles = (0..transactions.len() as u32)
+			.map(|index| InclusionHandle {
+				index,
+				height,
+				blob_ids: Vec::new(),
+				gas_price: gas_price.unwrap_or_default(),
+			})
+			.collect::<Vec<_>>();
+		for (packed_blob, celestia_blob) in packed_blobs.iter().zip(&celestia_blobs) {
+			let blob_id = serde_json::to_string(&celestia_blob.commitment)
+				.map_err(|e| anyhow::anyhow!("Failed to serialize commitment: {}", e))?;
+			for index in &packed_blob.items {
+				handles[*index as usize].blob_ids.push(blob_id.clone());
+			}
+		}
+
+		Ok(handles)
 	}
 
-	/// Gets the blobs at a given height.
+	/// Gets the blobs of the namespaces at a given height.
+	///
+	/// A failure of the backend is returned rather than read as no blobs, so that the streams
+	/// stop at the height instead of skipping its blobs.
 	pub async fn get_celestia_blobs_at_height(
 		&self,
 		height: u64,
+		namespaces: &[Namespace],
 	) -> Result<Vec<CelestiaBlob>, anyhow::Error> {
-		let blobs = self.default_client.blob_get_all(height, &[self.celestia_namespace]).await;
-
-		if let Err(e) = &blobs {
-			debug!("Error getting blobs: {:?}", e);
+		let mut blobs = self
+			.backend
+			.retrieve_blobs(height, namespaces)
+			.await
+			.with_context(|| format!("Failed to get the blobs at height {}", height))?;
+		if !self.authorized_sequencer_keys.is_empty() {
+			blobs.retain(|blob| self.authenticate_celestia_blob(blob, height));
 		}
 
-		let blobs = blobs.unwrap_or_default();
+		if self.config.read_verification() == ReadVerification::Trusted {
+			self.metrics.blobs_retrieved(blobs.len());
+			return Ok(blobs);
+		}
 
 		let mut verified_blobs = Vec::new();
 		for blob in blobs {
-			debug!("Verifying blob");
-
-			let blob_data = blob.data.clone();
-
-			// todo: improve error boundary here to detect crashes
-			let verified = self
-				.verifier
-				.verify(*self.verification_mode.read().await, &blob_data, height)
-				.await;
-
-			if let Err(e) = &verified {
-				debug!("Error verifying blob: {:?}", e);
+			if self.verify_celestia_blob(&blob, height).await {
+				verified_blobs.push(blob);
 			}
+		}
+		self.metrics.blobs_retrieved(verified_blobs.len());
 
-			// FIXME: check the implications of treating errors as verification success.
-			// @l-monninger: under the assumption we are running a light node in the same
-			// trusted setup and have not experience a highly intrusive(?), the vulnerability here
-			// is fairly low. The light node should take care of verification on its own.
-			let verified = verified.unwrap_or(true);
+		Ok(verified_blobs)
+	}
 
-			if verified {
-				verified_blobs.push(blob);
+	/// Checks that a blob is sealed by one of the authorized sequencers, so that garbage written
+	/// by others in the namespace is never read.
+	fn authenticate_celestia_blob(&self, blob: &CelestiaBlob, height: u64) -> bool {
+		match envelope::open(&blob.data, blob.namespace.as_bytes(), &self.authorized_sequencer_keys)
+		{
+			Ok(_) => true,
+			Err(e) => {
+				warn!(target: "movement_timing", height, error = %e, "blob_authentication_failed");
+				false
 			}
 		}
+	}
 
-		Ok(verified_blobs)
+	/// Checks the inclusion proof of a blob against the sampled header at its height, with the
+	/// verifier of the lane of the blob.
+	///
+	/// A blob failing the check or whose proof cannot be checked does not verify. The time taken
+	/// by the check is reported under the `movement_timing` target.
+	async fn verify_celestia_blob(&self, blob: &CelestiaBlob, height: u64) -> bool {
+		debug!("Verifying blob");
+		let Some(verifier) = self.verifiers.get(&self.lane_of(&blob.namespace)) else {
+			warn!(height, "No verifier for the namespace of the blob");
+			return false;
+		};
+		let start = Instant::now();
+		let verified =
+			verifier.verify(*self.verification_mode.read().await, &blob.data, height).await;
+		let elapsed = start.elapsed();
+		self.metrics.observe_proof_verification(elapsed);
+		let elapsed_us = elapsed.as_micros() as u64;
+
+		match verified {
+			Ok(verified) => {
+				info!(target: "movement_timing", height, verified, elapsed_us, "blob_proof_verification");
+				verified
+			}
+			Err(e) => {
+				warn!(target: "movement_timing", height, elapsed_us, error = %e, "blob_proof_verification_failed");
+				false
+			}
+		}
 	}
 
-	#[tracing::instrument(target = "movement_timing", level = "debug")]
-	async fn get_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error> {
-		let celestia_blobs = self.get_celestia_blobs_at_height(height).await?;
+	#[tracing::instrument(target = "movement_timing", level = "debug", skip(namespaces))]
+	async fn get_blobs_at_height(
+		&self,
+		height: u64,
+		namespaces: &[Namespace],
+	) -> Result<Vec<Blob>, anyhow::Error> {
+		let celestia_blobs = self.get_celestia_blobs_at_height(height, namespaces).await?;
 		let mut blobs = Vec::new();
 		for celestia_blob in celestia_blobs {
-			let blob = Self::celestia_blob_to_blob(celestia_blob, height)?;
+			let blob = self.celestia_blob_to_blob(celestia_blob, height)?;
 			debug!(blob_id = %blob.blob_id, "got blob");
 			blobs.push(blob);
 		}
@@ -163,6 +375,7 @@ impl LightNodeV1 {
 		&self,
 		start_height: u64,
 		end_height: Option<u64>,
+		namespaces: Vec<Namespace>,
 	) -> Result<
 		std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>,
 		anyhow::Error,
@@ -177,7 +390,7 @@ impl LightNodeV1 {
 					break;
 				}
 
-				let blobs = me.get_blobs_at_height(height).await?;
+				let blobs = me.get_blobs_at_height(height, &namespaces).await?;
 				for blob in blobs {
 					yield blob;
 				}
@@ -189,48 +402,50 @@ impl LightNodeV1 {
 			as std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>)
 	}
 
-	/// Streams the latest blobs that can subscribed to.
-	async fn stream_blobs_from_height_on(
+	/// Streams the blobs of the namespaces of every height from `start_height` on, in order, first
+	/// catching up to the network head and then following the new headers. The blobs of several
+	/// namespaces are merged in the order of the heights.
+	///
+	/// The lag behind the network head is recorded by the streams including the default namespace.
+	pub async fn stream_blobs_from(
 		&self,
-		start_height: Option<u64>,
+		start_height: u64,
+		namespaces: Vec<Namespace>,
 	) -> Result<
 		std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>,
 		anyhow::Error,
 	> {
-		let start_height = start_height.unwrap_or_else(|| u64::MAX);
 		let me = Arc::new(self.clone());
-		let mut subscription = me.default_client.header_subscribe().await?;
+		// subscribe before reading the head, so no header is missed in between
+		let mut headers = me.backend.stream_headers().await?;
+		let head = me.backend.network_head().await?;
+		let default_namespace = namespaces.contains(&me.celestia_namespace);
 
 		let stream = async_stream::try_stream! {
-			let mut first_flag = true;
-			while let Some(header_res) = subscription.next().await {
-
-				let header = header_res?;
-				let height = header.height().into();
-
-				debug!("Stream got header: {:?}", header.height());
-
-				// back fetch the blobs
-				if first_flag && (height > start_height) {
-
-					let mut blob_stream = me.stream_blobs_in_range(start_height, Some(height)).await?;
-
-					while let Some(blob) = blob_stream.next().await {
+			let mut height = start_height;
+			let mut target_height = head;
+			loop {
+				while height <= target_height {
+					let blobs = me.get_blobs_at_height(height, &namespaces).await?;
+					for blob in blobs {
 
 						debug!("Stream got blob: {:?}", blob);
 
-						yield blob?;
+						yield blob;
 					}
-
+					if default_namespace {
+						me.metrics.height_lag(target_height - height);
+					}
+					height += 1;
 				}
-				first_flag = false;
-
-				let blobs = me.get_blobs_at_height(height).await?;
-				for blob in blobs {
 
-					debug!("Stream got blob: {:?}", blob);
-
-					yield blob;
+				match headers.next().await {
+					Some(header_height) => {
+						let header_height = header_height?;
+						debug!("Stream got header: {:?}", header_height);
+						target_height = target_height.max(header_height);
+					}
+					None => break,
 				}
 			}
 		};
@@ -239,15 +454,44 @@ impl LightNodeV1 {
 			as std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>)
 	}
 
-	pub fn celestia_blob_to_blob(blob: CelestiaBlob, height: u64) -> Result<Blob, anyhow::Error> {
+	/// Streams the latest blobs that can subscribed to.
+	///
+	/// Without a start height, the stream starts after the network head.
+	async fn stream_blobs_from_height_on(
+		&self,
+		start_height: Option<u64>,
+		namespaces: Vec<Namespace>,
+	) -> Result<
+		std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>,
+		anyhow::Error,
+	> {
+		let start_height = match start_height {
+			Some(start_height) => start_height,
+			None => self.backend.network_head().await? + 1,
+		};
+		self.stream_blobs_from(start_height, namespaces).await
+	}
+
+	/// Buffers the blob stream for its reader, following the configured lag policy.
+	fn flow_controlled(&self, stream: FlowStream<Blob>) -> FlowStream<Blob> {
+		let (buffer_size, lag_policy) = self.config.stream_flow_parameters();
+		flow::flow_controlled(stream, buffer_size as usize, lag_policy)
+	}
+
+	pub fn celestia_blob_to_blob(
+		&self,
+		blob: CelestiaBlob,
+		height: u64,
+	) -> Result<Blob, anyhow::Error> {
 		let timestamp = chrono::Utc::now().timestamp_micros() as u64;
 
 		Ok(Blob {
-			data: blob.data,
+			data: codec::decode(envelope::payload(&blob.data)?)?,
 			blob_id: serde_json::to_string(&blob.commitment)
 				.map_err(|e| anyhow::anyhow!("Failed to serialize commitment: {}", e))?,
 			height,
 			timestamp,
+			namespace: self.lane_of(&blob.namespace),
 		})
 	}
 
@@ -283,11 +527,16 @@ impl LightNodeService for LightNodeV1 {
 		request: tonic::Request<StreamReadFromHeightRequest>,
 	) -> std::result::Result<tonic::Response<Self::StreamReadFromHeightStream>, tonic::Status> {
 		let me = Arc::new(self.clone());
-		let height = request.into_inner().height;
+		let request = request.into_inner();
+		let height = request.height;
+		let namespaces = me
+			.resolve_namespaces(&request.namespaces)
+			.map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
 
 		let output = async_stream::try_stream! {
 
-			let mut blob_stream = me.stream_blobs_from_height_on(Some(height)).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
+			let blob_stream = me.stream_blobs_from_height_on(Some(height), namespaces).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
+			let mut blob_stream = me.flow_controlled(blob_stream);
 
 			while let Some(blob) = blob_stream.next().await {
 				let blob = blob.map_err(|e| tonic::Status::internal(e.to_string()))?;
@@ -310,13 +559,17 @@ impl LightNodeService for LightNodeV1 {
 	/// Stream the latest blobs.
 	async fn stream_read_latest(
 		&self,
-		_request: tonic::Request<StreamReadLatestRequest>,
+		request: tonic::Request<StreamReadLatestRequest>,
 	) -> std::result::Result<tonic::Response<Self::StreamReadLatestStream>, tonic::Status> {
 		let me = Arc::new(self.clone());
+		let namespaces = me
+			.resolve_namespaces(&request.into_inner().namespaces)
+			.map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
 
 		let output = async_stream::try_stream! {
 
-			let mut blob_stream = me.stream_blobs_from_height_on(None).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
+			let blob_stream = me.stream_blobs_from_height_on(None, namespaces).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
+			let mut blob_stream = me.flow_controlled(blob_stream);
 			while let Some(blob) = blob_stream.next().await {
 				let blob = blob.map_err(|e| tonic::Status::internal(e.to_string()))?;
 				let response = StreamReadLatestResponse {
@@ -345,9 +598,9 @@ impl LightNodeService for LightNodeV1 {
 
 			while let Some(request) = stream.next().await {
 				let request = request?;
-				let blob_data = request.blob.ok_or(tonic::Status::invalid_argument("No blob in request"))?.data;
+				let blob_write = request.blob.ok_or(tonic::Status::invalid_argument("No blob in request"))?;
 
-				let blob = me.submit_blob(blob_data).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
+				let blob = me.submit_blob(&blob_write.namespace, blob_write.data).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
 
 				let write_response = StreamWriteBlobResponse {
 					blob : Some(Self::blob_to_blob_read_response(blob).map_err(|e| tonic::Status::internal(e.to_string()))?)
@@ -365,9 +618,12 @@ impl LightNodeService for LightNodeV1 {
 		&self,
 		request: tonic::Request<ReadAtHeightRequest>,
 	) -> std::result::Result<tonic::Response<ReadAtHeightResponse>, tonic::Status> {
-		let height = request.into_inner().height;
+		let request = request.into_inner();
+		let namespaces = self
+			.resolve_namespaces(&request.namespaces)
+			.map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
 		let blobs = self
-			.get_blobs_at_height(height)
+			.get_blobs_at_height(request.height, &namespaces)
 			.await
 			.map_err(|e| tonic::Status::internal(e.to_string()))?;
 
@@ -393,11 +649,14 @@ impl LightNodeService for LightNodeV1 {
 		&self,
 		request: tonic::Request<BatchReadRequest>,
 	) -> std::result::Result<tonic::Response<BatchReadResponse>, tonic::Status> {
-		let heights = request.into_inner().heights;
-		let mut responses = Vec::with_capacity(heights.len());
-		for height in heights {
+		let request = request.into_inner();
+		let namespaces = self
+			.resolve_namespaces(&request.namespaces)
+			.map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+		let mut responses = Vec::with_capacity(request.heights.len());
+		for height in request.heights {
 			let blobs = self
-				.get_blobs_at_height(height)
+				.get_blobs_at_height(height, &namespaces)
 				.await
 				.map_err(|e| tonic::Status::internal(e.to_string()))?;
 
@@ -428,7 +687,7 @@ impl LightNodeService for LightNodeV1 {
 		let mut responses = Vec::with_capacity(blobs.len());
 		for data in blobs {
 			let blob = self
-				.submit_blob(data.data)
+				.submit_blob(&data.namespace, data.data)
 				.await
 				.map_err(|e| tonic::Status::internal(e.to_string()))?;
 			responses.push(blob);
@@ -444,6 +703,22 @@ impl LightNodeService for LightNodeV1 {
 
 		Ok(tonic::Response::new(BatchWriteResponse { blobs: blob_responses }))
 	}
+
+	/// Submit many transactions, packed into blobs up to the max blob size.
+	async fn submit_batch(
+		&self,
+		request: tonic::Request<SubmitBatchRequest>,
+	) -> std::result::Result<tonic::Response<SubmitBatchResponse>, tonic::Status> {
+		let request = request.into_inner();
+		let transactions =
+			request.transactions.into_iter().map(|transaction| transaction.data).collect();
+		let handles = self
+			.submit_transaction_batch(&request.namespace, transactions)
+			.await
+			.map_err(|e| tonic::Status::internal(e.to_string()))?;
+
+		Ok(tonic::Response::new(SubmitBatchResponse { handles }))
+	}
 	/// Update and manage verification parameters.
 	async fn update_verification_parameters(
 		&self,
@@ -457,4 +732,134 @@ impl LightNodeService for LightNodeV1 {
 			mode: verification_mode.into(),
 		}))
 	}
+
+	/// Checkpoint the last DA height processed by the client.
+	async fn acknowledge_height(
+		&self,
+		request: tonic::Request<AcknowledgeHeightRequest>,
+	) -> std::result::Result<tonic::Response<AcknowledgeHeightResponse>, tonic::Status> {
+		let height = self
+			.checkpoint
+			.advance(request.into_inner().height)
+			.await
+			.map_err(|e| tonic::Status::internal(e.to_string()))?;
+
+		Ok(tonic::Response::new(AcknowledgeHeightResponse { height }))
+	}
+
+	/// Get the last DA height acknowledged by the client.
+	async fn get_checkpoint(
+		&self,
+		_request: tonic::Request<GetCheckpointRequest>,
+	) -> std::result::Result<tonic::Response<GetCheckpointResponse>, tonic::Status> {
+		Ok(tonic::Response::new(GetCheckpointResponse { height: self.checkpoint.height().await }))
+	}
+}
+
+/// Encodes the data of a blob of the namespace with the codec, then seals it with the signer if
+/// any.
+pub fn encode_blob_data(
+	namespace: Namespace,
+	data: &[u8],
+	blob_codec: BlobCodec,
+	signer: Option<&BlobSigner>,
+) -> Result<Vec<u8>, anyhow::Error> {
+	let encoded = codec::encode(data, blob_codec)?;
+	Ok(match signer {
+		Some(signer) => signer.seal(namespace.as_bytes(), &encoded),
+		None => encoded,
+	})
+}
+
+/// Packs the items of a batch into the data of blobs of the namespace, each at most
+/// `max_blob_size` bytes once encoded with the codec and sealed with the signer if any.
+pub fn pack_blob_data(
+	namespace: Namespace,
+	items: &[Vec<u8>],
+	max_blob_size: usize,
+	blob_codec: BlobCodec,
+	signer: Option<&BlobSigner>,
+) -> Result<Vec<(framing::PackedBlob, Vec<u8>)>, anyhow::Error> {
+	let envelope_len = if signer.is_some() { envelope::ENVELOPE_HEADER_LEN } else { 0 };
+	let max_data_len = codec::max_data_len(max_blob_size.saturating_sub(envelope_len), blob_codec);
+	framing::pack(items, max_data_len)?
+		.into_iter()
+		.map(|packed_blob| {
+			let data = encode_blob_data(namespace, &packed_blob.data, blob_codec, signer)?;
+			if data.len() > max_blob_size {
+				anyhow::bail!(
+					"Encoded blob of {} bytes is over the max blob size {}",
+					data.len(),
+					max_blob_size
+				);
+			}
+			Ok((packed_blob, data))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MAX_BLOB_SIZE: usize = 1024;
+
+	/// Data zstd cannot compress, from a xorshift generator.
+	fn incompressible(len: usize) -> Vec<u8> {
+		let mut state = 0x2545_f491_4f6c_dd1d_u64;
+		(0..len)
+			.map(|_| {
+				state ^= state << 13;
+				state ^= state >> 7;
+				state ^= state << 17;
+				state as u8
+			})
+			.collect()
+	}
+
+	/// Opens, decodes and unpacks the blobs, then reassembles the items of the batch.
+	fn unpack_blob_data(blob_data: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+		let mut frames = Vec::new();
+		for data in blob_data {
+			let decoded = codec::decode(envelope::payload(data)?)?;
+			frames.extend(framing::unpack(&decoded)?.ok_or(anyhow::anyhow!("Not a batch blob"))?);
+		}
+		framing::reassemble(frames)
+	}
+
+	#[test]
+	fn test_sealed_uncompressed_batch_fits_the_max_blob_size() -> Result<(), anyhow::Error> {
+		let namespace = Namespace::new_v0(b"movement")?;
+		let signer = BlobSigner::new(&[7; 32]);
+		let items = vec![vec![1; 3 * MAX_BLOB_SIZE], vec![2; 10], vec![3; MAX_BLOB_SIZE]];
+
+		let blob_data: Vec<_> =
+			pack_blob_data(namespace, &items, MAX_BLOB_SIZE, BlobCodec::None, Some(&signer))?
+				.into_iter()
+				.map(|(_, data)| data)
+				.collect();
+
+		// the parts of the split items fill the blobs up to the limit, and not beyond
+		assert!(blob_data.iter().all(|data| data.len() <= MAX_BLOB_SIZE));
+		assert!(blob_data.iter().any(|data| data.len() == MAX_BLOB_SIZE));
+		assert_eq!(unpack_blob_data(&blob_data)?, items);
+		Ok(())
+	}
+
+	#[test]
+	fn test_incompressible_batch_fits_the_max_blob_size() -> Result<(), anyhow::Error> {
+		let namespace = Namespace::new_v0(b"movement")?;
+		let signer = BlobSigner::new(&[7; 32]);
+		let items = vec![incompressible(3 * MAX_BLOB_SIZE), incompressible(100)];
+
+		let blob_data: Vec<_> =
+			pack_blob_data(namespace, &items, MAX_BLOB_SIZE, BlobCodec::Zstd, Some(&signer))?
+				.into_iter()
+				.map(|(_, data)| data)
+				.collect();
+
+		assert!(blob_data.iter().all(|data| data.len() <= MAX_BLOB_SIZE));
+		assert_eq!(unpack_blob_data(&blob_data)?, items);
+		Ok(())
+	}
 }
@@ -5,9 +5,8 @@ use std::{
 use tokio_stream::Stream;
 use tracing::{debug, info};
 
-use celestia_rpc::HeaderClient;
 use m1_da_light_node_grpc::light_node_service_server::LightNodeService;
-use m1_da_light_node_util::config::Config;
+use m1_da_light_node_util::config::{Config, DEFAULT_NAMESPACE_LANE};
 use std::{fmt::Debug, path::PathBuf};
 // FIXME: glob imports are bad style
 use m1_da_light_node_grpc::*;
@@ -30,7 +29,7 @@ const LOGGING_UID: AtomicU64 = AtomicU64::new(0);
 #[derive(Clone)]
 pub struct LightNodeV1 {
 	pub pass_through: LightNodeV1PassThrough,
-	pub memseq: Arc<memseq::Memseq<memseq::RocksdbMempool>>,
+	pub memseq: Arc<memseq::Memseq<memseq::PriorityMempool>>,
 }
 
 impl Debug for LightNodeV1 {
@@ -49,13 +48,22 @@ impl LightNodeV1Operations for LightNodeV1 {
 		let memseq_path = pass_through.config.try_memseq_path()?;
 		info!("Memseq path: {:?}", memseq_path);
 		let (max_block_size, build_time) = pass_through.config.try_block_building_parameters()?;
-
-		let memseq = Arc::new(memseq::Memseq::try_move_rocks(
-			PathBuf::from(memseq_path),
-			max_block_size,
-			build_time,
-		)?);
-		info!("Initialized Memseq with Move Rocks for LightNodeV1 in sequencer mode.");
+		let (max_transactions, max_bytes, max_transactions_per_account) =
+			pass_through.config.try_mempool_limits()?;
+		let limits =
+			memseq::MempoolLimits { max_transactions, max_bytes, max_transactions_per_account };
+		info!("Mempool limits: {:?}", limits);
+
+		let memseq = Arc::new(
+			memseq::Memseq::try_priority(
+				PathBuf::from(memseq_path),
+				limits,
+				max_block_size,
+				build_time,
+			)
+			.await?,
+		);
+		info!("Initialized Memseq with a priority mempool for LightNodeV1 in sequencer mode.");
 
 		Ok(Self { pass_through, memseq })
 	}
@@ -65,7 +73,7 @@ impl LightNodeV1Operations for LightNodeV1 {
 	}
 
 	async fn run_background_tasks(&self) -> Result<(), anyhow::Error> {
-		self.run_block_proposer().await?;
+		futures::try_join!(self.pass_through.run_background_tasks(), self.run_block_proposer())?;
 
 		Ok(())
 	}
@@ -80,6 +88,13 @@ impl LightNodeV1 {
 		let uid = LOGGING_UID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
 		debug!(target: "movement_timing", uid = %uid, "waiting_for_next_block",);
 		let block = memseq.wait_for_next_block().await?;
+		let mempool = memseq.mempool().metrics();
+		self.pass_through.metrics.mempool(
+			mempool.transactions,
+			mempool.bytes,
+			mempool.rejected,
+			mempool.evicted,
+		);
 		match block {
 			Some(block) => {
 				info!(target: "movement_timing", block_id = %block.id(), uid = %uid, transaction_count = block.transactions.len(), "received_block");
@@ -120,10 +135,14 @@ impl LightNodeV1 {
 		// wrap the blocks in a struct that can be split and compressed
 		// spawn blocking because the compression is blocking and could be slow
 		let namespace = self.pass_through.celestia_namespace.clone();
+		let blob_codec = self.pass_through.config.blob_codec();
+		let signer = self.pass_through.signer.clone();
 		let blocks = tokio::task::spawn_blocking(move || {
 			blocks
 				.into_iter()
-				.map(|block| block::WrappedBlock::try_new(block, namespace))
+				.map(|block| {
+					block::WrappedBlock::try_new(block, namespace, blob_codec, signer.as_deref())
+				})
 				.collect::<Result<Vec<_>, anyhow::Error>>()
 		})
 		.await??;
@@ -306,6 +325,7 @@ impl LightNodeV1 {
 				blob_id: "".to_string(),
 				height,
 				timestamp: 0,
+				namespace: DEFAULT_NAMESPACE_LANE.to_string(),
 			})),
 		})
 	}
@@ -373,14 +393,12 @@ impl LightNodeService for LightNodeV1 {
 	) -> std::result::Result<tonic::Response<BatchWriteResponse>, tonic::Status> {
 		let blobs_for_intent = request.into_inner().blobs;
 		let blobs_for_submission = blobs_for_intent.clone();
-		let height: u64 = self
+		let height = self
 			.pass_through
-			.default_client
-			.header_network_head()
+			.backend
+			.network_head()
 			.await
-			.map_err(|e| tonic::Status::internal(e.to_string()))?
-			.height()
-			.into();
+			.map_err(|e| tonic::Status::internal(e.to_string()))?;
 
 		let intents: Vec<BlobResponse> = blobs_for_intent
 			.into_iter()
@@ -398,15 +416,26 @@ impl LightNodeService for LightNodeV1 {
 			transactions.push(transaction);
 		}
 
-		// publish the transactions
+		// publish the transactions, the ones rejected by the mempool fail the request
 		let memseq = self.memseq.clone();
-		memseq
-			.publish_many(transactions)
-			.await
-			.map_err(|e| tonic::Status::internal(e.to_string()))?;
+		memseq.publish_many(transactions).await.map_err(|e| {
+			if e.downcast_ref::<memseq::RejectedTransactions>().is_some() {
+				tonic::Status::resource_exhausted(e.to_string())
+			} else {
+				tonic::Status::internal(e.to_string())
+			}
+		})?;
 
 		Ok(tonic::Response::new(BatchWriteResponse { blobs: intents }))
 	}
+
+	/// Submit many transactions, packed into blobs up to the max blob size.
+	async fn submit_batch(
+		&self,
+		request: tonic::Request<SubmitBatchRequest>,
+	) -> std::result::Result<tonic::Response<SubmitBatchResponse>, tonic::Status> {
+		self.pass_through.submit_batch(request).await
+	}
 	/// Update and manage verification parameters.
 	async fn update_verification_parameters(
 		&self,
@@ -414,11 +443,29 @@ impl LightNodeService for LightNodeV1 {
 	) -> std::result::Result<tonic::Response<UpdateVerificationParametersResponse>, tonic::Status> {
 		self.pass_through.update_verification_parameters(request).await
 	}
+
+	/// Checkpoint the last DA height processed by the client.
+	async fn acknowledge_height(
+		&self,
+		request: tonic::Request<AcknowledgeHeightRequest>,
+	) -> std::result::Result<tonic::Response<AcknowledgeHeightResponse>, tonic::Status> {
+		self.pass_through.acknowledge_height(request).await
+	}
+
+	/// Get the last DA height acknowledged by the client.
+	async fn get_checkpoint(
+		&self,
+		request: tonic::Request<GetCheckpointRequest>,
+	) -> std::result::Result<tonic::Response<GetCheckpointResponse>, tonic::Status> {
+		self.pass_through.get_checkpoint(request).await
+	}
 }
 
 mod block {
 
+	use crate::v1::{envelope::BlobSigner, passthrough::encode_blob_data};
 	use celestia_types::{nmt::Namespace, Blob};
+	use m1_da_light_node_util::config::local::m1_da_light_node::BlobCodec;
 	use movement_algs::grouping_heuristic::{binpacking::BinpackingWeighted, splitting::Splitable};
 	use movement_types::Block;
 
@@ -429,15 +476,21 @@ mod block {
 	}
 
 	impl WrappedBlock {
-		pub fn try_new(block: Block, namespace: Namespace) -> Result<Self, anyhow::Error> {
+		pub fn try_new(
+			block: Block,
+			namespace: Namespace,
+			blob_codec: BlobCodec,
+			signer: Option<&BlobSigner>,
+		) -> Result<Self, anyhow::Error> {
 			// first serialize the block
 			let block_bytes = bcs::to_bytes(&block)?;
 
-			// then compress the block bytes
-			let compressed_block_bytes = zstd::encode_all(block_bytes.as_slice(), 0)?;
+			// then encode the block bytes, compressing them unless disabled, and seal them
+			let encoded_block_bytes =
+				encode_blob_data(namespace, &block_bytes, blob_codec, signer)?;
 
-			// then create a blob from the compressed block bytes
-			let blob = Blob::new(namespace, compressed_block_bytes)?;
+			// then create a blob from the encoded block bytes
+			let blob = Blob::new(namespace, encoded_block_bytes)?;
 
 			Ok(Self { block, blob })
 		}
@@ -4,16 +4,12 @@ use m1_da_light_node_util::M1DaLightNodeConfig;
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
-	use tracing_subscriber::EnvFilter;
-
-	tracing_subscriber::fmt()
-		.with_env_filter(
-			EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-		)
-		.init();
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let _guard = movement_tracing::init_tracing_subscriber(
+		movement_tracing::Config::with_telemetry_from_config(&dot_movement, "celestia-appd")?,
+	);
 
 	// get the config file
-	let dot_movement = dot_movement::DotMovement::try_from_env()?;
 	let mut config_file = dot_movement.try_get_or_create_config_file().await?;
 
 	// get a matching godfig object
@@ -1,4 +1,19 @@
 use anyhow::Context;
+use reqwest::Client;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::{fs, time::sleep};
+use tracing::info;
+
+/// The name of the validator key in the test keyring of the celestia app.
+const VALIDATOR_KEY: &str = "validator";
+
+/// The coins of the validator genesis account.
+const GENESIS_COINS: &str = "1000000000000000utia";
+
+/// The stake of the validator genesis transaction.
+const GENESIS_STAKE: &str = "5000000000utia";
 
 #[derive(Debug, Clone)]
 pub struct Local;
@@ -8,6 +23,158 @@ impl Local {
 		Local
 	}
 
+	/// The file marking the celestia app as synced and ready to serve, for health checks.
+	pub fn ready_marker_path(dot_movement: &dot_movement::DotMovement) -> PathBuf {
+		dot_movement.get_path().join("celestia").join("celestia-appd.ready")
+	}
+
+	/// Initializes the data directory of the celestia app, unless already done by the setup or a
+	/// previous run: the chain, the validator key and the genesis with the validator.
+	async fn bootstrap(
+		&self,
+		config: &m1_da_light_node_util::config::local::Config,
+		app_path: &str,
+	) -> Result<(), anyhow::Error> {
+		let chain_id = config.appd.celestia_chain_id.as_str();
+		let app_dir = Path::new(app_path);
+
+		if !app_dir.join("config").join("genesis.json").exists() {
+			info!("Initializing the Celestia App for chain id {} at {}.", chain_id, app_path);
+			commander::run_command(
+				"celestia-appd",
+				&["init", chain_id, "--chain-id", chain_id, "--home", app_path],
+			)
+			.await?;
+		}
+
+		let validator_address = match self.validator_address(app_path).await {
+			Ok(address) => address,
+			Err(_) => {
+				self.provision_validator_key(config, app_path).await?;
+				self.validator_address(app_path).await?
+			}
+		};
+		info!("Celestia validator address: {}", validator_address);
+
+		if !self.has_gentxs(app_dir).await? {
+			info!("Adding the validator to the genesis.");
+			commander::run_command(
+				"celestia-appd",
+				&["add-genesis-account", &validator_address, GENESIS_COINS, "--home", app_path],
+			)
+			.await?;
+			commander::run_command(
+				"celestia-appd",
+				&[
+					"gentx",
+					VALIDATOR_KEY,
+					GENESIS_STAKE,
+					"--keyring-backend=test",
+					"--chain-id",
+					chain_id,
+					"--home",
+					app_path,
+				],
+			)
+			.await?;
+			commander::run_command("celestia-appd", &["collect-gentxs", "--home", app_path])
+				.await?;
+		}
+
+		info!("Celestia App bootstrapped with namespace {:?}.", config.appd.celestia_namespace);
+		Ok(())
+	}
+
+	/// Imports the validator key from the configured mnemonic, or generates a new one.
+	async fn provision_validator_key(
+		&self,
+		config: &m1_da_light_node_util::config::local::Config,
+		app_path: &str,
+	) -> Result<(), anyhow::Error> {
+		match &config.appd.celestia_validator_mnemonic_path {
+			Some(mnemonic_path) => {
+				info!("Importing the validator key from {}.", mnemonic_path);
+				commander::run_command(
+					"celestia-appd",
+					&[
+						"keys",
+						"add",
+						VALIDATOR_KEY,
+						"--recover",
+						"--source",
+						mnemonic_path,
+						"--keyring-backend=test",
+						"--home",
+						app_path,
+					],
+				)
+				.await?;
+			}
+			None => {
+				info!("Generating the validator key.");
+				commander::run_command(
+					"celestia-appd",
+					&["keys", "add", VALIDATOR_KEY, "--keyring-backend=test", "--home", app_path],
+				)
+				.await?;
+			}
+		}
+		Ok(())
+	}
+
+	async fn validator_address(&self, app_path: &str) -> Result<String, anyhow::Error> {
+		let address = commander::run_command(
+			"celestia-appd",
+			&["keys", "show", VALIDATOR_KEY, "-a", "--keyring-backend=test", "--home", app_path],
+		)
+		.await?;
+		Ok(address.trim().to_string())
+	}
+
+	async fn has_gentxs(&self, app_dir: &Path) -> Result<bool, anyhow::Error> {
+		let gentx_dir = app_dir.join("config").join("gentx");
+		if !gentx_dir.exists() {
+			return Ok(false);
+		}
+		Ok(fs::read_dir(gentx_dir).await?.next_entry().await?.is_some())
+	}
+
+	/// Waits for the celestia app to produce blocks and catch up, then marks it as ready.
+	async fn wait_for_ready(
+		&self,
+		rpc_address: &str,
+		ready_marker: &Path,
+	) -> Result<(), anyhow::Error> {
+		let client = Client::new();
+		let status_url = format!("http://{}/status", rpc_address);
+
+		loop {
+			sleep(Duration::from_secs(1)).await;
+			let status = match client.get(status_url.as_str()).send().await {
+				Ok(response) => serde_json::from_str::<Value>(&response.text().await?)?,
+				Err(e) => {
+					info!("Waiting for the Celestia App RPC: {}", e);
+					continue;
+				}
+			};
+
+			let sync_info = &status["result"]["sync_info"];
+			let catching_up = sync_info["catching_up"].as_bool().unwrap_or(true);
+			let height = sync_info["latest_block_height"]
+				.as_str()
+				.and_then(|height| height.parse::<u64>().ok())
+				.unwrap_or_default();
+			if catching_up || height == 0 {
+				info!("Waiting for the Celestia App to sync, at height {}.", height);
+				continue;
+			}
+
+			info!("Celestia App is synced at height {}.", height);
+			fs::write(ready_marker, height.to_string()).await?;
+			return Ok(());
+		}
+	}
+
 	pub async fn run(
 		&self,
 		dot_movement: dot_movement::DotMovement,
@@ -16,7 +183,7 @@ impl Local {
 		// celestia-appd start --grpc.enable --home $CELESTIA_APP_PATH --log_level $LOG_LEVEL
 
 		// get the app path
-		let app_path = config.appd.celestia_path.context("Celestia app path not set")?;
+		let app_path = config.appd.celestia_path.clone().context("Celestia app path not set")?;
 
 		// get the websocket address
 		let websocket_hostname = config.appd.celestia_websocket_connection_hostname.clone();
@@ -40,24 +207,35 @@ impl Local {
 			return Ok(());
 		}
 
-		commander::run_command(
-			"celestia-appd",
-			&[
-				"start",
-				"--address",
-				format!("tcp://{}", websocket_address).as_str(),
-				"--proxy_app",
-				format!("tcp://{}", websocket_address).as_str(),
-				"--grpc.enable",
-				"--home",
-				&app_path,
-				"--rpc.laddr",
-				format!("tcp://{}", rpc_address).as_str(),
-				"--log_level",
-				"error",
-			],
-		)
-		.await?;
+		// the app is only reported ready once synced by this run
+		let ready_marker = Self::ready_marker_path(&dot_movement);
+		if let Some(parent) = ready_marker.parent() {
+			fs::create_dir_all(parent).await?;
+		}
+		if ready_marker.exists() {
+			fs::remove_file(&ready_marker).await?;
+		}
+
+		self.bootstrap(&config, &app_path).await?;
+
+		let websocket_laddr = format!("tcp://{}", websocket_address);
+		let rpc_laddr = format!("tcp://{}", rpc_address);
+		let start_args: &[&str] = &[
+			"start",
+			"--address",
+			&websocket_laddr,
+			"--proxy_app",
+			&websocket_laddr,
+			"--grpc.enable",
+			"--home",
+			&app_path,
+			"--rpc.laddr",
+			&rpc_laddr,
+			"--log_level",
+			"error",
+		];
+		let start = commander::run_command("celestia-appd", start_args);
+		tokio::try_join!(start, self.wait_for_ready(&rpc_address, &ready_marker))?;
 
 		Ok(())
 	}
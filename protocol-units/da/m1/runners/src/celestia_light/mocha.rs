@@ -12,20 +12,22 @@ impl Mocha {
 		config: m1_da_light_node_util::config::local::Config,
 	) -> Result<(), anyhow::Error> {
 		// celestia light start --core.ip validator-1.celestia-mocha-11.com --p2p.network mocha
-		commander::run_command(
-			"celestia",
-			&[
-				"light",
-				"start",
-				"--core.ip",
-				"rpc-mocha.pops.one",
-				"--p2p.network",
-				"mocha",
-				"--log.level",
-				"FATAL",
-			],
-		)
-		.await?;
+		let mut args = vec![
+			"light",
+			"start",
+			"--core.ip",
+			"rpc-mocha.pops.one",
+			"--p2p.network",
+			"mocha",
+			"--log.level",
+			"FATAL",
+		];
+		// the fees of the blob submissions are paid by the granter, if any
+		if let Some(fee_granter_address) = &config.m1_da_light_node.celestia_gas.fee_granter_address
+		{
+			args.extend(["--granter.address", fee_granter_address.as_str()]);
+		}
+		commander::run_command("celestia", &args).await?;
 
 		Ok(())
 	}
@@ -99,7 +99,7 @@ impl Arabica {
 
 		// get the auth token
 		let auth_token = self.get_auth_token().await?;
-		config.appd.celestia_auth_token.replace(auth_token.clone());
+		config.appd.celestia_auth_token.replace(auth_token.clone().into());
 
 		// create and fund the account
 		self.create_and_fund_account(dot_movement.clone(), config.clone()).await?;
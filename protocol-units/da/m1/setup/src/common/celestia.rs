@@ -61,6 +61,18 @@ pub fn initialize_celestia_config(
 			.to_string(),
 	);
 
+	// update the light node checkpoint path with the chain id
+	config.m1_da_light_node.m1_da_light_node_checkpoint_path.replace(
+		dot_movement_path
+			.join("celestia")
+			.join(celestia_chain_id.clone())
+			.join(".m1-da-light-node")
+			.join("checkpoint.json")
+			.to_str()
+			.ok_or(anyhow::anyhow!("Failed to convert path to string."))?
+			.to_string(),
+	);
+
 	Ok(config)
 }
 
@@ -79,6 +91,13 @@ pub async fn make_dirs(dot_movement: DotMovement, config: Config) -> Result<Conf
 	info!("Creating Celestia Node Path: {}", node_path.as_str());
 	common::file::make_parent_dirs(node_path.as_str()).await?;
 
+	// make the light node checkpoint directory
+	if let Some(checkpoint_path) = config.m1_da_light_node.m1_da_light_node_checkpoint_path.clone()
+	{
+		info!("Creating M1 DA Light Node Checkpoint Path: {}", checkpoint_path.as_str());
+		common::file::make_parent_dirs(checkpoint_path.as_str()).await?;
+	}
+
 	// make the memseq database directory
 	let database_path = config.memseq.sequencer_database_path.clone().context(
         "Failed to get MemSeq database path from config. This is required for creating the MemSeq database directory.",
@@ -128,7 +128,7 @@ impl Local {
 		.await?
 		.trim()
 		.to_string();
-		config.appd.celestia_auth_token.replace(auth_token.clone());
+		config.appd.celestia_auth_token.replace(auth_token.clone().into());
 
 		info!("Celestia setup complete.");
 
@@ -98,7 +98,7 @@ impl Mocha {
 
 		// get the auth token
 		let auth_token = self.get_auth_token().await?;
-		config.appd.celestia_auth_token.replace(auth_token.clone());
+		config.appd.celestia_auth_token.replace(auth_token.clone().into());
 
 		Ok(config)
 	}
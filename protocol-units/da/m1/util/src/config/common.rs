@@ -1,3 +1,6 @@
+use crate::config::local::m1_da_light_node::{
+	BlobCodec, DaBackend, ReadVerification, StreamLagPolicy,
+};
 use celestia_types::nmt::Namespace;
 use godfig::env_default;
 
@@ -77,6 +80,91 @@ env_default!(
 	30730
 );
 
+// The default M1 DA Light Node DA backend
+env_default!(
+	default_m1_da_light_node_da_backend,
+	"M1_DA_LIGHT_NODE_DA_BACKEND",
+	DaBackend,
+	DaBackend::Celestia
+);
+
+// The default max size of the blobs packed by the M1 DA Light Node, under the Celestia max blob size
+env_default!(
+	default_m1_da_light_node_max_blob_size,
+	"M1_DA_LIGHT_NODE_MAX_BLOB_SIZE",
+	u64,
+	1_700_000
+);
+
+// The default codec of the blobs written by the M1 DA Light Node
+env_default!(
+	default_m1_da_light_node_blob_codec,
+	"M1_DA_LIGHT_NODE_BLOB_CODEC",
+	BlobCodec,
+	BlobCodec::Zstd
+);
+
+// The default verification of the blobs read by the M1 DA Light Node
+env_default!(
+	default_m1_da_light_node_read_verification,
+	"M1_DA_LIGHT_NODE_READ_VERIFICATION",
+	ReadVerification,
+	ReadVerification::Verified
+);
+
+// The default number of blobs buffered for each blob stream of the M1 DA Light Node
+env_default!(
+	default_m1_da_light_node_stream_buffer_size,
+	"M1_DA_LIGHT_NODE_STREAM_BUFFER_SIZE",
+	u32,
+	64
+);
+
+// The default policy of the M1 DA Light Node for the blob streams of lagging readers
+env_default!(
+	default_m1_da_light_node_stream_lag_policy,
+	"M1_DA_LIGHT_NODE_STREAM_LAG_POLICY",
+	StreamLagPolicy,
+	StreamLagPolicy::Park
+);
+
+// The default address to serve the metrics of the M1 DA Light Node on, unset by default
+env_default!(
+	default_m1_da_light_node_metrics_listen_address,
+	"M1_DA_LIGHT_NODE_METRICS_LISTEN_ADDRESS",
+	String
+);
+
+// The default gas price of the blob submissions of the M1 DA Light Node in utia, unset by default
+// for the Celestia node to estimate it
+env_default!(default_celestia_gas_price, "M1_DA_LIGHT_NODE_CELESTIA_GAS_PRICE", String);
+
+// The default max gas price the M1 DA Light Node escalates to in utia, unset by default
+env_default!(default_celestia_max_gas_price, "M1_DA_LIGHT_NODE_CELESTIA_MAX_GAS_PRICE", String);
+
+// The default increase of the gas price on each escalation, in percent
+env_default!(
+	default_celestia_gas_price_escalation_percent,
+	"M1_DA_LIGHT_NODE_CELESTIA_GAS_PRICE_ESCALATION_PERCENT",
+	u32,
+	50
+);
+
+// The default number of gas price escalations of a submission rejected for insufficient fees
+env_default!(
+	default_celestia_max_gas_price_escalations,
+	"M1_DA_LIGHT_NODE_CELESTIA_MAX_GAS_PRICE_ESCALATIONS",
+	u32,
+	3
+);
+
+// The default account granting the fees of the blob submissions, unset by default
+env_default!(
+	default_celestia_fee_granter_address,
+	"M1_DA_LIGHT_NODE_CELESTIA_FEE_GRANTER_ADDRESS",
+	String
+);
+
 // The default Celestia Namespace
 pub fn default_celestia_namespace() -> Namespace {
 	match std::env::var("CELESTIA_NAMESPACE") {
@@ -6,6 +6,7 @@ use crate::config::common::{
 };
 
 use celestia_types::nmt::Namespace;
+use godfig::Secret;
 use serde::{Deserialize, Serialize};
 
 /// The inner configuration for the local Celestia Appd Runner
@@ -28,7 +29,7 @@ pub struct Config {
 	pub celestia_websocket_connection_port: u16,
 
 	/// The auth token for the Celestia node
-	pub celestia_auth_token: Option<String>,
+	pub celestia_auth_token: Option<Secret>,
 
 	/// The Chain ID for the Celestia node
 	#[serde(default = "default_celestia_chain_id")]
@@ -46,6 +47,10 @@ pub struct Config {
 	/// This does not have a default because if it is needed, a default is generally not appropriate.
 	pub celestia_validator_address: Option<String>,
 
+	/// The path of a file with the mnemonic of the validator key to import when bootstrapping the
+	/// celestia app. A new key is generated if it is not set.
+	pub celestia_validator_mnemonic_path: Option<String>,
+
 	/// Whether to replace arguments in the celestia appd call
 	#[serde(default = "default_celestia_appd_use_replace_args")]
 	pub celestia_appd_use_replace_args: bool,
@@ -68,6 +73,7 @@ impl Default for Config {
 			celestia_namespace: default_celestia_namespace(),
 			celestia_path: None,
 			celestia_validator_address: None,
+			celestia_validator_mnemonic_path: None,
 			celestia_appd_use_replace_args: default_celestia_appd_use_replace_args(),
 			celestia_appd_replace_args: default_celestia_appd_replace_args(),
 		}
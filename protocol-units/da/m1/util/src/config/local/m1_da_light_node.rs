@@ -1,10 +1,172 @@
 use crate::config::common::{
-	default_celestia_rpc_connection_hostname, default_celestia_rpc_connection_port,
-	default_celestia_websocket_connection_hostname, default_celestia_websocket_connection_port,
+	default_celestia_fee_granter_address, default_celestia_gas_price,
+	default_celestia_gas_price_escalation_percent, default_celestia_max_gas_price,
+	default_celestia_max_gas_price_escalations, default_celestia_rpc_connection_hostname,
+	default_celestia_rpc_connection_port, default_celestia_websocket_connection_hostname,
+	default_celestia_websocket_connection_port, default_m1_da_light_node_blob_codec,
 	default_m1_da_light_node_connection_hostname, default_m1_da_light_node_connection_port,
-	default_m1_da_light_node_listen_hostname, default_m1_da_light_node_listen_port,
+	default_m1_da_light_node_da_backend, default_m1_da_light_node_listen_hostname,
+	default_m1_da_light_node_listen_port, default_m1_da_light_node_max_blob_size,
+	default_m1_da_light_node_metrics_listen_address, default_m1_da_light_node_read_verification,
+	default_m1_da_light_node_stream_buffer_size, default_m1_da_light_node_stream_lag_policy,
 };
+use celestia_types::nmt::Namespace;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// The DA backend the m1-da-light-node submits blobs to and reads them from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DaBackend {
+	/// A Celestia node
+	Celestia,
+	/// An in-process store, for development without a Celestia network
+	Mock,
+}
+
+impl FromStr for DaBackend {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"celestia" => Ok(DaBackend::Celestia),
+			"mock" => Ok(DaBackend::Mock),
+			_ => Err(anyhow::anyhow!("Unknown DA backend: {}", s)),
+		}
+	}
+}
+
+/// The codec the m1-da-light-node encodes the data of the blobs it writes with
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlobCodec {
+	/// The data is written as is
+	None,
+	/// The data is compressed with zstd
+	Zstd,
+}
+
+impl FromStr for BlobCodec {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"none" => Ok(BlobCodec::None),
+			"zstd" => Ok(BlobCodec::Zstd),
+			_ => Err(anyhow::anyhow!("Unknown blob codec: {}", s)),
+		}
+	}
+}
+
+/// Whether the m1-da-light-node checks the blobs it reads before handing them out
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadVerification {
+	/// The blobs are trusted, as when reading from a DA node run in the same trusted setup.
+	/// This skips the inclusion check and must be opted into.
+	Trusted,
+	/// The inclusion proof of each blob is checked against the sampled header of its height, and
+	/// the blobs failing the check are dropped
+	Verified,
+}
+
+impl FromStr for ReadVerification {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"trusted" => Ok(ReadVerification::Trusted),
+			"verified" => Ok(ReadVerification::Verified),
+			_ => Err(anyhow::anyhow!("Unknown read verification: {}", s)),
+		}
+	}
+}
+
+/// What the m1-da-light-node does with a blob stream whose reader falls behind a full buffer
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamLagPolicy {
+	/// The stream waits for the reader, holding back the reads from the DA
+	Park,
+	/// The stream is ended with an error, for the reader to resume from its last height
+	Drop,
+}
+
+impl FromStr for StreamLagPolicy {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"park" => Ok(StreamLagPolicy::Park),
+			"drop" => Ok(StreamLagPolicy::Drop),
+			_ => Err(anyhow::anyhow!("Unknown stream lag policy: {}", s)),
+		}
+	}
+}
+
+/// The fees the m1-da-light-node pays for its blob submissions to Celestia
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CelestiaGasConfig {
+	/// The gas price of the submissions in utia, e.g. `0.002`, estimated by the Celestia node if unset
+	#[serde(default = "default_celestia_gas_price")]
+	pub gas_price: Option<String>,
+
+	/// The max gas price in utia the gas price is escalated to, unbounded if unset
+	#[serde(default = "default_celestia_max_gas_price")]
+	pub max_gas_price: Option<String>,
+
+	/// The increase of the gas price, in percent, when a submission is rejected for insufficient fees
+	#[serde(default = "default_celestia_gas_price_escalation_percent")]
+	pub gas_price_escalation_percent: u32,
+
+	/// The number of times the gas price of a submission is escalated before giving up
+	#[serde(default = "default_celestia_max_gas_price_escalations")]
+	pub max_gas_price_escalations: u32,
+
+	/// The address of the account granting the fees of the submissions, if any
+	#[serde(default = "default_celestia_fee_granter_address")]
+	pub fee_granter_address: Option<String>,
+}
+
+impl Default for CelestiaGasConfig {
+	fn default() -> Self {
+		Self {
+			gas_price: default_celestia_gas_price(),
+			max_gas_price: default_celestia_max_gas_price(),
+			gas_price_escalation_percent: default_celestia_gas_price_escalation_percent(),
+			max_gas_price_escalations: default_celestia_max_gas_price_escalations(),
+			fee_granter_address: default_celestia_fee_granter_address(),
+		}
+	}
+}
+
+impl CelestiaGasConfig {
+	/// Parses the gas price, if set.
+	pub fn gas_price(&self) -> Result<Option<f64>, anyhow::Error> {
+		parse_gas_price(self.gas_price.as_deref())
+	}
+
+	/// Parses the max gas price, if set.
+	pub fn max_gas_price(&self) -> Result<Option<f64>, anyhow::Error> {
+		parse_gas_price(self.max_gas_price.as_deref())
+	}
+}
+
+fn parse_gas_price(gas_price: Option<&str>) -> Result<Option<f64>, anyhow::Error> {
+	gas_price
+		.map(|gas_price| {
+			let parsed = gas_price
+				.trim()
+				.parse::<f64>()
+				.map_err(|e| anyhow::anyhow!("Invalid gas price {}: {}", gas_price, e))?;
+			if !parsed.is_finite() || parsed < 0.0 {
+				anyhow::bail!("Invalid gas price {}", gas_price);
+			}
+			Ok(parsed)
+		})
+		.transpose()
+}
 
 /// The inner configuration for the local Celestia Appd Runner
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -40,6 +202,55 @@ pub struct Config {
 	/// The port for m1-da-light-node connection
 	#[serde(default = "default_m1_da_light_node_connection_port")]
 	pub m1_da_light_node_connection_port: u16,
+
+	/// The DA backend of the m1-da-light-node
+	#[serde(default = "default_m1_da_light_node_da_backend")]
+	pub da_backend: DaBackend,
+
+	/// The max size of the blobs the m1-da-light-node packs batches into
+	#[serde(default = "default_m1_da_light_node_max_blob_size")]
+	pub max_blob_size: u64,
+
+	/// The codec of the blobs written by the m1-da-light-node
+	#[serde(default = "default_m1_da_light_node_blob_codec")]
+	pub blob_codec: BlobCodec,
+
+	/// Whether the m1-da-light-node verifies the blobs it reads, verified by default
+	#[serde(default = "default_m1_da_light_node_read_verification")]
+	pub read_verification: ReadVerification,
+
+	/// The path of the checkpoint of the last DA height acknowledged to the m1-da-light-node
+	/// This does not have a default because if it is needed, a default is generally not appropriate.
+	pub m1_da_light_node_checkpoint_path: Option<String>,
+
+	/// The number of blobs buffered for each blob stream of the m1-da-light-node
+	#[serde(default = "default_m1_da_light_node_stream_buffer_size")]
+	pub stream_buffer_size: u32,
+
+	/// What the m1-da-light-node does with the blob streams of lagging readers
+	#[serde(default = "default_m1_da_light_node_stream_lag_policy")]
+	pub stream_lag_policy: StreamLagPolicy,
+
+	/// The address to serve the Prometheus metrics of the m1-da-light-node on, if any
+	#[serde(default = "default_m1_da_light_node_metrics_listen_address")]
+	pub metrics_listen_address: Option<String>,
+
+	/// The namespaces of the m1-da-light-node besides the default one, by lane key
+	#[serde(default)]
+	pub celestia_namespace_lanes: BTreeMap<String, Namespace>,
+
+	/// The path of the hex encoded ed25519 key the m1-da-light-node signs the blobs it writes with
+	/// This does not have a default because if it is needed, a default is generally not appropriate.
+	pub blob_signing_key_path: Option<String>,
+
+	/// The hex encoded ed25519 public keys of the sequencers whose blobs the m1-da-light-node reads
+	/// When empty, the blobs read are not authenticated.
+	#[serde(default)]
+	pub authorized_sequencer_keys: Vec<String>,
+
+	/// The fees of the blob submissions of the m1-da-light-node
+	#[serde(default)]
+	pub celestia_gas: CelestiaGasConfig,
 }
 
 impl Default for Config {
@@ -54,6 +265,18 @@ impl Default for Config {
 			m1_da_light_node_listen_port: default_m1_da_light_node_listen_port(),
 			m1_da_light_node_connection_hostname: default_m1_da_light_node_connection_hostname(),
 			m1_da_light_node_connection_port: default_m1_da_light_node_connection_port(),
+			da_backend: default_m1_da_light_node_da_backend(),
+			max_blob_size: default_m1_da_light_node_max_blob_size(),
+			blob_codec: default_m1_da_light_node_blob_codec(),
+			read_verification: default_m1_da_light_node_read_verification(),
+			m1_da_light_node_checkpoint_path: None,
+			stream_buffer_size: default_m1_da_light_node_stream_buffer_size(),
+			stream_lag_policy: default_m1_da_light_node_stream_lag_policy(),
+			metrics_listen_address: default_m1_da_light_node_metrics_listen_address(),
+			celestia_namespace_lanes: BTreeMap::new(),
+			blob_signing_key_path: None,
+			authorized_sequencer_keys: Vec::new(),
+			celestia_gas: CelestiaGasConfig::default(),
 		}
 	}
 }
@@ -2,10 +2,14 @@ use anyhow::Context;
 use celestia_rpc::Client;
 use celestia_types::nmt::Namespace;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 pub mod common;
 pub mod local;
 
+/// The lane key of the default Celestia namespace of the M1 DA Light Node.
+pub const DEFAULT_NAMESPACE_LANE: &str = "default";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Config {
 	Local(local::Config),
@@ -40,7 +44,7 @@ impl Config {
                     "Failed to get Celestia auth token from config. This is required for connecting to Celestia.",
                 )?;
 
-				let client = Client::new(&celestia_node_url, Some(&celestia_auth_token))
+				let client = Client::new(&celestia_node_url, Some(celestia_auth_token.expose()))
 					.await
 					.map_err(|e| {
 						anyhow::anyhow!(
@@ -63,7 +67,7 @@ impl Config {
 					"Failed to get Celestia auth token from config. This is required for connecting to Celestia.",
 				)?;
 
-				let client = Client::new(&celestia_node_url, Some(&celestia_auth_token))
+				let client = Client::new(&celestia_node_url, Some(celestia_auth_token.expose()))
 					.await
 					.map_err(|e| {
 						anyhow::anyhow!(
@@ -86,7 +90,7 @@ impl Config {
 					"Failed to get Celestia auth token from config. This is required for connecting to Celestia.",
 				)?;
 
-				let client = Client::new(&celestia_node_url, Some(&celestia_auth_token))
+				let client = Client::new(&celestia_node_url, Some(celestia_auth_token.expose()))
 					.await
 					.map_err(|e| {
 						anyhow::anyhow!(
@@ -101,6 +105,104 @@ impl Config {
 		}
 	}
 
+	/// Gets the DA backend of the M1 DA Light Node
+	pub fn da_backend(&self) -> local::m1_da_light_node::DaBackend {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.da_backend,
+			Config::Arabica(local) => local.m1_da_light_node.da_backend,
+			Config::Mocha(local) => local.m1_da_light_node.da_backend,
+		}
+	}
+
+	/// Gets the max size of the blobs packed by the M1 DA Light Node
+	pub fn max_blob_size(&self) -> u64 {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.max_blob_size,
+			Config::Arabica(local) => local.m1_da_light_node.max_blob_size,
+			Config::Mocha(local) => local.m1_da_light_node.max_blob_size,
+		}
+	}
+
+	/// Gets the codec of the blobs written by the M1 DA Light Node
+	pub fn blob_codec(&self) -> local::m1_da_light_node::BlobCodec {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.blob_codec,
+			Config::Arabica(local) => local.m1_da_light_node.blob_codec,
+			Config::Mocha(local) => local.m1_da_light_node.blob_codec,
+		}
+	}
+
+	/// Gets the verification of the blobs read by the M1 DA Light Node
+	pub fn read_verification(&self) -> local::m1_da_light_node::ReadVerification {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.read_verification,
+			Config::Arabica(local) => local.m1_da_light_node.read_verification,
+			Config::Mocha(local) => local.m1_da_light_node.read_verification,
+		}
+	}
+
+	/// Gets the path of the checkpoint of the M1 DA Light Node, if any
+	pub fn m1_da_light_node_checkpoint_path(&self) -> Option<String> {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.m1_da_light_node_checkpoint_path.clone(),
+			Config::Arabica(local) => {
+				local.m1_da_light_node.m1_da_light_node_checkpoint_path.clone()
+			}
+			Config::Mocha(local) => local.m1_da_light_node.m1_da_light_node_checkpoint_path.clone(),
+		}
+	}
+
+	/// Gets the buffer size and the lag policy of the blob streams of the M1 DA Light Node
+	pub fn stream_flow_parameters(&self) -> (u32, local::m1_da_light_node::StreamLagPolicy) {
+		match self {
+			Config::Local(local) => {
+				(local.m1_da_light_node.stream_buffer_size, local.m1_da_light_node.stream_lag_policy)
+			}
+			Config::Arabica(local) => {
+				(local.m1_da_light_node.stream_buffer_size, local.m1_da_light_node.stream_lag_policy)
+			}
+			Config::Mocha(local) => {
+				(local.m1_da_light_node.stream_buffer_size, local.m1_da_light_node.stream_lag_policy)
+			}
+		}
+	}
+
+	/// Gets the address to serve the metrics of the M1 DA Light Node on, if any
+	pub fn metrics_listen_address(&self) -> Option<String> {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.metrics_listen_address.clone(),
+			Config::Arabica(local) => local.m1_da_light_node.metrics_listen_address.clone(),
+			Config::Mocha(local) => local.m1_da_light_node.metrics_listen_address.clone(),
+		}
+	}
+
+	/// Gets the path of the key the M1 DA Light Node signs the blobs it writes with, if any
+	pub fn blob_signing_key_path(&self) -> Option<String> {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.blob_signing_key_path.clone(),
+			Config::Arabica(local) => local.m1_da_light_node.blob_signing_key_path.clone(),
+			Config::Mocha(local) => local.m1_da_light_node.blob_signing_key_path.clone(),
+		}
+	}
+
+	/// Gets the public keys of the sequencers whose blobs the M1 DA Light Node reads
+	pub fn authorized_sequencer_keys(&self) -> Vec<String> {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.authorized_sequencer_keys.clone(),
+			Config::Arabica(local) => local.m1_da_light_node.authorized_sequencer_keys.clone(),
+			Config::Mocha(local) => local.m1_da_light_node.authorized_sequencer_keys.clone(),
+		}
+	}
+
+	/// Gets the fee configuration of the blob submissions of the M1 DA Light Node
+	pub fn celestia_gas(&self) -> local::m1_da_light_node::CelestiaGasConfig {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.celestia_gas.clone(),
+			Config::Arabica(local) => local.m1_da_light_node.celestia_gas.clone(),
+			Config::Mocha(local) => local.m1_da_light_node.celestia_gas.clone(),
+		}
+	}
+
 	/// Gets the Celestia namespace
 	pub fn celestia_namespace(&self) -> Namespace {
 		match self {
@@ -110,6 +212,18 @@ impl Config {
 		}
 	}
 
+	/// Gets the Celestia namespaces of the M1 DA Light Node by lane key, with the default
+	/// namespace under [DEFAULT_NAMESPACE_LANE]
+	pub fn celestia_namespaces(&self) -> BTreeMap<String, Namespace> {
+		let mut namespaces = match self {
+			Config::Local(local) => local.m1_da_light_node.celestia_namespace_lanes.clone(),
+			Config::Arabica(local) => local.m1_da_light_node.celestia_namespace_lanes.clone(),
+			Config::Mocha(local) => local.m1_da_light_node.celestia_namespace_lanes.clone(),
+		};
+		namespaces.insert(DEFAULT_NAMESPACE_LANE.to_string(), self.celestia_namespace());
+		namespaces
+	}
+
 	/// Gets M1 DA Light Node listen hostname
 	pub fn m1_da_light_node_listen_hostname(&self) -> String {
 		match self {
@@ -184,6 +298,18 @@ impl Config {
 		}
 	}
 
+	/// Gets the max transactions, max bytes and max transactions per account of the mempool
+	pub fn try_mempool_limits(&self) -> Result<(usize, usize, usize), anyhow::Error> {
+		let memseq = match self {
+			Config::Local(local) | Config::Arabica(local) | Config::Mocha(local) => &local.memseq,
+		};
+		Ok((
+			memseq.memseq_max_mempool_transactions,
+			memseq.memseq_max_mempool_bytes,
+			memseq.memseq_max_account_transactions,
+		))
+	}
+
 }
 
 /// The M1 DA Light Node configuration as should be read from file.
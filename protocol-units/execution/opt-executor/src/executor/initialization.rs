@@ -1,4 +1,4 @@
-use super::Executor;
+use super::{rate_limit::RateLimiter, Executor};
 use aptos_api::Context;
 use aptos_config::config::NodeConfig;
 #[cfg(test)]
@@ -154,6 +154,10 @@ impl Executor {
 				maptos_config.chain.maptos_rest_listen_hostname,
 				maptos_config.chain.maptos_rest_listen_port
 			),
+			account_rate_limiter: Arc::new(RateLimiter::new(
+				maptos_config.rate_limit.maptos_rate_limit_account_transactions_per_second,
+				maptos_config.rate_limit.maptos_rate_limit_account_burst,
+			)),
 			maptos_config,
 			transactions_in_flight: Arc::new(AtomicU64::new(0)),
 		})
@@ -168,7 +172,7 @@ impl Executor {
 		node_config.indexer.enabled = true;
 		// indexer config
 		node_config.indexer.postgres_uri =
-			Some(maptos_config.indexer_processor.postgres_connection_string.clone());
+			Some(maptos_config.indexer_processor.postgres_connection_string.expose().clone());
 		node_config.indexer.processor = Some("default_processor".to_string());
 		node_config.indexer.check_chain_id = Some(false);
 		node_config.indexer.skip_migrations = Some(false);
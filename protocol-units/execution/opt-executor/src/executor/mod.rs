@@ -1,6 +1,7 @@
 //! Implementation is split over multiple files to make the code more manageable.
 pub mod execution;
 pub mod initialization;
+pub mod rate_limit;
 pub mod services;
 pub mod transaction_pipe;
 use anyhow::Context as _;
@@ -10,9 +11,11 @@ use aptos_db::AptosDB;
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_mempool::{MempoolClientRequest, MempoolClientSender};
 use aptos_storage_interface::DbReaderWriter;
+use aptos_types::account_address::AccountAddress;
 use aptos_types::validator_signer::ValidatorSigner;
 use aptos_vm::AptosVM;
 use futures::channel::mpsc as futures_mpsc;
+use rate_limit::RateLimiter;
 use std::sync::{atomic::AtomicU64, Arc};
 use tokio::sync::RwLock;
 pub mod indexer;
@@ -41,6 +44,8 @@ pub struct Executor {
 	pub maptos_config: maptos_execution_util::config::Config,
 	/// Transactions in flight counter.
 	pub transactions_in_flight: Arc<AtomicU64>,
+	/// The rate limiter of the transactions submitted by each account.
+	pub account_rate_limiter: Arc<RateLimiter<AccountAddress>>,
 }
 
 impl Executor {
@@ -77,6 +82,10 @@ impl Executor {
 				maptos_config.chain.maptos_rest_listen_hostname,
 				maptos_config.chain.maptos_rest_listen_port
 			),
+			account_rate_limiter: Arc::new(RateLimiter::new(
+				maptos_config.rate_limit.maptos_rate_limit_account_transactions_per_second,
+				maptos_config.rate_limit.maptos_rate_limit_account_burst,
+			)),
 			maptos_config,
 			transactions_in_flight: Arc::new(AtomicU64::new(0)),
 		})
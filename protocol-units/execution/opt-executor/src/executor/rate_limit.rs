@@ -0,0 +1,252 @@
+//! Token bucket rate limiting of the API, per IP on the REST server and per account in the
+//! transaction pipe, so that a single client cannot starve the others.
+use poem::{
+	http::{header, StatusCode},
+	Endpoint, IntoResponse, Middleware, Request, Response,
+};
+use std::{
+	collections::{hash_map::RandomState, HashMap},
+	hash::{BuildHasher, Hash},
+	net::IpAddr,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+/// The number of shards of the buckets, so that the requests of different keys rarely wait for
+/// the same lock.
+const SHARDS: usize = 16;
+
+/// A request or transaction refused by the rate limiter.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("rate limited, retry after {retry_after:?}")]
+pub struct RateLimited {
+	/// The time until a token is available again.
+	pub retry_after: Duration,
+}
+
+impl RateLimited {
+	/// The delay of the Retry-After header, rounded up to the second.
+	pub fn retry_after_secs(&self) -> u64 {
+		self.retry_after.as_secs_f64().ceil() as u64
+	}
+}
+
+impl IntoResponse for RateLimited {
+	/// A 429 response with the error in the format of the Aptos API errors.
+	fn into_response(self) -> Response {
+		let body = serde_json::json!({
+			"message": self.to_string(),
+			"error_code": "rate_limited",
+			"vm_error_code": null,
+			"retry_after_secs": self.retry_after_secs(),
+		});
+		Response::builder()
+			.status(StatusCode::TOO_MANY_REQUESTS)
+			.header(header::RETRY_AFTER, self.retry_after_secs().to_string())
+			.content_type("application/json")
+			.body(body.to_string())
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+	tokens: f64,
+	updated: Instant,
+}
+
+#[derive(Debug)]
+struct Shard<K> {
+	buckets: HashMap<K, TokenBucket>,
+	/// The last time the full buckets were dropped.
+	swept: Instant,
+}
+
+/// Limits each key to `rate` requests per second, after a burst of `burst` requests.
+/// Each key has a bucket of `burst` tokens refilled at `rate` tokens per second, and a request
+/// takes a token. A rate of 0 disables the limit.
+///
+/// The buckets are sharded by key. A bucket not used for the time it takes to refill is full,
+/// like a new one, so each shard drops those at most once per refill time, when a request
+/// locks it.
+#[derive(Debug)]
+pub struct RateLimiter<K> {
+	rate: f64,
+	burst: f64,
+	refill_time: Duration,
+	hasher: RandomState,
+	shards: Vec<Mutex<Shard<K>>>,
+}
+
+impl<K: Hash + Eq + Clone> RateLimiter<K> {
+	pub fn new(rate: u32, burst: u32) -> Self {
+		let burst = burst.max(1) as f64;
+		let refill_time =
+			if rate > 0 { Duration::from_secs_f64(burst / rate as f64) } else { Duration::ZERO };
+		let now = Instant::now();
+		Self {
+			rate: rate as f64,
+			burst,
+			refill_time,
+			hasher: RandomState::new(),
+			shards: (0..SHARDS)
+				.map(|_| Mutex::new(Shard { buckets: HashMap::new(), swept: now }))
+				.collect(),
+		}
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.rate > 0.0
+	}
+
+	/// Takes a token of the key, or tells how long to wait for one.
+	pub fn check(&self, key: &K) -> Result<(), RateLimited> {
+		self.check_at(key, Instant::now())
+	}
+
+	fn check_at(&self, key: &K, now: Instant) -> Result<(), RateLimited> {
+		if !self.is_enabled() {
+			return Ok(());
+		}
+		let mut shard =
+			self.shards[self.shard_index(key)].lock().expect("Rate limiter lock poisoned");
+		if now.saturating_duration_since(shard.swept) >= self.refill_time {
+			let refill_time = self.refill_time;
+			shard
+				.buckets
+				.retain(|_, bucket| now.saturating_duration_since(bucket.updated) < refill_time);
+			shard.swept = now;
+		}
+		let bucket = shard
+			.buckets
+			.entry(key.clone())
+			.or_insert(TokenBucket { tokens: self.burst, updated: now });
+		let tokens = self.refill(bucket, now);
+		if tokens >= 1.0 {
+			*bucket = TokenBucket { tokens: tokens - 1.0, updated: now };
+			Ok(())
+		} else {
+			Err(RateLimited { retry_after: Duration::from_secs_f64((1.0 - tokens) / self.rate) })
+		}
+	}
+
+	fn shard_index(&self, key: &K) -> usize {
+		self.hasher.hash_one(key) as usize % self.shards.len()
+	}
+
+	fn refill(&self, bucket: &TokenBucket, now: Instant) -> f64 {
+		let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+		(bucket.tokens + elapsed * self.rate).min(self.burst)
+	}
+}
+
+/// Middleware of the REST server limiting the requests of each IP.
+pub struct IpRateLimit {
+	limiter: Arc<RateLimiter<IpAddr>>,
+}
+
+impl IpRateLimit {
+	pub fn new(requests_per_second: u32, burst: u32) -> Self {
+		Self { limiter: Arc::new(RateLimiter::new(requests_per_second, burst)) }
+	}
+}
+
+impl<E: Endpoint> Middleware<E> for IpRateLimit {
+	type Output = IpRateLimitEndpoint<E>;
+
+	fn transform(&self, inner: E) -> Self::Output {
+		IpRateLimitEndpoint { inner, limiter: self.limiter.clone() }
+	}
+}
+
+pub struct IpRateLimitEndpoint<E> {
+	inner: E,
+	limiter: Arc<RateLimiter<IpAddr>>,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for IpRateLimitEndpoint<E> {
+	type Output = Response;
+
+	async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+		if let Some(address) = req.remote_addr().as_socket_addr() {
+			if let Err(limited) = self.limiter.check(&address.ip()) {
+				tracing::debug!("Rate limited request from {}: {limited}", address.ip());
+				return Ok(limited.into_response());
+			}
+		}
+		Ok(self.inner.call(req).await?.into_response())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_burst_then_rate() {
+		let limiter = RateLimiter::new(10, 3);
+		let start = Instant::now();
+
+		for _ in 0..3 {
+			assert!(limiter.check_at(&"alice", start).is_ok());
+		}
+		let limited = limiter.check_at(&"alice", start).unwrap_err();
+		assert_eq!(limited.retry_after, Duration::from_millis(100));
+		assert_eq!(limited.retry_after_secs(), 1);
+
+		// The other keys have their own bucket.
+		assert!(limiter.check_at(&"bob", start).is_ok());
+
+		// A token is refilled every 100ms, up to the burst.
+		let later = start + Duration::from_millis(100);
+		assert!(limiter.check_at(&"alice", later).is_ok());
+		assert!(limiter.check_at(&"alice", later).is_err());
+		let much_later = start + Duration::from_secs(60);
+		for _ in 0..3 {
+			assert!(limiter.check_at(&"alice", much_later).is_ok());
+		}
+		assert!(limiter.check_at(&"alice", much_later).is_err());
+	}
+
+	#[test]
+	fn test_full_buckets_are_dropped() {
+		let limiter = RateLimiter::new(10, 3);
+		let start = Instant::now();
+		let shard_len =
+			|key: &u32| limiter.shards[limiter.shard_index(key)].lock().unwrap().buckets.len();
+		let neighbour =
+			(1..).find(|key| limiter.shard_index(key) == limiter.shard_index(&0)).unwrap();
+
+		assert!(limiter.check_at(&0, start).is_ok());
+		assert!(limiter.check_at(&neighbour, start).is_ok());
+		assert_eq!(shard_len(&0), 2);
+
+		// the bucket of the key is full again after 300ms and dropped with the next request
+		let refilled = start + Duration::from_millis(300);
+		assert!(limiter.check_at(&neighbour, refilled).is_ok());
+		assert_eq!(shard_len(&0), 1);
+
+		// a dropped bucket starts full
+		for _ in 0..3 {
+			assert!(limiter.check_at(&0, refilled).is_ok());
+		}
+		assert!(limiter.check_at(&0, refilled).is_err());
+	}
+
+	#[test]
+	fn test_zero_rate_disables_the_limit() {
+		let limiter = RateLimiter::new(0, 0);
+		let now = Instant::now();
+		assert!(!limiter.is_enabled());
+		for _ in 0..1_000 {
+			assert!(limiter.check_at(&1u8, now).is_ok());
+		}
+	}
+
+	#[test]
+	fn test_rate_limited_response() {
+		let response = RateLimited { retry_after: Duration::from_millis(1_500) }.into_response();
+		assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+		assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "2");
+	}
+}
@@ -1,4 +1,4 @@
-use super::Executor;
+use super::{rate_limit::IpRateLimit, Executor};
 use aptos_api::{
 	get_api_service,
 	runtime::{get_apis, root_handler, Apis},
@@ -26,6 +26,11 @@ impl Executor {
 		let cors = Cors::new()
 			.allow_methods(vec![Method::GET, Method::POST])
 			.allow_credentials(true);
+		let rate_limit = &self.maptos_config.rate_limit;
+		let ip_rate_limit = IpRateLimit::new(
+			rate_limit.maptos_rate_limit_ip_requests_per_second,
+			rate_limit.maptos_rate_limit_ip_burst,
+		);
 		let app = Route::new()
 			.at("/", poem::get(root_handler))
 			.nest("/v1", api_service)
@@ -36,7 +41,8 @@ impl Executor {
 				"/set_failpoint",
 				poem::get(set_failpoints::set_failpoint_poem).data(self.context()),
 			)
-			.with(cors);
+			.with(cors)
+			.with(ip_rate_limit);
 
 		Server::new(TcpListener::bind(self.listen_url.clone()))
 			.run(app)
@@ -47,6 +47,23 @@ impl Executor {
 		if let Some(request) = next {
 			match request {
 				MempoolClientRequest::SubmitTransaction(transaction, callback) => {
+					if let Err(limited) = self.account_rate_limiter.check(&transaction.sender()) {
+						info!(
+							target: "movement_timing",
+							sender = %transaction.sender(),
+							"rate_limited"
+						);
+						let status = MempoolStatus::new(MempoolStatusCode::TooManyTransactions)
+							.with_message(format!("Account {} is {limited}", transaction.sender()));
+						callback.send(Ok((status, None))).map_err(|e| {
+							TransactionPipeError::InternalError(format!(
+								"Error sending transaction: {:?}",
+								e
+							))
+						})?;
+						return Ok(());
+					}
+
 					// For now, we are going to consider a transaction in flight until it exits the mempool and is sent to the DA as is indicated by WriteBatch.
 					let in_flight =
 						self.transactions_in_flight.load(std::sync::atomic::Ordering::SeqCst);
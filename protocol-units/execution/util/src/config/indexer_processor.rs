@@ -1,13 +1,14 @@
 use super::common::{default_indexer_processor_auth_token, default_postgres_connection_string};
+use godfig::Secret;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
 	#[serde(default = "default_postgres_connection_string")]
-	pub postgres_connection_string: String,
+	pub postgres_connection_string: Secret,
 
 	#[serde(default = "default_indexer_processor_auth_token")]
-	pub indexer_processor_auth_token: String,
+	pub indexer_processor_auth_token: Secret,
 }
 
 impl Default for Config {
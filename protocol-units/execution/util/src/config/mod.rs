@@ -5,6 +5,7 @@ pub mod faucet;
 pub mod fin;
 pub mod indexer;
 pub mod indexer_processor;
+pub mod rate_limit;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,6 +33,10 @@ pub struct Config {
 	/// The fin configuration
 	#[serde(default)]
 	pub fin: fin::Config,
+
+	/// The rate limiting configuration of the API
+	#[serde(default)]
+	pub rate_limit: rate_limit::Config,
 }
 
 impl Default for Config {
@@ -43,6 +48,7 @@ impl Default for Config {
 			client: client::Config::default(),
 			faucet: faucet::Config::default(),
 			fin: fin::Config::default(),
+			rate_limit: rate_limit::Config::default(),
 		}
 	}
 }
@@ -0,0 +1,38 @@
+use super::common::{
+	default_maptos_rate_limit_account_burst,
+	default_maptos_rate_limit_account_transactions_per_second, default_maptos_rate_limit_ip_burst,
+	default_maptos_rate_limit_ip_requests_per_second,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+	/// The requests per second accepted from an IP by the Aptos REST server, 0 disables the limit
+	#[serde(default = "default_maptos_rate_limit_ip_requests_per_second")]
+	pub maptos_rate_limit_ip_requests_per_second: u32,
+
+	/// The requests an IP can send at once before being limited to its rate
+	#[serde(default = "default_maptos_rate_limit_ip_burst")]
+	pub maptos_rate_limit_ip_burst: u32,
+
+	/// The transactions per second accepted from an account, 0 disables the limit
+	#[serde(default = "default_maptos_rate_limit_account_transactions_per_second")]
+	pub maptos_rate_limit_account_transactions_per_second: u32,
+
+	/// The transactions an account can submit at once before being limited to its rate
+	#[serde(default = "default_maptos_rate_limit_account_burst")]
+	pub maptos_rate_limit_account_burst: u32,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			maptos_rate_limit_ip_requests_per_second:
+				default_maptos_rate_limit_ip_requests_per_second(),
+			maptos_rate_limit_ip_burst: default_maptos_rate_limit_ip_burst(),
+			maptos_rate_limit_account_transactions_per_second:
+				default_maptos_rate_limit_account_transactions_per_second(),
+			maptos_rate_limit_account_burst: default_maptos_rate_limit_account_burst(),
+		}
+	}
+}
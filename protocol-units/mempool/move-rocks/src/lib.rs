@@ -71,6 +71,48 @@ impl RocksdbMempool {
 		.await?
 	}
 
+	/// Reads all the mempool transactions, without removing them.
+	pub async fn mempool_transactions(&self) -> Result<Vec<MempoolTransaction>, Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle = db
+				.cf_handle("mempool_transactions")
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+			db.iterator_cf(&cf_handle, rocksdb::IteratorMode::Start)
+				.map(|res| {
+					let (_, value) = res?;
+					Ok(bcs::from_bytes(&value)?)
+				})
+				.collect()
+		})
+		.await?
+	}
+
+	/// Removes the mempool transactions, ignoring the ones not in the mempool.
+	pub async fn remove_mempool_transactions(&self, transaction_ids: Vec<Id>) -> Result<(), Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let mempool_transactions_cf_handle = db
+				.cf_handle("mempool_transactions")
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+			let transaction_lookups_cf_handle = db
+				.cf_handle("transaction_lookups")
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+			for transaction_id in transaction_ids {
+				if let Some(key) =
+					Self::internal_get_mempool_transaction_key(db.clone(), &transaction_id)?
+				{
+					db.delete_cf(&mempool_transactions_cf_handle, key)?;
+					db.delete_cf(&transaction_lookups_cf_handle, transaction_id.to_vec())?;
+				}
+			}
+			Ok::<(), Error>(())
+		})
+		.await??;
+		Ok(())
+	}
+
 	fn internal_has_mempool_transaction(db: Arc<DB>, transaction_id: &Id) -> Result<bool, Error> {
 		let key = Self::internal_get_mempool_transaction_key(db.clone(), transaction_id)?;
 		match key {
@@ -0,0 +1,27 @@
+use crate::{Prioritizer, Priority};
+use aptos_types::transaction::SignedTransaction;
+use movement_types::Transaction;
+
+/// Prioritizes the Aptos transactions, whose data is the JSON of the signed transaction written
+/// by the full node, by their sender and gas unit price.
+/// The transactions which can't be decoded get their own account and the lowest priority.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AptosPrioritizer;
+
+impl Prioritizer for AptosPrioritizer {
+	fn prioritize(&self, transaction: &Transaction) -> Priority {
+		match serde_json::from_slice::<SignedTransaction>(&transaction.data) {
+			Ok(signed_transaction) => Priority {
+				sender: signed_transaction.sender().to_vec(),
+				gas_unit_price: signed_transaction.gas_unit_price(),
+			},
+			Err(error) => {
+				tracing::debug!(
+					"Mempool: failed to decode transaction {}: {error}",
+					transaction.id()
+				);
+				Priority { sender: transaction.id().to_vec(), gas_unit_price: 0 }
+			}
+		}
+	}
+}
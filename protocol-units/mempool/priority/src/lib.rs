@@ -0,0 +1,752 @@
+mod aptos;
+
+pub use aptos::AptosPrioritizer;
+
+use mempool_util::{MempoolBlockOperations, MempoolTransaction, MempoolTransactionOperations};
+use move_rocks::RocksdbMempool;
+use movement_types::{Block, Id, Transaction};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// The account and the fee of a transaction, which the mempool orders the transactions by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Priority {
+	/// The account whose transactions are handed out by sequence number.
+	pub sender: Vec<u8>,
+	pub gas_unit_price: u64,
+}
+
+/// Extracts the priority of the transactions, whose data the mempool doesn't know.
+pub trait Prioritizer: Send + Sync {
+	fn prioritize(&self, transaction: &Transaction) -> Priority;
+}
+
+/// The limits of the mempool, beyond which the transactions with the lowest fees are evicted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MempoolLimits {
+	pub max_transactions: usize,
+	pub max_bytes: usize,
+	pub max_transactions_per_account: usize,
+}
+
+impl Default for MempoolLimits {
+	fn default() -> Self {
+		Self {
+			max_transactions: 100_000,
+			max_bytes: 256 * 1024 * 1024,
+			max_transactions_per_account: 100,
+		}
+	}
+}
+
+/// Why a transaction was not admitted into the mempool.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MempoolError {
+	#[error("mempool is full of transactions with higher fees")]
+	Full,
+	#[error("account has {0} transactions in the mempool already")]
+	AccountFull(usize),
+	#[error("transaction with the same sequence number and a higher or equal fee already in the mempool")]
+	Underpriced,
+	#[error("transaction of {0} bytes is larger than the mempool")]
+	TooLarge(usize),
+}
+
+/// The transactions of a batch which were not admitted into the mempool, while the others were.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub struct RejectedTransactions(pub Vec<(Id, MempoolError)>);
+
+impl fmt::Display for RejectedTransactions {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "mempool rejected {} transactions", self.0.len())?;
+		for (i, (id, error)) in self.0.iter().enumerate() {
+			write!(f, "{} {id}: {error}", if i == 0 { ":" } else { "," })?;
+		}
+		Ok(())
+	}
+}
+
+/// The counters of the mempool, reported in the metrics of the node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MempoolMetrics {
+	/// The transactions in the mempool.
+	pub transactions: usize,
+	/// The bytes of the transactions in the mempool.
+	pub bytes: usize,
+	pub admitted: u64,
+	/// The transactions replaced by one with the same sequence number and a higher fee.
+	pub replaced: u64,
+	pub rejected: u64,
+	/// The transactions evicted to admit transactions with higher fees.
+	pub evicted: u64,
+	pub popped: u64,
+}
+
+struct Entry {
+	transaction: MempoolTransaction,
+	gas_unit_price: u64,
+	/// The order of admission, which orders the transactions with the same fee.
+	arrival: u64,
+	bytes: usize,
+}
+
+/// The key of the next transaction of an account, ordered by decreasing fee then arrival.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct HeadKey {
+	gas_unit_price: Reverse<u64>,
+	arrival: u64,
+	sender: Vec<u8>,
+}
+
+#[derive(Default)]
+struct State {
+	/// The transactions of each account, by sequence number.
+	accounts: HashMap<Vec<u8>, BTreeMap<u64, Entry>>,
+	/// The transactions with the lowest sequence number of each account, the only ones which
+	/// can be handed out.
+	heads: BTreeSet<HeadKey>,
+	/// The account and the sequence number of each transaction.
+	ids: HashMap<Id, (Vec<u8>, u64)>,
+	blocks: HashMap<Id, Block>,
+	next_arrival: u64,
+	metrics: MempoolMetrics,
+}
+
+impl State {
+	/// Removes the head of the account before its transactions change.
+	fn detach(&mut self, sender: &[u8]) {
+		if let Some(key) = self.head_key(sender) {
+			self.heads.remove(&key);
+		}
+	}
+
+	/// Adds back the head of the account after its transactions changed.
+	fn attach(&mut self, sender: &[u8]) {
+		match self.head_key(sender) {
+			Some(key) => {
+				self.heads.insert(key);
+			}
+			None => {
+				self.accounts.remove(sender);
+			}
+		}
+	}
+
+	fn head_key(&self, sender: &[u8]) -> Option<HeadKey> {
+		let (_, entry) = self.accounts.get(sender)?.first_key_value()?;
+		Some(HeadKey {
+			gas_unit_price: Reverse(entry.gas_unit_price),
+			arrival: entry.arrival,
+			sender: sender.to_vec(),
+		})
+	}
+
+	fn remove(&mut self, sender: &[u8], sequence_number: u64) -> Option<Entry> {
+		self.detach(sender);
+		let entry = self
+			.accounts
+			.get_mut(sender)
+			.and_then(|transactions| transactions.remove(&sequence_number));
+		self.attach(sender);
+		let entry = entry?;
+		self.ids.remove(&entry.transaction.id());
+		self.metrics.transactions -= 1;
+		self.metrics.bytes -= entry.bytes;
+		Some(entry)
+	}
+
+	/// The transactions to evict for a transaction of `bytes` with the fee to fit in the limits,
+	/// once the transaction it replaces is removed. The transaction with the highest sequence
+	/// number of the account with the lowest fee is evicted first, as it leaves no gap in the
+	/// sequence numbers of the account.
+	///
+	/// Nothing is removed here, so that a rejected transaction leaves the mempool as it was.
+	fn evictions(
+		&self,
+		replaced: Option<(&[u8], u64)>,
+		bytes: usize,
+		gas_unit_price: u64,
+		limits: &MempoolLimits,
+	) -> Result<Vec<(Vec<u8>, u64)>, MempoolError> {
+		let mut transactions = self.metrics.transactions + 1;
+		let mut total_bytes = self.metrics.bytes + bytes;
+		if let Some((sender, sequence_number)) = replaced {
+			if let Some(entry) = self.accounts.get(sender).and_then(|t| t.get(&sequence_number)) {
+				transactions -= 1;
+				total_bytes -= entry.bytes;
+			}
+		}
+
+		// the remaining transactions of each account, from the highest sequence number
+		let mut remaining: HashMap<&[u8], _> = self
+			.accounts
+			.iter()
+			.map(|(sender, entries)| {
+				let entries = entries.iter().rev().filter(move |(sequence_number, _)| {
+					replaced != Some((sender.as_slice(), **sequence_number))
+				});
+				(sender.as_slice(), entries)
+			})
+			.collect();
+		let mut candidates = BinaryHeap::new();
+		let mut next_candidate = |sender: &[u8], candidates: &mut BinaryHeap<_>| {
+			if let Some((sequence_number, entry)) =
+				remaining.get_mut(sender).and_then(Iterator::next)
+			{
+				let sender = sender.to_vec();
+				candidates.push(Reverse((
+					entry.gas_unit_price,
+					Reverse(entry.arrival),
+					sender,
+					*sequence_number,
+					entry.bytes,
+				)));
+			}
+		};
+		for sender in self.accounts.keys() {
+			next_candidate(sender, &mut candidates);
+		}
+
+		let mut evictions = Vec::new();
+		while transactions > limits.max_transactions || total_bytes > limits.max_bytes {
+			match candidates.pop() {
+				Some(Reverse((evicted_price, _, sender, sequence_number, evicted_bytes)))
+					if evicted_price < gas_unit_price =>
+				{
+					transactions -= 1;
+					total_bytes -= evicted_bytes;
+					next_candidate(&sender, &mut candidates);
+					evictions.push((sender, sequence_number));
+				}
+				_ => return Err(MempoolError::Full),
+			}
+		}
+		Ok(evictions)
+	}
+
+	/// Admits the transaction, and returns the transactions it replaced or evicted. A rejected
+	/// transaction leaves the mempool unchanged.
+	fn insert(
+		&mut self,
+		transaction: MempoolTransaction,
+		priority: Priority,
+		limits: &MempoolLimits,
+	) -> Result<Vec<Id>, MempoolError> {
+		let Priority { sender, gas_unit_price } = priority;
+		let sequence_number = transaction.transaction.sequence_number;
+		let bytes = transaction.transaction.data.len();
+		if bytes > limits.max_bytes {
+			return Err(MempoolError::TooLarge(bytes));
+		}
+
+		let replaced = self
+			.accounts
+			.get(&sender)
+			.and_then(|transactions| transactions.get(&sequence_number))
+			.map(|entry| entry.gas_unit_price);
+		let replaces = match replaced {
+			Some(replaced_price) if replaced_price >= gas_unit_price => {
+				return Err(MempoolError::Underpriced)
+			}
+			Some(_) => true,
+			None => {
+				let account_transactions = self.accounts.get(&sender).map_or(0, BTreeMap::len);
+				if account_transactions >= limits.max_transactions_per_account {
+					return Err(MempoolError::AccountFull(account_transactions));
+				}
+				false
+			}
+		};
+		let evictions = self.evictions(
+			replaces.then_some((sender.as_slice(), sequence_number)),
+			bytes,
+			gas_unit_price,
+			limits,
+		)?;
+
+		let mut removed = Vec::new();
+		if replaces {
+			if let Some(replaced) = self.remove(&sender, sequence_number) {
+				removed.push(replaced.transaction.id());
+			}
+			self.metrics.replaced += 1;
+		}
+		for (evicted_sender, evicted_sequence_number) in evictions {
+			if let Some(evicted) = self.remove(&evicted_sender, evicted_sequence_number) {
+				tracing::debug!(
+					"Mempool: evicted transaction {} with gas unit price {}",
+					evicted.transaction.id(),
+					evicted.gas_unit_price
+				);
+				removed.push(evicted.transaction.id());
+			}
+			self.metrics.evicted += 1;
+		}
+
+		let arrival = self.next_arrival;
+		self.next_arrival += 1;
+		self.ids.insert(transaction.id(), (sender.clone(), sequence_number));
+		self.detach(&sender);
+		self.accounts
+			.entry(sender.clone())
+			.or_default()
+			.insert(sequence_number, Entry { transaction, gas_unit_price, arrival, bytes });
+		self.attach(&sender);
+		self.metrics.transactions += 1;
+		self.metrics.bytes += bytes;
+		self.metrics.admitted += 1;
+		Ok(removed)
+	}
+
+	fn pop(&mut self) -> Option<MempoolTransaction> {
+		let head = self.heads.first()?.clone();
+		let sequence_number = *self.accounts.get(&head.sender)?.first_key_value()?.0;
+		let entry = self.remove(&head.sender, sequence_number)?;
+		self.metrics.popped += 1;
+		Some(entry.transaction)
+	}
+}
+
+/// A mempool bounded in transactions and bytes, handing out the transactions by decreasing
+/// fee. The transactions of an account are handed out by sequence number, a transaction is only
+/// competing with the other accounts once the previous ones are out. When the mempool is full,
+/// the transactions with the lowest fees are evicted to admit the ones with higher fees.
+///
+/// The transactions are ordered in memory. A persistent mempool also writes them through to a
+/// RocksDB mempool, which it reloads them from when the node restarts.
+#[derive(Clone)]
+pub struct PriorityMempool<P = AptosPrioritizer> {
+	state: Arc<Mutex<State>>,
+	limits: MempoolLimits,
+	prioritizer: P,
+	/// The store the changes are written to, locked across the change in memory and in the
+	/// store so that they are written in the same order.
+	store: Option<Arc<tokio::sync::Mutex<RocksdbMempool>>>,
+}
+
+impl<P: Prioritizer> PriorityMempool<P> {
+	/// Creates an in-memory mempool, whose transactions are lost when the node stops.
+	pub fn new(limits: MempoolLimits, prioritizer: P) -> Self {
+		Self { state: Arc::new(Mutex::new(State::default())), limits, prioritizer, store: None }
+	}
+
+	/// Creates a mempool persisted in the RocksDB mempool at the path, with the transactions
+	/// persisted there already. The transactions over the limits, if they were lowered, are
+	/// dropped.
+	pub async fn try_persistent(
+		path: &str,
+		limits: MempoolLimits,
+		prioritizer: P,
+	) -> Result<Self, anyhow::Error> {
+		let store = RocksdbMempool::try_new(path)?;
+		let transactions = store.mempool_transactions().await?;
+		let mut dropped = Vec::new();
+		let mempool = Self::new(limits, prioritizer);
+		for transaction in transactions {
+			let id = transaction.id();
+			match mempool.try_add(transaction) {
+				Ok(removed) => dropped.extend(removed),
+				Err(error) => {
+					tracing::warn!("Mempool: dropped persisted transaction {id}: {error}");
+					dropped.push(id);
+				}
+			}
+		}
+		store.remove_mempool_transactions(dropped).await?;
+		tracing::info!(
+			"Mempool: reloaded {} persisted transactions",
+			mempool.metrics().transactions
+		);
+		Ok(Self { store: Some(Arc::new(tokio::sync::Mutex::new(store))), ..mempool })
+	}
+
+	pub fn limits(&self) -> MempoolLimits {
+		self.limits
+	}
+
+	pub fn metrics(&self) -> MempoolMetrics {
+		self.lock_state().metrics
+	}
+
+	/// Admits the transaction in memory and returns the transactions it replaced or evicted, or
+	/// tells why it was rejected.
+	fn try_add(&self, transaction: MempoolTransaction) -> Result<Vec<Id>, MempoolError> {
+		let priority = self.prioritizer.prioritize(&transaction.transaction);
+		let mut state = self.lock_state();
+		if state.ids.contains_key(&transaction.id()) {
+			return Ok(Vec::new());
+		}
+		let result = state.insert(transaction, priority, &self.limits);
+		if result.is_err() {
+			state.metrics.rejected += 1;
+		}
+		result
+	}
+
+	fn lock_state(&self) -> std::sync::MutexGuard<'_, State> {
+		self.state.lock().expect("Mempool lock poisoned")
+	}
+
+	/// Admits the transactions one by one, so that a rejected transaction doesn't fail the
+	/// others, and writes the changes through to the store.
+	async fn add(
+		&self,
+		transactions: Vec<MempoolTransaction>,
+	) -> Result<Vec<(Id, MempoolError)>, anyhow::Error> {
+		let store = match &self.store {
+			Some(store) => Some(store.lock().await),
+			None => None,
+		};
+		let mut added = Vec::new();
+		let mut removed = HashSet::new();
+		let mut rejected = Vec::new();
+		for transaction in transactions {
+			let id = transaction.id();
+			match self.try_add(transaction.clone()) {
+				Ok(replaced) => {
+					removed.extend(replaced);
+					added.push(transaction);
+				}
+				Err(error) => {
+					tracing::debug!("Mempool: rejected transaction {id}: {error}");
+					rejected.push((id, error));
+				}
+			}
+		}
+		if let Some(store) = store {
+			// a transaction of the batch may have been evicted by the next ones
+			added.retain(|transaction| !removed.contains(&transaction.id()));
+			store.remove_mempool_transactions(removed.into_iter().collect()).await?;
+			store.add_mempool_transactions(added).await?;
+		}
+		Ok(rejected)
+	}
+
+	/// Pops the next transactions and removes them from the store.
+	async fn pop(&self, n: usize) -> Result<Vec<MempoolTransaction>, anyhow::Error> {
+		let store = match &self.store {
+			Some(store) => Some(store.lock().await),
+			None => None,
+		};
+		let transactions: Vec<_> = {
+			let mut state = self.lock_state();
+			std::iter::from_fn(|| state.pop()).take(n).collect()
+		};
+		if let Some(store) = store {
+			store
+				.remove_mempool_transactions(
+					transactions.iter().map(MempoolTransaction::id).collect(),
+				)
+				.await?;
+		}
+		Ok(transactions)
+	}
+}
+
+impl<P: Prioritizer> MempoolTransactionOperations for PriorityMempool<P> {
+	/// Admits the transactions one by one. The rejected ones are returned in a
+	/// [RejectedTransactions] error, after the others were admitted.
+	async fn add_mempool_transactions(
+		&self,
+		transactions: Vec<MempoolTransaction>,
+	) -> Result<(), anyhow::Error> {
+		let rejected = self.add(transactions).await?;
+		if rejected.is_empty() {
+			Ok(())
+		} else {
+			Err(RejectedTransactions(rejected).into())
+		}
+	}
+
+	async fn has_mempool_transaction(&self, transaction_id: Id) -> Result<bool, anyhow::Error> {
+		Ok(self.lock_state().ids.contains_key(&transaction_id))
+	}
+
+	async fn add_mempool_transaction(&self, tx: MempoolTransaction) -> Result<(), anyhow::Error> {
+		match self.add(vec![tx]).await?.pop() {
+			Some((_, error)) => Err(error.into()),
+			None => Ok(()),
+		}
+	}
+
+	async fn remove_mempool_transaction(&self, transaction_id: Id) -> Result<(), anyhow::Error> {
+		let store = match &self.store {
+			Some(store) => Some(store.lock().await),
+			None => None,
+		};
+		{
+			let mut state = self.lock_state();
+			if let Some((sender, sequence_number)) = state.ids.get(&transaction_id).cloned() {
+				state.remove(&sender, sequence_number);
+			}
+		}
+		if let Some(store) = store {
+			store.remove_mempool_transaction(transaction_id).await?;
+		}
+		Ok(())
+	}
+
+	async fn pop_mempool_transaction(&self) -> Result<Option<MempoolTransaction>, anyhow::Error> {
+		Ok(self.pop(1).await?.pop())
+	}
+
+	async fn get_mempool_transaction(
+		&self,
+		transaction_id: Id,
+	) -> Result<Option<MempoolTransaction>, anyhow::Error> {
+		let state = self.lock_state();
+		let transaction = state.ids.get(&transaction_id).and_then(|(sender, sequence_number)| {
+			let entry = state.accounts.get(sender)?.get(sequence_number)?;
+			Some(entry.transaction.clone())
+		});
+		Ok(transaction)
+	}
+
+	async fn pop_mempool_transactions(
+		&self,
+		n: usize,
+	) -> Result<Vec<MempoolTransaction>, anyhow::Error> {
+		self.pop(n).await
+	}
+}
+
+impl<P: Prioritizer> MempoolBlockOperations for PriorityMempool<P> {
+	async fn has_block(&self, block_id: Id) -> Result<bool, anyhow::Error> {
+		Ok(self.lock_state().blocks.contains_key(&block_id))
+	}
+
+	async fn add_block(&self, block: Block) -> Result<(), anyhow::Error> {
+		self.lock_state().blocks.insert(block.id(), block);
+		Ok(())
+	}
+
+	async fn remove_block(&self, block_id: Id) -> Result<(), anyhow::Error> {
+		self.lock_state().blocks.remove(&block_id);
+		Ok(())
+	}
+
+	async fn get_block(&self, block_id: Id) -> Result<Option<Block>, anyhow::Error> {
+		Ok(self.lock_state().blocks.get(&block_id).cloned())
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	/// Reads the sender and the gas unit price from the first two bytes of the data.
+	struct TestPrioritizer;
+
+	impl Prioritizer for TestPrioritizer {
+		fn prioritize(&self, transaction: &Transaction) -> Priority {
+			Priority {
+				sender: vec![transaction.data[0]],
+				gas_unit_price: transaction.data[1] as u64,
+			}
+		}
+	}
+
+	fn transaction(sender: u8, sequence_number: u64, gas_unit_price: u8) -> MempoolTransaction {
+		sized_transaction(sender, sequence_number, gas_unit_price, 2)
+	}
+
+	fn sized_transaction(
+		sender: u8,
+		sequence_number: u64,
+		gas_unit_price: u8,
+		bytes: usize,
+	) -> MempoolTransaction {
+		let mut data = vec![sender, gas_unit_price];
+		data.resize(bytes, 0);
+		MempoolTransaction::at_time(Transaction::new(data, sequence_number), 0)
+	}
+
+	fn mempool(max_transactions: usize) -> PriorityMempool<TestPrioritizer> {
+		let limits =
+			MempoolLimits { max_transactions, max_bytes: 1024, max_transactions_per_account: 3 };
+		PriorityMempool::new(limits, TestPrioritizer)
+	}
+
+	#[tokio::test]
+	async fn test_fee_ordering_within_account_sequence() -> Result<(), anyhow::Error> {
+		let mempool = mempool(10);
+		let tx_a0 = transaction(1, 0, 10);
+		let tx_a1 = transaction(1, 1, 50);
+		let tx_b0 = transaction(2, 0, 20);
+		let tx_c0 = transaction(3, 0, 20);
+		mempool
+			.add_mempool_transactions(vec![
+				tx_a1.clone(),
+				tx_a0.clone(),
+				tx_b0.clone(),
+				tx_c0.clone(),
+			])
+			.await?;
+
+		// The fee of a1 doesn't let it jump ahead of a0, and b0 came before c0.
+		let transactions = mempool.pop_mempool_transactions(4).await?;
+		assert_eq!(transactions, [tx_b0, tx_c0, tx_a0, tx_a1]);
+		assert!(mempool.pop_mempool_transaction().await?.is_none());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_eviction_of_lowest_fees() -> Result<(), anyhow::Error> {
+		let mempool = mempool(3);
+		let tx_a0 = transaction(1, 0, 10);
+		let tx_a1 = transaction(1, 1, 5);
+		let tx_b0 = transaction(2, 0, 20);
+		for tx in [&tx_a0, &tx_a1, &tx_b0] {
+			mempool.add_mempool_transaction(tx.clone()).await?;
+		}
+
+		// A higher fee evicts the transaction with the lowest fee.
+		let tx_c0 = transaction(3, 0, 30);
+		mempool.add_mempool_transaction(tx_c0.clone()).await?;
+		assert!(!mempool.has_mempool_transaction(tx_a1.id()).await?);
+
+		// A lower fee than all the transactions is rejected.
+		let tx_d0 = transaction(4, 0, 1);
+		assert_eq!(mempool.try_add(tx_d0.clone()), Err(MempoolError::Full));
+		assert!(mempool.add_mempool_transaction(tx_d0).await.is_err());
+
+		assert_eq!(
+			mempool.metrics(),
+			MempoolMetrics {
+				transactions: 3,
+				bytes: 6,
+				admitted: 4,
+				replaced: 0,
+				rejected: 2,
+				evicted: 1,
+				popped: 0,
+			}
+		);
+		assert_eq!(mempool.pop_mempool_transactions(3).await?, [tx_c0, tx_b0, tx_a0]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_account_limit_and_replacement() -> Result<(), anyhow::Error> {
+		let mempool = mempool(10);
+		for sequence_number in 0..3 {
+			mempool.try_add(transaction(1, sequence_number, 10))?;
+		}
+		assert_eq!(mempool.try_add(transaction(1, 3, 10)), Err(MempoolError::AccountFull(3)));
+
+		// The same sequence number replaces the transaction only with a higher fee.
+		assert_eq!(mempool.try_add(transaction(1, 1, 9)), Err(MempoolError::Underpriced));
+		let replacement = transaction(1, 1, 11);
+		mempool.try_add(replacement.clone())?;
+		assert_eq!(mempool.metrics().replaced, 1);
+		assert_eq!(mempool.metrics().transactions, 3);
+
+		let transactions = mempool.pop_mempool_transactions(3).await?;
+		assert_eq!(transactions[1], replacement);
+		assert!(!mempool.has_mempool_transaction(replacement.id()).await?);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_rejected_replacement_keeps_the_mempool() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().to_str().ok_or(anyhow::anyhow!("Invalid path"))?;
+		let limits =
+			MempoolLimits { max_transactions: 10, max_bytes: 10, max_transactions_per_account: 3 };
+		let tx_a0 = sized_transaction(1, 0, 5, 4);
+		let tx_b0 = sized_transaction(2, 0, 20, 4);
+		let tx_c0 = sized_transaction(3, 0, 2, 2);
+		let replacement = sized_transaction(1, 0, 6, 7);
+		{
+			let mempool = PriorityMempool::try_persistent(path, limits, TestPrioritizer).await?;
+			mempool
+				.add_mempool_transactions(vec![tx_a0.clone(), tx_b0.clone(), tx_c0.clone()])
+				.await?;
+
+			// The replacement only fits by evicting c0 and b0, whose fee is higher.
+			assert_eq!(mempool.try_add(replacement.clone()), Err(MempoolError::Full));
+			assert!(mempool.add_mempool_transaction(replacement.clone()).await.is_err());
+			for tx in [&tx_a0, &tx_b0, &tx_c0] {
+				assert!(mempool.has_mempool_transaction(tx.id()).await?);
+			}
+			assert_eq!(
+				mempool.metrics(),
+				MempoolMetrics {
+					transactions: 3,
+					bytes: 10,
+					admitted: 3,
+					replaced: 0,
+					rejected: 2,
+					evicted: 0,
+					popped: 0,
+				}
+			);
+		}
+
+		// The store holds the same transactions.
+		let store = RocksdbMempool::try_new(path)?;
+		let stored: HashSet<_> =
+			store.mempool_transactions().await?.iter().map(MempoolTransaction::id).collect();
+		assert_eq!(stored, HashSet::from([tx_a0.id(), tx_b0.id(), tx_c0.id()]));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_batch_returns_rejections() -> Result<(), anyhow::Error> {
+		let mempool = mempool(2);
+		let tx_a0 = transaction(1, 0, 10);
+		let tx_b0 = transaction(2, 0, 20);
+		let tx_c0 = transaction(3, 0, 5);
+		let error = mempool
+			.add_mempool_transactions(vec![tx_a0.clone(), tx_b0.clone(), tx_c0.clone()])
+			.await
+			.unwrap_err();
+
+		// The rejected transaction is reported, the others are admitted.
+		assert_eq!(
+			error.downcast_ref::<RejectedTransactions>(),
+			Some(&RejectedTransactions(vec![(tx_c0.id(), MempoolError::Full)]))
+		);
+		assert_eq!(mempool.pop_mempool_transactions(3).await?, [tx_b0, tx_a0]);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_persistent_mempool_reloads() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().to_str().ok_or(anyhow::anyhow!("Invalid path"))?;
+		let limits = |max_transactions| MempoolLimits {
+			max_transactions,
+			max_bytes: 1024,
+			max_transactions_per_account: 3,
+		};
+		let tx_a0 = transaction(1, 0, 10);
+		let tx_a1 = transaction(1, 1, 10);
+		let tx_b0 = transaction(2, 0, 20);
+		let tx_c0 = transaction(3, 0, 30);
+		let tx_d0 = transaction(4, 0, 5);
+		{
+			let mempool = PriorityMempool::try_persistent(path, limits(3), TestPrioritizer).await?;
+			mempool
+				.add_mempool_transactions(vec![tx_d0.clone(), tx_a0.clone(), tx_a1.clone()])
+				.await?;
+			// c0 evicts d0, b0 evicts a1, then c0 is popped
+			mempool.add_mempool_transaction(tx_c0.clone()).await?;
+			mempool.add_mempool_transaction(tx_b0.clone()).await?;
+			assert!(!mempool.has_mempool_transaction(tx_a1.id()).await?);
+			assert_eq!(mempool.pop_mempool_transactions(1).await?, [tx_c0]);
+		}
+
+		// The transactions left are reloaded, over the lowered limits the lowest fees are dropped.
+		let mempool = PriorityMempool::try_persistent(path, limits(1), TestPrioritizer).await?;
+		assert_eq!(mempool.metrics().transactions, 1);
+		assert!(mempool.has_mempool_transaction(tx_b0.id()).await?);
+		drop(mempool);
+		let mempool = PriorityMempool::try_persistent(path, limits(3), TestPrioritizer).await?;
+		assert_eq!(mempool.pop_mempool_transactions(3).await?, [tx_b0]);
+		Ok(())
+	}
+}
@@ -1,6 +1,9 @@
 use mempool_util::{MempoolBlockOperations, MempoolTransactionOperations};
 pub use move_rocks::RocksdbMempool;
 pub use movement_types::{Block, Id, Transaction};
+pub use priority_mempool::{
+	AptosPrioritizer, MempoolLimits, MempoolMetrics, PriorityMempool, RejectedTransactions,
+};
 pub use sequencing_util::Sequencer;
 use std::{path::PathBuf, sync::Arc};
 use tokio::sync::RwLock;
@@ -38,6 +41,10 @@ impl<T: MempoolBlockOperations + MempoolTransactionOperations> Memseq<T> {
 	pub fn building_time_ms(&self) -> u64 {
 		self.building_time_ms
 	}
+
+	pub fn mempool(&self) -> &T {
+		&self.mempool
+	}
 }
 
 impl Memseq<RocksdbMempool> {
@@ -58,6 +65,34 @@ impl Memseq<RocksdbMempool> {
 	}
 }
 
+impl Memseq<PriorityMempool> {
+	/// Creates the sequencer over an in memory mempool bounded by the limits, which builds the
+	/// blocks from the transactions paying the highest gas unit price.
+	pub fn priority(limits: MempoolLimits, block_size: u32, building_time_ms: u64) -> Self {
+		let mempool = PriorityMempool::new(limits, AptosPrioritizer);
+		let parent_block = Arc::new(RwLock::new(Id::default()));
+		Self::new(mempool, block_size, parent_block, building_time_ms)
+	}
+
+	/// Creates the sequencer over a priority mempool persisted at the path, which keeps the
+	/// transactions not sequenced yet when the node restarts.
+	pub async fn try_priority(
+		path: PathBuf,
+		limits: MempoolLimits,
+		block_size: u32,
+		building_time_ms: u64,
+	) -> Result<Self, anyhow::Error> {
+		let mempool = PriorityMempool::try_persistent(
+			path.to_str().ok_or(anyhow::anyhow!("PathBuf to str failed"))?,
+			limits,
+			AptosPrioritizer,
+		)
+		.await?;
+		let parent_block = Arc::new(RwLock::new(Id::default()));
+		Ok(Self::new(mempool, block_size, parent_block, building_time_ms))
+	}
+}
+
 impl<T: MempoolBlockOperations + MempoolTransactionOperations> Sequencer for Memseq<T> {
 	async fn publish_many(&self, transactions: Vec<Transaction>) -> Result<(), anyhow::Error> {
 		self.mempool.add_transactions(transactions).await?;
@@ -312,6 +347,26 @@ pub mod test {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_priority_memseq_bounds_mempool() -> Result<(), anyhow::Error> {
+		let limits = MempoolLimits { max_transactions: 2, ..Default::default() };
+		let memseq = Memseq::priority(limits, 128, 250);
+
+		let transactions: Vec<Transaction> =
+			(0..3).map(|i| Transaction::new(vec![i], 0)).collect();
+		let error = memseq.publish_many(transactions.clone()).await.unwrap_err();
+		let rejected = error.downcast_ref::<RejectedTransactions>();
+		assert_eq!(rejected.map(|rejected| rejected.0.len()), Some(1));
+		assert_eq!(memseq.mempool().metrics().rejected, 1);
+
+		let block = memseq.wait_for_next_block().await?;
+		let block = block.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(block.transactions.len(), 2);
+		assert_eq!(memseq.mempool().metrics().transactions, 0);
+
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_respects_size() -> Result<(), anyhow::Error> {
 		let dir = tempdir()?;
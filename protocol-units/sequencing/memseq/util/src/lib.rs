@@ -21,12 +21,40 @@ pub struct Config {
 	/// The memseq max block size
 	#[serde(default = "default_memseq_max_block_size")]
 	pub memseq_max_block_size: u32,
+
+	/// The max number of transactions in the mempool
+	#[serde(default = "default_memseq_max_mempool_transactions")]
+	pub memseq_max_mempool_transactions: usize,
+
+	/// The max bytes of the transactions in the mempool
+	#[serde(default = "default_memseq_max_mempool_bytes")]
+	pub memseq_max_mempool_bytes: usize,
+
+	/// The max number of transactions of an account in the mempool
+	#[serde(default = "default_memseq_max_account_transactions")]
+	pub memseq_max_account_transactions: usize,
 }
 
 env_default!(default_memseq_build_time, "MEMSEQ_BUILD_TIME", u64, 1000);
 
 env_default!(default_memseq_max_block_size, "MEMSEQ_MAX_BLOCK_SIZE", u32, 2048);
 
+env_default!(
+	default_memseq_max_mempool_transactions,
+	"MEMSEQ_MAX_MEMPOOL_TRANSACTIONS",
+	usize,
+	100_000
+);
+
+env_default!(default_memseq_max_mempool_bytes, "MEMSEQ_MAX_MEMPOOL_BYTES", usize, 268_435_456);
+
+env_default!(
+	default_memseq_max_account_transactions,
+	"MEMSEQ_MAX_ACCOUNT_TRANSACTIONS",
+	usize,
+	100
+);
+
 impl Default for Config {
 	fn default() -> Self {
 		Config {
@@ -34,6 +62,9 @@ impl Default for Config {
 			sequencer_database_path: Config::default_sequencer_database_path(),
 			memseq_build_time: default_memseq_build_time(),
 			memseq_max_block_size: default_memseq_max_block_size(),
+			memseq_max_mempool_transactions: default_memseq_max_mempool_transactions(),
+			memseq_max_mempool_bytes: default_memseq_max_mempool_bytes(),
+			memseq_max_account_transactions: default_memseq_max_account_transactions(),
 		}
 	}
 }
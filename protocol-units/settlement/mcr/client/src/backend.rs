@@ -0,0 +1,82 @@
+use crate::{
+	eth_client, mock, CommitmentStream, McrSettlementClientOperations, PostStatus,
+	SettlementEventStream,
+};
+use mcr_settlement_config::common::settlement::Backend;
+use mcr_settlement_config::Config;
+use movement_types::BlockCommitment;
+
+/// Settlement client of the backend selected at runtime.
+pub type McrSettlementClientBox = Box<dyn McrSettlementClientOperations + Send + Sync>;
+
+/// Builds the settlement client of the backend selected by the configuration.
+pub async fn build_with_config(config: Config) -> Result<McrSettlementClientBox, anyhow::Error> {
+	Ok(match config.settle.backend {
+		Backend::Eth => Box::new(eth_client::Client::build_with_config(config).await?),
+		Backend::Mock => Box::new(mock::McrSettlementClient::build_with_config(config).await?),
+	})
+}
+
+#[async_trait::async_trait]
+impl<C> McrSettlementClientOperations for Box<C>
+where
+	C: McrSettlementClientOperations + Send + Sync + ?Sized,
+{
+	async fn post_block_commitment(
+		&self,
+		block_commitment: BlockCommitment,
+	) -> Result<PostStatus, anyhow::Error> {
+		(**self).post_block_commitment(block_commitment).await
+	}
+
+	async fn post_block_commitment_batch(
+		&self,
+		block_commitment: Vec<BlockCommitment>,
+	) -> Result<PostStatus, anyhow::Error> {
+		(**self).post_block_commitment_batch(block_commitment).await
+	}
+
+	async fn stream_block_commitments(&self) -> Result<CommitmentStream, anyhow::Error> {
+		(**self).stream_block_commitments().await
+	}
+
+	async fn stream_settlement_events(&self) -> Result<SettlementEventStream, anyhow::Error> {
+		(**self).stream_settlement_events().await
+	}
+
+	async fn get_commitment_at_height(
+		&self,
+		height: u64,
+	) -> Result<Option<BlockCommitment>, anyhow::Error> {
+		(**self).get_commitment_at_height(height).await
+	}
+
+	async fn get_max_tolerable_block_height(&self) -> Result<u64, anyhow::Error> {
+		(**self).get_max_tolerable_block_height().await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use movement_types::Commitment;
+	use tokio_stream::StreamExt;
+
+	#[tokio::test]
+	async fn test_build_mock_backend() -> Result<(), anyhow::Error> {
+		let mut config = Config::default();
+		config.settle.backend = Backend::Mock;
+		let client = build_with_config(config).await?;
+
+		let commitment = BlockCommitment {
+			height: 1,
+			block_id: Default::default(),
+			commitment: Commitment::test(),
+		};
+		client.post_block_commitment(commitment.clone()).await?;
+		let mut stream = client.stream_block_commitments().await?;
+		assert_eq!(stream.next().await.expect("stream has ended")?, commitment);
+		assert_eq!(client.get_commitment_at_height(1).await?, Some(commitment));
+		Ok(())
+	}
+}
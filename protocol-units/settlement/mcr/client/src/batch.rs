@@ -0,0 +1,63 @@
+use std::future::Future;
+
+/// Returns the length of the longest prefix of `items`, found by halving, whose gas as estimated
+/// by `estimate_gas` is at most `max_gas`.
+///
+/// A single item is never split, even over `max_gas`: the transaction posting it fails on its own.
+/// The estimates are made against the current state of the contract, so the next chunk is sized
+/// once the previous one is posted.
+pub async fn fitting_chunk_len<T, F, Fut>(
+	items: &[T],
+	max_gas: u128,
+	mut estimate_gas: F,
+) -> Result<usize, anyhow::Error>
+where
+	F: FnMut(&[T]) -> Fut,
+	Fut: Future<Output = Result<u128, anyhow::Error>>,
+{
+	let mut len = items.len();
+	while len > 1 && estimate_gas(&items[..len]).await? > max_gas {
+		len = len.div_ceil(2);
+	}
+	Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn estimate(items: &[u64]) -> impl Future<Output = Result<u128, anyhow::Error>> {
+		// Base cost of the transaction plus the cost of each commitment.
+		let gas = 21_000 + 50_000 * items.len() as u128;
+		async move { Ok(gas) }
+	}
+
+	#[tokio::test]
+	async fn test_batch_under_max_gas_is_not_split() -> Result<(), anyhow::Error> {
+		let items: Vec<u64> = (0..10).collect();
+		assert_eq!(fitting_chunk_len(&items, 1_000_000, estimate).await?, 10);
+		assert_eq!(fitting_chunk_len(&items[..0], 1_000_000, estimate).await?, 0);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_batch_over_max_gas_is_halved() -> Result<(), anyhow::Error> {
+		let items: Vec<u64> = (0..10).collect();
+		// 5 items cost 271_000 gas.
+		assert_eq!(fitting_chunk_len(&items, 300_000, estimate).await?, 5);
+		// 3 items cost 171_000 gas.
+		assert_eq!(fitting_chunk_len(&items, 200_000, estimate).await?, 3);
+		// A single item is posted even over the limit.
+		assert_eq!(fitting_chunk_len(&items, 10_000, estimate).await?, 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_estimate_error_is_returned() {
+		let items: Vec<u64> = (0..10).collect();
+		let result =
+			fitting_chunk_len(&items, 300_000, |_| async { Err(anyhow::anyhow!("reverted")) })
+				.await;
+		assert!(result.is_err());
+	}
+}
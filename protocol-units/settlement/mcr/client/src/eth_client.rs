@@ -1,8 +1,13 @@
+use crate::queue::CommitmentQueue;
 use crate::send_eth_transaction::InsufficentFunds;
 use crate::send_eth_transaction::SendTransactionErrorRule;
 use crate::send_eth_transaction::UnderPriced;
 use crate::send_eth_transaction::VerifyRule;
-use crate::{CommitmentStream, McrSettlementClientOperations};
+use crate::send_eth_transaction::{FeeController, GasStrategy, InclusionPolicy, NonceManager};
+use crate::{
+	CommitmentStream, McrSettlementClientOperations, PostStatus, SettlementEvent,
+	SettlementEventStream,
+};
 use alloy::providers::fillers::ChainIdFiller;
 use alloy::providers::fillers::FillProvider;
 use alloy::providers::fillers::GasFiller;
@@ -12,22 +17,26 @@ use alloy::providers::fillers::WalletFiller;
 use alloy::providers::{Provider, ProviderBuilder, RootProvider};
 use alloy::pubsub::PubSubFrontend;
 use alloy::signers::local::PrivateKeySigner;
+use alloy_contract::{CallBuilder, CallDecoder};
 use alloy_network::Ethereum;
 use alloy_network::EthereumWallet;
 use alloy_primitives::Address;
+use alloy_primitives::TxHash;
 use alloy_primitives::U256;
 use alloy_sol_types::sol;
-use alloy_transport::BoxTransport;
+use alloy_transport::{BoxTransport, Transport, TransportError};
 use alloy_transport_ws::WsConnect;
 use anyhow::Context;
 use mcr_settlement_config::Config;
+use movement_retry::RetryPolicy;
 use movement_types::BlockCommitment;
 use movement_types::{Commitment, Id};
 use serde_json::Value as JsonValue;
-use std::array::TryFromSliceError;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 use thiserror::Error;
+use tokio::sync::{broadcast, Mutex};
 use tokio_stream::StreamExt;
 
 #[derive(Error, Debug)]
@@ -46,6 +55,10 @@ pub enum McrEthConnectorError {
 	EventNotificationError(#[from] alloy_sol_types::Error),
 	#[error("MCR Settlement BlockAccepted event notification stream close")]
 	EventNotificationStreamClosed,
+	#[error("MCR Settlement Transaction unknown or already mined: {0}")]
+	UnknownTransaction(String),
+	#[error("MCR Settlement Transaction held back by the fee controller: {0}")]
+	BudgetExceeded(String),
 }
 
 // Note: we prefer using the ABI because the [`sol!`](alloy_sol_types::sol) macro, when used with smart contract code directly, will not handle inheritance.
@@ -72,14 +85,35 @@ sol!(
 	"abis/MOVEToken.json"
 );
 
+/// A commitment posted by attesters at a height, competing with the other commitments posted at
+/// that height for the supermajority of the stake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompetingCommitment {
+	pub commitment: BlockCommitment,
+	pub attesters: Vec<Address>,
+	/// Stake of the attesters behind the commitment.
+	pub stake: U256,
+}
+
 pub struct Client<P> {
 	rpc_provider: P,
 	ws_provider: RootProvider<PubSubFrontend>,
 	pub signer_address: Address,
 	contract_address: Address,
 	send_transaction_error_rules: Vec<Box<dyn VerifyRule>>,
-	gas_limit: u64,
-	send_transaction_retries: u32,
+	gas_strategy: GasStrategy,
+	fee_controller: FeeController,
+	nonce_manager: NonceManager,
+	retry_policy: RetryPolicy,
+	inclusion: InclusionPolicy,
+	max_batch_gas: u128,
+	/// Commitments held back by the fee controller or not posted while the RPC was unavailable,
+	/// posted first on the next post.
+	queued_commitments: Arc<Mutex<CommitmentQueue>>,
+	/// Held by the post sending the queued commitments, the concurrent posts only queue theirs.
+	posting: Mutex<()>,
+	/// Settlement events raised by the client rather than by the contract.
+	local_events: broadcast::Sender<SettlementEvent>,
 }
 
 impl
@@ -99,8 +133,7 @@ impl
 	>
 {
 	pub async fn build_with_config(config: Config) -> Result<Self, anyhow::Error> {
-		let signer_private_key = config.settle.signer_private_key.clone();
-		let signer = signer_private_key.parse::<PrivateKeySigner>()?;
+		let signer = config.settle.signer_private_key.expose().parse::<PrivateKeySigner>()?;
 		let signer_address = signer.address();
 		let contract_address = config.settle.mcr_contract_address.parse()?;
 		let rpc_url = config.eth_rpc_connection_url();
@@ -111,14 +144,24 @@ impl
 			.on_builtin(&rpc_url)
 			.await
 			.context("Failed to create the RPC provider for the MCR settlement client")?;
+		let max_queued_commitments = config.transactions.max_queued_commitments as usize;
+		let commitment_queue = match &config.transactions.commitment_queue_path {
+			Some(path) => CommitmentQueue::open(path, max_queued_commitments)
+				.context("Failed to open the queue of the MCR settlement client")?,
+			None => CommitmentQueue::in_memory(max_queued_commitments),
+		};
 
 		let mut client = Client::build_with_provider(
 			rpc_provider,
 			ws_url,
 			signer_address,
 			contract_address,
-			config.transactions.gas_limit,
-			config.transactions.transaction_send_retries,
+			GasStrategy::from(&config.transactions),
+			FeeController::from(&config.transactions),
+			config.transactions.retry_policy(),
+			InclusionPolicy::from(&config.transactions),
+			config.transactions.max_batch_gas.into(),
+			commitment_queue,
 		)
 		.await?;
 		Ok(client)
@@ -126,13 +169,18 @@ impl
 }
 
 impl<P> Client<P> {
+	#[allow(clippy::too_many_arguments)]
 	async fn build_with_provider<S>(
 		rpc_provider: P,
 		ws_url: S,
 		signer_address: Address,
 		contract_address: Address,
-		gas_limit: u64,
-		send_transaction_retries: u32,
+		gas_strategy: GasStrategy,
+		fee_controller: FeeController,
+		retry_policy: RetryPolicy,
+		inclusion: InclusionPolicy,
+		max_batch_gas: u128,
+		commitment_queue: CommitmentQueue,
 	) -> Result<Self, anyhow::Error>
 	where
 		P: Provider + Clone,
@@ -146,6 +194,7 @@ impl<P> Client<P> {
 		let rule2: Box<dyn VerifyRule> =
 			Box::new(SendTransactionErrorRule::<InsufficentFunds>::new());
 		let send_transaction_error_rules = vec![rule1, rule2];
+		let (local_events, _) = broadcast::channel(16);
 
 		Ok(Client {
 			rpc_provider,
@@ -153,69 +202,238 @@ impl<P> Client<P> {
 			signer_address,
 			contract_address,
 			send_transaction_error_rules,
-			gas_limit,
-			send_transaction_retries,
+			gas_strategy,
+			fee_controller,
+			nonce_manager: NonceManager::new(),
+			retry_policy,
+			inclusion,
+			max_batch_gas,
+			queued_commitments: Arc::new(Mutex::new(commitment_queue)),
+			posting: Mutex::new(()),
+			local_events,
 		})
 	}
 }
 
-#[async_trait::async_trait]
-impl<P> McrSettlementClientOperations for Client<P>
+impl<P> Client<P>
 where
 	P: Provider + Clone,
 {
-	async fn post_block_commitment(
-		&self,
-		block_commitment: BlockCommitment,
-	) -> Result<(), anyhow::Error> {
-		let contract = MCR::new(self.contract_address, &self.rpc_provider);
-
-		let eth_block_commitment = MCR::BlockCommitment {
-			// Currently, to simplify the API, we'll say 0 is uncommitted all other numbers are legitimate heights
-			height: U256::from(block_commitment.height),
-			commitment: alloy_primitives::FixedBytes(block_commitment.commitment.0),
-			blockId: alloy_primitives::FixedBytes(block_commitment.block_id.0),
-		};
+	/// Returns the transactions of the client waiting in the mempool for longer than the stuck
+	/// transaction timeout.
+	pub async fn stuck_transactions(&self) -> Vec<TxHash> {
+		self.nonce_manager.stuck_transactions(self.inclusion.stuck_timeout).await
+	}
 
-		let call_builder = contract.submitBlockCommitment(eth_block_commitment);
+	/// Replaces a stuck transaction by the same one with fees per gas raised by `fee_bump`
+	/// percent.
+	pub async fn speed_up(&self, tx_hash: TxHash, fee_bump: u128) -> Result<TxHash, anyhow::Error> {
+		crate::send_eth_transaction::speed_up(
+			&self.rpc_provider,
+			&self.nonce_manager,
+			&self.gas_strategy,
+			tx_hash,
+			fee_bump,
+		)
+		.await
+	}
 
-		crate::send_eth_transaction::send_transaction(
-			call_builder,
-			&self.send_transaction_error_rules,
-			self.send_transaction_retries,
-			self.gas_limit as u128,
+	/// Cancels the stuck transaction of the signer with `nonce`.
+	pub async fn cancel(&self, nonce: u64) -> Result<TxHash, anyhow::Error> {
+		crate::send_eth_transaction::cancel(
+			&self.rpc_provider,
+			&self.nonce_manager,
+			&self.gas_strategy,
+			self.signer_address,
+			nonce,
+			self.inclusion.fee_bump,
 		)
 		.await
 	}
 
-	async fn post_block_commitment_batch(
+	/// Returns the commitments posted by the attesters at `height`, most staked first.
+	///
+	/// More than one commitment means the attesters disagree on the block at this height.
+	pub async fn get_competing_commitments(
+		&self,
+		height: u64,
+	) -> Result<Vec<CompetingCommitment>, anyhow::Error> {
+		let contract = MCR::new(self.contract_address, &self.ws_provider);
+		competing_commitments(&contract, height).await
+	}
+
+	/// Posts the commitments left in the queue, by a previous run or while the RPC was unavailable.
+	pub async fn replay_queued_commitments(&self) -> Result<PostStatus, anyhow::Error> {
+		self.post_commitments(Vec::new()).await
+	}
+
+	/// Queues the commitments and posts the queue, unless another post is sending it, in which
+	/// case the commitments are left to that post.
+	async fn post_commitments(
 		&self,
 		block_commitments: Vec<BlockCommitment>,
-	) -> Result<(), anyhow::Error> {
-		let contract = MCR::new(self.contract_address, &self.rpc_provider);
+	) -> Result<PostStatus, anyhow::Error> {
+		self.queued_commitments.lock().await.push(block_commitments)?;
+
+		let mut status = None;
+		loop {
+			let Ok(posting) = self.posting.try_lock() else {
+				break;
+			};
+			let posted = self.post_queued_commitments().await;
+			drop(posting);
+			// Commitments queued by a concurrent post which found this one in progress
+			let queued_meanwhile = !self.queued_commitments.lock().await.is_empty();
+			status = Some(posted?);
+			if status != Some(PostStatus::Posted) || !queued_meanwhile {
+				break;
+			}
+		}
+		match status {
+			Some(status) => Ok(status),
+			None => {
+				Ok(PostStatus::Queued { held_back: self.queued_commitments.lock().await.len() })
+			}
+		}
+	}
 
-		let eth_block_commitment: Vec<_> = block_commitments
-			.into_iter()
-			.map(|block_commitment| {
-				Ok(MCR::BlockCommitment {
-					// Currently, to simplify the API, we'll say 0 is uncommitted all other numbers are legitimate heights
-					height: U256::from(block_commitment.height),
-					commitment: alloy_primitives::FixedBytes(block_commitment.commitment.0),
-					blockId: alloy_primitives::FixedBytes(block_commitment.block_id.0),
+	/// Posts the queued commitments in chunks under the gas limit. The queue is not locked while
+	/// a chunk is sent, so the commitments posted meanwhile are queued without waiting.
+	///
+	/// The commitments left stay queued for the next post when the fee controller holds a chunk
+	/// back, and a `BudgetExceeded` settlement event is sent, or when the RPC is unavailable. On
+	/// any other error, only the commitments of the failed chunk are dropped.
+	async fn post_queued_commitments(&self) -> Result<PostStatus, anyhow::Error> {
+		let contract = MCR::new(self.contract_address, &self.rpc_provider);
+		loop {
+			let queued: Vec<BlockCommitment> =
+				self.queued_commitments.lock().await.commitments().cloned().collect();
+			if queued.is_empty() {
+				return Ok(PostStatus::Posted);
+			}
+			let remaining: Vec<_> = queued.iter().map(eth_block_commitment).collect();
+			// Chunks are sized as the previous ones get posted.
+			let chunk_len =
+				crate::batch::fitting_chunk_len(&remaining, self.max_batch_gas, |chunk| {
+					let call_builder = contract.submitBatchBlockCommitment(chunk.to_vec());
+					async move { Ok(call_builder.estimate_gas().await?) }
 				})
-			})
-			.collect::<Result<Vec<_>, TryFromSliceError>>()?;
+				.await
+				.unwrap_or_else(|err| {
+					// The first commitment is posted alone, and dropped if it fails on its own
+					tracing::warn!("Failed to size the commitment batch, posting one: {err}");
+					1
+				});
+			let result = if chunk_len == 1 {
+				let call_builder = contract.submitBlockCommitment(remaining[0].clone());
+				self.send_transaction(call_builder).await
+			} else {
+				if chunk_len < remaining.len() {
+					tracing::info!(
+						"Splitting the commitment batch over the gas limit: posting {chunk_len} of {} commitments",
+						remaining.len()
+					);
+				}
+				let call_builder =
+					contract.submitBatchBlockCommitment(remaining[..chunk_len].to_vec());
+				self.send_transaction(call_builder).await
+			};
 
-		let call_builder = contract.submitBatchBlockCommitment(eth_block_commitment);
+			let chunk = &queued[..chunk_len];
+			match result {
+				Ok(()) => self.queued_commitments.lock().await.remove(chunk)?,
+				Err(err)
+					if matches!(
+						err.downcast_ref::<McrEthConnectorError>(),
+						Some(McrEthConnectorError::BudgetExceeded(_))
+					) =>
+				{
+					let queue = self.queued_commitments.lock().await;
+					tracing::warn!("Holding back {} block commitments: {err}", queue.len());
+					// Sending fails only when there are no subscribers, which is fine.
+					let _ = self.local_events.send(SettlementEvent::BudgetExceeded {
+						height: queue.last_height().unwrap_or_default(),
+						held_back: queue.len(),
+						reason: err.to_string(),
+					});
+					return Ok(PostStatus::Queued { held_back: queue.len() });
+				}
+				Err(err) if is_rpc_unavailable(&err) => {
+					let held_back = self.queued_commitments.lock().await.len();
+					tracing::warn!(
+						"Queuing {held_back} block commitments until the RPC is available: {err}"
+					);
+					return Ok(PostStatus::Queued { held_back });
+				}
+				Err(err) => {
+					tracing::error!("Dropping {chunk_len} block commitments which failed to post");
+					self.queued_commitments.lock().await.remove(chunk)?;
+					return Err(err);
+				}
+			}
+		}
+	}
 
+	async fn send_transaction<D>(
+		&self,
+		call_builder: CallBuilder<BoxTransport, &&P, D, Ethereum>,
+	) -> Result<(), anyhow::Error>
+	where
+		D: CallDecoder + Clone,
+	{
 		crate::send_eth_transaction::send_transaction(
 			call_builder,
 			&self.send_transaction_error_rules,
-			self.send_transaction_retries,
-			self.gas_limit as u128,
+			&self.retry_policy,
+			&self.inclusion,
+			&self.gas_strategy,
+			&self.fee_controller,
+			&self.nonce_manager,
+			self.signer_address,
 		)
 		.await
 	}
+}
+
+/// Whether the error comes from the connection to the RPC rather than from the RPC itself.
+fn is_rpc_unavailable(err: &anyhow::Error) -> bool {
+	let transport_error = match err.downcast_ref::<alloy_contract::Error>() {
+		Some(alloy_contract::Error::TransportError(transport_error)) => transport_error,
+		_ => match err.downcast_ref::<TransportError>() {
+			Some(transport_error) => transport_error,
+			None => return false,
+		},
+	};
+	matches!(transport_error, TransportError::Transport(_))
+}
+
+fn eth_block_commitment(block_commitment: &BlockCommitment) -> MCR::BlockCommitment {
+	MCR::BlockCommitment {
+		// Currently, to simplify the API, we'll say 0 is uncommitted all other numbers are legitimate heights
+		height: U256::from(block_commitment.height),
+		commitment: alloy_primitives::FixedBytes(block_commitment.commitment.0),
+		blockId: alloy_primitives::FixedBytes(block_commitment.block_id.0),
+	}
+}
+
+#[async_trait::async_trait]
+impl<P> McrSettlementClientOperations for Client<P>
+where
+	P: Provider + Clone,
+{
+	async fn post_block_commitment(
+		&self,
+		block_commitment: BlockCommitment,
+	) -> Result<PostStatus, anyhow::Error> {
+		self.post_commitments(vec![block_commitment]).await
+	}
+
+	async fn post_block_commitment_batch(
+		&self,
+		block_commitments: Vec<BlockCommitment>,
+	) -> Result<PostStatus, anyhow::Error> {
+		self.post_commitments(block_commitments).await
+	}
 
 	async fn stream_block_commitments(&self) -> Result<CommitmentStream, anyhow::Error> {
 		// Register to contract BlockCommitmentSubmitted event
@@ -242,6 +460,49 @@ where
 		Ok(Box::pin(stream) as CommitmentStream)
 	}
 
+	async fn stream_settlement_events(&self) -> Result<SettlementEventStream, anyhow::Error> {
+		let contract = MCR::new(self.contract_address, self.ws_provider.clone());
+		let mut accepted_events = contract.BlockAccepted_filter().watch().await?.into_stream();
+		let signer_address = self.signer_address;
+
+		let stream = async_stream::try_stream! {
+			while let Some(event) = accepted_events.next().await {
+				let (event, _) = event.map_err(McrEthConnectorError::EventNotificationError)?;
+				let accepted = BlockCommitment {
+					height: event
+						.height
+						.try_into()
+						.context("Failed to convert the commitment height from U256 to u64")?,
+					block_id: Id(event.blockHash.0),
+					commitment: Commitment(event.stateCommitment.0),
+				};
+
+				// The contract does not emit rejections, compare with what the signer committed to.
+				let MCR::getValidatorCommitmentAtBlockHeightReturn { _0: posted } = contract
+					.getValidatorCommitmentAtBlockHeight(event.height, signer_address)
+					.call()
+					.await?;
+				let posted_height: u64 = posted
+					.height
+					.try_into()
+					.context("Failed to convert the commitment height from U256 to u64")?;
+				// Commitment with height 0 mean the signer did not commit at this height
+				if posted_height != 0 && posted.commitment.0 != accepted.commitment.0 {
+					let posted = BlockCommitment {
+						height: posted_height,
+						block_id: Id(posted.blockId.into()),
+						commitment: Commitment(posted.commitment.into()),
+					};
+					yield SettlementEvent::Rejected { posted, accepted: accepted.clone() };
+				}
+				yield SettlementEvent::Accepted(accepted);
+			}
+			Err::<(), _>(McrEthConnectorError::EventNotificationStreamClosed)?;
+		};
+		let local_events = crate::broadcast_event_stream(self.local_events.subscribe());
+		Ok(Box::pin(stream.merge(local_events)) as SettlementEventStream)
+	}
+
 	async fn get_commitment_at_height(
 		&self,
 		height: u64,
@@ -275,6 +536,54 @@ where
 	}
 }
 
+/// Returns the commitments posted by the attesters of the MCR `contract` at `height`, most staked
+/// first.
+async fn competing_commitments<T, P>(
+	contract: &MCR::MCRInstance<T, P>,
+	height: u64,
+) -> Result<Vec<CompetingCommitment>, anyhow::Error>
+where
+	T: Transport + Clone,
+	P: Provider<T, Ethereum>,
+{
+	let MCR::getAttestersReturn { _0: attesters } = contract.getAttesters().call().await?;
+
+	let mut competing: Vec<CompetingCommitment> = Vec::new();
+	for attester in attesters {
+		let MCR::getValidatorCommitmentAtBlockHeightReturn { _0: commitment } = contract
+			.getValidatorCommitmentAtBlockHeight(U256::from(height), attester)
+			.call()
+			.await?;
+		// Commitment with height 0 mean the attester did not commit at this height
+		if commitment.height.is_zero() {
+			continue;
+		}
+		match competing
+			.iter_mut()
+			.find(|claim| claim.commitment.commitment.0 == commitment.commitment.0)
+		{
+			Some(claim) => claim.attesters.push(attester),
+			None => {
+				let MCR::commitmentStakesReturn { stake } = contract
+					.commitmentStakes(U256::from(height), commitment.commitment)
+					.call()
+					.await?;
+				competing.push(CompetingCommitment {
+					commitment: BlockCommitment {
+						height,
+						block_id: Id(commitment.blockId.into()),
+						commitment: Commitment(commitment.commitment.into()),
+					},
+					attesters: vec![attester],
+					stake,
+				});
+			}
+		}
+	}
+	competing.sort_by(|a, b| b.stake.cmp(&a.stake));
+	Ok(competing)
+}
+
 pub struct AnvilAddressEntry {
 	pub address: String,
 	pub private_key: String,
@@ -313,3 +622,73 @@ pub fn read_anvil_json_file_addresses<P: AsRef<Path>>(
 		.collect::<Vec<_>>();
 	Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::rpc::MockRpc;
+	use alloy_primitives::{hex, FixedBytes};
+	use alloy_sol_types::SolCall;
+
+	fn commitment(height: u64, commitment: u8) -> MCR::BlockCommitment {
+		MCR::BlockCommitment {
+			height: U256::from(height),
+			commitment: FixedBytes([commitment; 32]),
+			blockId: FixedBytes([commitment + 100; 32]),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_competing_commitments_are_grouped_and_most_staked_first(
+	) -> Result<(), anyhow::Error> {
+		let attesters = [1, 2, 3, 4].map(Address::repeat_byte);
+		let rpc = MockRpc::new().on("eth_call", move |params| {
+			let call = &params[0];
+			let input = call.get("input").or_else(|| call.get("data")).unwrap().as_str().unwrap();
+			let input = hex::decode(input).unwrap();
+			let output = match input[..4].try_into().unwrap() {
+				MCR::getAttestersCall::SELECTOR => {
+					MCR::getAttestersCall::abi_encode_returns(&(attesters.to_vec(),))
+				}
+				MCR::getValidatorCommitmentAtBlockHeightCall::SELECTOR => {
+					let call =
+						MCR::getValidatorCommitmentAtBlockHeightCall::abi_decode(&input, true)
+							.unwrap();
+					assert_eq!(call.height, U256::from(7));
+					// The fourth attester did not commit at this height.
+					let posted = match call.attester.0[0] {
+						1 | 3 => commitment(7, 1),
+						2 => commitment(7, 2),
+						_ => commitment(0, 0),
+					};
+					MCR::getValidatorCommitmentAtBlockHeightCall::abi_encode_returns(&(posted,))
+				}
+				MCR::commitmentStakesCall::SELECTOR => {
+					let call = MCR::commitmentStakesCall::abi_decode(&input, true).unwrap();
+					let stake = if call.commitement == FixedBytes([1; 32]) { 50 } else { 80 };
+					MCR::commitmentStakesCall::abi_encode_returns(&(U256::from(stake),))
+				}
+				selector => panic!("unexpected call {selector:?}"),
+			};
+			JsonValue::String(hex::encode_prefixed(output))
+		});
+		let provider = rpc.provider();
+		let contract = MCR::new(Address::repeat_byte(9), &provider);
+
+		let competing = competing_commitments(&contract, 7).await?;
+
+		assert_eq!(competing.len(), 2);
+		assert_eq!(competing[0].commitment.height, 7);
+		assert_eq!(competing[0].commitment.commitment, Commitment([2; 32]));
+		assert_eq!(competing[0].commitment.block_id, Id([102; 32]));
+		assert_eq!(competing[0].attesters, vec![attesters[1]]);
+		assert_eq!(competing[0].stake, U256::from(80));
+		assert_eq!(competing[1].commitment.commitment, Commitment([1; 32]));
+		assert_eq!(competing[1].attesters, vec![attesters[0], attesters[2]]);
+		assert_eq!(competing[1].stake, U256::from(50));
+		// The stake is read once per distinct commitment.
+		let stake_calls = rpc.requests("eth_call").len() - 1 - attesters.len();
+		assert_eq!(stake_calls, 2);
+		Ok(())
+	}
+}
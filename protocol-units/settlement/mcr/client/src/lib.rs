@@ -14,28 +14,105 @@ pub mod eth_client;
 #[cfg(feature = "eth")]
 pub use eth_client::Client as McrEthSettlementClient;
 
-mod send_eth_transaction;
+pub mod send_eth_transaction;
+
+pub mod batch;
+
+pub mod backend;
+
+pub mod queue;
+
+pub use backend::McrSettlementClientBox;
 
 type CommitmentStream =
 	std::pin::Pin<Box<dyn Stream<Item = Result<BlockCommitment, anyhow::Error>> + Send>>;
 
+/// Settlement outcome of a block commitment observed on the MCR contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettlementEvent {
+	/// The commitment was accepted by a supermajority of the attesters at its height.
+	Accepted(BlockCommitment),
+	/// The commitment posted by this client lost to the commitment accepted at its height.
+	Rejected { posted: BlockCommitment, accepted: BlockCommitment },
+	/// The fee controller of the client holds back its commitments up to `height` instead of
+	/// posting them at any price. They are posted with the next commitment the budget allows.
+	BudgetExceeded { height: u64, held_back: usize, reason: String },
+}
+
+impl SettlementEvent {
+	pub fn height(&self) -> u64 {
+		match self {
+			Self::Accepted(commitment) => commitment.height,
+			Self::Rejected { accepted, .. } => accepted.height,
+			Self::BudgetExceeded { height, .. } => *height,
+		}
+	}
+}
+
+pub type SettlementEventStream =
+	std::pin::Pin<Box<dyn Stream<Item = Result<SettlementEvent, anyhow::Error>> + Send>>;
+
+/// Streams the settlement events sent on a broadcast channel.
+fn broadcast_event_stream(
+	mut receiver: tokio::sync::broadcast::Receiver<SettlementEvent>,
+) -> impl Stream<Item = Result<SettlementEvent, anyhow::Error>> + Send {
+	async_stream::stream! {
+		loop {
+			match receiver.recv().await {
+				Ok(event) => {
+					yield Ok(event);
+				}
+				Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+					yield Err(anyhow::anyhow!("Settlement events lagged by {skipped}"));
+				}
+				Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+			}
+		}
+	}
+}
+
+/// Outcome of a post of block commitments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostStatus {
+	/// The commitments were posted, after the ones queued before them.
+	Posted,
+	/// The commitments were queued instead, and are posted with the next ones: `held_back`
+	/// commitments wait for the fee budget, for the RPC to be available, or for the post in
+	/// progress to send them.
+	Queued { held_back: usize },
+}
+
+/// Operations of a settlement client, independent of the chain it settles on.
+///
+/// Implemented by the Ethereum client and the in-memory mock, which are selected at runtime
+/// with [`backend::build_with_config`].
 #[async_trait::async_trait]
 pub trait McrSettlementClientOperations {
 	/// Posts a block commitment to the settlement client.
 	async fn post_block_commitment(
 		&self,
 		block_commitment: BlockCommitment,
-	) -> Result<(), anyhow::Error>;
+	) -> Result<PostStatus, anyhow::Error>;
 
 	/// Posts a batch of block commitments to the settlement client.
+	///
+	/// Clients sending transactions split the batch in chunks, posted in order, when it is
+	/// over their gas limit.
 	async fn post_block_commitment_batch(
 		&self,
 		block_commitment: Vec<BlockCommitment>,
-	) -> Result<(), anyhow::Error>;
+	) -> Result<PostStatus, anyhow::Error>;
 
 	/// Streams block commitments from the settlement client.
 	async fn stream_block_commitments(&self) -> Result<CommitmentStream, anyhow::Error>;
 
+	/// Streams the settlement outcomes of the commitments from the settlement client.
+	///
+	/// Every accepted commitment is reported, and a commitment posted by this client
+	/// which differs from the accepted one at its height is reported as rejected.
+	/// Unlike `stream_block_commitments`, this can be called any number of times.
+	async fn stream_settlement_events(&self) -> Result<SettlementEventStream, anyhow::Error>;
+
 	/// Gets the accepted commitment at the given height.
 	async fn get_commitment_at_height(
 		&self,
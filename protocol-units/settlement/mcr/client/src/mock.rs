@@ -1,16 +1,21 @@
-use crate::{CommitmentStream, McrSettlementClientOperations};
+use crate::{
+	CommitmentStream, McrSettlementClientOperations, PostStatus, SettlementEvent,
+	SettlementEventStream,
+};
 use mcr_settlement_config::Config;
 use movement_types::BlockCommitment;
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio_stream::wrappers::ReceiverStream;
 
 #[derive(Clone)]
 pub struct McrSettlementClient {
 	commitments: Arc<RwLock<BTreeMap<u64, BlockCommitment>>>,
+	posted_commitments: Arc<RwLock<BTreeMap<u64, BlockCommitment>>>,
 	stream_sender: mpsc::Sender<Result<BlockCommitment, anyhow::Error>>,
 	stream_receiver: Arc<Mutex<Option<mpsc::Receiver<Result<BlockCommitment, anyhow::Error>>>>>,
+	event_sender: broadcast::Sender<SettlementEvent>,
 	pub current_height: Arc<RwLock<u64>>,
 	pub block_lead_tolerance: u64,
 	paused_at_height: Arc<RwLock<Option<u64>>>,
@@ -19,10 +24,13 @@ pub struct McrSettlementClient {
 impl McrSettlementClient {
 	pub fn new() -> Self {
 		let (stream_sender, receiver) = mpsc::channel(10);
+		let (event_sender, _) = broadcast::channel(64);
 		McrSettlementClient {
 			commitments: Arc::new(RwLock::new(BTreeMap::new())),
+			posted_commitments: Arc::new(RwLock::new(BTreeMap::new())),
 			stream_sender,
 			stream_receiver: Arc::new(Mutex::new(Some(receiver))),
+			event_sender,
 			current_height: Arc::new(RwLock::new(0)),
 			block_lead_tolerance: 16,
 			paused_at_height: Arc::new(RwLock::new(None)),
@@ -63,9 +71,22 @@ impl McrSettlementClient {
 			for (_, commitment) in commitments.range(resume_height + 1..) {
 				println!("resume sends commitment for height {}", commitment.height);
 				self.stream_sender.send(Ok(commitment.clone())).await.unwrap();
+				self.send_settlement_events(commitment).await;
 			}
 		}
 	}
+
+	/// Notifies the settlement event subscribers that `settled` was accepted at its height.
+	async fn send_settlement_events(&self, settled: &BlockCommitment) {
+		let posted = self.posted_commitments.read().await.get(&settled.height).cloned();
+		if let Some(posted) = posted.filter(|posted| posted.commitment != settled.commitment) {
+			// Sending fails only when there are no subscribers, which is fine.
+			let _ = self
+				.event_sender
+				.send(SettlementEvent::Rejected { posted, accepted: settled.clone() });
+		}
+		let _ = self.event_sender.send(SettlementEvent::Accepted(settled.clone()));
+	}
 }
 
 #[async_trait::async_trait]
@@ -73,9 +94,14 @@ impl McrSettlementClientOperations for McrSettlementClient {
 	async fn post_block_commitment(
 		&self,
 		block_commitment: BlockCommitment,
-	) -> Result<(), anyhow::Error> {
+	) -> Result<PostStatus, anyhow::Error> {
 		let height = block_commitment.height;
 
+		{
+			let mut posted_commitments = self.posted_commitments.write().await;
+			posted_commitments.insert(height, block_commitment.clone());
+		}
+
 		let settled = {
 			let mut commitments = self.commitments.write().await;
 			commitments.entry(block_commitment.height).or_insert(block_commitment).clone()
@@ -85,6 +111,7 @@ impl McrSettlementClientOperations for McrSettlementClient {
 			match *paused_at_height {
 				Some(ph) if ph < height => {}
 				_ => {
+					self.send_settlement_events(&settled).await;
 					self.stream_sender.send(Ok(settled)).await?;
 				}
 			}
@@ -97,17 +124,17 @@ impl McrSettlementClientOperations for McrSettlementClient {
 			}
 		}
 
-		Ok(())
+		Ok(PostStatus::Posted)
 	}
 
 	async fn post_block_commitment_batch(
 		&self,
 		block_commitment: Vec<BlockCommitment>,
-	) -> Result<(), anyhow::Error> {
+	) -> Result<PostStatus, anyhow::Error> {
 		for commitment in block_commitment {
 			self.post_block_commitment(commitment).await?;
 		}
-		Ok(())
+		Ok(PostStatus::Posted)
 	}
 
 	async fn stream_block_commitments(&self) -> Result<CommitmentStream, anyhow::Error> {
@@ -120,6 +147,10 @@ impl McrSettlementClientOperations for McrSettlementClient {
 		Ok(Box::pin(ReceiverStream::new(receiver)))
 	}
 
+	async fn stream_settlement_events(&self) -> Result<SettlementEventStream, anyhow::Error> {
+		Ok(Box::pin(crate::broadcast_event_stream(self.event_sender.subscribe())))
+	}
+
 	async fn get_commitment_at_height(
 		&self,
 		height: u64,
@@ -220,6 +251,44 @@ pub mod test {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_stream_settlement_events() -> Result<(), anyhow::Error> {
+		let client = McrSettlementClient::new();
+		let mut events = client.stream_settlement_events().await?;
+		let accepted = BlockCommitment {
+			height: 1,
+			block_id: Default::default(),
+			commitment: Commitment::test(),
+		};
+		client.override_block_commitment(accepted.clone()).await;
+		let posted = BlockCommitment {
+			height: 1,
+			block_id: Default::default(),
+			commitment: Commitment([1; 32]),
+		};
+		client.post_block_commitment(posted.clone()).await?;
+		let commitment2 = BlockCommitment {
+			height: 2,
+			block_id: Default::default(),
+			commitment: Commitment([2; 32]),
+		};
+		client.post_block_commitment(commitment2.clone()).await?;
+
+		assert_eq!(
+			events.next().await.expect("stream has ended")?,
+			SettlementEvent::Rejected { posted, accepted: accepted.clone() }
+		);
+		assert_eq!(
+			events.next().await.expect("stream has ended")?,
+			SettlementEvent::Accepted(accepted)
+		);
+		assert_eq!(
+			events.next().await.expect("stream has ended")?,
+			SettlementEvent::Accepted(commitment2)
+		);
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_pause() -> Result<(), anyhow::Error> {
 		let client = McrSettlementClient::new();
@@ -0,0 +1,186 @@
+use movement_types::BlockCommitment;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CommitmentQueueError {
+	#[error("Commitment queue is full: {0} commitments are waiting to be posted")]
+	Full(usize),
+	#[error("Commitment queue storage error: {0}")]
+	Storage(String),
+}
+
+/// Block commitments waiting to be posted, in height order.
+///
+/// With a path, the queue is written to disk on every change, so the commitments held back or
+/// not posted while the RPC is unavailable survive a restart and are replayed on the next post.
+#[derive(Debug)]
+pub struct CommitmentQueue {
+	commitments: BTreeMap<u64, BlockCommitment>,
+	path: Option<PathBuf>,
+	max_depth: usize,
+}
+
+impl CommitmentQueue {
+	pub fn in_memory(max_depth: usize) -> Self {
+		CommitmentQueue { commitments: BTreeMap::new(), path: None, max_depth }
+	}
+
+	/// Opens the queue stored at `path`, with the commitments left by the previous run.
+	pub fn open(path: impl Into<PathBuf>, max_depth: usize) -> Result<Self, CommitmentQueueError> {
+		let path = path.into();
+		let commitments = match fs::read(&path) {
+			Ok(bytes) => serde_json::from_slice::<Vec<BlockCommitment>>(&bytes)
+				.map_err(|e| CommitmentQueueError::Storage(e.to_string()))?
+				.into_iter()
+				.map(|commitment| (commitment.height, commitment))
+				.collect(),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+			Err(e) => return Err(CommitmentQueueError::Storage(e.to_string())),
+		};
+		if !commitments.is_empty() {
+			tracing::info!("Replaying {} queued block commitments", commitments.len());
+		}
+		Ok(CommitmentQueue { commitments, path: Some(path), max_depth })
+	}
+
+	pub fn len(&self) -> usize {
+		self.commitments.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.commitments.is_empty()
+	}
+
+	/// Height of the last commitment of the queue.
+	pub fn last_height(&self) -> Option<u64> {
+		self.commitments.keys().next_back().copied()
+	}
+
+	pub fn commitments(&self) -> impl Iterator<Item = &BlockCommitment> {
+		self.commitments.values()
+	}
+
+	/// Queues the commitments, replacing any queued at the same height.
+	///
+	/// Fails without queuing any of them if the queue would grow past its max depth.
+	pub fn push(
+		&mut self,
+		commitments: impl IntoIterator<Item = BlockCommitment>,
+	) -> Result<(), CommitmentQueueError> {
+		let mut queued = self.commitments.clone();
+		queued.extend(commitments.into_iter().map(|commitment| (commitment.height, commitment)));
+		if queued.len() > self.max_depth {
+			return Err(CommitmentQueueError::Full(self.commitments.len()));
+		}
+		self.commitments = queued;
+		self.persist()
+	}
+
+	/// Removes the first `count` commitments, once posted.
+	pub fn pop_front(&mut self, count: usize) -> Result<(), CommitmentQueueError> {
+		for _ in 0..count {
+			self.commitments.pop_first();
+		}
+		self.persist()
+	}
+
+	/// Removes the commitments, once posted or dropped, unless they were replaced in the queue
+	/// since.
+	pub fn remove<'a>(
+		&mut self,
+		commitments: impl IntoIterator<Item = &'a BlockCommitment>,
+	) -> Result<(), CommitmentQueueError> {
+		for commitment in commitments {
+			if self.commitments.get(&commitment.height) == Some(commitment) {
+				self.commitments.remove(&commitment.height);
+			}
+		}
+		self.persist()
+	}
+
+	pub fn clear(&mut self) -> Result<(), CommitmentQueueError> {
+		self.commitments.clear();
+		self.persist()
+	}
+
+	fn persist(&self) -> Result<(), CommitmentQueueError> {
+		let Some(path) = &self.path else {
+			return Ok(());
+		};
+		let bytes = serde_json::to_vec(&self.commitments.values().collect::<Vec<_>>())
+			.map_err(|e| CommitmentQueueError::Storage(e.to_string()))?;
+		// Write then rename, so a crash never leaves a truncated queue.
+		let tmp_path = path.with_extension("tmp");
+		fs::write(&tmp_path, bytes)
+			.and_then(|()| fs::rename(&tmp_path, path))
+			.map_err(|e| CommitmentQueueError::Storage(e.to_string()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use movement_types::Commitment;
+
+	fn commitment(height: u64) -> BlockCommitment {
+		BlockCommitment {
+			height,
+			block_id: Default::default(),
+			commitment: Commitment([height as u8; 32]),
+		}
+	}
+
+	#[test]
+	fn test_queue_survives_reopen() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("commitments.json");
+
+		let mut queue = CommitmentQueue::open(&path, 10)?;
+		queue.push([commitment(3), commitment(1), commitment(2)])?;
+		queue.pop_front(1)?;
+		drop(queue);
+
+		let queue = CommitmentQueue::open(&path, 10)?;
+		assert_eq!(
+			queue.commitments().cloned().collect::<Vec<_>>(),
+			vec![commitment(2), commitment(3)]
+		);
+		assert_eq!(queue.last_height(), Some(3));
+		Ok(())
+	}
+
+	#[test]
+	fn test_full_queue_rejects_commitments() -> Result<(), anyhow::Error> {
+		let mut queue = CommitmentQueue::in_memory(2);
+		queue.push([commitment(1)])?;
+		assert!(matches!(
+			queue.push([commitment(2), commitment(3)]),
+			Err(CommitmentQueueError::Full(1))
+		));
+		assert_eq!(queue.len(), 1);
+		// Replacing a queued commitment does not grow the queue.
+		queue.push([commitment(1), commitment(2)])?;
+		assert_eq!(queue.len(), 2);
+		Ok(())
+	}
+
+	#[test]
+	fn test_remove_keeps_replaced_commitments() -> Result<(), anyhow::Error> {
+		let mut queue = CommitmentQueue::in_memory(10);
+		queue.push([commitment(1), commitment(2), commitment(3)])?;
+		let posted: Vec<_> = queue.commitments().take(2).cloned().collect();
+
+		// The commitment at height 2 is replaced while the first two are posted.
+		let replacement = BlockCommitment { commitment: Commitment([9; 32]), ..commitment(2) };
+		queue.push([replacement.clone()])?;
+		queue.remove(&posted)?;
+		assert_eq!(
+			queue.commitments().cloned().collect::<Vec<_>>(),
+			vec![replacement, commitment(3)]
+		);
+		Ok(())
+	}
+}
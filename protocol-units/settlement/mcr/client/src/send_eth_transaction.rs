@@ -3,14 +3,34 @@ use alloy::providers::Provider;
 use alloy_contract::CallBuilder;
 use alloy_contract::CallDecoder;
 use alloy_network::Ethereum;
+use alloy_network::Network;
+use alloy_primitives::{Address, TxHash, U256};
 use alloy_transport::{Transport, TransportError};
+use mcr_settlement_config::common::transactions::Config as TransactionsConfig;
+use movement_retry::RetryPolicy;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+type TransactionRequest = <Ethereum as Network>::TransactionRequest;
+type TransactionReceipt = <Ethereum as Network>::ReceiptResponse;
+
+/// Gas of a plain transfer, used by the transactions cancelling another one.
+const TRANSFER_GAS: u128 = 21_000;
+
+/// Time between two polls of the receipts of a transaction waiting to be mined.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 // Define a rule to verify the error generated when a transaction is send to determine if:
 // * the Transaction must me resend with more gas: return Ok(true)
 // * a specific error must be return: return Err(McrEthConnectorError::xxx);
 // * the rule doesn't apply: return Ok(false)
+// The name of the rule selects its overrides in the retry policy.
 pub trait VerifyRule: Sync + Send {
+	fn name(&self) -> &'static str;
+
 	fn verify(&self, error: &alloy_contract::Error) -> Result<bool, McrEthConnectorError>;
 }
 
@@ -29,6 +49,10 @@ pub struct UnderPriced;
 pub struct InsufficentFunds;
 
 impl VerifyRule for SendTransactionErrorRule<UnderPriced> {
+	fn name(&self) -> &'static str {
+		"underpriced"
+	}
+
 	fn verify(&self, error: &alloy_contract::Error) -> Result<bool, McrEthConnectorError> {
 		let alloy_contract::Error::TransportError(TransportError::ErrorResp(payload)) = error
 		else {
@@ -44,6 +68,10 @@ impl VerifyRule for SendTransactionErrorRule<UnderPriced> {
 }
 
 impl VerifyRule for SendTransactionErrorRule<InsufficentFunds> {
+	fn name(&self) -> &'static str {
+		"insufficient_funds"
+	}
+
 	fn verify(&self, error: &alloy_contract::Error) -> Result<bool, McrEthConnectorError> {
 		let alloy_contract::Error::TransportError(TransportError::ErrorResp(payload)) = error
 		else {
@@ -58,6 +86,433 @@ impl VerifyRule for SendTransactionErrorRule<InsufficentFunds> {
 	}
 }
 
+/// Gas and EIP-1559 fees of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionFees {
+	pub gas: u128,
+	pub max_fee_per_gas: u128,
+	pub max_priority_fee_per_gas: u128,
+}
+
+impl TransactionFees {
+	/// The maximum fee paid for the transaction, in wei.
+	pub fn transaction_fee(&self) -> u128 {
+		self.gas.saturating_mul(self.max_fee_per_gas)
+	}
+}
+
+/// Prices the transactions: estimates their gas and computes their EIP-1559 fees, within the caps
+/// of the configuration.
+#[derive(Debug, Clone)]
+pub struct GasStrategy {
+	/// Margin added to the estimated gas, in percent, because initial gas estimates are too low.
+	pub gas_estimate_margin: u128,
+	pub max_fee_per_gas_cap: u128,
+	pub max_priority_fee_per_gas_cap: u128,
+	/// Maximum fee of a transaction, in wei.
+	pub transaction_fee_cap: u128,
+}
+
+impl From<&TransactionsConfig> for GasStrategy {
+	fn from(config: &TransactionsConfig) -> Self {
+		GasStrategy {
+			gas_estimate_margin: config.gas_estimate_margin.into(),
+			max_fee_per_gas_cap: config.max_fee_per_gas.into(),
+			max_priority_fee_per_gas_cap: config.max_priority_fee_per_gas.into(),
+			transaction_fee_cap: config.gas_limit.into(),
+		}
+	}
+}
+
+impl GasStrategy {
+	/// Estimates the gas of the call and the fees per gas of the network.
+	pub async fn estimate<P, T, D>(
+		&self,
+		call_builder: &CallBuilder<T, &&P, D, Ethereum>,
+	) -> Result<TransactionFees, anyhow::Error>
+	where
+		P: Provider<T, Ethereum> + Clone,
+		T: Transport + Clone,
+		D: CallDecoder + Clone,
+	{
+		let estimated_gas = call_builder.estimate_gas().await?;
+		let estimation = call_builder.provider.estimate_eip1559_fees(None).await?;
+		Ok(self.fees(
+			estimated_gas,
+			estimation.max_fee_per_gas,
+			estimation.max_priority_fee_per_gas,
+		))
+	}
+
+	/// Applies the gas margin and the fee caps to estimated values.
+	pub fn fees(
+		&self,
+		estimated_gas: u128,
+		max_fee_per_gas: u128,
+		max_priority_fee_per_gas: u128,
+	) -> TransactionFees {
+		let max_fee_per_gas = max_fee_per_gas.min(self.max_fee_per_gas_cap);
+		TransactionFees {
+			gas: estimated_gas + (estimated_gas * self.gas_estimate_margin) / 100,
+			max_fee_per_gas,
+			max_priority_fee_per_gas: max_priority_fee_per_gas
+				.min(self.max_priority_fee_per_gas_cap)
+				.min(max_fee_per_gas),
+		}
+	}
+
+	/// Raises the fees per gas by `percent`, within the caps, to replace an underpriced
+	/// transaction.
+	pub fn bump_fees(&self, fees: TransactionFees, percent: u128) -> TransactionFees {
+		let bump = |fee: u128| fee + (fee * percent) / 100;
+		self.fees_per_gas(fees.gas, bump(fees.max_fee_per_gas), bump(fees.max_priority_fee_per_gas))
+	}
+
+	/// Fails if the transaction could cost more than the transaction fee cap.
+	pub fn check_transaction_fee(
+		&self,
+		fees: &TransactionFees,
+	) -> Result<(), McrEthConnectorError> {
+		let transaction_fee = fees.transaction_fee();
+		if transaction_fee > self.transaction_fee_cap {
+			return Err(McrEthConnectorError::GasLimitExceed(
+				transaction_fee,
+				self.transaction_fee_cap,
+			));
+		}
+		Ok(())
+	}
+
+	fn fees_per_gas(
+		&self,
+		gas: u128,
+		max_fee_per_gas: u128,
+		max_priority_fee_per_gas: u128,
+	) -> TransactionFees {
+		TransactionFees { gas, ..self.fees(0, max_fee_per_gas, max_priority_fee_per_gas) }
+	}
+}
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Circuit breaker of the fees: holds back the transactions while the gas price is over a ceiling
+/// or while sending them could spend more than the hourly or daily budget.
+///
+/// The controller is cheap to clone and clones share the fees spent.
+#[derive(Debug, Default, Clone)]
+pub struct FeeController {
+	pub gas_price_ceiling: Option<u128>,
+	pub hourly_budget: Option<u128>,
+	pub daily_budget: Option<u128>,
+	/// Fees spent in the last day, oldest first.
+	spent: Arc<Mutex<VecDeque<(Instant, u128)>>>,
+}
+
+impl From<&TransactionsConfig> for FeeController {
+	fn from(config: &TransactionsConfig) -> Self {
+		FeeController {
+			gas_price_ceiling: config.gas_price_ceiling.map(Into::into),
+			hourly_budget: config.hourly_spend_budget.map(Into::into),
+			daily_budget: config.daily_spend_budget.map(Into::into),
+			spent: Default::default(),
+		}
+	}
+}
+
+impl FeeController {
+	/// Fails if the gas price of the transaction is over the ceiling, or if its maximum fee does
+	/// not fit in what is left of the budgets.
+	pub fn check(&self, fees: &TransactionFees) -> Result<(), McrEthConnectorError> {
+		self.check_at(fees, Instant::now())
+	}
+
+	/// Records the fee paid by a mined transaction.
+	pub fn record_spend(&self, fee: u128) {
+		self.record_spend_at(fee, Instant::now());
+	}
+
+	fn check_at(&self, fees: &TransactionFees, now: Instant) -> Result<(), McrEthConnectorError> {
+		if let Some(ceiling) = self.gas_price_ceiling {
+			if fees.max_fee_per_gas > ceiling {
+				return Err(McrEthConnectorError::BudgetExceeded(format!(
+					"gas price {} over the ceiling {ceiling}",
+					fees.max_fee_per_gas
+				)));
+			}
+		}
+		let budgets = [("hourly", HOUR, self.hourly_budget), ("daily", DAY, self.daily_budget)];
+		for (period, window, budget) in budgets {
+			let Some(budget) = budget else {
+				continue;
+			};
+			let spent = self.spent_since(now, window);
+			if spent.saturating_add(fees.transaction_fee()) > budget {
+				return Err(McrEthConnectorError::BudgetExceeded(format!(
+					"{period} budget {budget} would be exceeded: {spent} spent, transaction fee up to {}",
+					fees.transaction_fee()
+				)));
+			}
+		}
+		Ok(())
+	}
+
+	fn record_spend_at(&self, fee: u128, now: Instant) {
+		let mut spent = self.spent.lock().unwrap();
+		spent.push_back((now, fee));
+		while spent.front().is_some_and(|(at, _)| now.duration_since(*at) > DAY) {
+			spent.pop_front();
+		}
+	}
+
+	fn spent_since(&self, now: Instant, window: Duration) -> u128 {
+		self.spent
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|(at, _)| now.duration_since(*at) <= window)
+			.map(|(_, fee)| fee)
+			.sum()
+	}
+}
+
+/// How long a sent transaction is waited for, and when it is replaced because it is stuck in the
+/// mempool.
+#[derive(Debug, Clone)]
+pub struct InclusionPolicy {
+	/// Number of blocks a transaction must be included for before it is considered final.
+	pub confirmations: u64,
+	/// Time a transaction, or the transactions replacing it, can take to be mined.
+	pub receipt_timeout: Duration,
+	/// Time a transaction can wait in the mempool before it is replaced with higher fees.
+	pub stuck_timeout: Duration,
+	/// Raise of the fees per gas of a transaction replacing an underpriced or stuck one, in
+	/// percent.
+	pub fee_bump: u128,
+}
+
+impl From<&TransactionsConfig> for InclusionPolicy {
+	fn from(config: &TransactionsConfig) -> Self {
+		InclusionPolicy {
+			confirmations: config.confirmations,
+			receipt_timeout: Duration::from_millis(config.receipt_timeout),
+			stuck_timeout: Duration::from_millis(config.stuck_transaction_timeout),
+			fee_bump: config.underpriced_gas_bump.into(),
+		}
+	}
+}
+
+impl InclusionPolicy {
+	/// Whether a transaction included in block `included_at` has enough confirmations at the
+	/// `head` of the chain.
+	fn is_final(&self, included_at: u64, head: u64) -> bool {
+		head.saturating_add(1).saturating_sub(included_at) >= self.confirmations.max(1)
+	}
+}
+
+fn is_nonce_too_low(error: &alloy_contract::Error) -> bool {
+	let alloy_contract::Error::TransportError(TransportError::ErrorResp(payload)) = error else {
+		return false;
+	};
+	payload.message.contains("nonce too low")
+}
+
+/// Nonces of the transactions of one signer.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct SignerNonces {
+	/// Lowest nonce never allocated.
+	next: u64,
+	/// Allocated nonces whose transaction is not mined yet.
+	in_flight: BTreeSet<u64>,
+	/// Nonces below `next` without a transaction, allocated again before new ones.
+	gaps: BTreeSet<u64>,
+}
+
+impl SignerNonces {
+	fn new(next: u64) -> Self {
+		SignerNonces { next, ..Default::default() }
+	}
+
+	fn allocate(&mut self) -> u64 {
+		let nonce = match self.gaps.pop_first() {
+			Some(nonce) => nonce,
+			None => {
+				self.next += 1;
+				self.next - 1
+			}
+		};
+		self.in_flight.insert(nonce);
+		nonce
+	}
+
+	fn confirm(&mut self, nonce: u64) {
+		self.in_flight.remove(&nonce);
+	}
+
+	fn release(&mut self, nonce: u64) {
+		if self.in_flight.remove(&nonce) {
+			self.gaps.insert(nonce);
+		}
+	}
+
+	/// Aligns the nonces on the pending transaction count of the signer on chain, and returns the
+	/// gaps detected: nonces neither known by the chain nor in flight.
+	fn sync(&mut self, chain_nonce: u64) -> Vec<u64> {
+		self.gaps.retain(|nonce| *nonce >= chain_nonce);
+		self.in_flight.retain(|nonce| *nonce >= chain_nonce);
+		if chain_nonce >= self.next {
+			// Transactions were sent by another client of the signer.
+			self.next = chain_nonce;
+			return Vec::new();
+		}
+		let detected: Vec<u64> = (chain_nonce..self.next)
+			.filter(|nonce| !self.in_flight.contains(nonce) && !self.gaps.contains(nonce))
+			.collect();
+		self.gaps.extend(detected.iter().copied());
+		detected
+	}
+}
+
+/// A transaction sent and not mined yet, kept to replace it if it gets stuck in the mempool.
+#[derive(Debug, Clone)]
+pub struct SentTransaction {
+	pub signer: Address,
+	pub nonce: u64,
+	pub request: TransactionRequest,
+	pub fees: TransactionFees,
+	pub sent_at: Instant,
+	/// Whether the transaction is an empty transfer cancelling the transaction it replaced.
+	pub cancellation: bool,
+}
+
+/// Allocates the nonces of the transactions of each signer, so that transactions sent
+/// concurrently by the same signer never get the same nonce, and keeps the transactions sent
+/// until they are mined.
+///
+/// The manager is cheap to clone and clones share their nonces, so a single manager can be used by
+/// every client sending transactions with a signer.
+#[derive(Debug, Default, Clone)]
+pub struct NonceManager {
+	signers: Arc<tokio::sync::Mutex<HashMap<Address, SignerNonces>>>,
+	sent: Arc<tokio::sync::Mutex<HashMap<TxHash, SentTransaction>>>,
+}
+
+impl NonceManager {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Allocates the nonce of the next transaction of `signer`, fetching the transaction count of
+	/// the signer the first time it is used.
+	pub async fn allocate<P, T>(&self, provider: &P, signer: Address) -> Result<u64, TransportError>
+	where
+		P: Provider<T, Ethereum>,
+		T: Transport + Clone,
+	{
+		let mut signers = self.signers.lock().await;
+		let nonces = match signers.entry(signer) {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => {
+				let chain_nonce = provider.get_transaction_count(signer).pending().await?;
+				entry.insert(SignerNonces::new(chain_nonce))
+			}
+		};
+		Ok(nonces.allocate())
+	}
+
+	/// Marks the transaction with `nonce` as mined.
+	pub async fn confirm(&self, signer: Address, nonce: u64) {
+		if let Some(nonces) = self.signers.lock().await.get_mut(&signer) {
+			nonces.confirm(nonce);
+		}
+		self.forget_sent(signer, nonce).await;
+	}
+
+	/// Gives back the nonce of a transaction which was not sent, so it is allocated again.
+	pub async fn release(&self, signer: Address, nonce: u64) {
+		if let Some(nonces) = self.signers.lock().await.get_mut(&signer) {
+			nonces.release(nonce);
+		}
+		self.forget_sent(signer, nonce).await;
+	}
+
+	/// Keeps the transaction `tx_hash`, replacing any transaction sent before with the same nonce.
+	pub async fn record_sent(&self, tx_hash: TxHash, transaction: SentTransaction) {
+		let mut sent = self.sent.lock().await;
+		sent.retain(|_, other| {
+			(other.signer, other.nonce) != (transaction.signer, transaction.nonce)
+		});
+		sent.insert(tx_hash, transaction);
+	}
+
+	pub async fn sent_transaction(&self, tx_hash: &TxHash) -> Option<SentTransaction> {
+		self.sent.lock().await.get(tx_hash).cloned()
+	}
+
+	/// Returns the hash of the transaction sent by `signer` with `nonce`.
+	pub async fn sent_with_nonce(&self, signer: Address, nonce: u64) -> Option<TxHash> {
+		self.sent
+			.lock()
+			.await
+			.iter()
+			.find(|(_, sent)| sent.signer == signer && sent.nonce == nonce)
+			.map(|(tx_hash, _)| *tx_hash)
+	}
+
+	/// Returns the transactions sent more than `deadline` ago and not mined yet.
+	pub async fn stuck_transactions(&self, deadline: Duration) -> Vec<TxHash> {
+		self.sent
+			.lock()
+			.await
+			.iter()
+			.filter(|(_, sent)| sent.sent_at.elapsed() > deadline)
+			.map(|(tx_hash, _)| *tx_hash)
+			.collect()
+	}
+
+	async fn forget_sent(&self, signer: Address, nonce: u64) {
+		self.sent
+			.lock()
+			.await
+			.retain(|_, sent| (sent.signer, sent.nonce) != (signer, nonce));
+	}
+
+	/// Fetches the pending transaction count of `signer` and returns the nonces of the gaps
+	/// detected, which are filled by the next allocations.
+	pub async fn resync<P, T>(
+		&self,
+		provider: &P,
+		signer: Address,
+	) -> Result<Vec<u64>, TransportError>
+	where
+		P: Provider<T, Ethereum>,
+		T: Transport + Clone,
+	{
+		let mut signers = self.signers.lock().await;
+		let chain_nonce = provider.get_transaction_count(signer).pending().await?;
+		let gaps = signers.entry(signer).or_default().sync(chain_nonce);
+		if !gaps.is_empty() {
+			tracing::warn!("Nonce gaps detected for signer {signer}: {gaps:?}");
+		}
+		Ok(gaps)
+	}
+}
+
+/// Sends the transaction of the call, retrying on the errors managed by the rules as configured by
+/// the retry policy, and waits until it is included for the confirmations of the inclusion policy,
+/// to not act on a transaction removed by a reorg.
+///
+/// The transaction is replaced with higher fees whenever it is stuck in the mempool, and the wait
+/// ends when any of the transactions sent with its nonce is mined, or fails after the receipt
+/// timeout.
+///
+/// The transaction is not sent, and [`McrEthConnectorError::BudgetExceeded`] is returned, when the
+/// fee controller holds it back.
+///
+/// The span of the call records the signer and the nonce, nested in the span of the caller, such
+/// as the span of the bridge transfer the transaction belongs to.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all, fields(signer = %signer_address, nonce))]
 pub async fn send_transaction<
 	P: Provider<T, Ethereum> + Clone,
 	T: Transport + Clone,
@@ -65,60 +520,149 @@ pub async fn send_transaction<
 >(
 	base_call_builder: CallBuilder<T, &&P, D, Ethereum>,
 	send_transaction_error_rules: &[Box<dyn VerifyRule>],
-	number_retry: u32,
-	gas_limit: u128,
+	retry_policy: &RetryPolicy,
+	inclusion: &InclusionPolicy,
+	gas_strategy: &GasStrategy,
+	fee_controller: &FeeController,
+	nonce_manager: &NonceManager,
+	signer_address: Address,
 ) -> Result<(), anyhow::Error> {
-	//validate gas price.
-	let mut estimate_gas = base_call_builder.estimate_gas().await?;
-	// Add 20% because initial gas estimate are too low.
-	estimate_gas += (estimate_gas * 20) / 100;
+	let mut fees = gas_strategy.estimate(&base_call_builder).await?;
+	let provider = *base_call_builder.provider;
+	let mut nonce = nonce_manager.allocate(provider, signer_address).await?;
+	tracing::Span::current().record("nonce", nonce);
+	let mut rule_retries: HashMap<&'static str, u32> = HashMap::new();
 
 	// Sending Transaction automatically can lead to errors that depend on the state for Eth.
 	// It's convenient to manage some of them automatically to avoid to fail commitment Transaction.
 	// I define a first one but other should be added depending on the test with mainnet.
-	for _ in 0..number_retry {
-		let call_builder = base_call_builder.clone().gas(estimate_gas);
+	for _ in 0..retry_policy.max_attempts {
+		// Retries keep the nonce, so a resent transaction replaces the one sent before.
+		let call_builder = base_call_builder
+			.clone()
+			.nonce(nonce)
+			.gas(fees.gas)
+			.max_fee_per_gas(fees.max_fee_per_gas)
+			.max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
 
-		//detect if the gas price doesn't execeed the limit.
-		let gas_price = call_builder.provider.get_gas_price().await?;
-		let transaction_fee_wei = estimate_gas * gas_price;
-		if transaction_fee_wei > gas_limit {
-			return Err(McrEthConnectorError::GasLimitExceed(transaction_fee_wei, gas_limit).into());
+		//detect if the transaction fee doesn't execeed the limit nor the budget.
+		if let Err(err) = gas_strategy
+			.check_transaction_fee(&fees)
+			.and_then(|()| fee_controller.check(&fees))
+		{
+			nonce_manager.release(signer_address, nonce).await;
+			return Err(err.into());
 		}
 
 		//send the Transaction and detect send error.
-		let pending_transaction = match call_builder.send().await {
-			Ok(pending_transaction) => pending_transaction,
+		let request = call_builder.clone().into_transaction_request();
+		let tx_hash = match call_builder.send().await {
+			Ok(pending_transaction) => {
+				let sent = SentTransaction {
+					signer: signer_address,
+					nonce,
+					request,
+					fees,
+					sent_at: Instant::now(),
+					cancellation: false,
+				};
+				nonce_manager.record_sent(*pending_transaction.tx_hash(), sent).await;
+				*pending_transaction.tx_hash()
+			}
 			Err(err) => {
+				// The nonce was used by another transaction of the signer: resend with a new one.
+				if is_nonce_too_low(&err) {
+					nonce_manager.confirm(signer_address, nonce).await;
+					nonce_manager.resync(provider, signer_address).await?;
+					nonce = nonce_manager.allocate(provider, signer_address).await?;
+					tracing::Span::current().record("nonce", nonce);
+					continue;
+				}
+
 				//apply defined rules.
+				let mut retry_rule = None;
 				for rule in send_transaction_error_rules {
 					// Verify all rules. If one rule return true or an error stop verification.
-					// If true retry with more gas else return the error.
-					if rule.verify(&err)? {
-						//increase gas of 10% and retry
-						estimate_gas += (estimate_gas * 10) / 100;
-						tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-						continue;
+					// If true retry with higher fees else return the error.
+					match rule.verify(&err) {
+						Ok(true) => {
+							retry_rule = Some(rule.name());
+							break;
+						}
+						Ok(false) => (),
+						Err(err) => {
+							nonce_manager.release(signer_address, nonce).await;
+							return Err(err.into());
+						}
+					}
+				}
+				if let Some(rule) = retry_rule {
+					let retries = rule_retries.entry(rule).or_default();
+					if *retries >= retry_policy.max_attempts_for(rule) {
+						nonce_manager.release(signer_address, nonce).await;
+						return Err(McrEthConnectorError::RpcTransactionExecution(format!(
+							"Send commitment Transaction fail because of exceed max retry for {rule} errors"
+						))
+						.into());
 					}
+					//increase the fees per gas and retry after the backoff delay
+					fees = gas_strategy.bump_fees(fees, inclusion.fee_bump);
+					tokio::time::sleep(retry_policy.jittered_delay(rule, *retries)).await;
+					*retries += 1;
+					continue;
 				}
 
+				nonce_manager.release(signer_address, nonce).await;
 				return Err(McrEthConnectorError::from(err).into());
 			}
 		};
 
-		match pending_transaction.get_receipt().await {
+		match wait_for_receipt(
+			provider,
+			nonce_manager,
+			gas_strategy,
+			inclusion,
+			signer_address,
+			nonce,
+			tx_hash,
+		)
+		.await
+		{
+			// The transaction was cancelled, and its call never executed
+			Ok((transaction_receipt, true)) => {
+				nonce_manager.confirm(signer_address, nonce).await;
+				fee_controller.record_spend(
+					transaction_receipt
+						.gas_used
+						.saturating_mul(transaction_receipt.effective_gas_price),
+				);
+				return Err(McrEthConnectorError::RpcTransactionExecution(format!(
+					"Send commitment Transaction cancelled, receipt:{transaction_receipt:?}"
+				))
+				.into());
+			}
 			// Transaction execution fail
-			Ok(transaction_receipt) if !transaction_receipt.status() => {
+			Ok((transaction_receipt, false)) if !transaction_receipt.status() => {
+				// The failed transaction is mined, the retry needs a new nonce.
+				nonce_manager.confirm(signer_address, nonce).await;
+				fee_controller.record_spend(
+					transaction_receipt
+						.gas_used
+						.saturating_mul(transaction_receipt.effective_gas_price),
+				);
 				tracing::debug!(
-					"transaction_receipt.gas_used: {} / estimate_gas: {estimate_gas}",
-					transaction_receipt.gas_used
+					"transaction_receipt.gas_used: {} / gas: {}",
+					transaction_receipt.gas_used,
+					fees.gas
 				);
 				// Some valid Tx can abort cause of insufficient gas without consuming all its gas.
 				// Define a threshold a little less than estimated gas to detect them.
-				let tx_gas_consumption_threshold = estimate_gas - (estimate_gas * 10) / 100;
+				let tx_gas_consumption_threshold = fees.gas - (fees.gas * 10) / 100;
 				if transaction_receipt.gas_used >= tx_gas_consumption_threshold {
 					tracing::info!("Send commitment Transaction  fail because of insufficient gas, receipt:{transaction_receipt:?} ");
-					estimate_gas += (estimate_gas * 30) / 100;
+					fees.gas += (fees.gas * 30) / 100;
+					nonce = nonce_manager.allocate(provider, signer_address).await?;
+					tracing::Span::current().record("nonce", nonce);
 					continue;
 				} else {
 					return Err(McrEthConnectorError::RpcTransactionExecution(format!(
@@ -127,16 +671,570 @@ pub async fn send_transaction<
 					.into());
 				}
 			}
-			Ok(_) => return Ok(()),
+			Ok((transaction_receipt, false)) => {
+				nonce_manager.confirm(signer_address, nonce).await;
+				fee_controller.record_spend(
+					transaction_receipt
+						.gas_used
+						.saturating_mul(transaction_receipt.effective_gas_price),
+				);
+				return Ok(());
+			}
 			Err(err) => {
-				return Err(McrEthConnectorError::RpcTransactionExecution(err.to_string()).into())
+				// The transaction may still be mined: only a resync can tell if its nonce is free.
+				nonce_manager.resync(provider, signer_address).await?;
+				return Err(err);
 			}
 		};
 	}
 
 	//Max retry exceed
+	nonce_manager.release(signer_address, nonce).await;
 	Err(McrEthConnectorError::RpcTransactionExecution(
 		"Send commitment Transaction fail because of exceed max retry".to_string(),
 	)
 	.into())
 }
+
+/// Waits until one of the transactions sent by `signer` with `nonce`, starting with `tx_hash` and
+/// followed by the transactions replacing it, is mined and final, and returns its receipt and
+/// whether it is a cancellation.
+///
+/// The transaction is sped up each time it waits in the mempool for longer than the stuck timeout.
+/// Fails after the receipt timeout, or when the nonce is used by a transaction sent by another
+/// client.
+async fn wait_for_receipt<P, T>(
+	provider: &P,
+	nonce_manager: &NonceManager,
+	gas_strategy: &GasStrategy,
+	inclusion: &InclusionPolicy,
+	signer: Address,
+	nonce: u64,
+	tx_hash: TxHash,
+) -> Result<(TransactionReceipt, bool), anyhow::Error>
+where
+	P: Provider<T, Ethereum>,
+	T: Transport + Clone,
+{
+	let start = Instant::now();
+	let deadline = start + inclusion.receipt_timeout;
+	let mut replace_at = start + inclusion.stuck_timeout;
+	// The transactions replaced can still be mined instead of their replacement
+	let mut sent_hashes = vec![tx_hash];
+	loop {
+		let latest_hash = nonce_manager.sent_with_nonce(signer, nonce).await.unwrap_or(tx_hash);
+		if !sent_hashes.contains(&latest_hash) {
+			sent_hashes.push(latest_hash);
+		}
+
+		// The count is read before the receipts, so a mined nonce without a receipt was used by
+		// another transaction
+		let mined_count = provider.get_transaction_count(signer).latest().await?;
+		let mut mined = false;
+		for hash in &sent_hashes {
+			let Some(receipt) = provider.get_transaction_receipt(*hash).await? else {
+				continue;
+			};
+			let Some(included_at) = receipt.block_number else {
+				continue;
+			};
+			mined = true;
+			if inclusion.is_final(included_at, provider.get_block_number().await?) {
+				let cancellation = nonce_manager
+					.sent_transaction(hash)
+					.await
+					.is_some_and(|sent| sent.cancellation);
+				return Ok((receipt, cancellation));
+			}
+		}
+		if !mined && mined_count > nonce {
+			nonce_manager.confirm(signer, nonce).await;
+			return Err(McrEthConnectorError::RpcTransactionExecution(format!(
+				"Nonce {nonce} of {signer} was used by a transaction sent by another client"
+			))
+			.into());
+		}
+
+		let now = Instant::now();
+		if now >= deadline {
+			return Err(McrEthConnectorError::RpcTransactionExecution(format!(
+				"Transaction {latest_hash} with nonce {nonce} not mined after {:?}",
+				inclusion.receipt_timeout
+			))
+			.into());
+		}
+		// A mined transaction waiting for its confirmations is not replaced
+		if !mined && now >= replace_at {
+			tracing::warn!("Transaction {latest_hash} with nonce {nonce} is stuck, speeding it up");
+			if let Err(err) =
+				speed_up(provider, nonce_manager, gas_strategy, latest_hash, inclusion.fee_bump)
+					.await
+			{
+				tracing::warn!("Failed to speed up the stuck transaction {latest_hash}: {err}");
+			}
+			replace_at = now + inclusion.stuck_timeout;
+		}
+		tokio::time::sleep(RECEIPT_POLL_INTERVAL.min(deadline - now)).await;
+	}
+}
+
+/// Replaces the stuck transaction `tx_hash` by the same transaction with its fees per gas raised by
+/// `fee_bump` percent, and returns the hash of the replacement.
+pub async fn speed_up<P, T>(
+	provider: &P,
+	nonce_manager: &NonceManager,
+	gas_strategy: &GasStrategy,
+	tx_hash: TxHash,
+	fee_bump: u128,
+) -> Result<TxHash, anyhow::Error>
+where
+	P: Provider<T, Ethereum>,
+	T: Transport + Clone,
+{
+	let sent = nonce_manager
+		.sent_transaction(&tx_hash)
+		.await
+		.ok_or_else(|| McrEthConnectorError::UnknownTransaction(tx_hash.to_string()))?;
+	let fees = gas_strategy.bump_fees(sent.fees, fee_bump);
+	let replacement = replacement(sent.request.clone(), sent, fees);
+	replace_transaction(provider, nonce_manager, gas_strategy, replacement).await
+}
+
+/// Cancels the transaction sent by `signer` with `nonce`, replacing it by an empty transfer to the
+/// signer with its fees per gas raised by `fee_bump` percent, and returns the hash of the
+/// replacement.
+pub async fn cancel<P, T>(
+	provider: &P,
+	nonce_manager: &NonceManager,
+	gas_strategy: &GasStrategy,
+	signer: Address,
+	nonce: u64,
+	fee_bump: u128,
+) -> Result<TxHash, anyhow::Error>
+where
+	P: Provider<T, Ethereum>,
+	T: Transport + Clone,
+{
+	let tx_hash = nonce_manager.sent_with_nonce(signer, nonce).await.ok_or_else(|| {
+		McrEthConnectorError::UnknownTransaction(format!("nonce {nonce} of {signer}"))
+	})?;
+	let sent = nonce_manager
+		.sent_transaction(&tx_hash)
+		.await
+		.ok_or_else(|| McrEthConnectorError::UnknownTransaction(tx_hash.to_string()))?;
+	let cancellation = cancellation(sent, gas_strategy, fee_bump);
+	replace_transaction(provider, nonce_manager, gas_strategy, cancellation).await
+}
+
+/// Builds the empty transfer of the signer of the `sent` transaction to itself, replacing it with
+/// its fees per gas raised by `fee_bump` percent.
+fn cancellation(
+	sent: SentTransaction,
+	gas_strategy: &GasStrategy,
+	fee_bump: u128,
+) -> SentTransaction {
+	let fees = TransactionFees { gas: TRANSFER_GAS, ..gas_strategy.bump_fees(sent.fees, fee_bump) };
+	let request = TransactionRequest::default()
+		.from(sent.signer)
+		.to(sent.signer)
+		.value(U256::ZERO);
+	replacement(request, SentTransaction { cancellation: true, ..sent }, fees)
+}
+
+/// Builds the transaction sending `request` with the nonce of the `replaced` transaction and the
+/// new `fees`.
+fn replacement(
+	request: TransactionRequest,
+	replaced: SentTransaction,
+	fees: TransactionFees,
+) -> SentTransaction {
+	let request = request
+		.nonce(replaced.nonce)
+		.gas_limit(fees.gas)
+		.max_fee_per_gas(fees.max_fee_per_gas)
+		.max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+	SentTransaction { request, fees, sent_at: Instant::now(), ..replaced }
+}
+
+/// Sends the `replacement` of a transaction and keeps it in place of the transaction it replaces.
+async fn replace_transaction<P, T>(
+	provider: &P,
+	nonce_manager: &NonceManager,
+	gas_strategy: &GasStrategy,
+	replacement: SentTransaction,
+) -> Result<TxHash, anyhow::Error>
+where
+	P: Provider<T, Ethereum>,
+	T: Transport + Clone,
+{
+	gas_strategy.check_transaction_fee(&replacement.fees)?;
+	let pending_transaction = provider.send_transaction(replacement.request.clone()).await?;
+	let tx_hash = *pending_transaction.tx_hash();
+	nonce_manager.record_sent(tx_hash, replacement).await;
+	Ok(tx_hash)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::rpc::{quantity, MockRpc};
+	use alloy_primitives::TxKind;
+	use serde_json::{json, Value as JsonValue};
+
+	fn gas_strategy() -> GasStrategy {
+		GasStrategy {
+			gas_estimate_margin: 20,
+			max_fee_per_gas_cap: 100,
+			max_priority_fee_per_gas_cap: 10,
+			transaction_fee_cap: 100_000,
+		}
+	}
+
+	#[test]
+	fn test_fees_are_capped() {
+		let fees = gas_strategy().fees(1_000, 150, 20);
+		assert_eq!(
+			fees,
+			TransactionFees { gas: 1_200, max_fee_per_gas: 100, max_priority_fee_per_gas: 10 }
+		);
+		assert!(matches!(
+			gas_strategy().check_transaction_fee(&fees),
+			Err(McrEthConnectorError::GasLimitExceed(120_000, 100_000))
+		));
+	}
+
+	#[test]
+	fn test_bump_fees() {
+		let fees = gas_strategy().fees(500, 50, 5);
+		let bumped = gas_strategy().bump_fees(fees, 10);
+		assert_eq!(
+			bumped,
+			TransactionFees { gas: 600, max_fee_per_gas: 55, max_priority_fee_per_gas: 5 }
+		);
+		assert!(gas_strategy().check_transaction_fee(&bumped).is_ok());
+	}
+
+	#[test]
+	fn test_bumped_fees_stop_at_the_caps() {
+		let mut fees = gas_strategy().fees(500, 90, 9);
+		fees = gas_strategy().bump_fees(fees, 10);
+		assert_eq!(
+			fees,
+			TransactionFees { gas: 600, max_fee_per_gas: 99, max_priority_fee_per_gas: 9 }
+		);
+
+		// Bumped again, the fees per gas stay at their caps.
+		for _ in 0..3 {
+			fees = gas_strategy().bump_fees(fees, 50);
+		}
+		assert_eq!(
+			fees,
+			TransactionFees { gas: 600, max_fee_per_gas: 100, max_priority_fee_per_gas: 10 }
+		);
+
+		// The priority fee is never bumped over the max fee.
+		let low_cap = GasStrategy { max_fee_per_gas_cap: 8, ..gas_strategy() };
+		let bumped = low_cap.bump_fees(low_cap.fees(500, 5, 5), 100);
+		assert_eq!(bumped.max_fee_per_gas, 8);
+		assert_eq!(bumped.max_priority_fee_per_gas, 8);
+	}
+
+	#[test]
+	fn test_bump_over_the_transaction_fee_cap_is_refused() {
+		let gas_strategy = GasStrategy { transaction_fee_cap: 50_000, ..gas_strategy() };
+		let fees = gas_strategy.fees(500, 50, 5);
+		assert!(gas_strategy.check_transaction_fee(&fees).is_ok());
+		let bumped = gas_strategy.bump_fees(fees, 100);
+		assert!(matches!(
+			gas_strategy.check_transaction_fee(&bumped),
+			Err(McrEthConnectorError::GasLimitExceed(60_000, 50_000))
+		));
+	}
+
+	#[test]
+	fn test_gas_price_over_ceiling_is_held_back() {
+		let fee_controller = FeeController { gas_price_ceiling: Some(80), ..Default::default() };
+		assert!(fee_controller.check(&gas_strategy().fees(1_000, 80, 5)).is_ok());
+		assert!(matches!(
+			fee_controller.check(&gas_strategy().fees(1_000, 90, 5)),
+			Err(McrEthConnectorError::BudgetExceeded(_))
+		));
+	}
+
+	#[test]
+	fn test_spend_budgets() {
+		let fee_controller = FeeController {
+			hourly_budget: Some(100_000),
+			daily_budget: Some(150_000),
+			..Default::default()
+		};
+		// Up to 60_000 wei.
+		let fees = gas_strategy().fees(500, 100, 5);
+		let start = Instant::now();
+
+		assert!(fee_controller.check_at(&fees, start).is_ok());
+		fee_controller.record_spend_at(50_000, start);
+		assert!(matches!(
+			fee_controller.check_at(&fees, start),
+			Err(McrEthConnectorError::BudgetExceeded(_))
+		));
+
+		// The hourly budget is available again, the daily one is not.
+		let later = start + HOUR + Duration::from_secs(1);
+		assert!(fee_controller.check_at(&fees, later).is_ok());
+		fee_controller.record_spend_at(60_000, later);
+		fee_controller.record_spend_at(20_000, later);
+		assert!(matches!(
+			fee_controller.check_at(&fees, later + HOUR + Duration::from_secs(1)),
+			Err(McrEthConnectorError::BudgetExceeded(_))
+		));
+		assert!(fee_controller.check_at(&fees, later + DAY + Duration::from_secs(1)).is_ok());
+	}
+
+	fn inclusion(confirmations: u64) -> InclusionPolicy {
+		InclusionPolicy {
+			confirmations,
+			receipt_timeout: Duration::from_secs(600),
+			stuck_timeout: Duration::from_secs(120),
+			fee_bump: 10,
+		}
+	}
+
+	#[test]
+	fn test_budget_is_spent_up_to_its_limit() {
+		let fee_controller = FeeController { hourly_budget: Some(100_000), ..Default::default() };
+		// Up to 60_000 wei.
+		let fees = gas_strategy().fees(500, 100, 5);
+		let start = Instant::now();
+
+		// Spending exactly the budget is allowed, one more wei is not.
+		fee_controller.record_spend_at(40_000, start);
+		assert!(fee_controller.check_at(&fees, start).is_ok());
+		fee_controller.record_spend_at(1, start);
+		assert!(matches!(
+			fee_controller.check_at(&fees, start),
+			Err(McrEthConnectorError::BudgetExceeded(_))
+		));
+
+		// The fees spent count for a full hour.
+		assert!(fee_controller.check_at(&fees, start + HOUR).is_err());
+		assert!(fee_controller.check_at(&fees, start + HOUR + Duration::from_nanos(1)).is_ok());
+	}
+
+	#[test]
+	fn test_transaction_is_final_after_confirmations() {
+		let inclusion = inclusion(3);
+		assert!(!inclusion.is_final(10, 11));
+		assert!(inclusion.is_final(10, 12));
+
+		// A transaction is final once included when no confirmations are required.
+		let inclusion = InclusionPolicy { confirmations: 0, ..inclusion };
+		assert!(inclusion.is_final(10, 10));
+	}
+
+	#[test]
+	fn test_transaction_is_final_at_the_exact_confirmation_depth() {
+		// The block including the transaction is its first confirmation.
+		assert!(inclusion(1).is_final(10, 10));
+		// A head behind the block including the transaction gives no confirmation.
+		assert!(!inclusion(1).is_final(10, 9));
+
+		assert!(!inclusion(12).is_final(10, 20));
+		assert!(inclusion(12).is_final(10, 21));
+	}
+
+	fn receipt(tx_hash: TxHash, block_number: u64) -> JsonValue {
+		json!({
+			"type": "0x2",
+			"status": "0x1",
+			"cumulativeGasUsed": "0x5208",
+			"logs": [],
+			"logsBloom": format!("0x{}", "00".repeat(256)),
+			"transactionHash": tx_hash,
+			"transactionIndex": "0x0",
+			"blockHash": TxHash::repeat_byte(0xbb),
+			"blockNumber": quantity(block_number),
+			"gasUsed": "0x5208",
+			"effectiveGasPrice": "0x1",
+			"from": Address::repeat_byte(1),
+			"to": Address::repeat_byte(2),
+			"contractAddress": null,
+		})
+	}
+
+	/// Chain where the transaction `tx_hash`, with nonce 3, is included in block 10, and whose
+	/// head moves one block forward each time it is read.
+	fn chain(tx_hash: TxHash) -> MockRpc {
+		let mut head = 9;
+		MockRpc::new()
+			.on("eth_getTransactionCount", |_| quantity(4))
+			.on("eth_getTransactionReceipt", move |_| receipt(tx_hash, 10))
+			.on("eth_blockNumber", move |_| {
+				head += 1;
+				quantity(head)
+			})
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_receipt_is_returned_at_the_confirmation_depth() -> Result<(), anyhow::Error> {
+		let tx_hash = TxHash::repeat_byte(1);
+		let rpc = chain(tx_hash);
+		let (receipt, cancellation) = wait_for_receipt(
+			&rpc.provider(),
+			&NonceManager::new(),
+			&gas_strategy(),
+			&inclusion(3),
+			Address::repeat_byte(1),
+			3,
+			tx_hash,
+		)
+		.await?;
+		assert_eq!(receipt.transaction_hash, tx_hash);
+		assert!(!cancellation);
+		// Returned at head 12, the third block confirming the transaction.
+		assert_eq!(rpc.requests("eth_blockNumber").len(), 3);
+
+		// Without confirmations required, the block including the transaction is enough.
+		let rpc = chain(tx_hash);
+		wait_for_receipt(
+			&rpc.provider(),
+			&NonceManager::new(),
+			&gas_strategy(),
+			&inclusion(0),
+			Address::repeat_byte(1),
+			3,
+			tx_hash,
+		)
+		.await?;
+		assert_eq!(rpc.requests("eth_blockNumber").len(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn test_released_nonce_is_allocated_again() {
+		let mut nonces = SignerNonces::new(5);
+		assert_eq!(nonces.allocate(), 5);
+		assert_eq!(nonces.allocate(), 6);
+		assert_eq!(nonces.allocate(), 7);
+
+		nonces.release(6);
+		assert_eq!(nonces.allocate(), 6);
+		assert_eq!(nonces.allocate(), 8);
+	}
+
+	#[test]
+	fn test_sync_detects_gaps() {
+		let mut nonces = SignerNonces::new(0);
+		for _ in 0..4 {
+			nonces.allocate();
+		}
+		nonces.confirm(0);
+		nonces.confirm(1);
+		nonces.confirm(2);
+
+		// Transaction 2 was dropped from the mempool, 3 is still in flight.
+		assert_eq!(nonces.sync(2), vec![2]);
+		assert_eq!(nonces.allocate(), 2);
+		assert_eq!(nonces.allocate(), 4);
+
+		// Transactions sent by another client of the signer.
+		assert_eq!(nonces.sync(10), Vec::<u64>::new());
+		assert_eq!(nonces.allocate(), 10);
+	}
+
+	#[tokio::test]
+	async fn test_failed_send_leaves_no_nonce_gap() -> Result<(), anyhow::Error> {
+		let rpc = MockRpc::new().on("eth_getTransactionCount", |_| quantity(7));
+		let provider = rpc.provider();
+		let nonce_manager = NonceManager::new();
+		let signer = Address::repeat_byte(1);
+		assert_eq!(nonce_manager.allocate(&provider, signer).await?, 7);
+		assert_eq!(nonce_manager.allocate(&provider, signer).await?, 8);
+
+		// Sending the transaction with nonce 7 failed, the one with nonce 8 is in flight.
+		nonce_manager.release(signer, 7).await;
+		// Nothing was mined: the released nonce is known, not a gap.
+		assert!(nonce_manager.resync(&provider, signer).await?.is_empty());
+		// The next transaction takes the released nonce, so the one with nonce 8 can be mined.
+		assert_eq!(nonce_manager.allocate(&provider, signer).await?, 7);
+		assert_eq!(nonce_manager.allocate(&provider, signer).await?, 9);
+
+		// The transaction count is fetched on the first allocation and on resync only.
+		assert_eq!(rpc.requests("eth_getTransactionCount").len(), 2);
+		Ok(())
+	}
+
+	#[test]
+	fn test_nonce_used_by_another_client_is_skipped() {
+		let mut nonces = SignerNonces::new(3);
+		assert_eq!(nonces.allocate(), 3);
+
+		// The send failed with nonce too low: another client sent the transactions 3 and 4.
+		nonces.confirm(3);
+		assert_eq!(nonces.sync(5), Vec::<u64>::new());
+		assert_eq!(nonces.allocate(), 5);
+	}
+
+	#[tokio::test]
+	async fn test_replaced_transaction_is_stuck_until_mined() {
+		let nonce_manager = NonceManager::new();
+		let signer = Address::repeat_byte(1);
+		let sent = SentTransaction {
+			signer,
+			nonce: 3,
+			request: TransactionRequest::default(),
+			fees: gas_strategy().fees(500, 50, 5),
+			sent_at: Instant::now() - Duration::from_secs(60),
+			cancellation: false,
+		};
+		nonce_manager.record_sent(TxHash::repeat_byte(1), sent.clone()).await;
+		assert_eq!(
+			nonce_manager.stuck_transactions(Duration::from_secs(30)).await,
+			vec![TxHash::repeat_byte(1)]
+		);
+
+		// The replacement takes the place of the stuck transaction.
+		let replacement = SentTransaction { sent_at: Instant::now(), ..sent };
+		nonce_manager.record_sent(TxHash::repeat_byte(2), replacement).await;
+		assert!(nonce_manager.stuck_transactions(Duration::from_secs(30)).await.is_empty());
+		assert_eq!(nonce_manager.sent_with_nonce(signer, 3).await, Some(TxHash::repeat_byte(2)));
+
+		nonce_manager.confirm(signer, 3).await;
+		assert!(nonce_manager.sent_transaction(&TxHash::repeat_byte(2)).await.is_none());
+	}
+
+	#[tokio::test]
+	async fn test_cancel_replaces_the_transaction_with_its_nonce() {
+		let nonce_manager = NonceManager::new();
+		let signer = Address::repeat_byte(1);
+		for nonce in [3, 4] {
+			let sent = SentTransaction {
+				signer,
+				nonce,
+				request: TransactionRequest::default().to(Address::repeat_byte(2)),
+				fees: gas_strategy().fees(500, 50, 5),
+				sent_at: Instant::now(),
+				cancellation: false,
+			};
+			nonce_manager.record_sent(TxHash::repeat_byte(nonce as u8), sent).await;
+		}
+
+		let tx_hash = nonce_manager.sent_with_nonce(signer, 4).await.unwrap();
+		let sent = nonce_manager.sent_transaction(&tx_hash).await.unwrap();
+		let cancellation = cancellation(sent, &gas_strategy(), 10);
+		assert!(cancellation.cancellation);
+		assert_eq!(cancellation.nonce, 4);
+		assert_eq!(cancellation.request.nonce, Some(4));
+		assert_eq!(cancellation.request.to, Some(TxKind::Call(signer)));
+		assert_eq!(cancellation.request.value, Some(U256::ZERO));
+		assert_eq!(
+			cancellation.fees,
+			TransactionFees { gas: TRANSFER_GAS, max_fee_per_gas: 55, max_priority_fee_per_gas: 5 }
+		);
+
+		// Only the transaction with the cancelled nonce is replaced.
+		nonce_manager.record_sent(TxHash::repeat_byte(5), cancellation).await;
+		assert_eq!(nonce_manager.sent_with_nonce(signer, 4).await, Some(TxHash::repeat_byte(5)));
+		assert_eq!(nonce_manager.sent_with_nonce(signer, 3).await, Some(TxHash::repeat_byte(3)));
+	}
+}
@@ -33,6 +33,7 @@ async fn run_genesis_ceremony(
 		.well_known_account_private_keys
 		.get(1)
 		.context("No well known account")?
+		.expose()
 		.parse()?;
 	let alice_address = alice.address();
 	let alice_rpc_provider = ProviderBuilder::new()
@@ -52,6 +53,7 @@ async fn run_genesis_ceremony(
 		.well_known_account_private_keys
 		.get(2)
 		.context("No well known account")?
+		.expose()
 		.parse()?;
 	let bob_address = bob.address();
 	let bob_rpc_provider = ProviderBuilder::new()
@@ -230,7 +232,7 @@ pub async fn test_client_settlement() -> Result<(), anyhow::Error> {
 	let testing_config = config.testing.as_ref().context("Testing config not defined.")?;
 	run_genesis_ceremony(
 		&config,
-		PrivateKeySigner::from_str(&testing_config.mcr_testing_admin_account_private_key)?,
+		PrivateKeySigner::from_str(testing_config.mcr_testing_admin_account_private_key.expose())?,
 		&rpc_url,
 		Address::from_str(&testing_config.move_token_contract_address)?,
 		Address::from_str(&testing_config.movement_staking_contract_address)?,
@@ -246,7 +248,7 @@ pub async fn test_client_settlement() -> Result<(), anyhow::Error> {
 				.well_known_account_private_keys
 				.get(1)
 				.context("No well known account")?
-				.to_string(),
+				.clone(),
 			..config.settle.clone()
 		},
 		..config.clone()
@@ -273,7 +275,7 @@ pub async fn test_client_settlement() -> Result<(), anyhow::Error> {
 				.well_known_account_private_keys
 				.get(2)
 				.context("No well known account")?
-				.to_string(),
+				.clone(),
 			..config.settle.clone()
 		},
 		..config.clone()
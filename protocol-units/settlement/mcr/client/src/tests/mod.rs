@@ -1,2 +1,4 @@
 #[cfg(feature = "e2e")]
 pub mod e2e;
+
+pub mod rpc;
@@ -0,0 +1,97 @@
+use alloy::providers::RootProvider;
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::json_rpc::{
+	ErrorPayload, RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest,
+};
+use alloy_transport::{TransportError, TransportFut};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+type Handler = Box<dyn FnMut(JsonValue) -> JsonValue + Send>;
+
+/// Transport answering the JSON-RPC requests of the tests with handlers instead of a node.
+///
+/// The transport is cheap to clone and clones share their handlers and the requests received.
+#[derive(Clone, Default)]
+pub struct MockRpc {
+	handlers: Arc<Mutex<HashMap<String, Handler>>>,
+	requests: Arc<Mutex<Vec<(String, JsonValue)>>>,
+}
+
+impl MockRpc {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Answers the requests of `method` with the result returned by `handler` for their params.
+	pub fn on<F>(self, method: &str, handler: F) -> Self
+	where
+		F: FnMut(JsonValue) -> JsonValue + Send + 'static,
+	{
+		self.handlers.lock().unwrap().insert(method.to_string(), Box::new(handler));
+		self
+	}
+
+	pub fn provider(&self) -> RootProvider<MockRpc> {
+		RootProvider::new(RpcClient::new(self.clone(), true))
+	}
+
+	/// Returns the params of the requests of `method` received so far.
+	pub fn requests(&self, method: &str) -> Vec<JsonValue> {
+		self.requests
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|(received, _)| received == method)
+			.map(|(_, params)| params.clone())
+			.collect()
+	}
+
+	fn answer(&self, request: &SerializedRequest) -> Response {
+		let method = request.method().to_string();
+		let params = request
+			.params()
+			.map(|params| serde_json::from_str(params.get()).expect("params are JSON"))
+			.unwrap_or(JsonValue::Null);
+		self.requests.lock().unwrap().push((method.clone(), params.clone()));
+
+		let payload = match self.handlers.lock().unwrap().get_mut(&method) {
+			Some(handler) => ResponsePayload::Success(
+				serde_json::value::to_raw_value(&handler(params)).expect("result is JSON"),
+			),
+			None => ResponsePayload::Failure(ErrorPayload {
+				code: -32601,
+				message: format!("method {method} not mocked"),
+				data: None,
+			}),
+		};
+		Response { id: request.id().clone(), payload }
+	}
+}
+
+impl tower::Service<RequestPacket> for MockRpc {
+	type Response = ResponsePacket;
+	type Error = TransportError;
+	type Future = TransportFut<'static>;
+
+	fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, request: RequestPacket) -> Self::Future {
+		let response = match request {
+			RequestPacket::Single(request) => ResponsePacket::Single(self.answer(&request)),
+			RequestPacket::Batch(requests) => {
+				ResponsePacket::Batch(requests.iter().map(|request| self.answer(request)).collect())
+			}
+		};
+		Box::pin(async move { Ok(response) })
+	}
+}
+
+/// Hex quantity of `value`, as the JSON-RPC encodes the numbers.
+pub fn quantity(value: u64) -> JsonValue {
+	JsonValue::String(format!("{value:#x}"))
+}
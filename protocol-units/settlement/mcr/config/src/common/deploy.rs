@@ -1,5 +1,5 @@
 use alloy::signers::local::PrivateKeySigner;
-use godfig::env_short_default;
+use godfig::{env_short_default, Secret};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,7 +8,7 @@ pub struct Config {
 	pub mcr_deployment_working_directory: String,
 
 	#[serde(default = "mcr_deployment_account_private_key")]
-	pub mcr_deployment_account_private_key: String,
+	pub mcr_deployment_account_private_key: Secret,
 }
 
 env_short_default!(
@@ -19,7 +19,7 @@ env_short_default!(
 
 env_short_default!(
 	mcr_deployment_account_private_key,
-	String,
+	Secret,
 	PrivateKeySigner::random().to_bytes().to_string()
 );
 
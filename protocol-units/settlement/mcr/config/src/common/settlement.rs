@@ -1,7 +1,8 @@
 use alloy::signers::local::PrivateKeySigner;
-use godfig::env_default;
+use godfig::{env_default, Secret};
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::str::FromStr;
 
 const DEFAULT_MCR_CONTRACT_ADDRESS: &str = "0x0";
 
@@ -10,15 +11,39 @@ pub struct Config {
 	#[serde(default = "default_should_settle")]
 	pub should_settle: bool,
 	#[serde(default = "default_signer_private_key")]
-	pub signer_private_key: String,
+	pub signer_private_key: Secret,
 	#[serde(default = "default_mcr_contract_address")]
 	pub mcr_contract_address: String,
+	#[serde(default = "default_backend")]
+	pub backend: Backend,
 }
 
-pub fn default_signer_private_key() -> String {
+/// Backend the block commitments are settled on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+	/// The MCR contract on Ethereum.
+	Eth,
+	/// In-memory settlement accepting the first commitment posted at each height.
+	Mock,
+}
+
+impl FromStr for Backend {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"eth" => Ok(Backend::Eth),
+			"mock" => Ok(Backend::Mock),
+			_ => Err(anyhow::anyhow!("Unknown MCR settlement backend: {s}")),
+		}
+	}
+}
+
+pub fn default_signer_private_key() -> Secret {
 	let random_wallet = PrivateKeySigner::random();
 	let random_wallet_string = random_wallet.to_bytes().to_string();
-	env::var("ETH_SIGNER_PRIVATE_KEY").unwrap_or(random_wallet_string)
+	env::var("ETH_SIGNER_PRIVATE_KEY").unwrap_or(random_wallet_string).into()
 }
 
 env_default!(
@@ -32,12 +57,21 @@ pub fn default_should_settle() -> bool {
 	env::var("ETH_SIGNER_PRIVATE_KEY").is_ok()
 }
 
+/// Settles on Ethereum when a signer is provided, in memory otherwise.
+pub fn default_backend() -> Backend {
+	env::var("MCR_SETTLEMENT_BACKEND")
+		.ok()
+		.and_then(|v| v.parse().ok())
+		.unwrap_or_else(|| if default_should_settle() { Backend::Eth } else { Backend::Mock })
+}
+
 impl Default for Config {
 	fn default() -> Self {
 		Config {
 			should_settle: default_should_settle(),
 			signer_private_key: default_signer_private_key(),
 			mcr_contract_address: default_mcr_contract_address(),
+			backend: default_backend(),
 		}
 	}
 }
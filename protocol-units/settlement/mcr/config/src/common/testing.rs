@@ -1,13 +1,13 @@
-use godfig::env_short_default;
+use godfig::{env_short_default, Secret};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
 	#[serde(default = "Vec::new")]
-	pub well_known_account_private_keys: Vec<String>,
+	pub well_known_account_private_keys: Vec<Secret>,
 
 	#[serde(default = "default_mcr_testing_admin_account_private_key")]
-	pub mcr_testing_admin_account_private_key: String,
+	pub mcr_testing_admin_account_private_key: Secret,
 
 	#[serde(default = "default_move_token_contract_address")]
 	pub move_token_contract_address: String,
@@ -16,7 +16,7 @@ pub struct Config {
 	pub movement_staking_contract_address: String,
 }
 
-env_short_default!(default_mcr_testing_admin_account_private_key, String, "0x0");
+env_short_default!(default_mcr_testing_admin_account_private_key, Secret, "0x0");
 
 env_short_default!(default_move_token_contract_address, String, "0x0");
 
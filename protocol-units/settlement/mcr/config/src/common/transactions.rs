@@ -1,29 +1,119 @@
-use godfig::env_short_default;
+use godfig::{env_default, env_short_default};
+use movement_retry::RetryPolicy;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+	/// Maximum fee of a transaction (gas * max fee per gas), in wei
 	#[serde(default = "default_gas_limit")]
 	pub gas_limit: u64,
+	/// Cap on the EIP-1559 max fee per gas, in wei
+	#[serde(default = "default_max_fee_per_gas")]
+	pub max_fee_per_gas: u64,
+	/// Cap on the EIP-1559 max priority fee per gas, in wei
+	#[serde(default = "default_max_priority_fee_per_gas")]
+	pub max_priority_fee_per_gas: u64,
+	/// Gas price above which transactions are held back instead of sent, in wei
+	#[serde(default)]
+	pub gas_price_ceiling: Option<u64>,
+	/// Fees the transactions can spend per hour, in wei
+	#[serde(default)]
+	pub hourly_spend_budget: Option<u64>,
+	/// Fees the transactions can spend per day, in wei
+	#[serde(default)]
+	pub daily_spend_budget: Option<u64>,
+	/// Margin added to the estimated gas of a transaction, in percent
+	#[serde(default = "default_gas_estimate_margin")]
+	pub gas_estimate_margin: u64,
+	/// Maximum gas of a transaction posting a batch of commitments, larger batches are split
+	#[serde(default = "default_max_batch_gas")]
+	pub max_batch_gas: u64,
+	/// File keeping the commitments waiting to be posted across restarts, in memory if not set
+	#[serde(default)]
+	pub commitment_queue_path: Option<String>,
+	/// Maximum number of commitments waiting to be posted
+	#[serde(default = "default_max_queued_commitments")]
+	pub max_queued_commitments: u64,
 	/// Timeout for batching blocks, in milliseconds
 	#[serde(default = "default_batch_timeout")]
 	pub batch_timeout: u64,
-	#[serde(default = "default_transaction_send_retries")]
-	pub transaction_send_retries: u32,
+	/// Retries of the transactions whose sending fails
+	#[serde(default)]
+	pub retry: RetryPolicy,
+	/// Former key of `retry.max_attempts`, replacing it when set
+	#[serde(default = "default_transaction_send_retries", skip_serializing_if = "Option::is_none")]
+	pub transaction_send_retries: Option<u32>,
+	/// Increase of the fees per gas of a transaction replacing an underpriced or stuck one, in
+	/// percent
+	#[serde(default = "default_underpriced_gas_bump")]
+	pub underpriced_gas_bump: u64,
+	/// Number of blocks a transaction must be included for before it is considered final
+	#[serde(default = "default_confirmations")]
+	pub confirmations: u64,
+	/// Time a transaction can wait in the mempool before it is considered stuck, in milliseconds
+	#[serde(default = "default_stuck_transaction_timeout")]
+	pub stuck_transaction_timeout: u64,
+	/// Time a transaction, or the transactions replacing it, can take to be mined before sending
+	/// it fails, in milliseconds
+	#[serde(default = "default_receipt_timeout")]
+	pub receipt_timeout: u64,
 }
 
 env_short_default!(default_gas_limit, u64, 10_000_000_000_000_000 as u64);
 
+env_short_default!(default_max_fee_per_gas, u64, 500_000_000_000 as u64);
+
+env_short_default!(default_max_priority_fee_per_gas, u64, 10_000_000_000 as u64);
+
+env_short_default!(default_gas_estimate_margin, u64, 20 as u64);
+
+env_short_default!(default_max_batch_gas, u64, 10_000_000 as u64);
+
+env_short_default!(default_max_queued_commitments, u64, 1024 as u64);
+
 env_short_default!(default_batch_timeout, u64, 2000 as u64);
 
-env_short_default!(default_transaction_send_retries, u32, 10 as u32);
+env_default!(default_transaction_send_retries, "DEFAULT_TRANSACTION_SEND_RETRIES", u32);
+
+env_short_default!(default_underpriced_gas_bump, u64, 10 as u64);
+
+env_short_default!(default_confirmations, u64, 1 as u64);
+
+env_short_default!(default_stuck_transaction_timeout, u64, 120_000 as u64);
+
+env_short_default!(default_receipt_timeout, u64, 600_000 as u64);
 
 impl Default for Config {
 	fn default() -> Self {
 		Config {
 			gas_limit: default_gas_limit(),
+			max_fee_per_gas: default_max_fee_per_gas(),
+			max_priority_fee_per_gas: default_max_priority_fee_per_gas(),
+			gas_price_ceiling: None,
+			hourly_spend_budget: None,
+			daily_spend_budget: None,
+			gas_estimate_margin: default_gas_estimate_margin(),
+			max_batch_gas: default_max_batch_gas(),
+			commitment_queue_path: None,
+			max_queued_commitments: default_max_queued_commitments(),
 			batch_timeout: default_batch_timeout(),
+			retry: RetryPolicy::default(),
 			transaction_send_retries: default_transaction_send_retries(),
+			underpriced_gas_bump: default_underpriced_gas_bump(),
+			confirmations: default_confirmations(),
+			stuck_transaction_timeout: default_stuck_transaction_timeout(),
+			receipt_timeout: default_receipt_timeout(),
+		}
+	}
+}
+
+impl Config {
+	/// Retry policy of the transactions, with the maximum number of attempts of the former
+	/// `transaction_send_retries` key if it is set.
+	pub fn retry_policy(&self) -> RetryPolicy {
+		match self.transaction_send_retries {
+			Some(max_attempts) => RetryPolicy { max_attempts, ..self.retry.clone() },
+			None => self.retry.clone(),
 		}
 	}
 }
@@ -35,7 +35,8 @@ impl Deploy {
 		// enforce config.deploy = deploy
 		config.deploy = Some(deploy.clone());
 
-		let wallet: PrivateKeySigner = deploy.mcr_deployment_account_private_key.parse()?;
+		let wallet: PrivateKeySigner =
+			deploy.mcr_deployment_account_private_key.expose().parse()?;
 
 		// todo: make sure this shows up in the docker container as well
 		let mut solidity_path = std::env::current_dir()?;
@@ -66,7 +67,7 @@ impl Deploy {
 				"--rpc-url",
 				&config.eth_rpc_connection_url(),
 				"--private-key",
-				&deploy.mcr_deployment_account_private_key,
+				deploy.mcr_deployment_account_private_key.expose(),
 				"--legacy",
 				"--use",
 				&solc_path,
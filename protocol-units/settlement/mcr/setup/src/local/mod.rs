@@ -101,13 +101,16 @@ impl Local {
 				.get(0)
 				.ok_or(anyhow!("Failed to get Anvil address"))?
 				.private_key
-				.clone();
+				.clone()
+				.into();
 		}
 		if let Some(testing) = &mut config.testing {
 			// Remove the old one if the config was existing.
 			testing.well_known_account_private_keys.clear();
 			for anvil_address in &anvil_addresses {
-				testing.well_known_account_private_keys.push(anvil_address.private_key.clone());
+				testing
+					.well_known_account_private_keys
+					.push(anvil_address.private_key.clone().into());
 			}
 		}
 
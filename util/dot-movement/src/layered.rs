@@ -0,0 +1,228 @@
+//! Layered loading of the configurations: the defaults of the configuration, overridden by the
+//! config file, overridden by the environment.
+//!
+//! Every leaf of the configuration can be overridden by an environment variable named after its
+//! path, upper cased and joined by underscores under a prefix: with the `MOVEMENT` prefix,
+//! `{"eth": {"rpc_connection_url": ...}}` is overridden by `MOVEMENT_ETH_RPC_CONNECTION_URL`. The
+//! values of the file and of the environment are checked against the type of the defaults, so a
+//! mistyped value is reported with the key it is set for.
+
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+	#[error("Failed to read the config file {path}: {source}")]
+	Read { path: String, source: std::io::Error },
+	#[error("Failed to parse the config file {path}: {source}")]
+	Parse { path: String, source: serde_json::Error },
+	#[error("Invalid value for {key} in the config file: expected {expected}, got {value}")]
+	InvalidFileValue { key: String, expected: &'static str, value: Value },
+	#[error("Invalid value for {key} in {var}: expected {expected}, got {value:?}")]
+	InvalidEnvValue { key: String, var: String, expected: &'static str, value: String },
+	#[error("Failed to serialize the default config: {0}")]
+	Defaults(serde_json::Error),
+	#[error("Invalid config: {0}")]
+	Invalid(serde_json::Error),
+}
+
+/// The name of the kind of a JSON value, as reported in the errors.
+fn kind(value: &Value) -> &'static str {
+	match value {
+		Value::Null => "null",
+		Value::Bool(_) => "a boolean",
+		Value::Number(_) => "a number",
+		Value::String(_) => "a string",
+		Value::Array(_) => "an array",
+		Value::Object(_) => "an object",
+	}
+}
+
+/// Whether a value may replace the default value: one of the same kind, or any value when the
+/// default is unset.
+fn is_compatible(default: &Value, value: &Value) -> bool {
+	default.is_null()
+		|| value.is_null()
+		|| std::mem::discriminant(default) == std::mem::discriminant(value)
+}
+
+fn join_key(path: &[String]) -> String {
+	path.join(".")
+}
+
+/// Merges the values of `layer` into `base`, checking them against the kind of the values they
+/// replace.
+pub fn merge(base: &mut Value, layer: Value, path: &mut Vec<String>) -> Result<(), ConfigError> {
+	match (base, layer) {
+		(Value::Object(base), Value::Object(layer)) => {
+			for (key, value) in layer {
+				path.push(key.clone());
+				match base.get_mut(&key) {
+					Some(base_value) => merge(base_value, value, path)?,
+					None => {
+						base.insert(key, value);
+					}
+				}
+				path.pop();
+			}
+			Ok(())
+		}
+		(base, layer) => {
+			if !is_compatible(base, &layer) {
+				return Err(ConfigError::InvalidFileValue {
+					key: join_key(path),
+					expected: kind(base),
+					value: layer,
+				});
+			}
+			*base = layer;
+			Ok(())
+		}
+	}
+}
+
+/// Parses the value of an environment variable as the kind of the value it overrides.
+fn parse_env_value(current: &Value, raw: &str, key: &str, var: &str) -> Result<Value, ConfigError> {
+	let invalid = |expected| ConfigError::InvalidEnvValue {
+		key: key.to_string(),
+		var: var.to_string(),
+		expected,
+		value: raw.to_string(),
+	};
+	match current {
+		Value::String(_) => Ok(Value::String(raw.to_string())),
+		Value::Bool(_) => {
+			raw.trim().parse::<bool>().map(Value::Bool).map_err(|_| invalid("a boolean"))
+		}
+		Value::Number(_) => match serde_json::from_str(raw.trim()) {
+			Ok(Value::Number(number)) => Ok(Value::Number(number)),
+			_ => Err(invalid("a number")),
+		},
+		Value::Array(_) => match serde_json::from_str(raw) {
+			Ok(Value::Array(array)) => Ok(Value::Array(array)),
+			_ => Err(invalid("a JSON array")),
+		},
+		Value::Object(_) => match serde_json::from_str(raw) {
+			Ok(Value::Object(object)) => Ok(Value::Object(object)),
+			_ => Err(invalid("a JSON object")),
+		},
+		// an unset value takes the JSON value of the variable, or its text
+		Value::Null => {
+			Ok(serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string())))
+		}
+	}
+}
+
+/// Overrides the leaves of the configuration with the variables named after their path.
+pub fn apply_env_overrides(
+	config: &mut Value,
+	prefix: &str,
+	lookup: &impl Fn(&str) -> Option<String>,
+	path: &mut Vec<String>,
+) -> Result<(), ConfigError> {
+	match config {
+		Value::Object(object) if !object.is_empty() => {
+			for (key, value) in object.iter_mut() {
+				path.push(key.clone());
+				apply_env_overrides(value, prefix, lookup, path)?;
+				path.pop();
+			}
+			Ok(())
+		}
+		leaf => {
+			let var = env_var_name(prefix, path);
+			if let Some(raw) = lookup(&var) {
+				*leaf = parse_env_value(leaf, &raw, &join_key(path), &var)?;
+			}
+			Ok(())
+		}
+	}
+}
+
+/// The name of the environment variable overriding the value at `path`.
+pub fn env_var_name(prefix: &str, path: &[String]) -> String {
+	std::iter::once(prefix.to_string())
+		.chain(path.iter().map(|segment| segment.to_uppercase()))
+		.collect::<Vec<_>>()
+		.join("_")
+}
+
+/// Layers the defaults, the file and the environment, looked up with `lookup`.
+pub fn layer(
+	defaults: Value,
+	file: Option<Value>,
+	prefix: &str,
+	lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Value, ConfigError> {
+	let mut config = defaults;
+	if let Some(file) = file {
+		merge(&mut config, file, &mut Vec::new())?;
+	}
+	apply_env_overrides(&mut config, prefix, &lookup, &mut Vec::new())?;
+	Ok(config)
+}
+
+/// Gets the value at `key` in the config file, nested objects being traversed in order.
+pub fn select(file: Value, key: &[&str]) -> Option<Value> {
+	key.iter().try_fold(file, |value, segment| match value {
+		Value::Object(mut object) => object.remove(*segment),
+		_ => None,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+	use std::collections::HashMap;
+
+	fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+		let vars: HashMap<String, String> =
+			vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+		move |name| vars.get(name).cloned()
+	}
+
+	#[test]
+	fn test_env_overrides_file_overrides_defaults() -> Result<(), ConfigError> {
+		let defaults = json!({
+			"eth": { "rpc_url": "http://localhost:8545", "chain_id": 0, "ws_url": null },
+			"auto_refund": true,
+		});
+		let file = json!({ "eth": { "rpc_url": "http://eth:8545", "chain_id": 1 } });
+		let config = layer(
+			defaults,
+			Some(file),
+			"MOVEMENT",
+			env(&[("MOVEMENT_ETH_CHAIN_ID", "31337"), ("MOVEMENT_AUTO_REFUND", "false")]),
+		)?;
+		assert_eq!(
+			config,
+			json!({
+				"eth": { "rpc_url": "http://eth:8545", "chain_id": 31337, "ws_url": null },
+				"auto_refund": false,
+			})
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_mistyped_values_name_their_key() {
+		let defaults = json!({ "eth": { "chain_id": 0 } });
+
+		let error =
+			layer(defaults.clone(), None, "MOVEMENT", env(&[("MOVEMENT_ETH_CHAIN_ID", "one")]))
+				.unwrap_err();
+		assert!(matches!(
+			error,
+			ConfigError::InvalidEnvValue { ref key, ref var, .. }
+				if key == "eth.chain_id" && var == "MOVEMENT_ETH_CHAIN_ID"
+		));
+
+		let error =
+			layer(defaults, Some(json!({ "eth": { "chain_id": "one" } })), "MOVEMENT", env(&[]))
+				.unwrap_err();
+		assert!(
+			matches!(error, ConfigError::InvalidFileValue { ref key, .. } if key == "eth.chain_id")
+		);
+	}
+}
@@ -1,4 +1,10 @@
+pub mod layered;
 pub mod path;
+pub mod watch;
+
+use std::io::Write;
+
+pub use layered::ConfigError;
 
 #[derive(Debug, Clone)]
 pub struct DotMovement(std::path::PathBuf);
@@ -62,18 +68,84 @@ impl DotMovement {
 	}
 
 	/// Tries to write a configuration to a JSON file.
+	///
+	/// The configuration is written to a temporary file next to the config file, then renamed over
+	/// it, so the readers of the config file never see a partially written configuration.
 	pub fn try_write_config_to_json<T: serde::Serialize>(
 		&self,
 		config: &T,
 	) -> Result<(), anyhow::Error> {
-		let file = std::fs::File::create(self.get_config_json_path())
+		let config_path = self.get_config_json_path();
+		let parent = config_path
+			.parent()
+			.ok_or(anyhow::anyhow!("Failed to get parent directory of config path"))?;
+		let mut file = tempfile::NamedTempFile::new_in(parent)
 			.map_err(|e| anyhow::anyhow!("Failed to create file: {}", e))?;
-		let writer = std::io::BufWriter::new(file);
-		serde_json::to_writer_pretty(writer, config)
-			.map_err(|e| anyhow::anyhow!("Failed to write config: {}", e))?;
+		{
+			let mut writer = std::io::BufWriter::new(file.as_file_mut());
+			serde_json::to_writer_pretty(&mut writer, config)
+				.map_err(|e| anyhow::anyhow!("Failed to write config: {}", e))?;
+			writer.flush()?;
+		}
+		file.as_file().sync_all()?;
+		file.persist(&config_path)
+			.map_err(|e| anyhow::anyhow!("Failed to replace config file: {}", e))?;
 		Ok(())
 	}
 
+	/// Loads the configuration under `key` in the config file, layering the defaults of the
+	/// configuration, the config file if any and the environment variables prefixed with
+	/// `env_prefix`.
+	///
+	/// See [layered] for how the environment variables are named.
+	pub fn try_load_layered_config<T>(
+		&self,
+		key: &[&str],
+		env_prefix: &str,
+	) -> Result<T, ConfigError>
+	where
+		T: Default + serde::Serialize + serde::de::DeserializeOwned,
+	{
+		self.layer_config(self.try_read_config_contents()?, key, env_prefix)
+	}
+
+	/// Reads the contents of the config file, `None` if there is no config file.
+	fn try_read_config_contents(&self) -> Result<Option<String>, ConfigError> {
+		let path = self.get_config_json_path();
+		match std::fs::read_to_string(&path) {
+			Ok(contents) => Ok(Some(contents)),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+			Err(source) => Err(ConfigError::Read { path: path.display().to_string(), source }),
+		}
+	}
+
+	/// Layers the defaults, the contents of the config file and the environment.
+	fn layer_config<T>(
+		&self,
+		contents: Option<String>,
+		key: &[&str],
+		env_prefix: &str,
+	) -> Result<T, ConfigError>
+	where
+		T: Default + serde::Serialize + serde::de::DeserializeOwned,
+	{
+		let defaults = serde_json::to_value(T::default()).map_err(ConfigError::Defaults)?;
+		let file = match contents {
+			Some(contents) => {
+				let file: serde_json::Value =
+					serde_json::from_str(&contents).map_err(|source| ConfigError::Parse {
+						path: self.get_config_json_path().display().to_string(),
+						source,
+					})?;
+				layered::select(file, key)
+			}
+			None => None,
+		};
+
+		let config = layered::layer(defaults, file, env_prefix, |var| std::env::var(var).ok())?;
+		serde_json::from_value(config).map_err(ConfigError::Invalid)
+	}
+
 	pub fn try_from_env() -> Result<Self, anyhow::Error> {
 		let path = std::env::var(Self::DEFAULT_DOT_MOVEMENT_PATH_VAR_NAME)
 			.map_err(|_| anyhow::anyhow!("Dot movement path not provided"))?;
@@ -104,4 +176,25 @@ pub mod test {
 		assert_eq!(path.get_path(), std::path::Path::new("/tmp"));
 		Ok(())
 	}
+
+	#[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+	struct EthConfig {
+		rpc_url: String,
+		chain_id: u64,
+	}
+
+	#[test]
+	fn test_try_load_layered_config() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let dot_movement = DotMovement::new(dir.path().to_str().unwrap());
+		dot_movement.try_write_config_to_json(&serde_json::json!({
+			"relayer": { "eth": { "rpc_url": "http://eth:8545", "chain_id": 1 } }
+		}))?;
+		std::env::set_var("DOT_MOVEMENT_TEST_ETH_CHAIN_ID", "31337");
+
+		let config: EthConfig =
+			dot_movement.try_load_layered_config(&["relayer", "eth"], "DOT_MOVEMENT_TEST_ETH")?;
+		assert_eq!(config, EthConfig { rpc_url: "http://eth:8545".to_string(), chain_id: 31337 });
+		Ok(())
+	}
 }
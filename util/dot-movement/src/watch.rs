@@ -0,0 +1,101 @@
+//! Watching the config file, for the long-running services to reload their settings.
+
+use std::pin::Pin;
+use std::time::Duration;
+
+use tokio_stream::Stream;
+
+use crate::{ConfigError, DotMovement};
+
+/// How often the config file is checked for changes by default.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub type ConfigStream<T> = Pin<Box<dyn Stream<Item = Result<T, ConfigError>> + Send>>;
+
+impl DotMovement {
+	/// Streams the configuration under `key`, layered as with
+	/// [DotMovement::try_load_layered_config], each time the contents of the config file change.
+	///
+	/// The config file is polled every `poll_interval`, which also catches the files replaced by
+	/// a rename, as written by [DotMovement::try_write_config_to_json]. The configuration loaded
+	/// when the stream starts is not streamed. An invalid config file is streamed as an error and
+	/// the stream goes on, so a service can keep its current settings until the file is fixed.
+	pub fn watch_config<T>(
+		&self,
+		key: &[&str],
+		env_prefix: &str,
+		poll_interval: Duration,
+	) -> ConfigStream<T>
+	where
+		T: Default + serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+	{
+		let dot_movement = self.clone();
+		let key: Vec<String> = key.iter().map(|segment| segment.to_string()).collect();
+		let env_prefix = env_prefix.to_string();
+		// the contents are read when watching starts, rather than when the stream is first polled
+		let mut last_contents = dot_movement.try_read_config_contents().ok().flatten();
+
+		let stream = async_stream::stream! {
+			let key: Vec<&str> = key.iter().map(String::as_str).collect();
+			let mut interval = tokio::time::interval(poll_interval);
+			interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+			// the first tick completes immediately
+			interval.tick().await;
+			loop {
+				interval.tick().await;
+				let contents = match dot_movement.try_read_config_contents() {
+					Ok(contents) => contents,
+					Err(e) => {
+						yield Err(e);
+						continue;
+					}
+				};
+				if contents == last_contents {
+					continue;
+				}
+				last_contents = contents.clone();
+				yield dot_movement.layer_config(contents, &key, &env_prefix);
+			}
+		};
+
+		Box::pin(stream)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tokio_stream::StreamExt;
+
+	#[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+	struct LogConfig {
+		level: String,
+	}
+
+	#[tokio::test]
+	async fn test_watch_config_streams_changes() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let dot_movement = DotMovement::new(dir.path().to_str().unwrap());
+		dot_movement
+			.try_write_config_to_json(&serde_json::json!({ "log": { "level": "info" } }))?;
+
+		let mut configs = dot_movement.watch_config::<LogConfig>(
+			&["log"],
+			"DOT_MOVEMENT_TEST_WATCH",
+			Duration::from_millis(10),
+		);
+		dot_movement
+			.try_write_config_to_json(&serde_json::json!({ "log": { "level": "debug" } }))?;
+		let config = tokio::time::timeout(Duration::from_secs(5), configs.next())
+			.await?
+			.expect("config stream has ended")?;
+		assert_eq!(config, LogConfig { level: "debug".to_string() });
+
+		std::fs::write(dot_movement.get_config_json_path(), "{ not json")?;
+		let error = tokio::time::timeout(Duration::from_secs(5), configs.next())
+			.await?
+			.expect("config stream has ended");
+		assert!(matches!(error, Err(ConfigError::Parse { .. })));
+		Ok(())
+	}
+}
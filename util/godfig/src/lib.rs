@@ -1,6 +1,8 @@
 pub mod backend;
 pub mod godfig;
+pub mod secret;
 pub use godfig::*;
+pub use secret::Secret;
 
 #[macro_export]
 macro_rules! env_default {
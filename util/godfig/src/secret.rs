@@ -0,0 +1,90 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// A secret of a config, such as a private key or an auth token.
+///
+/// The secret is redacted when the config is printed with `Debug` or `Display`, so that logging
+/// a config does not leak it. It is read with [Secret::expose]. The secret is serialized as is,
+/// as the configs are written back to the config file.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T = String>(T);
+
+impl<T> Secret<T> {
+	pub fn new(secret: T) -> Self {
+		Self(secret)
+	}
+
+	/// Reads the secret.
+	pub fn expose(&self) -> &T {
+		&self.0
+	}
+
+	pub fn into_exposed(self) -> T {
+		self.0
+	}
+}
+
+impl Secret<String> {
+	/// Whether the secret is unset, without exposing it.
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+}
+
+impl<T> fmt::Debug for Secret<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Secret({})", REDACTED)
+	}
+}
+
+impl<T> fmt::Display for Secret<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(REDACTED)
+	}
+}
+
+impl<T> From<T> for Secret<T> {
+	fn from(secret: T) -> Self {
+		Self(secret)
+	}
+}
+
+impl From<&str> for Secret<String> {
+	fn from(secret: &str) -> Self {
+		Self(secret.to_string())
+	}
+}
+
+impl<T: FromStr> FromStr for Secret<T> {
+	type Err = T::Err;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		s.parse().map(Self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, Serialize, Deserialize)]
+	struct Config {
+		signer_private_key: Secret,
+	}
+
+	#[test]
+	fn test_secret_is_redacted_but_serialized() -> Result<(), anyhow::Error> {
+		let config: Config = serde_json::from_str(r#"{"signer_private_key":"0xdeadbeef"}"#)?;
+
+		assert_eq!(config.signer_private_key.expose(), "0xdeadbeef");
+		assert_eq!(format!("{:?}", config), "Config { signer_private_key: Secret([REDACTED]) }");
+		assert_eq!(config.signer_private_key.to_string(), "[REDACTED]");
+		assert_eq!(serde_json::to_string(&config)?, r#"{"signer_private_key":"0xdeadbeef"}"#);
+		Ok(())
+	}
+}
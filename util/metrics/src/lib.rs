@@ -0,0 +1,66 @@
+use std::net::SocketAddr;
+
+use poem::{
+	get, handler, http::StatusCode, listener::TcpListener, web::Data, Endpoint, EndpointExt,
+	IntoResponse, Response, Route, Server,
+};
+use prometheus::{Encoder, Registry, TextEncoder};
+
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Renders the metrics of the registry in the Prometheus text exposition format.
+pub fn encode(registry: &Registry) -> Result<String, prometheus::Error> {
+	let mut buffer = Vec::new();
+	TextEncoder::new().encode(&registry.gather(), &mut buffer)?;
+	String::from_utf8(buffer).map_err(|error| prometheus::Error::Msg(error.to_string()))
+}
+
+/// The `GET /metrics` route rendering the metrics of the registry.
+pub fn routes(registry: Registry) -> impl Endpoint {
+	Route::new().at(METRICS_PATH, get(metrics)).data(registry)
+}
+
+/// Serves the metrics of the registry on `GET /metrics`, for Prometheus to scrape.
+pub async fn serve(registry: Registry, address: SocketAddr) -> Result<(), anyhow::Error> {
+	tracing::info!("Metrics: serving on http://{address}{METRICS_PATH}");
+	Server::new(TcpListener::bind(address)).run(routes(registry)).await?;
+	Ok(())
+}
+
+#[handler]
+fn metrics(registry: Data<&Registry>) -> Response {
+	match encode(&registry) {
+		Ok(body) => body.with_content_type(TextEncoder::new().format_type()).into_response(),
+		Err(error) => {
+			error.to_string().with_status(StatusCode::INTERNAL_SERVER_ERROR).into_response()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use poem::test::TestClient;
+	use prometheus::{IntCounter, Opts};
+
+	#[tokio::test]
+	async fn test_serve_metrics() -> Result<(), anyhow::Error> {
+		let registry = Registry::new();
+		let counter = IntCounter::with_opts(Opts::new("requests_total", "Requests"))?;
+		registry.register(Box::new(counter.clone()))?;
+		counter.inc_by(3);
+		let client = TestClient::new(routes(registry));
+
+		let response = client.get(METRICS_PATH).send().await;
+		response.assert_status_is_ok();
+		response.assert_content_type("text/plain; version=0.0.4");
+		response
+			.assert_text(
+				"# HELP requests_total Requests\n# TYPE requests_total counter\nrequests_total 3\n",
+			)
+			.await;
+
+		client.get("/").send().await.assert_status(StatusCode::NOT_FOUND);
+		Ok(())
+	}
+}
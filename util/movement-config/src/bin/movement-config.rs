@@ -0,0 +1,28 @@
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: movement-config validate [--json]";
+
+/// Validates the config file of dot movement, printing the report. Exits with a failure when the
+/// config has errors.
+fn main() -> Result<ExitCode, anyhow::Error> {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	let json = args.iter().any(|arg| arg == "--json");
+	match args.first().map(String::as_str) {
+		Some("validate") => {}
+		_ => {
+			eprintln!("{}", USAGE);
+			return Ok(ExitCode::from(2));
+		}
+	}
+
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let report = movement_config::validate(&dot_movement)?;
+	if json {
+		println!("{}", serde_json::to_string_pretty(&report)?);
+	} else {
+		println!("Config: {}", dot_movement.get_config_json_path().display());
+		println!("{}", report);
+	}
+
+	Ok(if report.has_errors() { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
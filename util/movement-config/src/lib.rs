@@ -0,0 +1,8 @@
+//! Validation of the config tree of the Movement services, as read from the config file of dot
+//! movement: the config of the suzuka full node with its DA light node and settlement, the MCR
+//! settlement runner and the bridge relayer.
+pub mod report;
+pub mod validate;
+
+pub use report::{Finding, Report, Severity};
+pub use validate::{validate, validate_config_tree, ConfigTree};
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::SocketAddr;
+
+use bridge_shared::types::EthAddress;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+	Warning,
+	Error,
+}
+
+impl fmt::Display for Severity {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Severity::Warning => write!(f, "warning"),
+			Severity::Error => write!(f, "error"),
+		}
+	}
+}
+
+/// A problem found in the config, at `key` of the config `section`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+	pub severity: Severity,
+	pub section: String,
+	pub key: String,
+	pub message: String,
+}
+
+/// The result of the validation of the config tree.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+	/// The sections of the config tree which were validated.
+	pub sections: Vec<String>,
+	pub findings: Vec<Finding>,
+}
+
+impl Report {
+	pub fn error(&mut self, section: &str, key: &str, message: impl Into<String>) {
+		self.push(Severity::Error, section, key, message);
+	}
+
+	pub fn warning(&mut self, section: &str, key: &str, message: impl Into<String>) {
+		self.push(Severity::Warning, section, key, message);
+	}
+
+	fn push(&mut self, severity: Severity, section: &str, key: &str, message: impl Into<String>) {
+		self.findings.push(Finding {
+			severity,
+			section: section.to_string(),
+			key: key.to_string(),
+			message: message.into(),
+		});
+	}
+
+	pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+		self.findings.iter().filter(|finding| finding.severity == Severity::Error)
+	}
+
+	pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+		self.findings.iter().filter(|finding| finding.severity == Severity::Warning)
+	}
+
+	pub fn has_errors(&self) -> bool {
+		self.errors().next().is_some()
+	}
+
+	/// Logs the findings, for the services validating their config on startup.
+	pub fn log(&self) {
+		for finding in &self.findings {
+			match finding.severity {
+				Severity::Error => tracing::error!(
+					"Config: {}.{}: {}",
+					finding.section,
+					finding.key,
+					finding.message
+				),
+				Severity::Warning => tracing::warn!(
+					"Config: {}.{}: {}",
+					finding.section,
+					finding.key,
+					finding.message
+				),
+			}
+		}
+	}
+
+	/// Checks that the value is a URL with one of the `schemes`, any scheme if there are none.
+	pub fn check_url(&mut self, section: &str, key: &str, value: &str, schemes: &[&str]) {
+		match url::Url::parse(value) {
+			Ok(url) if schemes.is_empty() || schemes.contains(&url.scheme()) => {}
+			Ok(url) => self.error(
+				section,
+				key,
+				format!(
+					"Unexpected scheme `{}` in {}, expected one of {}",
+					url.scheme(),
+					value,
+					schemes.join(", ")
+				),
+			),
+			Err(e) => self.error(section, key, format!("Invalid URL {:?}: {}", value, e)),
+		}
+	}
+
+	/// Checks a URL like [`check_url`](Self::check_url), without quoting it in the findings, as
+	/// it may embed credentials.
+	pub fn check_secret_url(&mut self, section: &str, key: &str, value: &str, schemes: &[&str]) {
+		match url::Url::parse(value) {
+			Ok(url) if schemes.is_empty() || schemes.contains(&url.scheme()) => {}
+			Ok(url) => self.error(
+				section,
+				key,
+				format!(
+					"Unexpected scheme `{}`, expected one of {}",
+					url.scheme(),
+					schemes.join(", ")
+				),
+			),
+			Err(e) => self.error(section, key, format!("Invalid URL: {}", e)),
+		}
+	}
+
+	/// Checks that the value is an Ethereum address matching its EIP-55 checksum.
+	pub fn check_eth_address(&mut self, section: &str, key: &str, value: &str) {
+		if let Err(e) = value.parse::<EthAddress>() {
+			self.error(section, key, format!("Invalid Ethereum address {:?}: {}", value, e));
+		}
+	}
+
+	/// Parses a `host:port` listen address.
+	pub fn check_socket_address(
+		&mut self,
+		section: &str,
+		key: &str,
+		value: &str,
+	) -> Option<SocketAddr> {
+		match value.parse() {
+			Ok(address) => Some(address),
+			Err(e) => {
+				self.error(section, key, format!("Invalid listen address {:?}: {}", value, e));
+				None
+			}
+		}
+	}
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "Sections: {}", self.sections.join(", "))?;
+		for finding in &self.findings {
+			writeln!(
+				f,
+				"{:<8}{}.{}: {}",
+				finding.severity, finding.section, finding.key, finding.message
+			)?;
+		}
+		write!(f, "{} error(s), {} warning(s)", self.errors().count(), self.warnings().count())
+	}
+}
+
+/// The addresses the services of the config tree listen on, which must not share a port.
+#[derive(Debug, Default)]
+pub struct ListenAddresses {
+	by_port: BTreeMap<u16, Vec<(String, String, String)>>,
+}
+
+/// Whether the host listens on all the interfaces.
+fn is_wildcard(host: &str) -> bool {
+	matches!(host, "" | "0.0.0.0" | "::" | "[::]")
+}
+
+fn same_host(a: &str, b: &str) -> bool {
+	let loopback = |host: &str| matches!(host, "localhost" | "127.0.0.1" | "::1" | "[::1]");
+	a == b || is_wildcard(a) || is_wildcard(b) || (loopback(a) && loopback(b))
+}
+
+impl ListenAddresses {
+	pub fn add(&mut self, section: &str, key: &str, host: &str, port: u16) {
+		self.by_port.entry(port).or_default().push((
+			section.to_string(),
+			key.to_string(),
+			host.to_string(),
+		));
+	}
+
+	pub fn add_socket_address(&mut self, section: &str, key: &str, address: SocketAddr) {
+		self.add(section, key, &address.ip().to_string(), address.port());
+	}
+
+	/// Reports the services listening on the same port of the same host.
+	pub fn check(&self, report: &mut Report) {
+		for (port, listeners) in &self.by_port {
+			for (i, (section, key, host)) in listeners.iter().enumerate() {
+				if let Some((other_section, other_key, _)) =
+					listeners[..i].iter().find(|(_, _, other_host)| same_host(host, other_host))
+				{
+					report.error(
+						section,
+						key,
+						format!(
+							"Port {} is also listened on by {}.{}",
+							port, other_section, other_key
+						),
+					);
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_listen_addresses_conflict_on_the_same_host() {
+		let mut listen_addresses = ListenAddresses::default();
+		listen_addresses.add("suzuka", "maptos_rest_listen_port", "0.0.0.0", 30731);
+		listen_addresses.add("bridge_relayer", "metrics_listen_address", "127.0.0.1", 30731);
+		listen_addresses.add("suzuka", "fin_rest_listen_port", "10.0.0.1", 30732);
+		listen_addresses.add("bridge_relayer", "status_listen_address", "10.0.0.2", 30732);
+
+		let mut report = Report::default();
+		listen_addresses.check(&mut report);
+		assert_eq!(report.errors().count(), 1);
+		assert_eq!(report.findings[0].key, "metrics_listen_address");
+	}
+
+	#[test]
+	fn test_check_urls_and_addresses() {
+		let mut report = Report::default();
+		report.check_url("bridge_relayer", "eth.rpc_connection_url", "http://eth:8545", &[]);
+		report.check_url("bridge_relayer", "eth.ws_connection_url", "http://eth:8545", &["ws"]);
+		report.check_url(
+			"bridge_relayer",
+			"movement.rest_connection_url",
+			"movement:30731",
+			&["http", "https"],
+		);
+		report.check_eth_address(
+			"mcr_settlement",
+			"settle.mcr_contract_address",
+			"0x5FbDB2315678afecb367f032d93F642f64180aa3",
+		);
+		report.check_eth_address(
+			"mcr_settlement",
+			"settle.mcr_contract_address",
+			"0x5fbDB2315678afecb367f032d93F642f64180aa3",
+		);
+
+		report.check_secret_url(
+			"bridge_relayer",
+			"transfer_index_url",
+			"postgres//bridge:password@db/bridge",
+			&[],
+		);
+
+		let keys: Vec<&str> = report.errors().map(|finding| finding.key.as_str()).collect();
+		assert_eq!(
+			keys,
+			[
+				"eth.ws_connection_url",
+				"movement.rest_connection_url",
+				"settle.mcr_contract_address",
+				"transfer_index_url"
+			]
+		);
+		assert!(report.errors().all(|finding| !finding.message.contains("password")));
+	}
+}
@@ -0,0 +1,346 @@
+use dot_movement::DotMovement;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::report::{ListenAddresses, Report};
+
+/// The section of the config tree read by the suzuka full node, at the root of the config file.
+pub const SUZUKA_SECTION: &str = "suzuka";
+/// The keys of the suzuka config at the root of the config file.
+const SUZUKA_KEYS: [&str; 4] = ["maptos_config", "m1_da_light_node_config", "mcr", "da_db"];
+pub const MCR_SETTLEMENT_SECTION: &str = "mcr_settlement";
+pub const BRIDGE_RELAYER_SECTION: &str = "bridge_relayer";
+
+const HTTP_SCHEMES: [&str; 2] = ["http", "https"];
+const WS_SCHEMES: [&str; 2] = ["ws", "wss"];
+
+/// The configs of the services, as read from the config file of dot movement. The sections
+/// missing from the config file are `None`.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigTree {
+	pub suzuka: Option<suzuka_config::Config>,
+	pub mcr_settlement: Option<mcr_settlement_config::Config>,
+	pub bridge_relayer: Option<bridge_service::Config>,
+}
+
+fn read_section<T: DeserializeOwned>(
+	report: &mut Report,
+	section: &str,
+	value: Value,
+) -> Option<T> {
+	report.sections.push(section.to_string());
+	match serde_json::from_value(value) {
+		Ok(config) => Some(config),
+		Err(e) => {
+			report.error(section, "", format!("Does not match the config schema: {}", e));
+			None
+		}
+	}
+}
+
+impl ConfigTree {
+	/// Reads the sections of the config file, reporting those which do not match their schema.
+	pub fn read(root: Value, report: &mut Report) -> Self {
+		let mut tree = Self::default();
+		let Value::Object(mut root) = root else {
+			report.error("", "", "The config file is not a JSON object");
+			return tree;
+		};
+
+		let mcr_settlement = root.remove(MCR_SETTLEMENT_SECTION);
+		let bridge_relayer = root.remove(BRIDGE_RELAYER_SECTION);
+		if SUZUKA_KEYS.iter().any(|key| root.contains_key(*key)) {
+			tree.suzuka = read_section(report, SUZUKA_SECTION, Value::Object(root));
+		}
+		if let Some(value) = mcr_settlement {
+			tree.mcr_settlement = read_section(report, MCR_SETTLEMENT_SECTION, value);
+		}
+		if let Some(value) = bridge_relayer {
+			tree.bridge_relayer = read_section(report, BRIDGE_RELAYER_SECTION, value);
+		}
+		tree
+	}
+
+	/// Cross-checks the configs: the services must not listen on the same port, the URLs must
+	/// parse and the Ethereum addresses must match their checksum.
+	pub fn validate(&self, report: &mut Report) {
+		let mut listen_addresses = ListenAddresses::default();
+		if let Some(suzuka) = &self.suzuka {
+			validate_suzuka(suzuka, report, &mut listen_addresses);
+		}
+		if let Some(mcr) = &self.mcr_settlement {
+			validate_mcr(mcr, MCR_SETTLEMENT_SECTION, "", report);
+		}
+		if let Some(bridge) = &self.bridge_relayer {
+			validate_bridge(bridge, report, &mut listen_addresses);
+		}
+		listen_addresses.check(report);
+
+		// the settlement of the full node and of the settlement runner must agree
+		if let (Some(suzuka), Some(mcr)) = (&self.suzuka, &self.mcr_settlement) {
+			if suzuka.mcr.settle.mcr_contract_address != mcr.settle.mcr_contract_address {
+				report.warning(
+					MCR_SETTLEMENT_SECTION,
+					"settle.mcr_contract_address",
+					format!(
+						"Differs from the MCR contract of the full node, {}",
+						suzuka.mcr.settle.mcr_contract_address
+					),
+				);
+			}
+		}
+		// the bridge and the settlement connected to the same node must expect the same chain
+		let mcr = self.mcr_settlement.as_ref().or(self.suzuka.as_ref().map(|suzuka| &suzuka.mcr));
+		if let (Some(bridge), Some(mcr)) = (&self.bridge_relayer, mcr) {
+			let bridge_chain_id = bridge.eth.expected_chain_id();
+			let mcr_chain_id = Some(mcr.eth_connection.eth_chain_id).filter(|id| *id != 0);
+			if bridge.eth.rpc_connection_url == mcr.eth_rpc_connection_url()
+				&& bridge_chain_id.is_some()
+				&& mcr_chain_id.is_some()
+				&& bridge_chain_id != mcr_chain_id
+			{
+				report.error(
+					BRIDGE_RELAYER_SECTION,
+					"eth.chain_id",
+					format!(
+						"The Ethereum node {} is also expected on chain {} by the settlement",
+						bridge.eth.rpc_connection_url, mcr.eth_connection.eth_chain_id
+					),
+				);
+			}
+		}
+	}
+}
+
+fn validate_suzuka(
+	config: &suzuka_config::Config,
+	report: &mut Report,
+	listen_addresses: &mut ListenAddresses,
+) {
+	let section = SUZUKA_SECTION;
+	let maptos = &config.execution_config.maptos_config;
+	listen_addresses.add(
+		section,
+		"maptos_config.chain.maptos_rest_listen_port",
+		&maptos.chain.maptos_rest_listen_hostname,
+		maptos.chain.maptos_rest_listen_port,
+	);
+	listen_addresses.add(
+		section,
+		"maptos_config.faucet.maptos_faucet_rest_listen_port",
+		&maptos.faucet.maptos_faucet_rest_listen_hostname,
+		maptos.faucet.maptos_faucet_rest_listen_port,
+	);
+	listen_addresses.add(
+		section,
+		"maptos_config.fin.fin_rest_listen_port",
+		&maptos.fin.fin_rest_listen_hostname,
+		maptos.fin.fin_rest_listen_port,
+	);
+	listen_addresses.add(
+		section,
+		"maptos_config.indexer.maptos_indexer_grpc_listen_port",
+		&maptos.indexer.maptos_indexer_grpc_listen_hostname,
+		maptos.indexer.maptos_indexer_grpc_listen_port,
+	);
+
+	let da = &config.m1_da_light_node.m1_da_light_node_config;
+	listen_addresses.add(
+		section,
+		"m1_da_light_node_config.m1_da_light_node_listen_port",
+		&da.m1_da_light_node_listen_hostname(),
+		da.m1_da_light_node_listen_port(),
+	);
+	if let Some(address) = da.metrics_listen_address() {
+		let key = "m1_da_light_node_config.metrics_listen_address";
+		if let Some(address) = report.check_socket_address(section, key, &address) {
+			listen_addresses.add_socket_address(section, key, address);
+		}
+	}
+	let gas = da.celestia_gas();
+	match (gas.gas_price(), gas.max_gas_price()) {
+		(Ok(Some(gas_price)), Ok(Some(max_gas_price))) if max_gas_price < gas_price => report
+			.error(
+				section,
+				"m1_da_light_node_config.celestia_gas.max_gas_price",
+				format!("Lower than the gas price {}", gas_price),
+			),
+		(Err(e), _) => {
+			report.error(section, "m1_da_light_node_config.celestia_gas.gas_price", e.to_string())
+		}
+		(_, Err(e)) => report.error(
+			section,
+			"m1_da_light_node_config.celestia_gas.max_gas_price",
+			e.to_string(),
+		),
+		_ => {}
+	}
+
+	validate_mcr(&config.mcr, section, "mcr.", report);
+}
+
+fn validate_mcr(
+	config: &mcr_settlement_config::Config,
+	section: &str,
+	prefix: &str,
+	report: &mut Report,
+) {
+	let key = |key: &str| format!("{}{}", prefix, key);
+	report.check_url(
+		section,
+		&key("eth_connection.eth_rpc_connection"),
+		&config.eth_rpc_connection_url(),
+		&HTTP_SCHEMES,
+	);
+	report.check_url(
+		section,
+		&key("eth_connection.eth_ws_connection"),
+		&config.eth_ws_connection_url(),
+		&WS_SCHEMES,
+	);
+	if config.should_settle() {
+		report.check_eth_address(
+			section,
+			&key("settle.mcr_contract_address"),
+			&config.settle.mcr_contract_address,
+		);
+	}
+	if let Some(testing) = &config.testing {
+		report.check_eth_address(
+			section,
+			&key("testing.move_token_contract_address"),
+			&testing.move_token_contract_address,
+		);
+		report.check_eth_address(
+			section,
+			&key("testing.movement_staking_contract_address"),
+			&testing.movement_staking_contract_address,
+		);
+	}
+}
+
+fn validate_bridge(
+	config: &bridge_service::Config,
+	report: &mut Report,
+	listen_addresses: &mut ListenAddresses,
+) {
+	let section = BRIDGE_RELAYER_SECTION;
+	if let Err(e) = config.eth.validate() {
+		report.error(section, "eth", e.to_string());
+	}
+	if let Err(e) = config.movement.validate() {
+		report.error(section, "movement", e.to_string());
+	}
+
+	report.check_url(
+		section,
+		"eth.rpc_connection_url",
+		&config.eth.rpc_connection_url,
+		&HTTP_SCHEMES,
+	);
+	if let Some(url) = &config.eth.ws_connection_url {
+		report.check_url(section, "eth.ws_connection_url", url, &WS_SCHEMES);
+	}
+	if let bridge_service::config::eth::SignerConfig::Remote { url, .. } = &config.eth.signer {
+		report.check_url(section, "eth.signer.url", url, &HTTP_SCHEMES);
+	}
+	report.check_url(
+		section,
+		"movement.rest_connection_url",
+		&config.movement.rest_connection_url,
+		&HTTP_SCHEMES,
+	);
+	if let Some(url) = &config.balance_alert_webhook {
+		report.check_secret_url(section, "balance_alert_webhook", url.expose(), &HTTP_SCHEMES);
+	}
+	if let Some(url) = &config.transfer_index_url {
+		report.check_secret_url(section, "transfer_index_url", url.expose(), &[]);
+	}
+	if let Err(e) = config.validate_intervals() {
+		report.error(section, e.0, e.to_string());
+	}
+	if let Err(e) = config.approvers() {
+		report.error(section, "approver_keys", e.to_string());
+	}
+	if config.approval_threshold.is_some() && config.approver_keys.is_empty() {
+		report.error(section, "approver_keys", "Transfers pending approval can't be approved");
+	}
+	if config.admin_listen_address.is_some() && config.admin_token.is_none() {
+		report.error(section, "admin_token", "The admin API is served without a token");
+	}
+	if !config.eth.fee_collector_address.is_empty() {
+		report.check_eth_address(
+			section,
+			"eth.fee_collector_address",
+			&config.eth.fee_collector_address,
+		);
+	}
+
+	for (key, address) in [
+		("metrics_listen_address", &config.metrics_listen_address),
+		("admin_listen_address", &config.admin_listen_address),
+		("status_listen_address", &config.status_listen_address),
+	] {
+		if let Some(address) = address {
+			if let Some(address) = report.check_socket_address(section, key, address) {
+				listen_addresses.add_socket_address(section, key, address);
+			}
+		}
+	}
+}
+
+/// Validates the config tree, from the JSON of the config file.
+pub fn validate_config_tree(root: Value) -> Report {
+	let mut report = Report::default();
+	ConfigTree::read(root, &mut report).validate(&mut report);
+	report
+}
+
+/// Validates the config file of dot movement.
+pub fn validate(dot_movement: &DotMovement) -> Result<Report, anyhow::Error> {
+	let root: Value = dot_movement.try_get_config_from_json()?;
+	Ok(validate_config_tree(root))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_validate_config_tree() {
+		let report = validate_config_tree(json!({
+			"bridge_relayer": {
+				"refund_check_interval": 0,
+				"metrics_listen_address": "0.0.0.0:30740",
+				"status_listen_address": "127.0.0.1:30740",
+				"eth": {
+					"rpc_connection_url": "localhost:8545",
+					"signer_private_key": "file:/etc/movement/bridge.key",
+					"initiator_contract_address": "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+					"counterparty_contract_address": "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512",
+				},
+				"movement": {
+					"signer_private_key": "file:/etc/movement/bridge-movement.key",
+					"counterparty_module_address": "0x1",
+				},
+			},
+			"mcr_settlement": { "eth_connection": { "eth_chain_id": "one" } },
+		}));
+
+		assert_eq!(report.sections, [MCR_SETTLEMENT_SECTION, BRIDGE_RELAYER_SECTION]);
+		let errors: Vec<(&str, &str)> = report
+			.errors()
+			.map(|finding| (finding.section.as_str(), finding.key.as_str()))
+			.collect();
+		assert_eq!(
+			errors,
+			[
+				(MCR_SETTLEMENT_SECTION, ""),
+				(BRIDGE_RELAYER_SECTION, "eth.rpc_connection_url"),
+				(BRIDGE_RELAYER_SECTION, "refund_check_interval"),
+				(BRIDGE_RELAYER_SECTION, "status_listen_address"),
+			]
+		);
+	}
+}
@@ -0,0 +1,138 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use godfig::env_short_default;
+use serde::{Deserialize, Serialize};
+
+/// Retry behaviour of the requests and transactions failing with a retryable error, backing off
+/// exponentially between the attempts.
+///
+/// The errors are classified by rules, such as `underpriced` or `insufficient_funds` for the
+/// Ethereum transactions, whose retries can be configured apart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+	/// Maximum number of attempts
+	#[serde(default = "default_retry_max_attempts")]
+	pub max_attempts: u32,
+	/// Delay before the first retry, doubled on each following retry, in milliseconds
+	#[serde(default = "default_retry_backoff_base")]
+	pub backoff_base: u64,
+	/// Maximum delay between two attempts, in milliseconds
+	#[serde(default = "default_retry_backoff_max")]
+	pub backoff_max: u64,
+	/// Share of the delay removed at random, in percent, so that clients do not retry in lockstep
+	#[serde(default = "default_retry_jitter")]
+	pub jitter: u64,
+	/// Overrides of the policy for the errors of a rule, keyed by the rule name
+	#[serde(default)]
+	pub rules: BTreeMap<String, RetryRuleOverride>,
+}
+
+/// Settings replacing the ones of the [`RetryPolicy`] for the errors of one rule.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryRuleOverride {
+	#[serde(default)]
+	pub max_attempts: Option<u32>,
+	#[serde(default)]
+	pub backoff_base: Option<u64>,
+}
+
+env_short_default!(default_retry_max_attempts, u32, 10 as u32);
+
+env_short_default!(default_retry_backoff_base, u64, 1000 as u64);
+
+env_short_default!(default_retry_backoff_max, u64, 30_000 as u64);
+
+env_short_default!(default_retry_jitter, u64, 20 as u64);
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		RetryPolicy {
+			max_attempts: default_retry_max_attempts(),
+			backoff_base: default_retry_backoff_base(),
+			backoff_max: default_retry_backoff_max(),
+			jitter: default_retry_jitter(),
+			rules: BTreeMap::new(),
+		}
+	}
+}
+
+impl RetryPolicy {
+	/// A policy trying once, without retries
+	pub fn none() -> Self {
+		RetryPolicy { max_attempts: 1, ..Self::default() }
+	}
+
+	/// Maximum number of retries of the errors of `rule`, bounded by `max_attempts`
+	pub fn max_attempts_for(&self, rule: &str) -> u32 {
+		self.rules
+			.get(rule)
+			.and_then(|rule| rule.max_attempts)
+			.map_or(self.max_attempts, |max_attempts| max_attempts.min(self.max_attempts))
+	}
+
+	/// Delay before the retry number `attempt` (starting at 0) of an error of `rule`, with a
+	/// random share of the jitter removed
+	pub fn jittered_delay(&self, rule: &str, attempt: u32) -> Duration {
+		self.delay(rule, attempt, rand::random())
+	}
+
+	/// Delay before the retry number `attempt` (starting at 0) of an error of `rule`
+	///
+	/// `jitter_sample` is a value in `[0, 1)`, selecting the share of the jitter removed
+	pub fn delay(&self, rule: &str, attempt: u32, jitter_sample: f64) -> Duration {
+		let backoff_base = self
+			.rules
+			.get(rule)
+			.and_then(|rule| rule.backoff_base)
+			.unwrap_or(self.backoff_base);
+		let backoff =
+			backoff_base.saturating_mul(2u64.saturating_pow(attempt)).min(self.backoff_max);
+		let jitter = backoff.saturating_mul(self.jitter.min(100)) / 100;
+		let removed = (jitter as f64 * jitter_sample.clamp(0.0, 1.0)) as u64;
+		Duration::from_millis(backoff - removed.min(jitter))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn policy() -> RetryPolicy {
+		RetryPolicy {
+			max_attempts: 5,
+			backoff_base: 100,
+			backoff_max: 1_000,
+			jitter: 50,
+			rules: BTreeMap::from([(
+				"underpriced".to_string(),
+				RetryRuleOverride { max_attempts: Some(2), backoff_base: Some(10) },
+			)]),
+		}
+	}
+
+	#[test]
+	fn test_exponential_backoff_with_jitter() {
+		let policy = policy();
+		assert_eq!(policy.delay("insufficient_funds", 0, 0.0), Duration::from_millis(100));
+		assert_eq!(policy.delay("insufficient_funds", 2, 0.0), Duration::from_millis(400));
+		assert_eq!(policy.delay("insufficient_funds", 2, 0.5), Duration::from_millis(300));
+		assert_eq!(policy.delay("insufficient_funds", 10, 0.0), Duration::from_millis(1_000));
+		assert_eq!(policy.delay("insufficient_funds", 64, 1.0), Duration::from_millis(500));
+		for _ in 0..100 {
+			let delay = policy.jittered_delay("insufficient_funds", 2);
+			assert!((Duration::from_millis(200)..=Duration::from_millis(400)).contains(&delay));
+		}
+	}
+
+	#[test]
+	fn test_rule_overrides() {
+		let mut policy = policy();
+		assert_eq!(policy.max_attempts_for("underpriced"), 2);
+		assert_eq!(policy.max_attempts_for("insufficient_funds"), 5);
+		assert_eq!(policy.delay("underpriced", 1, 0.0), Duration::from_millis(20));
+
+		policy.rules.get_mut("underpriced").unwrap().max_attempts = Some(50);
+		assert_eq!(policy.max_attempts_for("underpriced"), 5);
+		assert_eq!(RetryPolicy::none().max_attempts_for("underpriced"), 1);
+	}
+}
@@ -1,3 +1,12 @@
+use dot_movement::{ConfigError, DotMovement};
+use opentelemetry::{trace::TraceError, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+	runtime,
+	trace::{self, Sampler, Tracer},
+	Resource,
+};
+use serde::{Deserialize, Serialize};
 use tracing_appender::non_blocking::WorkerGuard as AppenderGuard;
 use tracing_subscriber::filter::{self, EnvFilter, LevelFilter};
 use tracing_subscriber::fmt::format::FmtSpan;
@@ -6,6 +15,10 @@ use tracing_subscriber::prelude::*;
 use std::{env, fs::File, path::PathBuf};
 
 const TIMING_ENV: &str = "MOVEMENT_TIMING";
+/// Section of the config file holding the [`TelemetrySettings`].
+const TELEMETRY_CONFIG_KEY: &str = "telemetry";
+/// Prefix of the environment variables overriding the [`TelemetrySettings`].
+const TELEMETRY_ENV_PREFIX: &str = "MOVEMENT_OTLP";
 
 /// The default path name for the timing log file.
 /// If the path not specified in [`Config`] and the `MOVEMENT_TIMING`
@@ -13,8 +26,18 @@ const TIMING_ENV: &str = "MOVEMENT_TIMING";
 pub const DEFAULT_TIMING_LOG_FILE: &str = "movement-timing.log";
 
 /// A guard for background log appender(s) returned by `init_tracing_subscriber`.
+/// Dropping it also flushes the spans not yet exported to the OpenTelemetry collector.
 pub struct WorkerGuard {
 	_drop_me: Option<AppenderGuard>,
+	telemetry: bool,
+}
+
+impl Drop for WorkerGuard {
+	fn drop(&mut self) {
+		if self.telemetry {
+			opentelemetry::global::shutdown_tracer_provider();
+		}
+	}
 }
 
 /// Options for the tracing subscriber.
@@ -22,6 +45,96 @@ pub struct WorkerGuard {
 pub struct Config {
 	/// Custom name for the timing log file.
 	pub timing_log_path: Option<PathBuf>,
+	/// Export of the spans to an OpenTelemetry collector, disabled if not set.
+	pub telemetry: Option<TelemetryConfig>,
+}
+
+impl Config {
+	/// Options with the telemetry configured for the service in the config file of
+	/// `dot_movement`.
+	pub fn with_telemetry_from_config(
+		dot_movement: &DotMovement,
+		service_name: &str,
+	) -> Result<Self, ConfigError> {
+		let settings: TelemetrySettings =
+			dot_movement.try_load_layered_config(&[TELEMETRY_CONFIG_KEY], TELEMETRY_ENV_PREFIX)?;
+		Ok(Config { telemetry: settings.telemetry(service_name), ..Default::default() })
+	}
+}
+
+/// Options of the export of the spans over OTLP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryConfig {
+	/// gRPC endpoint of the OpenTelemetry collector, such as `http://localhost:4317`.
+	pub endpoint: String,
+	/// Name the spans of the process are exported with.
+	pub service_name: String,
+	/// Share of the traces exported, between 0 and 1.
+	pub sampling_ratio: f64,
+}
+
+/// Settings of the export of the spans over OTLP, in the `telemetry` section of the config file
+/// shared by the services.
+///
+/// Each setting is overridden by the environment variable named after it under the
+/// `MOVEMENT_OTLP` prefix, such as `MOVEMENT_OTLP_ENDPOINT`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetrySettings {
+	/// gRPC endpoint of the OpenTelemetry collector, the spans are not exported if not set.
+	#[serde(default)]
+	pub endpoint: Option<String>,
+	/// Name the spans are exported with, the name of the service if not set.
+	#[serde(default)]
+	pub service_name: Option<String>,
+	/// Share of the traces exported, between 0 and 1.
+	#[serde(default = "default_sampling_ratio")]
+	pub sampling_ratio: f64,
+}
+
+fn default_sampling_ratio() -> f64 {
+	1.0
+}
+
+impl Default for TelemetrySettings {
+	fn default() -> Self {
+		TelemetrySettings {
+			endpoint: None,
+			service_name: None,
+			sampling_ratio: default_sampling_ratio(),
+		}
+	}
+}
+
+impl TelemetrySettings {
+	/// The export of the spans of the service, `None` if no endpoint is set.
+	pub fn telemetry(self, service_name: &str) -> Option<TelemetryConfig> {
+		let endpoint = self.endpoint.filter(|endpoint| !endpoint.is_empty())?;
+		Some(TelemetryConfig {
+			endpoint,
+			service_name: self.service_name.unwrap_or_else(|| service_name.to_string()),
+			sampling_ratio: self.sampling_ratio,
+		})
+	}
+}
+
+/// Installs the batch exporter of the spans to the collector, which runs on the Tokio runtime.
+fn init_telemetry(config: &TelemetryConfig) -> Result<Tracer, TraceError> {
+	if tokio::runtime::Handle::try_current().is_err() {
+		return Err(TraceError::Other("the OTLP exporter requires a Tokio runtime".into()));
+	}
+	let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.sampling_ratio)));
+	let resource = Resource::new(vec![KeyValue::new("service.name", config.service_name.clone())]);
+	opentelemetry_otlp::new_pipeline()
+		.tracing()
+		.with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(&config.endpoint))
+		.with_trace_config(trace::config().with_sampler(sampler).with_resource(resource))
+		.install_batch(runtime::Tokio)
+}
+
+fn env_filter() -> EnvFilter {
+	EnvFilter::builder()
+		.with_default_directive(LevelFilter::INFO.into())
+		.from_env_lossy()
 }
 
 /// Sets up the tracing subscribers for a Movement process. This should be
@@ -33,10 +146,17 @@ pub struct Config {
 /// as this is the only facility
 pub fn init_tracing_subscriber(config: Config) -> WorkerGuard {
 	// TODO: compose console_subscriber as a layer
-	let env_filter = EnvFilter::builder()
-		.with_default_directive(LevelFilter::INFO.into())
-		.from_env_lossy();
-	let log_layer = tracing_subscriber::fmt::layer().with_filter(env_filter);
+	let log_layer = tracing_subscriber::fmt::layer().with_filter(env_filter());
+
+	let (tracer, telemetry_error) = match config.telemetry.as_ref().map(init_telemetry) {
+		Some(Ok(tracer)) => (Some(tracer), None),
+		Some(Err(e)) => (None, Some(e)),
+		None => (None, None),
+	};
+	let telemetry = tracer.is_some();
+	// The spans are filtered like the logs, so that the spans of the exporter itself are not exported.
+	let telemetry_layer = tracer
+		.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer).with_filter(env_filter()));
 
 	let (timing_layer, timing_writer_guard) = match env::var(TIMING_ENV) {
 		Err(env::VarError::NotPresent) => {
@@ -72,7 +192,64 @@ pub fn init_tracing_subscriber(config: Config) -> WorkerGuard {
 		}
 	};
 
-	tracing_subscriber::registry().with(log_layer).with(timing_layer).init();
+	tracing_subscriber::registry()
+		.with(log_layer)
+		.with(timing_layer)
+		.with(telemetry_layer)
+		.init();
+
+	if let (Some(telemetry), Some(e)) = (&config.telemetry, telemetry_error) {
+		tracing::warn!("can't export spans to `{}`: {}", telemetry.endpoint, e);
+	}
+
+	WorkerGuard { _drop_me: timing_writer_guard, telemetry }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_telemetry_from_config() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let dot_movement = DotMovement::new(&dir.path().display().to_string());
+		assert_eq!(Config::with_telemetry_from_config(&dot_movement, "relayer")?.telemetry, None);
+
+		std::fs::write(
+			dot_movement.get_config_json_path(),
+			r#"{"telemetry": {"endpoint": "http://localhost:4317", "sampling_ratio": 0.5}}"#,
+		)?;
+		let config = Config::with_telemetry_from_config(&dot_movement, "relayer")?;
+		assert_eq!(
+			config.telemetry,
+			Some(TelemetryConfig {
+				endpoint: "http://localhost:4317".to_string(),
+				service_name: "relayer".to_string(),
+				sampling_ratio: 0.5,
+			})
+		);
+		Ok(())
+	}
+	#[test]
+	fn test_unset_endpoint_disables_telemetry() -> Result<(), anyhow::Error> {
+		for settings in [r#"{}"#, r#"{"endpoint": "", "service_name": "bridge"}"#] {
+			let settings: TelemetrySettings = serde_json::from_str(settings)?;
+			assert_eq!(settings.telemetry("relayer"), None);
+		}
+
+		// Without an endpoint the subscriber is set up with the logs only, and no exporter.
+		let guard = init_tracing_subscriber(Config::default());
+		assert!(!guard.telemetry);
+		Ok(())
+	}
 
-	WorkerGuard { _drop_me: timing_writer_guard }
+	#[test]
+	fn test_telemetry_requires_a_runtime() {
+		let config = TelemetryConfig {
+			endpoint: "http://localhost:4317".to_string(),
+			service_name: "relayer".to_string(),
+			sampling_ratio: 1.0,
+		};
+		assert!(init_telemetry(&config).is_err());
+	}
 }